@@ -0,0 +1,293 @@
+// Backend-side agent executor (think -> tool call -> observe loop)
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::audit::{self, AuditLog};
+use crate::db::Database;
+use crate::llm::{self, ChatMessage, GenerationOptions, ProviderConfig, ToolCall};
+use crate::persona;
+use crate::profiles::ProfileManager;
+use crate::tools::ToolRegistry;
+
+const DEFAULT_MAX_ITERATIONS: u32 = 8;
+const MAX_OBSERVATION_CHARS: usize = 8000;
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentRequest {
+    provider: ProviderConfig,
+    messages: Vec<ChatMessage>,
+    #[serde(default)]
+    options: GenerationOptions,
+    max_iterations: Option<u32>,
+    // Restrict the agent to a subset of tools (all tools when omitted)
+    tools: Option<Vec<String>>,
+    // Set to false for models without native tool calling; tools are then described in the prompt
+    native_tools: Option<bool>,
+    // Persona to use instead of the active one
+    persona_id: Option<String>,
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentStepEvent {
+    run_id: String,
+    iteration: u32,
+    kind: String,
+    content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    arguments: Option<Value>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentRunResult {
+    run_id: String,
+    answer: String,
+    iterations: u32,
+    stop_reason: String,
+    messages: Vec<ChatMessage>,
+}
+
+struct StepEmitter<'a> {
+    app: &'a AppHandle,
+    run_id: String,
+}
+
+impl StepEmitter<'_> {
+    fn emit(&self, iteration: u32, kind: &str, content: &str, call: Option<&ToolCall>) {
+        let event = AgentStepEvent {
+            run_id: self.run_id.clone(),
+            iteration,
+            kind: kind.to_string(),
+            content: content.to_string(),
+            tool_name: call.map(|c| c.name.clone()),
+            arguments: call.map(|c| c.arguments.clone()),
+        };
+        if let Err(e) = self.app.emit("agent-step", event) {
+            eprintln!("[Agent] Failed to emit step event: {}", e);
+        }
+    }
+}
+
+fn tool_name(definition: &Value) -> &str {
+    definition["function"]["name"].as_str().unwrap_or("")
+}
+
+// Extract a JSON object from the text, stripping code fences and <tool_call> tags
+fn extract_json_object(text: &str) -> Option<Value> {
+    let text = text.trim();
+    let inner = if let Some(rest) = text.split("<tool_call>").nth(1) {
+        rest.split("</tool_call>").next().unwrap_or(rest)
+    } else if let Some(rest) = text.split("```json").nth(1).or_else(|| text.split("```").nth(1)) {
+        rest.split("```").next().unwrap_or(rest)
+    } else {
+        text
+    };
+
+    let start = inner.find('{')?;
+    let end = inner.rfind('}')?;
+    if end <= start {
+        return None;
+    }
+    serde_json::from_str(&inner[start..=end]).ok()
+}
+
+// Parse tool calls written as JSON in the message text (for models without native tool calling)
+fn parse_tool_calls_from_text(text: &str) -> Vec<ToolCall> {
+    let Some(value) = extract_json_object(text) else {
+        return Vec::new();
+    };
+
+    let entries = match value.get("tool_calls").and_then(|v| v.as_array()) {
+        Some(calls) => calls.clone(),
+        None => vec![value],
+    };
+
+    entries
+        .iter()
+        .enumerate()
+        .filter_map(|(i, entry)| {
+            let entry = entry.get("function").unwrap_or(entry);
+            let name = entry
+                .get("name")
+                .or_else(|| entry.get("tool"))
+                .and_then(|v| v.as_str())?
+                .to_string();
+            let arguments = entry
+                .get("arguments")
+                .or_else(|| entry.get("parameters"))
+                .or_else(|| entry.get("args"))
+                .cloned()
+                .unwrap_or_else(|| json!({}));
+            let arguments = match arguments {
+                Value::String(s) => serde_json::from_str(&s).unwrap_or(Value::String(s)),
+                other => other,
+            };
+            Some(ToolCall {
+                id: format!("call_{}", i),
+                name,
+                arguments,
+            })
+        })
+        .collect()
+}
+
+fn tool_prompt(tools: &[Value]) -> String {
+    let mut prompt = String::from(
+        "You can use tools. To call a tool, reply with only a JSON object of the form \
+         {\"name\": \"<tool name>\", \"arguments\": {...}}. \
+         After the tool result is returned, continue reasoning or give the final answer as plain text.\n\nAvailable tools:\n",
+    );
+    for tool in tools {
+        prompt.push_str(&format!(
+            "- {}: {} Parameters: {}\n",
+            tool_name(tool),
+            tool["function"]["description"].as_str().unwrap_or(""),
+            tool["function"]["parameters"]
+        ));
+    }
+    prompt
+}
+
+// Returns the observation and whether it was cut short
+fn truncate_observation(text: String) -> (String, bool) {
+    if text.chars().count() <= MAX_OBSERVATION_CHARS {
+        return (text, false);
+    }
+    let truncated: String = text.chars().take(MAX_OBSERVATION_CHARS).collect();
+    (format!("{}\n[truncated]", truncated), true)
+}
+
+fn step_start() -> (chrono::DateTime<chrono::Utc>, std::time::Instant) {
+    (chrono::Utc::now(), std::time::Instant::now())
+}
+
+#[tauri::command]
+pub async fn run_agent(
+    app: AppHandle,
+    registry: State<'_, ToolRegistry>,
+    db: State<'_, Database>,
+    profiles: State<'_, ProfileManager>,
+    request: AgentRequest,
+) -> Result<AgentRunResult, String> {
+    let mut provider = request.provider;
+    profiles.apply_credentials(&mut provider);
+    let mut messages = request.messages;
+    let mut enabled_tools = request.tools;
+    if let Some(persona) = persona::resolve(&db, request.persona_id.as_deref())? {
+        persona.apply(&mut provider, &mut messages);
+        if enabled_tools.is_none() {
+            enabled_tools = persona.default_tools.clone();
+        }
+    }
+
+    let max_iterations = request.max_iterations.unwrap_or(DEFAULT_MAX_ITERATIONS).max(1);
+    let native_tools = request.native_tools.unwrap_or(true);
+    let emitter = StepEmitter {
+        app: &app,
+        run_id: format!("agent-{}", chrono::Utc::now().timestamp_millis()),
+    };
+
+    let definitions: Vec<_> = registry
+        .definitions()
+        .into_iter()
+        .filter(|d| match &enabled_tools {
+            Some(enabled) => enabled.contains(&d.name),
+            None => true,
+        })
+        .collect();
+    let tools: Vec<Value> = definitions.iter().map(|d| d.to_function()).collect();
+
+    if !native_tools && !tools.is_empty() {
+        messages.insert(0, ChatMessage::new("system", tool_prompt(&tools)));
+    }
+    let native_defs: &[Value] = if native_tools { &tools } else { &[] };
+
+    eprintln!("[Agent] Starting run {} with {} tools, max {} iterations", emitter.run_id, tools.len(), max_iterations);
+    let mut audit_log = AuditLog::start(&app, &emitter.run_id, &provider.model);
+
+    let mut last_content = String::new();
+    for iteration in 1..=max_iterations {
+        let started = step_start();
+        let completion = match llm::chat(&provider, &messages, native_defs, &request.options).await {
+            Ok(c) => c,
+            Err(err) => {
+                audit_log.record(audit::step(iteration, "model", None, started, 0, false, Some(err.clone())));
+                audit_log.finish("error");
+                emitter.emit(iteration, "error", &err, None);
+                return Err(err);
+            }
+        };
+        let mut message = completion.message;
+        audit_log.record(audit::step(iteration, "model", None, started, message.content.chars().count(), false, None));
+
+        // Text is only searched for calls when the model was told to write them there, and only
+        // the tools it was offered count
+        if !native_tools && message.tool_calls.is_empty() && !tools.is_empty() {
+            message.tool_calls = parse_tool_calls_from_text(&message.content)
+                .into_iter()
+                .filter(|call| definitions.iter().any(|d| d.name == call.name))
+                .collect();
+        }
+        last_content = message.content.clone();
+
+        if message.tool_calls.is_empty() {
+            eprintln!("[Agent] Run {} finished after {} iteration(s)", emitter.run_id, iteration);
+            emitter.emit(iteration, "final", &message.content, None);
+            audit_log.finish("completed");
+            messages.push(message);
+            return Ok(AgentRunResult {
+                run_id: emitter.run_id,
+                answer: last_content,
+                iterations: iteration,
+                stop_reason: "completed".to_string(),
+                messages,
+            });
+        }
+
+        if !message.content.trim().is_empty() {
+            emitter.emit(iteration, "thought", &message.content, None);
+        }
+
+        let calls = message.tool_calls.clone();
+        messages.push(message);
+
+        for call in &calls {
+            eprintln!("[Agent] Iteration {}: calling {} with {}", iteration, call.name, call.arguments);
+            emitter.emit(iteration, "tool_call", "", Some(call));
+
+            let started = step_start();
+            let observation = match registry.execute(&call.name, call.arguments.clone()).await {
+                Ok(output) => {
+                    let output_chars = output.chars().count();
+                    let (observation, truncated) = truncate_observation(output);
+                    audit_log.record(audit::step(iteration, "tool", Some(call), started, output_chars, truncated, None));
+                    observation
+                }
+                Err(err) => {
+                    let observation = format!("Error: {}", err);
+                    audit_log.record(audit::step(iteration, "tool", Some(call), started, 0, false, Some(err)));
+                    observation
+                }
+            };
+
+            emitter.emit(iteration, "observation", &observation, Some(call));
+            messages.push(ChatMessage::tool_result(call, observation));
+        }
+    }
+
+    eprintln!("[Agent] Run {} stopped: max iterations ({}) reached", emitter.run_id, max_iterations);
+    emitter.emit(max_iterations, "final", &last_content, None);
+    audit_log.finish("max_iterations");
+
+    Ok(AgentRunResult {
+        run_id: emitter.run_id,
+        answer: last_content,
+        iterations: max_iterations,
+        stop_reason: "max_iterations".to_string(),
+        messages,
+    })
+}
@@ -0,0 +1,182 @@
+// Archiving cited pages as PDF: a tab of the shared browser loads the page and Chrome's
+// printToPDF renders it, with the paper size and margins chosen here. The file is written to the
+// given path and can also be kept as an attachment of a conversation message.
+use std::path::Path;
+use std::time::Duration;
+
+use headless_chrome::types::PrintToPdfOptions;
+use reqwest::Url;
+use tauri::{AppHandle, Manager};
+
+use crate::blobs::{self, BlobStore};
+use crate::conversations::Attachment;
+use crate::db::Database;
+use crate::{browser, settings};
+
+// Time for scripts to fill in the page after it has loaded
+const RENDER_DELAY: Duration = Duration::from_millis(1500);
+
+#[derive(serde::Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PaperSize {
+    #[default]
+    Letter,
+    Legal,
+    Tabloid,
+    A3,
+    A4,
+    A5,
+}
+
+impl PaperSize {
+    // Width and height in inches, portrait
+    fn inches(self) -> (f64, f64) {
+        match self {
+            PaperSize::Letter => (8.5, 11.0),
+            PaperSize::Legal => (8.5, 14.0),
+            PaperSize::Tabloid => (11.0, 17.0),
+            PaperSize::A3 => (11.69, 16.54),
+            PaperSize::A4 => (8.27, 11.69),
+            PaperSize::A5 => (5.83, 8.27),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfOptions {
+    #[serde(default)]
+    paper: PaperSize,
+    #[serde(default)]
+    landscape: bool,
+    // Margins in inches: all four sides, or individually
+    margin: Option<f64>,
+    margin_top: Option<f64>,
+    margin_bottom: Option<f64>,
+    margin_left: Option<f64>,
+    margin_right: Option<f64>,
+    // Page backgrounds and colors; Chrome leaves them out by default, as a browser print would
+    #[serde(default)]
+    print_background: bool,
+    scale: Option<f64>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PagePdf {
+    pub url: String,
+    pub title: Option<String>,
+    pub path: String,
+    pub size: u64,
+    // Set when the PDF was attached to a message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attachment: Option<Attachment>,
+}
+
+fn print_options(options: &PdfOptions) -> Result<PrintToPdfOptions, String> {
+    let margin = options.margin.unwrap_or(0.4);
+    let margins = [options.margin_top, options.margin_bottom, options.margin_left, options.margin_right]
+        .map(|side| side.unwrap_or(margin));
+    if margins.iter().any(|m| !(0.0..=3.0).contains(m)) {
+        return Err("Margins must be between 0 and 3 inches".to_string());
+    }
+    let scale = options.scale.unwrap_or(1.0);
+    if !(0.1..=2.0).contains(&scale) {
+        return Err("Scale must be between 0.1 and 2".to_string());
+    }
+    let (width, height) = options.paper.inches();
+    let [top, bottom, left, right] = margins;
+    if left + right >= width || top + bottom >= height {
+        return Err("Margins leave no room on the page".to_string());
+    }
+    Ok(PrintToPdfOptions {
+        // Chrome swaps width and height itself for landscape
+        landscape: Some(options.landscape),
+        print_background: Some(options.print_background),
+        scale: Some(scale),
+        paper_width: Some(width),
+        paper_height: Some(height),
+        margin_top: Some(top),
+        margin_bottom: Some(bottom),
+        margin_left: Some(left),
+        margin_right: Some(right),
+        ..Default::default()
+    })
+}
+
+// The page rendered to PDF bytes, with its title
+fn render(
+    pool: &browser::BrowserPool,
+    config: &browser::PoolConfig,
+    url: &str,
+    timeout: Duration,
+    options: PrintToPdfOptions,
+) -> Result<(Vec<u8>, Option<String>), String> {
+    let tab = pool.acquire(config, timeout)?;
+    tab.set_default_timeout(timeout);
+    tab.navigate_to(url).map_err(|err| format!("Failed to navigate: {err}"))?;
+    tab.wait_until_navigated().map_err(|err| format!("Navigation timeout: {err}"))?;
+    std::thread::sleep(RENDER_DELAY);
+    let title = tab.get_title().ok().map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    let bytes = tab.print_to_pdf(Some(options)).map_err(|err| format!("Failed to print page: {err}"))?;
+    Ok((bytes, title))
+}
+
+// File name for the attachment: the page title, or the host when there is none
+fn file_name(title: Option<&str>, url: &Url) -> String {
+    let name: String = title
+        .unwrap_or_else(|| url.host_str().unwrap_or("page"))
+        .chars()
+        .map(|c| if c.is_alphanumeric() || " -_.".contains(c) { c } else { '_' })
+        .take(100)
+        .collect();
+    format!("{}.pdf", name.trim())
+}
+
+// Save a web page as a PDF at `path`; with `message_id` the PDF is also attached to that message
+#[tauri::command]
+pub async fn page_to_pdf(
+    app: AppHandle,
+    url: String,
+    path: String,
+    options: Option<PdfOptions>,
+    message_id: Option<String>,
+) -> Result<PagePdf, String> {
+    let parsed = Url::parse(&url).map_err(|err| format!("Invalid URL: {err}"))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("Only http and https schemes are allowed".to_string());
+    }
+    let print = print_options(&options.unwrap_or_default())?;
+    let settings = settings::load(&app);
+    let config = browser::PoolConfig::from_settings(&settings);
+    let timeout = Duration::from_millis(settings.scrape_timeout_ms);
+    let pool = app.state::<browser::BrowserPool>().inner().clone();
+
+    let target = url.clone();
+    let (bytes, title) = tokio::task::spawn_blocking(move || render(&pool, &config, &target, timeout, print))
+        .await
+        .map_err(|e| format!("PDF rendering failed: {}", e))??;
+    let output = Path::new(&path);
+    if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    std::fs::write(output, &bytes).map_err(|e| format!("Failed to write {}: {}", output.display(), e))?;
+    eprintln!("[Archive] Saved {} ({} bytes) to {}", url, bytes.len(), output.display());
+
+    let attachment = match message_id {
+        Some(message_id) => {
+            let blob = app.state::<BlobStore>().put_bytes(&bytes)?;
+            let name = file_name(title.as_deref(), &parsed);
+            let mime_type = Some("application/pdf".to_string());
+            Some(blobs::attach_blob(&app.state::<Database>(), message_id, name, mime_type, blob)?)
+        }
+        None => None,
+    };
+    Ok(PagePdf {
+        url,
+        title,
+        path,
+        size: bytes.len() as u64,
+        attachment,
+    })
+}
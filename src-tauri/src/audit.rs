@@ -0,0 +1,255 @@
+// Persistent audit trail of agent runs, one JSON file per run under <profile data>/agent_runs
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use serde_json::Value;
+use tauri::AppHandle;
+
+use crate::profiles;
+
+const RUNS_DIR: &str = "agent_runs";
+const RETENTION_FILE: &str = "retention.json";
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionPolicy {
+    // Delete run logs older than this many days
+    pub max_age_days: Option<u64>,
+    // Keep at most this many run logs (newest first)
+    pub max_runs: Option<usize>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        RetentionPolicy {
+            max_age_days: Some(30),
+            max_runs: Some(200),
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditStep {
+    pub iteration: u32,
+    // "model" for LLM calls, "tool" for tool executions
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<Value>,
+    pub started_at: String,
+    pub duration_ms: u64,
+    pub output_chars: usize,
+    pub truncated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentRunTrace {
+    pub run_id: String,
+    pub model: String,
+    pub started_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
+    pub steps: Vec<AuditStep>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentRunSummary {
+    run_id: String,
+    model: String,
+    started_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_reason: Option<String>,
+    step_count: usize,
+}
+
+fn runs_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    profiles::data_dir(app).map(|dir| dir.join(RUNS_DIR))
+}
+
+fn run_path(dir: &Path, run_id: &str) -> Result<PathBuf, String> {
+    // Run ids become file names; reject anything that could escape the directory
+    if run_id.is_empty() || !run_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("Invalid run id: {}", run_id));
+    }
+    Ok(dir.join(format!("{}.json", run_id)))
+}
+
+// Writes the trace to disk after every step so a crashed run still leaves a log
+pub struct AuditLog {
+    path: Option<PathBuf>,
+    trace: AgentRunTrace,
+}
+
+impl AuditLog {
+    pub fn start(app: &AppHandle, run_id: &str, model: &str) -> Self {
+        let path = runs_dir(app).and_then(|dir| {
+            std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+            if let Err(err) = apply_retention(&dir, &load_retention(&dir)) {
+                eprintln!("[Audit] Retention cleanup failed: {}", err);
+            }
+            run_path(&dir, run_id)
+        });
+        let path = match path {
+            Ok(p) => Some(p),
+            Err(err) => {
+                eprintln!("[Audit] Run log disabled: {}", err);
+                None
+            }
+        };
+
+        let log = AuditLog {
+            path,
+            trace: AgentRunTrace {
+                run_id: run_id.to_string(),
+                model: model.to_string(),
+                started_at: chrono::Utc::now().to_rfc3339(),
+                finished_at: None,
+                stop_reason: None,
+                steps: Vec::new(),
+            },
+        };
+        log.flush();
+        log
+    }
+
+    pub fn record(&mut self, step: AuditStep) {
+        self.trace.steps.push(step);
+        self.flush();
+    }
+
+    pub fn finish(&mut self, stop_reason: &str) {
+        self.trace.finished_at = Some(chrono::Utc::now().to_rfc3339());
+        self.trace.stop_reason = Some(stop_reason.to_string());
+        self.flush();
+    }
+
+    fn flush(&self) {
+        let Some(path) = &self.path else { return };
+        let result = serde_json::to_string_pretty(&self.trace)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(path, json).map_err(|e| e.to_string()));
+        if let Err(err) = result {
+            eprintln!("[Audit] Failed to write {}: {}", path.display(), err);
+        }
+    }
+}
+
+// Build a step record from a start time and the step's outcome
+pub fn step(
+    iteration: u32,
+    kind: &str,
+    call: Option<&crate::llm::ToolCall>,
+    started: (chrono::DateTime<chrono::Utc>, std::time::Instant),
+    output_chars: usize,
+    truncated: bool,
+    error: Option<String>,
+) -> AuditStep {
+    AuditStep {
+        iteration,
+        kind: kind.to_string(),
+        tool_name: call.map(|c| c.name.clone()),
+        arguments: call.map(|c| c.arguments.clone()),
+        started_at: started.0.to_rfc3339(),
+        duration_ms: started.1.elapsed().as_millis() as u64,
+        output_chars,
+        truncated,
+        error,
+    }
+}
+
+fn load_retention(dir: &Path) -> RetentionPolicy {
+    std::fs::read_to_string(dir.join(RETENTION_FILE))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn run_files(dir: &Path) -> Result<Vec<(PathBuf, SystemTime)>, String> {
+    let mut files: Vec<(PathBuf, SystemTime)> = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension().and_then(|e| e.to_str()) == Some("json")
+                && path.file_name().and_then(|n| n.to_str()) != Some(RETENTION_FILE)
+        })
+        .filter_map(|path| {
+            let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            Some((path, modified))
+        })
+        .collect();
+    // Newest first
+    files.sort_by_key(|f| std::cmp::Reverse(f.1));
+    Ok(files)
+}
+
+fn apply_retention(dir: &Path, policy: &RetentionPolicy) -> Result<usize, String> {
+    let max_age = policy.max_age_days.map(|days| Duration::from_secs(days * 24 * 60 * 60));
+    let now = SystemTime::now();
+    let mut removed = 0;
+
+    for (index, (path, modified)) in run_files(dir)?.into_iter().enumerate() {
+        let too_old = max_age
+            .map(|age| now.duration_since(modified).unwrap_or_default() > age)
+            .unwrap_or(false);
+        let over_limit = policy.max_runs.map(|max| index >= max).unwrap_or(false);
+        if (too_old || over_limit) && std::fs::remove_file(&path).is_ok() {
+            removed += 1;
+        }
+    }
+    if removed > 0 {
+        eprintln!("[Audit] Removed {} old run log(s)", removed);
+    }
+    Ok(removed)
+}
+
+#[tauri::command]
+pub fn get_agent_run(app: AppHandle, run_id: String) -> Result<AgentRunTrace, String> {
+    let path = run_path(&runs_dir(&app)?, &run_id)?;
+    let text = std::fs::read_to_string(&path).map_err(|_| format!("No log found for run {}", run_id))?;
+    serde_json::from_str(&text).map_err(|e| format!("Corrupt run log: {}", e))
+}
+
+#[tauri::command]
+pub fn list_agent_runs(app: AppHandle) -> Result<Vec<AgentRunSummary>, String> {
+    let dir = runs_dir(&app)?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(run_files(&dir)?
+        .into_iter()
+        .filter_map(|(path, _)| {
+            let trace: AgentRunTrace = serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+            Some(AgentRunSummary {
+                step_count: trace.steps.len(),
+                run_id: trace.run_id,
+                model: trace.model,
+                started_at: trace.started_at,
+                stop_reason: trace.stop_reason,
+            })
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub fn get_agent_log_retention(app: AppHandle) -> Result<RetentionPolicy, String> {
+    Ok(load_retention(&runs_dir(&app)?))
+}
+
+// Save the retention policy and prune existing logs right away
+#[tauri::command]
+pub fn set_agent_log_retention(app: AppHandle, policy: RetentionPolicy) -> Result<usize, String> {
+    let dir = runs_dir(&app)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let json = serde_json::to_string_pretty(&policy).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(RETENTION_FILE), json).map_err(|e| format!("Failed to save retention policy: {}", e))?;
+    apply_retention(&dir, &policy)
+}
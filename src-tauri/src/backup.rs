@@ -0,0 +1,282 @@
+// Backup and restore of all app data (database, attachments, config files) as a single ZIP archive
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use tauri::{AppHandle, State};
+use zip::write::SimpleFileOptions;
+
+use crate::blobs::{is_blob_hash, BlobStore};
+use crate::db::{self, Database};
+use crate::profiles;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const BACKUP_FORMAT: &str = "openchat-backup";
+const DB_ENTRY: &str = "openchat.db";
+const BLOBS_PREFIX: &str = "blobs/";
+const CONFIG_PREFIX: &str = "config/";
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+    format: String,
+    schema_version: i64,
+    app_version: String,
+    created_at: String,
+    blob_count: usize,
+    config_files: Vec<String>,
+    // Database and blobs are encrypted with the storage key of the device that made the backup
+    #[serde(default)]
+    encrypted: bool,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreResult {
+    manifest: BackupManifest,
+    restored_blobs: usize,
+    restored_config_files: usize,
+    // Snapshot of the data as it was before the restore
+    safety_backup: String,
+}
+
+fn zip_error(e: impl std::fmt::Display) -> String {
+    format!("Archive error: {}", e)
+}
+
+fn add_file(zip: &mut zip::ZipWriter<std::fs::File>, name: &str, path: &Path) -> Result<(), String> {
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    zip.start_file(name, options).map_err(zip_error)?;
+    let mut file = std::fs::File::open(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    std::io::copy(&mut file, zip).map_err(zip_error)?;
+    Ok(())
+}
+
+// Top-level files of the config dir (MCP servers, settings, ...)
+fn config_files(dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+// Where an archive entry goes on restore
+enum Restored<'a> {
+    // The hash of a blob stored as blobs/<first 2 characters>/<hash>
+    Blob(&'a str),
+    // A plain file name in the config dir
+    Config(&'a str),
+    // The manifest or database, restored on their own
+    Skipped,
+}
+
+// Only the names write_backup makes are accepted; anything else could be written outside the
+// blob store or config dir
+fn restored_entry(name: &str) -> Result<Restored<'_>, String> {
+    if let Some(relative) = name.strip_prefix(BLOBS_PREFIX) {
+        match relative.split_once('/') {
+            Some((dir, hash)) if is_blob_hash(hash) && hash.starts_with(dir) && dir.len() == 2 => {
+                return Ok(Restored::Blob(hash))
+            }
+            _ => {}
+        }
+    } else if let Some(relative) = name.strip_prefix(CONFIG_PREFIX) {
+        let plain = !matches!(relative, "" | "." | "..") && !relative.contains(['/', '\\', ':']);
+        if plain {
+            return Ok(Restored::Config(relative));
+        }
+    } else if name == MANIFEST_FILE || name == DB_ENTRY {
+        return Ok(Restored::Skipped);
+    }
+    Err(format!("Backup contains an unsafe path: {}", name))
+}
+
+fn write_backup(app: &AppHandle, db: &Database, blobs: &BlobStore, path: &Path) -> Result<BackupManifest, String> {
+    let file = std::fs::File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+
+    let snapshot = std::env::temp_dir().join(format!("{}.db", db::new_id("openchat-backup")));
+    db.snapshot_to(&snapshot)?;
+    let result = add_file(&mut zip, DB_ENTRY, &snapshot);
+    let _ = std::fs::remove_file(&snapshot);
+    result?;
+
+    let mut blob_count = 0;
+    for prefix in std::fs::read_dir(blobs.root()).into_iter().flatten().flatten() {
+        if !prefix.path().is_dir() {
+            continue;
+        }
+        for blob in std::fs::read_dir(prefix.path()).into_iter().flatten().flatten() {
+            // Leftovers that aren't blobs wouldn't restore
+            if !is_blob_hash(&blob.file_name().to_string_lossy()) {
+                continue;
+            }
+            let name = format!(
+                "{}{}/{}",
+                BLOBS_PREFIX,
+                prefix.file_name().to_string_lossy(),
+                blob.file_name().to_string_lossy()
+            );
+            add_file(&mut zip, &name, &blob.path())?;
+            blob_count += 1;
+        }
+    }
+
+    let mut config_names = Vec::new();
+    for config in config_files(&profiles::config_dir(app)?) {
+        let name = config.file_name().unwrap_or_default().to_string_lossy().to_string();
+        add_file(&mut zip, &format!("{}{}", CONFIG_PREFIX, name), &config)?;
+        config_names.push(name);
+    }
+
+    let manifest = BackupManifest {
+        format: BACKUP_FORMAT.to_string(),
+        schema_version: db::SCHEMA_VERSION,
+        app_version: app.package_info().version.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        blob_count,
+        config_files: config_names,
+        encrypted: db.key().is_some(),
+    };
+    zip.start_file(MANIFEST_FILE, SimpleFileOptions::default()).map_err(zip_error)?;
+    let json = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+    zip.write_all(&json).map_err(zip_error)?;
+    zip.finish().map_err(zip_error)?;
+    Ok(manifest)
+}
+
+// Check the archive before touching any data: manifest, schema version, entry paths and database integrity.
+// Returns the manifest and the database extracted to a temp file.
+fn validate_backup(
+    db: &Database,
+    archive: &mut zip::ZipArchive<std::fs::File>,
+) -> Result<(BackupManifest, PathBuf), String> {
+    let manifest: BackupManifest = {
+        let mut entry = archive
+            .by_name(MANIFEST_FILE)
+            .map_err(|_| "Not an OpenChat backup (manifest missing)".to_string())?;
+        let mut text = String::new();
+        entry.read_to_string(&mut text).map_err(zip_error)?;
+        serde_json::from_str(&text).map_err(|e| format!("Invalid backup manifest: {}", e))?
+    };
+    if manifest.format != BACKUP_FORMAT {
+        return Err(format!("Unsupported backup format '{}'", manifest.format));
+    }
+    if manifest.schema_version > db::SCHEMA_VERSION {
+        return Err(format!(
+            "Backup was made by a newer version (schema {}, this build supports {})",
+            manifest.schema_version,
+            db::SCHEMA_VERSION
+        ));
+    }
+    if manifest.encrypted != db.key().is_some() {
+        return Err(if manifest.encrypted {
+            "Backup is encrypted; enable encryption with the same passphrase before restoring it".to_string()
+        } else {
+            "Backup is not encrypted; disable encryption before restoring it".to_string()
+        });
+    }
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i).map_err(zip_error)?;
+        restored_entry(entry.name())?;
+    }
+
+    let extracted = std::env::temp_dir().join(format!("{}.db", db::new_id("openchat-restore")));
+    {
+        let mut entry = archive
+            .by_name(DB_ENTRY)
+            .map_err(|_| "Backup does not contain a database".to_string())?;
+        let mut file = std::fs::File::create(&extracted).map_err(|e| format!("Failed to extract database: {}", e))?;
+        std::io::copy(&mut entry, &mut file).map_err(|e| format!("Failed to extract database: {}", e))?;
+    }
+    // Opened with the current key, so an encrypted backup from a different passphrase fails here
+    let check: Result<String, String> = db.open_sibling(&extracted).and_then(|conn| {
+        conn.query_row("PRAGMA integrity_check", [], |row| row.get(0))
+            .map_err(|e| format!("Backup database is unreadable: {}", e))
+    });
+    match check {
+        Ok(result) if result == "ok" => Ok((manifest, extracted)),
+        Ok(result) => {
+            let _ = std::fs::remove_file(&extracted);
+            Err(format!("Backup database failed the integrity check: {}", result))
+        }
+        Err(err) => {
+            let _ = std::fs::remove_file(&extracted);
+            Err(err)
+        }
+    }
+}
+
+#[tauri::command]
+pub fn create_backup(
+    app: AppHandle,
+    db: State<'_, Database>,
+    blobs: State<'_, BlobStore>,
+    path: String,
+) -> Result<BackupManifest, String> {
+    let manifest = write_backup(&app, &db, &blobs, Path::new(&path))?;
+    eprintln!("[Backup] Wrote {} ({} blobs)", path, manifest.blob_count);
+    Ok(manifest)
+}
+
+#[tauri::command]
+pub fn restore_backup(
+    app: AppHandle,
+    db: State<'_, Database>,
+    blobs: State<'_, BlobStore>,
+    path: String,
+) -> Result<RestoreResult, String> {
+    let file = std::fs::File::open(&path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(zip_error)?;
+    let (manifest, extracted_db) = validate_backup(&db, &mut archive)?;
+
+    // Keep the current data so a bad restore can be undone
+    let backups_dir = profiles::data_dir(&app)?.join("backups");
+    std::fs::create_dir_all(&backups_dir).map_err(|e| format!("Failed to create {}: {}", backups_dir.display(), e))?;
+    let safety_backup = backups_dir.join(format!("pre-restore-{}.zip", chrono::Utc::now().format("%Y%m%d-%H%M%S")));
+    let safety = write_backup(&app, &db, &blobs, &safety_backup);
+    if let Err(err) = safety {
+        let _ = std::fs::remove_file(&extracted_db);
+        return Err(format!("Failed to back up current data before restoring: {}", err));
+    }
+
+    let restored = db.restore_from(&extracted_db);
+    let _ = std::fs::remove_file(&extracted_db);
+    restored?;
+
+    let config_dir = profiles::config_dir(&app)?;
+    let mut restored_blobs = 0;
+    let mut restored_config_files = 0;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(zip_error)?;
+        let target = match restored_entry(entry.name())? {
+            Restored::Blob(hash) => {
+                restored_blobs += 1;
+                blobs.blob_path(hash)
+            }
+            Restored::Config(name) => {
+                restored_config_files += 1;
+                config_dir.join(name)
+            }
+            Restored::Skipped => continue,
+        };
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let mut out = std::fs::File::create(&target).map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+        std::io::copy(&mut entry, &mut out).map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+    }
+
+    eprintln!(
+        "[Backup] Restored {} ({} blobs, {} config files)",
+        path, restored_blobs, restored_config_files
+    );
+    Ok(RestoreResult {
+        manifest,
+        restored_blobs,
+        restored_config_files,
+        safety_backup: safety_backup.to_string_lossy().to_string(),
+    })
+}
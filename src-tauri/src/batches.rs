@@ -0,0 +1,107 @@
+// Cancellable scrape batches. scrape_urls runs under the batch id its caller gives it, and
+// cancel_scrape(id) stops that batch: URLs still waiting or running return as cancelled at once,
+// navigations in its browser tabs are stopped, and the batch ends with the results it has. The
+// blocking scrape threads can't be killed outright; they check the cancellation between steps and
+// give up at the next one.
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use headless_chrome::Tab;
+use tauri::State;
+use tokio::sync::watch;
+
+#[derive(Default)]
+struct TokenState {
+    cancelled: watch::Sender<bool>,
+    // Tabs working for the batch, by watch id
+    tabs: Mutex<Vec<(u64, Arc<Tab>)>>,
+    next_id: AtomicU64,
+}
+
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    state: Arc<TokenState>,
+}
+
+// Keeps a tab's navigation stoppable by its batch until dropped
+pub struct TabWatch {
+    token: CancelToken,
+    id: u64,
+}
+
+impl CancelToken {
+    pub fn is_cancelled(&self) -> bool {
+        *self.state.cancelled.borrow()
+    }
+
+    pub fn cancel(&self) {
+        self.state.cancelled.send_replace(true);
+        let tabs = self.state.tabs.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        for (_, tab) in tabs.iter() {
+            if let Err(err) = tab.stop_loading() {
+                eprintln!("[Scrape] Failed to stop a navigation: {}", err);
+            }
+        }
+    }
+
+    // Resolves once the batch is cancelled
+    pub async fn cancelled(&self) {
+        let mut receiver = self.state.cancelled.subscribe();
+        let _ = receiver.wait_for(|cancelled| *cancelled).await;
+    }
+
+    pub fn watch(&self, tab: Arc<Tab>) -> TabWatch {
+        let id = self.state.next_id.fetch_add(1, Ordering::Relaxed);
+        self.state.tabs.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push((id, tab));
+        TabWatch {
+            token: self.clone(),
+            id,
+        }
+    }
+}
+
+impl Drop for TabWatch {
+    fn drop(&mut self) {
+        let mut tabs = self.token.state.tabs.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        tabs.retain(|(id, _)| *id != self.id);
+    }
+}
+
+// Batches running, by id
+#[derive(Clone, Default)]
+pub struct ScrapeBatches {
+    running: Arc<Mutex<HashMap<String, CancelToken>>>,
+}
+
+impl ScrapeBatches {
+    // Register a batch; ids are only unique among running batches
+    pub fn start(&self, id: &str) -> Result<CancelToken, String> {
+        let mut running = self.running.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if running.contains_key(id) {
+            return Err(format!("A scrape batch {} is already running", id));
+        }
+        let token = CancelToken::default();
+        running.insert(id.to_string(), token.clone());
+        Ok(token)
+    }
+
+    pub fn finish(&self, id: &str) {
+        self.running.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).remove(id);
+    }
+}
+
+// Stop the scrape_urls batch `batch_id`; it returns what it has scraped so far. Returns whether
+// such a batch was running.
+#[tauri::command]
+pub fn cancel_scrape(batches: State<'_, ScrapeBatches>, batch_id: String) -> bool {
+    let running = batches.running.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    match running.get(&batch_id) {
+        Some(token) => {
+            eprintln!("[Scrape] Cancelling batch {}", batch_id);
+            token.cancel();
+            true
+        }
+        None => false,
+    }
+}
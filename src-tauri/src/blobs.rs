@@ -0,0 +1,379 @@
+// Content-addressed blob store for attachments: files live under <app data>/blobs/<sha256>
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use rusqlite::{params, OptionalExtension};
+use sha2::{Digest, Sha256};
+use tauri::State;
+
+use crate::conversations::{self, Attachment};
+use crate::db::{self, Database};
+
+// Header of encrypted blobs, followed by the 12-byte nonce and the AES-256-GCM ciphertext
+const ENCRYPTED_MAGIC: &[u8] = b"OCBLOB1\0";
+const NONCE_LEN: usize = 12;
+
+pub struct BlobStore {
+    // Follows the active profile
+    root: RwLock<PathBuf>,
+    // Set when storage encryption is enabled and unlocked
+    key: RwLock<Option<[u8; 32]>>,
+}
+
+// A blob written (or found) in the store
+pub struct StoredBlob {
+    pub hash: String,
+    pub path: PathBuf,
+    pub size: u64,
+    // True when an identical file was already stored
+    pub existing: bool,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentResult {
+    #[serde(flatten)]
+    attachment: Attachment,
+    deduplicated: bool,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GcReport {
+    removed_blobs: usize,
+    freed_bytes: u64,
+}
+
+// Lowercase hex, as blob names, keys and other hashes are written
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// Blob hashes are lowercase hex SHA-256; any other name from a remote or an archive could reach
+// outside the blob store
+pub(crate) fn is_blob_hash(hash: &str) -> bool {
+    hash.len() == 64 && hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+// Best-effort MIME type from the file extension
+pub fn guess_mime_type(file_name: &str) -> Option<String> {
+    let extension = Path::new(file_name).extension()?.to_str()?.to_lowercase();
+    let mime = match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "m4a" => "audio/mp4",
+        "webm" => "audio/webm",
+        "txt" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "json" => "application/json",
+        _ => return None,
+    };
+    Some(mime.to_string())
+}
+
+impl BlobStore {
+    pub fn new(root: PathBuf, key: Option<[u8; 32]>) -> Self {
+        BlobStore {
+            root: RwLock::new(root),
+            key: RwLock::new(key),
+        }
+    }
+
+    // Point the store at another directory (and key), e.g. after switching profiles
+    pub fn switch_to(&self, root: PathBuf, key: Option<[u8; 32]>) {
+        if let Ok(mut current) = self.root.write() {
+            *current = root;
+        }
+        self.set_key(key);
+    }
+
+    pub fn set_key(&self, key: Option<[u8; 32]>) {
+        if let Ok(mut current) = self.key.write() {
+            *current = key;
+        }
+    }
+
+    pub(crate) fn current_key(&self) -> Option<[u8; 32]> {
+        self.key.read().ok().and_then(|k| *k)
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.current_key().is_some()
+    }
+
+    pub(crate) fn encrypt(key: &[u8; 32], bytes: &[u8]) -> Result<Vec<u8>, String> {
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, bytes)
+            .map_err(|_| "Failed to encrypt blob".to_string())?;
+        let mut out = Vec::with_capacity(ENCRYPTED_MAGIC.len() + NONCE_LEN + ciphertext.len());
+        out.extend_from_slice(ENCRYPTED_MAGIC);
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&ciphertext);
+        Ok(out)
+    }
+
+    // Whether `data` was written by encrypt
+    pub(crate) fn is_encrypted_data(data: &[u8]) -> bool {
+        data.starts_with(ENCRYPTED_MAGIC)
+    }
+
+    pub(crate) fn decrypt_with(key: &[u8; 32], data: &[u8]) -> Result<Vec<u8>, String> {
+        let sealed = data.strip_prefix(ENCRYPTED_MAGIC).ok_or("Blob is not encrypted")?;
+        if sealed.len() < NONCE_LEN {
+            return Err("Encrypted blob is truncated".to_string());
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key))
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "Failed to decrypt blob (wrong key or corrupted file)".to_string())
+    }
+
+    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        if !Self::is_encrypted_data(data) {
+            // Written before encryption was enabled
+            return Ok(data.to_vec());
+        }
+        let key = self
+            .current_key()
+            .ok_or("Attachment is encrypted and storage is locked")?;
+        Self::decrypt_with(&key, data)
+    }
+
+    // Contents of a stored blob, decrypted if needed
+    pub fn read(&self, path: &Path) -> Result<Vec<u8>, String> {
+        let data = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        self.decrypt(&data)
+    }
+
+    // Rewrite every blob under a new key (None stores them as plaintext) and switch to it
+    pub fn reencrypt_all(&self, new_key: Option<[u8; 32]>) -> Result<usize, String> {
+        let mut rewritten = 0;
+        for (_, path, _) in self.list() {
+            let plain = self.read(&path)?;
+            let data = match &new_key {
+                Some(key) => Self::encrypt(key, &plain)?,
+                None => plain,
+            };
+            let tmp = self.tmp_path();
+            std::fs::write(&tmp, &data).map_err(|e| format!("Failed to write blob: {}", e))?;
+            std::fs::rename(&tmp, &path).map_err(|e| format!("Failed to replace blob: {}", e))?;
+            rewritten += 1;
+        }
+        self.set_key(new_key);
+        Ok(rewritten)
+    }
+
+    pub fn root(&self) -> PathBuf {
+        self.root.read().map(|r| r.clone()).unwrap_or_default()
+    }
+
+    // Two-level layout (ab/abcdef...) keeps directories small
+    pub fn blob_path(&self, hash: &str) -> PathBuf {
+        self.root().join(&hash[..2]).join(hash)
+    }
+
+    fn tmp_path(&self) -> PathBuf {
+        self.root().join(format!(".tmp-{}", db::new_id("blob")))
+    }
+
+    // Move a fully written temp file into place, or drop it if the blob already exists
+    fn commit(&self, tmp: &Path, hash: String, size: u64) -> Result<StoredBlob, String> {
+        let path = self.blob_path(&hash);
+        if path.exists() {
+            let _ = std::fs::remove_file(tmp);
+            return Ok(StoredBlob { hash, path, size, existing: true });
+        }
+        std::fs::create_dir_all(path.parent().unwrap_or(&self.root()))
+            .map_err(|e| format!("Failed to create blob dir: {}", e))?;
+        std::fs::rename(tmp, &path).map_err(|e| format!("Failed to store blob: {}", e))?;
+        Ok(StoredBlob { hash, path, size, existing: false })
+    }
+
+    pub fn put_bytes(&self, bytes: &[u8]) -> Result<StoredBlob, String> {
+        std::fs::create_dir_all(self.root()).map_err(|e| format!("Failed to create blob dir: {}", e))?;
+        // Hashed before encryption so identical files still deduplicate
+        let hash = to_hex(&Sha256::digest(bytes));
+        let tmp = self.tmp_path();
+        let written = match self.current_key() {
+            Some(key) => std::fs::write(&tmp, Self::encrypt(&key, bytes)?),
+            None => std::fs::write(&tmp, bytes),
+        };
+        written.map_err(|e| format!("Failed to write blob: {}", e))?;
+        self.commit(&tmp, hash, bytes.len() as u64)
+    }
+
+    // Copy a file into the store, hashing it while copying so large files aren't held in memory
+    pub fn put_file(&self, source: &Path) -> Result<StoredBlob, String> {
+        if self.is_encrypted() {
+            // AES-GCM seals the whole blob at once
+            let bytes = std::fs::read(source).map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+            return self.put_bytes(&bytes);
+        }
+        std::fs::create_dir_all(self.root()).map_err(|e| format!("Failed to create blob dir: {}", e))?;
+        let mut input =
+            std::fs::File::open(source).map_err(|e| format!("Failed to open {}: {}", source.display(), e))?;
+        let tmp = self.tmp_path();
+        let mut output = std::fs::File::create(&tmp).map_err(|e| format!("Failed to write blob: {}", e))?;
+
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0u8; 64 * 1024];
+        let mut size = 0u64;
+        loop {
+            let read = input
+                .read(&mut buffer)
+                .map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+            output
+                .write_all(&buffer[..read])
+                .map_err(|e| format!("Failed to write blob: {}", e))?;
+            size += read as u64;
+        }
+        drop(output);
+        self.commit(&tmp, to_hex(&hasher.finalize()), size)
+    }
+
+    // Every blob on disk as (hash, path, size)
+    pub fn list(&self) -> Vec<(String, PathBuf, u64)> {
+        let Ok(prefixes) = std::fs::read_dir(self.root()) else {
+            return Vec::new();
+        };
+        prefixes
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| std::fs::read_dir(entry.path()).ok())
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let hash = entry.file_name().to_str()?.to_string();
+                let size = entry.metadata().ok()?.len();
+                Some((hash, entry.path(), size))
+            })
+            .collect()
+    }
+}
+
+// Store a new attachment for a message from a file path or raw bytes
+#[tauri::command]
+pub fn store_attachment(
+    db: State<'_, Database>,
+    blobs: State<'_, BlobStore>,
+    message_id: String,
+    file_name: String,
+    mime_type: Option<String>,
+    source_path: Option<String>,
+    bytes: Option<Vec<u8>>,
+) -> Result<AttachmentResult, String> {
+    let blob = match (source_path, bytes) {
+        (Some(path), _) => blobs.put_file(Path::new(&path))?,
+        (None, Some(bytes)) => blobs.put_bytes(&bytes)?,
+        (None, None) => return Err("Either sourcePath or bytes is required".to_string()),
+    };
+    let deduplicated = blob.existing;
+    let attachment = attach_blob(&db, message_id, file_name, mime_type, blob)?;
+    Ok(AttachmentResult {
+        attachment,
+        deduplicated,
+    })
+}
+
+// Record a stored blob as an attachment of a message
+pub fn attach_blob(
+    db: &Database,
+    message_id: String,
+    file_name: String,
+    mime_type: Option<String>,
+    blob: StoredBlob,
+) -> Result<Attachment, String> {
+    let attachment = Attachment {
+        id: db::new_id("att"),
+        message_id,
+        mime_type: mime_type.or_else(|| guess_mime_type(&file_name)),
+        file_name,
+        size: Some(blob.size as i64),
+        path: blob.path.to_string_lossy().to_string(),
+        hash: Some(blob.hash),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    db.with_conn(|conn| conversations::insert_attachment(conn, &attachment))?;
+    if blob.existing {
+        eprintln!("[Blobs] Reused existing blob for {}", attachment.file_name);
+    }
+    Ok(attachment)
+}
+
+#[tauri::command]
+pub fn read_attachment(db: State<'_, Database>, blobs: State<'_, BlobStore>, id: String) -> Result<Vec<u8>, String> {
+    let path: Option<String> = db.with_conn(|conn| {
+        conn.query_row("SELECT path FROM attachments WHERE id = ?1", params![id], |row| row.get(0))
+            .optional()
+    })?;
+    let path = path.ok_or_else(|| format!("Attachment {} not found", id))?;
+    blobs.read(Path::new(&path))
+}
+
+// Remove the attachment row; the blob stays until garbage collection finds it unreferenced
+#[tauri::command]
+pub fn delete_attachment(db: State<'_, Database>, id: String) -> Result<(), String> {
+    let deleted = db.with_conn(|conn| conn.execute("DELETE FROM attachments WHERE id = ?1", params![id]))?;
+    if deleted == 0 {
+        return Err(format!("Attachment {} not found", id));
+    }
+    Ok(())
+}
+
+// Delete blobs no attachment refers to (left behind by deleted messages and conversations)
+#[tauri::command]
+pub fn collect_orphaned_blobs(db: State<'_, Database>, blobs: State<'_, BlobStore>) -> Result<GcReport, String> {
+    collect_orphans(&db, &blobs)
+}
+
+pub fn collect_orphans(db: &Database, blobs: &BlobStore) -> Result<GcReport, String> {
+    let referenced: HashSet<String> = db.with_conn(|conn| {
+        let mut stmt = conn.prepare("SELECT DISTINCT hash FROM attachments WHERE hash IS NOT NULL")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect()
+    })?;
+
+    let mut report = GcReport {
+        removed_blobs: 0,
+        freed_bytes: 0,
+    };
+    for (hash, path, size) in blobs.list() {
+        if referenced.contains(&hash) {
+            continue;
+        }
+        if std::fs::remove_file(&path).is_ok() {
+            report.removed_blobs += 1;
+            report.freed_bytes += size;
+        }
+    }
+    // Leftover temp files from interrupted writes are orphans too
+    for entry in std::fs::read_dir(blobs.root()).into_iter().flatten().flatten() {
+        let is_tmp = entry.file_name().to_str().is_some_and(|n| n.starts_with(".tmp-"));
+        if is_tmp && std::fs::remove_file(entry.path()).is_ok() {
+            report.removed_blobs += 1;
+        }
+    }
+    eprintln!(
+        "[Blobs] Garbage collection removed {} blobs ({} bytes)",
+        report.removed_blobs, report.freed_bytes
+    );
+    Ok(report)
+}
@@ -0,0 +1,345 @@
+// Shared headless browser for scraping: one Chrome process, launched on first use, whose tabs are
+// handed out to scrapes and reused afterwards. At most `browserPoolSize` tabs are open at once and
+// further scrapes wait for a free one. Tabs that fail a health check are replaced, a browser that
+// died is relaunched, and the browser shuts down after `browserIdleSecs` without use.
+use std::ffi::OsStr;
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use headless_chrome::{Browser, LaunchOptions, Tab};
+use tauri::State;
+
+use crate::proxy::{self, ProxyConfig};
+use crate::settings::Settings;
+use crate::stealth::{self, BrowserProfile};
+
+// How often the idle browser is checked for
+const REAPER_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Clone)]
+pub struct PoolConfig {
+    // Browser executable; found automatically when not given
+    pub browser_path: Option<PathBuf>,
+    pub max_tabs: usize,
+    pub idle_timeout: Duration,
+    pub launch: LaunchSettings,
+}
+
+// What the browser is launched with; the running browser is relaunched when these change
+#[derive(Clone, PartialEq, Default)]
+pub struct LaunchSettings {
+    pub proxy: Option<ProxyConfig>,
+    pub profile: Option<BrowserProfile>,
+    pub stealth: bool,
+}
+
+impl PoolConfig {
+    pub fn from_settings(settings: &Settings) -> Self {
+        PoolConfig {
+            browser_path: None,
+            max_tabs: settings.browser_pool_size.max(1),
+            idle_timeout: Duration::from_secs(settings.browser_idle_secs),
+            launch: LaunchSettings {
+                proxy: proxy::from_settings(settings),
+                profile: stealth::profile_from_settings(settings),
+                stealth: settings.browser_stealth,
+            },
+        }
+    }
+}
+
+#[derive(Default)]
+struct PoolState {
+    browser: Option<Browser>,
+    // Executable the running browser was launched from
+    path: Option<PathBuf>,
+    launch: LaunchSettings,
+    // Bumped on every launch, so tabs of an earlier browser aren't pooled again
+    generation: u64,
+    idle: Vec<Arc<Tab>>,
+    in_use: usize,
+    last_used: Option<Instant>,
+    idle_timeout: Duration,
+    reaper_running: bool,
+}
+
+#[derive(Default)]
+struct Shared {
+    state: Mutex<PoolState>,
+    // Signalled whenever a tab is returned
+    returned: Condvar,
+}
+
+#[derive(Clone, Default)]
+pub struct BrowserPool {
+    shared: Arc<Shared>,
+}
+
+// A tab checked out of the pool; it goes back when dropped
+pub struct PooledTab {
+    tab: Option<Arc<Tab>>,
+    generation: u64,
+    shared: Arc<Shared>,
+}
+
+impl Deref for PooledTab {
+    type Target = Tab;
+
+    fn deref(&self) -> &Tab {
+        self.tab.as_ref().expect("tab is only taken on drop")
+    }
+}
+
+impl PooledTab {
+    // The tab itself, for stopping its navigation from another thread
+    pub fn handle(&self) -> Arc<Tab> {
+        self.tab.clone().expect("tab is only taken on drop")
+    }
+}
+
+impl Drop for PooledTab {
+    fn drop(&mut self) {
+        let Some(tab) = self.tab.take() else { return };
+        // Leave the page so it stops running scripts; a tab that can't is closed
+        let reusable = tab.navigate_to("about:blank").is_ok();
+        let mut state = lock(&self.shared);
+        state.in_use = state.in_use.saturating_sub(1);
+        state.last_used = Some(Instant::now());
+        if reusable && self.generation == state.generation && state.browser.is_some() {
+            state.idle.push(tab);
+        } else {
+            let _ = tab.close(false);
+        }
+        drop(state);
+        self.shared.returned.notify_one();
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolStatus {
+    pub running: bool,
+    pub busy_tabs: usize,
+    pub idle_tabs: usize,
+    pub browser_path: Option<String>,
+    // Seconds since a tab was last returned
+    pub idle_secs: Option<u64>,
+}
+
+fn lock(shared: &Shared) -> MutexGuard<'_, PoolState> {
+    // A panic while holding the lock leaves nothing half-updated that matters here
+    shared.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+// Chrome, Chromium or Edge in their usual install locations on Windows; elsewhere
+// headless_chrome looks on the PATH itself
+pub fn find_chrome_path() -> Option<PathBuf> {
+    let possible_paths = [
+        // Chrome
+        std::env::var("PROGRAMFILES").ok().map(|p| format!("{}\\Google\\Chrome\\Application\\chrome.exe", p)),
+        std::env::var("PROGRAMFILES(X86)").ok().map(|p| format!("{}\\Google\\Chrome\\Application\\chrome.exe", p)),
+        std::env::var("LOCALAPPDATA").ok().map(|p| format!("{}\\Google\\Chrome\\Application\\chrome.exe", p)),
+        // Chromium
+        std::env::var("PROGRAMFILES").ok().map(|p| format!("{}\\Chromium\\Application\\chrome.exe", p)),
+        std::env::var("LOCALAPPDATA").ok().map(|p| format!("{}\\Chromium\\Application\\chrome.exe", p)),
+        // Edge (Chromium-based)
+        std::env::var("PROGRAMFILES(X86)").ok().map(|p| format!("{}\\Microsoft\\Edge\\Application\\msedge.exe", p)),
+        std::env::var("PROGRAMFILES").ok().map(|p| format!("{}\\Microsoft\\Edge\\Application\\msedge.exe", p)),
+    ];
+    let found = possible_paths.into_iter().flatten().map(PathBuf::from).find(|path| path.exists());
+    match &found {
+        Some(path) => eprintln!("[Browser] Found browser at: {}", path.display()),
+        None => eprintln!("[Browser] No Chrome/Chromium/Edge browser found in the usual locations"),
+    }
+    found
+}
+
+fn launch(path: Option<PathBuf>, settings: &LaunchSettings) -> Result<Browser, String> {
+    let proxy = settings.proxy.as_ref();
+    if proxy.is_some_and(|proxy| !proxy.browser_usable()) {
+        return Err("The browser can't sign in to the proxy".to_string());
+    }
+    let server = proxy.map(ProxyConfig::browser_server);
+    let mut args = stealth::launch_args(settings.profile.as_ref(), settings.stealth);
+    args.extend(proxy.and_then(ProxyConfig::browser_bypass_arg));
+    // Chrome marks itself as automated with this default flag
+    let automation = [OsStr::new("--enable-automation")];
+    let options = LaunchOptions {
+        headless: true,
+        sandbox: false,
+        path,
+        proxy_server: server.as_deref(),
+        window_size: settings.profile.as_ref().map(|profile| profile.viewport),
+        args: args.iter().map(OsStr::new).collect(),
+        ignore_default_args: if settings.stealth { automation.to_vec() } else { Vec::new() },
+        // The pool shuts the browser down itself; headless_chrome would after 30 quiet seconds
+        idle_browser_timeout: Duration::from_secs(24 * 60 * 60),
+        ..Default::default()
+    };
+    Browser::new(options).map_err(|err| format!("Failed to launch browser: {err}"))
+}
+
+impl BrowserPool {
+    // A tab of the running browser, launching it first when needed; waits up to `wait` when all
+    // `max_tabs` tabs are busy
+    pub fn acquire(&self, config: &PoolConfig, wait: Duration) -> Result<PooledTab, String> {
+        let deadline = Instant::now() + wait;
+        let mut state = lock(&self.shared);
+        state.idle_timeout = config.idle_timeout;
+        loop {
+            // A browser launched with other settings (another proxy, say) is relaunched once all its
+            // tabs are back; until then none of them may be handed out
+            let outdated = state.browser.is_some() && state.launch != config.launch;
+            if outdated && state.in_use == 0 {
+                eprintln!("[Browser] Launch settings changed, relaunching");
+                state.browser = None;
+                state.idle.clear();
+                state.generation += 1;
+            }
+            let waiting_for_relaunch = outdated && state.in_use > 0;
+            if let Some(tab) = (!waiting_for_relaunch).then(|| state.idle.pop()).flatten() {
+                state.in_use += 1;
+                let generation = state.generation;
+                drop(state);
+                if tab.evaluate("1", false).is_ok() {
+                    return Ok(self.checked_out(tab, generation));
+                }
+                eprintln!("[Browser] Replacing a tab that stopped responding");
+                let _ = tab.close(false);
+                state = lock(&self.shared);
+                state.in_use -= 1;
+                continue;
+            }
+            if !waiting_for_relaunch && state.in_use < config.max_tabs {
+                let browser = self.healthy_browser(&mut state, config)?;
+                state.in_use += 1;
+                let generation = state.generation;
+                drop(state);
+                match browser.new_tab() {
+                    Ok(tab) => {
+                        let launch = &config.launch;
+                        if let Err(err) = stealth::prepare_tab(&tab, launch.profile.as_ref(), launch.stealth) {
+                            eprintln!("[Browser] {}", err);
+                        }
+                        return Ok(self.checked_out(tab, generation));
+                    }
+                    Err(err) => {
+                        let mut state = lock(&self.shared);
+                        state.in_use -= 1;
+                        // Most likely the browser went away; the next acquire relaunches it
+                        if state.generation == generation && state.in_use == 0 {
+                            state.browser = None;
+                        }
+                        drop(state);
+                        self.shared.returned.notify_one();
+                        return Err(format!("Failed to create tab: {err}"));
+                    }
+                }
+            }
+            let now = Instant::now();
+            if now >= deadline && waiting_for_relaunch {
+                return Err("The browser is busy with pages under other launch settings".to_string());
+            }
+            if now >= deadline {
+                return Err(format!("All {} browser tabs are busy", config.max_tabs));
+            }
+            state = self
+                .shared
+                .returned
+                .wait_timeout(state, deadline - now)
+                .map(|(state, _)| state)
+                .unwrap_or_else(|poisoned| poisoned.into_inner().0);
+        }
+    }
+
+    fn checked_out(&self, tab: Arc<Tab>, generation: u64) -> PooledTab {
+        PooledTab {
+            tab: Some(tab),
+            generation,
+            shared: self.shared.clone(),
+        }
+    }
+
+    // The running browser if it still answers, otherwise a newly launched one. An explicit path
+    // other than the running browser's only takes effect once no tabs are in use.
+    fn healthy_browser(&self, state: &mut PoolState, config: &PoolConfig) -> Result<Browser, String> {
+        if let Some(browser) = &state.browser {
+            let other_path = config.browser_path.is_some() && config.browser_path != state.path;
+            let alive = browser.get_version().is_ok();
+            if alive && !(other_path && state.in_use == 0) {
+                return Ok(browser.clone());
+            }
+            if !alive {
+                eprintln!("[Browser] Browser stopped responding, relaunching");
+            }
+        }
+        let path = config.browser_path.clone().or_else(find_chrome_path);
+        state.browser = None;
+        state.idle.clear();
+        state.generation += 1;
+        let browser = launch(path.clone(), &config.launch)?;
+        eprintln!("[Browser] Launched headless browser (pool of {} tabs)", config.max_tabs);
+        state.browser = Some(browser.clone());
+        state.path = path;
+        state.launch = config.launch.clone();
+        state.last_used = Some(Instant::now());
+        if !state.reaper_running {
+            state.reaper_running = true;
+            self.start_reaper();
+        }
+        Ok(browser)
+    }
+
+    // Shut the browser down once it has been unused for the idle timeout
+    fn start_reaper(&self) {
+        let shared = self.shared.clone();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(REAPER_INTERVAL);
+            let mut state = lock(&shared);
+            let idle_for = state.last_used.map(|at| at.elapsed()).unwrap_or_default();
+            if state.browser.is_some() && state.in_use == 0 && idle_for >= state.idle_timeout {
+                eprintln!("[Browser] Shutting down after {}s idle", idle_for.as_secs());
+                state.browser = None;
+                state.idle.clear();
+                state.generation += 1;
+            }
+            if state.browser.is_none() {
+                state.reaper_running = false;
+                return;
+            }
+        });
+    }
+
+    pub fn status(&self) -> PoolStatus {
+        let state = lock(&self.shared);
+        PoolStatus {
+            running: state.browser.is_some(),
+            busy_tabs: state.in_use,
+            idle_tabs: state.idle.len(),
+            browser_path: state.path.as_ref().map(|p| p.display().to_string()),
+            idle_secs: state.last_used.filter(|_| state.in_use == 0).map(|at| at.elapsed().as_secs()),
+        }
+    }
+
+    // Close the browser now; tabs still in use are closed when they are returned
+    pub fn shutdown(&self) {
+        let mut state = lock(&self.shared);
+        if state.browser.take().is_some() {
+            eprintln!("[Browser] Shut down on request");
+        }
+        state.idle.clear();
+        state.generation += 1;
+    }
+}
+
+#[tauri::command]
+pub fn browser_pool_status(pool: State<'_, BrowserPool>) -> PoolStatus {
+    pool.status()
+}
+
+#[tauri::command]
+pub fn shutdown_browser_pool(pool: State<'_, BrowserPool>) {
+    pool.shutdown()
+}
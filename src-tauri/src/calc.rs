@@ -0,0 +1,590 @@
+// Deterministic arithmetic for the agent: an expression evaluator, unit conversion and currency
+// conversion with ECB reference rates (fetched at most once a day).
+//
+// "2^10 / 3", "sqrt(2) * pi", "5 km to mi", "(20 + 5) degC in degF", "100 USD to EUR"
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::{NaiveDate, Utc};
+
+use crate::sources::SourceClient;
+
+// Reference rates against the euro, published each working day
+const RATES_API: &str = "https://api.frankfurter.dev/v1/latest?base=EUR";
+// Largest n whose factorial is finite in an f64
+const MAX_FACTORIAL: f64 = 170.0;
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Calculation {
+    pub value: f64,
+    // Target unit or currency of a conversion
+    pub unit: Option<String>,
+    // The value formatted with its unit, e.g. "3.10686 mi"
+    pub text: String,
+    // Publication date of the exchange rates used
+    pub rates_date: Option<String>,
+}
+
+struct Parser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    input: &'a str,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("{} at position {} in '{}'", message, self.pos + 1, self.input)
+    }
+
+    // sum := product (('+' | '-') product)*
+    fn sum(&mut self) -> Result<f64, String> {
+        let mut value = self.product()?;
+        loop {
+            if self.eat('+') {
+                value += self.product()?;
+            } else if self.eat('-') || self.eat('−') {
+                value -= self.product()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    // product := unary (('*' | '/' | '%') unary)*
+    fn product(&mut self) -> Result<f64, String> {
+        let mut value = self.unary()?;
+        loop {
+            if self.eat('*') || self.eat('×') {
+                value *= self.unary()?;
+            } else if self.eat('/') || self.eat('÷') {
+                let divisor = self.unary()?;
+                if divisor == 0.0 {
+                    return Err("Division by zero".to_string());
+                }
+                value /= divisor;
+            } else if self.eat('%') {
+                let divisor = self.unary()?;
+                if divisor == 0.0 {
+                    return Err("Division by zero".to_string());
+                }
+                value %= divisor;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    // unary := ('-' | '+') unary | power
+    fn unary(&mut self) -> Result<f64, String> {
+        if self.eat('-') || self.eat('−') {
+            return Ok(-self.unary()?);
+        }
+        if self.eat('+') {
+            return self.unary();
+        }
+        self.power()
+    }
+
+    // power := postfix (('^' | '**') unary)?, right-associative
+    fn power(&mut self) -> Result<f64, String> {
+        let base = self.postfix()?;
+        let is_power = self.eat('^') || (self.peek() == Some('*') && self.chars.get(self.pos + 1) == Some(&'*'));
+        if !is_power {
+            return Ok(base);
+        }
+        if self.chars.get(self.pos) == Some(&'*') {
+            self.pos += 2;
+        }
+        Ok(base.powf(self.unary()?))
+    }
+
+    // postfix := primary '!'*
+    fn postfix(&mut self) -> Result<f64, String> {
+        let mut value = self.primary()?;
+        while self.eat('!') {
+            if value < 0.0 || value.fract() != 0.0 || value > MAX_FACTORIAL {
+                return Err(format!("Factorial needs a whole number from 0 to {}", MAX_FACTORIAL));
+            }
+            value = (1..=value as u64).map(|n| n as f64).product();
+        }
+        Ok(value)
+    }
+
+    // primary := number | constant | function '(' args ')' | '(' sum ')'
+    fn primary(&mut self) -> Result<f64, String> {
+        match self.peek() {
+            Some('(') => {
+                self.pos += 1;
+                let value = self.sum()?;
+                if !self.eat(')') {
+                    return Err(self.error("Expected ')'"));
+                }
+                Ok(value)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => self.number(),
+            Some(c) if c.is_alphabetic() || c == 'π' => {
+                let start = self.pos;
+                while self.chars.get(self.pos).is_some_and(|c| c.is_alphanumeric() || *c == 'π') {
+                    self.pos += 1;
+                }
+                let name: String = self.chars[start..self.pos].iter().collect::<String>().to_lowercase();
+                if self.eat('(') {
+                    let mut args = Vec::new();
+                    if !self.eat(')') {
+                        loop {
+                            args.push(self.sum()?);
+                            if self.eat(')') {
+                                break;
+                            }
+                            if !self.eat(',') {
+                                return Err(self.error("Expected ',' or ')'"));
+                            }
+                        }
+                    }
+                    return call(&name, &args);
+                }
+                match name.as_str() {
+                    "pi" | "π" => Ok(std::f64::consts::PI),
+                    "e" => Ok(std::f64::consts::E),
+                    "tau" => Ok(std::f64::consts::TAU),
+                    _ => Err(format!("Unknown name '{}'", name)),
+                }
+            }
+            Some(c) => Err(self.error(&format!("Unexpected '{}'", c))),
+            None => Err(self.error("Unexpected end of expression")),
+        }
+    }
+
+    // Digits with an optional fraction and exponent; _ separates thousands
+    fn number(&mut self) -> Result<f64, String> {
+        let start = self.pos;
+        let digits = |parser: &mut Self| {
+            while parser.chars.get(parser.pos).is_some_and(|c| c.is_ascii_digit() || *c == '_') {
+                parser.pos += 1;
+            }
+        };
+        digits(self);
+        if self.chars.get(self.pos) == Some(&'.') {
+            self.pos += 1;
+            digits(self);
+        }
+        if matches!(self.chars.get(self.pos), Some('e' | 'E'))
+            && self.chars.get(self.pos + 1).is_some_and(|c| c.is_ascii_digit() || *c == '-' || *c == '+')
+        {
+            self.pos += 2;
+            digits(self);
+        }
+        let text: String = self.chars[start..self.pos].iter().filter(|c| **c != '_').collect();
+        text.parse().map_err(|_| self.error(&format!("Invalid number '{}'", text)))
+    }
+}
+
+fn call(name: &str, args: &[f64]) -> Result<f64, String> {
+    let one = |f: fn(f64) -> f64| match args {
+        [x] => Ok(f(*x)),
+        _ => Err(format!("{}() takes one argument", name)),
+    };
+    match name {
+        "sqrt" => one(f64::sqrt),
+        "cbrt" => one(f64::cbrt),
+        "abs" => one(f64::abs),
+        "ln" => one(f64::ln),
+        "log" | "log10" => one(f64::log10),
+        "log2" => one(f64::log2),
+        "exp" => one(f64::exp),
+        "sin" => one(f64::sin),
+        "cos" => one(f64::cos),
+        "tan" => one(f64::tan),
+        "asin" => one(f64::asin),
+        "acos" => one(f64::acos),
+        "atan" => one(f64::atan),
+        "floor" => one(f64::floor),
+        "ceil" => one(f64::ceil),
+        "round" => one(f64::round),
+        "trunc" => one(f64::trunc),
+        "min" | "max" if args.is_empty() => Err(format!("{}() needs at least one argument", name)),
+        "min" => Ok(args.iter().copied().fold(f64::INFINITY, f64::min)),
+        "max" => Ok(args.iter().copied().fold(f64::NEG_INFINITY, f64::max)),
+        "pow" => match args {
+            [base, exponent] => Ok(base.powf(*exponent)),
+            _ => Err("pow() takes two arguments".to_string()),
+        },
+        _ => Err(format!("Unknown function '{}'", name)),
+    }
+}
+
+// Value of an arithmetic expression
+pub fn evaluate(expression: &str) -> Result<f64, String> {
+    let mut parser = Parser {
+        chars: expression.chars().collect(),
+        pos: 0,
+        input: expression,
+    };
+    let value = parser.sum()?;
+    if parser.peek().is_some() {
+        return Err(parser.error("Unexpected input"));
+    }
+    if !value.is_finite() {
+        return Err(format!("'{}' has no finite value", expression.trim()));
+    }
+    Ok(value)
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Dimension {
+    Length,
+    Mass,
+    Time,
+    Volume,
+    Area,
+    Speed,
+    Data,
+    Energy,
+    Power,
+    Pressure,
+    Temperature,
+}
+
+// Names of a unit, its dimension and its size in the dimension's base unit (m, kg, s, l, m², m/s,
+// byte, J, W, Pa; temperatures are converted separately)
+const UNITS: &[(&[&str], Dimension, f64)] = &[
+    (&["nm", "nanometer", "nanometers"], Dimension::Length, 1e-9),
+    (&["um", "µm", "micrometer", "micrometers", "micron"], Dimension::Length, 1e-6),
+    (&["mm", "millimeter", "millimeters"], Dimension::Length, 0.001),
+    (&["cm", "centimeter", "centimeters"], Dimension::Length, 0.01),
+    (&["m", "meter", "meters", "metre", "metres"], Dimension::Length, 1.0),
+    (&["km", "kilometer", "kilometers", "kilometre", "kilometres"], Dimension::Length, 1000.0),
+    (&["in", "inch", "inches", "\""], Dimension::Length, 0.0254),
+    (&["ft", "foot", "feet", "'"], Dimension::Length, 0.3048),
+    (&["yd", "yard", "yards"], Dimension::Length, 0.9144),
+    (&["mi", "mile", "miles"], Dimension::Length, 1609.344),
+    (&["nmi", "nautical mile", "nautical miles"], Dimension::Length, 1852.0),
+    (&["mg", "milligram", "milligrams"], Dimension::Mass, 1e-6),
+    (&["g", "gram", "grams"], Dimension::Mass, 0.001),
+    (&["kg", "kilogram", "kilograms", "kilo", "kilos"], Dimension::Mass, 1.0),
+    (&["t", "tonne", "tonnes", "metric ton"], Dimension::Mass, 1000.0),
+    (&["oz", "ounce", "ounces"], Dimension::Mass, 0.028349523125),
+    (&["lb", "lbs", "pound", "pounds"], Dimension::Mass, 0.45359237),
+    (&["st", "stone", "stones"], Dimension::Mass, 6.35029318),
+    (&["ms", "millisecond", "milliseconds"], Dimension::Time, 0.001),
+    (&["s", "sec", "secs", "second", "seconds"], Dimension::Time, 1.0),
+    (&["min", "mins", "minute", "minutes"], Dimension::Time, 60.0),
+    (&["h", "hr", "hrs", "hour", "hours"], Dimension::Time, 3600.0),
+    (&["d", "day", "days"], Dimension::Time, 86400.0),
+    (&["wk", "week", "weeks"], Dimension::Time, 604800.0),
+    // Average Gregorian month and year
+    (&["month", "months"], Dimension::Time, 2629746.0),
+    (&["yr", "year", "years"], Dimension::Time, 31556952.0),
+    (&["ml", "milliliter", "milliliters", "millilitre", "millilitres"], Dimension::Volume, 0.001),
+    (&["cl", "centiliter", "centiliters"], Dimension::Volume, 0.01),
+    (&["dl", "deciliter", "deciliters"], Dimension::Volume, 0.1),
+    (&["l", "liter", "liters", "litre", "litres"], Dimension::Volume, 1.0),
+    (&["m3", "m³", "cubic meter", "cubic meters"], Dimension::Volume, 1000.0),
+    (&["cm3", "cm³", "cc"], Dimension::Volume, 0.001),
+    (&["tsp", "teaspoon", "teaspoons"], Dimension::Volume, 0.00492892159375),
+    (&["tbsp", "tablespoon", "tablespoons"], Dimension::Volume, 0.01478676478125),
+    (&["floz", "fl oz", "fluid ounce", "fluid ounces"], Dimension::Volume, 0.0295735295625),
+    (&["cup", "cups"], Dimension::Volume, 0.2365882365),
+    (&["pt", "pint", "pints"], Dimension::Volume, 0.473176473),
+    (&["qt", "quart", "quarts"], Dimension::Volume, 0.946352946),
+    (&["gal", "gallon", "gallons"], Dimension::Volume, 3.785411784),
+    (&["impgal", "imperial gallon", "imperial gallons"], Dimension::Volume, 4.54609),
+    (&["mm2", "mm²"], Dimension::Area, 1e-6),
+    (&["cm2", "cm²"], Dimension::Area, 1e-4),
+    (&["m2", "m²", "square meter", "square meters"], Dimension::Area, 1.0),
+    (&["km2", "km²", "square kilometer", "square kilometers"], Dimension::Area, 1e6),
+    (&["ha", "hectare", "hectares"], Dimension::Area, 1e4),
+    (&["in2", "in²", "square inch", "square inches"], Dimension::Area, 0.00064516),
+    (&["ft2", "ft²", "sqft", "square foot", "square feet"], Dimension::Area, 0.09290304),
+    (&["acre", "acres"], Dimension::Area, 4046.8564224),
+    (&["mi2", "mi²", "square mile", "square miles"], Dimension::Area, 2589988.110336),
+    (&["m/s", "mps"], Dimension::Speed, 1.0),
+    (&["km/h", "kmh", "kph"], Dimension::Speed, 1.0 / 3.6),
+    (&["mph", "mi/h"], Dimension::Speed, 0.44704),
+    (&["ft/s", "fps"], Dimension::Speed, 0.3048),
+    (&["kn", "kt", "knot", "knots"], Dimension::Speed, 1852.0 / 3600.0),
+    // Bytes before bits, so a case-insensitive "mb" means megabytes
+    (&["B", "byte", "bytes"], Dimension::Data, 1.0),
+    (&["KB", "kB", "kilobyte", "kilobytes"], Dimension::Data, 1e3),
+    (&["MB", "megabyte", "megabytes"], Dimension::Data, 1e6),
+    (&["GB", "gigabyte", "gigabytes"], Dimension::Data, 1e9),
+    (&["TB", "terabyte", "terabytes"], Dimension::Data, 1e12),
+    (&["KiB", "kibibyte", "kibibytes"], Dimension::Data, 1024.0),
+    (&["MiB", "mebibyte", "mebibytes"], Dimension::Data, 1048576.0),
+    (&["GiB", "gibibyte", "gibibytes"], Dimension::Data, 1073741824.0),
+    (&["TiB", "tebibyte", "tebibytes"], Dimension::Data, 1099511627776.0),
+    (&["b", "bit", "bits"], Dimension::Data, 0.125),
+    (&["Kb", "kilobit", "kilobits"], Dimension::Data, 125.0),
+    (&["Mb", "megabit", "megabits"], Dimension::Data, 125e3),
+    (&["Gb", "gigabit", "gigabits"], Dimension::Data, 125e6),
+    (&["J", "joule", "joules"], Dimension::Energy, 1.0),
+    (&["kJ", "kilojoule", "kilojoules"], Dimension::Energy, 1e3),
+    (&["cal", "calorie", "calories"], Dimension::Energy, 4.184),
+    (&["kcal", "kilocalorie", "kilocalories"], Dimension::Energy, 4184.0),
+    (&["Wh", "watt hour", "watt hours"], Dimension::Energy, 3600.0),
+    (&["kWh", "kilowatt hour", "kilowatt hours"], Dimension::Energy, 3.6e6),
+    (&["BTU", "btu"], Dimension::Energy, 1055.05585262),
+    (&["eV", "electronvolt", "electronvolts"], Dimension::Energy, 1.602176634e-19),
+    (&["W", "watt", "watts"], Dimension::Power, 1.0),
+    (&["kW", "kilowatt", "kilowatts"], Dimension::Power, 1e3),
+    (&["MW", "megawatt", "megawatts"], Dimension::Power, 1e6),
+    (&["hp", "horsepower"], Dimension::Power, 745.6998715822702),
+    (&["Pa", "pascal", "pascals"], Dimension::Pressure, 1.0),
+    (&["hPa", "hectopascal", "hectopascals", "mbar"], Dimension::Pressure, 100.0),
+    (&["kPa", "kilopascal", "kilopascals"], Dimension::Pressure, 1e3),
+    (&["bar", "bars"], Dimension::Pressure, 1e5),
+    (&["atm", "atmosphere", "atmospheres"], Dimension::Pressure, 101325.0),
+    (&["psi"], Dimension::Pressure, 6894.757293168),
+    (&["mmHg"], Dimension::Pressure, 133.322387415),
+    (&["°C", "C", "degC", "celsius"], Dimension::Temperature, 0.0),
+    (&["°F", "F", "degF", "fahrenheit"], Dimension::Temperature, 0.0),
+    (&["K", "kelvin"], Dimension::Temperature, 0.0),
+];
+
+struct Unit {
+    name: &'static str,
+    dimension: Dimension,
+    factor: f64,
+}
+
+// Exact spelling first, so MB and Mb stay apart; then case-insensitively
+fn unit(name: &str) -> Option<Unit> {
+    let name = name.trim();
+    let find = |matches: &dyn Fn(&str) -> bool| {
+        UNITS.iter().find_map(|(names, dimension, factor)| {
+            names.iter().any(|n| matches(n)).then(|| Unit {
+                name: names[0],
+                dimension: *dimension,
+                factor: *factor,
+            })
+        })
+    };
+    find(&|n| n == name).or_else(|| find(&|n| n.eq_ignore_ascii_case(name)))
+}
+
+fn to_kelvin(value: f64, unit: &str) -> f64 {
+    match unit {
+        "°C" => value + 273.15,
+        "°F" => (value - 32.0) * 5.0 / 9.0 + 273.15,
+        _ => value,
+    }
+}
+
+fn from_kelvin(value: f64, unit: &str) -> f64 {
+    match unit {
+        "°C" => value - 273.15,
+        "°F" => (value - 273.15) * 9.0 / 5.0 + 32.0,
+        _ => value,
+    }
+}
+
+fn convert_unit(value: f64, from: &Unit, to: &Unit) -> Result<f64, String> {
+    if from.dimension != to.dimension {
+        let dimension = |unit: &Unit| format!("{:?}", unit.dimension).to_lowercase();
+        return Err(format!(
+            "Can't convert {} ({}) to {} ({})",
+            from.name,
+            dimension(from),
+            to.name,
+            dimension(to)
+        ));
+    }
+    if from.dimension == Dimension::Temperature {
+        return Ok(from_kelvin(to_kelvin(value, from.name), to.name));
+    }
+    Ok(value * from.factor / to.factor)
+}
+
+// ISO 4217 code for a currency code or symbol
+fn currency(name: &str) -> Option<String> {
+    match name.trim() {
+        "$" => Some("USD".to_string()),
+        "€" => Some("EUR".to_string()),
+        "£" => Some("GBP".to_string()),
+        "¥" => Some("JPY".to_string()),
+        code if code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic()) => Some(code.to_ascii_uppercase()),
+        _ => None,
+    }
+}
+
+struct Rates {
+    fetched: NaiveDate,
+    // Publication date of the rates
+    date: String,
+    // Units of each currency per euro
+    per_euro: HashMap<String, f64>,
+}
+
+static RATES: Mutex<Option<Rates>> = Mutex::new(None);
+
+// Rates of `from` and `to` per euro and the rates' date, refreshing the cache once a day
+async fn rates(from: &str, to: &str, http: &SourceClient) -> Result<(f64, f64, String), String> {
+    let today = Utc::now().date_naive();
+    let lookup = |rates: &Rates| -> Result<(f64, f64, String), String> {
+        let rate = |code: &str| {
+            rates
+                .per_euro
+                .get(code)
+                .copied()
+                .ok_or_else(|| format!("Unknown currency: {}", code))
+        };
+        Ok((rate(from)?, rate(to)?, rates.date.clone()))
+    };
+    if let Some(cached) = RATES.lock().unwrap_or_else(|e| e.into_inner()).as_ref().filter(|r| r.fetched == today) {
+        return lookup(cached);
+    }
+
+    let request = http.get(RATES_API);
+    let json = crate::sources::get_json(request, "Exchange rate service")
+        .await?
+        .ok_or("Exchange rates are unavailable")?;
+    let mut per_euro: HashMap<String, f64> = json
+        .get("rates")
+        .and_then(|r| r.as_object())
+        .into_iter()
+        .flatten()
+        .filter_map(|(code, rate)| Some((code.clone(), rate.as_f64()?)))
+        .collect();
+    per_euro.insert("EUR".to_string(), 1.0);
+    let fetched = Rates {
+        fetched: today,
+        date: json.get("date").and_then(|d| d.as_str()).unwrap_or_default().to_string(),
+        per_euro,
+    };
+    eprintln!("[Calc] Fetched {} exchange rates from {}", fetched.per_euro.len(), fetched.date);
+    let result = lookup(&fetched);
+    *RATES.lock().unwrap_or_else(|e| e.into_inner()) = Some(fetched);
+    result
+}
+
+// Up to 10 significant digits, without trailing zeros or scientific notation for everyday sizes
+pub fn format_number(value: f64) -> String {
+    if value == 0.0 {
+        return "0".to_string();
+    }
+    let magnitude = value.abs().log10().floor() as i32;
+    if !(-6..15).contains(&magnitude) {
+        let text = format!("{:.9e}", value);
+        let (mantissa, exponent) = text.split_once('e').unwrap_or((&text, "0"));
+        return format!("{}e{}", mantissa.trim_end_matches('0').trim_end_matches('.'), exponent);
+    }
+    let decimals = (9 - magnitude).max(0) as usize;
+    let text = format!("{:.*}", decimals, value);
+    if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        text
+    }
+}
+
+// Split "<expression> <unit>" at the unit written after the number
+fn split_quantity(text: &str) -> (&str, &str) {
+    let text = text.trim();
+    // Multi-word units ("fl oz", "square feet") are matched before single words
+    for words in [2, 1] {
+        let mut split = text.len();
+        for _ in 0..words {
+            let before = text[..split].trim_end();
+            // Past the whole whitespace character, which may be several bytes (a no-break space)
+            split = before.char_indices().rev().find(|(_, c)| c.is_whitespace()).map_or(0, |(i, c)| i + c.len_utf8());
+        }
+        let (expression, name) = text.split_at(split);
+        if !expression.trim().is_empty() && (unit(name).is_some() || currency(name).is_some()) {
+            return (expression, name.trim());
+        }
+    }
+    // No space: 5km, 20°C, $100
+    if let Some(rest) = text.strip_prefix(['$', '€', '£', '¥']) {
+        return (rest, &text[..text.len() - rest.len()]);
+    }
+    let unit_start = text
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| !(c.is_ascii_digit() || *c == ')' || *c == '.' || c.is_whitespace()))
+        .last()
+        .map_or(text.len(), |(i, _)| i);
+    text.split_at(unit_start)
+}
+
+// Evaluate an expression, or a conversion written "<expression> <unit> to|in <unit>"
+pub async fn calculate(input: &str, http: &SourceClient) -> Result<Calculation, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("Nothing to calculate".to_string());
+    }
+    let conversion = [" to ", " in ", " as "]
+        .iter()
+        .filter_map(|keyword| input.rfind(keyword).map(|i| (i, keyword.len())))
+        .max_by_key(|(i, _)| *i);
+    let Some((at, keyword_len)) = conversion else {
+        let value = evaluate(input)?;
+        return Ok(Calculation {
+            value,
+            unit: None,
+            text: format_number(value),
+            rates_date: None,
+        });
+    };
+    let (quantity, target) = (&input[..at], input[at + keyword_len..].trim());
+    let (expression, source) = split_quantity(quantity);
+    let amount = evaluate(expression)?;
+    convert(amount, source, target, http).await
+}
+
+// Convert between units of one dimension, or between currencies
+pub async fn convert(value: f64, from: &str, to: &str, http: &SourceClient) -> Result<Calculation, String> {
+    if let (Some(from), Some(to)) = (unit(from), unit(to)) {
+        let converted = convert_unit(value, &from, &to)?;
+        return Ok(Calculation {
+            value: converted,
+            unit: Some(to.name.to_string()),
+            text: format!("{} {}", format_number(converted), to.name),
+            rates_date: None,
+        });
+    }
+    match (currency(from), currency(to)) {
+        (Some(from), Some(to)) => {
+            let (from_rate, to_rate, date) = rates(&from, &to, http).await?;
+            let converted = value / from_rate * to_rate;
+            Ok(Calculation {
+                value: converted,
+                text: format!("{:.2} {}", converted, to),
+                unit: Some(to),
+                rates_date: Some(date),
+            })
+        }
+        _ => Err(format!("Unknown unit: {}", if unit(from).is_none() { from } else { to })),
+    }
+}
+
+// Result of an expression or a "<quantity> <unit> to <unit>" conversion
+#[tauri::command]
+pub async fn calculate_expression(app: tauri::AppHandle, expression: String) -> Result<Calculation, String> {
+    calculate(&expression, &SourceClient::for_app(&app)?).await
+}
+
+#[tauri::command]
+pub async fn convert_units(app: tauri::AppHandle, value: f64, from: String, to: String) -> Result<Calculation, String> {
+    convert(value, &from, &to, &SourceClient::for_app(&app)?).await
+}
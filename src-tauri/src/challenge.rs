@@ -0,0 +1,73 @@
+// Anti-bot challenge pages. Sites behind Cloudflare, Akamai and similar services answer suspected
+// bots with an interstitial ("Just a moment...") instead of the page; scraped as is, its text would
+// pass for the page's content. They are recognised by their titles and the scripts and markup the
+// services use.
+use std::fmt;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Challenge {
+    Cloudflare,
+    Akamai,
+    Imperva,
+    DataDome,
+    PerimeterX,
+}
+
+impl fmt::Display for Challenge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Challenge::Cloudflare => "Cloudflare",
+            Challenge::Akamai => "Akamai",
+            Challenge::Imperva => "Imperva",
+            Challenge::DataDome => "DataDome",
+            Challenge::PerimeterX => "PerimeterX",
+        };
+        f.write_str(name)
+    }
+}
+
+// Titles only a challenge page has, lowercased
+const CLOUDFLARE_TITLES: &[&str] =
+    &["just a moment...", "attention required! | cloudflare", "please wait... | cloudflare"];
+// Markup of the challenge itself; ordinary pages on these networks don't carry it (Cloudflare
+// does add a challenge-platform script to many of them, but not the orchestrating one)
+const MARKERS: &[(&str, Challenge)] = &[
+    ("/cdn-cgi/challenge-platform/h/b/orchestrate/", Challenge::Cloudflare),
+    ("cf-browser-verification", Challenge::Cloudflare),
+    ("cf_chl_opt", Challenge::Cloudflare),
+    ("cf-challenge-running", Challenge::Cloudflare),
+    ("/_sec/cp_challenge/", Challenge::Akamai),
+    ("sec-if-cpt-container", Challenge::Akamai),
+    ("_incapsula_resource", Challenge::Imperva),
+    ("incapsula incident id", Challenge::Imperva),
+    ("captcha-delivery.com", Challenge::DataDome),
+    ("px-captcha", Challenge::PerimeterX),
+    ("/px/captcha/", Challenge::PerimeterX),
+];
+
+fn title(html: &str) -> Option<String> {
+    let lower = html.to_lowercase();
+    let start = lower.find("<title")?;
+    let start = start + lower[start..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    Some(lower[start..end].split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+// The service behind the challenge when `html` is a challenge page rather than content. `status`
+// is the HTTP status where known: Akamai's denial pages are only told apart by theirs.
+pub fn detect(html: &str, status: Option<u16>) -> Option<Challenge> {
+    // Challenge pages are small; a long page that merely loads a challenge script is content
+    if html.len() > 200_000 {
+        return None;
+    }
+    let lower = html.to_lowercase();
+    let title = title(html).unwrap_or_default();
+    if CLOUDFLARE_TITLES.contains(&title.as_str()) {
+        return Some(Challenge::Cloudflare);
+    }
+    if let Some((_, challenge)) = MARKERS.iter().find(|(marker, _)| lower.contains(marker)) {
+        return Some(*challenge);
+    }
+    let denied = title == "access denied" && lower.contains("errors.edgesuite.net");
+    (denied && status.is_none_or(|status| status == 403)).then_some(Challenge::Akamai)
+}
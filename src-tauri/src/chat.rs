@@ -0,0 +1,601 @@
+// Backend chat pipeline: prepares the prompt in Rust before calling the provider
+use rusqlite::{params, OptionalExtension};
+use tauri::{AppHandle, State};
+
+use crate::citations::{self, Citation, RetrievalOptions};
+use crate::context::{self, ContextOptions, TruncationPolicy};
+use crate::db::{self, Database};
+use crate::conversations;
+use crate::llm::{self, ChatMessage, GenerationOptions, ProviderConfig, ProviderType};
+use crate::memory::{self, MemoryOptions};
+use crate::persona;
+use crate::profiles::ProfileManager;
+use crate::semantic_cache::{self, CachedAnswer, Lookup, SemanticCacheOptions};
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatRequest {
+    provider: ProviderConfig,
+    messages: Vec<ChatMessage>,
+    #[serde(default)]
+    options: GenerationOptions,
+    context: Option<ContextOptions>,
+    memory: Option<MemoryOptions>,
+    // Document collections to retrieve excerpts from; the answer cites them
+    retrieval: Option<RetrievalOptions>,
+    // Answer from earlier responses to questions meaning the same, when there is one
+    semantic_cache: Option<SemanticCacheOptions>,
+    // Persona to use instead of the active one
+    persona_id: Option<String>,
+    // Set when the request regenerates an earlier response
+    regeneration: Option<RegenerationContext>,
+}
+
+#[derive(serde::Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RegenerationContext {
+    // Generation being replaced; its sampling parameters are reused and the new response is tracked as an alternate
+    generation_id: Option<String>,
+    // Text of the response being replaced (read from the generation record when omitted)
+    previous_response: Option<String>,
+    // Optional user instruction such as "shorter" or "more formal"
+    instruction: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Alternate {
+    generation_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent_generation_id: Option<String>,
+    seed: i64,
+    response: String,
+    created_at: String,
+}
+
+#[derive(serde::Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseMetadata {
+    generation_id: String,
+    seed: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent_generation_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    persona_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    regeneration_instruction: Option<String>,
+    policy: TruncationPolicy,
+    trimmed_messages: usize,
+    summarized_messages: usize,
+    recalled_memories: usize,
+    // Excerpts given to the model, flagged when the answer cites them
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    citations: Vec<Citation>,
+    // Rewordings of the question that were searched besides it
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    retrieval_queries: Vec<String>,
+    // Answered from the semantic cache, without calling the provider
+    cached: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_similarity: Option<f32>,
+    estimated_prompt_tokens: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prompt_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    completion_tokens: Option<u64>,
+    latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ttft_ms: Option<u64>,
+}
+
+// How a stored message was produced, from its generation record when it has one
+#[derive(serde::Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageMetadata {
+    message_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    generation_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent_generation_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    provider: Option<ProviderType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    base_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prompt_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    completion_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ttft_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<i64>,
+    // Sampling parameters (temperature, top-p, max tokens, grammar/schema)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<GenerationOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    generated_at: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatResponse {
+    message: ChatMessage,
+    metadata: ResponseMetadata,
+}
+
+#[tauri::command]
+pub async fn chat_completion(
+    app: AppHandle,
+    db: State<'_, Database>,
+    profiles: State<'_, ProfileManager>,
+    request: ChatRequest,
+) -> Result<ChatResponse, String> {
+    let mut metadata = ResponseMetadata::default();
+    let mut messages = request.messages;
+    let mut provider = request.provider;
+    let mut options = request.options;
+    let mut memory_options = request.memory;
+    profiles.apply_credentials(&mut provider);
+    if let Some(embedding) = memory_options.as_mut().and_then(|m| m.embedding.as_mut()) {
+        profiles.apply_credentials(embedding);
+    }
+
+    if let Some(persona) = persona::resolve(&db, request.persona_id.as_deref())? {
+        persona.apply(&mut provider, &mut messages);
+        metadata.persona_id = Some(persona.id);
+    }
+
+    // A regeneration asks for a different answer, so it never comes from the cache
+    let mut cache_lookup = None;
+    if let Some(mut cache_options) = request.semantic_cache.filter(|_| request.regeneration.is_none()) {
+        profiles.apply_credentials(&mut cache_options.embedding);
+        match semantic_cache::lookup(&app, &db, &cache_options, &provider, &messages, &options).await {
+            Ok(Some(Lookup { hit: Some(hit), .. })) => return cached_response(&db, hit, metadata),
+            Ok(lookup) => cache_lookup = lookup,
+            // Like memory recall, a failed lookup just means the provider answers
+            Err(err) => eprintln!("[Chat] Semantic cache lookup failed: {}", err),
+        }
+    }
+
+    let query = messages
+        .iter()
+        .rev()
+        .find(|m| m.role == "user")
+        .map(|m| m.content.clone())
+        .unwrap_or_default();
+    if let Some(memory_options) = &memory_options {
+        // Recall failures (e.g. embedding model not pulled) shouldn't block the chat
+        match memory::memory_prompt(&db, &query, memory_options).await {
+            Ok(Some((prompt, count))) => {
+                let pinned = messages.iter().take_while(|m| m.role == "system").count();
+                messages.insert(pinned, ChatMessage::new("system", prompt));
+                metadata.recalled_memories = count;
+            }
+            Ok(None) => {}
+            Err(err) => eprintln!("[Chat] Memory recall failed: {}", err),
+        }
+    }
+
+    if let Some(mut retrieval) = request.retrieval {
+        profiles.apply_credentials(&mut retrieval.embedding);
+        // Like memory recall, a failed search answers without excerpts instead of failing the chat
+        match citations::retrieval_prompt(&app, &db, &query, &retrieval, &provider).await {
+            Ok(Some(found)) => {
+                let position = messages.iter().rposition(|m| m.role == "user").unwrap_or(messages.len());
+                messages.insert(position, ChatMessage::new("system", found.prompt));
+                metadata.citations = found.citations;
+                metadata.retrieval_queries = found.queries.into_iter().skip(1).collect();
+            }
+            Ok(None) => {}
+            Err(err) => eprintln!("[Chat] Retrieval failed: {}", err),
+        }
+    }
+
+    if let Some(context_options) = &request.context {
+        let outcome = context::apply_policy(
+            &provider,
+            messages,
+            context_options,
+            options.max_tokens,
+        )
+        .await?;
+        messages = outcome.messages;
+        metadata.policy = context_options.policy;
+        metadata.trimmed_messages = outcome.trimmed_count;
+        metadata.summarized_messages = outcome.summarized_count;
+    }
+
+    if let Some(regeneration) = &request.regeneration {
+        prepare_regeneration(&db, regeneration, &mut messages, &mut options, &mut metadata)?;
+    }
+
+    let response = generate(&db, provider, messages, options, metadata).await?;
+    if let Some(lookup) = &cache_lookup {
+        if let Err(err) = semantic_cache::store(&db, lookup, &response.message.content, &response.metadata.generation_id) {
+            eprintln!("[Chat] Failed to cache the answer: {}", err);
+        }
+    }
+    Ok(response)
+}
+
+// The cached answer, linked to the generation that first produced it along with its citations
+fn cached_response(db: &Database, hit: CachedAnswer, mut metadata: ResponseMetadata) -> Result<ChatResponse, String> {
+    if let Some(generation_id) = hit.generation_id {
+        metadata.citations = db.with_conn(|conn| citations::load(conn, &generation_id))?;
+        metadata.generation_id = generation_id;
+    }
+    metadata.cached = true;
+    metadata.cache_similarity = Some(hit.similarity);
+    Ok(ChatResponse {
+        message: ChatMessage::new("assistant", hit.response),
+        metadata,
+    })
+}
+
+struct GenerationRecord {
+    options: GenerationOptions,
+    response: String,
+}
+
+fn load_generation(db: &Database, id: &str) -> Result<GenerationRecord, String> {
+    let record = db.with_conn(|conn| {
+        conn.query_row(
+            "SELECT options, response FROM generations WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+        )
+        .optional()
+    })?;
+    let (options, response) = record.ok_or_else(|| format!("Generation {} not found", id))?;
+    Ok(GenerationRecord {
+        options: serde_json::from_str(&options).map_err(|e| format!("Corrupt generation record: {}", e))?,
+        response,
+    })
+}
+
+// Steer the model away from the response being replaced.
+// The original sampling parameters are kept but a fresh seed is drawn so the output actually changes.
+fn prepare_regeneration(
+    db: &Database,
+    regeneration: &RegenerationContext,
+    messages: &mut Vec<ChatMessage>,
+    options: &mut GenerationOptions,
+    metadata: &mut ResponseMetadata,
+) -> Result<(), String> {
+    let record = match &regeneration.generation_id {
+        Some(id) => Some(load_generation(db, id)?),
+        None => None,
+    };
+    let previous = regeneration
+        .previous_response
+        .clone()
+        .or_else(|| record.as_ref().map(|r| r.response.clone()))
+        .filter(|text| !text.trim().is_empty());
+
+    if let Some(record) = record {
+        *options = GenerationOptions {
+            seed: None,
+            ..record.options
+        };
+    } else {
+        options.seed = None;
+    }
+
+    let instruction = regeneration
+        .instruction
+        .as_deref()
+        .map(str::trim)
+        .filter(|i| !i.is_empty());
+    let mut note = String::from("The user asked for a new answer to their last message.");
+    if let Some(previous) = &previous {
+        note.push_str(&format!(
+            " Your previous answer was:\n<previous_answer>\n{}\n</previous_answer>\n\
+             Write a new answer that differs meaningfully from it instead of repeating it.",
+            previous
+        ));
+    }
+    if let Some(instruction) = instruction {
+        note.push_str(&format!(" Follow this instruction for the new answer: {}", instruction));
+    }
+
+    // Place the note just before the last user message so it applies to this turn only
+    let position = messages.iter().rposition(|m| m.role == "user").unwrap_or(messages.len());
+    messages.insert(position, ChatMessage::new("system", note));
+
+    eprintln!(
+        "[Chat] Regenerating {} (instruction: {})",
+        regeneration.generation_id.as_deref().unwrap_or("unrecorded response"),
+        instruction.unwrap_or("none")
+    );
+    metadata.parent_generation_id = regeneration.generation_id.clone();
+    metadata.regeneration_instruction = instruction.map(str::to_string);
+    Ok(())
+}
+
+// Call the provider with a pinned seed and record the generation so it can be reproduced
+async fn generate(
+    db: &Database,
+    provider: ProviderConfig,
+    messages: Vec<ChatMessage>,
+    mut options: GenerationOptions,
+    mut metadata: ResponseMetadata,
+) -> Result<ChatResponse, String> {
+    let seed = *options.seed.get_or_insert_with(llm::random_seed);
+    metadata.seed = seed;
+    metadata.estimated_prompt_tokens = context::total_tokens(&messages);
+
+    let completion = llm::chat(&provider, &messages, &[], &options).await?;
+    metadata.prompt_tokens = completion.prompt_tokens;
+    metadata.completion_tokens = completion.completion_tokens;
+    metadata.latency_ms = completion.latency_ms;
+    metadata.ttft_ms = completion.ttft_ms;
+    citations::mark_cited(&mut metadata.citations, &completion.message.content);
+    let stored_citations = if metadata.citations.is_empty() {
+        None
+    } else {
+        serde_json::to_string(&metadata.citations).ok()
+    };
+
+    // Credentials are never persisted; regeneration takes them from the caller again
+    let stored_provider = ProviderConfig {
+        api_key: None,
+        ..provider
+    };
+    let id = db::new_id("gen");
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO generations (id, parent_id, provider, prompt, options, seed, response, created_at,
+                                      prompt_tokens, completion_tokens, latency_ms, ttft_ms, citations)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            params![
+                id,
+                metadata.parent_generation_id,
+                serde_json::to_string(&stored_provider).unwrap_or_default(),
+                serde_json::to_string(&messages).unwrap_or_default(),
+                serde_json::to_string(&options).unwrap_or_default(),
+                seed,
+                completion.message.content,
+                chrono::Utc::now().to_rfc3339(),
+                completion.prompt_tokens.map(|n| n as i64),
+                completion.completion_tokens.map(|n| n as i64),
+                completion.latency_ms as i64,
+                completion.ttft_ms.map(|n| n as i64),
+                stored_citations
+            ],
+        )
+    })?;
+    metadata.generation_id = id;
+
+    Ok(ChatResponse {
+        message: completion.message,
+        metadata,
+    })
+}
+
+// Re-run a recorded generation with the same prompt and options.
+// Pass the recorded seed to reproduce the response, another seed (or none) to vary it.
+#[tauri::command]
+pub async fn regenerate_with_seed(
+    db: State<'_, Database>,
+    profiles: State<'_, ProfileManager>,
+    generation_id: String,
+    seed: Option<i64>,
+    api_key: Option<String>,
+) -> Result<ChatResponse, String> {
+    let record = db.with_conn(|conn| {
+        conn.query_row(
+            "SELECT provider, prompt, options FROM generations WHERE id = ?1",
+            params![generation_id],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)),
+        )
+        .optional()
+    })?;
+    let (provider, prompt, options) = record.ok_or_else(|| format!("Generation {} not found", generation_id))?;
+
+    let mut provider: ProviderConfig =
+        serde_json::from_str(&provider).map_err(|e| format!("Corrupt generation record: {}", e))?;
+    provider.api_key = api_key;
+    profiles.apply_credentials(&mut provider);
+    let messages: Vec<ChatMessage> =
+        serde_json::from_str(&prompt).map_err(|e| format!("Corrupt generation record: {}", e))?;
+    let mut options: GenerationOptions =
+        serde_json::from_str(&options).map_err(|e| format!("Corrupt generation record: {}", e))?;
+    options.seed = seed;
+
+    // The recorded prompt already holds the retrieved excerpts, so their citations carry over
+    let citations = db.with_conn(|conn| citations::load(conn, &generation_id))?;
+
+    eprintln!("[Chat] Regenerating {} with seed {:?}", generation_id, seed);
+    let metadata = ResponseMetadata {
+        parent_generation_id: Some(generation_id),
+        citations,
+        ..Default::default()
+    };
+    generate(&db, provider, messages, options, metadata).await
+}
+
+// All responses generated for the same turn: the original generation and every regeneration of it
+#[tauri::command]
+pub fn list_alternates(db: State<'_, Database>, generation_id: String) -> Result<Vec<Alternate>, String> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "WITH RECURSIVE
+                ancestors(id, parent_id) AS (
+                    SELECT id, parent_id FROM generations WHERE id = ?1
+                    UNION ALL
+                    SELECT g.id, g.parent_id FROM generations g JOIN ancestors a ON g.id = a.parent_id
+                ),
+                tree(id) AS (
+                    SELECT id FROM ancestors WHERE parent_id IS NULL
+                    UNION ALL
+                    SELECT g.id FROM generations g JOIN tree t ON g.parent_id = t.id
+                )
+             SELECT id, parent_id, seed, response, created_at FROM generations
+             WHERE id IN (SELECT id FROM tree) ORDER BY created_at",
+        )?;
+        let rows = stmt.query_map(params![generation_id], |row| {
+            Ok(Alternate {
+                generation_id: row.get(0)?,
+                parent_generation_id: row.get(1)?,
+                seed: row.get(2)?,
+                response: row.get(3)?,
+                created_at: row.get(4)?,
+            })
+        })?;
+        rows.collect()
+    })
+}
+
+// Generation details of a stored message. The message links to its generation through the
+// `generationId` the frontend copies from the chat response into the message metadata; messages
+// without one (imported, typed by the user) only report what their metadata says.
+#[tauri::command]
+pub fn get_message_metadata(db: State<'_, Database>, id: String) -> Result<MessageMetadata, String> {
+    let message = db
+        .with_conn(|conn| conversations::get_message(conn, &id))?
+        .ok_or_else(|| format!("Message {} not found", id))?;
+    let extra = message.metadata.unwrap_or_default();
+    let mut result = MessageMetadata {
+        message_id: message.id,
+        generation_id: extra.get("generationId").and_then(|v| v.as_str()).map(str::to_string),
+        model: extra.get("model").and_then(|v| v.as_str()).map(str::to_string),
+        ..Default::default()
+    };
+    let Some(generation_id) = result.generation_id.clone() else {
+        return Ok(result);
+    };
+
+    let record = db.with_conn(|conn| {
+        conn.query_row(
+            "SELECT parent_id, provider, options, seed, created_at, prompt_tokens, completion_tokens, latency_ms, ttft_ms
+             FROM generations WHERE id = ?1",
+            params![generation_id],
+            |row| {
+                Ok((
+                    row.get::<_, Option<String>>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, String>(4)?,
+                    [row.get::<_, Option<i64>>(5)?, row.get(6)?, row.get(7)?, row.get(8)?],
+                ))
+            },
+        )
+        .optional()
+    })?;
+    // The generation may have been recorded on another device or removed
+    let Some((parent_id, provider, options, seed, created_at, counters)) = record else {
+        return Ok(result);
+    };
+    let provider: Option<ProviderConfig> = serde_json::from_str(&provider).ok();
+    let [prompt_tokens, completion_tokens, latency_ms, ttft_ms] = counters.map(|n| n.map(|n| n as u64));
+    result.parent_generation_id = parent_id;
+    if let Some(provider) = provider {
+        result.model = Some(provider.model);
+        result.provider = Some(provider.provider);
+        result.base_url = Some(provider.base_url);
+    }
+    result.options = serde_json::from_str(&options).ok();
+    result.seed = Some(seed);
+    result.generated_at = Some(created_at);
+    result.prompt_tokens = prompt_tokens;
+    result.completion_tokens = completion_tokens;
+    result.latency_ms = latency_ms;
+    result.ttft_ms = ttft_ms;
+    Ok(result)
+}
+
+const TITLE_TIMEOUT_MS: u64 = 2500;
+const TITLE_MAX_WORDS: usize = 6;
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TitleResult {
+    title: String,
+    // "model" when the LLM produced the title, "heuristic" for the first-words fallback
+    source: String,
+}
+
+// Fallback title: the first few words of the message
+fn heuristic_title(message: &str) -> String {
+    let words: Vec<&str> = message.split_whitespace().take(TITLE_MAX_WORDS).collect();
+    if words.is_empty() {
+        return "New Chat".to_string();
+    }
+    let mut title = words.join(" ");
+    if message.split_whitespace().count() > TITLE_MAX_WORDS {
+        title.push_str("...");
+    }
+    title
+}
+
+// Strip quotes, "Title:" prefixes and trailing punctuation from a model-generated title
+fn clean_title(raw: &str) -> Option<String> {
+    let line = raw.lines().map(|l| l.trim()).find(|l| !l.is_empty())?;
+    let line = line
+        .strip_prefix("Title:")
+        .or_else(|| line.strip_prefix("title:"))
+        .unwrap_or(line);
+    let title = line
+        .trim()
+        .trim_matches(|c: char| c == '"' || c == '\'' || c == '*' || c == '#')
+        .trim_end_matches(['.', '!', ':'])
+        .trim();
+    if title.is_empty() || title.chars().count() > 80 {
+        return None;
+    }
+    Some(title.to_string())
+}
+
+async fn model_title(provider: &ProviderConfig, message: &str) -> Result<String, String> {
+    let excerpt: String = message.chars().take(1000).collect();
+    let prompt = vec![
+        ChatMessage::new(
+            "system",
+            "Generate a short title (3 to 6 words) for a chat that starts with the user's message. \
+             Reply with the title only, no quotes or punctuation.",
+        ),
+        ChatMessage::new("user", excerpt),
+    ];
+    let options = GenerationOptions {
+        temperature: Some(0.3),
+        max_tokens: Some(24),
+        ..Default::default()
+    };
+
+    let completion = tokio::time::timeout(
+        std::time::Duration::from_millis(TITLE_TIMEOUT_MS),
+        llm::chat(provider, &prompt, &[], &options),
+    )
+    .await
+    .map_err(|_| "Title generation timed out".to_string())??;
+
+    clean_title(&completion.message.content).ok_or_else(|| "Model returned an unusable title".to_string())
+}
+
+#[tauri::command]
+pub async fn generate_chat_title(
+    profiles: State<'_, ProfileManager>,
+    mut provider: ProviderConfig,
+    message: String,
+) -> Result<TitleResult, String> {
+    // Async commands that borrow state must return a Result; title generation itself never fails
+    profiles.apply_credentials(&mut provider);
+    Ok(match model_title(&provider, &message).await {
+        Ok(title) => TitleResult {
+            title,
+            source: "model".to_string(),
+        },
+        Err(err) => {
+            eprintln!("[Chat] Falling back to heuristic title: {}", err);
+            TitleResult {
+                title: heuristic_title(&message),
+                source: "heuristic".to_string(),
+            }
+        }
+    })
+}
@@ -0,0 +1,304 @@
+// Retrieval-augmented answers with citations: the chunks retrieved for a chat turn are numbered
+// in the prompt, recorded with the generation, and flagged when the answer cites them as [n].
+use std::cmp::Ordering;
+use std::time::Duration;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, State};
+
+use crate::conversations;
+use crate::db::Database;
+use crate::llm::{self, ChatMessage, GenerationOptions, ProviderConfig};
+use crate::vector::{self, HybridMatch};
+
+const DEFAULT_TOP_K: usize = 5;
+// Characters of chunk text kept with a citation for previews
+const EXCERPT_CHARS: usize = 280;
+const MAX_QUERY_VARIANTS: usize = 3;
+const EXPANSION_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RetrievalOptions {
+    pub collections: Vec<String>,
+    pub top_k: Option<usize>,
+    // Must be the model the collections were built with
+    pub embedding: ProviderConfig,
+    pub filter: Option<Map<String, Value>>,
+    // Rewordings of the question the chat model is asked for (up to 3), each searched as well and
+    // the results fused; helps recall for vague questions at the cost of one extra request
+    #[serde(default)]
+    pub query_variants: Option<usize>,
+}
+
+pub struct Retrieval {
+    // System message with the numbered excerpts
+    pub prompt: String,
+    pub citations: Vec<Citation>,
+    // Queries searched: the question, then any rewordings
+    pub queries: Vec<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Citation {
+    // Number the excerpt had in the prompt, from 1
+    pub index: usize,
+    pub collection: String,
+    pub chunk_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    pub score: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rerank_score: Option<f32>,
+    pub excerpt: String,
+    // Whether the answer referred to this excerpt
+    #[serde(default)]
+    pub cited: bool,
+}
+
+fn metadata_str(metadata: Option<&Value>, key: &str) -> Option<String> {
+    metadata?.get(key)?.as_str().map(str::to_string)
+}
+
+fn citation(index: usize, collection: &str, found: &HybridMatch) -> Citation {
+    let metadata = found.item.metadata.as_ref();
+    let content = found.item.content.as_deref().unwrap_or("");
+    let mut excerpt: String = content.chars().take(EXCERPT_CHARS).collect();
+    if excerpt.len() < content.len() {
+        excerpt.push_str("...");
+    }
+    Citation {
+        index,
+        collection: collection.to_string(),
+        chunk_id: found.item.id.clone(),
+        source: metadata_str(metadata, "source"),
+        file_name: metadata_str(metadata, "fileName"),
+        title: metadata_str(metadata, "title"),
+        section: metadata_str(metadata, "section"),
+        page: metadata.and_then(|m| m.get("page")).and_then(Value::as_u64).map(|p| p as u32),
+        score: found.item.score,
+        rerank_score: found.rerank_score,
+        excerpt,
+        cited: false,
+    }
+}
+
+// How an excerpt is introduced in the prompt, e.g. "report.pdf, page 3, Results"
+fn label(citation: &Citation) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    if let Some(name) = citation.title.as_ref().or(citation.file_name.as_ref()).or(citation.source.as_ref()) {
+        parts.push(name.clone());
+    }
+    if let Some(page) = citation.page {
+        parts.push(format!("page {}", page));
+    }
+    if let Some(section) = citation.section.as_ref().filter(|s| Some(*s) != citation.title.as_ref()) {
+        parts.push(section.clone());
+    }
+    if parts.is_empty() {
+        parts.push(citation.chunk_id.clone());
+    }
+    parts.join(", ")
+}
+
+// A line of the model's list of rewordings, without numbering, bullets or quotes
+fn clean_variant(line: &str) -> Option<String> {
+    let mut line = line.trim().trim_start_matches(['-', '*', '•']).trim_start();
+    // "1." or "2)" numbering, but not a leading number such as a year
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let rest = &line[digits..];
+    if digits > 0 && rest.starts_with(['.', ')']) && rest[1..].starts_with(char::is_whitespace) {
+        line = &rest[1..];
+    }
+    let line = line.trim().trim_matches(['"', '\'', '`']).trim();
+    (!line.is_empty() && line.len() <= 300).then(|| line.to_string())
+}
+
+// Ask the chat model for other ways to phrase the question as a search
+async fn query_variants(provider: &ProviderConfig, query: &str, count: usize) -> Result<Vec<String>, String> {
+    let question: String = query.chars().take(2000).collect();
+    let prompt = vec![
+        ChatMessage::new(
+            "system",
+            format!(
+                "You help search a document collection. Rewrite the user's question as {} different search \
+                 queries that could find the relevant passages: use synonyms, spell out what is implied, or \
+                 split it into the parts it asks about. Reply with one query per line and nothing else.",
+                count
+            ),
+        ),
+        ChatMessage::new("user", question),
+    ];
+    let options = GenerationOptions {
+        temperature: Some(0.4),
+        max_tokens: Some(200),
+        ..Default::default()
+    };
+    let completion = tokio::time::timeout(EXPANSION_TIMEOUT, llm::chat(provider, &prompt, &[], &options))
+        .await
+        .map_err(|_| "Query expansion timed out".to_string())??;
+    let mut variants: Vec<String> = Vec::new();
+    for variant in completion.message.content.lines().filter_map(clean_variant) {
+        let duplicate = variant.eq_ignore_ascii_case(query.trim()) || variants.iter().any(|v| v.eq_ignore_ascii_case(&variant));
+        if !duplicate {
+            variants.push(variant);
+        }
+    }
+    variants.truncate(count);
+    Ok(variants)
+}
+
+// Merge rankings with reciprocal-rank fusion, so chunks found by several queries come first
+fn fuse(rankings: Vec<(String, Vec<HybridMatch>)>) -> Vec<(String, HybridMatch)> {
+    let mut fused: Vec<(String, HybridMatch, f32)> = Vec::new();
+    for (collection, ranking) in rankings {
+        for (index, found) in ranking.into_iter().enumerate() {
+            let contribution = 1.0 / (vector::RRF_K + (index + 1) as f32);
+            match fused.iter_mut().find(|(c, m, _)| *c == collection && m.item.id == found.item.id) {
+                Some(entry) => entry.2 += contribution,
+                None => fused.push((collection.clone(), found, contribution)),
+            }
+        }
+    }
+    fused.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(Ordering::Equal));
+    fused.into_iter().map(|(collection, found, _)| (collection, found)).collect()
+}
+
+// System message with the excerpts most relevant to `query`, and their citations. Results from
+// several collections are merged by rerank score when reranked, otherwise by fused score; with
+// query variants, the rankings of all queries are fused by rank. `provider` writes the variants.
+pub async fn retrieval_prompt(
+    app: &AppHandle,
+    db: &Database,
+    query: &str,
+    options: &RetrievalOptions,
+    provider: &ProviderConfig,
+) -> Result<Option<Retrieval>, String> {
+    if query.trim().is_empty() || options.collections.is_empty() {
+        return Ok(None);
+    }
+    let top_k = options.top_k.unwrap_or(DEFAULT_TOP_K);
+    let mut queries = vec![query.to_string()];
+    let wanted = options.query_variants.unwrap_or(0).min(MAX_QUERY_VARIANTS);
+    if wanted > 0 {
+        // Without rewordings the question alone is searched
+        match query_variants(provider, query, wanted).await {
+            Ok(variants) => queries.extend(variants),
+            Err(err) => eprintln!("[Citations] Query expansion failed: {}", err),
+        }
+    }
+
+    let mut rankings: Vec<(String, Vec<HybridMatch>)> = Vec::new();
+    for text in &queries {
+        for collection in &options.collections {
+            let matches = vector::retrieve(app, db, collection, text, top_k, &options.embedding, options.filter.clone()).await?;
+            rankings.push((collection.clone(), matches));
+        }
+    }
+    let mut found: Vec<(String, HybridMatch)> = if queries.len() > 1 {
+        fuse(rankings)
+    } else {
+        let mut found: Vec<(String, HybridMatch)> = rankings
+            .into_iter()
+            .flat_map(|(collection, matches)| matches.into_iter().map(move |m| (collection.clone(), m)))
+            .collect();
+        found.sort_by(|(_, a), (_, b)| {
+            let key = |m: &HybridMatch| m.rerank_score.unwrap_or(m.item.score);
+            key(b).partial_cmp(&key(a)).unwrap_or(Ordering::Equal)
+        });
+        found
+    };
+    found.truncate(top_k);
+    if found.is_empty() {
+        return Ok(None);
+    }
+
+    let mut prompt = String::from(
+        "Answer using the numbered excerpts from the user's documents below when they are relevant. \
+         Cite the excerpts you rely on by number in square brackets, like [1] or [2][3]. If the excerpts \
+         don't contain the answer, say so instead of guessing.",
+    );
+    let mut citations = Vec::with_capacity(found.len());
+    for (i, (collection, found)) in found.iter().enumerate() {
+        let citation = citation(i + 1, collection, found);
+        prompt.push_str(&format!(
+            "\n\n[{}] {}\n{}",
+            citation.index,
+            label(&citation),
+            found.item.content.as_deref().unwrap_or("").trim()
+        ));
+        citations.push(citation);
+    }
+    eprintln!("[Citations] Injecting {} excerpts from {} queries", citations.len(), queries.len());
+    Ok(Some(Retrieval {
+        prompt,
+        citations,
+        queries,
+    }))
+}
+
+// Numbers the text cites in square brackets: [1], [2][3] and [1, 4] all count
+fn cited_numbers(text: &str) -> Vec<usize> {
+    let mut numbers = Vec::new();
+    for (start, _) in text.match_indices('[') {
+        let Some(length) = text[start + 1..].find(']') else { break };
+        let inner = &text[start + 1..start + 1 + length];
+        if !inner.is_empty() && inner.chars().all(|c| c.is_ascii_digit() || c == ',' || c == ' ') {
+            numbers.extend(inner.split(',').filter_map(|n| n.trim().parse::<usize>().ok()));
+        }
+    }
+    numbers
+}
+
+// Flag the citations the response refers to
+pub fn mark_cited(citations: &mut [Citation], response: &str) {
+    let numbers = cited_numbers(response);
+    for citation in citations.iter_mut() {
+        citation.cited = numbers.contains(&citation.index);
+    }
+}
+
+// Citations recorded with a generation
+pub fn load(conn: &Connection, generation_id: &str) -> rusqlite::Result<Vec<Citation>> {
+    let stored: Option<Option<String>> = conn
+        .query_row(
+            "SELECT citations FROM generations WHERE id = ?1",
+            params![generation_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(stored
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default())
+}
+
+// Sources behind a stored message: the citations of its generation (linked through the
+// `generationId` in the message metadata), or a `citations` list kept in the metadata itself
+#[tauri::command]
+pub fn get_message_citations(db: State<'_, Database>, id: String) -> Result<Vec<Citation>, String> {
+    let message = db
+        .with_conn(|conn| conversations::get_message(conn, &id))?
+        .ok_or_else(|| format!("Message {} not found", id))?;
+    let metadata = message.metadata.unwrap_or_default();
+    if let Some(generation_id) = metadata.get("generationId").and_then(|v| v.as_str()) {
+        let citations = db.with_conn(|conn| load(conn, generation_id))?;
+        if !citations.is_empty() {
+            return Ok(citations);
+        }
+    }
+    Ok(metadata
+        .get("citations")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default())
+}
@@ -0,0 +1,277 @@
+// Context compression: token estimation and rolling conversation summaries
+use crate::llm::{self, ChatMessage, GenerationOptions, ProviderConfig};
+
+const SUMMARY_PREFIX: &str = "Summary of the earlier conversation:\n";
+// Per-message overhead for role markers and separators in chat templates
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+
+// Rough token estimate (~4 characters per token for English text)
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+pub fn message_tokens(message: &ChatMessage) -> usize {
+    let tool_tokens: usize = message
+        .tool_calls
+        .iter()
+        .map(|c| estimate_tokens(&c.name) + estimate_tokens(&c.arguments.to_string()))
+        .sum();
+    estimate_tokens(&message.content) + tool_tokens + MESSAGE_OVERHEAD_TOKENS
+}
+
+pub fn total_tokens(messages: &[ChatMessage]) -> usize {
+    messages.iter().map(message_tokens).sum()
+}
+
+fn is_summary(message: &ChatMessage) -> bool {
+    message.role == "system" && message.content.starts_with(SUMMARY_PREFIX)
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummarizeResult {
+    messages: Vec<ChatMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<String>,
+    summarized_count: usize,
+    original_tokens: usize,
+    compacted_tokens: usize,
+}
+
+fn transcript(messages: &[ChatMessage]) -> String {
+    messages
+        .iter()
+        .map(|m| {
+            if is_summary(m) {
+                format!("[previous summary]\n{}", &m.content[SUMMARY_PREFIX.len()..])
+            } else {
+                format!("{}: {}", m.role, m.content)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+// Ask the model for a summary of the given messages (including any previous summary)
+pub async fn summarize_messages(
+    provider: &ProviderConfig,
+    messages: &[ChatMessage],
+    max_summary_tokens: usize,
+) -> Result<String, String> {
+    let prompt = vec![
+        ChatMessage::new(
+            "system",
+            "You compress chat histories. Write a concise summary of the conversation below that preserves \
+             facts, decisions, names, numbers, open questions and the user's goals. If a previous summary is \
+             included, merge it into the new one. Reply with the summary only.",
+        ),
+        ChatMessage::new("user", transcript(messages)),
+    ];
+    let options = GenerationOptions {
+        temperature: Some(0.2),
+        max_tokens: Some(max_summary_tokens as u32),
+        ..Default::default()
+    };
+
+    let completion = llm::chat(provider, &prompt, &[], &options).await?;
+    let summary = completion.message.content.trim().to_string();
+    if summary.is_empty() {
+        return Err("Model returned an empty summary".to_string());
+    }
+    Ok(summary)
+}
+
+// Summarize the oldest messages so the history fits in `target_tokens`.
+// Leading system prompts are kept verbatim; the newest messages are kept until half the budget is used.
+pub async fn compact_history(
+    provider: &ProviderConfig,
+    messages: Vec<ChatMessage>,
+    target_tokens: usize,
+) -> Result<SummarizeResult, String> {
+    let original_tokens = total_tokens(&messages);
+    if original_tokens <= target_tokens {
+        return Ok(SummarizeResult {
+            messages,
+            summary: None,
+            summarized_count: 0,
+            original_tokens,
+            compacted_tokens: original_tokens,
+        });
+    }
+
+    let pinned = messages
+        .iter()
+        .take_while(|m| m.role == "system" && !is_summary(m))
+        .count();
+
+    // Walk back from the newest message, keeping as many as fit in half the budget
+    let recent_budget = target_tokens / 2;
+    let mut recent_tokens = 0;
+    let mut split = messages.len();
+    while split > pinned + 1 {
+        let tokens = message_tokens(&messages[split - 1]);
+        if recent_tokens + tokens > recent_budget {
+            break;
+        }
+        recent_tokens += tokens;
+        split -= 1;
+    }
+    // Never start the kept window with tool results orphaned from their call
+    while split < messages.len() && messages[split].role == "tool" {
+        split += 1;
+    }
+
+    let to_summarize = &messages[pinned..split];
+    if to_summarize.is_empty() {
+        return Ok(SummarizeResult {
+            messages,
+            summary: None,
+            summarized_count: 0,
+            original_tokens,
+            compacted_tokens: original_tokens,
+        });
+    }
+    let pinned_tokens = total_tokens(&messages[..pinned]);
+    let summary_budget = target_tokens
+        .saturating_sub(pinned_tokens + recent_tokens)
+        .clamp(128, 1024);
+
+    eprintln!(
+        "[Context] Summarizing {} messages ({} tokens, target {})",
+        to_summarize.len(),
+        original_tokens,
+        target_tokens
+    );
+    let summary = summarize_messages(provider, to_summarize, summary_budget).await?;
+
+    let mut compacted: Vec<ChatMessage> = messages[..pinned].to_vec();
+    compacted.push(ChatMessage::new("system", format!("{}{}", SUMMARY_PREFIX, summary)));
+    compacted.extend_from_slice(&messages[split..]);
+    let compacted_tokens = total_tokens(&compacted);
+
+    Ok(SummarizeResult {
+        messages: compacted,
+        summary: Some(summary),
+        summarized_count: to_summarize.len(),
+        original_tokens,
+        compacted_tokens,
+    })
+}
+
+#[tauri::command]
+pub async fn summarize_history(
+    messages: Vec<ChatMessage>,
+    target_tokens: usize,
+    model: ProviderConfig,
+) -> Result<SummarizeResult, String> {
+    compact_history(&model, messages, target_tokens).await
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum TruncationPolicy {
+    #[default]
+    None,
+    DropOldest,
+    SlidingWindow,
+    SummarizeThenDrop,
+}
+
+#[derive(serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextOptions {
+    #[serde(default)]
+    pub policy: TruncationPolicy,
+    pub max_context_tokens: usize,
+    // Tokens kept free for the response (defaults to the request's maxTokens or 512)
+    pub reserve_tokens: Option<usize>,
+}
+
+pub struct TrimOutcome {
+    pub messages: Vec<ChatMessage>,
+    pub trimmed_count: usize,
+    pub summarized_count: usize,
+}
+
+// Remove messages from `start` onwards (oldest first) until the history fits; the last message is always kept
+fn drop_from(mut messages: Vec<ChatMessage>, start: usize, budget: usize) -> (Vec<ChatMessage>, usize) {
+    let mut dropped = 0;
+    while total_tokens(&messages) > budget && messages.len() > start + 1 {
+        messages.remove(start);
+        dropped += 1;
+        // Tool results without their assistant call are rejected by providers
+        while messages.len() > start + 1 && messages[start].role == "tool" {
+            messages.remove(start);
+            dropped += 1;
+        }
+    }
+    (messages, dropped)
+}
+
+fn pinned_count(messages: &[ChatMessage]) -> usize {
+    messages.iter().take_while(|m| m.role == "system").count()
+}
+
+// Apply the selected truncation policy so the prompt fits in the context window
+pub async fn apply_policy(
+    provider: &ProviderConfig,
+    messages: Vec<ChatMessage>,
+    options: &ContextOptions,
+    response_tokens: Option<u32>,
+) -> Result<TrimOutcome, String> {
+    let reserve = options
+        .reserve_tokens
+        .unwrap_or_else(|| response_tokens.map(|t| t as usize).unwrap_or(512));
+    let budget = options.max_context_tokens.saturating_sub(reserve);
+
+    if options.policy == TruncationPolicy::None || total_tokens(&messages) <= budget {
+        return Ok(TrimOutcome {
+            messages,
+            trimmed_count: 0,
+            summarized_count: 0,
+        });
+    }
+
+    let outcome = match options.policy {
+        TruncationPolicy::None => unreachable!(),
+        TruncationPolicy::DropOldest => {
+            let (messages, trimmed_count) = drop_from(messages, 0, budget);
+            TrimOutcome {
+                messages,
+                trimmed_count,
+                summarized_count: 0,
+            }
+        }
+        TruncationPolicy::SlidingWindow => {
+            let pinned = pinned_count(&messages);
+            let (messages, trimmed_count) = drop_from(messages, pinned, budget);
+            TrimOutcome {
+                messages,
+                trimmed_count,
+                summarized_count: 0,
+            }
+        }
+        TruncationPolicy::SummarizeThenDrop => {
+            let (messages, summarized_count) = match compact_history(provider, messages.clone(), budget).await {
+                Ok(result) => (result.messages, result.summarized_count),
+                Err(err) => {
+                    eprintln!("[Context] Summarization failed, dropping instead: {}", err);
+                    (messages, 0)
+                }
+            };
+            let pinned = pinned_count(&messages);
+            let (messages, trimmed_count) = drop_from(messages, pinned, budget);
+            TrimOutcome {
+                messages,
+                trimmed_count,
+                summarized_count,
+            }
+        }
+    };
+
+    eprintln!(
+        "[Context] Policy {:?}: trimmed {}, summarized {} (budget {} tokens)",
+        options.policy, outcome.trimmed_count, outcome.summarized_count, budget
+    );
+    Ok(outcome)
+}
@@ -0,0 +1,808 @@
+// Durable conversation storage: conversations, their messages and message attachments
+use std::collections::HashMap;
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde_json::Value;
+use tauri::State;
+
+use crate::blobs::{self, BlobStore};
+use crate::db::{self, Database};
+use crate::llm::{ChatMessage, ToolCall};
+use crate::tags::{self, Tag};
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Conversation {
+    pub id: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub persona_id: Option<String>,
+    // Last message of the branch currently shown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_leaf_id: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    // Incremented on every change; used by sync to detect concurrent edits
+    pub revision: i64,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationSummary {
+    #[serde(flatten)]
+    conversation: Conversation,
+    message_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    folder_id: Option<String>,
+    tags: Vec<Tag>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    archived_at: Option<String>,
+}
+
+#[derive(serde::Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ConversationSort {
+    #[default]
+    UpdatedAt,
+    CreatedAt,
+    Title,
+    MessageCount,
+}
+
+#[derive(serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationQuery {
+    // Only conversations in this folder
+    folder_id: Option<String>,
+    // Only conversations not in any folder (ignored when folderId is set)
+    #[serde(default)]
+    unfiled: bool,
+    // Only conversations carrying all of these tags
+    #[serde(default)]
+    tag_ids: Vec<String>,
+    // Case-insensitive match on the title
+    search: Option<String>,
+    // List archived conversations instead of active ones
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    sort: ConversationSort,
+    // Defaults to newest/largest first for dates and counts, A-Z for titles
+    ascending: Option<bool>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    pub id: String,
+    pub message_id: String,
+    pub file_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<i64>,
+    pub path: String,
+    // SHA-256 of the content for files kept in the blob store
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredMessage {
+    pub id: String,
+    pub conversation_id: String,
+    #[serde(flatten)]
+    pub message: ChatMessage,
+    // Free-form per-message data from the frontend (model, generation id, ...)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Value>,
+    pub attachments: Vec<Attachment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    // Position among the messages sharing this parent, and how many there are
+    pub branch_index: i64,
+    #[serde(default)]
+    pub sibling_count: usize,
+    pub created_at: String,
+    // Pins and favorites stay on this device; sync ignores them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub favorited_at: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashedConversation {
+    #[serde(flatten)]
+    conversation: Conversation,
+    message_count: usize,
+    deleted_at: String,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FavoriteMessage {
+    #[serde(flatten)]
+    message: StoredMessage,
+    conversation_title: String,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationDetail {
+    #[serde(flatten)]
+    conversation: Conversation,
+    messages: Vec<StoredMessage>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationInput {
+    title: Option<String>,
+    model: Option<String>,
+    persona_id: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentInput {
+    file_name: String,
+    mime_type: Option<String>,
+    path: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageInput {
+    #[serde(flatten)]
+    message: ChatMessage,
+    metadata: Option<Value>,
+    #[serde(default)]
+    attachments: Vec<AttachmentInput>,
+}
+
+const SELECT_CONVERSATION: &str =
+    "SELECT id, title, model, persona_id, active_leaf_id, created_at, updated_at, revision FROM conversations";
+const SELECT_MESSAGE: &str = "SELECT m.id, m.conversation_id, m.role, m.content, m.tool_calls, m.tool_call_id, m.name,
+        m.metadata, m.created_at, m.parent_id, m.branch_index,
+        (SELECT COUNT(*) FROM messages s WHERE s.conversation_id = m.conversation_id AND s.parent_id IS m.parent_id),
+        m.pinned_at, m.favorited_at
+    FROM messages m";
+const SELECT_ATTACHMENT: &str =
+    "SELECT id, message_id, file_name, mime_type, size, path, hash, created_at FROM attachments";
+
+fn row_to_conversation(row: &Row) -> rusqlite::Result<Conversation> {
+    Ok(Conversation {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        model: row.get(2)?,
+        persona_id: row.get(3)?,
+        active_leaf_id: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+        revision: row.get(7)?,
+    })
+}
+
+fn row_to_message(row: &Row) -> rusqlite::Result<StoredMessage> {
+    let tool_calls: Option<String> = row.get(4)?;
+    let metadata: Option<String> = row.get(7)?;
+    Ok(StoredMessage {
+        id: row.get(0)?,
+        conversation_id: row.get(1)?,
+        message: ChatMessage {
+            role: row.get(2)?,
+            content: row.get(3)?,
+            tool_calls: tool_calls
+                .and_then(|t| serde_json::from_str::<Vec<ToolCall>>(&t).ok())
+                .unwrap_or_default(),
+            tool_call_id: row.get(5)?,
+            name: row.get(6)?,
+        },
+        metadata: metadata.and_then(|m| serde_json::from_str(&m).ok()),
+        attachments: Vec::new(),
+        created_at: row.get(8)?,
+        parent_id: row.get(9)?,
+        branch_index: row.get(10)?,
+        sibling_count: row.get::<_, i64>(11)? as usize,
+        pinned_at: row.get(12)?,
+        favorited_at: row.get(13)?,
+    })
+}
+
+fn row_to_attachment(row: &Row) -> rusqlite::Result<Attachment> {
+    Ok(Attachment {
+        id: row.get(0)?,
+        message_id: row.get(1)?,
+        file_name: row.get(2)?,
+        mime_type: row.get(3)?,
+        size: row.get(4)?,
+        path: row.get(5)?,
+        hash: row.get(6)?,
+        created_at: row.get(7)?,
+    })
+}
+
+pub fn get_conversation_row(conn: &Connection, id: &str) -> rusqlite::Result<Option<Conversation>> {
+    conn.query_row(&format!("{} WHERE id = ?1", SELECT_CONVERSATION), params![id], row_to_conversation)
+        .optional()
+}
+
+fn attach_files(conn: &Connection, messages: &mut [StoredMessage]) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(&format!("{} WHERE message_id = ?1 ORDER BY created_at", SELECT_ATTACHMENT))?;
+    for message in messages.iter_mut() {
+        message.attachments = stmt
+            .query_map(params![message.id], row_to_attachment)?
+            .collect::<rusqlite::Result<_>>()?;
+    }
+    Ok(())
+}
+
+pub fn get_message(conn: &Connection, id: &str) -> rusqlite::Result<Option<StoredMessage>> {
+    conn.query_row(&format!("{} WHERE m.id = ?1", SELECT_MESSAGE), params![id], row_to_message)
+        .optional()
+}
+
+// Messages on the conversation's active branch, root first, with their attachments
+pub fn load_messages(conn: &Connection, conversation_id: &str) -> rusqlite::Result<Vec<StoredMessage>> {
+    let mut stmt = conn.prepare(&format!("{} WHERE m.conversation_id = ?1", SELECT_MESSAGE))?;
+    let mut by_id: HashMap<String, StoredMessage> = stmt
+        .query_map(params![conversation_id], row_to_message)?
+        .map(|m| m.map(|m| (m.id.clone(), m)))
+        .collect::<rusqlite::Result<_>>()?;
+
+    let leaf: Option<String> = conn.query_row(
+        "SELECT active_leaf_id FROM conversations WHERE id = ?1",
+        params![conversation_id],
+        |row| row.get(0),
+    )?;
+    let mut path = Vec::new();
+    let mut next = leaf;
+    while let Some(message) = next.and_then(|id| by_id.remove(&id)) {
+        next = message.parent_id.clone();
+        path.push(message);
+    }
+    path.reverse();
+    attach_files(conn, &mut path)?;
+    Ok(path)
+}
+
+// Every message of the conversation across all branches, oldest first, with attachments
+pub fn load_all_messages(conn: &Connection, conversation_id: &str) -> rusqlite::Result<Vec<StoredMessage>> {
+    let mut stmt = conn.prepare(&format!(
+        "{} WHERE m.conversation_id = ?1 ORDER BY m.created_at, m.rowid",
+        SELECT_MESSAGE
+    ))?;
+    let mut messages: Vec<StoredMessage> = stmt
+        .query_map(params![conversation_id], row_to_message)?
+        .collect::<rusqlite::Result<_>>()?;
+    attach_files(conn, &mut messages)?;
+    Ok(messages)
+}
+
+pub fn insert_conversation(conn: &Connection, conversation: &Conversation) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO conversations (id, title, model, persona_id, active_leaf_id, created_at, updated_at, revision)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            conversation.id,
+            conversation.title,
+            conversation.model,
+            conversation.persona_id,
+            conversation.active_leaf_id,
+            conversation.created_at,
+            conversation.updated_at,
+            conversation.revision
+        ],
+    )?;
+    Ok(())
+}
+
+// Insert a message row under `parent_id` (attachments are stored separately) and make it the active leaf
+pub fn insert_message(
+    conn: &Connection,
+    conversation_id: &str,
+    parent_id: Option<&str>,
+    message: &ChatMessage,
+    metadata: Option<&Value>,
+    created_at: &str,
+) -> rusqlite::Result<String> {
+    let id = db::new_id("msg");
+    let branch_index: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM messages WHERE conversation_id = ?1 AND parent_id IS ?2",
+        params![conversation_id, parent_id],
+        |row| row.get(0),
+    )?;
+    let tool_calls = if message.tool_calls.is_empty() {
+        None
+    } else {
+        Some(serde_json::to_string(&message.tool_calls).unwrap_or_default())
+    };
+    conn.execute(
+        "INSERT INTO messages (id, conversation_id, role, content, tool_calls, tool_call_id, name, metadata,
+                               parent_id, branch_index, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            id,
+            conversation_id,
+            message.role,
+            message.content,
+            tool_calls,
+            message.tool_call_id,
+            message.name,
+            metadata.map(|m| m.to_string()),
+            parent_id,
+            branch_index,
+            created_at
+        ],
+    )?;
+    conn.execute(
+        "UPDATE conversations SET active_leaf_id = ?1, revision = revision + 1 WHERE id = ?2",
+        params![id, conversation_id],
+    )?;
+    Ok(id)
+}
+
+#[tauri::command]
+pub fn create_conversation(db: State<'_, Database>, conversation: ConversationInput) -> Result<Conversation, String> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let conversation = Conversation {
+        id: db::new_id("conv"),
+        title: conversation
+            .title
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(|| "New Chat".to_string()),
+        model: conversation.model,
+        persona_id: conversation.persona_id,
+        active_leaf_id: None,
+        created_at: now.clone(),
+        updated_at: now,
+        revision: 0,
+    };
+    db.with_conn(|conn| insert_conversation(conn, &conversation))?;
+    eprintln!("[Conversations] Created {}", conversation.id);
+    Ok(conversation)
+}
+
+// Insert a message exactly as stored elsewhere (same id and position in the tree), with its attachments.
+// Parents must be inserted before their children.
+pub fn insert_stored_message(conn: &Connection, message: &StoredMessage) -> rusqlite::Result<()> {
+    let tool_calls = if message.message.tool_calls.is_empty() {
+        None
+    } else {
+        Some(serde_json::to_string(&message.message.tool_calls).unwrap_or_default())
+    };
+    conn.execute(
+        "INSERT INTO messages (id, conversation_id, role, content, tool_calls, tool_call_id, name, metadata,
+                               parent_id, branch_index, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            message.id,
+            message.conversation_id,
+            message.message.role,
+            message.message.content,
+            tool_calls,
+            message.message.tool_call_id,
+            message.message.name,
+            message.metadata.as_ref().map(|m| m.to_string()),
+            message.parent_id,
+            message.branch_index,
+            message.created_at
+        ],
+    )?;
+    for attachment in &message.attachments {
+        insert_attachment(conn, attachment)?;
+    }
+    Ok(())
+}
+
+pub fn insert_attachment(conn: &Connection, attachment: &Attachment) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO attachments (id, message_id, file_name, mime_type, size, path, hash, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            attachment.id,
+            attachment.message_id,
+            attachment.file_name,
+            attachment.mime_type,
+            attachment.size,
+            attachment.path,
+            attachment.hash,
+            attachment.created_at
+        ],
+    )?;
+    Ok(())
+}
+
+// Where a new message goes in the conversation tree
+enum Placement<'a> {
+    // After the last message of the active branch
+    ActiveLeaf,
+    // Next to an existing message, as an alternate version of it
+    SiblingOf(&'a StoredMessage),
+}
+
+// Insert the message and its attachments in one transaction and bump the conversation's updated_at
+fn store_message(
+    db: &Database,
+    blobs: &BlobStore,
+    conversation_id: &str,
+    placement: Placement,
+    message: MessageInput,
+) -> Result<StoredMessage, String> {
+    let now = chrono::Utc::now().to_rfc3339();
+    // Files are copied into the blob store so the message keeps them if the originals move
+    let mut attachments = Vec::with_capacity(message.attachments.len());
+    for input in &message.attachments {
+        let blob = blobs.put_file(Path::new(&input.path))?;
+        attachments.push(Attachment {
+            id: db::new_id("att"),
+            message_id: String::new(),
+            file_name: input.file_name.clone(),
+            mime_type: input.mime_type.clone().or_else(|| blobs::guess_mime_type(&input.file_name)),
+            size: Some(blob.size as i64),
+            path: blob.path.to_string_lossy().to_string(),
+            hash: Some(blob.hash),
+            created_at: now.clone(),
+        });
+    }
+
+    let stored = db.with_conn(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        let Some(conversation) = get_conversation_row(&tx, conversation_id)? else {
+            return Ok(None);
+        };
+        let parent_id = match placement {
+            Placement::ActiveLeaf => conversation.active_leaf_id,
+            Placement::SiblingOf(sibling) => sibling.parent_id.clone(),
+        };
+        let id = insert_message(
+            &tx,
+            conversation_id,
+            parent_id.as_deref(),
+            &message.message,
+            message.metadata.as_ref(),
+            &now,
+        )?;
+        for attachment in attachments.iter_mut() {
+            attachment.message_id = id.clone();
+            insert_attachment(&tx, attachment)?;
+        }
+        tx.execute(
+            "UPDATE conversations SET updated_at = ?1 WHERE id = ?2",
+            params![now, conversation_id],
+        )?;
+        let stored = get_message(&tx, &id)?;
+        tx.commit()?;
+        Ok(stored)
+    })?;
+    let mut stored = stored.ok_or_else(|| format!("Conversation {} not found", conversation_id))?;
+    stored.attachments = attachments;
+    Ok(stored)
+}
+
+fn find_message(db: &Database, id: &str) -> Result<StoredMessage, String> {
+    db.with_conn(|conn| get_message(conn, id))?
+        .ok_or_else(|| format!("Message {} not found", id))
+}
+
+#[tauri::command]
+pub fn append_message(
+    db: State<'_, Database>,
+    blobs: State<'_, BlobStore>,
+    conversation_id: String,
+    message: MessageInput,
+) -> Result<StoredMessage, String> {
+    store_message(&db, &blobs, &conversation_id, Placement::ActiveLeaf, message)
+}
+
+// Add an alternate version of a message (an edit or a regeneration) and switch to it.
+// The original message and everything after it stay available on their own branch.
+#[tauri::command]
+pub fn create_branch(
+    db: State<'_, Database>,
+    blobs: State<'_, BlobStore>,
+    message_id: String,
+    message: MessageInput,
+) -> Result<StoredMessage, String> {
+    let original = find_message(&db, &message_id)?;
+    eprintln!("[Conversations] Branching from {}", message_id);
+    store_message(&db, &blobs, &original.conversation_id, Placement::SiblingOf(&original), message)
+}
+
+// All versions of a message (including itself), in the order they were created
+#[tauri::command]
+pub fn list_siblings(db: State<'_, Database>, message_id: String) -> Result<Vec<StoredMessage>, String> {
+    let message = find_message(&db, &message_id)?;
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE m.conversation_id = ?1 AND m.parent_id IS ?2 ORDER BY m.branch_index",
+            SELECT_MESSAGE
+        ))?;
+        let mut siblings: Vec<StoredMessage> = stmt
+            .query_map(params![message.conversation_id, message.parent_id], row_to_message)?
+            .collect::<rusqlite::Result<_>>()?;
+        attach_files(conn, &mut siblings)?;
+        Ok(siblings)
+    })
+}
+
+// Make the branch through `message_id` active, continuing down its most recent replies
+#[tauri::command]
+pub fn switch_branch(db: State<'_, Database>, message_id: String) -> Result<ConversationDetail, String> {
+    let message = find_message(&db, &message_id)?;
+    db.with_conn(|conn| {
+        let mut leaf = message.id.clone();
+        let mut stmt = conn.prepare(
+            "SELECT id FROM messages WHERE parent_id = ?1 ORDER BY created_at DESC, rowid DESC LIMIT 1",
+        )?;
+        while let Some(child) = stmt.query_row(params![leaf], |row| row.get::<_, String>(0)).optional()? {
+            leaf = child;
+        }
+        conn.execute(
+            "UPDATE conversations SET active_leaf_id = ?1, revision = revision + 1 WHERE id = ?2",
+            params![leaf, message.conversation_id],
+        )?;
+        let conversation = get_conversation_row(conn, &message.conversation_id)?
+            .ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+        let messages = load_messages(conn, &message.conversation_id)?;
+        Ok(ConversationDetail { conversation, messages })
+    })
+}
+
+// Conversations matching the folder/tag/title filters, sorted and paged
+#[tauri::command]
+pub fn list_conversations(
+    db: State<'_, Database>,
+    query: Option<ConversationQuery>,
+) -> Result<Vec<ConversationSummary>, String> {
+    let query = query.unwrap_or_default();
+    let mut conditions: Vec<String> = vec![
+        if query.archived {
+            "c.archived_at IS NOT NULL".to_string()
+        } else {
+            "c.archived_at IS NULL".to_string()
+        },
+        "NOT EXISTS (SELECT 1 FROM trash t WHERE t.conversation_id = c.id)".to_string(),
+    ];
+    let mut values: Vec<String> = Vec::new();
+    if let Some(folder_id) = &query.folder_id {
+        values.push(folder_id.clone());
+        conditions.push(format!("c.folder_id = ?{}", values.len()));
+    } else if query.unfiled {
+        conditions.push("c.folder_id IS NULL".to_string());
+    }
+    for tag_id in &query.tag_ids {
+        values.push(tag_id.clone());
+        conditions.push(format!(
+            "EXISTS (SELECT 1 FROM conversation_tags ct WHERE ct.conversation_id = c.id AND ct.tag_id = ?{})",
+            values.len()
+        ));
+    }
+    if let Some(search) = query.search.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        values.push(format!("%{}%", search));
+        conditions.push(format!("c.title LIKE ?{}", values.len()));
+    }
+
+    let (column, default_ascending) = match query.sort {
+        ConversationSort::UpdatedAt => ("c.updated_at", false),
+        ConversationSort::CreatedAt => ("c.created_at", false),
+        ConversationSort::Title => ("c.title COLLATE NOCASE", true),
+        ConversationSort::MessageCount => ("message_count", false),
+    };
+    let direction = if query.ascending.unwrap_or(default_ascending) { "ASC" } else { "DESC" };
+    let mut sql = format!(
+        "SELECT c.id, c.title, c.model, c.persona_id, c.active_leaf_id, c.created_at, c.updated_at, c.revision,
+                (SELECT COUNT(*) FROM messages m WHERE m.conversation_id = c.id) AS message_count,
+                (SELECT substr(m.content, 1, 200) FROM messages m WHERE m.id = c.active_leaf_id),
+                c.folder_id, c.archived_at
+         FROM conversations c WHERE {} ORDER BY {} {}, c.id",
+        conditions.join(" AND "),
+        column,
+        direction
+    );
+    if query.limit.is_some() || query.offset.is_some() {
+        sql.push_str(&format!(
+            " LIMIT {} OFFSET {}",
+            query.limit.map(i64::from).unwrap_or(-1),
+            query.offset.unwrap_or(0)
+        ));
+    }
+
+    db.with_conn(|conn| {
+        let mut tags = tags::tags_by_conversation(conn)?;
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(&values), |row| {
+            Ok(ConversationSummary {
+                conversation: row_to_conversation(row)?,
+                message_count: row.get::<_, i64>(8)? as usize,
+                last_message: row.get(9)?,
+                folder_id: row.get(10)?,
+                tags: Vec::new(),
+                archived_at: row.get(11)?,
+            })
+        })?;
+        rows.map(|row| {
+            row.map(|mut summary| {
+                summary.tags = tags.remove(&summary.conversation.id).unwrap_or_default();
+                summary
+            })
+        })
+        .collect()
+    })
+}
+
+#[tauri::command]
+pub fn get_conversation(db: State<'_, Database>, id: String) -> Result<ConversationDetail, String> {
+    let detail = db.with_conn(|conn| {
+        let Some(conversation) = get_conversation_row(conn, &id)? else {
+            return Ok(None);
+        };
+        let messages = load_messages(conn, &id)?;
+        Ok(Some(ConversationDetail { conversation, messages }))
+    })?;
+    detail.ok_or_else(|| format!("Conversation {} not found", id))
+}
+
+// Archived conversations are hidden from the default listing but otherwise kept as they are
+#[tauri::command]
+pub fn set_conversation_archived(db: State<'_, Database>, id: String, archived: bool) -> Result<(), String> {
+    let archived_at = archived.then(|| chrono::Utc::now().to_rfc3339());
+    let updated = db.with_conn(|conn| {
+        conn.execute(
+            "UPDATE conversations SET archived_at = ?1 WHERE id = ?2",
+            params![archived_at, id],
+        )
+    })?;
+    if updated == 0 {
+        return Err(format!("Conversation {} not found", id));
+    }
+    Ok(())
+}
+
+// Shared by pinning and favoriting: sets or clears the timestamp column of one message
+fn mark_message(db: &Database, id: &str, column: &str, marked: bool) -> Result<StoredMessage, String> {
+    let marked_at = marked.then(|| chrono::Utc::now().to_rfc3339());
+    let message = db.with_conn(|conn| {
+        let updated = conn.execute(
+            &format!("UPDATE messages SET {} = ?1 WHERE id = ?2", column),
+            params![marked_at, id],
+        )?;
+        if updated == 0 {
+            return Ok(None);
+        }
+        let mut message = get_message(conn, id)?;
+        attach_files(conn, message.as_mut_slice())?;
+        Ok(message)
+    })?;
+    message.ok_or_else(|| format!("Message {} not found", id))
+}
+
+#[tauri::command]
+pub fn set_message_pinned(db: State<'_, Database>, id: String, pinned: bool) -> Result<StoredMessage, String> {
+    mark_message(&db, &id, "pinned_at", pinned)
+}
+
+#[tauri::command]
+pub fn set_message_favorite(db: State<'_, Database>, id: String, favorite: bool) -> Result<StoredMessage, String> {
+    mark_message(&db, &id, "favorited_at", favorite)
+}
+
+// Pinned messages of a conversation from every branch, oldest pin first
+#[tauri::command]
+pub fn list_pinned(db: State<'_, Database>, conversation_id: String) -> Result<Vec<StoredMessage>, String> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE m.conversation_id = ?1 AND m.pinned_at IS NOT NULL ORDER BY m.pinned_at",
+            SELECT_MESSAGE
+        ))?;
+        let mut messages: Vec<StoredMessage> = stmt
+            .query_map(params![conversation_id], row_to_message)?
+            .collect::<rusqlite::Result<_>>()?;
+        attach_files(conn, &mut messages)?;
+        Ok(messages)
+    })
+}
+
+// Favorites across all conversations, most recently favorited first
+#[tauri::command]
+pub fn list_favorites(db: State<'_, Database>) -> Result<Vec<FavoriteMessage>, String> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE m.favorited_at IS NOT NULL ORDER BY m.favorited_at DESC",
+            SELECT_MESSAGE
+        ))?;
+        let mut messages: Vec<StoredMessage> = stmt.query_map([], row_to_message)?.collect::<rusqlite::Result<_>>()?;
+        attach_files(conn, &mut messages)?;
+
+        let mut titles = conn.prepare("SELECT title FROM conversations WHERE id = ?1")?;
+        messages
+            .into_iter()
+            .map(|message| {
+                let conversation_title = titles.query_row(params![message.conversation_id], |row| row.get(0))?;
+                Ok(FavoriteMessage { message, conversation_title })
+            })
+            .collect()
+    })
+}
+
+// Deleting moves the conversation to the trash; it can be restored until the trash is purged
+#[tauri::command]
+pub fn delete_conversation(db: State<'_, Database>, id: String) -> Result<(), String> {
+    let trashed = db.with_conn(|conn| {
+        conn.execute(
+            "INSERT OR IGNORE INTO trash (conversation_id, deleted_at)
+             SELECT id, ?2 FROM conversations WHERE id = ?1",
+            params![id, chrono::Utc::now().to_rfc3339()],
+        )?;
+        conn.query_row("SELECT 1 FROM trash WHERE conversation_id = ?1", params![id], |_| Ok(()))
+            .optional()
+    })?;
+    if trashed.is_none() {
+        return Err(format!("Conversation {} not found", id));
+    }
+    eprintln!("[Conversations] Moved {} to trash", id);
+    Ok(())
+}
+
+// Trashed conversations, most recently deleted first
+#[tauri::command]
+pub fn list_trash(db: State<'_, Database>) -> Result<Vec<TrashedConversation>, String> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT c.id, c.title, c.model, c.persona_id, c.active_leaf_id, c.created_at, c.updated_at, c.revision,
+                    (SELECT COUNT(*) FROM messages m WHERE m.conversation_id = c.id), t.deleted_at
+             FROM trash t JOIN conversations c ON c.id = t.conversation_id
+             ORDER BY t.deleted_at DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(TrashedConversation {
+                conversation: row_to_conversation(row)?,
+                message_count: row.get::<_, i64>(8)? as usize,
+                deleted_at: row.get(9)?,
+            })
+        })?;
+        rows.collect()
+    })
+}
+
+#[tauri::command]
+pub fn restore_conversation(db: State<'_, Database>, id: String) -> Result<(), String> {
+    let restored = db.with_conn(|conn| conn.execute("DELETE FROM trash WHERE conversation_id = ?1", params![id]))?;
+    if restored == 0 {
+        return Err(format!("Conversation {} is not in the trash", id));
+    }
+    eprintln!("[Conversations] Restored {} from trash", id);
+    Ok(())
+}
+
+// Permanently delete trashed conversations, only those trashed more than `older_than_days` ago when given.
+// Messages and attachment rows are removed by the foreign key cascade; blobs are left for garbage collection.
+// Sync sees a conversation as deleted only once it is purged.
+#[tauri::command]
+pub fn purge_trash(db: State<'_, Database>, older_than_days: Option<u64>) -> Result<usize, String> {
+    let cutoff = match older_than_days {
+        Some(days) => (chrono::Utc::now() - chrono::Duration::days(days as i64)).to_rfc3339(),
+        // Every RFC 3339 timestamp sorts before this
+        None => "9999".to_string(),
+    };
+    let purged = db.with_conn(|conn| {
+        conn.execute(
+            "DELETE FROM conversations WHERE id IN (SELECT conversation_id FROM trash WHERE deleted_at < ?1)",
+            params![cutoff],
+        )
+    })?;
+    eprintln!("[Conversations] Purged {} conversation(s) from trash", purged);
+    Ok(purged)
+}
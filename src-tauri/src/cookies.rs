@@ -0,0 +1,235 @@
+// Cookies kept between scrapes, per profile, in <profile data>/cookies.json. Both scrape paths share
+// them: scrape sessions on the shared HTTP client send and keep the jar's cookies, and browser tabs
+// get the page's cookies before navigating and hand back what the page set. Consent banners and
+// logins then only have to be dealt with once. Off unless `scrapeCookies` is set; session cookies
+// aren't saved.
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use cookie_store::{CookieExpiration, CookieStore, RawCookie};
+use headless_chrome::protocol::cdp::Network;
+use headless_chrome::Tab;
+use reqwest::header::HeaderValue;
+use reqwest::Url;
+use tauri::{AppHandle, Manager};
+
+use crate::browser::{self, BrowserPool};
+
+const COOKIES_FILE: &str = "cookies.json";
+
+#[derive(Default)]
+struct JarState {
+    // File of the profile the cookies belong to
+    path: Option<PathBuf>,
+    store: CookieStore,
+}
+
+#[derive(Clone, Default)]
+pub struct CookieJar {
+    state: Arc<Mutex<JarState>>,
+}
+
+fn load_store(path: &Path) -> CookieStore {
+    let Ok(file) = std::fs::File::open(path) else { return CookieStore::default() };
+    cookie_store::serde::json::load(BufReader::new(file)).unwrap_or_else(|e| {
+        eprintln!("[Cookies] Failed to read {}: {}", path.display(), e);
+        CookieStore::default()
+    })
+}
+
+fn save_store(state: &JarState) {
+    let Some(path) = &state.path else { return };
+    let mut json = Vec::new();
+    let saved = cookie_store::serde::json::save(&state.store, &mut json)
+        .map_err(|e| e.to_string())
+        .and_then(|_| std::fs::write(path, &json).map_err(|e| e.to_string()));
+    if let Err(e) = saved {
+        eprintln!("[Cookies] Failed to save {}: {}", path.display(), e);
+    }
+}
+
+// Whether a cookie domain is `domain` or one of its subdomains
+fn in_domain(cookie_domain: &str, domain: &str) -> bool {
+    let cookie_domain = cookie_domain.trim_start_matches('.').to_lowercase();
+    let domain = domain.trim().trim_start_matches('.').to_lowercase();
+    cookie_domain == domain || cookie_domain.ends_with(&format!(".{}", domain))
+}
+
+impl CookieJar {
+    fn lock(&self) -> MutexGuard<'_, JarState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    // Switch to the cookies of the profile whose data lives in `data_dir`, loading them on first use
+    pub fn use_profile(&self, data_dir: &Path) {
+        let path = data_dir.join(COOKIES_FILE);
+        let mut state = self.lock();
+        if state.path.as_ref() != Some(&path) {
+            state.store = load_store(&path);
+            state.path = Some(path);
+        }
+    }
+
+    // Cookies for `url`, for a browser tab about to load it
+    pub fn browser_cookies(&self, url: &Url) -> Vec<Network::CookieParam> {
+        let state = self.lock();
+        state
+            .store
+            .matches(url)
+            .into_iter()
+            .filter_map(|cookie| {
+                let expires = match cookie.expires {
+                    CookieExpiration::AtUtc(at) => Some(at.unix_timestamp() as f64),
+                    CookieExpiration::SessionEnd => None,
+                };
+                // Host-only cookies are scoped by URL, domain cookies by their domain
+                let (url, domain) = match &cookie.domain {
+                    cookie_store::CookieDomain::Suffix(domain) => (None, Some(format!(".{}", domain))),
+                    _ => (Some(url.to_string()), None),
+                };
+                let param = serde_json::json!({
+                    "name": cookie.name(),
+                    "value": cookie.value(),
+                    "url": url,
+                    "domain": domain,
+                    "path": String::from(&cookie.path),
+                    "secure": cookie.secure().unwrap_or(false),
+                    "httpOnly": cookie.http_only().unwrap_or(false),
+                    "expires": expires,
+                });
+                serde_json::from_value(param).ok()
+            })
+            .collect()
+    }
+
+    // Keep the cookies a browser tab holds for the page at `url`
+    pub fn store_browser_cookies(&self, cookies: &[Network::Cookie], url: &Url) {
+        let now = chrono::Utc::now().timestamp() as f64;
+        let mut state = self.lock();
+        for cookie in cookies {
+            let mut line = format!("{}={}; Path={}", cookie.name, cookie.value, cookie.path);
+            // Chrome marks domain cookies with a leading dot
+            if cookie.domain.starts_with('.') {
+                line.push_str(&format!("; Domain={}", cookie.domain));
+            }
+            if cookie.secure {
+                line.push_str("; Secure");
+            }
+            if cookie.http_only {
+                line.push_str("; HttpOnly");
+            }
+            if !cookie.session {
+                line.push_str(&format!("; Max-Age={}", (cookie.expires - now).max(0.0) as i64));
+            }
+            let _ = state.store.parse(&line, url);
+        }
+        save_store(&state);
+    }
+
+    // Share cookies with a tab before it loads `url`; failures only cost the cookies
+    pub fn prepare_tab(&self, tab: &Tab, url: &Url) {
+        let cookies = self.browser_cookies(url);
+        if !cookies.is_empty() {
+            if let Err(err) = tab.set_cookies(cookies) {
+                eprintln!("[Cookies] Failed to give cookies to the browser: {err}");
+            }
+        }
+    }
+
+    // Take back the cookies of the page a tab has loaded
+    pub fn collect_from_tab(&self, tab: &Tab) {
+        let Ok(url) = Url::parse(&tab.get_url()) else { return };
+        match tab.get_cookies() {
+            Ok(cookies) => self.store_browser_cookies(&cookies, &url),
+            Err(err) => eprintln!("[Cookies] Failed to read the browser's cookies: {err}"),
+        }
+    }
+
+    // Remove the cookies of `domain` and its subdomains, or all of them; returns how many went
+    pub fn clear(&self, domain: Option<&str>) -> usize {
+        let mut state = self.lock();
+        let doomed: Vec<(String, String, String)> = state
+            .store
+            .iter_any()
+            .filter(|cookie| domain.is_none_or(|domain| in_domain(&String::from(&cookie.domain), domain)))
+            .map(|cookie| (String::from(&cookie.domain), String::from(&cookie.path), cookie.name().to_string()))
+            .collect();
+        for (domain, path, name) in &doomed {
+            state.store.remove(domain, path, name);
+        }
+        save_store(&state);
+        doomed.len()
+    }
+}
+
+impl reqwest::cookie::CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let cookies: Vec<RawCookie<'static>> = cookie_headers
+            .filter_map(|header| header.to_str().ok())
+            .filter_map(|header| RawCookie::parse(header.to_string()).ok())
+            .collect();
+        if cookies.is_empty() {
+            return;
+        }
+        let mut state = self.lock();
+        state.store.store_response_cookies(cookies.into_iter(), url);
+        save_store(&state);
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let state = self.lock();
+        let header = state
+            .store
+            .get_request_values(url)
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ");
+        if header.is_empty() {
+            return None;
+        }
+        HeaderValue::from_str(&header).ok()
+    }
+}
+
+// Cookies of the browser for `domain`, or all of them, deleted when it is running
+fn clear_browser(pool: &BrowserPool, config: &browser::PoolConfig, domain: Option<&str>) -> Result<(), String> {
+    if !pool.status().running {
+        return Ok(());
+    }
+    let tab = pool.acquire(config, std::time::Duration::from_secs(5))?;
+    let cookies = tab
+        .call_method(Network::GetAllCookies(None))
+        .map_err(|err| format!("Failed to read the browser's cookies: {err}"))?
+        .cookies;
+    let doomed: Vec<Network::DeleteCookies> = cookies
+        .into_iter()
+        .filter(|cookie| domain.is_none_or(|domain| in_domain(&cookie.domain, domain)))
+        .map(|cookie| Network::DeleteCookies {
+            name: cookie.name,
+            url: None,
+            domain: Some(cookie.domain),
+            path: Some(cookie.path),
+            partition_key: None,
+        })
+        .collect();
+    tab.delete_cookies(doomed).map_err(|err| format!("Failed to delete the browser's cookies: {err}"))
+}
+
+// Forget the scrape cookies of `domain` (with its subdomains), or all of them without one, in the
+// active profile and in the running browser. Returns the number of stored cookies removed.
+#[tauri::command]
+pub async fn clear_cookies(app: AppHandle, domain: Option<String>) -> Result<usize, String> {
+    let jar = app.state::<CookieJar>().inner().clone();
+    let pool = app.state::<BrowserPool>().inner().clone();
+    let config = browser::PoolConfig::from_settings(&crate::settings::load(&app));
+    jar.use_profile(&crate::profiles::data_dir(&app)?);
+    let domain = domain.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+    let removed = jar.clear(domain.as_deref());
+    let label = domain.clone().unwrap_or_else(|| "all sites".to_string());
+    tokio::task::spawn_blocking(move || clear_browser(&pool, &config, domain.as_deref()))
+        .await
+        .map_err(|e| format!("Clearing browser cookies failed: {}", e))??;
+    eprintln!("[Cookies] Cleared {} cookies for {}", removed, label);
+    Ok(removed)
+}
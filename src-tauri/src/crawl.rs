@@ -0,0 +1,174 @@
+// Crawling a site from a start page: pages are scraped level by level, breadth-first, following
+// the links each page has, until `max_depth` links away from the start or `max_pages` pages. Every
+// URL is visited once, scrapes wait on the per-host limits like any other, and each page is sent
+// to the frontend as a `crawl-page` event as soon as it is scraped.
+use std::collections::HashSet;
+
+use futures::stream::{self, StreamExt};
+use reqwest::Url;
+use tauri::{AppHandle, Emitter};
+
+use crate::{html, settings};
+
+const PAGE_EVENT: &str = "crawl-page";
+const DEFAULT_DEPTH: usize = 2;
+const MAX_DEPTH: usize = 10;
+const DEFAULT_PAGES: usize = 50;
+const MAX_PAGES: usize = 1000;
+// Links to these are files rather than pages; PDFs are scraped
+const SKIPPED_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "webp", "svg", "ico", "bmp", "mp3", "mp4", "webm", "avi", "mov", "zip", "gz", "tar",
+    "7z", "rar", "exe", "dmg", "msi", "apk", "iso", "css", "js", "json", "xml", "woff", "woff2", "ttf",
+];
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct CrawlPage {
+    start_url: String,
+    url: String,
+    // Links followed from the start page to get here
+    depth: usize,
+    // Position of the page in the crawl, from 1
+    page: usize,
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<crate::ScrapedContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrawledUrl {
+    url: String,
+    depth: usize,
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrawlSummary {
+    pages: Vec<CrawledUrl>,
+    succeeded: usize,
+    failed: usize,
+    // Links found but not visited because the page or depth limit was reached
+    unvisited: usize,
+}
+
+// The URL without its fragment, which only points into the same page
+fn normalize(url: &str) -> Option<Url> {
+    let mut url = Url::parse(url).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    url.set_fragment(None);
+    Some(url)
+}
+
+fn site(url: &Url) -> String {
+    url.host_str().unwrap_or_default().trim_start_matches("www.").to_lowercase()
+}
+
+fn is_page(url: &Url) -> bool {
+    let extension = url.path().rsplit_once('.').map(|(_, e)| e.to_lowercase()).unwrap_or_default();
+    !SKIPPED_EXTENSIONS.contains(&extension.as_str())
+}
+
+// Scrape pages breadth-first from `start_url`; `same_domain` (on by default) keeps the crawl on the
+// start page's site. `format` is "text" (default) or "markdown", and `limits` cuts long pages, as
+// for scrape_urls.
+#[tauri::command]
+pub async fn crawl_site(
+    app: AppHandle,
+    start_url: String,
+    max_depth: Option<usize>,
+    max_pages: Option<usize>,
+    same_domain: Option<bool>,
+    format: Option<html::Format>,
+    limits: Option<crate::truncation::ContentLimits>,
+) -> Result<CrawlSummary, String> {
+    let start = normalize(start_url.trim()).ok_or_else(|| format!("Invalid http(s) URL: {}", start_url))?;
+    let max_depth = max_depth.unwrap_or(DEFAULT_DEPTH).min(MAX_DEPTH);
+    let max_pages = max_pages.unwrap_or(DEFAULT_PAGES).clamp(1, MAX_PAGES);
+    let same_domain = same_domain.unwrap_or(true);
+    let start_site = site(&start);
+
+    let settings = settings::load(&app);
+    let mut options = crate::ScrapeOptions::for_app(&app, &settings);
+    options.format = format.unwrap_or_default();
+    options.max_chars = limits.and_then(|limits| limits.max_chars());
+    let batch = settings.scrape_max_concurrent.max(1);
+    eprintln!("[Crawl] Crawling {} to depth {}, at most {} pages", start, max_depth, max_pages);
+
+    let mut seen: HashSet<String> = HashSet::from([start.to_string()]);
+    let mut level = vec![start.to_string()];
+    let mut pages = Vec::new();
+    let mut unvisited = 0;
+    for depth in 0..=max_depth {
+        let room = max_pages - pages.len();
+        if level.len() > room {
+            unvisited += level.len() - room;
+            level.truncate(room);
+        }
+        let mut next = Vec::new();
+        // Links are only worth collecting while there is a level left to follow them to
+        options.collect_links = depth < max_depth;
+        // Up to `batch` pages in flight at any time, handled in the order of the level
+        let mut scrapes = stream::iter(std::mem::take(&mut level))
+            .map(|url| {
+                let options = options.clone();
+                async move {
+                    let result = crate::scrape_url_async(url.clone(), options).await;
+                    (url, result)
+                }
+            })
+            .buffered(batch);
+        while let Some((url, result)) = scrapes.next().await {
+            let links = result.content.as_ref().map(|c| c.links.clone()).unwrap_or_default();
+            for link in links.iter().filter_map(|link| normalize(link)) {
+                let allowed = !same_domain || site(&link) == start_site;
+                if allowed && is_page(&link) && seen.insert(link.to_string()) {
+                    next.push(link.to_string());
+                }
+            }
+            pages.push(CrawledUrl {
+                url: url.clone(),
+                depth,
+                success: result.success,
+                error: result.error.clone(),
+            });
+            let event = CrawlPage {
+                start_url: start.to_string(),
+                url: url.clone(),
+                depth,
+                page: pages.len(),
+                success: result.success,
+                content: result.content.map(|mut content| {
+                    content.links.clear();
+                    content
+                }),
+                error: result.error,
+            };
+            if let Err(e) = app.emit(PAGE_EVENT, event) {
+                eprintln!("[Crawl] Failed to emit page: {}", e);
+            }
+        }
+        if pages.len() >= max_pages || next.is_empty() {
+            unvisited += next.len();
+            break;
+        }
+        level = next;
+    }
+
+    let succeeded = pages.iter().filter(|p| p.success).count();
+    let failed = pages.len() - succeeded;
+    eprintln!("[Crawl] Done: {} pages scraped, {} failed, {} links left unvisited", succeeded, failed, unvisited);
+    Ok(CrawlSummary {
+        pages,
+        succeeded,
+        failed,
+        unvisited,
+    })
+}
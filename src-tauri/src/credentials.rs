@@ -0,0 +1,191 @@
+// Credentials for scraping private sites (internal wikis, docs behind a login). A credential is
+// saved once under a name: the secret goes to the OS keychain and only the name, kind and allowed
+// hosts to scrape_credentials.json in the profile's config. Fetches and scrapes refer to it by
+// name, so the secret never travels with a request from the frontend, and it is only ever sent to
+// the hosts it was saved for.
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use base64::Engine;
+use reqwest::Url;
+use tauri::State;
+
+use crate::encryption::KEYCHAIN_SERVICE;
+use crate::profiles::ProfileManager;
+
+const CREDENTIALS_FILE: &str = "scrape_credentials.json";
+// Headers a caller can't set: they belong to the connection or come from the credential
+const RESERVED_HEADERS: &[&str] = &["host", "content-length", "connection", "transfer-encoding", "authorization"];
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Secret {
+    // Authorization: Bearer <token>
+    Bearer { token: String },
+    // Authorization: Basic <username:password>
+    Basic { username: String, password: String },
+    // Any other header, such as X-API-Key or a session cookie
+    Header { name: String, value: String },
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialInfo {
+    pub name: String,
+    // bearer, basic or header
+    pub kind: String,
+    // Hosts the credential is sent to, subdomains included
+    pub hosts: Vec<String>,
+}
+
+// Extra request headers and a saved credential to send them with, for fetch_url and scrape_url
+#[derive(serde::Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestAuth {
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    // Name of a credential saved with set_scrape_credential
+    pub credential: Option<String>,
+}
+
+fn credentials_path(profiles: &ProfileManager) -> PathBuf {
+    profiles.config_dir().join(CREDENTIALS_FILE)
+}
+
+fn load_index(profiles: &ProfileManager) -> Result<Vec<CredentialInfo>, String> {
+    let path = credentials_path(profiles);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let text = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&text).map_err(|e| format!("Invalid credentials file: {}", e))
+}
+
+fn save_index(profiles: &ProfileManager, credentials: &[CredentialInfo]) -> Result<(), String> {
+    let path = credentials_path(profiles);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(credentials).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn secret_entry(profiles: &ProfileManager, name: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, &format!("scrape-credential-{}-{}", profiles.active_id(), name))
+        .map_err(|e| format!("Keychain unavailable: {}", e))
+}
+
+fn host_allowed(host: &str, allowed: &[String]) -> bool {
+    let host = host.to_lowercase();
+    allowed.iter().any(|a| {
+        let a = a.trim().trim_start_matches("*.").to_lowercase();
+        host == a || host.ends_with(&format!(".{}", a))
+    })
+}
+
+fn header(name: &str, value: &str) -> Result<(String, String), String> {
+    reqwest::header::HeaderName::from_bytes(name.as_bytes()).map_err(|_| format!("Invalid header name: {}", name))?;
+    reqwest::header::HeaderValue::from_str(value).map_err(|_| format!("Invalid value for header {}", name))?;
+    Ok((name.to_string(), value.to_string()))
+}
+
+impl Secret {
+    fn kind(&self) -> &'static str {
+        match self {
+            Secret::Bearer { .. } => "bearer",
+            Secret::Basic { .. } => "basic",
+            Secret::Header { .. } => "header",
+        }
+    }
+
+    fn header(&self) -> Result<(String, String), String> {
+        match self {
+            Secret::Bearer { token } => header("Authorization", &format!("Bearer {}", token.trim())),
+            Secret::Basic { username, password } => {
+                let encoded = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password));
+                header("Authorization", &format!("Basic {}", encoded))
+            }
+            Secret::Header { name, value } => header(name.trim(), value.trim()),
+        }
+    }
+}
+
+// The headers to send to `url`: the caller's own, then the credential's, which is only resolved
+// when `url` is on one of its hosts
+pub fn resolve(profiles: &ProfileManager, auth: &RequestAuth, url: &Url) -> Result<Vec<(String, String)>, String> {
+    let mut headers = Vec::new();
+    for (name, value) in &auth.headers {
+        if RESERVED_HEADERS.contains(&name.trim().to_lowercase().as_str()) {
+            return Err(format!("The {} header can't be set directly; save it as a credential", name));
+        }
+        headers.push(header(name.trim(), value)?);
+    }
+    let Some(name) = auth.credential.as_deref().filter(|n| !n.trim().is_empty()) else { return Ok(headers) };
+    let info = load_index(profiles)?
+        .into_iter()
+        .find(|c| c.name == name)
+        .ok_or_else(|| format!("No credential named {}", name))?;
+    let host = url.host_str().unwrap_or_default();
+    if !host_allowed(host, &info.hosts) {
+        return Err(format!("Credential {} isn't allowed for {}", name, host));
+    }
+    let stored = secret_entry(profiles, name)?
+        .get_password()
+        .map_err(|e| format!("Failed to read credential {} from the keychain: {}", name, e))?;
+    let secret: Secret = serde_json::from_str(&stored).map_err(|e| format!("Invalid credential {}: {}", name, e))?;
+    headers.push(secret.header()?);
+    Ok(headers)
+}
+
+// Headers for a request to `url` given by the app, with the active profile's credentials
+pub fn resolve_for_app(app: &tauri::AppHandle, auth: &RequestAuth, url: &Url) -> Result<Vec<(String, String)>, String> {
+    use tauri::Manager;
+    let profiles = app.try_state::<ProfileManager>().ok_or("Profiles are not initialized")?;
+    resolve(&profiles, auth, url)
+}
+
+#[tauri::command]
+pub fn list_scrape_credentials(profiles: State<'_, ProfileManager>) -> Result<Vec<CredentialInfo>, String> {
+    load_index(&profiles)
+}
+
+// Save a credential under `name` for `hosts`, replacing one of the same name; without a secret
+// the credential is removed
+#[tauri::command]
+pub fn set_scrape_credential(
+    profiles: State<'_, ProfileManager>,
+    name: String,
+    hosts: Vec<String>,
+    secret: Option<Secret>,
+) -> Result<(), String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("A credential needs a name".to_string());
+    }
+    let mut index = load_index(&profiles)?;
+    index.retain(|c| c.name != name);
+    let entry = secret_entry(&profiles, &name)?;
+    let Some(secret) = secret else {
+        match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => {}
+            Err(err) => return Err(format!("Failed to remove credential {}: {}", name, err)),
+        }
+        return save_index(&profiles, &index);
+    };
+    let hosts: Vec<String> = hosts.iter().map(|h| h.trim().to_lowercase()).filter(|h| !h.is_empty()).collect();
+    if hosts.is_empty() {
+        return Err("A credential needs at least one host to be sent to".to_string());
+    }
+    // Catch a malformed header now rather than at the first scrape
+    secret.header()?;
+    let json = serde_json::to_string(&secret).map_err(|e| e.to_string())?;
+    entry
+        .set_password(&json)
+        .map_err(|e| format!("Failed to store credential {}: {}", name, e))?;
+    index.push(CredentialInfo {
+        name,
+        kind: secret.kind().to_string(),
+        hosts,
+    });
+    save_index(&profiles, &index)
+}
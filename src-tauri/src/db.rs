@@ -0,0 +1,334 @@
+// Shared SQLite database stored in the app data directory
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rusqlite::{params, Connection};
+
+pub const DB_FILE: &str = "openchat.db";
+// Latest migration; databases at a higher version come from a newer build
+pub const SCHEMA_VERSION: i64 = MIGRATIONS.len() as i64;
+const LOCKED_ERROR: &str = "Storage is encrypted and locked; unlock it with your passphrase";
+
+// Applied in order; a migration's version is its position in this list (starting at 1).
+// Never edit a migration that has shipped, add a new one instead.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        name: "baseline",
+        sql: include_str!("migrations/0001_baseline.sql"),
+    },
+    Migration {
+        name: "message_pins",
+        sql: include_str!("migrations/0002_message_pins.sql"),
+    },
+    Migration {
+        name: "trash",
+        sql: include_str!("migrations/0003_trash.sql"),
+    },
+    Migration {
+        name: "vector_store",
+        sql: include_str!("migrations/0004_vector_store.sql"),
+    },
+    Migration {
+        name: "vector_keywords",
+        sql: include_str!("migrations/0005_vector_keywords.sql"),
+    },
+    Migration {
+        name: "watched_sources",
+        sql: include_str!("migrations/0006_watched_sources.sql"),
+    },
+    Migration {
+        name: "citations",
+        sql: include_str!("migrations/0007_citations.sql"),
+    },
+    Migration {
+        name: "ingested_files",
+        sql: include_str!("migrations/0008_ingested_files.sql"),
+    },
+    Migration {
+        name: "semantic_cache",
+        sql: include_str!("migrations/0009_semantic_cache.sql"),
+    },
+];
+
+const MIGRATIONS_TABLE: &str = "
+    CREATE TABLE IF NOT EXISTS schema_migrations (
+        version INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        applied_at TEXT NOT NULL
+    );
+";
+
+struct Migration {
+    name: &'static str,
+    sql: &'static str,
+}
+
+fn schema_version(conn: &Connection) -> rusqlite::Result<i64> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+// Apply pending migrations in order, each in its own transaction; progress is kept in user_version
+fn migrate(conn: &Connection) -> Result<(), String> {
+    let current = schema_version(conn).map_err(|e| format!("Failed to read schema version: {}", e))?;
+    if current > SCHEMA_VERSION {
+        return Err(format!(
+            "Database schema version {} is newer than this build supports ({})",
+            current, SCHEMA_VERSION
+        ));
+    }
+    conn.execute_batch(MIGRATIONS_TABLE)
+        .map_err(|e| format!("Failed to create migrations table: {}", e))?;
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(current as usize) {
+        let version = index as i64 + 1;
+        let apply = || -> rusqlite::Result<()> {
+            let tx = conn.unchecked_transaction()?;
+            tx.execute_batch(migration.sql)?;
+            tx.execute(
+                "INSERT OR REPLACE INTO schema_migrations (version, name, applied_at) VALUES (?1, ?2, ?3)",
+                params![version, migration.name, chrono::Utc::now().to_rfc3339()],
+            )?;
+            tx.execute_batch(&format!("PRAGMA user_version = {}", version))?;
+            tx.commit()
+        };
+        apply().map_err(|e| format!("Migration {} ({}) failed: {}", version, migration.name, e))?;
+        eprintln!("[DB] Applied migration {} ({})", version, migration.name);
+    }
+    Ok(())
+}
+
+static ID_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+// Unique, roughly time-ordered id for text primary keys
+pub fn new_id(prefix: &str) -> String {
+    let millis = chrono::Utc::now().timestamp_millis();
+    let seq = ID_COUNTER.fetch_add(1, Ordering::Relaxed) % 10_000;
+    format!("{}-{}-{:04}", prefix, millis, seq)
+}
+
+// Open a connection, applying the SQLCipher key first when the database is encrypted
+fn open_connection(path: &Path, key: Option<&str>) -> Result<Connection, String> {
+    let conn = Connection::open(path).map_err(|e| format!("Failed to open database: {}", e))?;
+    if let Some(key) = key {
+        conn.execute_batch(&format!("PRAGMA key = \"x'{}'\";", key))
+            .map_err(|e| format!("Failed to apply database key: {}", e))?;
+        // The key is only checked on first read
+        conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))
+            .map_err(|_| "Wrong passphrase or corrupted database".to_string())?;
+    }
+    conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA foreign_keys = ON;")
+        .map_err(|e| format!("Failed to configure database: {}", e))?;
+    migrate(&conn)?;
+    Ok(conn)
+}
+
+#[derive(Clone)]
+pub struct Database {
+    conn: Arc<Mutex<Connection>>,
+    path: Arc<Mutex<PathBuf>>,
+    // Hex SQLCipher key when the database is encrypted
+    key: Arc<Mutex<Option<String>>>,
+    // Set while an encrypted database waits for its passphrase
+    locked: Arc<AtomicBool>,
+}
+
+impl Database {
+    pub fn open(data_dir: &Path, key: Option<&str>) -> Result<Self, String> {
+        std::fs::create_dir_all(data_dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+        let path = data_dir.join(DB_FILE);
+        eprintln!("[DB] Opening {}", path.display());
+
+        let conn = open_connection(&path, key)?;
+        Ok(Database {
+            conn: Arc::new(Mutex::new(conn)),
+            path: Arc::new(Mutex::new(path)),
+            key: Arc::new(Mutex::new(key.map(str::to_string))),
+            locked: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    // An encrypted database whose key isn't available yet; every query fails until `unlock`
+    pub fn locked(data_dir: &Path) -> Result<Self, String> {
+        let conn = Connection::open_in_memory().map_err(|e| format!("Failed to open database: {}", e))?;
+        eprintln!("[DB] Database is encrypted and locked");
+        Ok(Database {
+            conn: Arc::new(Mutex::new(conn)),
+            path: Arc::new(Mutex::new(data_dir.join(DB_FILE))),
+            key: Arc::new(Mutex::new(None)),
+            locked: Arc::new(AtomicBool::new(true)),
+        })
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::SeqCst)
+    }
+
+    pub fn key(&self) -> Option<String> {
+        self.key.lock().ok().and_then(|k| k.clone())
+    }
+
+    pub fn path(&self) -> Result<PathBuf, String> {
+        self.path.lock().map(|p| p.clone()).map_err(|_| "Database lock poisoned".to_string())
+    }
+
+    // Take over another database's connection, so every clone of this handle now uses it
+    pub fn replace_with(&self, other: Database) -> Result<(), String> {
+        fn poisoned<E>(_: E) -> String {
+            "Database lock poisoned".to_string()
+        }
+        std::mem::swap(&mut *self.conn.lock().map_err(poisoned)?, &mut *other.conn.lock().map_err(poisoned)?);
+        std::mem::swap(&mut *self.path.lock().map_err(poisoned)?, &mut *other.path.lock().map_err(poisoned)?);
+        std::mem::swap(&mut *self.key.lock().map_err(poisoned)?, &mut *other.key.lock().map_err(poisoned)?);
+        self.locked.store(other.is_locked(), Ordering::SeqCst);
+        Ok(())
+    }
+
+    pub fn unlock(&self, key: &str) -> Result<(), String> {
+        let conn = open_connection(&self.path()?, Some(key))?;
+        *self.conn.lock().map_err(|_| "Database lock poisoned".to_string())? = conn;
+        *self.key.lock().map_err(|_| "Database lock poisoned".to_string())? = Some(key.to_string());
+        self.locked.store(false, Ordering::SeqCst);
+        eprintln!("[DB] Database unlocked");
+        Ok(())
+    }
+
+    // Rewrite the database file under a new key (None for plaintext) and reopen it
+    pub fn rekey(&self, new_key: Option<&str>) -> Result<(), String> {
+        if self.is_locked() {
+            return Err(LOCKED_ERROR.to_string());
+        }
+        let path = self.path()?;
+        let mut conn = self.conn.lock().map_err(|_| "Database lock poisoned".to_string())?;
+        let migrated = path.with_extension("db.rekey");
+        let _ = std::fs::remove_file(&migrated);
+
+        // An empty key attaches a plaintext database; the hex key is passed raw so it isn't re-derived
+        let attach_key = new_key.map(|k| format!("x'{}'", k)).unwrap_or_default();
+        conn.execute(
+            &format!("ATTACH DATABASE ?1 AS migrated KEY \"{}\"", attach_key),
+            params![migrated.to_string_lossy()],
+        )
+        .and_then(|_| conn.query_row("SELECT sqlcipher_export('migrated')", [], |_| Ok(())))
+        // The export copies tables but not the header's schema version
+        .and_then(|_| schema_version(&conn))
+        .and_then(|version| conn.execute_batch(&format!("PRAGMA migrated.user_version = {}", version)))
+        .and_then(|_| conn.execute_batch("DETACH DATABASE migrated"))
+        .map_err(|e| format!("Failed to re-encrypt database: {}", e))?;
+
+        // Close the old file before replacing it
+        *conn = Connection::open_in_memory().map_err(|e| format!("Failed to open database: {}", e))?;
+        for suffix in ["-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+        std::fs::rename(&migrated, &path).map_err(|e| format!("Failed to replace database: {}", e))?;
+        *conn = open_connection(&path, new_key)?;
+        *self.key.lock().map_err(|_| "Database lock poisoned".to_string())? = new_key.map(str::to_string);
+        Ok(())
+    }
+
+    // Open another database file with this database's key
+    pub fn open_sibling(&self, path: &Path) -> Result<Connection, String> {
+        let conn = Connection::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        if let Some(key) = self.key() {
+            conn.execute_batch(&format!("PRAGMA key = \"x'{}'\";", key))
+                .map_err(|e| format!("Failed to apply database key: {}", e))?;
+        }
+        Ok(conn)
+    }
+
+    // Write a consistent copy of the database (encrypted with the same key) to `path`
+    pub fn snapshot_to(&self, path: &Path) -> Result<(), String> {
+        let mut target = self.open_sibling(path)?;
+        self.with_conn(|conn| {
+            rusqlite::backup::Backup::new(conn, &mut target)?.run_to_completion(256, Duration::ZERO, None)
+        })
+    }
+
+    // Replace the live database contents with the database file at `path`
+    pub fn restore_from(&self, path: &Path) -> Result<(), String> {
+        if self.is_locked() {
+            return Err(LOCKED_ERROR.to_string());
+        }
+        let source = self.open_sibling(path)?;
+        let mut conn = self.conn.lock().map_err(|_| "Database lock poisoned".to_string())?;
+        rusqlite::backup::Backup::new(&source, &mut conn)
+            .and_then(|backup| backup.run_to_completion(256, Duration::ZERO, None))
+            .map_err(|e| format!("Failed to restore database: {}", e))?;
+        migrate(&conn)
+    }
+
+    // Run a closure with the connection, mapping SQLite errors to strings
+    pub fn with_conn<T>(&self, f: impl FnOnce(&Connection) -> rusqlite::Result<T>) -> Result<T, String> {
+        if self.is_locked() {
+            return Err(LOCKED_ERROR.to_string());
+        }
+        let conn = self.conn.lock().map_err(|_| "Database lock poisoned".to_string())?;
+        f(&conn).map_err(|e| format!("Database error: {}", e))
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedMigration {
+    version: i64,
+    name: String,
+    applied_at: String,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbInfo {
+    path: String,
+    schema_version: i64,
+    latest_schema_version: i64,
+    migrations: Vec<AppliedMigration>,
+    // Database file plus its write-ahead log
+    size_bytes: u64,
+    page_size: i64,
+    page_count: i64,
+    free_pages: i64,
+    encrypted: bool,
+    // "ok", or the problems SQLite found
+    integrity: Vec<String>,
+}
+
+// Storage diagnostics; `full` runs the complete integrity check instead of the quick one
+#[tauri::command]
+pub fn get_db_info(db: tauri::State<'_, Database>, full: Option<bool>) -> Result<DbInfo, String> {
+    let path = db.path()?;
+    let size_bytes = ["", "-wal"]
+        .iter()
+        .filter_map(|suffix| std::fs::metadata(format!("{}{}", path.display(), suffix)).ok())
+        .map(|meta| meta.len())
+        .sum();
+    let check = if full.unwrap_or(false) { "integrity_check" } else { "quick_check" };
+    db.with_conn(|conn| {
+        let pragma = |name: &str| conn.query_row(&format!("PRAGMA {}", name), [], |row| row.get::<_, i64>(0));
+        let mut stmt = conn.prepare("SELECT version, name, applied_at FROM schema_migrations ORDER BY version")?;
+        let migrations = stmt
+            .query_map([], |row| {
+                Ok(AppliedMigration {
+                    version: row.get(0)?,
+                    name: row.get(1)?,
+                    applied_at: row.get(2)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let mut stmt = conn.prepare(&format!("PRAGMA {}", check))?;
+        let integrity = stmt.query_map([], |row| row.get(0))?.collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(DbInfo {
+            path: path.to_string_lossy().to_string(),
+            schema_version: schema_version(conn)?,
+            latest_schema_version: SCHEMA_VERSION,
+            migrations,
+            size_bytes,
+            page_size: pragma("page_size")?,
+            page_count: pragma("page_count")?,
+            free_pages: pragma("freelist_count")?,
+            encrypted: db.key().is_some(),
+            integrity,
+        })
+    })
+}
@@ -0,0 +1,104 @@
+// Duplicate detection for ingested chunks. Exact duplicates are found by hashing the chunk text
+// after normalizing case and whitespace; near duplicates (the same passage with small edits, such
+// as a boilerplate footer with another date) by the Hamming distance of 64-bit simhashes over word
+// trigrams. Hashes are stored in chunk metadata, so they must stay stable across builds.
+use std::collections::HashMap;
+
+use rusqlite::params;
+use sha2::{Digest, Sha256};
+
+use crate::db::Database;
+
+// Simhashes at most this many bits apart count as near duplicates
+const NEAR_DUPLICATE_BITS: u32 = 3;
+// Words per shingle
+const SHINGLE: usize = 3;
+
+fn normalized_words(text: &str) -> Vec<String> {
+    text.split_whitespace().map(|w| w.to_lowercase()).collect()
+}
+
+// Hex SHA-256 of the text with case and whitespace normalized
+pub fn chunk_hash(text: &str) -> String {
+    format!("{:x}", Sha256::digest(normalized_words(text).join(" ").as_bytes()))
+}
+
+// FNV-1a, which unlike std's hasher is fixed
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| (hash ^ *b as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+pub fn simhash(text: &str) -> u64 {
+    let words = normalized_words(text);
+    let mut weights = [0i32; 64];
+    let mut add = |shingle: &[String]| {
+        let hash = fnv1a(shingle.join(" ").as_bytes());
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            *weight += if hash >> bit & 1 == 1 { 1 } else { -1 };
+        }
+    };
+    if words.len() < SHINGLE {
+        add(&words);
+    } else {
+        words.windows(SHINGLE).for_each(&mut add);
+    }
+    weights.iter().enumerate().fold(0, |hash, (bit, weight)| if *weight > 0 { hash | 1 << bit } else { hash })
+}
+
+pub fn format_simhash(hash: u64) -> String {
+    format!("{:016x}", hash)
+}
+
+// Hashes of the chunks already in a collection, and of those added since
+#[derive(Default)]
+pub struct SeenChunks {
+    // Chunk hash to the id of the chunk holding that text
+    exact: HashMap<String, String>,
+    near: Vec<(u64, String)>,
+}
+
+impl SeenChunks {
+    // Chunks of the collection, except those of `skip_source`, which are about to be replaced
+    pub fn load(db: &Database, collection: &str, skip_source: &str) -> Result<Self, String> {
+        let rows: Vec<(String, Option<String>, Option<String>)> = db.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, json_extract(metadata, '$.chunkHash'), json_extract(metadata, '$.simhash')
+                 FROM vector_items
+                 WHERE collection = ?1 AND json_extract(metadata, '$.source') IS NOT ?2",
+            )?;
+            let rows = stmt.query_map(params![collection, skip_source], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+            rows.collect()
+        })?;
+        let mut seen = SeenChunks::default();
+        for (id, hash, simhash) in rows {
+            if let Some(hash) = hash {
+                seen.exact.entry(hash).or_insert_with(|| id.clone());
+            }
+            if let Some(simhash) = simhash.and_then(|s| u64::from_str_radix(&s, 16).ok()) {
+                seen.near.push((simhash, id));
+            }
+        }
+        Ok(seen)
+    }
+
+    pub fn duplicate_of(&self, hash: &str) -> Option<&str> {
+        self.exact.get(hash).map(String::as_str)
+    }
+
+    // Closest chunk within the near-duplicate distance
+    pub fn near_duplicate_of(&self, simhash: u64) -> Option<&str> {
+        self.near
+            .iter()
+            .map(|(other, id)| ((other ^ simhash).count_ones(), id))
+            .filter(|(distance, _)| *distance <= NEAR_DUPLICATE_BITS)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, id)| id.as_str())
+    }
+
+    pub fn insert(&mut self, id: &str, hash: String, simhash: Option<u64>) {
+        self.exact.entry(hash).or_insert_with(|| id.to_string());
+        if let Some(simhash) = simhash {
+            self.near.push((simhash, id.to_string()));
+        }
+    }
+}
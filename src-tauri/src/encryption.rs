@@ -0,0 +1,258 @@
+// Encryption at rest for conversations and attachments.
+//
+// The database is encrypted with SQLCipher and attachment blobs with AES-256-GCM. Both keys come
+// from one 32-byte key derived from the user's passphrase with Argon2id. The derived key is kept in
+// the OS keychain (one entry per profile, told apart by the salt) so the app opens without a
+// prompt; when the keychain has no entry (another machine, a wiped keychain) storage stays locked
+// until `unlock_storage` is called.
+use std::path::Path;
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::OsRng;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, State};
+
+use crate::blobs::{to_hex, BlobStore};
+use crate::db::Database;
+use crate::profiles;
+
+const CONFIG_FILE: &str = "encryption.json";
+pub const KEYCHAIN_SERVICE: &str = "openchat";
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+// Lives next to the database, since it describes how to open it
+#[derive(serde::Serialize, serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct EncryptionConfig {
+    enabled: bool,
+    // Hex Argon2 salt
+    salt: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptionStatus {
+    enabled: bool,
+    locked: bool,
+    // Blobs rewritten by the last enable/disable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    migrated_blobs: Option<usize>,
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn load_config(data_dir: &Path) -> Result<EncryptionConfig, String> {
+    let path = data_dir.join(CONFIG_FILE);
+    if !path.exists() {
+        return Ok(EncryptionConfig::default());
+    }
+    let text = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&text).map_err(|e| format!("Invalid encryption config: {}", e))
+}
+
+fn save_config(data_dir: &Path, config: &EncryptionConfig) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    std::fs::write(data_dir.join(CONFIG_FILE), json).map_err(|e| format!("Failed to save encryption config: {}", e))
+}
+
+fn derive_key(passphrase: &str, salt: &str) -> Result<[u8; 32], String> {
+    let salt = from_hex(salt).ok_or("Invalid salt in encryption config")?;
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+        .map_err(|e| format!("Failed to derive key: {}", e))?;
+    Ok(key)
+}
+
+// Separate key for blobs so the SQLCipher key is never used for anything else
+fn blob_key(key: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"openchat-blob");
+    hasher.update(key);
+    hasher.finalize().into()
+}
+
+fn keychain_entry(config: &EncryptionConfig) -> Result<keyring::Entry, String> {
+    let salt = config.salt.as_deref().ok_or("Encryption config has no salt")?;
+    keyring::Entry::new(KEYCHAIN_SERVICE, &format!("storage-key-{}", salt))
+        .map_err(|e| format!("Keychain unavailable: {}", e))
+}
+
+fn keychain_key(config: &EncryptionConfig) -> Option<[u8; 32]> {
+    let hex = keychain_entry(config).ok()?.get_password().ok()?;
+    from_hex(&hex)?.try_into().ok()
+}
+
+// Failing to remember the key only means a passphrase prompt on the next start
+fn remember_key(config: &EncryptionConfig, key: &[u8; 32]) {
+    let stored = keychain_entry(config).and_then(|entry| entry.set_password(&to_hex(key)).map_err(|e| e.to_string()));
+    if let Err(err) = stored {
+        eprintln!("[Encryption] Could not store key in keychain: {}", err);
+    }
+}
+
+fn forget_key(config: &EncryptionConfig) {
+    if let Ok(entry) = keychain_entry(config) {
+        let _ = entry.delete_credential();
+    }
+}
+
+// Open the database in `data_dir`, using the keychain key when it is encrypted.
+// Also returns the blob key; locked storage has none.
+fn open_database(data_dir: &Path) -> Result<(Database, Option<[u8; 32]>), String> {
+    let config = load_config(data_dir)?;
+    if !config.enabled {
+        return Ok((Database::open(data_dir, None)?, None));
+    }
+    let Some(key) = keychain_key(&config) else {
+        eprintln!("[Encryption] No key in keychain, storage stays locked");
+        return Ok((Database::locked(data_dir)?, None));
+    };
+    match Database::open(data_dir, Some(&to_hex(&key))) {
+        Ok(db) => Ok((db, Some(blob_key(&key)))),
+        Err(err) => {
+            eprintln!("[Encryption] Keychain key rejected ({}), storage stays locked", err);
+            Ok((Database::locked(data_dir)?, None))
+        }
+    }
+}
+
+pub fn open_storage(data_dir: &Path) -> Result<(Database, BlobStore), String> {
+    let (db, key) = open_database(data_dir)?;
+    Ok((db, BlobStore::new(data_dir.join("blobs"), key)))
+}
+
+// Re-point the shared database and blob store at another data dir
+pub fn switch_storage(data_dir: &Path, db: &Database, blobs: &BlobStore) -> Result<(), String> {
+    let (opened, key) = open_database(data_dir)?;
+    db.replace_with(opened)?;
+    blobs.switch_to(data_dir.join("blobs"), key);
+    Ok(())
+}
+
+// Derive the key for `passphrase` and check it against the open database
+fn verify_passphrase(db: &Database, config: &EncryptionConfig, passphrase: &str) -> Result<[u8; 32], String> {
+    let salt = config.salt.as_deref().ok_or("Encryption config has no salt")?;
+    let key = derive_key(passphrase, salt)?;
+    if db.key().as_deref() != Some(to_hex(&key).as_str()) {
+        return Err("Wrong passphrase".to_string());
+    }
+    Ok(key)
+}
+
+#[tauri::command]
+pub fn get_encryption_status(app: AppHandle, db: State<'_, Database>) -> Result<EncryptionStatus, String> {
+    let config = load_config(&profiles::data_dir(&app)?)?;
+    Ok(EncryptionStatus {
+        enabled: config.enabled,
+        locked: db.is_locked(),
+        migrated_blobs: None,
+    })
+}
+
+// Encrypt the existing plaintext database and blobs in place
+#[tauri::command]
+pub fn enable_encryption(
+    app: AppHandle,
+    db: State<'_, Database>,
+    blobs: State<'_, BlobStore>,
+    passphrase: String,
+) -> Result<EncryptionStatus, String> {
+    let data_dir = profiles::data_dir(&app)?;
+    if load_config(&data_dir)?.enabled {
+        return Err("Encryption is already enabled".to_string());
+    }
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(format!("Passphrase must be at least {} characters", MIN_PASSPHRASE_LEN));
+    }
+
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let config = EncryptionConfig {
+        enabled: true,
+        salt: Some(to_hex(&salt)),
+    };
+    let key = derive_key(&passphrase, config.salt.as_deref().unwrap_or_default())?;
+
+    db.rekey(Some(&to_hex(&key)))?;
+    // Saved as soon as the database is encrypted so a failure below can't leave it unopenable.
+    // Blobs are read whether encrypted or not, so an interrupted migration leaves them all readable.
+    save_config(&data_dir, &config)?;
+    remember_key(&config, &key);
+    let migrated = blobs.reencrypt_all(Some(blob_key(&key)))?;
+
+    eprintln!("[Encryption] Enabled, encrypted {} blobs", migrated);
+    Ok(EncryptionStatus {
+        enabled: true,
+        locked: false,
+        migrated_blobs: Some(migrated),
+    })
+}
+
+// Decrypt everything back to plaintext; requires the current passphrase
+#[tauri::command]
+pub fn disable_encryption(
+    app: AppHandle,
+    db: State<'_, Database>,
+    blobs: State<'_, BlobStore>,
+    passphrase: String,
+) -> Result<EncryptionStatus, String> {
+    let data_dir = profiles::data_dir(&app)?;
+    let config = load_config(&data_dir)?;
+    if !config.enabled {
+        return Err("Encryption is not enabled".to_string());
+    }
+    if db.is_locked() {
+        return Err("Unlock storage before disabling encryption".to_string());
+    }
+    verify_passphrase(&db, &config, &passphrase)?;
+
+    // Blobs first: plaintext blobs stay readable whichever state the database ends up in
+    let migrated = blobs.reencrypt_all(None)?;
+    db.rekey(None)?;
+    save_config(&data_dir, &EncryptionConfig::default())?;
+    forget_key(&config);
+
+    eprintln!("[Encryption] Disabled, decrypted {} blobs", migrated);
+    Ok(EncryptionStatus {
+        enabled: false,
+        locked: false,
+        migrated_blobs: Some(migrated),
+    })
+}
+
+// Open locked storage with the passphrase and remember the key in the keychain
+#[tauri::command]
+pub fn unlock_storage(
+    app: AppHandle,
+    db: State<'_, Database>,
+    blobs: State<'_, BlobStore>,
+    passphrase: String,
+) -> Result<EncryptionStatus, String> {
+    let config = load_config(&profiles::data_dir(&app)?)?;
+    if !config.enabled {
+        return Err("Encryption is not enabled".to_string());
+    }
+    if !db.is_locked() {
+        return Err("Storage is already unlocked".to_string());
+    }
+    let salt = config.salt.as_deref().ok_or("Encryption config has no salt")?;
+    let key = derive_key(&passphrase, salt)?;
+    db.unlock(&to_hex(&key))?;
+    blobs.set_key(Some(blob_key(&key)));
+    remember_key(&config, &key);
+
+    Ok(EncryptionStatus {
+        enabled: true,
+        locked: false,
+        migrated_blobs: None,
+    })
+}
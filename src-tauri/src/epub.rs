@@ -0,0 +1,212 @@
+// EPUB reading for ingestion: chapters in spine (reading) order, titled from the table of contents
+// (the EPUB 3 nav document or the EPUB 2 NCX), each rendered to text like an HTML file.
+use std::collections::HashMap;
+use std::io::{Read, Seek};
+use std::path::Path;
+
+use zip::ZipArchive;
+
+use crate::html;
+use crate::xml::{attr, local_name, read_part, resolve_target, walk, Node};
+
+pub struct Chapter {
+    pub title: Option<String>,
+    pub text: String,
+}
+
+pub struct EpubBook {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub chapters: Vec<Chapter>,
+}
+
+struct ManifestItem {
+    path: String,
+    media_type: String,
+    properties: String,
+}
+
+#[derive(Default)]
+struct Package {
+    title: Option<String>,
+    author: Option<String>,
+    manifest: HashMap<String, ManifestItem>,
+    spine: Vec<String>,
+    // Manifest id of the EPUB 2 table of contents
+    ncx: Option<String>,
+}
+
+fn folder(path: &str) -> &str {
+    path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("")
+}
+
+// Archive path of an href in a file inside `dir`, without its fragment
+fn href_path(dir: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or(href);
+    let decoded = urlencoding::decode(href).map(|h| h.into_owned()).unwrap_or_else(|_| href.to_string());
+    resolve_target(dir, &decoded)
+}
+
+fn package_path<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<String, String> {
+    let container = read_part(archive, "META-INF/container.xml")?.ok_or("Not an EPUB: META-INF/container.xml is missing")?;
+    let mut path = None;
+    walk(&container, |node| {
+        if let Node::Open(e) = node {
+            if local_name(e) == b"rootfile" && path.is_none() {
+                path = attr(e, "full-path");
+            }
+        }
+    })?;
+    path.ok_or_else(|| "EPUB container names no package file".to_string())
+}
+
+fn read_package(xml: &str, dir: &str) -> Result<Package, String> {
+    let mut package = Package::default();
+    let mut in_metadata = false;
+    // Dublin Core element whose text is being read
+    let mut field: Option<&'static str> = None;
+    let mut value = String::new();
+    walk(xml, |node| match node {
+        Node::Open(e) => match local_name(e) {
+            b"metadata" => in_metadata = true,
+            b"title" if in_metadata => field = Some("title"),
+            b"creator" if in_metadata => field = Some("creator"),
+            b"item" => {
+                if let (Some(id), Some(href)) = (attr(e, "id"), attr(e, "href")) {
+                    package.manifest.insert(
+                        id,
+                        ManifestItem {
+                            path: href_path(dir, &href),
+                            media_type: attr(e, "media-type").unwrap_or_default(),
+                            properties: attr(e, "properties").unwrap_or_default(),
+                        },
+                    );
+                }
+            }
+            b"spine" => package.ncx = attr(e, "toc"),
+            // Non-linear items (footnotes, answer keys) are outside the reading order
+            b"itemref" if attr(e, "linear").as_deref() != Some("no") => {
+                if let Some(id) = attr(e, "idref") {
+                    package.spine.push(id);
+                }
+            }
+            _ => {}
+        },
+        Node::Text(text) if field.is_some() => value.push_str(text),
+        Node::Close(b"metadata") => in_metadata = false,
+        Node::Close(name @ (b"title" | b"creator")) if field.is_some() => {
+            let text = value.split_whitespace().collect::<Vec<_>>().join(" ");
+            let slot = if name == b"title" { &mut package.title } else { &mut package.author };
+            if slot.is_none() && !text.is_empty() {
+                *slot = Some(text);
+            }
+            field = None;
+            value.clear();
+        }
+        _ => {}
+    })?;
+    Ok(package)
+}
+
+// Chapter titles by file from the EPUB 3 nav document; only its "toc" nav counts
+fn nav_titles(xml: &str, dir: &str, titles: &mut HashMap<String, String>) -> Result<(), String> {
+    let mut in_toc = false;
+    let mut link: Option<String> = None;
+    let mut label = String::new();
+    walk(xml, |node| match node {
+        Node::Open(e) => match local_name(e) {
+            b"nav" => in_toc = attr(e, "epub:type").is_some_and(|t| t.split_whitespace().any(|t| t == "toc")),
+            b"a" if in_toc => {
+                link = attr(e, "href").map(|href| href_path(dir, &href));
+                label.clear();
+            }
+            _ => {}
+        },
+        Node::Text(text) if link.is_some() => label.push_str(text),
+        Node::Close(b"a") => {
+            if let Some(path) = link.take() {
+                let label = label.split_whitespace().collect::<Vec<_>>().join(" ");
+                if !label.is_empty() {
+                    titles.entry(path).or_insert(label);
+                }
+            }
+        }
+        Node::Close(b"nav") => in_toc = false,
+        _ => {}
+    })
+}
+
+// Chapter titles by file from the EPUB 2 NCX; each navPoint's label precedes its content link
+fn ncx_titles(xml: &str, dir: &str, titles: &mut HashMap<String, String>) -> Result<(), String> {
+    let mut in_label = false;
+    let mut label = String::new();
+    walk(xml, |node| match node {
+        Node::Open(e) => match local_name(e) {
+            b"navLabel" => {
+                in_label = true;
+                label.clear();
+            }
+            b"content" => {
+                let text = label.split_whitespace().collect::<Vec<_>>().join(" ");
+                if let (Some(src), false) = (attr(e, "src"), text.is_empty()) {
+                    titles.entry(href_path(dir, &src)).or_insert(text);
+                }
+            }
+            _ => {}
+        },
+        Node::Text(text) if in_label => label.push_str(text),
+        Node::Close(b"navLabel") => in_label = false,
+        _ => {}
+    })
+}
+
+pub fn load(path: &Path) -> Result<EpubBook, String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("Not an EPUB: {}", e))?;
+    let package_path = package_path(&mut archive)?;
+    let package_xml = read_part(&mut archive, &package_path)?.ok_or_else(|| format!("{} is missing", package_path))?;
+    let package = read_package(&package_xml, folder(&package_path))?;
+
+    let mut titles = HashMap::new();
+    if let Some(nav) = package.manifest.values().find(|item| item.properties.split_whitespace().any(|p| p == "nav")) {
+        if let Some(xml) = read_part(&mut archive, &nav.path)? {
+            nav_titles(&xml, folder(&nav.path), &mut titles)?;
+        }
+    }
+    if let Some(ncx) = package.ncx.as_ref().and_then(|id| package.manifest.get(id)) {
+        if let Some(xml) = read_part(&mut archive, &ncx.path)? {
+            ncx_titles(&xml, folder(&ncx.path), &mut titles)?;
+        }
+    }
+
+    let mut chapters = Vec::new();
+    for id in &package.spine {
+        let Some(item) = package.manifest.get(id) else {
+            continue;
+        };
+        if !item.media_type.contains("html") {
+            continue;
+        }
+        let Some(xhtml) = read_part(&mut archive, &item.path)? else {
+            eprintln!("[EPUB] Spine item {} is missing from the archive", item.path);
+            continue;
+        };
+        let page = html::extract(&xhtml);
+        if page.text.is_empty() {
+            continue;
+        }
+        // Per-file <title> elements usually repeat the book title, so headings come first
+        chapters.push(Chapter {
+            title: titles.get(&item.path).cloned().or(page.heading).or(page.title),
+            text: page.text,
+        });
+    }
+    if chapters.is_empty() {
+        return Err("EPUB has no readable chapters".to_string());
+    }
+    Ok(EpubBook {
+        title: package.title,
+        author: package.author,
+        chapters,
+    })
+}
@@ -0,0 +1,223 @@
+// Conversation export to Markdown, HTML and JSON files
+use tauri::State;
+
+use crate::conversations::{self, Conversation, StoredMessage};
+use crate::db::Database;
+
+#[derive(serde::Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Markdown,
+    Html,
+    Json,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportResult {
+    path: String,
+    message_count: usize,
+    bytes: usize,
+}
+
+fn role_label(role: &str) -> &str {
+    match role {
+        "user" => "User",
+        "assistant" => "Assistant",
+        "system" => "System",
+        "tool" => "Tool",
+        other => other,
+    }
+}
+
+// Model recorded on the message by the frontend, falling back to the conversation's model
+fn message_model<'a>(message: &'a StoredMessage, conversation: &'a Conversation) -> Option<&'a str> {
+    message
+        .metadata
+        .as_ref()
+        .and_then(|m| m.get("model"))
+        .and_then(|m| m.as_str())
+        .or(conversation.model.as_deref())
+}
+
+pub fn render_markdown(conversation: &Conversation, messages: &[StoredMessage]) -> String {
+    let mut out = format!("# {}\n\n", conversation.title);
+    if let Some(model) = &conversation.model {
+        out.push_str(&format!("- Model: {}\n", model));
+    }
+    out.push_str(&format!("- Created: {}\n", conversation.created_at));
+    out.push_str(&format!("- Updated: {}\n\n", conversation.updated_at));
+
+    for message in messages {
+        out.push_str(&format!("## {}\n\n", role_label(&message.message.role)));
+        let mut details = vec![message.created_at.clone()];
+        if message.message.role == "assistant" {
+            if let Some(model) = message_model(message, conversation) {
+                details.push(model.to_string());
+            }
+        }
+        out.push_str(&format!("*{}*\n\n", details.join(" · ")));
+
+        // Content is already Markdown, so code fences survive as-is
+        out.push_str(message.message.content.trim_end());
+        out.push_str("\n\n");
+
+        for call in &message.message.tool_calls {
+            out.push_str(&format!("Tool call `{}`:\n\n```json\n{}\n```\n\n", call.name, call.arguments));
+        }
+        for attachment in &message.attachments {
+            out.push_str(&format!("📎 [{}]({})\n\n", attachment.file_name, attachment.path.replace(' ', "%20")));
+        }
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Render message text as paragraphs with fenced code blocks turned into <pre><code>
+fn content_to_html(content: &str) -> String {
+    let mut out = String::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut code: Option<(String, Vec<&str>)> = None;
+
+    let flush_paragraph = |paragraph: &mut Vec<&str>, out: &mut String| {
+        if !paragraph.is_empty() {
+            let lines: Vec<String> = paragraph.iter().map(|l| escape_html(l)).collect();
+            out.push_str(&format!("<p>{}</p>\n", lines.join("<br>\n")));
+            paragraph.clear();
+        }
+    };
+
+    for line in content.lines() {
+        let fence = line.trim_start().strip_prefix("```");
+        match (&mut code, fence) {
+            (Some((language, lines)), Some(_)) => {
+                let class = if language.is_empty() {
+                    String::new()
+                } else {
+                    format!(" class=\"language-{}\"", escape_html(language))
+                };
+                out.push_str(&format!("<pre><code{}>{}</code></pre>\n", class, escape_html(&lines.join("\n"))));
+                code = None;
+            }
+            (Some((_, lines)), None) => lines.push(line),
+            (None, Some(language)) => {
+                flush_paragraph(&mut paragraph, &mut out);
+                code = Some((language.trim().to_string(), Vec::new()));
+            }
+            (None, None) if line.trim().is_empty() => flush_paragraph(&mut paragraph, &mut out),
+            (None, None) => paragraph.push(line),
+        }
+    }
+    // Unterminated fence: keep the code rather than dropping it
+    if let Some((_, lines)) = code {
+        out.push_str(&format!("<pre><code>{}</code></pre>\n", escape_html(&lines.join("\n"))));
+    }
+    flush_paragraph(&mut paragraph, &mut out);
+    out
+}
+
+const HTML_STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:820px;margin:2rem auto;padding:0 1rem;line-height:1.5}\
+.message{border-top:1px solid #ddd;padding:1rem 0}.role{font-weight:600}.meta{color:#777;font-size:.85em}\
+pre{background:#f4f4f4;padding:.75rem;overflow-x:auto;border-radius:4px}";
+
+fn render_html(conversation: &Conversation, messages: &[StoredMessage]) -> String {
+    let title = escape_html(&conversation.title);
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        title, HTML_STYLE, title
+    );
+    out.push_str("<p class=\"meta\">");
+    if let Some(model) = &conversation.model {
+        out.push_str(&format!("Model: {}<br>", escape_html(model)));
+    }
+    out.push_str(&format!(
+        "Created: {}<br>Updated: {}</p>\n",
+        conversation.created_at, conversation.updated_at
+    ));
+
+    for message in messages {
+        out.push_str(&format!(
+            "<div class=\"message {}\">\n<div class=\"role\">{}</div>\n<div class=\"meta\">{}",
+            escape_html(&message.message.role),
+            escape_html(role_label(&message.message.role)),
+            message.created_at
+        ));
+        if message.message.role == "assistant" {
+            if let Some(model) = message_model(message, conversation) {
+                out.push_str(&format!(" · {}", escape_html(model)));
+            }
+        }
+        out.push_str("</div>\n");
+        out.push_str(&content_to_html(&message.message.content));
+
+        for call in &message.message.tool_calls {
+            out.push_str(&format!(
+                "<p>Tool call <code>{}</code></p>\n<pre><code class=\"language-json\">{}</code></pre>\n",
+                escape_html(&call.name),
+                escape_html(&call.arguments.to_string())
+            ));
+        }
+        if !message.attachments.is_empty() {
+            out.push_str("<ul class=\"attachments\">\n");
+            for attachment in &message.attachments {
+                out.push_str(&format!(
+                    "<li><a href=\"{}\">{}</a></li>\n",
+                    escape_html(&attachment.path),
+                    escape_html(&attachment.file_name)
+                ));
+            }
+            out.push_str("</ul>\n");
+        }
+        out.push_str("</div>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn render_json(conversation: &Conversation, messages: &[StoredMessage]) -> Result<String, String> {
+    let document = serde_json::json!({
+        "format": "openchat-conversation",
+        "version": 1,
+        "exportedAt": chrono::Utc::now().to_rfc3339(),
+        "conversation": conversation,
+        "messages": messages,
+    });
+    serde_json::to_string_pretty(&document).map_err(|e| format!("Failed to serialize conversation: {}", e))
+}
+
+#[tauri::command]
+pub fn export_conversation(
+    db: State<'_, Database>,
+    id: String,
+    format: ExportFormat,
+    path: String,
+) -> Result<ExportResult, String> {
+    let (conversation, messages) = db
+        .with_conn(|conn| {
+            let Some(conversation) = conversations::get_conversation_row(conn, &id)? else {
+                return Ok(None);
+            };
+            Ok(Some((conversation, conversations::load_messages(conn, &id)?)))
+        })?
+        .ok_or_else(|| format!("Conversation {} not found", id))?;
+
+    let rendered = match format {
+        ExportFormat::Markdown => render_markdown(&conversation, &messages),
+        ExportFormat::Html => render_html(&conversation, &messages),
+        ExportFormat::Json => render_json(&conversation, &messages)?,
+    };
+
+    std::fs::write(&path, &rendered).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    eprintln!("[Export] Wrote {} as {:?} to {}", id, format, path);
+    Ok(ExportResult {
+        path,
+        message_count: messages.len(),
+        bytes: rendered.len(),
+    })
+}
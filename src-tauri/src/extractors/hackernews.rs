@@ -0,0 +1,101 @@
+// Hacker News items (news.ycombinator.com/item?id=…) through the Algolia API, which returns a story
+// with its whole comment tree in one request. Comments keep their nesting as an indented list.
+use reqwest::Url;
+use serde_json::Value;
+
+use super::{bold, continue_lines, get_json, heading, link, render_fragment, Extracted};
+use crate::html::Format;
+use crate::http_client::Session;
+use crate::sources::str_field;
+
+const API: &str = "https://hn.algolia.com/api/v1/items";
+// Comments kept, in thread order, and how deep replies go
+const MAX_COMMENTS: usize = 300;
+const MAX_DEPTH: usize = 8;
+
+fn children(item: &Value) -> &[Value] {
+    item.get("children").and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default()
+}
+
+fn count_comments(item: &Value) -> usize {
+    children(item).iter().map(|child| 1 + count_comments(child)).sum()
+}
+
+// Deleted comments keep their place only when they have replies
+fn push_comments(out: &mut String, item: &Value, depth: usize, kept: &mut usize, format: Format) {
+    if depth >= MAX_DEPTH {
+        return;
+    }
+    let indent = "  ".repeat(depth);
+    for child in children(item) {
+        if *kept >= MAX_COMMENTS {
+            return;
+        }
+        let text = str_field(child, "text").map(|text| render_fragment(text, format, None));
+        match (str_field(child, "author"), text) {
+            (Some(author), Some(text)) => {
+                let date = str_field(child, "created_at").map(|d| format!(" ({})", &d[..d.len().min(10)]));
+                let text = continue_lines(&text, &format!("{}  ", indent));
+                out.push_str(&format!("{}- {}{}: {}\n", indent, bold(author, format), date.unwrap_or_default(), text));
+            }
+            _ if children(child).is_empty() => continue,
+            _ => out.push_str(&format!("{}- [deleted]\n", indent)),
+        }
+        *kept += 1;
+        push_comments(out, child, depth + 1, kept, format);
+    }
+}
+
+pub fn extract(url: &Url, session: &Session, format: Format) -> Result<Option<Extracted>, String> {
+    if url.path() != "/item" {
+        return Ok(None);
+    }
+    let Some(id) = url.query_pairs().find(|(key, _)| key == "id").and_then(|(_, id)| id.parse::<u64>().ok()) else {
+        return Ok(None);
+    };
+    let item = get_json(session, &format!("{}/{}", API, id), "Hacker News")?
+        .ok_or_else(|| format!("Hacker News item {} not found", id))?;
+
+    let author = str_field(&item, "author").map(str::to_string);
+    let is_comment = str_field(&item, "type") == Some("comment");
+    let title = match (str_field(&item, "title"), &author) {
+        (Some(title), _) => title.to_string(),
+        (None, Some(author)) if is_comment => format!("Comment by {} on Hacker News", author),
+        _ => format!("Hacker News item {}", id),
+    };
+
+    let mut content = heading(1, &title, format);
+    content.push_str("\n\n");
+    if let Some(link_url) = str_field(&item, "url") {
+        content.push_str(&format!("{}\n", link(link_url, link_url, format)));
+    }
+    let comments = count_comments(&item);
+    let mut byline = Vec::new();
+    if let Some(points) = item.get("points").and_then(Value::as_i64) {
+        byline.push(format!("{} points", points));
+    }
+    if let Some(author) = &author {
+        byline.push(format!("by {}", author));
+    }
+    byline.push(format!("{} comments", comments));
+    content.push_str(&format!("{}\n\n", byline.join(" | ")));
+    if let Some(text) = str_field(&item, "text") {
+        content.push_str(&format!("{}\n\n", render_fragment(text, format, None)));
+    }
+    if comments > 0 {
+        content.push_str(&format!("{}\n\n", heading(2, "Comments", format)));
+        let mut kept = 0;
+        push_comments(&mut content, &item, 0, &mut kept, format);
+        if kept < comments {
+            content.push_str(&format!("\n[{} of {} comments shown]\n", kept, comments));
+        }
+    }
+
+    Ok(Some(Extracted {
+        title,
+        content,
+        author,
+        published_date: str_field(&item, "created_at").map(str::to_string),
+        paywalled: false,
+    }))
+}
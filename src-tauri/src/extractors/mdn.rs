@@ -0,0 +1,69 @@
+// MDN Web Docs pages through the index.json published next to each of them, which holds the page as
+// sections of prose. Compatibility tables are left as a link; specifications become a list.
+use reqwest::Url;
+use serde_json::Value;
+
+use super::{get_json, heading, link, render_fragment, Extracted};
+use crate::html::Format;
+use crate::http_client::Session;
+use crate::sources::str_field;
+
+fn section(out: &mut String, section: &Value, page: &Url, format: Format) {
+    let Some(value) = section.get("value") else { return };
+    let title = str_field(value, "title");
+    let level = if value.get("isH3").and_then(Value::as_bool).unwrap_or(false) { 3 } else { 2 };
+    let body = match str_field(section, "type") {
+        Some("prose") => str_field(value, "content").map(|content| render_fragment(content, format, Some(page))),
+        Some("specifications") => {
+            let specs: Vec<String> = value
+                .get("specifications")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|spec| {
+                    let url = str_field(spec, "bcdSpecificationURL")?;
+                    Some(format!("- {}", link(str_field(spec, "title").unwrap_or(url), url, format)))
+                })
+                .collect();
+            (!specs.is_empty()).then(|| specs.join("\n"))
+        }
+        Some("browser_compatibility") => {
+            let mut table = page.clone();
+            table.set_fragment(str_field(value, "id"));
+            Some(format!("See the compatibility table at {}.", link(table.as_str(), table.as_str(), format)))
+        }
+        _ => None,
+    };
+    if let Some(title) = title {
+        out.push_str(&format!("{}\n\n", heading(level, title, format)));
+    }
+    if let Some(body) = body.filter(|body| !body.is_empty()) {
+        out.push_str(&format!("{}\n\n", body));
+    }
+}
+
+pub fn extract(url: &Url, session: &Session, format: Format) -> Result<Option<Extracted>, String> {
+    if !url.path().contains("/docs/") {
+        return Ok(None);
+    }
+    let mut page = url.clone();
+    page.set_query(None);
+    page.set_fragment(None);
+    let json_url = format!("{}/index.json", page.as_str().trim_end_matches('/'));
+    let Some(response) = get_json(session, &json_url, "MDN")? else { return Ok(None) };
+    let doc = response.get("doc").ok_or("MDN response has no document")?;
+
+    let title = str_field(doc, "title").unwrap_or("MDN Web Docs").to_string();
+    let mut content = format!("{}\n\n", heading(1, &title, format));
+    for part in doc.get("body").and_then(Value::as_array).into_iter().flatten() {
+        section(&mut content, part, &page, format);
+    }
+
+    Ok(Some(Extracted {
+        title,
+        content,
+        author: None,
+        published_date: str_field(doc, "modified").map(str::to_string),
+        paywalled: false,
+    }))
+}
@@ -0,0 +1,64 @@
+// Medium stories (medium.com and its publication subdomains). The story's own blocks carry a
+// data-selectable-paragraph attribute, so taking only those leaves out the claps, follow buttons,
+// reading time and member prompts around them.
+use reqwest::Url;
+
+use super::Extracted;
+use crate::html::{self, Format};
+use crate::http_client::Session;
+
+const BLOCKS: &str = "[data-selectable-paragraph]";
+
+fn meta(document: &kuchikiki::NodeRef, selector: &str) -> Option<String> {
+    let element = document.select_first(selector).ok()?;
+    let content = element.attributes.borrow().get("content").map(|c| c.trim().to_string());
+    content.filter(|c| !c.is_empty())
+}
+
+pub fn extract(url: &Url, session: &Session, format: Format) -> Result<Option<Extracted>, String> {
+    if url.path().trim_matches('/').is_empty() {
+        return Ok(None);
+    }
+    let response = session.send(session.get(url.as_str())).map_err(|e| format!("Medium: {}", e))?.response;
+    if !response.status().is_success() {
+        return Err(format!("Medium returned status {}", response.status()));
+    }
+    let markup = response.text().map_err(|e| format!("Failed to read the Medium page: {}", e))?;
+    let document = html::parse(&markup);
+    let blocks: Vec<_> = document.select(BLOCKS).map(|blocks| blocks.collect()).unwrap_or_default();
+    // Profiles, lists and tag pages have no story
+    if blocks.is_empty() {
+        return Ok(None);
+    }
+
+    let title = document
+        .select_first("[data-testid=\"storyTitle\"]")
+        .ok()
+        .map(|title| title.text_contents().trim().to_string())
+        .filter(|title| !title.is_empty())
+        .or_else(|| meta(&document, "meta[property=\"og:title\"]"))
+        .unwrap_or_else(|| "Untitled".to_string());
+    let parts: Vec<String> = blocks
+        .iter()
+        // The title is one of the blocks; blocks inside another (list items) come with it
+        .filter(|block| block.text_contents().trim() != title)
+        .filter(|block| !block.as_node().ancestors().any(|ancestor| is_block(&ancestor)))
+        .map(|block| html::render_as(block.as_node(), format, Some(url)).trim().to_string())
+        .filter(|part| !part.is_empty())
+        .collect();
+    let content = parts.join("\n\n");
+    let word_count = content.split_whitespace().count();
+
+    Ok(Some(Extracted {
+        title,
+        author: meta(&document, "meta[name=\"author\"]"),
+        published_date: meta(&document, "meta[property=\"article:published_time\"]"),
+        // Member-only stories show the first paragraphs to everyone else
+        paywalled: crate::paywall::detect(&markup, word_count),
+        content,
+    }))
+}
+
+fn is_block(node: &kuchikiki::NodeRef) -> bool {
+    node.as_element().is_some_and(|element| element.attributes.borrow().contains("data-selectable-paragraph"))
+}
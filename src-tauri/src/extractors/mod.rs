@@ -0,0 +1,150 @@
+// Extraction paths of their own for sites the generic scraper reads poorly: Hacker News threads,
+// posts on X (Twitter), Medium stories and MDN pages. Each is registered by domain and reads the
+// site's API or the parts of its markup that hold the content. A page an extractor doesn't handle,
+// or fails on, goes through the generic scraper.
+use reqwest::Url;
+
+use crate::html::Format;
+use crate::http_client::Session;
+use crate::{extract_domain, scrape_client, ContentMetadata, ScrapeOptions, ScrapedContent};
+
+mod hackernews;
+mod mdn;
+mod medium;
+mod twitter;
+
+// What an extractor made of a page
+struct Extracted {
+    title: String,
+    content: String,
+    author: Option<String>,
+    published_date: Option<String>,
+    paywalled: bool,
+}
+
+// None when the page isn't one the extractor handles, e.g. the Hacker News front page
+type Extract = fn(&Url, &Session, Format) -> Result<Option<Extracted>, String>;
+
+struct Extractor {
+    name: &'static str,
+    // Subdomains included
+    domains: &'static [&'static str],
+    extract: Extract,
+}
+
+const EXTRACTORS: &[Extractor] = &[
+    Extractor {
+        name: "Hacker News",
+        domains: &["news.ycombinator.com"],
+        extract: hackernews::extract,
+    },
+    Extractor {
+        name: "X",
+        domains: &["twitter.com", "x.com"],
+        extract: twitter::extract,
+    },
+    Extractor {
+        name: "Medium",
+        domains: &["medium.com"],
+        extract: medium::extract,
+    },
+    Extractor {
+        name: "MDN",
+        domains: &["developer.mozilla.org"],
+        extract: mdn::extract,
+    },
+];
+
+fn for_host(host: &str) -> Option<&'static Extractor> {
+    let host = host.trim_start_matches("www.");
+    EXTRACTORS.iter().find(|extractor| {
+        extractor.domains.iter().any(|domain| host == *domain || host.ends_with(&format!(".{}", domain)))
+    })
+}
+
+// The page through its site's extractor, or None for the generic scraper. Scrapes that send
+// credentials, pick elements by selector or collect links need the page itself and skip these.
+pub fn extract(url: &Url, options: &ScrapeOptions) -> Option<ScrapedContent> {
+    let extractor = for_host(url.host_str()?)?;
+    if !options.headers.is_empty() || !options.selectors.is_empty() || options.collect_links {
+        return None;
+    }
+    let extracted = scrape_client(options).and_then(|session| (extractor.extract)(url, &session, options.format));
+    match extracted {
+        Ok(Some(extracted)) => {
+            eprintln!("[Extractors] Read {} with the {} extractor", url, extractor.name);
+            Some(scraped(url, extracted))
+        }
+        Ok(None) => None,
+        Err(err) => {
+            eprintln!("[Extractors] {} extractor failed on {}, falling back: {}", extractor.name, url, err);
+            None
+        }
+    }
+}
+
+fn scraped(url: &Url, extracted: Extracted) -> ScrapedContent {
+    let content = extracted.content.trim().to_string();
+    ScrapedContent {
+        url: url.to_string(),
+        title: extracted.title,
+        metadata: ContentMetadata {
+            published_date: extracted.published_date,
+            author: extracted.author,
+            domain: extract_domain(url.as_str()),
+            word_count: content.split_whitespace().count(),
+            ..Default::default()
+        },
+        content,
+        links: Vec::new(),
+        content_type: None,
+        paywalled: extracted.paywalled,
+        selected: Default::default(),
+        response: Default::default(),
+    }
+}
+
+// GET a JSON document; None when it doesn't exist (404)
+fn get_json(session: &Session, url: &str, source: &str) -> Result<Option<serde_json::Value>, String> {
+    let response = session.send(session.get(url)).map_err(|e| format!("{}: {}", source, e))?.response;
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        return Err(format!("{} returned status {}", source, status));
+    }
+    response.json().map(Some).map_err(|e| format!("Invalid {} response: {}", source, e))
+}
+
+// An HTML fragment from an API as text or Markdown
+fn render_fragment(markup: &str, format: Format, base: Option<&Url>) -> String {
+    crate::html::render_as(&crate::html::parse(markup), format, base).trim().to_string()
+}
+
+fn heading(level: usize, text: &str, format: Format) -> String {
+    match format {
+        Format::Markdown => format!("{} {}", "#".repeat(level), text),
+        Format::Text => text.to_string(),
+    }
+}
+
+fn bold(text: &str, format: Format) -> String {
+    match format {
+        Format::Markdown => format!("**{}**", text),
+        Format::Text => text.to_string(),
+    }
+}
+
+fn link(text: &str, url: &str, format: Format) -> String {
+    match format {
+        Format::Markdown => format!("[{}]({})", text, url),
+        Format::Text if text == url => url.to_string(),
+        Format::Text => format!("{} ({})", text, url),
+    }
+}
+
+// Every line of `text` after the first indented by `indent`, to continue a list item
+fn continue_lines(text: &str, indent: &str) -> String {
+    text.lines().filter(|line| !line.trim().is_empty()).collect::<Vec<_>>().join(&format!("\n{}", indent))
+}
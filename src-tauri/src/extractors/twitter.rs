@@ -0,0 +1,151 @@
+// Posts on X (x.com and twitter.com …/status/<id>) through the syndication endpoint behind embedded
+// posts, which needs no login. The post comes with the one it replies to and the one it quotes.
+use reqwest::Url;
+use serde_json::Value;
+
+use super::{bold, get_json, link, Extracted};
+use crate::html::Format;
+use crate::http_client::Session;
+use crate::sources::str_field;
+
+const API: &str = "https://cdn.syndication.twimg.com/tweet-result";
+// Characters of the post in the page title
+const TITLE_CHARS: usize = 80;
+
+// The id after a "status" segment: /<user>/status/<id>, /i/web/status/<id>
+fn post_id(url: &Url) -> Option<u64> {
+    let segments: Vec<&str> = url.path_segments()?.collect();
+    let at = segments.iter().position(|segment| *segment == "status" || *segment == "statuses")?;
+    segments.get(at + 1)?.parse().ok()
+}
+
+// The token embeds send along: (id / 1e15 * π) in base 36, without zeros and the point, with as
+// many digits as JavaScript's toString(36) gives
+fn token(id: u64) -> String {
+    const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    let value = id as f64 / 1e15 * std::f64::consts::PI;
+    let (mut whole, mut fraction) = (value.trunc() as u64, value.fract());
+    // Fraction digits stop where the precision of the double ends, rounding the last one
+    let mut delta = (0.5 * (f64::from_bits(value.to_bits() + 1) - value)).max(f64::from_bits(1));
+    let mut fraction_digits = Vec::new();
+    while fraction >= delta {
+        fraction *= 36.0;
+        delta *= 36.0;
+        let digit = fraction.trunc() as usize;
+        fraction_digits.push(digit);
+        fraction -= digit as f64;
+        if (fraction > 0.5 || (fraction == 0.5 && digit % 2 == 1)) && fraction + delta > 1.0 {
+            loop {
+                match fraction_digits.pop() {
+                    Some(digit) if digit + 1 < 36 => fraction_digits.push(digit + 1),
+                    Some(_) => continue,
+                    None => whole += 1,
+                }
+                break;
+            }
+            break;
+        }
+    }
+    let mut integer_digits = Vec::new();
+    while whole > 0 {
+        integer_digits.push((whole % 36) as usize);
+        whole /= 36;
+    }
+    integer_digits.reverse();
+    integer_digits.iter().chain(&fraction_digits).map(|&d| DIGITS[d] as char).filter(|&c| c != '0').collect()
+}
+
+// The text with t.co links expanded and the links to its own media dropped
+fn post_text(post: &Value) -> String {
+    let mut text = str_field(post, "text").unwrap_or_default().to_string();
+    let entities = post.get("entities");
+    for url in entities.and_then(|e| e.get("urls")).and_then(Value::as_array).into_iter().flatten() {
+        if let (Some(short), Some(expanded)) = (str_field(url, "url"), str_field(url, "expanded_url")) {
+            text = text.replace(short, expanded);
+        }
+    }
+    for media in entities.and_then(|e| e.get("media")).and_then(Value::as_array).into_iter().flatten() {
+        if let Some(short) = str_field(media, "url") {
+            text = text.replace(short, "");
+        }
+    }
+    text.replace("&amp;", "&").replace("&lt;", "<").replace("&gt;", ">").trim().to_string()
+}
+
+fn author(post: &Value, format: Format) -> String {
+    let user = post.get("user");
+    let name = user.and_then(|u| str_field(u, "name")).unwrap_or("Unknown");
+    match user.and_then(|u| str_field(u, "screen_name")) {
+        Some(handle) => format!("{} (@{})", bold(name, format), handle),
+        None => bold(name, format),
+    }
+}
+
+fn media(post: &Value, format: Format) -> Vec<String> {
+    let details = post.get("mediaDetails").and_then(Value::as_array);
+    details
+        .into_iter()
+        .flatten()
+        .filter_map(|media| {
+            let url = str_field(media, "media_url_https")?;
+            let kind = if str_field(media, "type") == Some("photo") { "Image" } else { "Video" };
+            let label = match str_field(media, "ext_alt_text") {
+                Some(alt) => format!("{}: {}", kind, alt),
+                None => kind.to_string(),
+            };
+            Some(link(&label, url, format))
+        })
+        .collect()
+}
+
+// A post another one replies to or quotes
+fn nested(label: &str, post: &Value, format: Format) -> String {
+    let text = post_text(post);
+    let body = match format {
+        Format::Markdown => text.lines().map(|line| format!("> {}", line)).collect::<Vec<_>>().join("\n"),
+        Format::Text => text,
+    };
+    format!("{} {}:\n\n{}", label, author(post, format), body)
+}
+
+pub fn extract(url: &Url, session: &Session, format: Format) -> Result<Option<Extracted>, String> {
+    let Some(id) = post_id(url) else { return Ok(None) };
+    let request = format!("{}?id={}&lang=en&token={}", API, id, token(id));
+    let post = get_json(session, &request, "X")?.ok_or_else(|| format!("Post {} not found or not public", id))?;
+    // Deleted and protected posts come back as tombstones without text
+    if str_field(&post, "text").is_none() {
+        return Err(format!("Post {} is not available", id));
+    }
+
+    let mut parts = Vec::new();
+    if let Some(parent) = post.get("parent").filter(|parent| parent.get("text").is_some()) {
+        parts.push(nested("Replying to", parent, format));
+    }
+    let date = str_field(&post, "created_at");
+    parts.push(match date {
+        Some(date) => format!("{} · {}", author(&post, format), &date[..date.len().min(10)]),
+        None => author(&post, format),
+    });
+    let text = post_text(&post);
+    parts.push(text.clone());
+    parts.extend(media(&post, format));
+    if let Some(quoted) = post.get("quoted_tweet").filter(|quoted| quoted.get("text").is_some()) {
+        parts.push(nested("Quoting", quoted, format));
+    }
+    let count = |key| post.get(key).and_then(Value::as_u64).unwrap_or(0);
+    parts.push(format!("{} likes · {} replies", count("favorite_count"), count("conversation_count")));
+
+    let name = post.get("user").and_then(|u| str_field(u, "name")).unwrap_or("Someone");
+    let flat = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut excerpt: String = flat.chars().take(TITLE_CHARS).collect();
+    if flat.chars().count() > TITLE_CHARS {
+        excerpt.push('…');
+    }
+    Ok(Some(Extracted {
+        title: format!("{} on X: \"{}\"", name, excerpt),
+        content: parts.join("\n\n"),
+        author: post.get("user").and_then(|u| str_field(u, "screen_name")).map(|handle| format!("@{}", handle)),
+        published_date: date.map(str::to_string),
+        paywalled: false,
+    }))
+}
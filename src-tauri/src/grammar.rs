@@ -0,0 +1,185 @@
+// JSON Schema to GBNF conversion for grammar-constrained generation
+//
+// Covers the schema subset used for tool calls and structured answers: objects with
+// properties, arrays, enums/consts, anyOf/oneOf and the primitive types. Unknown
+// constructs fall back to an unconstrained JSON value.
+use serde_json::Value;
+
+const PRIMITIVES: &[(&str, &str)] = &[
+    ("ws", r#"[ \t\n]*"#),
+    ("string", r#""\"" ( [^"\\\x7F\x00-\x1F] | "\\" ( ["\\/bfnrt] | "u" [0-9a-fA-F] [0-9a-fA-F] [0-9a-fA-F] [0-9a-fA-F] ) )* "\"" ws"#),
+    ("number", r#""-"? ( [0-9] | [1-9] [0-9]* ) ( "." [0-9]+ )? ( [eE] [-+]? [0-9]+ )? ws"#),
+    ("integer", r#""-"? ( [0-9] | [1-9] [0-9]* ) ws"#),
+    ("boolean", r#"( "true" | "false" ) ws"#),
+    ("null", r#""null" ws"#),
+    ("value", r#"object | array | string | number | boolean | null"#),
+    ("object", r#""{" ws ( string ":" ws value ( "," ws string ":" ws value )* )? "}" ws"#),
+    ("array", r#""[" ws ( value ( "," ws value )* )? "]" ws"#),
+];
+
+struct Converter {
+    rules: Vec<(String, String)>,
+}
+
+// Quote text as a GBNF string literal
+fn literal(text: &str) -> String {
+    let mut out = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn rule_name(base: &str) -> String {
+    base.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect()
+}
+
+impl Converter {
+    fn add_rule(&mut self, name: &str, body: String) -> String {
+        let mut unique = rule_name(name);
+        let mut n = 1;
+        while self.rules.iter().any(|(existing, _)| *existing == unique) {
+            n += 1;
+            unique = format!("{}-{}", rule_name(name), n);
+        }
+        self.rules.push((unique.clone(), body));
+        unique
+    }
+
+    // Returns a GBNF expression matching the schema
+    fn visit(&mut self, schema: &Value, name: &str) -> Result<String, String> {
+        if let Some(value) = schema.get("const") {
+            return Ok(format!("{} ws", literal(&value.to_string())));
+        }
+        if let Some(values) = schema.get("enum").and_then(|e| e.as_array()) {
+            let alternatives: Vec<String> = values.iter().map(|v| literal(&v.to_string())).collect();
+            return Ok(format!("( {} ) ws", alternatives.join(" | ")));
+        }
+        if let Some(variants) = schema
+            .get("anyOf")
+            .or_else(|| schema.get("oneOf"))
+            .and_then(|v| v.as_array())
+        {
+            let mut alternatives = Vec::new();
+            for (i, variant) in variants.iter().enumerate() {
+                let expr = self.visit(variant, &format!("{}-{}", name, i))?;
+                alternatives.push(self.add_rule(&format!("{}-{}", name, i), expr));
+            }
+            return Ok(alternatives.join(" | "));
+        }
+
+        match schema.get("type") {
+            Some(Value::String(kind)) => self.visit_type(schema, kind, name),
+            // ["string", "null"] style unions
+            Some(Value::Array(kinds)) => {
+                let mut alternatives = Vec::new();
+                for kind in kinds.iter().filter_map(|k| k.as_str()) {
+                    let expr = self.visit_type(schema, kind, &format!("{}-{}", name, kind))?;
+                    alternatives.push(self.add_rule(&format!("{}-{}", name, kind), expr));
+                }
+                Ok(alternatives.join(" | "))
+            }
+            _ if schema.get("properties").is_some() => self.visit_type(schema, "object", name),
+            _ => Ok("value".to_string()),
+        }
+    }
+
+    fn visit_type(&mut self, schema: &Value, kind: &str, name: &str) -> Result<String, String> {
+        match kind {
+            "string" | "number" | "integer" | "boolean" | "null" => Ok(kind.to_string()),
+            "array" => match schema.get("items") {
+                Some(items) => {
+                    let item_expr = self.visit(items, &format!("{}-item", name))?;
+                    let item = self.add_rule(&format!("{}-item", name), item_expr);
+                    let min_items = schema.get("minItems").and_then(|v| v.as_u64()).unwrap_or(0);
+                    if min_items > 0 {
+                        Ok(format!(r#""[" ws {} ( "," ws {} )* "]" ws"#, item, item))
+                    } else {
+                        Ok(format!(r#""[" ws ( {} ( "," ws {} )* )? "]" ws"#, item, item))
+                    }
+                }
+                None => Ok("array".to_string()),
+            },
+            "object" => {
+                let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) else {
+                    return Ok("object".to_string());
+                };
+                let required: Vec<&str> = schema
+                    .get("required")
+                    .and_then(|r| r.as_array())
+                    .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+                    .unwrap_or_default();
+
+                let mut required_parts = Vec::new();
+                let mut optional_parts = Vec::new();
+                for (key, prop_schema) in properties {
+                    let prop_name = format!("{}-{}", name, key);
+                    let value_expr = self.visit(prop_schema, &prop_name)?;
+                    let value_rule = self.add_rule(&prop_name, value_expr);
+                    let key_literal = literal(&serde_json::to_string(key).map_err(|e| e.to_string())?);
+                    let pair = format!(r#"{} ws ":" ws {}"#, key_literal, value_rule);
+                    if required.contains(&key.as_str()) {
+                        required_parts.push(pair);
+                    } else {
+                        optional_parts.push(pair);
+                    }
+                }
+
+                // Required properties in schema order, then each optional property may follow
+                let mut body = String::from(r#""{" ws "#);
+                if required_parts.is_empty() {
+                    if !optional_parts.is_empty() {
+                        let rest: Vec<String> = optional_parts[1..]
+                            .iter()
+                            .map(|p| format!(r#"( "," ws {} )?"#, p))
+                            .collect();
+                        body.push_str(&format!("( {} {} )? ", optional_parts[0], rest.join(" ")));
+                    }
+                } else {
+                    body.push_str(&required_parts.join(r#" "," ws "#));
+                    body.push(' ');
+                    for part in &optional_parts {
+                        body.push_str(&format!(r#"( "," ws {} )? "#, part));
+                    }
+                }
+                body.push_str(r#""}" ws"#);
+                Ok(body)
+            }
+            other => Err(format!("Unsupported schema type '{}'", other)),
+        }
+    }
+}
+
+pub fn json_schema_to_gbnf(schema: &Value) -> Result<String, String> {
+    let mut converter = Converter { rules: Vec::new() };
+    // Reserve the primitive names so generated rules never shadow them
+    for (name, body) in PRIMITIVES {
+        converter.rules.push((name.to_string(), body.to_string()));
+    }
+    let root_expr = converter.visit(schema, "root")?;
+    let primitives = PRIMITIVES.len();
+
+    let mut grammar = format!("root ::= {}\n", root_expr);
+    for (name, body) in converter.rules.iter().skip(primitives) {
+        grammar.push_str(&format!("{} ::= {}\n", name, body));
+    }
+    for (name, body) in converter.rules.iter().take(primitives) {
+        grammar.push_str(&format!("{} ::= {}\n", name, body));
+    }
+    Ok(grammar)
+}
+
+#[tauri::command]
+pub fn json_schema_to_grammar(schema: Value) -> Result<String, String> {
+    json_schema_to_gbnf(&schema)
+}
@@ -0,0 +1,463 @@
+// Readable text from HTML documents: boilerplate (scripts, navigation, site headers and footers,
+// forms) is dropped and the main content is rendered as plain text with Markdown-style headings
+// and list items, or as Markdown proper with links, emphasis, tables and code blocks.
+use kuchikiki::traits::TendrilSink;
+use kuchikiki::NodeRef;
+use reqwest::Url;
+
+use crate::clean_text;
+
+const BOILERPLATE: &str = "script, style, noscript, template, svg, canvas, iframe, object, form, button, \
+    nav, aside, [role=navigation], [role=banner], [role=contentinfo], [role=complementary], [aria-hidden=true]";
+// Headers and footers of the page, not those of an article
+const PAGE_CHROME: &str = "header, footer";
+// Where the content is, most specific first
+const MAIN_CONTENT: &[&str] = &["main", "[role=main]", "article", "body"];
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address", "article", "blockquote", "dd", "details", "div", "dl", "dt", "figcaption", "figure", "hr", "main",
+    "ol", "p", "section", "summary", "table", "ul",
+];
+
+pub struct HtmlText {
+    // The document's <title>
+    pub title: Option<String>,
+    // The first h1-h3 of the content
+    pub heading: Option<String>,
+    pub text: String,
+}
+
+pub fn parse(html: &str) -> NodeRef {
+    kuchikiki::parse_html().one(html).document_node
+}
+
+fn is_inside(node: &NodeRef, names: &[&str]) -> bool {
+    node.ancestors()
+        .any(|a| a.as_element().is_some_and(|e| names.contains(&&*e.name.local)))
+}
+
+// Scripts, navigation, forms and the page's own header and footer
+pub fn remove_boilerplate(document: &NodeRef) {
+    let mut doomed: Vec<NodeRef> = document
+        .select(BOILERPLATE)
+        .map(|found| found.map(|e| e.as_node().clone()).collect())
+        .unwrap_or_default();
+    if let Ok(chrome) = document.select(PAGE_CHROME) {
+        doomed.extend(
+            chrome
+                .map(|e| e.as_node().clone())
+                .filter(|node| !is_inside(node, &["article", "main"])),
+        );
+    }
+    for node in doomed {
+        node.detach();
+    }
+}
+
+fn paragraph_break(out: &mut String) {
+    if !out.is_empty() && !out.ends_with("\n\n") {
+        out.push_str(if out.ends_with('\n') { "\n" } else { "\n\n" });
+    }
+}
+
+fn line_break(out: &mut String) {
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+fn render(node: &NodeRef, out: &mut String, preformatted: bool) {
+    if let Some(text) = node.as_text() {
+        let text = text.borrow();
+        if preformatted {
+            out.push_str(&text);
+            return;
+        }
+        for (i, word) in text.split_whitespace().enumerate() {
+            let spaced = i > 0 || text.starts_with(char::is_whitespace);
+            if spaced && !out.is_empty() && !out.ends_with([' ', '\n', '\t']) {
+                out.push(' ');
+            }
+            out.push_str(word);
+        }
+        if text.ends_with(char::is_whitespace) && !text.trim().is_empty() && !out.ends_with([' ', '\n', '\t']) {
+            out.push(' ');
+        }
+        return;
+    }
+    let Some(element) = node.as_element() else {
+        node.children().for_each(|child| render(&child, out, preformatted));
+        return;
+    };
+    let name = &*element.name.local;
+    match name {
+        "br" => out.push('\n'),
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            paragraph_break(out);
+            let level = name[1..].parse::<usize>().unwrap_or(1);
+            out.push_str(&format!("{} {}", "#".repeat(level), clean_text(&node.text_contents())));
+            paragraph_break(out);
+        }
+        "li" => {
+            line_break(out);
+            out.push_str("- ");
+            node.children().for_each(|child| render(&child, out, preformatted));
+            line_break(out);
+        }
+        "pre" => {
+            paragraph_break(out);
+            node.children().for_each(|child| render(&child, out, true));
+            paragraph_break(out);
+        }
+        "tr" => {
+            line_break(out);
+            node.children().for_each(|child| render(&child, out, preformatted));
+            line_break(out);
+        }
+        "td" | "th" => {
+            if !out.ends_with('\n') {
+                out.push('\t');
+            }
+            out.push_str(&clean_text(&node.text_contents()));
+        }
+        _ if BLOCK_ELEMENTS.contains(&name) => {
+            paragraph_break(out);
+            node.children().for_each(|child| render(&child, out, preformatted));
+            paragraph_break(out);
+        }
+        _ => node.children().for_each(|child| render(&child, out, preformatted)),
+    }
+}
+
+// Render a node's content as text; runs of blank lines are collapsed
+pub fn to_text(node: &NodeRef) -> String {
+    let mut out = String::new();
+    render(node, &mut out, false);
+    let mut text = String::with_capacity(out.len());
+    let mut blank = false;
+    for line in out.lines().map(str::trim_end) {
+        if line.is_empty() {
+            blank = !text.is_empty();
+            continue;
+        }
+        if !text.is_empty() {
+            text.push_str(if blank { "\n\n" } else { "\n" });
+        }
+        text.push_str(line);
+        blank = false;
+    }
+    text
+}
+
+// The most specific element holding the page's content
+pub fn main_content(document: NodeRef) -> NodeRef {
+    MAIN_CONTENT
+        .iter()
+        .find_map(|selector| document.select_first(selector).ok())
+        .map(|e| e.as_node().clone())
+        .unwrap_or(document)
+}
+
+// Title, first heading and main text of an HTML document
+pub fn extract(html: &str) -> HtmlText {
+    let document = parse(html);
+    let title = document
+        .select_first("title")
+        .ok()
+        .map(|t| clean_text(&t.text_contents()))
+        .filter(|t| !t.is_empty());
+    remove_boilerplate(&document);
+
+    let root = main_content(document);
+    let heading = root
+        .select("h1, h2, h3")
+        .ok()
+        .and_then(|mut headings| headings.find_map(|h| Some(clean_text(&h.text_contents())).filter(|t| !t.is_empty())));
+    HtmlText {
+        title,
+        heading,
+        text: to_text(&root),
+    }
+}
+
+// Output format of rendered content
+#[derive(serde::Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    #[default]
+    Text,
+    Markdown,
+}
+
+// Render a node as text or Markdown; `base` resolves relative links and images in Markdown
+pub fn render_as(node: &NodeRef, format: Format, base: Option<&Url>) -> String {
+    match format {
+        Format::Text => to_text(node),
+        Format::Markdown => to_markdown(node, base),
+    }
+}
+
+struct Markdown<'a> {
+    out: String,
+    base: Option<&'a Url>,
+    // Ordered lists hold the next item number
+    lists: Vec<Option<usize>>,
+    // Where the current list item's content starts
+    item_start: usize,
+}
+
+impl<'a> Markdown<'a> {
+    fn new(base: Option<&'a Url>) -> Self {
+        Markdown {
+            out: String::new(),
+            base,
+            lists: Vec::new(),
+            item_start: 0,
+        }
+    }
+
+    fn children(&mut self, node: &NodeRef) {
+        node.children().for_each(|child| self.node(&child));
+    }
+
+    // Render a node's children on their own, for wrapping in link or emphasis markers
+    fn inline(&mut self, node: &NodeRef) -> String {
+        let outer = std::mem::take(&mut self.out);
+        self.children(node);
+        let inner = std::mem::replace(&mut self.out, outer);
+        inner.trim().to_string()
+    }
+
+    fn wrap(&mut self, node: &NodeRef, marker: &str) {
+        let inner = self.inline(node);
+        if inner.is_empty() {
+            return;
+        }
+        if !self.out.is_empty() && !self.out.ends_with([' ', '\n', '(', '[']) {
+            self.out.push(' ');
+        }
+        self.out.push_str(&format!("{}{}{}", marker, inner, marker));
+    }
+
+    fn url(&self, href: &str) -> Option<String> {
+        let href = href.trim();
+        if href.is_empty() || href.starts_with('#') || href.starts_with("javascript:") {
+            return None;
+        }
+        match self.base {
+            Some(base) => base.join(href).ok().map(|url| url.to_string()),
+            None => Some(href.to_string()),
+        }
+    }
+
+    fn block_break(&mut self) {
+        // Inside list items blocks only start a new line, so the item stays together
+        if self.lists.is_empty() {
+            paragraph_break(&mut self.out);
+        } else if self.out.len() != self.item_start {
+            line_break(&mut self.out);
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        for (i, word) in text.split_whitespace().enumerate() {
+            let spaced = i > 0 || text.starts_with(char::is_whitespace);
+            if spaced && !self.out.is_empty() && !self.out.ends_with([' ', '\n']) {
+                self.out.push(' ');
+            }
+            self.out.push_str(word);
+        }
+        if text.ends_with(char::is_whitespace) && !text.trim().is_empty() && !self.out.ends_with([' ', '\n']) {
+            self.out.push(' ');
+        }
+    }
+
+    fn code_block(&mut self, node: &NodeRef) {
+        let code = node.text_contents();
+        // Highlighters name the language in a class on <pre> or its <code>
+        let language = std::iter::once(node.clone())
+            .chain(node.select("code").into_iter().flatten().map(|e| e.as_node().clone()))
+            .filter_map(|n| n.as_element().and_then(|e| e.attributes.borrow().get("class").map(str::to_string)))
+            .flat_map(|class| class.split_whitespace().map(str::to_string).collect::<Vec<_>>())
+            .find_map(|class| {
+                let language = class.strip_prefix("language-").or_else(|| class.strip_prefix("lang-"))?;
+                Some(language.to_string())
+            })
+            .unwrap_or_default();
+        // The fence must be longer than any backtick run in the code
+        let longest = code.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+        let fence = "`".repeat(longest.max(2) + 1);
+        paragraph_break(&mut self.out);
+        self.out.push_str(&format!("{}{}\n{}", fence, language, code.trim_matches('\n')));
+        self.out.push_str(&format!("\n{}", fence));
+        paragraph_break(&mut self.out);
+    }
+
+    fn table(&mut self, node: &NodeRef) {
+        let rows: Vec<Vec<String>> = node
+            .select("tr")
+            .into_iter()
+            .flatten()
+            .map(|row| {
+                row.as_node()
+                    .children()
+                    .filter(|cell| cell.as_element().is_some_and(|e| matches!(&*e.name.local, "td" | "th")))
+                    .map(|cell| clean_text(&cell.text_contents()).replace('|', "\\|"))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|row: &Vec<String>| !row.is_empty())
+            .collect();
+        let Some(columns) = rows.iter().map(Vec::len).max() else { return };
+        paragraph_break(&mut self.out);
+        for (i, row) in rows.iter().enumerate() {
+            let cells: Vec<&str> = (0..columns).map(|c| row.get(c).map_or("", String::as_str)).collect();
+            self.out.push_str(&format!("| {} |\n", cells.join(" | ")));
+            // The first row is the header
+            if i == 0 {
+                self.out.push_str(&format!("|{}\n", " --- |".repeat(columns)));
+            }
+        }
+        paragraph_break(&mut self.out);
+    }
+
+    fn node(&mut self, node: &NodeRef) {
+        if let Some(text) = node.as_text() {
+            let text = text.borrow().clone();
+            self.text(&text);
+            return;
+        }
+        let Some(element) = node.as_element() else {
+            self.children(node);
+            return;
+        };
+        let name = &*element.name.local;
+        let attribute = |key: &str| element.attributes.borrow().get(key).map(str::to_string);
+        match name {
+            "br" => self.out.push('\n'),
+            "hr" => {
+                paragraph_break(&mut self.out);
+                self.out.push_str("---");
+                paragraph_break(&mut self.out);
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                paragraph_break(&mut self.out);
+                let level = name[1..].parse::<usize>().unwrap_or(1);
+                let heading = self.inline(node).replace('\n', " ");
+                self.out.push_str(&format!("{} {}", "#".repeat(level), heading));
+                paragraph_break(&mut self.out);
+            }
+            "a" => {
+                let text = self.inline(node);
+                match attribute("href").and_then(|href| self.url(&href)) {
+                    Some(url) if !text.is_empty() => {
+                        if !self.out.is_empty() && !self.out.ends_with([' ', '\n', '(']) {
+                            self.out.push(' ');
+                        }
+                        self.out.push_str(&format!("[{}]({})", text.replace('\n', " "), url));
+                    }
+                    _ => self.text(&format!(" {}", text)),
+                }
+            }
+            "img" => {
+                let alt = clean_text(&attribute("alt").unwrap_or_default());
+                if let Some(src) = attribute("src").and_then(|src| self.url(&src)).filter(|_| !alt.is_empty()) {
+                    self.out.push_str(&format!("![{}]({})", alt, src));
+                }
+            }
+            "strong" | "b" => self.wrap(node, "**"),
+            "em" | "i" => self.wrap(node, "*"),
+            "del" | "s" | "strike" => self.wrap(node, "~~"),
+            "code" | "kbd" | "samp" => {
+                let code = clean_text(&node.text_contents());
+                if !code.is_empty() {
+                    let fence = if code.contains('`') { "``" } else { "`" };
+                    if !self.out.is_empty() && !self.out.ends_with([' ', '\n', '(', '[']) {
+                        self.out.push(' ');
+                    }
+                    self.out.push_str(&format!("{}{}{}", fence, code, fence));
+                }
+            }
+            "pre" => self.code_block(node),
+            "table" => self.table(node),
+            "blockquote" => {
+                let mut inner = Markdown::new(self.base);
+                inner.children(node);
+                let quote = tidy_markdown(&inner.out);
+                if !quote.is_empty() {
+                    paragraph_break(&mut self.out);
+                    let quoted: Vec<String> =
+                        quote.lines().map(|line| format!("> {}", line).trim_end().to_string()).collect();
+                    self.out.push_str(&quoted.join("\n"));
+                    paragraph_break(&mut self.out);
+                }
+            }
+            "ul" | "ol" => {
+                self.block_break();
+                let start = attribute("start").and_then(|s| s.parse().ok()).unwrap_or(1);
+                self.lists.push((name == "ol").then_some(start));
+                self.children(node);
+                self.lists.pop();
+                self.block_break();
+            }
+            "li" => {
+                line_break(&mut self.out);
+                let depth = self.lists.len().max(1);
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}.", *number - 1)
+                    }
+                    _ => "-".to_string(),
+                };
+                self.out.push_str(&format!("{}{} ", "  ".repeat(depth - 1), marker));
+                self.item_start = self.out.len();
+                self.children(node);
+                line_break(&mut self.out);
+            }
+            _ if BLOCK_ELEMENTS.contains(&name) => {
+                self.block_break();
+                self.children(node);
+                self.block_break();
+            }
+            _ => self.children(node),
+        }
+    }
+}
+
+// Trailing spaces and runs of blank lines go, except inside code blocks
+fn tidy_markdown(markdown: &str) -> String {
+    let mut out = String::with_capacity(markdown.len());
+    let mut fence: Option<String> = None;
+    let mut blank = false;
+    for line in markdown.lines() {
+        if let Some(open) = &fence {
+            out.push_str(line);
+            out.push('\n');
+            if line.trim() == open {
+                fence = None;
+            }
+            continue;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            blank = !out.is_empty();
+            continue;
+        }
+        if blank {
+            out.push('\n');
+            blank = false;
+        }
+        if line.starts_with("```") {
+            fence = Some(line.chars().take_while(|&c| c == '`').collect());
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+// Render a node's content as Markdown, keeping headings, emphasis, links, lists, tables and code
+pub fn to_markdown(node: &NodeRef, base: Option<&Url>) -> String {
+    let mut markdown = Markdown::new(base);
+    markdown.node(node);
+    tidy_markdown(&markdown.out)
+}
@@ -0,0 +1,265 @@
+// HTTP clients shared by the app's requests, one async and one blocking, so they reuse pooled
+// connections and TLS sessions instead of each opening its own. A client is built on first use for
+// each proxy and set of connection settings in use. Timeouts, user agents and credentials are set
+// per request. The blocking client leaves redirects to Session, which follows them per request:
+// scrapes log them, requests with credentials keep to their first host, and scrapes keep cookies
+// in their jar.
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::Duration;
+
+use reqwest::blocking::{Client, Request, RequestBuilder, Response};
+use reqwest::cookie::CookieStore;
+use reqwest::header::{self, HeaderMap, HeaderName, HeaderValue};
+use reqwest::{IntoUrl, Method, StatusCode, Url};
+use tauri::{AppHandle, Manager};
+
+use crate::cookies::CookieJar;
+use crate::proxy::{self, ProxyConfig};
+use crate::settings::Settings;
+
+// The browser user agent of scrapes, page fetches and DuckDuckGo; some sites turn away anything else
+pub const BROWSER_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:109.0) Gecko/20100101 Firefox/115.0";
+// As many as reqwest follows by default
+const MAX_REDIRECTS: usize = 10;
+// Clients kept of each kind; the oldest go first
+const MAX_CLIENTS: usize = 4;
+
+// Connection settings of the shared clients, which requests can't change
+#[derive(Clone, PartialEq)]
+pub struct ClientDefaults {
+    pub connect_timeout: Duration,
+    // Idle connections are closed after this long
+    pub pool_idle_timeout: Duration,
+    pub pool_max_idle_per_host: usize,
+}
+
+impl ClientDefaults {
+    pub fn from_settings(settings: &Settings) -> Self {
+        ClientDefaults {
+            connect_timeout: Duration::from_secs(settings.http_connect_timeout_secs),
+            pool_idle_timeout: Duration::from_secs(settings.http_pool_idle_secs),
+            pool_max_idle_per_host: settings.http_pool_max_idle_per_host,
+        }
+    }
+}
+
+// For code without settings at hand, such as the model calls
+impl Default for ClientDefaults {
+    fn default() -> Self {
+        ClientDefaults::from_settings(&Settings::default())
+    }
+}
+
+type Key = (ClientDefaults, Option<ProxyConfig>);
+
+#[derive(Default)]
+struct Built {
+    clients: Vec<(Key, reqwest::Client)>,
+    blocking: Vec<(Key, Client)>,
+}
+
+#[derive(Clone, Default)]
+pub struct HttpClients {
+    built: Arc<Mutex<Built>>,
+}
+
+// The client for `defaults` and `proxy`, built when there is none yet; clients are handles to a
+// shared pool, so copies are cheap
+fn cached<C: Clone>(
+    clients: &mut Vec<(Key, C)>,
+    defaults: &ClientDefaults,
+    proxy: Option<&ProxyConfig>,
+    build: impl FnOnce() -> Result<C, String>,
+) -> Result<C, String> {
+    let found = clients.iter().find(|((d, p), _)| d == defaults && p.as_ref() == proxy);
+    if let Some((_, client)) = found {
+        return Ok(client.clone());
+    }
+    let client = build()?;
+    if clients.len() >= MAX_CLIENTS {
+        clients.remove(0);
+    }
+    clients.push(((defaults.clone(), proxy.cloned()), client.clone()));
+    Ok(client)
+}
+
+impl HttpClients {
+    // The clients the app manages, also for code that has no app state to take them from
+    pub fn shared() -> HttpClients {
+        static SHARED: OnceLock<HttpClients> = OnceLock::new();
+        SHARED.get_or_init(HttpClients::default).clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Built> {
+        self.built.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    // The async client, which follows redirects itself
+    pub fn client(&self, defaults: &ClientDefaults, proxy: Option<&ProxyConfig>) -> Result<reqwest::Client, String> {
+        cached(&mut self.lock().clients, defaults, proxy, || {
+            proxy::apply(reqwest::Client::builder(), proxy)?
+                .connect_timeout(defaults.connect_timeout)
+                .pool_idle_timeout(defaults.pool_idle_timeout)
+                .pool_max_idle_per_host(defaults.pool_max_idle_per_host)
+                .build()
+                .map_err(|e| format!("Failed to build HTTP client: {}", e))
+        })
+    }
+
+    fn blocking(&self, defaults: &ClientDefaults, proxy: Option<&ProxyConfig>) -> Result<Client, String> {
+        cached(&mut self.lock().blocking, defaults, proxy, || {
+            proxy::apply_blocking(Client::builder(), proxy)?
+                .connect_timeout(defaults.connect_timeout)
+                .pool_idle_timeout(defaults.pool_idle_timeout)
+                .pool_max_idle_per_host(defaults.pool_max_idle_per_host)
+                .redirect(reqwest::redirect::Policy::none())
+                .build()
+                .map_err(|e| format!("Failed to build HTTP client: {}", e))
+        })
+    }
+
+    // Blocking requests through `proxy` with a browser user agent, each allowed `timeout`. Not from
+    // async code: the blocking client must be built and dropped outside a runtime.
+    pub fn session(
+        &self,
+        defaults: &ClientDefaults,
+        proxy: Option<&ProxyConfig>,
+        timeout: Duration,
+    ) -> Result<Session, String> {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::USER_AGENT, HeaderValue::from_static(BROWSER_USER_AGENT));
+        Ok(Session {
+            client: self.blocking(defaults, proxy)?,
+            timeout,
+            headers,
+            same_host: false,
+            cookies: None,
+        })
+    }
+}
+
+// The app's async client with the connection settings and proxy of `settings`, for commands
+pub fn for_app(app: &AppHandle, settings: &Settings) -> Result<reqwest::Client, String> {
+    let clients = app.state::<HttpClients>();
+    clients.client(&ClientDefaults::from_settings(settings), proxy::from_settings(settings).as_ref())
+}
+
+// Blocking requests on the shared client with the headers, cookies and redirect rules of one
+// caller, such as a scrape
+pub struct Session {
+    client: Client,
+    timeout: Duration,
+    headers: HeaderMap,
+    // Redirects to other hosts aren't followed, so credentials stay with their site
+    same_host: bool,
+    cookies: Option<CookieJar>,
+}
+
+// A response and the redirects that led to it
+pub struct Sent {
+    pub response: Response,
+    // The URLs that redirected, each with its status
+    pub redirects: Vec<(Url, StatusCode)>,
+}
+
+fn redirects_to(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::MOVED_PERMANENTLY
+            | StatusCode::FOUND
+            | StatusCode::SEE_OTHER
+            | StatusCode::TEMPORARY_REDIRECT
+            | StatusCode::PERMANENT_REDIRECT
+    )
+}
+
+impl Session {
+    // Extra request headers, credentials included; they are kept out of logs, and redirects
+    // no longer leave the first host
+    pub fn with_headers(mut self, headers: &[(String, String)]) -> Result<Self, String> {
+        for (name, value) in headers {
+            let name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| format!("Invalid header name: {}", name))?;
+            let mut value = HeaderValue::from_str(value).map_err(|_| format!("Invalid value for header {}", name))?;
+            value.set_sensitive(true);
+            self.headers.insert(name, value);
+            self.same_host = true;
+        }
+        Ok(self)
+    }
+
+    // Send the jar's cookies and keep the ones sites set
+    pub fn with_cookies(mut self, cookies: Option<CookieJar>) -> Self {
+        self.cookies = cookies;
+        self
+    }
+
+    fn request(&self, method: Method, url: impl IntoUrl) -> RequestBuilder {
+        self.client.request(method, url).headers(self.headers.clone()).timeout(self.timeout)
+    }
+
+    pub fn get(&self, url: impl IntoUrl) -> RequestBuilder {
+        self.request(Method::GET, url)
+    }
+
+    pub fn head(&self, url: impl IntoUrl) -> RequestBuilder {
+        self.request(Method::HEAD, url)
+    }
+
+    pub fn post(&self, url: impl IntoUrl) -> RequestBuilder {
+        self.request(Method::POST, url)
+    }
+
+    // Send a request made with this session, following redirects as reqwest would. A redirect
+    // that isn't followed (to another host, or past the tenth) is returned as the response.
+    pub fn send(&self, request: RequestBuilder) -> Result<Sent, String> {
+        let mut request = request.build().map_err(|e| format!("Invalid request: {}", e))?;
+        let first_host = request.url().host_str().map(str::to_string);
+        let mut redirects = Vec::new();
+        loop {
+            if let Some(cookies) = self.cookies.as_ref().and_then(|jar| jar.cookies(request.url())) {
+                request.headers_mut().insert(header::COOKIE, cookies);
+            }
+            let url = request.url().clone();
+            let retry = request.try_clone();
+            let response = self.client.execute(request).map_err(|e| format!("Request failed: {}", e))?;
+            if let Some(jar) = &self.cookies {
+                jar.set_cookies(&mut response.headers().get_all(header::SET_COOKIE).iter(), &url);
+            }
+
+            let status = response.status();
+            let location = response.headers().get(header::LOCATION).and_then(|l| l.to_str().ok());
+            let next = location.and_then(|location| url.join(location).ok());
+            let (Some(retry), Some(next)) = (retry.filter(|_| redirects_to(status)), next) else {
+                return Ok(Sent { response, redirects });
+            };
+            if redirects.len() >= MAX_REDIRECTS || (self.same_host && next.host_str() != first_host.as_deref()) {
+                return Ok(Sent { response, redirects });
+            }
+            request = redirected(retry, status, next);
+            redirects.push((url, status));
+        }
+    }
+}
+
+// The request again for the URL it was redirected to. 303, and 301 and 302 after a POST, go on
+// as a GET without the body; credentials are dropped at another host.
+fn redirected(mut request: Request, status: StatusCode, next: Url) -> Request {
+    let method = request.method().clone();
+    if (status == StatusCode::SEE_OTHER && method != Method::HEAD)
+        || (matches!(status, StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND) && method == Method::POST)
+    {
+        *request.method_mut() = Method::GET;
+        *request.body_mut() = None;
+        for name in [header::CONTENT_TYPE, header::CONTENT_LENGTH] {
+            request.headers_mut().remove(name);
+        }
+    }
+    let current = request.url();
+    if next.host_str() != current.host_str() || next.port_or_known_default() != current.port_or_known_default() {
+        for name in [header::AUTHORIZATION, header::COOKIE, header::PROXY_AUTHORIZATION, header::WWW_AUTHENTICATE] {
+            request.headers_mut().remove(name);
+        }
+    }
+    *request.url_mut() = next;
+    request
+}
@@ -0,0 +1,370 @@
+// HTTP requests made on behalf of the frontend, for APIs the webview can't call itself because of
+// CORS: search services and model providers, with the method, headers and query parameters they
+// need. Binary responses (speech audio, generated images, model files) come back as base64 when
+// small and as a temporary file when large. Multipart uploads read their files from disk here, so
+// audio and images don't have to pass through the webview.
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use reqwest::multipart::{Form, Part};
+use reqwest::{Method, Url};
+use tauri::{AppHandle, State};
+use tokio::io::AsyncWriteExt;
+
+use crate::http_client::{ClientDefaults, HttpClients};
+use crate::{db, settings};
+
+// Binary bodies up to this size come back inline; larger ones are written to a file as they arrive
+const MAX_INLINE_BYTES: usize = 4 * 1024 * 1024;
+// Under the system's temporary directory
+const DOWNLOAD_DIR: &str = "openchat-proxy";
+// Downloads older than this are removed when the next one starts
+const DOWNLOAD_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+// The response whatever its status; API error bodies (validation errors, rate limit details) are
+// often what the caller needs to see
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyResponse {
+    pub status: u16,
+    // Reason phrase, e.g. Not Found
+    pub status_text: String,
+    // Lowercase names; repeated headers are joined with ", "
+    pub headers: HashMap<String, String>,
+    // Text bodies; empty for binary ones
+    pub body: String,
+    // Binary bodies up to 4 MB, as plain base64 without a data: prefix
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body_base64: Option<String>,
+    // Larger binary bodies, saved to a temporary file that is removed after a day
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_path: Option<String>,
+    // Bytes of the body
+    pub size: u64,
+    // Media type without parameters, e.g. application/json; for generic downloads and untyped
+    // bodies, as told by their first bytes
+    pub content_type: Option<String>,
+    // From sending the request to the end of the body
+    pub elapsed_ms: u64,
+}
+
+// A field of a multipart/form-data body: a text `value`, or the file at `path`, sent under its own
+// name and with a media type from its extension unless `fileName` and `contentType` say otherwise
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormPart {
+    pub name: String,
+    pub value: Option<String>,
+    pub path: Option<String>,
+    pub file_name: Option<String>,
+    pub content_type: Option<String>,
+}
+
+enum Body {
+    Text(String),
+    Base64(String),
+    File(PathBuf),
+}
+
+fn is_text_type(media_type: &str) -> bool {
+    media_type.starts_with("text/")
+        || media_type.ends_with("+json")
+        || media_type.ends_with("+xml")
+        || matches!(
+            media_type,
+            "application/json"
+                | "application/xml"
+                | "application/javascript"
+                | "application/x-ndjson"
+                | "application/x-www-form-urlencoded"
+        )
+}
+
+// Signatures of binary formats: offset, bytes and media type
+const SIGNATURES: &[(usize, &[u8], &str)] = &[
+    (0, b"\x89PNG\r\n\x1a\n", "image/png"),
+    (0, b"\xff\xd8\xff", "image/jpeg"),
+    (0, b"GIF8", "image/gif"),
+    (8, b"WEBP", "image/webp"),
+    (8, b"WAVE", "audio/wav"),
+    (0, b"ID3", "audio/mpeg"),
+    (0, b"\xff\xfb", "audio/mpeg"),
+    (0, b"\xff\xf3", "audio/mpeg"),
+    (0, b"OggS", "audio/ogg"),
+    (0, b"fLaC", "audio/flac"),
+    (4, b"ftyp", "video/mp4"),
+    (0, b"%PDF-", "application/pdf"),
+    (0, b"PK\x03\x04", "application/zip"),
+    (0, b"GGUF", "application/x-gguf"),
+];
+
+// Media type of a body from its first bytes
+fn sniff(bytes: &[u8]) -> Option<&'static str> {
+    SIGNATURES
+        .iter()
+        .find(|(offset, signature, _)| bytes.get(*offset..).is_some_and(|rest| rest.starts_with(signature)))
+        .map(|(_, _, media_type)| *media_type)
+}
+
+fn extension(media_type: &str) -> &'static str {
+    match media_type {
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "audio/wav" | "audio/x-wav" | "audio/wave" => "wav",
+        "audio/mpeg" => "mp3",
+        "audio/ogg" => "ogg",
+        "audio/flac" => "flac",
+        "video/mp4" | "audio/mp4" => "mp4",
+        "application/pdf" => "pdf",
+        "application/zip" => "zip",
+        "application/x-gguf" => "gguf",
+        _ => "bin",
+    }
+}
+
+// Remove downloads past their age; failures only leave files behind
+fn remove_stale_downloads(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let modified = entry.metadata().and_then(|m| m.modified()).ok();
+        if modified.and_then(|m| m.elapsed().ok()).is_some_and(|age| age > DOWNLOAD_MAX_AGE) {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+async fn create_download(media_type: Option<&str>) -> Result<(PathBuf, tokio::fs::File), String> {
+    let dir = std::env::temp_dir().join(DOWNLOAD_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    remove_stale_downloads(&dir);
+    let name = format!("{}.{}", db::new_id("download"), extension(media_type.unwrap_or_default()));
+    let path = dir.join(name);
+    let file = tokio::fs::File::create(&path).await.map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    Ok((path, file))
+}
+
+// The body as text, or as base64 or a file when it's binary; also the media type and size. Text
+// is told by the declared media type, or for untyped bodies by being valid UTF-8.
+async fn read_body(
+    mut response: reqwest::Response,
+    declared: Option<String>,
+) -> Result<(Body, Option<String>, u64), String> {
+    let read_error = |e: reqwest::Error| format!("Failed to read response: {}", e);
+    let mut buffer = Vec::new();
+    if let Some(chunk) = response.chunk().await.map_err(read_error)? {
+        buffer.extend_from_slice(&chunk);
+    }
+    let content_type = match declared {
+        Some(declared) if declared != "application/octet-stream" => Some(declared),
+        declared => sniff(&buffer).map(str::to_string).or(declared),
+    };
+    let binary = match content_type.as_deref() {
+        Some(media_type) => !is_text_type(media_type),
+        // An error in the middle is invalid UTF-8; at the very end, only a character cut in two
+        None => matches!(std::str::from_utf8(&buffer), Err(e) if e.error_len().is_some()) || buffer.contains(&0),
+    };
+
+    if !binary {
+        while let Some(chunk) = response.chunk().await.map_err(read_error)? {
+            buffer.extend_from_slice(&chunk);
+        }
+        let size = buffer.len() as u64;
+        return Ok((Body::Text(String::from_utf8_lossy(&buffer).into_owned()), content_type, size));
+    }
+
+    let mut size = buffer.len() as u64;
+    let mut download = None;
+    if response.content_length().is_some_and(|length| length > MAX_INLINE_BYTES as u64) {
+        download = Some(create_download(content_type.as_deref()).await?);
+    }
+    loop {
+        if let Some((path, file)) = &mut download {
+            if !buffer.is_empty() {
+                file.write_all(&buffer).await.map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+                buffer.clear();
+            }
+        } else if buffer.len() > MAX_INLINE_BYTES {
+            download = Some(create_download(content_type.as_deref()).await?);
+            continue;
+        }
+        let Some(chunk) = response.chunk().await.map_err(read_error)? else { break };
+        size += chunk.len() as u64;
+        buffer.extend_from_slice(&chunk);
+    }
+
+    let body = match download {
+        Some((path, mut file)) => {
+            file.flush().await.map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            eprintln!("[Rust Proxy] Saved {} bytes to {}", size, path.display());
+            Body::File(path)
+        }
+        None => Body::Base64(base64::engine::general_purpose::STANDARD.encode(&buffer)),
+    };
+    Ok((body, content_type, size))
+}
+
+fn parse_method(name: &str) -> Result<Method, String> {
+    match name.trim().to_uppercase().as_str() {
+        "GET" => Ok(Method::GET),
+        "POST" => Ok(Method::POST),
+        "PUT" => Ok(Method::PUT),
+        "PATCH" => Ok(Method::PATCH),
+        "DELETE" => Ok(Method::DELETE),
+        "HEAD" => Ok(Method::HEAD),
+        "OPTIONS" => Ok(Method::OPTIONS),
+        _ => Err(format!("Unsupported method: {}", name)),
+    }
+}
+
+pub fn response_headers(headers: &HeaderMap) -> HashMap<String, String> {
+    let mut map: HashMap<String, String> = HashMap::new();
+    for (name, value) in headers {
+        let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+        map.entry(name.as_str().to_string())
+            .and_modify(|joined| {
+                joined.push_str(", ");
+                joined.push_str(&value);
+            })
+            .or_insert(value);
+    }
+    map
+}
+
+fn header_map(headers: &HashMap<String, String>) -> Result<HeaderMap, String> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        let name = HeaderName::from_bytes(name.trim().as_bytes()).map_err(|_| format!("Invalid header name: {}", name))?;
+        let value = HeaderValue::from_str(value).map_err(|_| format!("Invalid value for header {}", name))?;
+        map.insert(name, value);
+    }
+    Ok(map)
+}
+
+// Media type of a response from its headers, without parameters
+pub fn media_type(headers: &HashMap<String, String>) -> Option<String> {
+    let value = headers.get(CONTENT_TYPE.as_str())?.split(';').next()?.trim().to_lowercase();
+    (!value.is_empty()).then_some(value)
+}
+
+// The frontend's request with its method and headers; `query` is added to the URL's own
+// parameters, and bodies go as JSON unless `headers` give a Content-Type
+pub fn build_request(
+    client: &reqwest::Client,
+    method: &str,
+    url: &str,
+    body: Option<String>,
+    headers: Option<HashMap<String, String>>,
+    query: Option<HashMap<String, String>>,
+) -> Result<reqwest::RequestBuilder, String> {
+    let method = parse_method(method)?;
+    let mut parsed = Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("Only http and https schemes are allowed".to_string());
+    }
+    if let Some(query) = query.filter(|query| !query.is_empty()) {
+        parsed.query_pairs_mut().extend_pairs(query.iter());
+    }
+    let mut headers = header_map(&headers.unwrap_or_default())?;
+
+    let mut request = client.request(method, parsed);
+    if let Some(body) = body {
+        if !headers.contains_key(CONTENT_TYPE) {
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        }
+        request = request.body(body);
+    }
+    Ok(request.headers(headers))
+}
+
+// Files are streamed from disk as the request is sent
+async fn multipart_form(parts: Vec<FormPart>) -> Result<Form, String> {
+    let mut form = Form::new();
+    for field in parts {
+        let mut part = match (field.value, field.path) {
+            (Some(value), None) => Part::text(value),
+            (None, Some(path)) => {
+                Part::file(&path).await.map_err(|e| format!("Failed to open {} for upload: {}", path, e))?
+            }
+            _ => return Err(format!("Form field {} needs either a value or a path", field.name)),
+        };
+        if let Some(file_name) = field.file_name {
+            part = part.file_name(file_name);
+        }
+        if let Some(content_type) = field.content_type {
+            part = part
+                .mime_str(&content_type)
+                .map_err(|_| format!("Invalid content type for form field {}: {}", field.name, content_type))?;
+        }
+        form = form.part(field.name, part);
+    }
+    Ok(form)
+}
+
+// Send a request for the frontend (see build_request). `timeout_ms` replaces the proxyTimeoutSecs
+// setting for this request, including the download of the body. Responses come back with any
+// status; only failing to get one is an error. `form` sends a multipart/form-data body instead of
+// `body`, for transcription and upload APIs.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn proxy_http_request(
+    app: AppHandle,
+    clients: State<'_, HttpClients>,
+    url: String,
+    method: String,
+    body: Option<String>,
+    headers: Option<HashMap<String, String>>,
+    query: Option<HashMap<String, String>>,
+    timeout_ms: Option<u64>,
+    form: Option<Vec<FormPart>>,
+) -> Result<ProxyResponse, String> {
+    eprintln!("[Rust Proxy] Request: {} {}", method, url);
+    let settings = settings::load(&app);
+    let client = clients.client(&ClientDefaults::from_settings(&settings), None)?;
+    if body.is_some() && form.is_some() {
+        return Err("A request has either a body or a form, not both".to_string());
+    }
+    let mut request = build_request(&client, &method, &url, body, headers, query)?;
+    if let Some(form) = form {
+        // Sets the Content-Type with the form's boundary
+        request = request.multipart(multipart_form(form).await?);
+    }
+    let timeout = match timeout_ms {
+        Some(timeout_ms) => Duration::from_millis(timeout_ms.max(1)),
+        None => Duration::from_secs(settings.proxy_timeout_secs),
+    };
+    request = request.timeout(timeout);
+
+    let started = Instant::now();
+    let response = request.send().await.map_err(|e| format!("Request failed: {}", e))?;
+
+    let status = response.status();
+    eprintln!("[Rust Proxy] Response status: {}", status);
+    let headers = response_headers(response.headers());
+    let content_type = media_type(&headers);
+
+    let (body, content_type, size) = read_body(response, content_type).await?;
+
+    eprintln!("[Rust Proxy] Response length: {} bytes", size);
+    let mut proxied = ProxyResponse {
+        status: status.as_u16(),
+        status_text: status.canonical_reason().unwrap_or_default().to_string(),
+        headers,
+        body: String::new(),
+        body_base64: None,
+        file_path: None,
+        size,
+        content_type,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    };
+    match body {
+        Body::Text(text) => proxied.body = text,
+        Body::Base64(data) => proxied.body_base64 = Some(data),
+        Body::File(path) => proxied.file_path = Some(path.to_string_lossy().into_owned()),
+    }
+    Ok(proxied)
+}
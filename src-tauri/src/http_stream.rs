@@ -0,0 +1,304 @@
+// Streaming requests for the frontend, for token streams of OpenAI-style APIs that the webview
+// can't call itself. Server-Sent Events and NDJSON are parsed here, and each event goes to the
+// frontend over a channel as soon as it arrives. Every stream runs under an id the caller gives,
+// and cancel_sse_request(id) closes it.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tauri::ipc::Channel;
+use tauri::{AppHandle, State};
+
+use crate::batches::CancelToken;
+use crate::http_client::{ClientDefaults, HttpClients};
+use crate::http_proxy::{build_request, media_type, response_headers};
+use crate::settings;
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum StreamEvent {
+    // The response arrived; its events follow
+    Open {
+        status: u16,
+        status_text: String,
+        headers: HashMap<String, String>,
+        content_type: Option<String>,
+    },
+    // A server-sent event, or one line of NDJSON as `data`
+    #[serde(rename_all = "camelCase")]
+    Event {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        event: Option<String>,
+        data: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+    },
+    // The whole body of a response that isn't a stream, such as an API error
+    Body { body: String },
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamEnd {
+    // Events sent, the Open event not counted
+    pub events: usize,
+    pub cancelled: bool,
+}
+
+// Streams running, by id
+#[derive(Clone, Default)]
+pub struct StreamRequests {
+    running: Arc<Mutex<HashMap<String, CancelToken>>>,
+}
+
+impl StreamRequests {
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, CancelToken>> {
+        self.running.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+// Complete lines of a byte stream; a line's bytes are only decoded once all of them are in
+#[derive(Default)]
+struct Lines {
+    pending: Vec<u8>,
+}
+
+impl Lines {
+    fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(chunk);
+        let Some(end) = self.pending.iter().rposition(|&b| b == b'\n') else { return Vec::new() };
+        let complete: Vec<u8> = self.pending.drain(..=end).collect();
+        complete[..end]
+            .split(|&b| b == b'\n')
+            .map(|line| String::from_utf8_lossy(line.strip_suffix(b"\r").unwrap_or(line)).into_owned())
+            .collect()
+    }
+
+    // A last line without a line break
+    fn rest(&mut self) -> Option<String> {
+        let rest = std::mem::take(&mut self.pending);
+        (!rest.is_empty()).then(|| String::from_utf8_lossy(rest.strip_suffix(b"\r").unwrap_or(&rest)).into_owned())
+    }
+}
+
+// Server-Sent Events as the HTML standard parses them: fields until a blank line, data lines
+// joined with line breaks, comment lines (":") skipped
+#[derive(Default)]
+struct SseParser {
+    event: Option<String>,
+    data: Option<String>,
+    // Event ids carry over to later events until changed
+    last_id: Option<String>,
+}
+
+impl SseParser {
+    fn line(&mut self, line: &str) -> Option<StreamEvent> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => match &mut self.data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => self.data = Some(value.to_string()),
+            },
+            "id" if !value.contains('\0') => self.last_id = Some(value.to_string()),
+            _ => {}
+        }
+        None
+    }
+
+    // Events without data are dropped, as browsers do
+    fn dispatch(&mut self) -> Option<StreamEvent> {
+        let event = self.event.take().filter(|event| !event.is_empty());
+        Some(StreamEvent::Event {
+            event,
+            data: self.data.take()?,
+            id: self.last_id.clone(),
+        })
+    }
+}
+
+enum Format {
+    Sse,
+    Ndjson,
+    // Anything else comes as one Body event
+    Whole,
+}
+
+fn stream_format(content_type: Option<&str>) -> Format {
+    match content_type {
+        Some("text/event-stream") => Format::Sse,
+        Some("application/x-ndjson" | "application/ndjson" | "application/jsonl" | "application/jsonlines") => {
+            Format::Ndjson
+        }
+        _ => Format::Whole,
+    }
+}
+
+// The events of a chunk of the stream, by its format; the whole body collects in `body`
+fn parse_chunk(
+    format: &Format,
+    lines: &mut Lines,
+    sse: &mut SseParser,
+    chunk: &[u8],
+    body: &mut Vec<u8>,
+) -> Vec<StreamEvent> {
+    match format {
+        Format::Sse => lines.push(chunk).iter().filter_map(|line| sse.line(line)).collect(),
+        Format::Ndjson => lines
+            .push(chunk)
+            .into_iter()
+            .filter(|line| !line.trim().is_empty())
+            .map(|data| StreamEvent::Event {
+                event: None,
+                data,
+                id: None,
+            })
+            .collect(),
+        Format::Whole => {
+            body.extend_from_slice(chunk);
+            Vec::new()
+        }
+    }
+}
+
+fn send(channel: &Channel<StreamEvent>, event: StreamEvent) -> Result<(), String> {
+    channel.send(event).map_err(|e| format!("Failed to send a stream event: {}", e))
+}
+
+async fn run_stream(
+    response: reqwest::Response,
+    channel: &Channel<StreamEvent>,
+    cancel: &CancelToken,
+    idle_timeout: Duration,
+) -> Result<StreamEnd, String> {
+    let status = response.status();
+    let headers = response_headers(response.headers());
+    let content_type = media_type(&headers);
+    // Error statuses come with an explanation rather than a stream
+    let format = if status.is_success() { stream_format(content_type.as_deref()) } else { Format::Whole };
+    send(
+        channel,
+        StreamEvent::Open {
+            status: status.as_u16(),
+            status_text: status.canonical_reason().unwrap_or_default().to_string(),
+            headers,
+            content_type,
+        },
+    )?;
+
+    let mut response = response;
+    let (mut lines, mut sse, mut body) = (Lines::default(), SseParser::default(), Vec::new());
+    let mut events = 0;
+    loop {
+        let chunk = tokio::select! {
+            _ = cancel.cancelled() => return Ok(StreamEnd { events, cancelled: true }),
+            chunk = tokio::time::timeout(idle_timeout, response.chunk()) => chunk
+                .map_err(|_| format!("No data for {} seconds", idle_timeout.as_secs()))?
+                .map_err(|e| format!("Stream failed: {}", e))?,
+        };
+        let Some(chunk) = chunk else { break };
+        for event in parse_chunk(&format, &mut lines, &mut sse, &chunk, &mut body) {
+            send(channel, event)?;
+            events += 1;
+        }
+    }
+
+    // What is left when the stream ends without a final line break or blank line
+    let last = match format {
+        Format::Sse => lines.rest().and_then(|line| sse.line(&line)).or_else(|| sse.dispatch()),
+        Format::Ndjson => lines.rest().filter(|line| !line.trim().is_empty()).map(|data| StreamEvent::Event {
+            event: None,
+            data,
+            id: None,
+        }),
+        Format::Whole => Some(StreamEvent::Body {
+            body: String::from_utf8_lossy(&body).into_owned(),
+        }),
+    };
+    if let Some(event) = last {
+        send(channel, event)?;
+        events += 1;
+    }
+    Ok(StreamEnd { events, cancelled: false })
+}
+
+// Open a streaming request (method, headers, query and body as for proxy_http_request) and send
+// its events over `on_event` as they arrive: first Open with the status and headers, then an
+// Event for each server-sent event or NDJSON line. Other responses, such as API errors, come as
+// one Body event. Resolves when the stream ends or is cancelled with cancel_sse_request(id).
+// `idle_timeout_ms` (by default proxyTimeoutSecs) is how long the stream may go without data.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn proxy_sse_request(
+    app: AppHandle,
+    clients: State<'_, HttpClients>,
+    streams: State<'_, StreamRequests>,
+    id: String,
+    url: String,
+    method: String,
+    body: Option<String>,
+    headers: Option<HashMap<String, String>>,
+    query: Option<HashMap<String, String>>,
+    idle_timeout_ms: Option<u64>,
+    on_event: Channel<StreamEvent>,
+) -> Result<StreamEnd, String> {
+    eprintln!("[Rust Proxy] Stream {}: {} {}", id, method, url);
+    let settings = settings::load(&app);
+    let idle_timeout = match idle_timeout_ms {
+        Some(ms) => Duration::from_millis(ms.max(1)),
+        None => Duration::from_secs(settings.proxy_timeout_secs),
+    };
+    // No overall timeout; a stream lasts as long as the answer takes
+    let client = clients.client(&ClientDefaults::from_settings(&settings), None)?;
+    let request = build_request(&client, &method, &url, body, headers, query)?;
+
+    let cancel = {
+        let mut running = streams.lock();
+        if running.contains_key(&id) {
+            return Err(format!("A stream {} is already running", id));
+        }
+        let token = CancelToken::default();
+        running.insert(id.clone(), token.clone());
+        token
+    };
+    let result = async {
+        let response = tokio::select! {
+            _ = cancel.cancelled() => return Ok(StreamEnd { events: 0, cancelled: true }),
+            response = tokio::time::timeout(idle_timeout, request.send()) => response
+                .map_err(|_| format!("No response within {} seconds", idle_timeout.as_secs()))?
+                .map_err(|e| format!("Request failed: {}", e))?,
+        };
+        run_stream(response, &on_event, &cancel, idle_timeout).await
+    }
+    .await;
+    streams.lock().remove(&id);
+
+    match &result {
+        Ok(end) => eprintln!("[Rust Proxy] Stream {} ended after {} events", id, end.events),
+        Err(err) => eprintln!("[Rust Proxy] Stream {} failed: {}", id, err),
+    }
+    result
+}
+
+// Close the stream `id`; returns whether it was running
+#[tauri::command]
+pub fn cancel_sse_request(streams: State<'_, StreamRequests>, id: String) -> bool {
+    match streams.lock().get(&id) {
+        Some(token) => {
+            eprintln!("[Rust Proxy] Cancelling stream {}", id);
+            token.cancel();
+            true
+        }
+        None => false,
+    }
+}
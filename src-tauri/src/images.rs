@@ -0,0 +1,186 @@
+// Image preparation for vision models: images are decoded, turned upright, scaled down and
+// re-encoded in Rust so the webview never holds full-size photos. Re-encoding drops EXIF and
+// other metadata (camera, GPS) along the way.
+use std::io::{BufRead, Cursor, Seek};
+use std::time::Duration;
+use std::path::Path;
+
+use base64::Engine;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader};
+
+// Files larger than this aren't read at all
+const MAX_INPUT_BYTES: u64 = 50 * 1024 * 1024;
+// Decoded images larger than this many pixels are rejected before allocation
+const MAX_INPUT_PIXELS: u64 = 100_000_000;
+// Longest side after resizing, by default and at most
+const DEFAULT_MAX_DIM: u32 = 2048;
+const MAX_DIM_LIMIT: u32 = 8192;
+// OpenAI rejects images above 20 MB
+const MAX_OUTPUT_BYTES: usize = 20 * 1024 * 1024;
+const JPEG_QUALITY: u8 = 85;
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreparedImage {
+    // Plain base64 without a data: prefix, as Ollama's `images` expects; OpenAI takes it as
+    // `data:{mimeType};base64,{data}`
+    data: String,
+    mime_type: String,
+    width: u32,
+    height: u32,
+    original_width: u32,
+    original_height: u32,
+    bytes: usize,
+}
+
+fn input_format(format: ImageFormat) -> Result<ImageFormat, String> {
+    match format {
+        ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::Gif | ImageFormat::WebP | ImageFormat::Bmp | ImageFormat::Tiff => {
+            Ok(format)
+        }
+        other => Err(format!("Unsupported image format: {:?}", other)),
+    }
+}
+
+// Requested output format; by default PNG when the image has transparency, JPEG otherwise
+fn output_format(requested: Option<&str>, image: &DynamicImage) -> Result<ImageFormat, String> {
+    match requested.map(|f| f.trim().to_lowercase()).as_deref() {
+        None | Some("") | Some("auto") => Ok(if image.color().has_alpha() {
+            ImageFormat::Png
+        } else {
+            ImageFormat::Jpeg
+        }),
+        Some("jpeg" | "jpg") => Ok(ImageFormat::Jpeg),
+        Some("png") => Ok(ImageFormat::Png),
+        Some(other) => Err(format!("Unsupported output format '{}'; use jpeg or png", other)),
+    }
+}
+
+fn check_size(size: u64) -> Result<(), String> {
+    if size > MAX_INPUT_BYTES {
+        return Err(format!("Image is too large ({} MB, at most {} MB)", size / 1024 / 1024, MAX_INPUT_BYTES / 1024 / 1024));
+    }
+    Ok(())
+}
+
+pub fn prepare(path: &Path, max_dim: Option<u32>, format: Option<&str>) -> Result<PreparedImage, String> {
+    let size = std::fs::metadata(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+        .len();
+    check_size(size)?;
+    let reader = ImageReader::open(path)
+        .and_then(|r| r.with_guessed_format())
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    prepare_reader(reader, max_dim, format)
+}
+
+fn prepare_bytes(bytes: &[u8], max_dim: Option<u32>, format: Option<&str>) -> Result<PreparedImage, String> {
+    check_size(bytes.len() as u64)?;
+    let reader = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| format!("Failed to read image: {}", e))?;
+    prepare_reader(reader, max_dim, format)
+}
+
+fn prepare_reader<R: BufRead + Seek>(
+    reader: ImageReader<R>,
+    max_dim: Option<u32>,
+    format: Option<&str>,
+) -> Result<PreparedImage, String> {
+    let max_dim = max_dim.unwrap_or(DEFAULT_MAX_DIM);
+    if max_dim == 0 || max_dim > MAX_DIM_LIMIT {
+        return Err(format!("maxDim must be between 1 and {}", MAX_DIM_LIMIT));
+    }
+    // The content decides the format, whatever the file is called
+    input_format(reader.format().ok_or("Not a recognized image file")?)?;
+    let mut decoder = reader.into_decoder().map_err(|e| format!("Failed to decode image: {}", e))?;
+    let (width, height) = decoder.dimensions();
+    if width as u64 * height as u64 > MAX_INPUT_PIXELS {
+        return Err(format!("Image is too large ({}x{} pixels)", width, height));
+    }
+    // Phones store photos sideways and rotate them through EXIF, which is about to be dropped
+    let orientation = decoder.orientation().map_err(|e| format!("Failed to decode image: {}", e))?;
+    let mut image = DynamicImage::from_decoder(decoder).map_err(|e| format!("Failed to decode image: {}", e))?;
+    image.apply_orientation(orientation);
+    let (original_width, original_height) = (image.width(), image.height());
+
+    if image.width().max(image.height()) > max_dim {
+        image = image.resize(max_dim, max_dim, image::imageops::FilterType::Lanczos3);
+    }
+    let format = output_format(format, &image)?;
+    let mut encoded = Vec::new();
+    match format {
+        ImageFormat::Jpeg => {
+            // JPEG has no alpha channel
+            let rgb = DynamicImage::ImageRgb8(image.to_rgb8());
+            let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut encoded, JPEG_QUALITY);
+            rgb.write_with_encoder(encoder)
+        }
+        _ => image.write_to(&mut Cursor::new(&mut encoded), format),
+    }
+    .map_err(|e| format!("Failed to encode image: {}", e))?;
+    if encoded.len() > MAX_OUTPUT_BYTES {
+        return Err(format!(
+            "Prepared image is {} MB, above the {} MB limit; use a smaller maxDim or JPEG",
+            encoded.len() / 1024 / 1024,
+            MAX_OUTPUT_BYTES / 1024 / 1024
+        ));
+    }
+
+    Ok(PreparedImage {
+        data: base64::engine::general_purpose::STANDARD.encode(&encoded),
+        mime_type: format.to_mime_type().to_string(),
+        width: image.width(),
+        height: image.height(),
+        original_width,
+        original_height,
+        bytes: encoded.len(),
+    })
+}
+
+// Load an image for a vision request: at most `maxDim` pixels on its longest side (2048 by
+// default), re-encoded as `format` ("jpeg", "png" or "auto") without metadata
+#[tauri::command]
+pub async fn prepare_image(path: String, max_dim: Option<u32>, format: Option<String>) -> Result<PreparedImage, String> {
+    tokio::task::spawn_blocking(move || prepare(Path::new(&path), max_dim, format.as_deref()))
+        .await
+        .map_err(|e| format!("Image task failed: {}", e))?
+}
+
+// Download an image (e.g. from search_images) and prepare it like prepare_image
+#[tauri::command]
+pub async fn prepare_image_url(
+    app: tauri::AppHandle,
+    url: String,
+    max_dim: Option<u32>,
+    format: Option<String>,
+) -> Result<PreparedImage, String> {
+    let parsed = reqwest::Url::parse(&url).map_err(|e| format!("Invalid image URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("Only http(s) image URLs can be fetched".to_string());
+    }
+    let settings = crate::settings::load(&app);
+    let client = crate::http_client::for_app(&app, &settings)?;
+    let mut response = client
+        .get(parsed)
+        .timeout(Duration::from_secs(settings.fetch_timeout_secs))
+        .header("User-Agent", crate::http_client::BROWSER_USER_AGENT)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to download image: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Image download failed with status {}", response.status()));
+    }
+    if let Some(length) = response.content_length() {
+        check_size(length)?;
+    }
+    // Servers don't always announce the length; stop reading once the limit is passed
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| format!("Failed to download image: {}", e))? {
+        bytes.extend_from_slice(&chunk);
+        check_size(bytes.len() as u64)?;
+    }
+    tokio::task::spawn_blocking(move || prepare_bytes(&bytes, max_dim, format.as_deref()))
+        .await
+        .map_err(|e| format!("Image task failed: {}", e))?
+}
@@ -0,0 +1,700 @@
+// Importers for conversation exports from other chat apps: ChatGPT data exports and the chat
+// histories of LM Studio, Jan and SillyTavern
+use std::collections::{BTreeSet, HashMap};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::{json, Value};
+use tauri::State;
+
+use crate::conversations::{self, Conversation};
+use crate::db::{self, Database};
+use crate::llm::ChatMessage;
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedConversation {
+    source_id: String,
+    conversation_id: String,
+    title: String,
+    message_count: usize,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedItem {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    reason: String,
+}
+
+#[derive(serde::Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    imported: Vec<ImportedConversation>,
+    // Conversations imported earlier, identified by their source id
+    duplicates: Vec<ImportedConversation>,
+    skipped: Vec<SkippedItem>,
+    // Messages left out of imported conversations (empty, tool output, images, ...)
+    skipped_messages: usize,
+    // Source fields with no counterpart in OpenChat, as "scope.field"; their data is not imported
+    unmapped_fields: BTreeSet<String>,
+}
+
+// A conversation converted from a foreign format, ready to store
+struct ParsedConversation {
+    source_id: String,
+    title: String,
+    model: Option<String>,
+    created_at: String,
+    updated_at: String,
+    messages: Vec<ParsedMessage>,
+    // Index of the message ending the active branch
+    active: Option<usize>,
+}
+
+struct ParsedMessage {
+    message: ChatMessage,
+    metadata: Value,
+    created_at: String,
+    // Index of the parent message in the same conversation
+    parent: Option<usize>,
+}
+
+fn timestamp(value: Option<&Value>) -> Option<String> {
+    let secs = value?.as_f64()?;
+    chrono::DateTime::from_timestamp(secs.trunc() as i64, (secs.fract() * 1e9) as u32).map(|t| t.to_rfc3339())
+}
+
+// Epoch seconds or milliseconds (nothing recent is below 1e11 in milliseconds), RFC 3339, or the
+// local-time formats SillyTavern writes
+fn flexible_timestamp(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::Number(n) => {
+            let n = n.as_f64()?;
+            timestamp(Some(&json!(if n > 1e11 { n / 1000.0 } else { n })))
+        }
+        Value::String(s) => {
+            let s = s.trim();
+            if let Ok(t) = chrono::DateTime::parse_from_rfc3339(s) {
+                return Some(t.with_timezone(&chrono::Utc).to_rfc3339());
+            }
+            ["%B %d, %Y %I:%M%p", "%B %d, %Y %I:%M %p", "%Y-%m-%d@%Hh%Mm%Ss", "%Y-%m-%d @%Hh %Mm %Ss %3fms"]
+                .iter()
+                .find_map(|format| chrono::NaiveDateTime::parse_from_str(s, format).ok())
+                .and_then(|t| t.and_local_timezone(chrono::Local).earliest())
+                .map(|t| t.with_timezone(&chrono::Utc).to_rfc3339())
+        }
+        _ => None,
+    }
+}
+
+// Record the keys of `value` that the importer doesn't carry over
+fn note_unmapped(report: &mut ImportReport, scope: &str, value: &Value, mapped: &[&str]) {
+    let Some(object) = value.as_object() else { return };
+    for key in object.keys().filter(|key| !mapped.contains(&key.as_str())) {
+        report.unmapped_fields.insert(format!("{}.{}", scope, key));
+    }
+}
+
+// Files under `path` (or `path` itself) accepted by `matches`, sorted for a stable import order
+fn collect_files(path: &Path, matches: &dyn Fn(&Path) -> bool) -> Result<Vec<PathBuf>, String> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files = Vec::new();
+    let entries = std::fs::read_dir(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    for entry in entries.filter_map(|e| e.ok()) {
+        let entry_path = entry.path();
+        if entry_path.is_dir() {
+            files.extend(collect_files(&entry_path, matches)?);
+        } else if matches(&entry_path) {
+            files.push(entry_path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+// Text of a content value: a plain string, or a list of parts where text parts are either
+// {"type": "text", "text": "..."} or {"type": "text", "text": {"value": "..."}}
+fn content_text(content: &Value, report: &mut ImportReport) -> String {
+    if let Some(text) = content.as_str() {
+        return text.to_string();
+    }
+    let mut texts = Vec::new();
+    for part in content.as_array().map(|p| p.as_slice()).unwrap_or(&[]) {
+        let kind = part["type"].as_str().unwrap_or("unknown");
+        match part["text"].as_str().or_else(|| part["text"]["value"].as_str()) {
+            Some(text) if kind == "text" => texts.push(text),
+            _ => {
+                report.unmapped_fields.insert(format!("content.{}", kind));
+            }
+        }
+    }
+    texts.join("\n")
+}
+
+fn is_chat_role(role: &str) -> bool {
+    matches!(role, "user" | "assistant" | "system")
+}
+
+// Stored system prompts become a leading system message; returns its index
+fn push_system_prompt(messages: &mut Vec<ParsedMessage>, prompt: Option<&str>, source: &str, created_at: &str) -> Option<usize> {
+    let prompt = prompt.map(str::trim).filter(|p| !p.is_empty())?;
+    messages.push(ParsedMessage {
+        message: ChatMessage::new("system", prompt),
+        metadata: json!({ "source": source, "systemPrompt": true }),
+        created_at: created_at.to_string(),
+        parent: None,
+    });
+    Some(messages.len() - 1)
+}
+
+// Read conversations.json either directly or from inside the export ZIP
+fn read_export_json(path: &str) -> Result<Value, String> {
+    let is_zip = path.to_lowercase().ends_with(".zip");
+    let text = if is_zip {
+        let file = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+        let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Invalid ZIP archive: {}", e))?;
+        let mut entry = archive
+            .by_name("conversations.json")
+            .map_err(|_| "conversations.json not found in the archive".to_string())?;
+        let mut text = String::new();
+        entry
+            .read_to_string(&mut text)
+            .map_err(|e| format!("Failed to read conversations.json: {}", e))?;
+        text
+    } else {
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?
+    };
+    serde_json::from_str(&text).map_err(|e| format!("Invalid export JSON: {}", e))
+}
+
+// Text of a ChatGPT message; non-text parts (images, files) are dropped
+fn chatgpt_text(content: &Value) -> String {
+    if let Some(parts) = content.get("parts").and_then(|p| p.as_array()) {
+        return parts
+            .iter()
+            .filter_map(|p| p.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+    content.get("text").and_then(|t| t.as_str()).unwrap_or("").to_string()
+}
+
+// Convert the mapping tree, keeping every branch and remembering which one was active
+fn parse_chatgpt_conversation(raw: &Value, report: &mut ImportReport) -> Result<ParsedConversation, String> {
+    let source_id = raw
+        .get("conversation_id")
+        .or_else(|| raw.get("id"))
+        .and_then(|v| v.as_str())
+        .ok_or("missing conversation id")?
+        .to_string();
+    let mapping = raw.get("mapping").and_then(|m| m.as_object()).ok_or("missing message mapping")?;
+    let title = raw
+        .get("title")
+        .and_then(|t| t.as_str())
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or("Imported chat")
+        .to_string();
+
+    // Depth-first from the roots so parents are always stored before their replies.
+    // Nodes that are not imported pass their parent on to their children.
+    let is_root = |node: &Value| {
+        node.get("parent")
+            .and_then(|p| p.as_str())
+            .is_none_or(|p| !mapping.contains_key(p))
+    };
+    let mut stack: Vec<(&str, Option<usize>)> = mapping
+        .iter()
+        .filter(|(_, node)| is_root(node))
+        .map(|(id, _)| (id.as_str(), None))
+        .collect();
+    let mut nearest: HashMap<&str, Option<usize>> = HashMap::new();
+    let mut model = None;
+    let mut messages: Vec<ParsedMessage> = Vec::new();
+
+    while let Some((id, parent)) = stack.pop() {
+        let Some(node) = mapping.get(id) else { continue };
+        if nearest.contains_key(id) {
+            continue;
+        }
+        let mut current = parent;
+        if let Some(message) = node.get("message").filter(|m| !m.is_null()) {
+            let role = message["author"]["role"].as_str().unwrap_or("");
+            let content = chatgpt_text(&message["content"]);
+            let hidden = message["metadata"]["is_visually_hidden_from_conversation"].as_bool() == Some(true);
+            if !matches!(role, "user" | "assistant" | "system") || content.trim().is_empty() || hidden {
+                // Empty root/system nodes are structural, not content the user would miss
+                if role != "system" || !content.trim().is_empty() {
+                    report.skipped_messages += 1;
+                }
+            } else {
+                let slug = message["metadata"]["model_slug"].as_str().map(str::to_string);
+                if role == "assistant" && slug.is_some() {
+                    model = slug.clone();
+                }
+                messages.push(ParsedMessage {
+                    message: ChatMessage::new(role, content),
+                    metadata: json!({
+                        "source": "chatgpt",
+                        "sourceMessageId": message["id"],
+                        "model": slug,
+                    }),
+                    created_at: timestamp(message.get("create_time"))
+                        .or_else(|| timestamp(raw.get("create_time")))
+                        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339()),
+                    parent,
+                });
+                current = Some(messages.len() - 1);
+            }
+        }
+        nearest.insert(id, current);
+
+        let children = node.get("children").and_then(|c| c.as_array()).map(|c| c.as_slice()).unwrap_or(&[]);
+        // Reversed so the first child is visited (and numbered) first
+        for child in children.iter().rev().filter_map(|c| c.as_str()) {
+            if let Some((key, _)) = mapping.get_key_value(child) {
+                stack.push((key.as_str(), current));
+            }
+        }
+    }
+
+    // Show the branch that was selected in ChatGPT, or the last message otherwise
+    let active = raw
+        .get("current_node")
+        .and_then(|n| n.as_str())
+        .and_then(|n| nearest.get(n).copied().flatten())
+        .or_else(|| messages.len().checked_sub(1));
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let created_at = timestamp(raw.get("create_time")).unwrap_or_else(|| now.clone());
+    Ok(ParsedConversation {
+        source_id,
+        title,
+        model,
+        updated_at: timestamp(raw.get("update_time")).unwrap_or_else(|| created_at.clone()),
+        created_at,
+        messages,
+        active,
+    })
+}
+
+fn existing_import(conn: &Connection, source: &str, source_id: &str) -> rusqlite::Result<Option<String>> {
+    conn.query_row(
+        "SELECT conversation_id FROM conversation_imports WHERE source = ?1 AND source_id = ?2",
+        params![source, source_id],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+// Store parsed conversations, each in its own transaction, skipping ones already imported
+fn store_conversations(db: &Database, source: &str, parsed: Vec<ParsedConversation>, report: &mut ImportReport) -> Result<(), String> {
+    for conversation in parsed {
+        let message_count = conversation.messages.len();
+        if message_count == 0 {
+            report.skipped.push(SkippedItem {
+                source_id: Some(conversation.source_id),
+                title: Some(conversation.title),
+                reason: "no importable messages".to_string(),
+            });
+            continue;
+        }
+
+        let outcome = db.with_conn(|conn| {
+            if let Some(existing) = existing_import(conn, source, &conversation.source_id)? {
+                return Ok(Err(existing));
+            }
+            let tx = conn.unchecked_transaction()?;
+            let stored = Conversation {
+                id: db::new_id("conv"),
+                title: conversation.title.clone(),
+                model: conversation.model.clone(),
+                persona_id: None,
+                active_leaf_id: None,
+                created_at: conversation.created_at.clone(),
+                updated_at: conversation.updated_at.clone(),
+                revision: 0,
+            };
+            conversations::insert_conversation(&tx, &stored)?;
+            let mut ids: Vec<String> = Vec::with_capacity(message_count);
+            for parsed in &conversation.messages {
+                let parent_id = parsed.parent.map(|i| ids[i].as_str());
+                let id = conversations::insert_message(
+                    &tx,
+                    &stored.id,
+                    parent_id,
+                    &parsed.message,
+                    Some(&parsed.metadata),
+                    &parsed.created_at,
+                )?;
+                ids.push(id);
+            }
+            tx.execute(
+                "UPDATE conversations SET active_leaf_id = ?1 WHERE id = ?2",
+                params![conversation.active.map(|i| ids[i].as_str()), stored.id],
+            )?;
+            tx.execute(
+                "INSERT INTO conversation_imports (source, source_id, conversation_id, imported_at) VALUES (?1, ?2, ?3, ?4)",
+                params![source, conversation.source_id, stored.id, chrono::Utc::now().to_rfc3339()],
+            )?;
+            tx.commit()?;
+            Ok(Ok(stored.id))
+        })?;
+
+        let entry = |conversation_id| ImportedConversation {
+            source_id: conversation.source_id.clone(),
+            conversation_id,
+            title: conversation.title.clone(),
+            message_count,
+        };
+        match outcome {
+            Ok(id) => report.imported.push(entry(id)),
+            Err(existing) => report.duplicates.push(entry(existing)),
+        }
+    }
+    Ok(())
+}
+
+// Import a chat.openai.com data export (the ZIP or its conversations.json)
+#[tauri::command]
+pub fn import_chatgpt_export(db: State<'_, Database>, path: String) -> Result<ImportReport, String> {
+    let export = read_export_json(&path)?;
+    let items = export.as_array().ok_or("Expected a list of conversations")?;
+
+    let mut report = ImportReport::default();
+    let mut parsed = Vec::new();
+    for item in items {
+        match parse_chatgpt_conversation(item, &mut report) {
+            Ok(conversation) => parsed.push(conversation),
+            Err(reason) => report.skipped.push(SkippedItem {
+                source_id: item.get("id").and_then(|v| v.as_str()).map(str::to_string),
+                title: item.get("title").and_then(|v| v.as_str()).map(str::to_string),
+                reason: reason.to_string(),
+            }),
+        }
+    }
+    finish_import(&db, "chatgpt", "ChatGPT export", parsed, report)
+}
+
+fn finish_import(
+    db: &Database,
+    source: &str,
+    label: &str,
+    parsed: Vec<ParsedConversation>,
+    mut report: ImportReport,
+) -> Result<ImportReport, String> {
+    store_conversations(db, source, parsed, &mut report)?;
+    eprintln!(
+        "[Import] {}: {} imported, {} duplicates, {} skipped",
+        label,
+        report.imported.len(),
+        report.duplicates.len(),
+        report.skipped.len()
+    );
+    Ok(report)
+}
+
+fn skipped_file(path: &Path, reason: impl Into<String>) -> SkippedItem {
+    SkippedItem {
+        source_id: Some(path.display().to_string()),
+        title: None,
+        reason: reason.into(),
+    }
+}
+
+// LM Studio
+
+const LMSTUDIO_CONVERSATION_FIELDS: &[&str] = &["name", "createdAt", "systemPrompt", "messages", "lastUsedModel"];
+const LMSTUDIO_MESSAGE_FIELDS: &[&str] = &["versions", "currentlySelected", "role", "content"];
+const LMSTUDIO_VERSION_FIELDS: &[&str] = &["type", "role", "content", "steps", "senderInfo"];
+
+// Assistant replies are a list of steps; only content blocks carry the answer text
+fn lmstudio_text(version: &Value, report: &mut ImportReport) -> String {
+    let Some(steps) = version.get("steps").and_then(|s| s.as_array()) else {
+        return content_text(&version["content"], report);
+    };
+    let mut blocks = Vec::new();
+    for step in steps {
+        match step["type"].as_str() {
+            Some("contentBlock") => blocks.push(content_text(&step["content"], report)),
+            other => {
+                report.unmapped_fields.insert(format!("step.{}", other.unwrap_or("unknown")));
+            }
+        }
+    }
+    blocks.retain(|b| !b.trim().is_empty());
+    blocks.join("\n\n")
+}
+
+// One conversation file; every regenerated version of a message becomes a branch
+fn parse_lmstudio_conversation(raw: &Value, source_id: String, report: &mut ImportReport) -> Result<ParsedConversation, String> {
+    let items = raw.get("messages").and_then(|m| m.as_array()).ok_or("missing messages")?;
+    note_unmapped(report, "conversation", raw, LMSTUDIO_CONVERSATION_FIELDS);
+    let created_at = flexible_timestamp(raw.get("createdAt")).unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+    let mut model = raw["lastUsedModel"]["identifier"].as_str().map(str::to_string);
+
+    let mut messages = Vec::new();
+    let mut parent = push_system_prompt(&mut messages, raw["systemPrompt"].as_str(), "lmstudio", &created_at);
+    for item in items {
+        note_unmapped(report, "message", item, LMSTUDIO_MESSAGE_FIELDS);
+        // Older versions store plain {role, content} messages
+        let versions = match item.get("versions").and_then(|v| v.as_array()) {
+            Some(versions) => versions.as_slice(),
+            None => std::slice::from_ref(item),
+        };
+        let selected = item["currentlySelected"].as_u64().unwrap_or(0) as usize;
+        let mut chosen = None;
+        for (index, version) in versions.iter().enumerate() {
+            if item.get("versions").is_some() {
+                note_unmapped(report, "version", version, LMSTUDIO_VERSION_FIELDS);
+            }
+            let role = version["role"].as_str().unwrap_or("");
+            let content = lmstudio_text(version, report);
+            if !is_chat_role(role) || content.trim().is_empty() {
+                report.skipped_messages += 1;
+                continue;
+            }
+            let sender = version["senderInfo"]["senderName"].as_str().map(str::to_string);
+            if role == "assistant" && model.is_none() {
+                model = sender.clone();
+            }
+            messages.push(ParsedMessage {
+                message: ChatMessage::new(role, content),
+                metadata: json!({ "source": "lmstudio", "model": sender }),
+                created_at: created_at.clone(),
+                parent,
+            });
+            if index == selected || chosen.is_none() {
+                chosen = Some(messages.len() - 1);
+            }
+        }
+        // The conversation continues from the version that was selected
+        parent = chosen.or(parent);
+    }
+
+    Ok(ParsedConversation {
+        source_id,
+        title: raw["name"]
+            .as_str()
+            .filter(|t| !t.trim().is_empty())
+            .unwrap_or("Imported chat")
+            .to_string(),
+        model,
+        updated_at: created_at.clone(),
+        created_at,
+        active: parent,
+        messages,
+    })
+}
+
+// Import LM Studio chats: a *.conversation.json file or the conversations folder
+#[tauri::command]
+pub fn import_lmstudio_chats(db: State<'_, Database>, path: String) -> Result<ImportReport, String> {
+    let files = collect_files(Path::new(&path), &|p| p.to_string_lossy().ends_with(".json"))?;
+    let mut report = ImportReport::default();
+    let mut parsed = Vec::new();
+    for file in files {
+        let raw = match std::fs::read_to_string(&file).map(|text| serde_json::from_str::<Value>(&text)) {
+            Ok(Ok(raw)) => raw,
+            Ok(Err(err)) => {
+                report.skipped.push(skipped_file(&file, format!("invalid JSON: {}", err)));
+                continue;
+            }
+            Err(err) => {
+                report.skipped.push(skipped_file(&file, err.to_string()));
+                continue;
+            }
+        };
+        let source_id = file_stem(&file).trim_end_matches(".conversation").to_string();
+        match parse_lmstudio_conversation(&raw, source_id, &mut report) {
+            Ok(conversation) => parsed.push(conversation),
+            Err(reason) => report.skipped.push(skipped_file(&file, reason)),
+        }
+    }
+    finish_import(&db, "lmstudio", "LM Studio", parsed, report)
+}
+
+// Jan
+
+const JAN_THREAD_FIELDS: &[&str] = &["id", "object", "title", "assistants", "created", "updated"];
+// object and thread_id only restate what the file layout already says
+const JAN_MESSAGE_FIELDS: &[&str] = &["id", "object", "thread_id", "role", "content", "created_at", "created", "assistant_id"];
+
+// A thread folder holds thread.json and messages.jsonl
+fn parse_jan_thread(dir: &Path, report: &mut ImportReport) -> Result<ParsedConversation, String> {
+    let text = std::fs::read_to_string(dir.join("thread.json")).map_err(|e| format!("failed to read thread.json: {}", e))?;
+    let thread: Value = serde_json::from_str(&text).map_err(|e| format!("invalid thread.json: {}", e))?;
+    note_unmapped(report, "thread", &thread, JAN_THREAD_FIELDS);
+    let assistant = &thread["assistants"][0];
+    let created_at = flexible_timestamp(thread.get("created")).unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+    let model = assistant["model"]["id"].as_str().map(str::to_string);
+
+    let mut messages = Vec::new();
+    let mut parent = push_system_prompt(&mut messages, assistant["instructions"].as_str(), "jan", &created_at);
+    // Threads that were never written to have no messages file
+    let lines = std::fs::read_to_string(dir.join("messages.jsonl")).unwrap_or_default();
+    for line in lines.lines().filter(|l| !l.trim().is_empty()) {
+        let Ok(raw) = serde_json::from_str::<Value>(line) else {
+            report.skipped_messages += 1;
+            continue;
+        };
+        note_unmapped(report, "message", &raw, JAN_MESSAGE_FIELDS);
+        let role = raw["role"].as_str().unwrap_or("");
+        let content = content_text(&raw["content"], report);
+        if !is_chat_role(role) || content.trim().is_empty() {
+            report.skipped_messages += 1;
+            continue;
+        }
+        messages.push(ParsedMessage {
+            message: ChatMessage::new(role, content),
+            metadata: json!({
+                "source": "jan",
+                "sourceMessageId": raw["id"],
+                "model": if role == "assistant" { model.clone() } else { None },
+            }),
+            created_at: flexible_timestamp(raw.get("created_at").or_else(|| raw.get("created")))
+                .unwrap_or_else(|| created_at.clone()),
+            parent,
+        });
+        parent = Some(messages.len() - 1);
+    }
+
+    Ok(ParsedConversation {
+        source_id: thread["id"].as_str().map(str::to_string).unwrap_or_else(|| file_stem(dir)),
+        title: thread["title"]
+            .as_str()
+            .filter(|t| !t.trim().is_empty())
+            .unwrap_or("Imported chat")
+            .to_string(),
+        model,
+        updated_at: flexible_timestamp(thread.get("updated")).unwrap_or_else(|| created_at.clone()),
+        created_at,
+        active: parent,
+        messages,
+    })
+}
+
+// Import Jan threads: one thread folder or the threads folder containing them
+#[tauri::command]
+pub fn import_jan_threads(db: State<'_, Database>, path: String) -> Result<ImportReport, String> {
+    let threads: Vec<PathBuf> = collect_files(Path::new(&path), &|p| p.file_name().is_some_and(|n| n == "thread.json"))?
+        .into_iter()
+        .filter_map(|file| file.parent().map(Path::to_path_buf))
+        .collect();
+    let mut report = ImportReport::default();
+    let mut parsed = Vec::new();
+    for dir in threads {
+        match parse_jan_thread(&dir, &mut report) {
+            Ok(conversation) => parsed.push(conversation),
+            Err(reason) => report.skipped.push(skipped_file(&dir, reason)),
+        }
+    }
+    finish_import(&db, "jan", "Jan", parsed, report)
+}
+
+// SillyTavern
+
+const SILLYTAVERN_HEADER_FIELDS: &[&str] = &["user_name", "character_name", "create_date"];
+const SILLYTAVERN_MESSAGE_FIELDS: &[&str] =
+    &["name", "is_user", "is_system", "send_date", "mes", "swipes", "swipe_id", "extra"];
+
+// One chat file: a header line followed by one message per line. Swipes (alternative replies)
+// become branches, with the swipe that was shown as the active one.
+fn parse_sillytavern_chat(file: &Path, report: &mut ImportReport) -> Result<ParsedConversation, String> {
+    let text = std::fs::read_to_string(file).map_err(|e| e.to_string())?;
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+    let header: Value = lines
+        .next()
+        .and_then(|line| serde_json::from_str(line).ok())
+        .filter(|h: &Value| h.get("mes").is_none())
+        .ok_or("missing chat header")?;
+    note_unmapped(report, "chat", &header, SILLYTAVERN_HEADER_FIELDS);
+    let character = header["character_name"].as_str().unwrap_or("Character").to_string();
+    let created_at = flexible_timestamp(header.get("create_date")).unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+
+    let mut model = None;
+    let mut messages: Vec<ParsedMessage> = Vec::new();
+    let mut parent = None;
+    for line in lines {
+        let Ok(raw) = serde_json::from_str::<Value>(line) else {
+            report.skipped_messages += 1;
+            continue;
+        };
+        note_unmapped(report, "message", &raw, SILLYTAVERN_MESSAGE_FIELDS);
+        let role = if raw["is_system"].as_bool() == Some(true) {
+            "system"
+        } else if raw["is_user"].as_bool() == Some(true) {
+            "user"
+        } else {
+            "assistant"
+        };
+        let shown = raw["mes"].as_str().unwrap_or("");
+        let swipes: Vec<&str> = raw["swipes"]
+            .as_array()
+            .map(|s| s.iter().filter_map(|v| v.as_str()).collect())
+            .filter(|s: &Vec<&str>| s.len() > 1)
+            .unwrap_or_else(|| vec![shown]);
+        let selected = raw["swipe_id"].as_u64().map(|i| i as usize).unwrap_or(0);
+        let message_model = raw["extra"]["model"].as_str().map(str::to_string);
+        if role == "assistant" && message_model.is_some() {
+            model = message_model.clone();
+        }
+        let sent_at = flexible_timestamp(raw.get("send_date"))
+            .or_else(|| messages.last().map(|m| m.created_at.clone()))
+            .unwrap_or_else(|| created_at.clone());
+
+        let mut chosen = None;
+        for (index, content) in swipes.into_iter().enumerate() {
+            if content.trim().is_empty() {
+                report.skipped_messages += 1;
+                continue;
+            }
+            messages.push(ParsedMessage {
+                message: ChatMessage::new(role, content),
+                metadata: json!({ "source": "sillytavern", "name": raw["name"], "model": message_model }),
+                created_at: sent_at.clone(),
+                parent,
+            });
+            if index == selected || chosen.is_none() {
+                chosen = Some(messages.len() - 1);
+            }
+        }
+        parent = chosen.or(parent);
+    }
+
+    let stem = file_stem(file);
+    Ok(ParsedConversation {
+        source_id: format!("{}/{}", character, stem),
+        title: stem,
+        model,
+        updated_at: messages.last().map(|m| m.created_at.clone()).unwrap_or_else(|| created_at.clone()),
+        created_at,
+        active: parent,
+        messages,
+    })
+}
+
+// Import SillyTavern chats: a .jsonl chat file or a chats folder (searched recursively)
+#[tauri::command]
+pub fn import_sillytavern_chats(db: State<'_, Database>, path: String) -> Result<ImportReport, String> {
+    let files = collect_files(Path::new(&path), &|p| p.extension().is_some_and(|e| e == "jsonl"))?;
+    let mut report = ImportReport::default();
+    let mut parsed = Vec::new();
+    for file in files {
+        match parse_sillytavern_chat(&file, &mut report) {
+            Ok(conversation) => parsed.push(conversation),
+            Err(reason) => report.skipped.push(skipped_file(&file, reason)),
+        }
+    }
+    finish_import(&db, "sillytavern", "SillyTavern", parsed, report)
+}
@@ -0,0 +1,598 @@
+// Document ingestion: extract text from files, split it into overlapping chunks, embed the chunks
+// and store them in a vector collection. Progress is reported per file with `ingest-progress` events.
+use std::path::Path;
+
+use rusqlite::{params, OptionalExtension};
+
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::db::Database;
+use crate::dedup::{self, SeenChunks};
+use crate::epub;
+use crate::html;
+use crate::llm::{self, ProviderConfig};
+use crate::ocr::{self, OcrConfig};
+use crate::office;
+use crate::pdf::{self, PdfDocument};
+use crate::profiles::ProfileManager;
+use crate::vector::{self, Metric, VectorItem};
+use crate::watch::{self, FileStamp};
+
+const PROGRESS_EVENT: &str = "ingest-progress";
+const DEFAULT_CHUNK_SIZE: usize = 1000;
+const DEFAULT_CHUNK_OVERLAP: usize = 200;
+// Chunks sent to the embedder per request
+const EMBED_BATCH: usize = 32;
+// Files read as plain text
+const TEXT_EXTENSIONS: &[&str] = &[
+    "txt", "md", "markdown", "rst", "org", "csv", "tsv", "json", "yaml", "yml", "toml", "xml", "log", "rs", "py", "js",
+    "ts", "tsx", "jsx", "go", "java", "c", "h", "cpp", "hpp", "cs", "rb", "php", "sh", "sql", "css", "swift", "kt",
+];
+// Saved web pages, reduced to their main content
+const HTML_EXTENSIONS: &[&str] = &["html", "htm", "xhtml"];
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestOptions {
+    pub embedding: ProviderConfig,
+    // Characters per chunk and characters shared with the previous chunk
+    pub chunk_size: Option<usize>,
+    pub chunk_overlap: Option<usize>,
+    // Tesseract languages for scanned pages and images, e.g. "eng+deu"; defaults to the setting
+    #[serde(default)]
+    pub ocr_languages: Option<String>,
+    // Skip chunks whose text is already in the collection; on by default
+    #[serde(default)]
+    pub dedup: Option<bool>,
+    // Flag chunks that nearly match one already stored (by simhash); off by default
+    #[serde(default)]
+    pub near_duplicates: Option<bool>,
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct IngestProgress {
+    path: String,
+    // Position of the file in the request, from 1
+    file: usize,
+    files: usize,
+    // extracting, embedding, done, skipped or failed
+    stage: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chunks: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestedFile {
+    path: String,
+    // added, updated, skipped (unchanged since the last ingestion) or failed
+    status: &'static str,
+    chunks: usize,
+    duplicates: usize,
+    near_duplicates: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestReport {
+    collection: String,
+    files: Vec<IngestedFile>,
+    added: usize,
+    updated: usize,
+    skipped: usize,
+    failed: usize,
+    total_chunks: usize,
+    // Chunks skipped as exact duplicates, and chunks stored but flagged as near duplicates
+    total_duplicates: usize,
+    total_near_duplicates: usize,
+}
+
+#[derive(Default, Clone, Copy)]
+pub struct ChunkStats {
+    pub stored: usize,
+    pub duplicates: usize,
+    pub near_duplicates: usize,
+}
+
+pub struct Chunk {
+    pub text: String,
+    // Character offset of the chunk in the text it was cut from
+    pub start: usize,
+}
+
+// Part of a document with its own heading, such as an EPUB chapter
+pub struct Section {
+    pub title: Option<String>,
+    // Page number, from 1, for documents split by page
+    pub page: Option<u32>,
+    // Mean word confidence (0-100) when the text came from OCR
+    pub ocr_confidence: Option<f32>,
+    pub text: String,
+}
+
+pub struct Extracted {
+    // Title and author from the file's metadata
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub sections: Vec<Section>,
+}
+
+impl Extracted {
+    fn whole(title: Option<String>, text: String) -> Self {
+        Extracted {
+            title,
+            author: None,
+            sections: vec![Section {
+                title: None,
+                page: None,
+                ocr_confidence: None,
+                text,
+            }],
+        }
+    }
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+// Whether `extract` can read the file, judging by its extension
+pub fn is_supported(path: &Path) -> bool {
+    let extension = extension(path);
+    let extension = extension.as_str();
+    matches!(extension, "pdf" | "epub" | "docx" | "docm" | "xlsx" | "xlsm" | "pptx" | "pptm")
+        || HTML_EXTENSIONS.contains(&extension)
+        || TEXT_EXTENSIONS.contains(&extension)
+        || ocr::IMAGE_EXTENSIONS.contains(&extension)
+}
+
+// One section per page so chunks can cite their page, titled by the bookmark the page falls under
+fn pdf_sections(doc: PdfDocument) -> Extracted {
+    let sections = doc
+        .pages
+        .into_iter()
+        .filter(|page| !page.text.trim().is_empty())
+        .map(|page| Section {
+            title: doc
+                .outline
+                .iter()
+                .filter(|entry| entry.page <= page.number as usize)
+                .max_by_key(|entry| (entry.page, entry.level))
+                .map(|entry| entry.title.clone()),
+            page: Some(page.number),
+            ocr_confidence: page.ocr_confidence,
+            text: page.text,
+        })
+        .collect();
+    Extracted {
+        title: doc.metadata.title,
+        author: doc.metadata.author,
+        sections,
+    }
+}
+
+// Text of a supported document; chapter boundaries are kept as sections. Images, and PDF pages
+// without a text layer, are read with OCR when `ocr` is given.
+pub fn extract(path: &Path, ocr: Option<&OcrConfig>) -> Result<Extracted, String> {
+    let extension = extension(path);
+    match extension.as_str() {
+        "pdf" => return pdf::load(path, ocr).map(pdf_sections),
+        "docx" | "docm" | "xlsx" | "xlsm" | "pptx" | "pptm" => {
+            return office::load(path).map(|doc| Extracted::whole(None, doc.text()))
+        }
+        "epub" => {
+            return epub::load(path).map(|book| Extracted {
+                title: book.title,
+                author: book.author,
+                sections: book
+                    .chapters
+                    .into_iter()
+                    .map(|c| Section {
+                        title: c.title,
+                        page: None,
+                        ocr_confidence: None,
+                        text: c.text,
+                    })
+                    .collect(),
+            })
+        }
+        _ if ocr::is_image(path) => {
+            let config = ocr.ok_or("Images need OCR, which is turned off in settings")?;
+            let result = ocr::load_image(path, config)?;
+            return Ok(Extracted {
+                title: None,
+                author: None,
+                sections: vec![Section {
+                    title: None,
+                    page: None,
+                    ocr_confidence: result.confidence,
+                    text: result.text,
+                }],
+            });
+        }
+        _ => {}
+    }
+    let is_html = HTML_EXTENSIONS.contains(&extension.as_str());
+    if !is_html && !TEXT_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(format!("Unsupported file type: .{}", extension));
+    }
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let text = String::from_utf8_lossy(&bytes);
+    if is_html {
+        let page = html::extract(&text);
+        return Ok(Extracted::whole(page.title.or(page.heading), page.text));
+    }
+    Ok(Extracted::whole(None, text.into_owned()))
+}
+
+// Split into chunks of about `size` characters, each starting `overlap` characters before the
+// previous one ended. Chunks end at a paragraph, sentence or word break when one falls in the
+// last fifth of the window.
+pub fn chunk_text(text: &str, size: usize, overlap: usize) -> Vec<Chunk> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+        let limit = (start + size).min(chars.len());
+        let mut end = limit;
+        if limit < chars.len() {
+            let floor = start + size - size / 5;
+            let window = &chars[floor..limit];
+            let break_after = |pattern: &dyn Fn(usize) -> bool| (0..window.len()).rev().find(|&i| pattern(i));
+            let found = break_after(&|i| window[i] == '\n' && i > 0 && window[i - 1] == '\n')
+                .or_else(|| break_after(&|i| matches!(window[i], '.' | '!' | '?') && window.get(i + 1).is_some_and(|c| c.is_whitespace())))
+                .or_else(|| break_after(&|i| window[i].is_whitespace()));
+            if let Some(i) = found {
+                end = floor + i + 1;
+            }
+        }
+        let text: String = chars[start..end].iter().collect();
+        if !text.trim().is_empty() {
+            chunks.push(Chunk {
+                text: text.trim().to_string(),
+                start,
+            });
+        }
+        if end >= chars.len() {
+            break;
+        }
+        start = end.saturating_sub(overlap).max(start + 1);
+    }
+    chunks
+}
+
+// Chunk size and overlap; without an explicit overlap, small chunks share a fifth of their text
+pub fn chunk_settings(options: &IngestOptions) -> Result<(usize, usize), String> {
+    let size = options.chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE);
+    let overlap = options.chunk_overlap.unwrap_or(DEFAULT_CHUNK_OVERLAP.min(size / 5));
+    if size == 0 || overlap >= size {
+        return Err("Chunk overlap must be smaller than the chunk size".to_string());
+    }
+    Ok((size, overlap))
+}
+
+fn emit_progress(app: &AppHandle, progress: IngestProgress) {
+    if let Err(e) = app.emit(PROGRESS_EVENT, progress) {
+        eprintln!("[Ingest] Failed to emit progress: {}", e);
+    }
+}
+
+// Chunk, embed and store one file, replacing the chunks of an earlier ingestion of the same path.
+// Chunks already in the collection, from another file or earlier in this one, are skipped.
+pub async fn ingest_file(
+    db: &Database,
+    collection: &str,
+    path: &Path,
+    options: &IngestOptions,
+    ocr: Option<&OcrConfig>,
+    mut on_embedding: impl FnMut(usize),
+) -> Result<ChunkStats, String> {
+    let document = extract(path, ocr)?;
+    let mut hasher = Sha256::new();
+    document.sections.iter().for_each(|s| hasher.update(s.text.as_bytes()));
+    let hash = format!("{:x}", hasher.finalize());
+    let (size, overlap) = chunk_settings(options)?;
+    // Chunks never cross a section boundary, so each one cites a single chapter or page
+    let chunks: Vec<(usize, Chunk)> = document
+        .sections
+        .iter()
+        .enumerate()
+        .flat_map(|(index, section)| chunk_text(&section.text, size, overlap).into_iter().map(move |c| (index, c)))
+        .collect();
+    if chunks.is_empty() {
+        return Err("No text found".to_string());
+    }
+
+    let source = path.to_string_lossy().to_string();
+    let (skip_duplicates, flag_near) = (options.dedup.unwrap_or(true), options.near_duplicates.unwrap_or(false));
+    let mut seen = if skip_duplicates || flag_near {
+        SeenChunks::load(db, collection, &source)?
+    } else {
+        SeenChunks::default()
+    };
+    let mut stats = ChunkStats::default();
+    // Chunks to store, with their hash, simhash and the chunk they nearly duplicate
+    let mut kept = Vec::with_capacity(chunks.len());
+    for (section, chunk) in &chunks {
+        let chunk_hash = dedup::chunk_hash(&chunk.text);
+        if skip_duplicates && seen.duplicate_of(&chunk_hash).is_some() {
+            stats.duplicates += 1;
+            continue;
+        }
+        let simhash = flag_near.then(|| dedup::simhash(&chunk.text));
+        let near_duplicate_of = simhash.and_then(|s| seen.near_duplicate_of(s)).map(str::to_string);
+        if near_duplicate_of.is_some() {
+            stats.near_duplicates += 1;
+        }
+        seen.insert(&format!("{}#{}", source, kept.len()), chunk_hash.clone(), simhash);
+        kept.push((*section, chunk, chunk_hash, simhash, near_duplicate_of));
+    }
+    if kept.is_empty() {
+        // Every chunk is stored already, under other files; only the earlier version goes
+        if vector::collection(db, collection).is_ok() {
+            let mut previous = Map::new();
+            previous.insert("source".to_string(), Value::String(source));
+            vector::delete_matching(db, collection, &previous)?;
+        }
+        return Ok(stats);
+    }
+    on_embedding(kept.len());
+
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string());
+    let ingested_at = chrono::Utc::now().to_rfc3339();
+    let mut items = Vec::with_capacity(kept.len());
+    for batch in kept.chunks(EMBED_BATCH) {
+        let texts: Vec<String> = batch.iter().map(|(_, c, ..)| c.text.clone()).collect();
+        let vectors = llm::embed(&options.embedding, &texts).await?;
+        if vectors.len() != batch.len() {
+            return Err(format!("Embedder returned {} vectors for {} chunks", vectors.len(), batch.len()));
+        }
+        for ((section, chunk, chunk_hash, simhash, near_duplicate_of), embedding) in batch.iter().zip(vectors) {
+            let index = items.len();
+            let mut metadata = json!({
+                "source": source,
+                "fileName": file_name,
+                "chunk": index,
+                "chunks": kept.len(),
+                "start": chunk.start,
+                "hash": hash,
+                "chunkHash": chunk_hash,
+                "ingestedAt": ingested_at,
+            });
+            if let Some(title) = &document.title {
+                metadata["title"] = json!(title);
+            }
+            if let Some(author) = &document.author {
+                metadata["author"] = json!(author);
+            }
+            // Start offsets are relative to the section in multi-section documents
+            if document.sections.len() > 1 {
+                metadata["sectionIndex"] = json!(section);
+                metadata["section"] = json!(document.sections[*section].title);
+            }
+            if let Some(page) = document.sections[*section].page {
+                metadata["page"] = json!(page);
+            }
+            if let Some(confidence) = document.sections[*section].ocr_confidence {
+                metadata["ocrConfidence"] = json!(confidence);
+            }
+            if let Some(simhash) = simhash {
+                metadata["simhash"] = json!(dedup::format_simhash(*simhash));
+            }
+            if let Some(id) = near_duplicate_of {
+                metadata["nearDuplicateOf"] = json!(id);
+            }
+            items.push(VectorItem {
+                id: format!("{}#{}", source, index),
+                embedding,
+                content: Some(chunk.text.clone()),
+                metadata: Some(metadata),
+            });
+        }
+    }
+
+    // The collection takes its dimensions from the first embeddings stored in it
+    let dimensions = items[0].embedding.len();
+    let existing = vector::create_collection(db, collection, dimensions, Metric::Cosine, Some(&options.embedding.model))?;
+    if let Some(model) = existing.embedding_model.as_deref().filter(|m| *m != options.embedding.model) {
+        return Err(format!("Collection '{}' holds embeddings from {}, not {}", collection, model, options.embedding.model));
+    }
+    let mut previous = Map::new();
+    previous.insert("source".to_string(), Value::String(source));
+    vector::delete_matching(db, collection, &previous)?;
+    stats.stored = vector::upsert(db, collection, &items)?;
+    Ok(stats)
+}
+
+// SHA-256 of the file's bytes
+fn file_hash(path: &Path) -> Result<String, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+// Modification time, size, hash and chunk count recorded when the file was last ingested into
+// the collection
+fn previous_ingestion(db: &Database, collection: &str, path: &str) -> Result<Option<(FileStamp, String, usize)>, String> {
+    db.with_conn(|conn| {
+        conn.query_row(
+            "SELECT modified_ms, size, hash, chunks FROM ingested_files WHERE collection = ?1 AND path = ?2",
+            params![collection, path],
+            |row| {
+                let stamp = FileStamp {
+                    modified_ms: row.get(0)?,
+                    size: row.get(1)?,
+                };
+                Ok((stamp, row.get(2)?, row.get::<_, i64>(3)? as usize))
+            },
+        )
+        .optional()
+    })
+}
+
+fn record_ingestion(db: &Database, collection: &str, path: &str, stamp: FileStamp, hash: &str, chunks: usize) -> Result<(), String> {
+    // The collection doesn't exist yet when nothing has been stored in it
+    if vector::collection(db, collection).is_err() {
+        return Ok(());
+    }
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO ingested_files (collection, path, modified_ms, size, hash, chunks, ingested_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                collection,
+                path,
+                stamp.modified_ms,
+                stamp.size,
+                hash,
+                chunks as i64,
+                chrono::Utc::now().to_rfc3339()
+            ],
+        )
+    })?;
+    Ok(())
+}
+
+// Files to ingest: the paths given, with folders replaced by the supported files in them
+fn expand_paths(paths: &[String]) -> Vec<String> {
+    let mut expanded = Vec::new();
+    for path in paths {
+        if !Path::new(path).is_dir() {
+            expanded.push(path.clone());
+            continue;
+        }
+        let mut found = std::collections::HashMap::new();
+        match watch::scan_folder(Path::new(path), true, &mut found) {
+            Ok(()) => {
+                let mut found: Vec<String> = found.into_keys().collect();
+                found.sort();
+                expanded.extend(found);
+            }
+            // Reported as a failed file
+            Err(_) => expanded.push(path.clone()),
+        }
+    }
+    expanded
+}
+
+// Ingest files and folders into a vector collection (created on first use); a failing file doesn't
+// stop the rest. Files ingested before are skipped unless their content changed, or `force` is set
+// (for instance after changing the chunk size).
+#[tauri::command]
+pub async fn ingest_documents(
+    app: AppHandle,
+    db: State<'_, Database>,
+    profiles: State<'_, ProfileManager>,
+    paths: Vec<String>,
+    collection: String,
+    mut options: IngestOptions,
+    force: Option<bool>,
+) -> Result<IngestReport, String> {
+    chunk_settings(&options)?;
+    profiles.apply_credentials(&mut options.embedding);
+    let ocr = ocr::config(&app, options.ocr_languages.as_deref())?;
+    let force = force.unwrap_or(false);
+    let paths = expand_paths(&paths);
+
+    let mut files = Vec::new();
+    for (index, path) in paths.iter().enumerate() {
+        let progress = |stage, chunks, error| IngestProgress {
+            path: path.clone(),
+            file: index + 1,
+            files: paths.len(),
+            stage,
+            chunks,
+            error,
+        };
+        let file = |status, stats: ChunkStats, error| IngestedFile {
+            path: path.clone(),
+            status,
+            chunks: stats.stored,
+            duplicates: stats.duplicates,
+            near_duplicates: stats.near_duplicates,
+            error,
+        };
+        let checked = std::fs::metadata(path)
+            .map_err(|e| format!("Failed to read {}: {}", path, e))
+            .and_then(|meta| Ok((FileStamp::of(&meta), previous_ingestion(&db, &collection, path)?)));
+        let (stamp, previous) = match checked {
+            Ok(checked) => checked,
+            Err(err) => {
+                emit_progress(&app, progress("failed", None, Some(err.clone())));
+                files.push(file("failed", ChunkStats::default(), Some(err)));
+                continue;
+            }
+        };
+        // Unchanged time and size skip the file unread; otherwise the hash decides
+        if !force && previous.as_ref().is_some_and(|(before, ..)| *before == stamp) {
+            emit_progress(&app, progress("skipped", None, None));
+            files.push(file("skipped", ChunkStats::default(), None));
+            continue;
+        }
+        let hash = match file_hash(Path::new(path)) {
+            Ok(hash) => hash,
+            Err(err) => {
+                emit_progress(&app, progress("failed", None, Some(err.clone())));
+                files.push(file("failed", ChunkStats::default(), Some(err)));
+                continue;
+            }
+        };
+        if let Some((_, _, chunks)) = previous.as_ref().filter(|(_, before, _)| !force && *before == hash) {
+            // Touched but not changed; remember the new time so the next run needn't hash it
+            record_ingestion(&db, &collection, path, stamp, &hash, *chunks)?;
+            emit_progress(&app, progress("skipped", None, None));
+            files.push(file("skipped", ChunkStats::default(), None));
+            continue;
+        }
+
+        emit_progress(&app, progress("extracting", None, None));
+        let result = ingest_file(&db, &collection, Path::new(path), &options, ocr.as_ref(), |chunks| {
+            emit_progress(&app, progress("embedding", Some(chunks), None))
+        })
+        .await;
+        match result {
+            Ok(stats) => {
+                record_ingestion(&db, &collection, path, stamp, &hash, stats.stored)?;
+                emit_progress(&app, progress("done", Some(stats.stored), None));
+                let status = if previous.is_some() { "updated" } else { "added" };
+                files.push(file(status, stats, None));
+            }
+            Err(err) => {
+                eprintln!("[Ingest] {} failed: {}", path, err);
+                emit_progress(&app, progress("failed", None, Some(err.clone())));
+                files.push(file("failed", ChunkStats::default(), Some(err)));
+            }
+        }
+    }
+
+    let count = |status| files.iter().filter(|f| f.status == status).count();
+    let (added, updated, skipped, failed) = (count("added"), count("updated"), count("skipped"), count("failed"));
+    let total_chunks = files.iter().map(|f| f.chunks).sum();
+    let total_duplicates = files.iter().map(|f| f.duplicates).sum();
+    let total_near_duplicates = files.iter().map(|f| f.near_duplicates).sum();
+    eprintln!(
+        "[Ingest] Stored {} chunks in {}: {} files added, {} updated, {} unchanged, {} failed ({} duplicate chunks skipped, {} near duplicates)",
+        total_chunks, collection, added, updated, skipped, failed, total_duplicates, total_near_duplicates
+    );
+    Ok(IngestReport {
+        collection,
+        files,
+        added,
+        updated,
+        skipped,
+        failed,
+        total_chunks,
+        total_duplicates,
+        total_near_duplicates,
+    })
+}
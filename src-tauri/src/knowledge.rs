@@ -0,0 +1,276 @@
+// Knowledge bases: the vector collections documents are ingested into, managed as a whole. Adds
+// what the raw vector store doesn't track: how many documents a collection holds, how much space it
+// takes, and moving it to another embedding model. Collections that are renamed, deleted or
+// re-embedded take their watched folders along.
+use rusqlite::{params, Connection, OptionalExtension};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::db::Database;
+use crate::ingest::IngestOptions;
+use crate::llm::{self, ProviderConfig};
+use crate::profiles::ProfileManager;
+use crate::vector::{self, Metric};
+use crate::watch::WatchEngine;
+
+const PROGRESS_EVENT: &str = "reembed-progress";
+// Chunks sent to the embedder per request
+const EMBED_BATCH: usize = 32;
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeBase {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    embedding_model: Option<String>,
+    dimensions: usize,
+    metric: Metric,
+    // Distinct source files, and the chunks cut from them
+    documents: usize,
+    chunks: usize,
+    // Stored embeddings, text and metadata, in bytes
+    size: u64,
+    watched_folders: usize,
+    created_at: String,
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ReembedProgress {
+    collection: String,
+    done: usize,
+    total: usize,
+}
+
+fn get_knowledge_base(conn: &Connection, name: &str) -> rusqlite::Result<Option<KnowledgeBase>> {
+    conn.query_row(
+        "SELECT c.name, c.embedding_model, c.dimensions, c.metric, c.created_at,
+                (SELECT COUNT(DISTINCT json_extract(i.metadata, '$.source')) FROM vector_items i WHERE i.collection = c.name),
+                (SELECT COUNT(*) FROM vector_items i WHERE i.collection = c.name),
+                (SELECT COALESCE(SUM(length(i.embedding) + COALESCE(length(CAST(i.content AS BLOB)), 0)
+                                     + COALESCE(length(CAST(i.metadata AS BLOB)), 0)), 0)
+                 FROM vector_items i WHERE i.collection = c.name),
+                (SELECT COUNT(*) FROM watched_sources w WHERE w.collection = c.name)
+         FROM vector_collections c WHERE c.name = ?1",
+        params![name],
+        |row| {
+            Ok(KnowledgeBase {
+                name: row.get(0)?,
+                embedding_model: row.get(1)?,
+                dimensions: row.get::<_, i64>(2)? as usize,
+                metric: Metric::parse(&row.get::<_, String>(3)?),
+                created_at: row.get(4)?,
+                documents: row.get::<_, i64>(5)? as usize,
+                chunks: row.get::<_, i64>(6)? as usize,
+                size: row.get::<_, i64>(7)? as u64,
+                watched_folders: row.get::<_, i64>(8)? as usize,
+            })
+        },
+    )
+    .optional()
+}
+
+fn knowledge_base(db: &Database, name: &str) -> Result<KnowledgeBase, String> {
+    db.with_conn(|conn| get_knowledge_base(conn, name))?
+        .ok_or_else(|| format!("Knowledge base '{}' not found", name))
+}
+
+async fn embed_batch(embedding: &ProviderConfig, texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+    let vectors = llm::embed(embedding, texts).await?;
+    if vectors.len() != texts.len() {
+        return Err(format!("Embedder returned {} vectors for {} texts", vectors.len(), texts.len()));
+    }
+    if vectors.iter().any(|v| v.is_empty()) {
+        return Err("Embedder returned an empty vector".to_string());
+    }
+    Ok(vectors)
+}
+
+// Dimensions of the model's embeddings, which also checks that it can be reached
+async fn probe_dimensions(embedding: &ProviderConfig) -> Result<usize, String> {
+    let vectors = embed_batch(embedding, &["dimensions".to_string()]).await?;
+    Ok(vectors[0].len())
+}
+
+// Have the collection's watched folders ingest with a new embedding model
+fn update_watched_sources(conn: &Connection, collection: &str, embedding: &ProviderConfig) -> rusqlite::Result<()> {
+    let sources: Vec<(String, String)> = {
+        let mut stmt = conn.prepare("SELECT id, options FROM watched_sources WHERE collection = ?1")?;
+        let rows = stmt.query_map(params![collection], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+    for (id, options) in sources {
+        let Ok(mut options) = serde_json::from_str::<IngestOptions>(&options) else {
+            eprintln!("[Knowledge] Watched folder {} has invalid options; left unchanged", id);
+            continue;
+        };
+        options.embedding = ProviderConfig {
+            api_key: None,
+            ..embedding.clone()
+        };
+        let options = serde_json::to_string(&options).unwrap_or_default();
+        conn.execute("UPDATE watched_sources SET options = ?1 WHERE id = ?2", params![options, id])?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_knowledge_bases(db: State<'_, Database>) -> Result<Vec<KnowledgeBase>, String> {
+    db.with_conn(|conn| {
+        let names: Vec<String> = {
+            let mut stmt = conn.prepare("SELECT name FROM vector_collections ORDER BY name")?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+        let mut bases = Vec::with_capacity(names.len());
+        for name in names {
+            bases.extend(get_knowledge_base(conn, &name)?);
+        }
+        Ok(bases)
+    })
+}
+
+#[tauri::command]
+pub fn get_knowledge_base_info(db: State<'_, Database>, name: String) -> Result<KnowledgeBase, String> {
+    knowledge_base(&db, &name)
+}
+
+// Create an empty knowledge base for `embedding`'s model; its dimensions are read from a test
+// embedding
+#[tauri::command]
+pub async fn create_knowledge_base(
+    db: State<'_, Database>,
+    profiles: State<'_, ProfileManager>,
+    name: String,
+    mut embedding: ProviderConfig,
+) -> Result<KnowledgeBase, String> {
+    let name = name.trim().to_string();
+    if db.with_conn(|conn| get_knowledge_base(conn, &name))?.is_some() {
+        return Err(format!("Knowledge base '{}' already exists", name));
+    }
+    profiles.apply_credentials(&mut embedding);
+    let dimensions = probe_dimensions(&embedding).await?;
+    vector::create_collection(&db, &name, dimensions, Metric::Cosine, Some(&embedding.model))?;
+    knowledge_base(&db, &name)
+}
+
+#[tauri::command]
+pub async fn rename_knowledge_base(app: AppHandle, name: String, new_name: String) -> Result<KnowledgeBase, String> {
+    let new_name = new_name.trim().to_string();
+    if new_name.is_empty() {
+        return Err("Knowledge base name cannot be empty".to_string());
+    }
+    if new_name == name {
+        return knowledge_base(&app.state::<Database>(), &name);
+    }
+    // No folder scan may add chunks under the old name meanwhile
+    let engine = app.state::<WatchEngine>();
+    let _guard = engine.hold().await;
+    let db = app.state::<Database>();
+    knowledge_base(&db, &name)?;
+    if db.with_conn(|conn| get_knowledge_base(conn, &new_name))?.is_some() {
+        return Err(format!("Knowledge base '{}' already exists", new_name));
+    }
+    db.with_conn(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO vector_collections (name, dimensions, metric, embedding_model, created_at)
+             SELECT ?2, dimensions, metric, embedding_model, created_at FROM vector_collections WHERE name = ?1",
+            params![name, new_name],
+        )?;
+        for table in ["vector_items", "vector_fts_keys", "watched_sources", "ingested_files"] {
+            tx.execute(&format!("UPDATE {} SET collection = ?2 WHERE collection = ?1", table), params![name, new_name])?;
+        }
+        tx.execute("DELETE FROM vector_collections WHERE name = ?1", params![name])?;
+        tx.commit()
+    })?;
+    eprintln!("[Knowledge] Renamed {} to {}", name, new_name);
+    knowledge_base(&db, &new_name)
+}
+
+// Delete the knowledge base with all its chunks, and stop watching the folders feeding it
+#[tauri::command]
+pub async fn delete_knowledge_base(app: AppHandle, name: String) -> Result<(), String> {
+    let engine = app.state::<WatchEngine>();
+    let _guard = engine.hold().await;
+    let db = app.state::<Database>();
+    let deleted = db.with_conn(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM watched_sources WHERE collection = ?1", params![name])?;
+        let deleted = tx.execute("DELETE FROM vector_collections WHERE name = ?1", params![name])?;
+        tx.commit()?;
+        Ok(deleted)
+    })?;
+    if deleted == 0 {
+        return Err(format!("Knowledge base '{}' not found", name));
+    }
+    eprintln!("[Knowledge] Deleted {}", name);
+    Ok(())
+}
+
+// Embed every chunk again with another model, for when the embedding model changes. The stored
+// chunks are replaced only once all of them are embedded, so a failure leaves the knowledge base
+// as it was. Progress is reported with `reembed-progress` events.
+#[tauri::command]
+pub async fn reembed_knowledge_base(
+    app: AppHandle,
+    profiles: State<'_, ProfileManager>,
+    name: String,
+    mut embedding: ProviderConfig,
+) -> Result<KnowledgeBase, String> {
+    profiles.apply_credentials(&mut embedding);
+    let engine = app.state::<WatchEngine>();
+    let _guard = engine.hold().await;
+    let db = app.state::<Database>();
+    knowledge_base(&db, &name)?;
+
+    let items: Vec<(String, Option<String>)> = db.with_conn(|conn| {
+        let mut stmt = conn.prepare("SELECT id, content FROM vector_items WHERE collection = ?1 ORDER BY id")?;
+        let rows = stmt.query_map(params![name], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    })?;
+    let without_text = items.iter().filter(|(_, content)| content.is_none()).count();
+    if without_text > 0 {
+        return Err(format!("{} chunks in '{}' have no text and can't be re-embedded", without_text, name));
+    }
+
+    let mut embeddings: Vec<Vec<f32>> = Vec::with_capacity(items.len());
+    for batch in items.chunks(EMBED_BATCH) {
+        let texts: Vec<String> = batch.iter().map(|(_, content)| content.clone().unwrap_or_default()).collect();
+        embeddings.extend(embed_batch(&embedding, &texts).await?);
+        let progress = ReembedProgress {
+            collection: name.clone(),
+            done: embeddings.len(),
+            total: items.len(),
+        };
+        if let Err(e) = app.emit(PROGRESS_EVENT, progress) {
+            eprintln!("[Knowledge] Failed to emit progress: {}", e);
+        }
+    }
+    let dimensions = match embeddings.first() {
+        Some(first) => first.len(),
+        None => probe_dimensions(&embedding).await?,
+    };
+    if embeddings.iter().any(|v| v.len() != dimensions) {
+        return Err("Embedder returned vectors of different sizes".to_string());
+    }
+
+    let updated_at = chrono::Utc::now().to_rfc3339();
+    db.with_conn(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "UPDATE vector_collections SET dimensions = ?1, embedding_model = ?2 WHERE name = ?3",
+            params![dimensions as i64, embedding.model, name],
+        )?;
+        {
+            let mut stmt =
+                tx.prepare("UPDATE vector_items SET embedding = ?1, updated_at = ?2 WHERE collection = ?3 AND id = ?4")?;
+            for ((id, _), vector) in items.iter().zip(&embeddings) {
+                stmt.execute(params![vector::to_blob(vector), updated_at, name, id])?;
+            }
+        }
+        update_watched_sources(&tx, &name, &embedding)?;
+        tx.commit()
+    })?;
+    eprintln!("[Knowledge] Re-embedded {} chunks of {} with {}", items.len(), name, embedding.model);
+    knowledge_base(&db, &name)
+}
@@ -0,0 +1,58 @@
+// Language of scraped pages, detected from their text with whatlang. Codes are ISO 639-3 (eng,
+// deu, fra), as for OCR. With preferred languages set (`scrapeLanguages`), pages in others are
+// either skipped or sorted after the rest; pages whose language can't be told count as preferred.
+use crate::settings::Settings;
+
+// Shorter texts are too short to tell apart related languages
+const MIN_CHARS: usize = 40;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum LanguageFilter {
+    // Keep pages in other languages but put them last
+    #[default]
+    Deprioritize,
+    Skip,
+}
+
+#[derive(Clone, Default)]
+pub struct LanguagePreference {
+    // Empty for any language
+    languages: Vec<String>,
+    filter: LanguageFilter,
+}
+
+// The language of `text`, when whatlang is sure of it
+pub fn detect(text: &str) -> Option<String> {
+    if text.trim().chars().count() < MIN_CHARS {
+        return None;
+    }
+    whatlang::detect(text).filter(|info| info.is_reliable()).map(|info| info.lang().code().to_string())
+}
+
+pub fn valid_code(code: &str) -> bool {
+    whatlang::Lang::from_code(code).is_some()
+}
+
+// English name of a language code, for messages
+pub fn name(code: &str) -> &str {
+    whatlang::Lang::from_code(code).map(|lang| lang.eng_name()).unwrap_or(code)
+}
+
+impl LanguagePreference {
+    pub fn from_settings(settings: &Settings) -> Self {
+        LanguagePreference {
+            languages: settings.scrape_languages.iter().map(|code| code.trim().to_lowercase()).collect(),
+            filter: settings.scrape_language_filter,
+        }
+    }
+
+    pub fn prefers(&self, language: Option<&str>) -> bool {
+        self.languages.is_empty() || language.is_none_or(|language| self.languages.iter().any(|l| l == language))
+    }
+
+    // Whether a page in `language` is dropped rather than kept
+    pub fn skips(&self, language: Option<&str>) -> bool {
+        self.filter == LanguageFilter::Skip && !self.prefers(language)
+    }
+}
@@ -2,84 +2,125 @@
 use std::time::Duration;
 use std::process::Command;
 
-use reqwest::blocking::Client;
 use reqwest::Url;
-use headless_chrome::{Browser, LaunchOptions};
 use tokio::time::timeout;
-use futures::future::join_all;
+use futures::stream::{self, StreamExt};
+use tauri::Manager;
+
+mod agent;
+mod archive;
+mod audit;
+mod backup;
+mod batches;
+mod blobs;
+mod browser;
+mod calc;
+mod challenge;
+mod chat;
+mod citations;
+mod context;
+mod cookies;
+mod credentials;
+mod crawl;
+mod conversations;
+mod db;
+mod dedup;
+mod encryption;
+mod epub;
+mod export;
+mod extractors;
+mod grammar;
+mod html;
+mod http_client;
+mod http_proxy;
+mod http_stream;
+mod images;
+mod import;
+mod ingest;
+mod knowledge;
+mod language;
+mod llm;
+mod maintenance;
+mod mcp;
+mod memory;
+mod metadata;
+mod ocr;
+mod office;
+mod page;
+mod page_cache;
+mod paywall;
+mod pdf;
+mod persona;
+mod politeness;
+mod preview;
+mod profiles;
+mod proxy;
+mod readability;
+mod rerank;
+mod search;
+mod selection;
+mod semantic_cache;
+mod settings;
+mod share;
+mod sitemap;
+mod sources;
+mod speech;
+mod stealth;
+mod stats;
+mod sync;
+mod tabular;
+mod tables;
+mod tags;
+mod templates;
+mod tools;
+mod transcribe;
+mod truncation;
+mod vector;
+mod watch;
+mod xml;
 
 #[tauri::command]
 fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
+// `auth` adds request headers and a saved credential, for private sites; `proxy` is a proxy URL to
+// use instead of the networkProxy setting
 #[tauri::command]
-async fn proxy_http_request(url: String, method: String, body: Option<String>) -> Result<String, String> {
-    eprintln!("[Rust Proxy] Request: {} {}", method, url);
-    
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(30))
-        .build()
-        .map_err(|e| format!("Failed to build client: {}", e))?;
-    
-    let request = match method.to_uppercase().as_str() {
-        "GET" => client.get(&url),
-        "POST" => {
-            let mut req = client.post(&url);
-            if let Some(b) = body {
-                req = req.header("Content-Type", "application/json").body(b);
-            }
-            req
-        }
-        _ => return Err(format!("Unsupported method: {}", method)),
-    };
-    
-    let response = request
-        .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
-    
-    let status = response.status();
-    eprintln!("[Rust Proxy] Response status: {}", status);
-    
-    if !status.is_success() {
-        return Err(format!("Request failed with status: {}", status));
-    }
-    
-    let text = response
-        .text()
-        .await
-        .map_err(|e| format!("Failed to read response: {}", e))?;
-    
-    eprintln!("[Rust Proxy] Response length: {} bytes", text.len());
-    Ok(text)
-}
-
-#[tauri::command]
-fn fetch_url(url: String) -> Result<String, String> {
+fn fetch_url(
+    app: tauri::AppHandle,
+    clients: tauri::State<'_, http_client::HttpClients>,
+    url: String,
+    auth: Option<credentials::RequestAuth>,
+    proxy: Option<String>,
+) -> Result<String, String> {
     let parsed = Url::parse(&url).map_err(|err| format!("Invalid URL: {err}"))?;
 
     match parsed.scheme() {
         "http" | "https" => {}
         _ => return Err("Only http and https schemes are allowed".to_string()),
     }
+    let headers = match &auth {
+        Some(auth) => credentials::resolve_for_app(&app, auth, &parsed)?,
+        None => Vec::new(),
+    };
 
-    let client = Client::builder()
-        .timeout(Duration::from_secs(20))
-        .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:109.0) Gecko/20100101 Firefox/115.0")
-        .build()
-        .map_err(|err| format!("Failed to build HTTP client: {err}"))?;
+    let settings = settings::load(&app);
+    let proxy = proxy::for_request(&settings, proxy.as_deref())?;
+    let defaults = http_client::ClientDefaults::from_settings(&settings);
+    let session = clients
+        .session(&defaults, proxy.as_ref(), Duration::from_secs(settings.fetch_timeout_secs))?
+        .with_headers(&headers)?;
 
-    let response = client
+    let request = session
         .get(parsed.clone())
         .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8")
         .header("Accept-Language", "en-US,en;q=0.9")
         .header("Accept-Encoding", "gzip, deflate, br")
         .header("DNT", "1")
         .header("Connection", "keep-alive")
-        .header("Upgrade-Insecure-Requests", "1")
-        .send()
-        .map_err(|err| format!("Request failed: {err}"))?;
+        .header("Upgrade-Insecure-Requests", "1");
+    let response = session.send(request)?.response;
 
     if !response.status().is_success() {
         return Err(format!("Request failed with status {}", response.status()));
@@ -90,8 +131,9 @@ fn fetch_url(url: String) -> Result<String, String> {
         .map_err(|err| format!("Failed to read response body: {err}"))
 }
 
+// Rendered HTML of a page, from a tab of the shared browser; `browser_path` picks the executable
 #[tauri::command]
-fn fetch_url_browser(url: String, browser_path: Option<String>) -> Result<String, String> {
+fn fetch_url_browser(app: tauri::AppHandle, url: String, browser_path: Option<String>) -> Result<String, String> {
     let parsed = Url::parse(&url).map_err(|err| format!("Invalid URL: {err}"))?;
 
     match parsed.scheme() {
@@ -99,27 +141,11 @@ fn fetch_url_browser(url: String, browser_path: Option<String>) -> Result<String
         _ => return Err("Only http and https schemes are allowed".to_string()),
     }
 
-    // Launch headless browser with custom path if provided
-    let mut launch_options = LaunchOptions {
-        headless: true,
-        sandbox: false,
-        ..Default::default()
-    };
-    
-    // Use provided path, or try to find Chrome automatically
-    if let Some(path) = browser_path {
-        launch_options.path = Some(std::path::PathBuf::from(path));
-    } else if let Some(path) = find_chrome_path() {
-        launch_options.path = Some(path);
-    }
-    
-    let browser = Browser::new(launch_options)
-        .map_err(|err| format!("Failed to launch browser: {err}"))?;
-
-    // Create a new tab
-    let tab = browser
-        .new_tab()
-        .map_err(|err| format!("Failed to create tab: {err}"))?;
+    // Take a tab from the shared browser, with the custom browser if one is given
+    let settings = settings::load(&app);
+    let mut config = browser::PoolConfig::from_settings(&settings);
+    config.browser_path = browser_path.map(std::path::PathBuf::from);
+    let tab = app.state::<browser::BrowserPool>().acquire(&config, Duration::from_millis(settings.scrape_timeout_ms))?;
 
     // Navigate to URL with timeout
     tab.navigate_to(&url)
@@ -139,22 +165,69 @@ fn fetch_url_browser(url: String, browser_path: Option<String>) -> Result<String
     Ok(html)
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
-struct SearchResult {
-    title: String,
-    url: String,
-    snippet: String,
-}
-
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
 struct ScrapedContent {
     url: String,
     title: String,
     content: String,
     metadata: ContentMetadata,
+    // Links on the page, collected for crawling
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    links: Vec<String>,
+    // Media type the page was served as, e.g. text/html, application/pdf or text/plain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content_type: Option<String>,
+    // Behind a paywall or login wall, so the content is likely a teaser
+    #[serde(default)]
+    paywalled: bool,
+    // Elements matched by each of the requested CSS selectors
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    selected: std::collections::HashMap<String, Vec<selection::SelectedElement>>,
+    #[serde(flatten)]
+    response: PageResponse,
+}
+
+// How the site answered for a page; empty for pages read through a site's API
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default)]
+struct PageResponse {
+    // HTTP status of the final response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+    // Where the URL led after redirects, when that is somewhere else
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    final_url: Option<String>,
+    // Redirects in order, from the requested URL on
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    redirects: Vec<Redirect>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
+struct Redirect {
+    // The URL that redirected
+    url: String,
+    // e.g. 301 or 302; the browser doesn't tell
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+}
+
+impl PageResponse {
+    fn new(requested: &str, status: Option<u16>, final_url: &str, redirects: Vec<Redirect>) -> Self {
+        // Compared as parsed, without fragments, so https://a.com and https://a.com/ are the same
+        let normalized = |url: &str| {
+            let mut url = Url::parse(url).ok()?;
+            url.set_fragment(None);
+            Some(url.to_string())
+        };
+        let moved = normalized(final_url) != normalized(requested);
+        PageResponse {
+            status,
+            final_url: moved.then(|| final_url.to_string()),
+            redirects,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default)]
 struct ContentMetadata {
     #[serde(skip_serializing_if = "Option::is_none")]
     published_date: Option<String>,
@@ -162,6 +235,166 @@ struct ContentMetadata {
     author: Option<String>,
     domain: String,
     word_count: usize,
+    // og: meta tags
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    open_graph: Option<metadata::OpenGraph>,
+    // twitter: meta tags
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    twitter_card: Option<metadata::TwitterCard>,
+    // schema.org articles, products and recipes from JSON-LD
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    structured_data: Vec<metadata::StructuredData>,
+    // ISO 639-3 code detected from the content, e.g. eng
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+    // Length of the content in characters before and after it was cut to a limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    original_chars: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    truncated_chars: Option<usize>,
+}
+
+impl ContentMetadata {
+    // Metadata of a page from its markup; the publisher's structured data wins over heuristics
+    fn from_page(
+        html: &str,
+        url: &str,
+        word_count: usize,
+        author: Option<String>,
+        published_date: Option<String>,
+    ) -> Self {
+        let page = metadata::extract(html);
+        ContentMetadata {
+            published_date: page.published_date().or(published_date),
+            author: page.author().or(author),
+            domain: extract_domain(url),
+            word_count,
+            open_graph: page.open_graph,
+            twitter_card: page.twitter_card,
+            structured_data: page.structured_data,
+            ..Default::default()
+        }
+    }
+}
+
+// How pages are scraped: the scrape settings with any per-call overrides, the shared browser and
+// the per-host limits
+#[derive(Clone)]
+struct ScrapeOptions {
+    timeout_ms: u64,
+    max_retries: u32,
+    format: html::Format,
+    browser: browser::BrowserPool,
+    browser_config: browser::PoolConfig,
+    hosts: politeness::HostScheduler,
+    host_limits: politeness::HostLimits,
+    // Fill in the links of scraped pages
+    collect_links: bool,
+    // Cookies shared between scrapes, when they are kept
+    cookies: Option<cookies::CookieJar>,
+    // Extra request headers, credentials included; such scrapes don't use the browser
+    headers: Vec<(String, String)>,
+    // Proxy for the scrape; the browser only runs with the one of the settings
+    proxy: Option<proxy::ProxyConfig>,
+    // Shared clients the scrape's requests go through, and their connection settings
+    clients: http_client::HttpClients,
+    client_defaults: http_client::ClientDefaults,
+    // Languages wanted, and whether pages in others are skipped
+    languages: language::LanguagePreference,
+    // Pages kept for revalidation, when they are cached
+    cache: Option<page_cache::PageCache>,
+    // Told of each retry with the attempt about to start and the error that caused it
+    on_retry: Option<RetryHook>,
+    // Cancellation of the batch the scrape belongs to
+    cancel: Option<batches::CancelToken>,
+    // Longer content is cut to this many characters
+    max_chars: Option<usize>,
+    // CSS selectors whose matches are returned besides the content
+    selectors: Vec<String>,
+}
+
+type RetryHook = std::sync::Arc<dyn Fn(u32, &str) + Send + Sync>;
+
+impl ScrapeOptions {
+    fn new(
+        settings: &settings::Settings,
+        browser: browser::BrowserPool,
+        hosts: politeness::HostScheduler,
+        clients: http_client::HttpClients,
+    ) -> Self {
+        let browser_config = browser::PoolConfig::from_settings(settings);
+        ScrapeOptions {
+            timeout_ms: settings.scrape_timeout_ms,
+            max_retries: settings.scrape_max_retries,
+            format: html::Format::Text,
+            browser,
+            proxy: browser_config.launch.proxy.clone(),
+            clients,
+            client_defaults: http_client::ClientDefaults::from_settings(settings),
+            browser_config,
+            hosts,
+            host_limits: politeness::HostLimits::from_settings(settings),
+            collect_links: false,
+            cookies: None,
+            headers: Vec::new(),
+            languages: language::LanguagePreference::from_settings(settings),
+            cache: None,
+            on_retry: None,
+            cancel: None,
+            max_chars: None,
+            selectors: Vec::new(),
+        }
+    }
+
+    // Options from the settings, with the app's shared browser, host scheduler, HTTP clients, cookies
+    // and page cache
+    fn for_app(app: &tauri::AppHandle, settings: &settings::Settings) -> Self {
+        let browser = app.state::<browser::BrowserPool>().inner().clone();
+        let hosts = app.state::<politeness::HostScheduler>().inner().clone();
+        let clients = app.state::<http_client::HttpClients>().inner().clone();
+        let mut options = ScrapeOptions::new(settings, browser, hosts, clients);
+        if settings.scrape_cookies {
+            let jar = app.state::<cookies::CookieJar>().inner().clone();
+            match profiles::data_dir(app) {
+                Ok(dir) => {
+                    jar.use_profile(&dir);
+                    options.cookies = Some(jar);
+                }
+                Err(err) => eprintln!("[Cookies] No profile directory, scraping without cookies: {}", err),
+            }
+        }
+        if settings.scrape_cache {
+            match profiles::data_dir(app) {
+                Ok(dir) => options.cache = Some(page_cache::PageCache::for_profile(&dir)),
+                Err(err) => eprintln!("[PageCache] No profile directory, scraping without the cache: {}", err),
+            }
+        }
+        options
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(|cancel| cancel.is_cancelled())
+    }
+
+    // Tells apart cached extractions of the same page
+    fn cache_variant(&self) -> String {
+        let format = match self.format {
+            html::Format::Text => "text",
+            html::Format::Markdown => "markdown",
+        };
+        let links = if self.collect_links { "+links" } else { "" };
+        format!("{}{}\n{}", format, links, self.selectors.join("\n"))
+    }
+
+    fn cached_page(&self, url: &str) -> Option<page_cache::CachedPage<ScrapedContent>> {
+        self.cache.as_ref()?.lookup(url, &self.cache_variant())
+    }
+
+    fn cache_page(&self, url: &str, validators: page_cache::Validators, content: &ScrapedContent) {
+        if let Some(cache) = &self.cache {
+            cache.store(url, &self.cache_variant(), validators, content);
+        }
+    }
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -171,73 +404,167 @@ struct ScrapeResult {
     content: Option<ScrapedContent>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error_kind: Option<ScrapeErrorKind>,
+    // HTTP status of the page, also when it failed, e.g. 404 for a dead link
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
 }
 
-#[tauri::command]
-fn web_search_and_scrape(query: String, max_results: Option<usize>) -> Result<Vec<ScrapedContent>, String> {
-    let _limit = max_results.unwrap_or(5);
-    
-    // Step 1: Search DuckDuckGo using POST (required by DuckDuckGo)
-    let _search_html = search_duckduckgo(&query)?;
-    
-    // Step 2: Parse search results (done in frontend, so we return empty for now)
-    // Frontend will handle parsing and call backend for scraping individual URLs
-    
-    Ok(Vec::new())
+// How a failed scrape failed
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+enum ScrapeErrorKind {
+    // The site answered with an anti-bot challenge page, also after waiting in the browser
+    Challenge,
+    // The page isn't in one of the preferred languages
+    Language,
+    // Its batch was cancelled
+    Cancelled,
+    Timeout,
+    Failed,
 }
 
-#[tauri::command]
-fn search_duckduckgo(query: &str) -> Result<String, String> {
-    // reqwest automatically handles decompression when using .text()
-    // The key is to NOT manually set Accept-Encoding header
-    let client = Client::builder()
-        .timeout(Duration::from_secs(20))
-        .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:109.0) Gecko/20100101 Firefox/115.0")
-        .build()
-        .map_err(|err| format!("Failed to build HTTP client: {err}"))?;
-
-    // DuckDuckGo requires POST request with form data
-    let params = [("q", query), ("b", ""), ("kl", "wt-wt")];
-    
-    eprintln!("Searching DuckDuckGo for: {}", query);
-    
-    let response = client
-        .post("https://html.duckduckgo.com/html/")
-        .form(&params)
-        .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8")
-        .header("Accept-Language", "en-US,en;q=0.9")
-        // NOTE: Do NOT set Accept-Encoding - let reqwest handle it automatically
-        .header("DNT", "1")
-        .header("Connection", "keep-alive")
-        .header("Upgrade-Insecure-Requests", "1")
-        .send()
-        .map_err(|err| format!("Request failed: {err}"))?;
+impl ScrapeResult {
+    fn failed(error: String, kind: ScrapeErrorKind) -> Self {
+        ScrapeResult {
+            success: false,
+            content: None,
+            error: Some(error),
+            error_kind: Some(kind),
+            status: None,
+        }
+    }
+}
 
-    eprintln!("Response status: {}", response.status());
-    eprintln!("Response headers: {:?}", response.headers());
+// Why a scrape attempt failed; challenge pages are told apart so they aren't taken for content
+enum ScrapeError {
+    Challenge(challenge::Challenge),
+    // The site answered with an error status
+    Status(reqwest::StatusCode),
+    Failed(String),
+}
 
-    if !response.status().is_success() {
-        return Err(format!("Request failed with status {}", response.status()));
+impl From<String> for ScrapeError {
+    fn from(error: String) -> Self {
+        ScrapeError::Failed(error)
     }
+}
 
-    // Using .text() automatically handles decompression
-    let text = response
-        .text()
-        .map_err(|err| format!("Failed to read response body: {err}"))?;
-    
-    // Debug: Log response info
-    eprintln!("Received {} characters of text", text.len());
-    eprintln!("First 200 chars: {}", &text.chars().take(200).collect::<String>());
-    
-    Ok(text)
+impl std::fmt::Display for ScrapeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScrapeError::Challenge(challenge) => write!(f, "Blocked by a {} bot challenge", challenge),
+            ScrapeError::Status(status) => write!(f, "Request failed with status {}", status),
+            ScrapeError::Failed(error) => f.write_str(error),
+        }
+    }
+}
+
+// How long a challenge page gets in the browser to let it through
+const CHALLENGE_WAIT: Duration = Duration::from_secs(15);
+
+// Search with the configured provider chain and scrape the top results. Pages that can't be
+// scraped fall back to the result's snippet so every hit is returned. With `since` (RFC 3339 or
+// YYYY-MM-DD) only news published since then is searched, and the article dates fill in pages
+// whose markup carries none. `limits` cuts each page to a length, as for scrape_urls.
+#[tauri::command]
+async fn web_search_and_scrape(
+    app: tauri::AppHandle,
+    query: String,
+    max_results: Option<usize>,
+    since: Option<String>,
+    limits: Option<truncation::ContentLimits>,
+) -> Result<Vec<ScrapedContent>, String> {
+    let limit = max_results.unwrap_or(5);
+    let settings = settings::load(&app);
+
+    let options = search::SearchOptions {
+        max_results: Some(limit),
+        ..Default::default()
+    };
+    let results: Vec<(search::SearchResult, Option<String>)> = match since.as_deref().filter(|s| !s.trim().is_empty()) {
+        Some(since) => {
+            let since = search::news::parse_date(since).ok_or_else(|| format!("Invalid date: {}", since))?;
+            search::news::news_search(&app, &query, Some(since), &options)
+                .await?
+                .results
+                .into_iter()
+                .map(|news| {
+                    let result = search::SearchResult {
+                        title: news.title,
+                        url: news.url,
+                        snippet: news.snippet,
+                    };
+                    (result, news.published_date)
+                })
+                .collect()
+        }
+        None => search::web_search(&app, &query, &options)
+            .await?
+            .results
+            .into_iter()
+            .map(|result| (result, None))
+            .collect(),
+    };
+
+    let mut scrape_options = ScrapeOptions::for_app(&app, &settings);
+    scrape_options.max_chars = limits.and_then(|limits| limits.max_chars());
+    // Up to scrapeMaxConcurrent pages in flight at any time, in the order of the results
+    let urls: Vec<String> = results.iter().map(|(result, _)| result.url.clone()).collect();
+    let scrapes: Vec<ScrapeResult> = stream::iter(urls)
+        .map(|url| scrape_url_async(url, scrape_options.clone()))
+        .buffered(settings.scrape_max_concurrent.max(1))
+        .collect()
+        .await;
+    let mut scraped = Vec::with_capacity(results.len());
+    for ((result, published_date), scrape) in results.iter().zip(scrapes) {
+        // Its snippet would be in the same unwanted language
+        if scrape.error_kind == Some(ScrapeErrorKind::Language) {
+            continue;
+        }
+        let mut content = scrape.content.unwrap_or_else(|| ScrapedContent {
+            url: result.url.clone(),
+            title: result.title.clone(),
+            content: result.snippet.clone(),
+            metadata: ContentMetadata {
+                published_date: None,
+                author: None,
+                domain: extract_domain(&result.url),
+                word_count: result.snippet.split_whitespace().count(),
+                ..Default::default()
+            },
+            links: Vec::new(),
+            content_type: None,
+            paywalled: false,
+            selected: Default::default(),
+            response: Default::default(),
+        });
+        if content.metadata.published_date.is_none() {
+            content.metadata.published_date = published_date.clone();
+        }
+        scraped.push(content);
+    }
+    // Pages in other languages go last, in their order
+    scraped.sort_by_key(|content| !scrape_options.languages.prefers(content.metadata.language.as_deref()));
+    Ok(scraped)
 }
 
-// Parsing is done in frontend (TypeScript has better HTML parsing)
-// This function is kept for future backend parsing implementation
-#[allow(dead_code)]
-fn parse_duckduckgo_results(_html: &str, _limit: usize) -> Result<Vec<SearchResult>, String> {
-    let results = Vec::new();
-    Ok(results)
+// Raw DuckDuckGo result page; options scope it by region, safe search, time range and page
+#[tauri::command]
+fn search_duckduckgo(
+    app: tauri::AppHandle,
+    clients: tauri::State<'_, http_client::HttpClients>,
+    query: &str,
+    options: Option<search::SearchOptions>,
+) -> Result<String, String> {
+    let options = options.unwrap_or_default();
+    let settings = settings::load(&app);
+    let proxy = proxy::for_request(&settings, options.proxy.as_deref())?;
+    let params = search::duckduckgo::form_params(&options, options.max_results.unwrap_or(search::DEFAULT_MAX_RESULTS));
+    let defaults = http_client::ClientDefaults::from_settings(&settings);
+    let session = clients.session(&defaults, proxy.as_ref(), Duration::from_secs(settings.fetch_timeout_secs))?;
+    search::duckduckgo::fetch_html(&session, query, &params)
 }
 
 // Helper function to extract domain from URL
@@ -249,7 +576,7 @@ fn extract_domain(url: &str) -> String {
 }
 
 // Helper function to clean and normalize text
-fn clean_text(text: &str) -> String {
+pub(crate) fn clean_text(text: &str) -> String {
     // Normalize whitespace
     text.split_whitespace()
         .collect::<Vec<_>>()
@@ -258,28 +585,123 @@ fn clean_text(text: &str) -> String {
         .to_string()
 }
 
+// Helper function to strip tags, scripts and styles from HTML, leaving plain text
+fn strip_html_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len() / 2);
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        text.push(' ');
+        rest = &rest[start..];
+
+        // Skip the contents of script and style elements entirely
+        let lower: String = rest.chars().take(7).collect::<String>().to_lowercase();
+        let closing = if lower.starts_with("<script") {
+            Some("</script>")
+        } else if lower.starts_with("<style") {
+            Some("</style>")
+        } else {
+            None
+        };
+
+        let end = match closing {
+            Some(tag) => rest.to_lowercase().find(tag).map(|i| i + tag.len()),
+            None => rest.find('>').map(|i| i + 1),
+        };
+
+        match end {
+            Some(end) => rest = &rest[end..],
+            None => {
+                rest = "";
+                break;
+            }
+        }
+    }
+    text.push_str(rest);
+
+    clean_text(
+        &text
+            .replace("&nbsp;", " ")
+            .replace("&amp;", "&")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&#39;", "'"),
+    )
+}
+
 // Helper function to scrape a single URL with retry mechanism
-fn scrape_single_url_with_retry(url: String, timeout_ms: u64, max_retries: u32) -> ScrapeResult {
+fn scrape_single_url_with_retry(url: String, options: &ScrapeOptions) -> ScrapeResult {
+    let max_retries = options.max_retries;
     let mut attempts = 0;
     let mut last_error = String::new();
+    let mut last_status = None;
+    let mut challenge_wait = Duration::ZERO;
     
     while attempts < max_retries {
+        if options.is_cancelled() {
+            return ScrapeResult::failed("Cancelled".to_string(), ScrapeErrorKind::Cancelled);
+        }
         attempts += 1;
         
-        match scrape_single_url_internal(&url, timeout_ms) {
-            Ok(content) => {
+        match scrape_single_url_internal(&url, options, challenge_wait) {
+            Ok(mut content) => {
+                content.metadata.language = language::detect(&content.content);
+                let detected = content.metadata.language.as_deref();
+                if let Some(code) = detected.filter(|code| options.languages.skips(Some(code))) {
+                    let error = format!("The page is in {}, not a preferred language", language::name(code));
+                    eprintln!("Skipping {}: {}", url, error);
+                    return ScrapeResult::failed(error, ScrapeErrorKind::Language);
+                }
+                if let Some(max_chars) = options.max_chars {
+                    limit_length(&mut content, max_chars);
+                }
                 return ScrapeResult {
                     success: true,
+                    status: content.response.status,
                     content: Some(content),
                     error: None,
+                    error_kind: None,
+                };
+            }
+            // Challenges let a real browser through after a few seconds of scripts, once; quick
+            // retries only get the same page
+            Err(err @ ScrapeError::Challenge(_)) => {
+                if !challenge_wait.is_zero() || !browser_allowed(options) {
+                    eprintln!("Error scraping {}: {}", url, err);
+                    return ScrapeResult::failed(err.to_string(), ScrapeErrorKind::Challenge);
+                }
+                eprintln!("{} for {}, retrying in the browser with a longer wait", err, url);
+                if let Some(on_retry) = &options.on_retry {
+                    on_retry(attempts, &err.to_string());
+                }
+                challenge_wait = CHALLENGE_WAIT;
+                // The retry doesn't use up an attempt
+                attempts -= 1;
+            }
+            // Missing pages and refused requests would only answer the same again
+            Err(ScrapeError::Status(status)) if status.is_client_error() && !matches!(status.as_u16(), 408 | 429) => {
+                let error = ScrapeError::Status(status).to_string();
+                eprintln!("Error scraping {}: {}", url, error);
+                return ScrapeResult {
+                    status: Some(status.as_u16()),
+                    ..ScrapeResult::failed(error, ScrapeErrorKind::Failed)
                 };
             }
             Err(err) => {
+                last_status = match &err {
+                    ScrapeError::Status(status) => Some(status.as_u16()),
+                    _ => None,
+                };
                 last_error = format!("Attempt {}/{}: {}", attempts, max_retries, err);
                 eprintln!("Error scraping {}: {}", url, last_error);
                 
                 // Wait before retry (exponential backoff)
                 if attempts < max_retries {
+                    if let Some(on_retry) = &options.on_retry {
+                        on_retry(attempts + 1, &err.to_string());
+                    }
                     let wait_ms = 1000 * (2_u64.pow(attempts - 1));
                     std::thread::sleep(Duration::from_millis(wait_ms));
                 }
@@ -288,140 +710,334 @@ fn scrape_single_url_with_retry(url: String, timeout_ms: u64, max_retries: u32)
     }
     
     ScrapeResult {
-        success: false,
-        content: None,
-        error: Some(last_error),
+        status: last_status,
+        ..ScrapeResult::failed(last_error, ScrapeErrorKind::Failed)
     }
 }
 
+// Cut the content to `max_chars`, noting the lengths in its metadata
+fn limit_length(content: &mut ScrapedContent, max_chars: usize) {
+    if let Some(truncated) = truncation::truncate(&content.content, max_chars) {
+        content.metadata.original_chars = Some(content.content.chars().count());
+        content.metadata.truncated_chars = Some(truncated.chars().count());
+        content.content = truncated;
+    }
+}
+
+// Requests of a scrape on the shared client, with its headers and the kept cookies when there are
+// any. Scrapes that send credentials don't follow redirects to another host.
+fn scrape_client(options: &ScrapeOptions) -> Result<http_client::Session, String> {
+    let timeout = Duration::from_millis(options.timeout_ms);
+    let session = options.clients.session(&options.client_defaults, options.proxy.as_ref(), timeout)?;
+    Ok(session.with_headers(&options.headers)?.with_cookies(options.cookies.clone()))
+}
+
 // Fallback function to scrape using reqwest (no browser)
-fn scrape_with_reqwest(url: &str, timeout_ms: u64) -> Result<ScrapedContent, String> {
-    let client = Client::builder()
-        .timeout(Duration::from_millis(timeout_ms))
-        .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:109.0) Gecko/20100101 Firefox/115.0")
-        .build()
-        .map_err(|err| format!("Failed to build HTTP client: {err}"))?;
-
-    let response = client
+fn scrape_with_reqwest(url: &str, options: &ScrapeOptions) -> Result<ScrapedContent, ScrapeError> {
+    let session = scrape_client(options)?;
+    let cached = options.cached_page(url);
+
+    let mut request = session
         .get(url)
         .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8")
-        .header("Accept-Language", "en-US,en;q=0.9")
-        .send()
-        .map_err(|err| format!("Request failed: {err}"))?;
+        .header("Accept-Language", "en-US,en;q=0.9");
+    if let Some(cached) = &cached {
+        request = cached.conditional(request);
+    }
+    let http_client::Sent { response, redirects } = session.send(request)?;
 
-    if !response.status().is_success() {
-        return Err(format!("Request failed with status {}", response.status()));
+    let validators = page_cache::Validators::from_headers(response.headers());
+    if let Some(cached) = cached.filter(|_| response.status() == reqwest::StatusCode::NOT_MODIFIED) {
+        eprintln!("{} is unchanged, using the cached copy", url);
+        options.cache_page(url, validators.or(cached.validators()), &cached.page);
+        return Ok(cached.page);
+    }
+    let redirects = redirects
+        .into_iter()
+        .map(|(from, status)| Redirect {
+            url: from.to_string(),
+            status: Some(status.as_u16()),
+        })
+        .collect();
+    let answered = PageResponse::new(url, Some(response.status().as_u16()), response.url().as_str(), redirects);
+    let mut content = read_page(url, options, response)?;
+    content.response = answered;
+    options.cache_page(url, validators, &content);
+    Ok(content)
+}
+
+// Content of a fetched page
+fn read_page(
+    url: &str,
+    options: &ScrapeOptions,
+    response: reqwest::blocking::Response,
+) -> Result<ScrapedContent, ScrapeError> {
+    let format = options.format;
+
+    // PDFs and text files skip the HTML pipeline
+    let status = response.status();
+    let content_type = media_type(response.headers());
+    if status.is_success() {
+        match content_type.as_deref() {
+            Some("application/pdf" | "application/octet-stream") => {
+                let bytes = response.bytes().map_err(|err| format!("Failed to read response body: {err}"))?;
+                // Servers send PDFs as generic downloads too; those are known by their header
+                if content_type.as_deref() == Some("application/pdf") || bytes.starts_with(b"%PDF-") {
+                    return Ok(pdf_content(url, &bytes)?);
+                }
+                return Err("Unsupported content type application/octet-stream".to_string().into());
+            }
+            Some(kind) if is_plain_text(kind) => {
+                let text = response.text().map_err(|err| format!("Failed to read response body: {err}"))?;
+                return Ok(text_content(url, &text, kind));
+            }
+            Some(kind) if !is_markup(kind) => return Err(format!("Unsupported content type {}", kind).into()),
+            _ => {}
+        }
     }
 
+    // Challenge pages often come with an error status, so the body is read first
     let html = response
         .text()
         .map_err(|err| format!("Failed to read response body: {err}"))?;
+    if let Some(challenge) = challenge::detect(&html, Some(status.as_u16())) {
+        return Err(ScrapeError::Challenge(challenge));
+    }
+    if !status.is_success() {
+        return Err(ScrapeError::Status(status));
+    }
 
-    // Simple HTML parsing - extract title and body text
-    let title = html
-        .split("<title>")
-        .nth(1)
-        .and_then(|s| s.split("</title>").next())
-        .unwrap_or("Untitled")
-        .to_string();
+    // Main article only, without navigation, sidebars and comments
+    let article = readability::extract(&html, format, Url::parse(url).ok().as_ref());
+    let word_count = article.text.split_whitespace().count();
+    let metadata = ContentMetadata::from_page(&html, url, word_count, article.byline, article.published_date);
 
-    // Very basic content extraction - just get the text
-    let content = clean_text(&html);
+    Ok(ScrapedContent {
+        url: url.to_string(),
+        title: article.title.unwrap_or_else(|| "Untitled".to_string()),
+        content: article.text,
+        metadata,
+        links: page_links(&html, url, options),
+        content_type: Some(content_type.unwrap_or_else(|| "text/html".to_string())),
+        paywalled: paywall::detect(&html, word_count),
+        selected: selection::extract(&html, &options.selectors),
+        response: Default::default(),
+    })
+}
+
+// Media type of a response, lowercased and without parameters such as the charset
+fn media_type(headers: &reqwest::header::HeaderMap) -> Option<String> {
+    let value = headers.get(reqwest::header::CONTENT_TYPE)?.to_str().ok()?;
+    let media_type = value.split(';').next()?.trim().to_lowercase();
+    (!media_type.is_empty()).then_some(media_type)
+}
+
+fn is_markup(media_type: &str) -> bool {
+    matches!(media_type, "text/html" | "application/xhtml+xml")
+}
+
+// Returned as they are
+fn is_plain_text(media_type: &str) -> bool {
+    matches!(media_type, "text/plain" | "text/markdown" | "text/x-markdown" | "text/csv" | "application/json")
+}
+
+// The file name at the end of a URL, for files without a title of their own
+fn file_title(url: &str) -> String {
+    url.split(['?', '#'])
+        .next()
+        .unwrap_or(url)
+        .rsplit('/')
+        .find(|segment| !segment.is_empty())
+        .map(|name| urlencoding::decode(name).map(|n| n.into_owned()).unwrap_or_else(|_| name.to_string()))
+        .unwrap_or_else(|| "Untitled".to_string())
+}
+
+// Text of a downloaded PDF
+fn pdf_content(url: &str, bytes: &[u8]) -> Result<ScrapedContent, String> {
+    let doc = pdf::parse(bytes, None)?;
+
+    let content = doc.text();
     let word_count = content.split_whitespace().count();
-    let domain = extract_domain(url);
+    let title = doc.metadata.title.clone().unwrap_or_else(|| file_title(url));
 
     Ok(ScrapedContent {
         url: url.to_string(),
-        title: clean_text(&title),
+        title,
         content,
         metadata: ContentMetadata {
-            published_date: None,
-            author: None,
-            domain,
+            published_date: doc.metadata.created,
+            author: doc.metadata.author,
+            domain: extract_domain(url),
             word_count,
+            ..Default::default()
         },
+        links: Vec::new(),
+        content_type: Some("application/pdf".to_string()),
+        paywalled: false,
+        selected: Default::default(),
+        response: Default::default(),
     })
 }
 
-// Helper function to find Chrome/Chromium on the system
-fn find_chrome_path() -> Option<std::path::PathBuf> {
-    // Common Chrome/Chromium paths on Windows
-    let possible_paths = vec![
-        // Chrome
-        std::env::var("PROGRAMFILES").ok().map(|p| format!("{}\\Google\\Chrome\\Application\\chrome.exe", p)),
-        std::env::var("PROGRAMFILES(X86)").ok().map(|p| format!("{}\\Google\\Chrome\\Application\\chrome.exe", p)),
-        std::env::var("LOCALAPPDATA").ok().map(|p| format!("{}\\Google\\Chrome\\Application\\chrome.exe", p)),
-        // Chromium
-        std::env::var("PROGRAMFILES").ok().map(|p| format!("{}\\Chromium\\Application\\chrome.exe", p)),
-        std::env::var("LOCALAPPDATA").ok().map(|p| format!("{}\\Chromium\\Application\\chrome.exe", p)),
-        // Edge (Chromium-based)
-        std::env::var("PROGRAMFILES(X86)").ok().map(|p| format!("{}\\Microsoft\\Edge\\Application\\msedge.exe", p)),
-        std::env::var("PROGRAMFILES").ok().map(|p| format!("{}\\Microsoft\\Edge\\Application\\msedge.exe", p)),
-    ];
-
-    for path_opt in possible_paths {
-        if let Some(path_str) = path_opt {
-            let path = std::path::PathBuf::from(&path_str);
-            if path.exists() {
-                eprintln!("Found browser at: {}", path_str);
-                return Some(path);
-            }
-        }
+// A plain text file as it is
+fn text_content(url: &str, text: &str, media_type: &str) -> ScrapedContent {
+    let content = text.trim().to_string();
+    ScrapedContent {
+        url: url.to_string(),
+        title: file_title(url),
+        metadata: ContentMetadata {
+            domain: extract_domain(url),
+            word_count: content.split_whitespace().count(),
+            ..Default::default()
+        },
+        content,
+        links: Vec::new(),
+        content_type: Some(media_type.to_string()),
+        paywalled: false,
+        selected: Default::default(),
+        response: Default::default(),
+    }
+}
+
+// Absolute http(s) links of a page when the options ask for them
+fn page_links(html: &str, url: &str, options: &ScrapeOptions) -> Vec<String> {
+    if !options.collect_links {
+        return Vec::new();
+    }
+    page::links(html, Url::parse(url).ok().as_ref()).into_iter().map(|link| link.url).collect()
+}
+
+// The browser would send the headers to every host the page loads from, and it can't switch to a
+// proxy of the scrape's own
+fn browser_allowed(options: &ScrapeOptions) -> bool {
+    options.headers.is_empty() && options.proxy == options.browser_config.launch.proxy
+}
+
+// The rendered markup of a tab's page
+fn page_markup(tab: &headless_chrome::Tab) -> Result<String, String> {
+    Ok(tab
+        .evaluate("document.documentElement.outerHTML", false)
+        .map_err(|err| format!("Failed to read the page markup: {err}"))?
+        .value
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default())
+}
+
+// Status of the page a tab shows and where it ended up. The browser doesn't list the redirects
+// in between, only that the requested URL led elsewhere.
+fn browser_response(tab: &headless_chrome::Tab, requested: &str) -> PageResponse {
+    let status = tab
+        .evaluate("performance.getEntriesByType('navigation')[0]?.responseStatus ?? null", false)
+        .ok()
+        .and_then(|result| result.value)
+        .and_then(|value| value.as_u64())
+        .and_then(|status| u16::try_from(status).ok())
+        .filter(|&status| status > 0);
+    let final_url = tab.get_url();
+    let mut response = PageResponse::new(requested, status, &final_url, Vec::new());
+    if response.final_url.is_some() {
+        response.redirects.push(Redirect {
+            url: requested.to_string(),
+            status: None,
+        });
     }
+    response
+}
 
-    eprintln!("No Chrome/Chromium/Edge browser found on system");
-    None
+// Give a challenge page up to `wait` to pass the browser on to the page
+fn wait_out_challenge(tab: &headless_chrome::Tab, wait: Duration) {
+    let deadline = std::time::Instant::now() + wait;
+    while std::time::Instant::now() < deadline {
+        if page_markup(tab).is_ok_and(|markup| challenge::detect(&markup, None).is_none()) {
+            let _ = tab.wait_until_navigated();
+            std::thread::sleep(Duration::from_millis(1000));
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
 }
 
-// Internal function to scrape a single URL
-fn scrape_single_url_internal(url: &str, timeout_ms: u64) -> Result<ScrapedContent, String> {
+// Internal function to scrape a single URL; `challenge_wait` is how long a challenge page gets to
+// let the browser through
+fn scrape_single_url_internal(
+    url: &str,
+    options: &ScrapeOptions,
+    challenge_wait: Duration,
+) -> Result<ScrapedContent, ScrapeError> {
+    let (timeout_ms, format) = (options.timeout_ms, options.format);
     // Validate URL
     let parsed = Url::parse(url).map_err(|err| format!("Invalid URL: {err}"))?;
     
     match parsed.scheme() {
         "http" | "https" => {}
-        _ => return Err("Only http and https schemes are allowed".to_string()),
+        _ => return Err("Only http and https schemes are allowed".to_string().into()),
     }
-    
-    // Try to find Chrome on the system
-    let chrome_path = find_chrome_path();
-    
-    // Try to launch headless browser, fallback to reqwest if it fails
-    let mut launch_options = LaunchOptions {
-        headless: true,
-        sandbox: false,
-        ..Default::default()
-    };
-    
-    // Use found Chrome path if available
-    if let Some(path) = chrome_path {
-        launch_options.path = Some(path);
+
+    // Sites with an extractor of their own skip the generic pipeline
+    if let Some(content) = extractors::extract(&parsed, options) {
+        return Ok(content);
     }
-    
-    let browser = match Browser::new(launch_options) {
-        Ok(b) => b,
+
+    // PDFs are fetched directly too; the browser would only show its viewer
+    if !browser_allowed(options) || parsed.path().to_lowercase().ends_with(".pdf") {
+        return scrape_with_reqwest(url, options);
+    }
+
+    // Pages the site reports unchanged aren't rendered again
+    let validators = match revalidate(url, options) {
+        Revalidated::Unchanged(content) => return Ok(*content),
+        Revalidated::Changed(validators) => validators,
+    };
+
+    // Take a tab from the shared browser, falling back to reqwest when there is none to be had
+    // within half the budget
+    let tab = match options.browser.acquire(&options.browser_config, Duration::from_millis(timeout_ms / 2)) {
+        Ok(tab) => tab,
         Err(err) => {
-            eprintln!("Failed to launch browser, falling back to reqwest: {}", err);
-            return scrape_with_reqwest(url, timeout_ms);
+            eprintln!("No browser tab available, falling back to reqwest: {}", err);
+            return scrape_with_reqwest(url, options);
         }
     };
-    
-    let tab = browser
-        .new_tab()
-        .map_err(|err| format!("Failed to create tab: {err}"))?;
+    // Cancelling the batch stops the navigation; dropped before the tab goes back
+    let _watch = options.cancel.as_ref().map(|cancel| cancel.watch(tab.handle()));
     
     // Set timeout for navigation
     tab.set_default_timeout(Duration::from_millis(timeout_ms));
+    if let Some(jar) = &options.cookies {
+        jar.prepare_tab(&tab, &parsed);
+    }
     
-    // Navigate to URL
-    tab.navigate_to(url)
-        .map_err(|err| format!("Failed to navigate: {err}"))?;
+    // Navigate to URL; files the browser downloads rather than shows abort the navigation
+    match tab.navigate_to(url) {
+        Ok(_) => {}
+        Err(err) if err.to_string().contains("ERR_ABORTED") => return scrape_with_reqwest(url, options),
+        Err(err) => return Err(format!("Failed to navigate: {err}").into()),
+    }
     
     tab.wait_until_navigated()
         .map_err(|err| format!("Navigation timeout: {err}"))?;
+    if options.is_cancelled() {
+        return Err("Cancelled".to_string().into());
+    }
+
+    // PDFs and text files are read from the response rather than from the browser's viewer
+    let content_type = tab
+        .evaluate("document.contentType", false)
+        .ok()
+        .and_then(|result| result.value)
+        .and_then(|value| value.as_str().map(str::to_lowercase))
+        .unwrap_or_else(|| "text/html".to_string());
+    if !is_markup(&content_type) {
+        return scrape_with_reqwest(url, options);
+    }
     
     // Wait for content to load
     std::thread::sleep(Duration::from_millis(1500));
+    wait_out_challenge(&tab, challenge_wait);
+    if let Some(jar) = &options.cookies {
+        jar.collect_from_tab(&tab);
+    }
+    let response = browser_response(&tab, url);
     
     // Extract content, metadata, and title using JavaScript
     let extraction_script = r#"
@@ -527,10 +1143,16 @@ fn scrape_single_url_internal(url: &str, timeout_ms: u64) -> Result<ScrapedConte
                         author: None,
                         domain,
                         word_count,
+                        ..Default::default()
                     },
+                    links: Vec::new(),
+                    content_type: Some(content_type),
+                    paywalled: false,
+                    selected: Default::default(),
+                    response,
                 });
             } else {
-                return Err("No value returned from extraction and fallback failed".to_string());
+                return Err("No value returned from extraction and fallback failed".to_string().into());
             }
         }
     };
@@ -557,104 +1179,305 @@ fn scrape_single_url_internal(url: &str, timeout_ms: u64) -> Result<ScrapedConte
         .and_then(|v| v.as_str())
         .map(|s| s.to_string());
     
-    // Clean the content
-    let cleaned_content = clean_text(&content_text);
+    // The rendered markup, for structured metadata and Markdown
+    let markup = page_markup(&tab)?;
+    if let Some(challenge) = challenge::detect(&markup, None) {
+        return Err(ScrapeError::Challenge(challenge));
+    }
+    // Error pages fail as they do without the browser
+    let status = response.status.and_then(|status| reqwest::StatusCode::from_u16(status).ok());
+    if let Some(status) = status.filter(|status| status.is_client_error() || status.is_server_error()) {
+        return Err(ScrapeError::Status(status));
+    }
+
+    // Clean the content; Markdown is rendered from the page's markup instead of its innerText
+    let cleaned_content = match format {
+        html::Format::Text => clean_text(&content_text),
+        html::Format::Markdown => readability::extract(&markup, format, Some(&parsed)).text,
+    };
     
     // Calculate word count
     let word_count = cleaned_content.split_whitespace().count();
     
-    // Extract domain
-    let domain = extract_domain(url);
-    
-    Ok(ScrapedContent {
+    let content = ScrapedContent {
         url: url.to_string(),
         title: clean_text(&title),
         content: cleaned_content,
-        metadata: ContentMetadata {
-            published_date,
-            author,
-            domain,
-            word_count,
-        },
-    })
+        metadata: ContentMetadata::from_page(&markup, url, word_count, author, published_date),
+        links: page_links(&markup, url, options),
+        content_type: Some(content_type),
+        paywalled: paywall::detect(&markup, word_count),
+        selected: selection::extract(&markup, &options.selectors),
+        response,
+    };
+    options.cache_page(url, validators, &content);
+    Ok(content)
+}
+
+enum Revalidated {
+    Unchanged(Box<ScrapedContent>),
+    // With the page's current validators, to cache a new rendering under
+    Changed(page_cache::Validators),
+}
+
+// Ask the site whether the cached copy of a page is still current, with a HEAD request so nothing
+// is downloaded; the browser only gets the page when it changed
+fn revalidate(url: &str, options: &ScrapeOptions) -> Revalidated {
+    let unknown = Revalidated::Changed(page_cache::Validators::default());
+    if options.cache.is_none() {
+        return unknown;
+    }
+    let cached = options.cached_page(url);
+    let Ok(session) = scrape_client(options) else { return unknown };
+    let mut request = session.head(url);
+    if let Some(cached) = &cached {
+        request = cached.conditional(request);
+    }
+    let Ok(http_client::Sent { response, .. }) = session.send(request) else { return unknown };
+    let validators = page_cache::Validators::from_headers(response.headers());
+    match cached {
+        Some(cached) if response.status() == reqwest::StatusCode::NOT_MODIFIED => {
+            eprintln!("{} is unchanged, using the cached copy", url);
+            options.cache_page(url, validators.or(cached.validators()), &cached.page);
+            Revalidated::Unchanged(Box::new(cached.page))
+        }
+        _ => Revalidated::Changed(validators),
+    }
 }
 
 // Async wrapper for scraping with timeout
-async fn scrape_url_async(url: String, timeout_ms: u64, max_retries: u32) -> ScrapeResult {
+async fn scrape_url_async(url: String, options: ScrapeOptions) -> ScrapeResult {
+    let timeout_ms = options.timeout_ms;
+    // Waiting for the site's turn doesn't count against the timeout
+    let _host = options.hosts.acquire(&url, options.host_limits).await;
     // Run the blocking scrape operation in a separate thread
     let result = tokio::task::spawn_blocking(move || {
-        scrape_single_url_with_retry(url, timeout_ms, max_retries)
+        scrape_single_url_with_retry(url, &options)
     });
     
-    // Apply timeout to the entire operation
-    match timeout(Duration::from_millis(timeout_ms + 5000), result).await {
+    // Apply timeout to the entire operation; waiting out a challenge gets its own time
+    match timeout(Duration::from_millis(timeout_ms + 5000) + CHALLENGE_WAIT, result).await {
         Ok(Ok(scrape_result)) => scrape_result,
-        Ok(Err(err)) => ScrapeResult {
-            success: false,
-            content: None,
-            error: Some(format!("Task error: {}", err)),
-        },
-        Err(_) => ScrapeResult {
-            success: false,
-            content: None,
-            error: Some("Overall timeout exceeded".to_string()),
-        },
+        Ok(Err(err)) => ScrapeResult::failed(format!("Task error: {}", err), ScrapeErrorKind::Failed),
+        Err(_) => ScrapeResult::failed("Overall timeout exceeded".to_string(), ScrapeErrorKind::Timeout),
+    }
+}
+
+const SCRAPE_PROGRESS_EVENT: &str = "scrape-progress";
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ScrapeProgress {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    batch_id: Option<String>,
+    url: String,
+    domain: String,
+    // Position of the URL in the request, from 1
+    index: usize,
+    total: usize,
+    // started, retrying, succeeded, failed or cancelled
+    stage: &'static str,
+    // The attempt about to start, when retrying
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attempt: Option<u32>,
+    // Since the URL started
+    elapsed_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error_kind: Option<ScrapeErrorKind>,
+}
+
+// Sends the progress of one URL of a batch to the frontend
+struct ProgressReporter {
+    app: tauri::AppHandle,
+    batch_id: Option<String>,
+    url: String,
+    index: usize,
+    total: usize,
+    started: std::time::Instant,
+}
+
+impl ProgressReporter {
+    fn emit(
+        &self,
+        stage: &'static str,
+        attempt: Option<u32>,
+        error: Option<String>,
+        error_kind: Option<ScrapeErrorKind>,
+    ) {
+        use tauri::Emitter;
+        let progress = ScrapeProgress {
+            batch_id: self.batch_id.clone(),
+            url: self.url.clone(),
+            domain: extract_domain(&self.url),
+            index: self.index,
+            total: self.total,
+            stage,
+            attempt,
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+            error,
+            error_kind,
+        };
+        if let Err(e) = self.app.emit(SCRAPE_PROGRESS_EVENT, progress) {
+            eprintln!("Failed to emit scrape progress: {}", e);
+        }
     }
 }
 
-// Main command to scrape multiple URLs in parallel
+// Scrape one URL of a batch, reporting as scrape-progress events when it starts, retries,
+// succeeds and fails
+async fn scrape_with_progress(
+    app: &tauri::AppHandle,
+    batch_id: Option<&str>,
+    url: String,
+    index: usize,
+    total: usize,
+    mut options: ScrapeOptions,
+) -> ScrapeResult {
+    let reporter = std::sync::Arc::new(ProgressReporter {
+        app: app.clone(),
+        batch_id: batch_id.map(str::to_string),
+        url: url.clone(),
+        index,
+        total,
+        started: std::time::Instant::now(),
+    });
+    let on_retry = reporter.clone();
+    options.on_retry = Some(std::sync::Arc::new(move |attempt, error: &str| {
+        on_retry.emit("retrying", Some(attempt), Some(error.to_string()), None);
+    }));
+    let cancel = options.cancel.clone().unwrap_or_default();
+    reporter.emit("started", None, None, None);
+    let result = tokio::select! {
+        biased;
+        _ = cancel.cancelled() => ScrapeResult::failed("Cancelled".to_string(), ScrapeErrorKind::Cancelled),
+        result = scrape_url_async(url, options) => result,
+    };
+    let stage = match result.error_kind {
+        None => "succeeded",
+        Some(ScrapeErrorKind::Cancelled) => "cancelled",
+        Some(_) => "failed",
+    };
+    reporter.emit(stage, None, result.error.clone(), result.error_kind);
+    result
+}
+
+// Main command to scrape multiple URLs in parallel; each URL's progress is sent as it happens
+// (scrape-progress events). A batch given a `batch_id` can be stopped with cancel_scrape, and
+// then returns what it has, the URLs it didn't finish marked cancelled.
+// Limits default to the scrape settings; the per-call parameters only remain for existing callers.
+// With a `query` and reranking enabled, successful pages come back most relevant first.
+// `format` is "text" (default) or "markdown", which keeps headings, lists, links and code blocks.
+// `limits` (maxChars, maxTokens) cuts longer pages, keeping their beginning and the headings of the
+// sections cut; the metadata then has the original and truncated lengths.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn scrape_urls(
+    app: tauri::AppHandle,
     urls: Vec<String>,
     timeout_ms: Option<u64>,
     max_retries: Option<u32>,
     max_concurrent: Option<usize>,
+    query: Option<String>,
+    format: Option<html::Format>,
+    batch_id: Option<String>,
+    limits: Option<truncation::ContentLimits>,
 ) -> Result<Vec<ScrapeResult>, String> {
-    let timeout_ms = timeout_ms.unwrap_or(45000); // Default 45 seconds (increased for slow sites like GitHub)
-    let max_retries = max_retries.unwrap_or(3); // Default 3 retries
-    let max_concurrent = max_concurrent.unwrap_or(5); // Default 5 concurrent requests
+    let settings = settings::load(&app);
+    let mut options = ScrapeOptions::for_app(&app, &settings);
+    options.timeout_ms = timeout_ms.unwrap_or(settings.scrape_timeout_ms);
+    options.max_retries = max_retries.unwrap_or(settings.scrape_max_retries);
+    options.format = format.unwrap_or_default();
+    options.max_chars = limits.and_then(|limits| limits.max_chars());
+    let max_concurrent = max_concurrent.unwrap_or(settings.scrape_max_concurrent).max(1);
     
     if urls.is_empty() {
         return Ok(Vec::new());
     }
     
     eprintln!("Starting scrape of {} URLs with max {} concurrent requests", urls.len(), max_concurrent);
+    let batches = app.state::<batches::ScrapeBatches>().inner().clone();
+    let batch_id = batch_id.filter(|id| !id.trim().is_empty());
+    if let Some(id) = &batch_id {
+        options.cancel = Some(batches.start(id)?);
+    }
     
-    // Process URLs in batches to limit concurrency
-    let mut all_results = Vec::new();
-    
-    for chunk in urls.chunks(max_concurrent) {
-        let futures: Vec<_> = chunk
-            .iter()
-            .map(|url| {
-                let url = url.clone();
-                scrape_url_async(url, timeout_ms, max_retries)
-            })
-            .collect();
-        
-        let results = join_all(futures).await;
-        all_results.extend(results);
+    // Up to max_concurrent scrapes in flight at any time, so a slow page only holds up its own
+    // slot; results keep the order of the URLs
+    let total = urls.len();
+    let mut all_results: Vec<ScrapeResult> = stream::iter(urls.into_iter().enumerate())
+        .map(|(i, url)| scrape_with_progress(&app, batch_id.as_deref(), url, i + 1, total, options.clone()))
+        .buffered(max_concurrent)
+        .collect()
+        .await;
+    if let Some(id) = &batch_id {
+        batches.finish(id);
     }
     
     // Log summary
     let successful = all_results.iter().filter(|r| r.success).count();
     let failed = all_results.len() - successful;
     eprintln!("Scraping complete: {} successful, {} failed", successful, failed);
+
+    if let Some(query) = query.filter(|q| !q.trim().is_empty()) {
+        all_results = tokio::task::spawn_blocking(move || {
+            let (mut scraped, failed): (Vec<_>, Vec<_>) = all_results.into_iter().partition(|r| r.content.is_some());
+            rerank::rerank_items(&app, &query, &mut scraped, |r| {
+                r.content.as_ref().map(|c| c.content.as_str()).unwrap_or("")
+            });
+            scraped.extend(failed);
+            scraped
+        })
+        .await
+        .map_err(|e| format!("Rerank task failed: {}", e))?;
+    }
+    // Pages in other languages go last, in their order
+    all_results.sort_by_key(|r| {
+        !options.languages.prefers(r.content.as_ref().and_then(|c| c.metadata.language.as_deref()))
+    });
     
     Ok(all_results)
 }
 
-// Command to scrape a single URL (for convenience)
+// Command to scrape a single URL (for convenience); `format` and `limits` as for scrape_urls. `auth`
+// adds request headers and a saved credential, and `proxy` replaces the networkProxy setting; such
+// pages are fetched without the browser. `selectors` (CSS, e.g. .price or #changelog li) returns the
+// text and HTML of the elements each matches in the content's `selected`.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn scrape_url(
+    app: tauri::AppHandle,
     url: String,
     timeout_ms: Option<u64>,
     max_retries: Option<u32>,
+    format: Option<html::Format>,
+    auth: Option<credentials::RequestAuth>,
+    proxy: Option<String>,
+    limits: Option<truncation::ContentLimits>,
+    selectors: Option<Vec<String>>,
 ) -> Result<ScrapeResult, String> {
-    let timeout_ms = timeout_ms.unwrap_or(45000); // Increased for slow sites
-    let max_retries = max_retries.unwrap_or(3);
-    
-    Ok(scrape_url_async(url, timeout_ms, max_retries).await)
+    let settings = settings::load(&app);
+    let mut options = ScrapeOptions::for_app(&app, &settings);
+    options.timeout_ms = timeout_ms.unwrap_or(settings.scrape_timeout_ms);
+    options.max_retries = max_retries.unwrap_or(settings.scrape_max_retries);
+    options.format = format.unwrap_or_default();
+    options.max_chars = limits.and_then(|limits| limits.max_chars());
+    options.selectors = selectors.unwrap_or_default();
+    selection::validate(&options.selectors)?;
+    if let Some(auth) = &auth {
+        let parsed = Url::parse(&url).map_err(|err| format!("Invalid URL: {err}"))?;
+        options.headers = credentials::resolve_for_app(&app, auth, &parsed)?;
+    }
+    if proxy.is_some() {
+        options.proxy = proxy::for_request(&settings, proxy.as_deref())?;
+    }
+    // What a credential shows isn't kept for later scrapes
+    if !options.headers.is_empty() {
+        options.cache = None;
+    }
+
+    Ok(scrape_url_async(url, options).await)
 }
 
 // CUDA detection command
@@ -879,24 +1702,233 @@ fn write_file_content(path: String, content: String) -> Result<(), String> {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // Headless MCP server mode: serve the native tools to other MCP clients instead of opening the UI
+    if let Some(mode) = mcp::server::mode_from_args() {
+        mcp::server::run_blocking(mode);
+        return;
+    }
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_shell::init())
+        .manage(tools::ToolRegistry::with_builtin_tools())
+        .manage(mcp::client::McpManager::default())
+        .manage(sync::SyncEngine::default())
+        .manage(rerank::RerankerState::default())
+        .manage(watch::WatchEngine::default())
+        .manage(transcribe::TranscriberState::default())
+        .manage(speech::SpeechEngine::default())
+        .manage(browser::BrowserPool::default())
+        .manage(politeness::HostScheduler::default())
+        .manage(cookies::CookieJar::default())
+        .manage(batches::ScrapeBatches::default())
+        .manage(preview::PreviewCache::default())
+        .manage(http_stream::StreamRequests::default())
+        .manage(http_client::HttpClients::shared())
+        .setup(|app| {
+            let profile_manager = profiles::ProfileManager::load(app.path().app_data_dir()?, app.path().app_config_dir()?)?;
+            let (database, blob_store) = encryption::open_storage(&profile_manager.data_dir())?;
+            app.manage(profile_manager);
+            app.manage(blob_store);
+            memory::register_tools(&app.state::<tools::ToolRegistry>(), &database);
+            search::register_tools(&app.state::<tools::ToolRegistry>(), app.handle());
+            sources::github::register_tools(&app.state::<tools::ToolRegistry>(), app.handle());
+            tools::register_app_tools(&app.state::<tools::ToolRegistry>(), app.handle());
+            app.manage(database);
+
+            sync::start_background(app.handle().clone());
+            maintenance::start_background(app.handle().clone());
+            watch::start_background(app.handle().clone());
+
+            // Connect configured MCP servers in the background so startup isn't blocked
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(err) = mcp::client::start_configured_servers(&handle).await {
+                    eprintln!("[MCP] Failed to start servers: {}", err);
+                }
+            });
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             greet, 
             fetch_url, 
             fetch_url_browser, 
             web_search_and_scrape, 
             search_duckduckgo,
+            search::search,
+            search::news::search_news,
+            search::images::search_images,
+            search::brave::set_brave_api_key,
+            sources::wikipedia::wikipedia_lookup,
+            sources::papers::search_arxiv_papers,
+            sources::papers::search_semantic_scholar_papers,
+            sources::papers::download_paper,
+            sources::reddit::reddit_search,
+            sources::reddit::reddit_thread,
+            sources::stackexchange::stackexchange_search,
+            sources::github::github_search_repos,
+            sources::github::github_search_issues,
+            sources::github::github_search_code,
+            sources::github::github_get_file,
+            sources::youtube::get_youtube_transcript,
+            calc::calculate_expression,
+            calc::convert_units,
+            sources::weather::get_weather,
+            tables::extract_tables,
+            page::extract_links,
+            page::extract_outline,
+            browser::browser_pool_status,
+            browser::shutdown_browser_pool,
+            archive::page_to_pdf,
+            sitemap::crawl_sitemap,
+            crawl::crawl_site,
+            cookies::clear_cookies,
+            page_cache::clear_page_cache,
+            batches::cancel_scrape,
+            preview::get_site_preview,
+            credentials::list_scrape_credentials,
+            credentials::set_scrape_credential,
+            proxy::set_proxy_password,
             scrape_urls,
             scrape_url,
-            proxy_http_request,
+            http_proxy::proxy_http_request,
+            http_stream::proxy_sse_request,
+            http_stream::cancel_sse_request,
             detect_cuda,
             run_terminal_command,
             read_file_content,
-            write_file_content
+            write_file_content,
+            agent::run_agent,
+            audit::get_agent_run,
+            audit::list_agent_runs,
+            audit::get_agent_log_retention,
+            audit::set_agent_log_retention,
+            tools::list_tools,
+            tabular::describe_data_file,
+            tabular::query_data_file,
+            mcp::client::reload_mcp_servers,
+            mcp::client::list_mcp_servers,
+            mcp::client::read_mcp_resource,
+            context::summarize_history,
+            chat::chat_completion,
+            chat::generate_chat_title,
+            semantic_cache::clear_semantic_cache,
+            conversations::create_conversation,
+            conversations::append_message,
+            conversations::list_conversations,
+            conversations::get_conversation,
+            conversations::set_conversation_archived,
+            conversations::set_message_pinned,
+            conversations::set_message_favorite,
+            conversations::list_pinned,
+            conversations::list_favorites,
+            conversations::delete_conversation,
+            conversations::list_trash,
+            conversations::restore_conversation,
+            conversations::purge_trash,
+            tags::list_tags,
+            tags::create_tag,
+            tags::update_tag,
+            tags::delete_tag,
+            tags::set_conversation_tags,
+            tags::list_folders,
+            tags::create_folder,
+            tags::rename_folder,
+            tags::delete_folder,
+            tags::move_to_folder,
+            conversations::create_branch,
+            conversations::list_siblings,
+            conversations::switch_branch,
+            blobs::store_attachment,
+            blobs::read_attachment,
+            blobs::delete_attachment,
+            blobs::collect_orphaned_blobs,
+            backup::create_backup,
+            backup::restore_backup,
+            profiles::list_profiles,
+            profiles::create_profile,
+            profiles::switch_profile,
+            profiles::set_provider_api_key,
+            profiles::list_provider_api_keys,
+            encryption::get_encryption_status,
+            encryption::enable_encryption,
+            encryption::disable_encryption,
+            encryption::unlock_storage,
+            sync::get_sync_config,
+            sync::set_sync_config,
+            sync::sync_now,
+            export::export_conversation,
+            share::share_conversation,
+            share::get_share_settings,
+            share::set_share_settings,
+            share::set_github_token,
+            import::import_chatgpt_export,
+            import::import_lmstudio_chats,
+            import::import_jan_threads,
+            import::import_sillytavern_chats,
+            chat::regenerate_with_seed,
+            chat::list_alternates,
+            chat::get_message_metadata,
+            citations::get_message_citations,
+            db::get_db_info,
+            stats::get_usage_stats,
+            vector::create_vector_collection,
+            vector::list_vector_collections,
+            vector::delete_vector_collection,
+            vector::upsert_vectors,
+            vector::delete_vectors,
+            vector::query_vectors,
+            vector::hybrid_query,
+            knowledge::list_knowledge_bases,
+            knowledge::get_knowledge_base_info,
+            knowledge::create_knowledge_base,
+            knowledge::rename_knowledge_base,
+            knowledge::delete_knowledge_base,
+            knowledge::reembed_knowledge_base,
+            rerank::rerank,
+            rerank::get_reranker_status,
+            watch::add_watched_source,
+            watch::remove_watched_source,
+            watch::list_watched_sources,
+            watch::rescan_watched_source,
+            ingest::ingest_documents,
+            pdf::extract_pdf,
+            ocr::ocr_image,
+            ocr::get_ocr_status,
+            images::prepare_image,
+            images::prepare_image_url,
+            transcribe::transcribe_audio,
+            transcribe::get_transcriber_status,
+            speech::speak,
+            speech::stop_speaking,
+            speech::pause_speaking,
+            speech::resume_speaking,
+            speech::list_voices,
+            office::extract_office,
+            settings::get_setting,
+            settings::set_setting,
+            settings::get_all_settings,
+            maintenance::get_maintenance_policy,
+            maintenance::set_maintenance_policy,
+            maintenance::run_maintenance,
+            memory::add_memory,
+            memory::list_memories,
+            memory::update_memory,
+            memory::delete_memory,
+            memory::recall_memories,
+            templates::list_templates,
+            templates::create_template,
+            templates::update_template,
+            templates::delete_template,
+            templates::render_template,
+            persona::list_personas,
+            persona::create_persona,
+            persona::update_persona,
+            persona::delete_persona,
+            persona::apply_persona,
+            grammar::json_schema_to_grammar
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
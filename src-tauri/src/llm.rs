@@ -0,0 +1,425 @@
+// Minimal chat client for the local LLM providers (Ollama and LM Studio)
+// Used by backend-side features that need to talk to a model directly.
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::http_client::{ClientDefaults, HttpClients};
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderType {
+    Ollama,
+    Lmstudio,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderConfig {
+    pub provider: ProviderType,
+    pub base_url: String,
+    pub model: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+    // GBNF grammar constraining the output (llama.cpp-based OpenAI-compatible servers)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grammar: Option<String>,
+    // JSON Schema the output must match (Ollama `format`, OpenAI `response_format`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json_schema: Option<Value>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    pub arguments: Value,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatMessage {
+    pub role: String,
+    #[serde(default)]
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl ChatMessage {
+    pub fn new(role: &str, content: impl Into<String>) -> Self {
+        ChatMessage {
+            role: role.to_string(),
+            content: content.into(),
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+            name: None,
+        }
+    }
+
+    pub fn tool_result(call: &ToolCall, content: impl Into<String>) -> Self {
+        ChatMessage {
+            role: "tool".to_string(),
+            content: content.into(),
+            tool_calls: Vec::new(),
+            tool_call_id: Some(call.id.clone()),
+            name: Some(call.name.clone()),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ChatCompletion {
+    pub message: ChatMessage,
+    pub prompt_tokens: Option<u64>,
+    pub completion_tokens: Option<u64>,
+    // Wall-clock time of the whole request
+    pub latency_ms: u64,
+    // Time to first token as reported by the server (requests aren't streamed, so it can't be measured here)
+    pub ttft_ms: Option<u64>,
+}
+
+// Random seed for requests that didn't pin one, so every response can be reproduced later
+pub fn random_seed() -> i64 {
+    use std::hash::{BuildHasher, Hasher};
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_i64(chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default());
+    (hasher.finish() & 0x7fff_ffff) as i64
+}
+
+// Send a non-streaming chat request and return the assistant message
+pub async fn chat(
+    config: &ProviderConfig,
+    messages: &[ChatMessage],
+    tools: &[Value],
+    options: &GenerationOptions,
+) -> Result<ChatCompletion, String> {
+    let base_url = config.base_url.trim_end_matches('/');
+    let (url, body) = match config.provider {
+        ProviderType::Ollama => (format!("{}/api/chat", base_url), ollama_body(config, messages, tools, options)),
+        ProviderType::Lmstudio => (format!("{}/v1/chat/completions", base_url), openai_body(config, messages, tools, options)),
+    };
+
+    eprintln!("[LLM] {} request to {} ({} messages, {} tools)", config.model, url, messages.len(), tools.len());
+
+    let client = HttpClients::shared().client(&ClientDefaults::default(), None)?;
+    let mut request = client.post(&url).timeout(Duration::from_secs(300)).json(&body);
+    if let Some(key) = config.api_key.as_deref().filter(|k| !k.is_empty()) {
+        request = request.bearer_auth(key);
+    }
+
+    let started = std::time::Instant::now();
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("LLM request failed: {}", e))?;
+
+    let status = response.status();
+    let data: Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse LLM response: {}", e))?;
+
+    if !status.is_success() {
+        let details = data
+            .get("error")
+            .map(|e| e.get("message").unwrap_or(e).to_string())
+            .unwrap_or_else(|| status.to_string());
+        return Err(format!("LLM request failed with status {}: {}", status, details));
+    }
+
+    let mut completion = match config.provider {
+        ProviderType::Ollama => parse_ollama_response(&data),
+        ProviderType::Lmstudio => parse_openai_response(&data),
+    }?;
+    completion.latency_ms = started.elapsed().as_millis() as u64;
+    Ok(completion)
+}
+
+fn ollama_body(config: &ProviderConfig, messages: &[ChatMessage], tools: &[Value], options: &GenerationOptions) -> Value {
+    let messages: Vec<Value> = messages
+        .iter()
+        .map(|msg| {
+            let mut m = json!({ "role": msg.role, "content": msg.content });
+            if !msg.tool_calls.is_empty() {
+                m["tool_calls"] = msg
+                    .tool_calls
+                    .iter()
+                    .map(|c| json!({ "function": { "name": c.name, "arguments": c.arguments } }))
+                    .collect();
+            }
+            if let Some(name) = &msg.name {
+                m["tool_name"] = json!(name);
+            }
+            m
+        })
+        .collect();
+
+    let mut body = json!({
+        "model": config.model,
+        "messages": messages,
+        "stream": false,
+        "options": {},
+    });
+    if let Some(t) = options.temperature {
+        body["options"]["temperature"] = json!(t);
+    }
+    if let Some(p) = options.top_p {
+        body["options"]["top_p"] = json!(p);
+    }
+    if let Some(n) = options.max_tokens {
+        body["options"]["num_predict"] = json!(n);
+    }
+    if let Some(seed) = options.seed {
+        body["options"]["seed"] = json!(seed);
+    }
+    if let Some(schema) = &options.json_schema {
+        body["format"] = schema.clone();
+    }
+    if options.grammar.is_some() {
+        eprintln!("[LLM] Ollama does not accept GBNF grammars, ignoring; pass a JSON schema instead");
+    }
+    if !tools.is_empty() {
+        body["tools"] = json!(tools);
+    }
+    body
+}
+
+fn openai_body(config: &ProviderConfig, messages: &[ChatMessage], tools: &[Value], options: &GenerationOptions) -> Value {
+    let messages: Vec<Value> = messages
+        .iter()
+        .map(|msg| {
+            let mut m = json!({ "role": msg.role, "content": msg.content });
+            if !msg.tool_calls.is_empty() {
+                m["tool_calls"] = msg
+                    .tool_calls
+                    .iter()
+                    .map(|c| {
+                        json!({
+                            "id": c.id,
+                            "type": "function",
+                            "function": { "name": c.name, "arguments": c.arguments.to_string() },
+                        })
+                    })
+                    .collect();
+            }
+            if let Some(id) = &msg.tool_call_id {
+                m["tool_call_id"] = json!(id);
+            }
+            m
+        })
+        .collect();
+
+    let mut body = json!({
+        "model": config.model,
+        "messages": messages,
+        "stream": false,
+    });
+    if let Some(t) = options.temperature {
+        body["temperature"] = json!(t);
+    }
+    if let Some(p) = options.top_p {
+        body["top_p"] = json!(p);
+    }
+    if let Some(n) = options.max_tokens {
+        body["max_tokens"] = json!(n);
+    }
+    if let Some(seed) = options.seed {
+        body["seed"] = json!(seed);
+    }
+    if let Some(grammar) = &options.grammar {
+        body["grammar"] = json!(grammar);
+    }
+    if let Some(schema) = &options.json_schema {
+        body["response_format"] = json!({
+            "type": "json_schema",
+            "json_schema": { "name": "response", "strict": true, "schema": schema },
+        });
+    }
+    if !tools.is_empty() {
+        body["tools"] = json!(tools);
+    }
+    body
+}
+
+// Arguments arrive as an object (Ollama) or as a JSON-encoded string (OpenAI)
+fn parse_arguments(value: Option<&Value>) -> Value {
+    match value {
+        Some(Value::String(s)) => serde_json::from_str(s).unwrap_or_else(|_| Value::String(s.clone())),
+        Some(v) => v.clone(),
+        None => json!({}),
+    }
+}
+
+fn parse_tool_calls(calls: Option<&Value>) -> Vec<ToolCall> {
+    calls
+        .and_then(|c| c.as_array())
+        .map(|calls| {
+            calls
+                .iter()
+                .enumerate()
+                .filter_map(|(i, call)| {
+                    let function = call.get("function")?;
+                    let name = function.get("name")?.as_str()?.to_string();
+                    let id = call
+                        .get("id")
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string())
+                        .unwrap_or_else(|| format!("call_{}", i));
+                    Some(ToolCall {
+                        id,
+                        name,
+                        arguments: parse_arguments(function.get("arguments")),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn parse_ollama_response(data: &Value) -> Result<ChatCompletion, String> {
+    let msg = data
+        .get("message")
+        .ok_or_else(|| "LLM response has no message".to_string())?;
+
+    let mut message = ChatMessage::new("assistant", msg.get("content").and_then(|v| v.as_str()).unwrap_or(""));
+    message.tool_calls = parse_tool_calls(msg.get("tool_calls"));
+
+    // Durations are in nanoseconds; the first token follows model loading and prompt evaluation
+    let nanos = |key: &str| data.get(key).and_then(|v| v.as_u64());
+    let ttft_ms = match (nanos("load_duration"), nanos("prompt_eval_duration")) {
+        (None, None) => None,
+        (load, prompt) => Some((load.unwrap_or(0) + prompt.unwrap_or(0)) / 1_000_000),
+    };
+
+    Ok(ChatCompletion {
+        message,
+        prompt_tokens: data.get("prompt_eval_count").and_then(|v| v.as_u64()),
+        completion_tokens: data.get("eval_count").and_then(|v| v.as_u64()),
+        latency_ms: 0,
+        ttft_ms,
+    })
+}
+
+fn parse_openai_response(data: &Value) -> Result<ChatCompletion, String> {
+    let msg = data
+        .get("choices")
+        .and_then(|c| c.get(0))
+        .and_then(|c| c.get("message"))
+        .ok_or_else(|| "LLM response has no choices".to_string())?;
+
+    let mut message = ChatMessage::new("assistant", msg.get("content").and_then(|v| v.as_str()).unwrap_or(""));
+    message.tool_calls = parse_tool_calls(msg.get("tool_calls"));
+
+    let usage = data.get("usage");
+    // llama.cpp-based servers report prompt processing time; LM Studio reports time to first token in seconds
+    let ttft_ms = data
+        .get("timings")
+        .and_then(|t| t.get("prompt_ms"))
+        .and_then(|v| v.as_f64())
+        .or_else(|| {
+            data.get("stats")
+                .and_then(|s| s.get("time_to_first_token"))
+                .and_then(|v| v.as_f64())
+                .map(|secs| secs * 1000.0)
+        })
+        .map(|ms| ms as u64);
+    Ok(ChatCompletion {
+        message,
+        prompt_tokens: usage.and_then(|u| u.get("prompt_tokens")).and_then(|v| v.as_u64()),
+        completion_tokens: usage.and_then(|u| u.get("completion_tokens")).and_then(|v| v.as_u64()),
+        latency_ms: 0,
+        ttft_ms,
+    })
+}
+
+// Compute embeddings for a batch of texts with the provider's embedding endpoint
+pub async fn embed(config: &ProviderConfig, inputs: &[String]) -> Result<Vec<Vec<f32>>, String> {
+    if inputs.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let base_url = config.base_url.trim_end_matches('/');
+    let url = match config.provider {
+        ProviderType::Ollama => format!("{}/api/embed", base_url),
+        ProviderType::Lmstudio => format!("{}/v1/embeddings", base_url),
+    };
+
+    let client = HttpClients::shared().client(&ClientDefaults::default(), None)?;
+    let mut request = client
+        .post(&url)
+        .timeout(Duration::from_secs(120))
+        .json(&json!({ "model": config.model, "input": inputs }));
+    if let Some(key) = config.api_key.as_deref().filter(|k| !k.is_empty()) {
+        request = request.bearer_auth(key);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Embedding request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Embedding request failed with status {}", response.status()));
+    }
+    let data: Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse embedding response: {}", e))?;
+
+    let to_vector = |v: &Value| -> Vec<f32> {
+        v.as_array()
+            .map(|a| a.iter().filter_map(|x| x.as_f64()).map(|x| x as f32).collect())
+            .unwrap_or_default()
+    };
+
+    let embeddings: Vec<Vec<f32>> = match config.provider {
+        ProviderType::Ollama => data
+            .get("embeddings")
+            .and_then(|e| e.as_array())
+            .map(|rows| rows.iter().map(to_vector).collect())
+            .unwrap_or_default(),
+        ProviderType::Lmstudio => {
+            let mut rows: Vec<(u64, Vec<f32>)> = data
+                .get("data")
+                .and_then(|d| d.as_array())
+                .map(|rows| {
+                    rows.iter()
+                        .map(|r| {
+                            let index = r.get("index").and_then(|i| i.as_u64()).unwrap_or(0);
+                            (index, to_vector(r.get("embedding").unwrap_or(&Value::Null)))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            rows.sort_by_key(|(index, _)| *index);
+            rows.into_iter().map(|(_, v)| v).collect()
+        }
+    };
+
+    if embeddings.len() != inputs.len() {
+        return Err(format!(
+            "Expected {} embeddings but the provider returned {}",
+            inputs.len(),
+            embeddings.len()
+        ));
+    }
+    Ok(embeddings)
+}
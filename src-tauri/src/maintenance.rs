@@ -0,0 +1,264 @@
+// Storage maintenance: archives stale conversations and keeps attachments under a size cap.
+// Runs in the background on the configured interval, or on demand (optionally as a dry run).
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use rusqlite::params;
+use tauri::{AppHandle, Manager};
+
+use crate::blobs::{self, BlobStore, GcReport};
+use crate::db::Database;
+use crate::profiles;
+
+const POLICY_FILE: &str = "maintenance.json";
+// How often the background task checks whether maintenance is due
+const BACKGROUND_TICK: Duration = Duration::from_secs(15 * 60);
+const DEFAULT_INTERVAL_HOURS: u64 = 24;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenancePolicy {
+    // Archive conversations not updated for this many days
+    pub archive_after_days: Option<u64>,
+    // Remove attachments of the oldest messages once blobs take more than this many bytes
+    pub max_attachment_bytes: Option<u64>,
+    // Hours between background runs (defaults to daily)
+    pub interval_hours: Option<u64>,
+    // Set by the maintenance task
+    #[serde(default)]
+    pub last_run: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivedConversation {
+    id: String,
+    title: String,
+    updated_at: String,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemovedAttachment {
+    id: String,
+    message_id: String,
+    file_name: String,
+    created_at: String,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceReport {
+    dry_run: bool,
+    archived: Vec<ArchivedConversation>,
+    removed_attachments: Vec<RemovedAttachment>,
+    attachment_bytes_before: u64,
+    attachment_bytes_after: u64,
+    // Blob files deleted once nothing referenced them (None in dry runs)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    garbage_collection: Option<GcReport>,
+    finished_at: String,
+}
+
+fn policy_path(app: &AppHandle) -> Result<PathBuf, String> {
+    profiles::config_dir(app).map(|dir| dir.join(POLICY_FILE))
+}
+
+fn load_policy(app: &AppHandle) -> Result<MaintenancePolicy, String> {
+    let path = policy_path(app)?;
+    if !path.exists() {
+        return Ok(MaintenancePolicy::default());
+    }
+    let text = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&text).map_err(|e| format!("Invalid maintenance policy: {}", e))
+}
+
+fn save_policy(app: &AppHandle, policy: &MaintenancePolicy) -> Result<(), String> {
+    let path = policy_path(app)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(policy).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to save maintenance policy: {}", e))
+}
+
+fn stale_conversations(db: &Database, days: u64) -> Result<Vec<ArchivedConversation>, String> {
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(days as i64)).to_rfc3339();
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, title, updated_at FROM conversations
+             WHERE archived_at IS NULL AND updated_at < ?1
+               AND id NOT IN (SELECT conversation_id FROM trash)
+             ORDER BY updated_at",
+        )?;
+        let rows = stmt.query_map(params![cutoff], |row| {
+            Ok(ArchivedConversation {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                updated_at: row.get(2)?,
+            })
+        })?;
+        rows.collect()
+    })
+}
+
+// Oldest attachments to drop so the referenced blobs fit in `max_bytes`.
+// A blob only frees space once every attachment sharing it is gone.
+fn attachments_over_cap(
+    db: &Database,
+    blobs: &BlobStore,
+    max_bytes: u64,
+) -> Result<(Vec<RemovedAttachment>, u64, u64), String> {
+    let sizes: HashMap<String, u64> = blobs.list().into_iter().map(|(hash, _, size)| (hash, size)).collect();
+    let attachments: Vec<(RemovedAttachment, String)> = db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, message_id, file_name, created_at, hash FROM attachments
+             WHERE hash IS NOT NULL ORDER BY created_at, id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                RemovedAttachment {
+                    id: row.get(0)?,
+                    message_id: row.get(1)?,
+                    file_name: row.get(2)?,
+                    created_at: row.get(3)?,
+                },
+                row.get(4)?,
+            ))
+        })?;
+        rows.collect()
+    })?;
+
+    let mut references: HashMap<&str, usize> = HashMap::new();
+    for (_, hash) in &attachments {
+        *references.entry(hash.as_str()).or_default() += 1;
+    }
+    let before: u64 = references.keys().filter_map(|hash| sizes.get(*hash)).sum();
+
+    let mut total = before;
+    let mut removed = Vec::new();
+    for (attachment, hash) in &attachments {
+        if total <= max_bytes {
+            break;
+        }
+        let count = references.entry(hash.as_str()).or_default();
+        *count -= 1;
+        if *count == 0 {
+            total -= sizes.get(hash).copied().unwrap_or(0);
+        }
+        removed.push(RemovedAttachment {
+            id: attachment.id.clone(),
+            message_id: attachment.message_id.clone(),
+            file_name: attachment.file_name.clone(),
+            created_at: attachment.created_at.clone(),
+        });
+    }
+    Ok((removed, before, total))
+}
+
+pub fn run(db: &Database, blobs: &BlobStore, policy: &MaintenancePolicy, dry_run: bool) -> Result<MaintenanceReport, String> {
+    let archived = match policy.archive_after_days {
+        Some(days) => stale_conversations(db, days)?,
+        None => Vec::new(),
+    };
+    let (removed_attachments, before, after) = match policy.max_attachment_bytes {
+        Some(max) => attachments_over_cap(db, blobs, max)?,
+        None => (Vec::new(), 0, 0),
+    };
+
+    let mut garbage_collection = None;
+    if !dry_run {
+        let now = chrono::Utc::now().to_rfc3339();
+        db.with_conn(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            for conversation in &archived {
+                tx.execute(
+                    "UPDATE conversations SET archived_at = ?1 WHERE id = ?2",
+                    params![now, conversation.id],
+                )?;
+            }
+            for attachment in &removed_attachments {
+                tx.execute("DELETE FROM attachments WHERE id = ?1", params![attachment.id])?;
+            }
+            tx.commit()
+        })?;
+        if !removed_attachments.is_empty() {
+            garbage_collection = Some(blobs::collect_orphans(db, blobs)?);
+        }
+    }
+
+    Ok(MaintenanceReport {
+        dry_run,
+        archived,
+        removed_attachments,
+        attachment_bytes_before: before,
+        attachment_bytes_after: after,
+        garbage_collection,
+        finished_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+fn run_and_record(app: &AppHandle, dry_run: bool) -> Result<MaintenanceReport, String> {
+    let mut policy = load_policy(app)?;
+    let report = run(&app.state::<Database>(), &app.state::<BlobStore>(), &policy, dry_run)?;
+    if !dry_run {
+        policy.last_run = Some(report.finished_at.clone());
+        save_policy(app, &policy)?;
+        eprintln!(
+            "[Maintenance] Archived {} conversations, removed {} attachments",
+            report.archived.len(),
+            report.removed_attachments.len()
+        );
+    }
+    Ok(report)
+}
+
+fn is_due(policy: &MaintenancePolicy) -> bool {
+    if policy.archive_after_days.is_none() && policy.max_attachment_bytes.is_none() {
+        return false;
+    }
+    let interval = chrono::Duration::hours(policy.interval_hours.unwrap_or(DEFAULT_INTERVAL_HOURS).max(1) as i64);
+    policy
+        .last_run
+        .as_deref()
+        .and_then(|last| chrono::DateTime::parse_from_rfc3339(last).ok())
+        .is_none_or(|last| chrono::Utc::now() - last.with_timezone(&chrono::Utc) >= interval)
+}
+
+// Background loop: runs maintenance whenever a policy is configured and the interval has elapsed
+pub fn start_background(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(BACKGROUND_TICK).await;
+            let Ok(policy) = load_policy(&app) else { continue };
+            if !is_due(&policy) {
+                continue;
+            }
+            if let Err(err) = run_and_record(&app, false) {
+                eprintln!("[Maintenance] Background run failed: {}", err);
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_maintenance_policy(app: AppHandle) -> Result<MaintenancePolicy, String> {
+    load_policy(&app)
+}
+
+#[tauri::command]
+pub fn set_maintenance_policy(app: AppHandle, policy: MaintenancePolicy) -> Result<MaintenancePolicy, String> {
+    let policy = MaintenancePolicy {
+        last_run: load_policy(&app)?.last_run,
+        ..policy
+    };
+    save_policy(&app, &policy)?;
+    Ok(policy)
+}
+
+// Apply the policy now; with `dryRun` only report what would be archived and removed
+#[tauri::command]
+pub fn run_maintenance(app: AppHandle, dry_run: Option<bool>) -> Result<MaintenanceReport, String> {
+    run_and_record(&app, dry_run.unwrap_or(false))
+}
@@ -0,0 +1,380 @@
+// MCP client: spawns configured stdio servers and registers their tools with the agent
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager, State};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::oneshot;
+
+use super::{content_to_text, notification_message, request_message, PROTOCOL_VERSION};
+use crate::profiles;
+use crate::tools::{Tool, ToolParameters, ToolRegistry};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+const CONFIG_FILE: &str = "mcp_servers.json";
+
+// Same layout as other MCP hosts: { "mcpServers": { "<name>": { "command": ..., "args": [...], "env": {...} } } }
+#[derive(serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct McpConfig {
+    #[serde(default)]
+    mcp_servers: HashMap<String, McpServerConfig>,
+}
+
+#[derive(serde::Deserialize, Clone)]
+struct McpServerConfig {
+    command: String,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    env: HashMap<String, String>,
+    #[serde(default)]
+    disabled: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct McpResource {
+    uri: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mime_type: Option<String>,
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct McpServerInfo {
+    name: String,
+    tools: Vec<String>,
+    resources: Vec<McpResource>,
+}
+
+type PendingMap = Arc<Mutex<HashMap<u64, oneshot::Sender<Result<Value, String>>>>>;
+
+pub struct McpClient {
+    name: String,
+    stdin: tokio::sync::Mutex<ChildStdin>,
+    child: tokio::sync::Mutex<Child>,
+    pending: PendingMap,
+    next_id: AtomicU64,
+    tools: Mutex<Vec<String>>,
+    resources: Mutex<Vec<McpResource>>,
+}
+
+impl McpClient {
+    async fn spawn(name: &str, config: &McpServerConfig) -> Result<Arc<Self>, String> {
+        let mut child = Command::new(&config.command)
+            .args(&config.args)
+            .envs(&config.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Failed to start MCP server '{}': {}", name, e))?;
+
+        let stdin = child.stdin.take().ok_or("MCP server has no stdin")?;
+        let stdout = child.stdout.take().ok_or("MCP server has no stdout")?;
+        let pending: PendingMap = Arc::new(Mutex::new(HashMap::new()));
+
+        // Route responses back to the waiting requests by id
+        let reader_pending = pending.clone();
+        let server_name = name.to_string();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stdout).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let Ok(message) = serde_json::from_str::<Value>(&line) else {
+                    eprintln!("[MCP:{}] Ignoring non-JSON output: {}", server_name, line);
+                    continue;
+                };
+                let Some(id) = message.get("id").and_then(|v| v.as_u64()) else {
+                    continue; // notification or server-initiated request
+                };
+                if let Some(sender) = reader_pending.lock().unwrap().remove(&id) {
+                    let result = match message.get("error") {
+                        Some(err) => Err(err
+                            .get("message")
+                            .and_then(|m| m.as_str())
+                            .unwrap_or("Unknown MCP error")
+                            .to_string()),
+                        None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                    };
+                    let _ = sender.send(result);
+                }
+            }
+            eprintln!("[MCP:{}] Server output closed", server_name);
+            for (_, sender) in reader_pending.lock().unwrap().drain() {
+                let _ = sender.send(Err("MCP server exited".to_string()));
+            }
+        });
+
+        let client = Arc::new(McpClient {
+            name: name.to_string(),
+            stdin: tokio::sync::Mutex::new(stdin),
+            child: tokio::sync::Mutex::new(child),
+            pending,
+            next_id: AtomicU64::new(1),
+            tools: Mutex::new(Vec::new()),
+            resources: Mutex::new(Vec::new()),
+        });
+
+        client
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": { "name": "openchat", "version": env!("CARGO_PKG_VERSION") },
+                }),
+            )
+            .await?;
+        client.notify("notifications/initialized", json!({})).await?;
+
+        Ok(client)
+    }
+
+    async fn write(&self, message: &Value) -> Result<(), String> {
+        let mut line = message.to_string();
+        line.push('\n');
+        let mut stdin = self.stdin.lock().await;
+        stdin
+            .write_all(line.as_bytes())
+            .await
+            .map_err(|e| format!("Failed to write to MCP server '{}': {}", self.name, e))?;
+        stdin.flush().await.map_err(|e| e.to_string())
+    }
+
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, tx);
+
+        if let Err(e) = self.write(&request_message(id, method, params)).await {
+            self.pending.lock().unwrap().remove(&id);
+            return Err(e);
+        }
+
+        match tokio::time::timeout(REQUEST_TIMEOUT, rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(format!("MCP server '{}' dropped the request", self.name)),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&id);
+                Err(format!("MCP request '{}' to '{}' timed out", method, self.name))
+            }
+        }
+    }
+
+    async fn notify(&self, method: &str, params: Value) -> Result<(), String> {
+        self.write(&notification_message(method, params)).await
+    }
+
+    // Collect a paginated list (tools/list, resources/list)
+    async fn list_all(&self, method: &str, key: &str) -> Result<Vec<Value>, String> {
+        let mut items = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match &cursor {
+                Some(c) => json!({ "cursor": c }),
+                None => json!({}),
+            };
+            let result = self.request(method, params).await?;
+            if let Some(page) = result.get(key).and_then(|v| v.as_array()) {
+                items.extend(page.iter().cloned());
+            }
+            cursor = result.get("nextCursor").and_then(|v| v.as_str()).map(|s| s.to_string());
+            if cursor.is_none() {
+                return Ok(items);
+            }
+        }
+    }
+
+    async fn shutdown(&self) {
+        if let Err(e) = self.child.lock().await.kill().await {
+            eprintln!("[MCP:{}] Failed to stop server: {}", self.name, e);
+        }
+    }
+}
+
+// Agent tool that forwards calls to a tool on an MCP server
+struct McpTool {
+    client: Arc<McpClient>,
+    name: String,
+    remote_name: String,
+    description: String,
+    parameters: ToolParameters,
+}
+
+impl Tool for McpTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        self.parameters.clone()
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move {
+            let result = self
+                .client
+                .request("tools/call", json!({ "name": self.remote_name, "arguments": args }))
+                .await?;
+            let text = content_to_text(&result);
+            if result.get("isError").and_then(|v| v.as_bool()).unwrap_or(false) {
+                return Err(text);
+            }
+            Ok(text)
+        })
+    }
+}
+
+// Provider tool names are limited to [a-zA-Z0-9_-]
+fn tool_id(server: &str, tool: &str) -> String {
+    format!("{}__{}", server, tool)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .take(64)
+        .collect()
+}
+
+#[derive(Default)]
+pub struct McpManager {
+    clients: tokio::sync::Mutex<HashMap<String, Arc<McpClient>>>,
+}
+
+fn config_path(app: &AppHandle) -> Result<PathBuf, String> {
+    profiles::config_dir(app).map(|dir| dir.join(CONFIG_FILE))
+}
+
+fn load_config(app: &AppHandle) -> Result<McpConfig, String> {
+    let path = config_path(app)?;
+    if !path.exists() {
+        return Ok(McpConfig::default());
+    }
+    let text = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&text).map_err(|e| format!("Invalid MCP config {}: {}", path.display(), e))
+}
+
+async fn connect_server(name: &str, config: &McpServerConfig, registry: &ToolRegistry) -> Result<Arc<McpClient>, String> {
+    let client = McpClient::spawn(name, config).await?;
+
+    let tools = client.list_all("tools/list", "tools").await?;
+    let mut tool_names = Vec::new();
+    for tool in tools {
+        let Some(remote_name) = tool.get("name").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        let parameters = tool
+            .get("inputSchema")
+            .and_then(|schema| serde_json::from_value(schema.clone()).ok())
+            .unwrap_or_default();
+        let mcp_tool = McpTool {
+            client: client.clone(),
+            name: tool_id(name, remote_name),
+            remote_name: remote_name.to_string(),
+            description: tool.get("description").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            parameters,
+        };
+        tool_names.push(mcp_tool.name.clone());
+        registry.register(Arc::new(mcp_tool));
+    }
+
+    // Resources are optional; servers without the capability return an error
+    let resources = client
+        .list_all("resources/list", "resources")
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|r| serde_json::from_value(r).ok())
+        .collect();
+
+    eprintln!("[MCP:{}] Connected with {} tools", name, tool_names.len());
+    *client.tools.lock().unwrap() = tool_names;
+    *client.resources.lock().unwrap() = resources;
+    Ok(client)
+}
+
+async fn stop_all(manager: &McpManager, registry: &ToolRegistry) {
+    let mut clients = manager.clients.lock().await;
+    for (_, client) in clients.drain() {
+        for tool in client.tools.lock().unwrap().iter() {
+            registry.unregister(tool);
+        }
+        client.shutdown().await;
+    }
+}
+
+// Start every enabled server from the config file; failures are logged per server
+pub async fn start_configured_servers(app: &AppHandle) -> Result<usize, String> {
+    let manager = app.state::<McpManager>();
+    let registry = app.state::<ToolRegistry>();
+    stop_all(&manager, &registry).await;
+
+    let config = load_config(app)?;
+    let mut clients = manager.clients.lock().await;
+    for (name, server) in config.mcp_servers.iter().filter(|(_, s)| !s.disabled) {
+        match connect_server(name, server, &registry).await {
+            Ok(client) => {
+                clients.insert(name.clone(), client);
+            }
+            Err(err) => eprintln!("[MCP:{}] {}", name, err),
+        }
+    }
+    Ok(clients.len())
+}
+
+#[tauri::command]
+pub async fn reload_mcp_servers(app: AppHandle) -> Result<usize, String> {
+    start_configured_servers(&app).await
+}
+
+#[tauri::command]
+pub async fn list_mcp_servers(manager: State<'_, McpManager>) -> Result<Vec<McpServerInfo>, String> {
+    let clients = manager.clients.lock().await;
+    Ok(clients
+        .values()
+        .map(|c| McpServerInfo {
+            name: c.name.clone(),
+            tools: c.tools.lock().unwrap().clone(),
+            resources: c.resources.lock().unwrap().clone(),
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn read_mcp_resource(manager: State<'_, McpManager>, server: String, uri: String) -> Result<String, String> {
+    let client = manager
+        .clients
+        .lock()
+        .await
+        .get(&server)
+        .cloned()
+        .ok_or_else(|| format!("MCP server '{}' is not connected", server))?;
+    let result = client.request("resources/read", json!({ "uri": uri })).await?;
+    let text = result
+        .get("contents")
+        .and_then(|c| c.as_array())
+        .map(|contents| {
+            contents
+                .iter()
+                .filter_map(|c| c.get("text").and_then(|t| t.as_str()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default();
+    Ok(text)
+}
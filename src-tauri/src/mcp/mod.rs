@@ -0,0 +1,39 @@
+// Model Context Protocol support (JSON-RPC 2.0 over stdio)
+use serde_json::{json, Value};
+
+pub mod client;
+pub mod server;
+
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+pub fn request_message(id: u64, method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+}
+
+pub fn notification_message(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+// Flatten a tools/call result's content blocks into plain text
+pub fn content_to_text(result: &Value) -> String {
+    result
+        .get("content")
+        .and_then(|c| c.as_array())
+        .map(|blocks| {
+            blocks
+                .iter()
+                .map(|block| match block.get("type").and_then(|t| t.as_str()) {
+                    Some("text") => block.get("text").and_then(|t| t.as_str()).unwrap_or("").to_string(),
+                    Some("resource") => block
+                        .pointer("/resource/text")
+                        .and_then(|t| t.as_str())
+                        .unwrap_or("")
+                        .to_string(),
+                    Some(other) => format!("[{} content omitted]", other),
+                    None => block.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,295 @@
+// MCP server mode: exports the native search, scrape and file tools to other MCP clients
+//
+//   openchat --mcp-server          serve over stdio
+//   openchat --mcp-sse [port]      serve over HTTP + SSE on 127.0.0.1 (default port 3921)
+//
+// Any local process can connect over SSE, so read_file is only exported there with
+// --mcp-allow-read and write_file with --mcp-allow-write. Only requests from this machine are
+// answered: Host must be localhost and a browser Origin must be localhost too, so web pages can't
+// reach the server through DNS rebinding.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::OsRng;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+use super::PROTOCOL_VERSION;
+use crate::blobs::to_hex;
+use crate::tools::ToolRegistry;
+
+const EXPORTED_TOOLS: &[&str] = &["web_search", "scrape_url", "read_file", "write_file"];
+// The file tools are added over SSE by their flags
+const SSE_EXPORTED_TOOLS: &[&str] = &["web_search", "scrape_url"];
+const DEFAULT_SSE_PORT: u16 = 3921;
+// Largest JSON-RPC message accepted over SSE
+const MAX_BODY_BYTES: usize = 4 * 1024 * 1024;
+const LOCAL_HOSTS: &[&str] = &["localhost", "127.0.0.1", "[::1]"];
+
+pub enum ServerMode {
+    Stdio,
+    Sse { port: u16, allow_read: bool, allow_write: bool },
+}
+
+// Check the command line for an MCP server flag
+pub fn mode_from_args() -> Option<ServerMode> {
+    let args: Vec<String> = std::env::args().collect();
+    if args.iter().any(|a| a == "--mcp-server") {
+        return Some(ServerMode::Stdio);
+    }
+    let pos = args.iter().position(|a| a == "--mcp-sse")?;
+    let port = args
+        .get(pos + 1)
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(DEFAULT_SSE_PORT);
+    let allow_read = args.iter().any(|a| a == "--mcp-allow-read");
+    let allow_write = args.iter().any(|a| a == "--mcp-allow-write");
+    Some(ServerMode::Sse { port, allow_read, allow_write })
+}
+
+pub fn run_blocking(mode: ServerMode) {
+    let runtime = tokio::runtime::Runtime::new().expect("failed to start tokio runtime");
+    let registry = Arc::new(ToolRegistry::with_builtin_tools());
+    let result = runtime.block_on(async move {
+        match mode {
+            ServerMode::Stdio => serve_stdio(registry).await,
+            ServerMode::Sse { port, allow_read, allow_write } => {
+                let mut tools = SSE_EXPORTED_TOOLS.to_vec();
+                if allow_read {
+                    tools.push("read_file");
+                }
+                if allow_write {
+                    tools.push("write_file");
+                }
+                serve_sse(registry, tools.into(), port).await
+            }
+        }
+    });
+    if let Err(err) = result {
+        eprintln!("[MCP Server] {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+// Handle one JSON-RPC message, with only `tools` callable; notifications produce no response
+async fn handle_message(registry: &ToolRegistry, tools: &[&str], message: Value) -> Option<Value> {
+    let id = message.get("id").cloned()?;
+    let method = message.get("method").and_then(|m| m.as_str()).unwrap_or("");
+    let params = message.get("params").cloned().unwrap_or_else(|| json!({}));
+
+    let result = match method {
+        "initialize" => json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "openchat", "version": env!("CARGO_PKG_VERSION") },
+        }),
+        "ping" => json!({}),
+        "tools/list" => {
+            let tools: Vec<Value> = registry
+                .definitions()
+                .into_iter()
+                .filter(|d| tools.contains(&d.name.as_str()))
+                .map(|d| json!({ "name": d.name, "description": d.description, "inputSchema": d.parameters }))
+                .collect();
+            json!({ "tools": tools })
+        }
+        "tools/call" => {
+            let name = params.get("name").and_then(|n| n.as_str()).unwrap_or("");
+            if !tools.contains(&name) {
+                return Some(error_response(id, -32602, &format!("Unknown tool: {}", name)));
+            }
+            let args = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+            eprintln!("[MCP Server] Calling tool {}", name);
+            let (text, is_error) = match registry.execute(name, args).await {
+                Ok(text) => (text, false),
+                Err(err) => (err, true),
+            };
+            json!({ "content": [{ "type": "text", "text": text }], "isError": is_error })
+        }
+        other => return Some(error_response(id, -32601, &format!("Method not found: {}", other))),
+    };
+
+    Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+}
+
+async fn handle_line(registry: &ToolRegistry, tools: &[&str], line: &str) -> Option<Value> {
+    match serde_json::from_str::<Value>(line) {
+        Ok(message) => handle_message(registry, tools, message).await,
+        Err(e) => Some(error_response(Value::Null, -32700, &format!("Parse error: {}", e))),
+    }
+}
+
+async fn serve_stdio(registry: Arc<ToolRegistry>) -> Result<(), String> {
+    eprintln!("[MCP Server] Serving over stdio");
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+
+    while let Some(line) = lines.next_line().await.map_err(|e| e.to_string())? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle_line(&registry, EXPORTED_TOOLS, &line).await {
+            let mut out = response.to_string();
+            out.push('\n');
+            stdout.write_all(out.as_bytes()).await.map_err(|e| e.to_string())?;
+            stdout.flush().await.map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+type Sessions = Arc<Mutex<HashMap<String, mpsc::UnboundedSender<Value>>>>;
+
+async fn serve_sse(registry: Arc<ToolRegistry>, tools: Arc<[&'static str]>, port: u16) -> Result<(), String> {
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .await
+        .map_err(|e| format!("Failed to bind port {}: {}", port, e))?;
+    eprintln!("[MCP Server] Serving SSE on http://127.0.0.1:{}/sse", port);
+
+    let sessions: Sessions = Arc::new(Mutex::new(HashMap::new()));
+    loop {
+        let (stream, _) = listener.accept().await.map_err(|e| e.to_string())?;
+        let registry = registry.clone();
+        let sessions = sessions.clone();
+        let tools = tools.clone();
+        tokio::spawn(async move {
+            if let Err(err) = handle_connection(stream, registry, &tools, port, sessions).await {
+                eprintln!("[MCP Server] Connection error: {}", err);
+            }
+        });
+    }
+}
+
+// A random session id, so other local processes can't guess one and post into a session
+fn session_token() -> String {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    to_hex(&bytes)
+}
+
+// Whether `host` (a Host header, "name[:port]") names this machine on our port
+fn local_host(host: &str, port: u16) -> bool {
+    let (name, host_port) = match host.rsplit_once(':') {
+        Some((name, p)) if !p.ends_with(']') => (name, p.parse().ok()),
+        _ => (host, None),
+    };
+    LOCAL_HOSTS.contains(&name.to_ascii_lowercase().as_str()) && host_port.unwrap_or(80) == port
+}
+
+// Whether a browser Origin is a page served from this machine
+fn local_origin(origin: &str) -> bool {
+    reqwest::Url::parse(origin)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_ascii_lowercase))
+        .is_some_and(|host| LOCAL_HOSTS.contains(&host.as_str()))
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    registry: Arc<ToolRegistry>,
+    tools: &[&str],
+    port: u16,
+    sessions: Sessions,
+) -> Result<(), String> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await.map_err(|e| e.to_string())?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("").to_string();
+    let target = parts.next().unwrap_or("").to_string();
+
+    let mut content_length = 0usize;
+    let (mut host, mut origin, mut content_type) = (None, None, None);
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).await.map_err(|e| e.to_string())?;
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let value = value.trim().to_string();
+            match name.to_ascii_lowercase().as_str() {
+                "content-length" => content_length = value.parse().unwrap_or(0),
+                "content-type" => content_type = Some(value),
+                "host" => host = Some(value),
+                "origin" => origin = Some(value),
+                _ => {}
+            }
+        }
+    }
+
+    if !host.as_deref().is_some_and(|host| local_host(host, port)) || !origin.as_deref().is_none_or(local_origin) {
+        eprintln!("[MCP Server] Refused a request from host {:?}, origin {:?}", host, origin);
+        return write_status(&mut reader.into_inner(), "403 Forbidden").await;
+    }
+
+    let (path, query) = target.split_once('?').unwrap_or((target.as_str(), ""));
+    match (method.as_str(), path) {
+        ("GET", "/sse") => {
+            let session_id = session_token();
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            sessions.lock().unwrap().insert(session_id.clone(), tx);
+
+            let mut stream = reader.into_inner();
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\nevent: endpoint\ndata: /messages?sessionId={}\n\n",
+                session_id
+            );
+            let mut result = stream.write_all(head.as_bytes()).await;
+            while result.is_ok() {
+                let Some(message) = rx.recv().await else { break };
+                result = stream
+                    .write_all(format!("event: message\ndata: {}\n\n", message).as_bytes())
+                    .await;
+            }
+            sessions.lock().unwrap().remove(&session_id);
+            Ok(())
+        }
+        ("POST", "/messages") => {
+            let json = content_type
+                .as_deref()
+                .and_then(|value| value.split(';').next())
+                .is_some_and(|media| media.trim().eq_ignore_ascii_case("application/json"));
+            if !json {
+                return write_status(&mut reader.into_inner(), "415 Unsupported Media Type").await;
+            }
+            if content_length > MAX_BODY_BYTES {
+                return write_status(&mut reader.into_inner(), "413 Payload Too Large").await;
+            }
+            let mut body = vec![0u8; content_length];
+            reader.read_exact(&mut body).await.map_err(|e| e.to_string())?;
+
+            let session_id = query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("sessionId="))
+                .unwrap_or("");
+            let sender = sessions.lock().unwrap().get(session_id).cloned();
+
+            let mut stream = reader.into_inner();
+            let Some(sender) = sender else {
+                return write_status(&mut stream, "404 Not Found").await;
+            };
+            write_status(&mut stream, "202 Accepted").await?;
+
+            if let Some(response) = handle_line(&registry, tools, &String::from_utf8_lossy(&body)).await {
+                let _ = sender.send(response);
+            }
+            Ok(())
+        }
+        _ => write_status(&mut reader.into_inner(), "404 Not Found").await,
+    }
+}
+
+async fn write_status(stream: &mut TcpStream, status: &str) -> Result<(), String> {
+    let response = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+    stream.write_all(response.as_bytes()).await.map_err(|e| e.to_string())
+}
@@ -0,0 +1,310 @@
+// Long-term memory: facts about the user recalled by embedding similarity
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use rusqlite::{params, OptionalExtension};
+use serde_json::Value;
+use tauri::State;
+
+use crate::db::Database;
+use crate::llm::{self, ProviderConfig};
+use crate::profiles::ProfileManager;
+use crate::tools::{string_arg, Tool, ToolParameters, ToolRegistry};
+use crate::vector;
+
+const DEFAULT_TOP_K: usize = 5;
+// Memories scoring below this are not considered relevant enough to inject
+const MIN_SIMILARITY: f32 = 0.3;
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Memory {
+    id: i64,
+    content: String,
+    created_at: String,
+    updated_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    score: Option<f32>,
+}
+
+#[derive(serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryOptions {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub top_k: Option<usize>,
+    // Embedding model used for recall; keyword matching is used when omitted
+    pub embedding: Option<ProviderConfig>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+struct StoredMemory {
+    memory: Memory,
+    embedding: Option<Vec<f32>>,
+    embedding_model: Option<String>,
+}
+
+fn insert_memory(db: &Database, content: &str, embedding: Option<(&[f32], &str)>) -> Result<Memory, String> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let id = db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO memories (content, embedding, embedding_model, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?4)",
+            params![
+                content,
+                embedding.map(|(v, _)| vector::to_blob(v)),
+                embedding.map(|(_, model)| model),
+                now
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    })?;
+    eprintln!("[Memory] Stored memory {}", id);
+    Ok(Memory {
+        id,
+        content: content.to_string(),
+        created_at: now.clone(),
+        updated_at: now,
+        score: None,
+    })
+}
+
+fn load_memories(db: &Database) -> Result<Vec<StoredMemory>, String> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, content, created_at, updated_at, embedding, embedding_model FROM memories ORDER BY updated_at DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let blob: Option<Vec<u8>> = row.get(4)?;
+            Ok(StoredMemory {
+                memory: Memory {
+                    id: row.get(0)?,
+                    content: row.get(1)?,
+                    created_at: row.get(2)?,
+                    updated_at: row.get(3)?,
+                    score: None,
+                },
+                embedding: blob.map(|b| vector::from_blob(&b)),
+                embedding_model: row.get(5)?,
+            })
+        })?;
+        rows.collect()
+    })
+}
+
+// Fallback relevance when no embedding model is configured: share of query words found in the memory
+fn keyword_score(query: &str, content: &str) -> f32 {
+    let content = content.to_lowercase();
+    let words: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.len() > 2)
+        .map(|w| w.to_lowercase())
+        .collect();
+    if words.is_empty() {
+        return 0.0;
+    }
+    let hits = words.iter().filter(|w| content.contains(w.as_str())).count();
+    hits as f32 / words.len() as f32
+}
+
+// Return the top-k memories most relevant to the query
+pub async fn recall(
+    db: &Database,
+    query: &str,
+    top_k: usize,
+    embedding: Option<&ProviderConfig>,
+) -> Result<Vec<Memory>, String> {
+    let mut stored = load_memories(db)?;
+    if stored.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut scored: Vec<Memory> = match embedding {
+        Some(config) => {
+            // Embed memories written without a vector (e.g. by the agent tool) or with another model
+            let missing: Vec<usize> = stored
+                .iter()
+                .enumerate()
+                .filter(|(_, m)| m.embedding.is_none() || m.embedding_model.as_deref() != Some(config.model.as_str()))
+                .map(|(i, _)| i)
+                .collect();
+            if !missing.is_empty() {
+                let texts: Vec<String> = missing.iter().map(|&i| stored[i].memory.content.clone()).collect();
+                let vectors = llm::embed(config, &texts).await?;
+                for (&i, v) in missing.iter().zip(vectors) {
+                    let id = stored[i].memory.id;
+                    let blob = vector::to_blob(&v);
+                    db.with_conn(|conn| {
+                        conn.execute(
+                            "UPDATE memories SET embedding = ?1, embedding_model = ?2 WHERE id = ?3",
+                            params![blob, config.model, id],
+                        )
+                    })?;
+                    stored[i].embedding = Some(v);
+                }
+            }
+
+            let query_vector = llm::embed(config, &[query.to_string()])
+                .await?
+                .pop()
+                .unwrap_or_default();
+            stored
+                .into_iter()
+                .map(|m| {
+                    let score = m
+                        .embedding
+                        .as_deref()
+                        .map(|v| vector::cosine_similarity(&query_vector, v))
+                        .unwrap_or(0.0);
+                    Memory { score: Some(score), ..m.memory }
+                })
+                .filter(|m| m.score.unwrap_or(0.0) >= MIN_SIMILARITY)
+                .collect()
+        }
+        None => stored
+            .into_iter()
+            .map(|m| {
+                let score = keyword_score(query, &m.memory.content);
+                Memory { score: Some(score), ..m.memory }
+            })
+            .filter(|m| m.score.unwrap_or(0.0) > 0.0)
+            .collect(),
+    };
+
+    scored.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    scored.truncate(top_k);
+    Ok(scored)
+}
+
+// System message listing the memories relevant to the latest user message, with the number recalled
+pub async fn memory_prompt(
+    db: &Database,
+    query: &str,
+    options: &MemoryOptions,
+) -> Result<Option<(String, usize)>, String> {
+    if !options.enabled || query.trim().is_empty() {
+        return Ok(None);
+    }
+    let memories = recall(db, query, options.top_k.unwrap_or(DEFAULT_TOP_K), options.embedding.as_ref()).await?;
+    if memories.is_empty() {
+        return Ok(None);
+    }
+    eprintln!("[Memory] Injecting {} memories", memories.len());
+    let lines: Vec<String> = memories.iter().map(|m| format!("- {}", m.content)).collect();
+    let prompt = format!(
+        "Things you remember about the user from earlier conversations:\n{}",
+        lines.join("\n")
+    );
+    Ok(Some((prompt, memories.len())))
+}
+
+// Agent tool for saving facts about the user
+pub struct RememberTool {
+    db: Database,
+}
+
+impl Tool for RememberTool {
+    fn name(&self) -> &str {
+        "remember"
+    }
+
+    fn description(&self) -> &str {
+        "Save a durable fact about the user (preferences, background, ongoing projects) to long-term memory"
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        ToolParameters::new().required("fact", "string", "The fact to remember, as a short standalone sentence")
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move {
+            let fact = string_arg(&args, "fact")?;
+            let memory = insert_memory(&self.db, fact.trim(), None)?;
+            Ok(format!("Remembered (memory #{})", memory.id))
+        })
+    }
+}
+
+pub fn register_tools(registry: &ToolRegistry, db: &Database) {
+    registry.register(Arc::new(RememberTool { db: db.clone() }));
+}
+
+#[tauri::command]
+pub async fn add_memory(
+    db: State<'_, Database>,
+    profiles: State<'_, ProfileManager>,
+    content: String,
+    mut embedding: Option<ProviderConfig>,
+) -> Result<Memory, String> {
+    if let Some(config) = embedding.as_mut() {
+        profiles.apply_credentials(config);
+    }
+    let content = content.trim();
+    if content.is_empty() {
+        return Err("Memory content cannot be empty".to_string());
+    }
+    match embedding {
+        Some(config) => {
+            let vector = llm::embed(&config, &[content.to_string()]).await?.pop().unwrap_or_default();
+            insert_memory(&db, content, Some((&vector, &config.model)))
+        }
+        None => insert_memory(&db, content, None),
+    }
+}
+
+#[tauri::command]
+pub fn list_memories(db: State<'_, Database>) -> Result<Vec<Memory>, String> {
+    Ok(load_memories(&db)?.into_iter().map(|m| m.memory).collect())
+}
+
+#[tauri::command]
+pub fn update_memory(db: State<'_, Database>, id: i64, content: String) -> Result<Memory, String> {
+    let now = chrono::Utc::now().to_rfc3339();
+    // Clear the embedding so it is recomputed on the next recall
+    let updated = db.with_conn(|conn| {
+        conn.execute(
+            "UPDATE memories SET content = ?1, embedding = NULL, embedding_model = NULL, updated_at = ?2 WHERE id = ?3",
+            params![content.trim(), now, id],
+        )?;
+        conn.query_row(
+            "SELECT id, content, created_at, updated_at FROM memories WHERE id = ?1",
+            params![id],
+            |row| {
+                Ok(Memory {
+                    id: row.get(0)?,
+                    content: row.get(1)?,
+                    created_at: row.get(2)?,
+                    updated_at: row.get(3)?,
+                    score: None,
+                })
+            },
+        )
+        .optional()
+    })?;
+    updated.ok_or_else(|| format!("Memory {} not found", id))
+}
+
+#[tauri::command]
+pub fn delete_memory(db: State<'_, Database>, id: i64) -> Result<(), String> {
+    let deleted = db.with_conn(|conn| conn.execute("DELETE FROM memories WHERE id = ?1", params![id]))?;
+    if deleted == 0 {
+        return Err(format!("Memory {} not found", id));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn recall_memories(
+    db: State<'_, Database>,
+    profiles: State<'_, ProfileManager>,
+    query: String,
+    top_k: Option<usize>,
+    mut embedding: Option<ProviderConfig>,
+) -> Result<Vec<Memory>, String> {
+    if let Some(config) = embedding.as_mut() {
+        profiles.apply_credentials(config);
+    }
+    recall(&db, &query, top_k.unwrap_or(DEFAULT_TOP_K), embedding.as_ref()).await
+}
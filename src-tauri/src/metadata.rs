@@ -0,0 +1,368 @@
+// Structured metadata of web pages: OpenGraph (og:) and Twitter Card (twitter:) meta tags, and
+// schema.org JSON-LD for articles, products and recipes, so scraped pages carry their publisher's
+// own title, author, dates, prices and the like.
+use kuchikiki::NodeRef;
+use serde_json::Value;
+
+use crate::{clean_text, html};
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default)]
+pub struct OpenGraph {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    // og:type, e.g. article or product
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub site_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default)]
+pub struct TwitterCard {
+    // summary, summary_large_image, player or app
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub card: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    // @handle of the site and of the author
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub site: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creator: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct Rating {
+    pub value: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub best: Option<f64>,
+}
+
+// schema.org items found in JSON-LD; durations stay ISO 8601 (PT1H30M)
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum StructuredData {
+    Article {
+        // The schema.org type, e.g. NewsArticle or BlogPosting
+        schema_type: String,
+        headline: Option<String>,
+        description: Option<String>,
+        authors: Vec<String>,
+        publisher: Option<String>,
+        date_published: Option<String>,
+        date_modified: Option<String>,
+        image: Option<String>,
+        section: Option<String>,
+        keywords: Vec<String>,
+    },
+    Product {
+        name: Option<String>,
+        description: Option<String>,
+        brand: Option<String>,
+        sku: Option<String>,
+        image: Option<String>,
+        price: Option<String>,
+        currency: Option<String>,
+        // e.g. InStock, OutOfStock
+        availability: Option<String>,
+        rating: Option<Rating>,
+    },
+    Recipe {
+        name: Option<String>,
+        description: Option<String>,
+        authors: Vec<String>,
+        image: Option<String>,
+        prep_time: Option<String>,
+        cook_time: Option<String>,
+        total_time: Option<String>,
+        // e.g. "4 servings"
+        recipe_yield: Option<String>,
+        category: Option<String>,
+        cuisine: Option<String>,
+        calories: Option<String>,
+        ingredients: Vec<String>,
+        instructions: Vec<String>,
+        rating: Option<Rating>,
+    },
+}
+
+#[derive(Default)]
+pub struct PageMetadata {
+    pub open_graph: Option<OpenGraph>,
+    pub twitter_card: Option<TwitterCard>,
+    pub structured_data: Vec<StructuredData>,
+}
+
+impl PageMetadata {
+    // Author from JSON-LD, then OpenGraph
+    pub fn author(&self) -> Option<String> {
+        let from_schema = self.structured_data.iter().find_map(|item| match item {
+            StructuredData::Article { authors, .. } | StructuredData::Recipe { authors, .. } if !authors.is_empty() => {
+                Some(authors.join(", "))
+            }
+            _ => None,
+        });
+        from_schema.or_else(|| self.open_graph.as_ref()?.author.clone())
+    }
+
+    // Publication date from JSON-LD, then OpenGraph
+    pub fn published_date(&self) -> Option<String> {
+        let from_schema = self.structured_data.iter().find_map(|item| match item {
+            StructuredData::Article { date_published, .. } => date_published.clone(),
+            _ => None,
+        });
+        from_schema.or_else(|| self.open_graph.as_ref()?.published_time.clone())
+    }
+}
+
+// Content of the first <meta> whose property or name is `key`
+pub fn meta(document: &NodeRef, key: &str) -> Option<String> {
+    let Ok(metas) = document.select("meta[content]") else { return None };
+    metas.into_iter().find_map(|meta| {
+        let attributes = meta.attributes.borrow();
+        let name = attributes.get("property").or_else(|| attributes.get("name"))?;
+        if !name.eq_ignore_ascii_case(key) {
+            return None;
+        }
+        Some(clean_text(attributes.get("content")?)).filter(|c| !c.is_empty())
+    })
+}
+
+fn open_graph(document: &NodeRef) -> Option<OpenGraph> {
+    let og = |key: &str| meta(document, &format!("og:{}", key));
+    let graph = OpenGraph {
+        title: og("title"),
+        description: og("description"),
+        kind: og("type"),
+        url: og("url"),
+        image: og("image").or_else(|| og("image:url")),
+        site_name: og("site_name"),
+        locale: og("locale"),
+        published_time: meta(document, "article:published_time"),
+        modified_time: meta(document, "article:modified_time").or_else(|| og("updated_time")),
+        author: meta(document, "article:author"),
+    };
+    let empty = graph.title.is_none() && graph.kind.is_none() && graph.url.is_none() && graph.image.is_none();
+    (!empty).then_some(graph)
+}
+
+fn twitter_card(document: &NodeRef) -> Option<TwitterCard> {
+    let twitter = |key: &str| meta(document, &format!("twitter:{}", key));
+    let card = TwitterCard {
+        card: twitter("card"),
+        title: twitter("title"),
+        description: twitter("description"),
+        image: twitter("image").or_else(|| twitter("image:src")),
+        site: twitter("site"),
+        creator: twitter("creator"),
+    };
+    let empty = card.card.is_none() && card.title.is_none() && card.image.is_none() && card.site.is_none();
+    (!empty).then_some(card)
+}
+
+// Plain text of a value: strings as they are, numbers printed, objects by their name or text
+fn text(value: Option<&Value>) -> Option<String> {
+    let text = match value? {
+        // Entities and tags turn up in the strings of many sites
+        Value::String(s) if s.contains(['<', '&']) => html::parse(s).text_contents(),
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Array(items) => return items.iter().find_map(|item| text(Some(item))),
+        Value::Object(object) => {
+            return ["name", "text", "@value", "url"].iter().find_map(|key| text(object.get(*key)));
+        }
+        _ => return None,
+    };
+    Some(clean_text(&text)).filter(|t| !t.is_empty())
+}
+
+fn texts(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::Array(items)) => items.iter().filter_map(|item| text(Some(item))).collect(),
+        // Keywords often come as one comma-separated string
+        Some(Value::String(s)) if s.contains(',') => {
+            s.split(',').map(|k| k.trim().to_string()).filter(|k| !k.is_empty()).collect()
+        }
+        value => text(value).into_iter().collect(),
+    }
+}
+
+// Images are a URL, an ImageObject or a list of either
+fn image(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::Array(items) => items.iter().find_map(|item| image(Some(item))),
+        Value::Object(object) => text(object.get("url").or_else(|| object.get("contentUrl"))),
+        value => text(Some(value)),
+    }
+}
+
+fn number(value: Option<&Value>) -> Option<f64> {
+    match value? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn rating(value: Option<&Value>) -> Option<Rating> {
+    let rating = value?;
+    Some(Rating {
+        value: number(rating.get("ratingValue"))?,
+        count: number(rating.get("ratingCount").or_else(|| rating.get("reviewCount"))).map(|n| n as u64),
+        best: number(rating.get("bestRating")),
+    })
+}
+
+// Steps from a string, a list of strings, HowToSteps, or HowToSections holding steps
+fn instructions(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::Array(items)) => items.iter().flat_map(|item| instructions(Some(item))).collect(),
+        Some(Value::Object(object)) => match object.get("itemListElement") {
+            Some(steps) => instructions(Some(steps)),
+            None => text(object.get("text").or_else(|| object.get("name"))).into_iter().collect(),
+        },
+        Some(Value::String(s)) => {
+            let text = html::to_text(&html::parse(s));
+            text.lines().map(str::trim).filter(|l| !l.is_empty()).map(str::to_string).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn types(item: &Value) -> Vec<String> {
+    match item.get("@type") {
+        Some(Value::String(kind)) => vec![kind.clone()],
+        Some(Value::Array(kinds)) => kinds.iter().filter_map(|k| k.as_str()).map(str::to_string).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn is_article(kind: &str) -> bool {
+    kind.ends_with("Article") || matches!(kind, "BlogPosting" | "LiveBlogPosting" | "Report" | "SocialMediaPosting")
+}
+
+fn structured_item(item: &Value) -> Option<StructuredData> {
+    let kinds = types(item);
+    let get = |key: &str| item.get(key);
+    if let Some(kind) = kinds.iter().find(|k| is_article(k)) {
+        return Some(StructuredData::Article {
+            schema_type: kind.clone(),
+            headline: text(get("headline")).or_else(|| text(get("name"))),
+            description: text(get("description")),
+            authors: texts(get("author")),
+            publisher: text(get("publisher")),
+            date_published: text(get("datePublished")),
+            date_modified: text(get("dateModified")),
+            image: image(get("image")),
+            section: text(get("articleSection")),
+            keywords: texts(get("keywords")),
+        });
+    }
+    if kinds.iter().any(|k| k == "Product" || k == "ProductGroup") {
+        // One offer, the first of a list, or an AggregateOffer with a price range
+        let offer = match get("offers") {
+            Some(Value::Array(offers)) => offers.first(),
+            offer => offer,
+        };
+        let offer_field = |key: &str| offer.and_then(|o| o.get(key));
+        let price = text(offer_field("price")).or_else(|| {
+            let (low, high) = (text(offer_field("lowPrice"))?, text(offer_field("highPrice")));
+            Some(high.map_or(low.clone(), |high| format!("{}-{}", low, high)))
+        });
+        return Some(StructuredData::Product {
+            name: text(get("name")),
+            description: text(get("description")),
+            brand: text(get("brand")),
+            sku: text(get("sku")).or_else(|| text(get("gtin13"))),
+            image: image(get("image")),
+            price,
+            currency: text(offer_field("priceCurrency")),
+            availability: text(offer_field("availability")).map(|a| a.rsplit('/').next().unwrap_or(&a).to_string()),
+            rating: rating(get("aggregateRating")),
+        });
+    }
+    if kinds.iter().any(|k| k == "Recipe") {
+        return Some(StructuredData::Recipe {
+            name: text(get("name")),
+            description: text(get("description")),
+            authors: texts(get("author")),
+            image: image(get("image")),
+            prep_time: text(get("prepTime")),
+            cook_time: text(get("cookTime")),
+            total_time: text(get("totalTime")),
+            recipe_yield: text(get("recipeYield")),
+            category: text(get("recipeCategory")),
+            cuisine: text(get("recipeCuisine")),
+            calories: get("nutrition").and_then(|n| text(n.get("calories"))),
+            ingredients: texts(get("recipeIngredient").or_else(|| get("ingredients"))),
+            instructions: instructions(get("recipeInstructions")),
+            rating: rating(get("aggregateRating")),
+        });
+    }
+    None
+}
+
+// Items of a JSON-LD document: an object, a list, or a @graph of objects; a page's main item is
+// often nested as the mainEntity of a WebPage
+fn collect_items<'a>(value: &'a Value, items: &mut Vec<&'a Value>) {
+    match value {
+        Value::Array(values) => values.iter().for_each(|v| collect_items(v, items)),
+        Value::Object(object) => {
+            if let Some(graph) = object.get("@graph") {
+                collect_items(graph, items);
+            }
+            if let Some(main) = object.get("mainEntity") {
+                collect_items(main, items);
+            }
+            if object.contains_key("@type") {
+                items.push(value);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn structured_data(document: &NodeRef) -> Vec<StructuredData> {
+    let Ok(scripts) = document.select("script[type='application/ld+json']") else { return Vec::new() };
+    let mut found = Vec::new();
+    for script in scripts {
+        let source = script.text_contents();
+        // Some CMSs wrap the JSON in CDATA markers or leave control characters in strings
+        let source = source.trim().trim_start_matches("<![CDATA[").trim_end_matches("]]>");
+        let cleaned: String = source.chars().map(|c| if c.is_control() { ' ' } else { c }).collect();
+        let Ok(json) = serde_json::from_str::<Value>(&cleaned) else { continue };
+        let mut items = Vec::new();
+        collect_items(&json, &mut items);
+        found.extend(items.into_iter().filter_map(structured_item));
+    }
+    found
+}
+
+pub fn extract(html: &str) -> PageMetadata {
+    let document = html::parse(html);
+    PageMetadata {
+        open_graph: open_graph(&document),
+        twitter_card: twitter_card(&document),
+        structured_data: structured_data(&document),
+    }
+}
@@ -0,0 +1,5 @@
+-- Pinned messages (per conversation) and favorites (across all conversations)
+ALTER TABLE messages ADD COLUMN pinned_at TEXT;
+ALTER TABLE messages ADD COLUMN favorited_at TEXT;
+CREATE INDEX IF NOT EXISTS idx_messages_pinned ON messages(conversation_id, pinned_at) WHERE pinned_at IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_messages_favorited ON messages(favorited_at) WHERE favorited_at IS NOT NULL;
@@ -0,0 +1,6 @@
+-- Deleted conversations stay in place until purged; a row here hides them from listings
+CREATE TABLE IF NOT EXISTS trash (
+    conversation_id TEXT PRIMARY KEY REFERENCES conversations(id) ON DELETE CASCADE,
+    deleted_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_trash_deleted ON trash(deleted_at);
@@ -0,0 +1,18 @@
+-- Named collections of embeddings with optional text and JSON metadata, queried by similarity
+CREATE TABLE IF NOT EXISTS vector_collections (
+    name TEXT PRIMARY KEY,
+    dimensions INTEGER NOT NULL,
+    metric TEXT NOT NULL DEFAULT 'cosine',
+    embedding_model TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS vector_items (
+    collection TEXT NOT NULL REFERENCES vector_collections(name) ON DELETE CASCADE,
+    id TEXT NOT NULL,
+    embedding BLOB NOT NULL,
+    content TEXT,
+    metadata TEXT,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (collection, id)
+);
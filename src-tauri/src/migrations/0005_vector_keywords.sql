@@ -0,0 +1,41 @@
+-- Full-text index over vector item content for keyword (BM25) and hybrid queries.
+-- Underscores are part of tokens so identifiers like MAX_RETRIES match whole.
+CREATE VIRTUAL TABLE IF NOT EXISTS vector_fts USING fts5(
+    content,
+    tokenize = "unicode61 remove_diacritics 2 tokenchars '_'"
+);
+
+-- vector_items has no stable integer key (VACUUM may renumber its rowids), so the index rows are
+-- tied to items through this table
+CREATE TABLE IF NOT EXISTS vector_fts_keys (
+    key INTEGER PRIMARY KEY,
+    collection TEXT NOT NULL,
+    id TEXT NOT NULL,
+    UNIQUE (collection, id)
+);
+
+-- INSERT OR REPLACE doesn't fire delete triggers, so inserts clear any earlier entry first
+CREATE TRIGGER IF NOT EXISTS vector_items_fts_insert AFTER INSERT ON vector_items BEGIN
+    DELETE FROM vector_fts WHERE rowid IN (SELECT key FROM vector_fts_keys WHERE collection = new.collection AND id = new.id);
+    DELETE FROM vector_fts_keys WHERE collection = new.collection AND id = new.id;
+    INSERT INTO vector_fts_keys (collection, id) SELECT new.collection, new.id WHERE new.content IS NOT NULL;
+    INSERT INTO vector_fts (rowid, content) SELECT last_insert_rowid(), new.content WHERE new.content IS NOT NULL;
+END;
+
+CREATE TRIGGER IF NOT EXISTS vector_items_fts_update AFTER UPDATE OF content ON vector_items BEGIN
+    DELETE FROM vector_fts WHERE rowid IN (SELECT key FROM vector_fts_keys WHERE collection = old.collection AND id = old.id);
+    DELETE FROM vector_fts_keys WHERE collection = old.collection AND id = old.id;
+    INSERT INTO vector_fts_keys (collection, id) SELECT new.collection, new.id WHERE new.content IS NOT NULL;
+    INSERT INTO vector_fts (rowid, content) SELECT last_insert_rowid(), new.content WHERE new.content IS NOT NULL;
+END;
+
+CREATE TRIGGER IF NOT EXISTS vector_items_fts_delete AFTER DELETE ON vector_items BEGIN
+    DELETE FROM vector_fts WHERE rowid IN (SELECT key FROM vector_fts_keys WHERE collection = old.collection AND id = old.id);
+    DELETE FROM vector_fts_keys WHERE collection = old.collection AND id = old.id;
+END;
+
+-- Index what is already stored
+INSERT OR IGNORE INTO vector_fts_keys (collection, id)
+    SELECT collection, id FROM vector_items WHERE content IS NOT NULL;
+INSERT INTO vector_fts (rowid, content)
+    SELECT k.key, i.content FROM vector_fts_keys k JOIN vector_items i ON i.collection = k.collection AND i.id = k.id;
@@ -0,0 +1,25 @@
+-- Folders kept in sync with a vector collection, and the state of every file indexed from them
+CREATE TABLE IF NOT EXISTS watched_sources (
+    id TEXT PRIMARY KEY,
+    path TEXT NOT NULL,
+    collection TEXT NOT NULL,
+    -- Ingestion options as JSON, without API keys
+    options TEXT NOT NULL,
+    recursive INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL,
+    last_scan TEXT,
+    last_error TEXT,
+    UNIQUE (path, collection)
+);
+
+-- Files that failed keep their modification time and size too, so they are retried only once changed
+CREATE TABLE IF NOT EXISTS watched_files (
+    source_id TEXT NOT NULL REFERENCES watched_sources(id) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    modified_ms INTEGER NOT NULL,
+    size INTEGER NOT NULL,
+    chunks INTEGER NOT NULL DEFAULT 0,
+    indexed_at TEXT NOT NULL,
+    error TEXT,
+    PRIMARY KEY (source_id, path)
+);
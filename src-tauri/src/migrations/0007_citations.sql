@@ -0,0 +1,2 @@
+-- Retrieved excerpts a generation was given, as a JSON array of citations
+ALTER TABLE generations ADD COLUMN citations TEXT;
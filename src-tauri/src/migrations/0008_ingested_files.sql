@@ -0,0 +1,12 @@
+-- Files ingested into each collection, so ingesting them again only re-processes the changed ones
+CREATE TABLE IF NOT EXISTS ingested_files (
+    collection TEXT NOT NULL REFERENCES vector_collections(name) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    modified_ms INTEGER NOT NULL,
+    size INTEGER NOT NULL,
+    -- SHA-256 of the file's bytes
+    hash TEXT NOT NULL,
+    chunks INTEGER NOT NULL,
+    ingested_at TEXT NOT NULL,
+    PRIMARY KEY (collection, path)
+);
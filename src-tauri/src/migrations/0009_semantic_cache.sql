@@ -0,0 +1,16 @@
+-- Answers reused for questions that mean the same as one already answered in the same context
+CREATE TABLE IF NOT EXISTS semantic_cache (
+    id INTEGER PRIMARY KEY,
+    -- Hash of the model, sampling options and every message before the question
+    context_key TEXT NOT NULL,
+    embedding_model TEXT NOT NULL,
+    embedding BLOB NOT NULL,
+    query TEXT NOT NULL,
+    response TEXT NOT NULL,
+    generation_id TEXT,
+    created_at TEXT NOT NULL,
+    hits INTEGER NOT NULL DEFAULT 0,
+    last_hit_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_semantic_cache_context ON semantic_cache(context_key, embedding_model);
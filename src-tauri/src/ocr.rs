@@ -0,0 +1,205 @@
+// OCR with the Tesseract command-line tool, for scanned PDF pages and image files. Images go to
+// `tesseract stdin stdout tsv` so nothing touches the disk, and the per-word TSV output gives the
+// text layout and a confidence score. Tesseract and its language packs are installed separately.
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use tauri::AppHandle;
+
+use crate::settings;
+
+// Image files ingested through OCR
+pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "tif", "tiff", "bmp", "gif", "webp", "jp2"];
+const DEFAULT_BINARY: &str = "tesseract";
+
+#[derive(Clone)]
+pub struct OcrConfig {
+    binary: String,
+    // Tesseract language codes joined with '+', e.g. "eng+deu"
+    languages: String,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OcrResult {
+    pub text: String,
+    // Mean word confidence, 0-100; absent when no words were found
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    pub words: usize,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OcrStatus {
+    enabled: bool,
+    available: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    // Installed language packs
+    languages: Vec<String>,
+    default_languages: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+pub fn valid_languages(languages: &str) -> bool {
+    !languages.is_empty()
+        && languages
+            .split('+')
+            .all(|code| !code.is_empty() && code.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+}
+
+// OCR settings for this request, or None when OCR is turned off. `languages` overrides the
+// configured default.
+pub fn config(app: &AppHandle, languages: Option<&str>) -> Result<Option<OcrConfig>, String> {
+    let settings = settings::load(app);
+    if !settings.ocr_enabled {
+        return Ok(None);
+    }
+    let languages = languages.map(str::trim).filter(|l| !l.is_empty()).unwrap_or(&settings.ocr_languages);
+    if !valid_languages(languages) {
+        return Err(format!("Invalid OCR languages '{}'; use codes like eng or eng+deu", languages));
+    }
+    Ok(Some(OcrConfig {
+        binary: settings
+            .tesseract_path
+            .filter(|p| !p.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_BINARY.to_string()),
+        languages: languages.to_string(),
+    }))
+}
+
+pub fn is_image(path: &Path) -> bool {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.as_str()))
+}
+
+fn run(binary: &str, args: &[&str], input: Option<&[u8]>) -> Result<Vec<u8>, String> {
+    let mut child = Command::new(binary)
+        .args(args)
+        .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {} (is Tesseract installed?): {}", binary, e))?;
+    // Written from another thread so a full stdout pipe can't deadlock the write
+    let writer = match (input, child.stdin.take()) {
+        (Some(input), Some(mut stdin)) => {
+            let input = input.to_vec();
+            Some(std::thread::spawn(move || stdin.write_all(&input)))
+        }
+        _ => None,
+    };
+    let output = child.wait_with_output().map_err(|e| format!("Tesseract failed: {}", e))?;
+    if let Some(writer) = writer {
+        // A broken pipe here means Tesseract rejected the image; its stderr says why
+        let _ = writer.join();
+    }
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = stderr.lines().rev().find(|l| !l.trim().is_empty()).unwrap_or("no output");
+        return Err(format!("Tesseract failed: {}", message.trim()));
+    }
+    Ok(output.stdout)
+}
+
+// Text from Tesseract's TSV output: words joined into lines, blank lines between paragraphs
+fn parse_tsv(tsv: &str) -> OcrResult {
+    let mut text = String::new();
+    let mut confidence_sum = 0.0;
+    let mut words = 0;
+    let mut last: Option<(&str, &str, &str)> = None;
+    // Columns: level page block paragraph line word left top width height conf text
+    for row in tsv.lines().skip(1) {
+        let columns: Vec<&str> = row.splitn(12, '\t').collect();
+        if columns.len() < 12 || columns[0] != "5" {
+            continue;
+        }
+        let word = columns[11].trim();
+        let confidence: f32 = columns[10].parse().unwrap_or(-1.0);
+        if word.is_empty() || confidence < 0.0 {
+            continue;
+        }
+        let position = (columns[2], columns[3], columns[4]);
+        match last {
+            Some((block, paragraph, _)) if (block, paragraph) != (position.0, position.1) => text.push_str("\n\n"),
+            Some(previous) if previous != position => text.push('\n'),
+            Some(_) => text.push(' '),
+            None => {}
+        }
+        text.push_str(word);
+        last = Some(position);
+        confidence_sum += confidence;
+        words += 1;
+    }
+    OcrResult {
+        text,
+        confidence: (words > 0).then(|| confidence_sum / words as f32),
+        words,
+    }
+}
+
+// Recognize text in an encoded image (PNG, JPEG, TIFF, PNM, ...)
+pub fn recognize(image: &[u8], config: &OcrConfig) -> Result<OcrResult, String> {
+    let output = run(&config.binary, &["stdin", "stdout", "-l", &config.languages, "tsv"], Some(image))?;
+    Ok(parse_tsv(&String::from_utf8_lossy(&output)))
+}
+
+pub fn load_image(path: &Path, config: &OcrConfig) -> Result<OcrResult, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    recognize(&bytes, config)
+}
+
+#[tauri::command]
+pub async fn ocr_image(app: AppHandle, path: String, languages: Option<String>) -> Result<OcrResult, String> {
+    let config = config(&app, languages.as_deref())?.ok_or("OCR is turned off in settings")?;
+    tokio::task::spawn_blocking(move || load_image(Path::new(&path), &config))
+        .await
+        .map_err(|e| format!("OCR task failed: {}", e))?
+}
+
+// Whether Tesseract can be run, and which languages it has
+#[tauri::command]
+pub async fn get_ocr_status(app: AppHandle) -> Result<OcrStatus, String> {
+    let settings = settings::load(&app);
+    let binary = settings
+        .tesseract_path
+        .clone()
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_BINARY.to_string());
+    let probe = tokio::task::spawn_blocking(move || {
+        let version = run(&binary, &["--version"], None)?;
+        let languages = run(&binary, &["--list-langs"], None)?;
+        Ok::<_, String>((version, languages))
+    })
+    .await
+    .map_err(|e| format!("OCR task failed: {}", e))?;
+
+    let mut status = OcrStatus {
+        enabled: settings.ocr_enabled,
+        available: false,
+        version: None,
+        languages: Vec::new(),
+        default_languages: settings.ocr_languages,
+        error: None,
+    };
+    match probe {
+        Ok((version, languages)) => {
+            status.available = true;
+            status.version = String::from_utf8_lossy(&version).lines().next().map(|l| l.trim().to_string());
+            // The first line is a header: List of available languages in "..." (N):
+            status.languages = String::from_utf8_lossy(&languages)
+                .lines()
+                .skip(1)
+                .map(str::trim)
+                .filter(|l| !l.is_empty() && *l != "osd")
+                .map(str::to_string)
+                .collect();
+        }
+        Err(err) => status.error = Some(err),
+    }
+    Ok(status)
+}
@@ -0,0 +1,496 @@
+// Text extraction for Office Open XML documents (DOCX, XLSX, PPTX).
+//
+// The output keeps the structure that matters for reading and retrieval: Word headings start new
+// sections, tables become tab-separated rows, every sheet and slide is its own section and slide
+// notes are kept next to their slide. The format is detected from the archive contents, so a
+// renamed file still extracts.
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Seek};
+use std::path::Path;
+
+use zip::ZipArchive;
+
+use crate::xml::{attr, local_name, read_part, relationships, walk, Node};
+
+#[derive(serde::Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum OfficeFormat {
+    Docx,
+    Xlsx,
+    Pptx,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OfficeSection {
+    // Heading, sheet name or slide title; the text before a document's first heading has none
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    // Heading level, 1 for sheets and slides
+    pub level: usize,
+    pub text: String,
+    // Speaker notes of a slide
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OfficeDocument {
+    pub format: OfficeFormat,
+    pub sections: Vec<OfficeSection>,
+}
+
+impl OfficeDocument {
+    // The whole document as Markdown-style text: titles as headings, then the text and notes
+    pub fn text(&self) -> String {
+        let mut parts = Vec::new();
+        for section in &self.sections {
+            if let Some(title) = &section.title {
+                parts.push(format!("{} {}", "#".repeat(section.level.clamp(1, 6)), title));
+            }
+            if !section.text.is_empty() {
+                parts.push(section.text.clone());
+            }
+            if let Some(notes) = &section.notes {
+                parts.push(format!("Notes:\n{}", notes));
+            }
+        }
+        parts.join("\n\n")
+    }
+}
+
+fn clean_cell(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn tsv(rows: &[Vec<String>]) -> String {
+    rows.iter().map(|row| row.join("\t")).collect::<Vec<_>>().join("\n")
+}
+
+enum Block {
+    Paragraph { text: String, heading: Option<usize> },
+    Table(String),
+}
+
+// Paragraphs and tables of WordprocessingML or DrawingML text, which share element names
+// (p, t, tab, br, tbl, tr, tc). Nested tables are flattened into the cell that holds them.
+#[derive(Default)]
+struct Flow {
+    blocks: Vec<Block>,
+    paragraph: String,
+    heading: Option<usize>,
+    in_text: bool,
+    table_depth: usize,
+    rows: Vec<Vec<String>>,
+    row: Vec<String>,
+    cell: Vec<String>,
+}
+
+impl Flow {
+    fn visit(&mut self, node: &Node, heading_of: impl Fn(&str) -> Option<usize>) {
+        match node {
+            Node::Open(e) => match local_name(e) {
+                b"p" => {
+                    self.paragraph.clear();
+                    self.heading = None;
+                }
+                b"pStyle" => self.heading = attr(e, "w:val").and_then(|style| heading_of(&style)).or(self.heading),
+                b"outlineLvl" => {
+                    if let Some(level) = attr(e, "w:val").and_then(|v| v.parse::<usize>().ok()).filter(|l| *l < 9) {
+                        self.heading = Some(level + 1);
+                    }
+                }
+                b"t" => self.in_text = true,
+                b"tab" if self.table_depth == 0 => self.paragraph.push('\t'),
+                b"tab" => self.paragraph.push(' '),
+                b"br" | b"cr" => self.paragraph.push('\n'),
+                b"tbl" => {
+                    if self.table_depth == 0 {
+                        self.rows.clear();
+                    }
+                    self.table_depth += 1;
+                }
+                b"tr" if self.table_depth == 1 => self.row.clear(),
+                b"tc" if self.table_depth == 1 => self.cell.clear(),
+                _ => {}
+            },
+            Node::Text(text) if self.in_text => self.paragraph.push_str(text),
+            Node::Text(_) => {}
+            Node::Close(name) => match *name {
+                b"t" => self.in_text = false,
+                b"p" => {
+                    let text = self.paragraph.trim().to_string();
+                    if text.is_empty() {
+                        return;
+                    }
+                    if self.table_depth > 0 {
+                        self.cell.push(text);
+                    } else {
+                        self.blocks.push(Block::Paragraph {
+                            text,
+                            heading: self.heading,
+                        });
+                    }
+                }
+                b"tc" if self.table_depth == 1 => self.row.push(clean_cell(&self.cell.join(" "))),
+                b"tr" if self.table_depth == 1 && self.row.iter().any(|c| !c.is_empty()) => {
+                    self.rows.push(std::mem::take(&mut self.row));
+                }
+                b"tbl" => {
+                    self.table_depth = self.table_depth.saturating_sub(1);
+                    if self.table_depth == 0 && !self.rows.is_empty() {
+                        self.blocks.push(Block::Table(tsv(&self.rows)));
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+
+    fn text(&self) -> String {
+        self.blocks
+            .iter()
+            .map(|block| match block {
+                Block::Paragraph { text, .. } | Block::Table(text) => text.as_str(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+// "heading 1".."heading 9" and "Title", by style name or by the English style ids
+fn builtin_heading(name: &str) -> Option<usize> {
+    let lower = name.to_lowercase().replace(' ', "");
+    if lower == "title" {
+        return Some(1);
+    }
+    lower
+        .strip_prefix("heading")
+        .and_then(|n| n.parse::<usize>().ok())
+        .filter(|n| (1..=9).contains(n))
+}
+
+// Heading level of each paragraph style id; localized documents use ids like "berschrift1",
+// so the style's name and outline level are what count
+fn heading_styles(xml: &str) -> Result<HashMap<String, usize>, String> {
+    let mut styles = HashMap::new();
+    let mut current: Option<(String, Option<usize>)> = None;
+    walk(xml, |node| match node {
+        Node::Open(e) => match local_name(e) {
+            b"style" => current = attr(e, "w:styleId").map(|id| (id, None)),
+            b"name" => {
+                if let (Some((_, level)), Some(name)) = (current.as_mut(), attr(e, "w:val")) {
+                    *level = level.or(builtin_heading(&name));
+                }
+            }
+            b"outlineLvl" => {
+                if let (Some((_, level)), Some(value)) = (current.as_mut(), attr(e, "w:val")) {
+                    if let Some(outline) = value.parse::<usize>().ok().filter(|l| *l < 9) {
+                        *level = Some(outline + 1);
+                    }
+                }
+            }
+            _ => {}
+        },
+        Node::Close(b"style") => {
+            if let Some((id, Some(level))) = current.take() {
+                styles.insert(id, level);
+            }
+        }
+        _ => {}
+    })?;
+    Ok(styles)
+}
+
+fn extract_docx<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<Vec<OfficeSection>, String> {
+    let document = read_part(archive, "word/document.xml")?.ok_or("word/document.xml is missing")?;
+    let styles = match read_part(archive, "word/styles.xml")? {
+        Some(xml) => heading_styles(&xml)?,
+        None => HashMap::new(),
+    };
+    let mut flow = Flow::default();
+    walk(&document, |node| {
+        flow.visit(&node, |style| styles.get(style).copied().or_else(|| builtin_heading(style)))
+    })?;
+
+    // Every heading starts a section holding the blocks up to the next heading
+    let mut sections = Vec::new();
+    let mut section = OfficeSection {
+        title: None,
+        level: 1,
+        text: String::new(),
+        notes: None,
+    };
+    for block in flow.blocks {
+        let (text, heading) = match block {
+            Block::Paragraph { text, heading } => (text, heading),
+            Block::Table(text) => (text, None),
+        };
+        match heading {
+            Some(level) => {
+                if section.title.is_some() || !section.text.is_empty() {
+                    sections.push(section);
+                }
+                section = OfficeSection {
+                    title: Some(clean_cell(&text)),
+                    level,
+                    text: String::new(),
+                    notes: None,
+                };
+            }
+            None => {
+                if !section.text.is_empty() {
+                    section.text.push_str("\n\n");
+                }
+                section.text.push_str(&text);
+            }
+        }
+    }
+    if section.title.is_some() || !section.text.is_empty() {
+        sections.push(section);
+    }
+    Ok(sections)
+}
+
+fn shared_strings<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<Vec<String>, String> {
+    let Some(xml) = read_part(archive, "xl/sharedStrings.xml")? else {
+        return Ok(Vec::new());
+    };
+    let mut strings = Vec::new();
+    let mut current = String::new();
+    let mut in_text = false;
+    // Phonetic guides (rPh) repeat the reading of East Asian text
+    let mut in_phonetic = false;
+    walk(&xml, |node| match node {
+        Node::Open(e) => match local_name(e) {
+            b"si" => current.clear(),
+            b"t" => in_text = true,
+            b"rPh" => in_phonetic = true,
+            _ => {}
+        },
+        Node::Text(text) if in_text && !in_phonetic => current.push_str(text),
+        Node::Close(b"t") => in_text = false,
+        Node::Close(b"rPh") => in_phonetic = false,
+        Node::Close(b"si") => strings.push(std::mem::take(&mut current)),
+        _ => {}
+    })?;
+    Ok(strings)
+}
+
+// Zero-based column of a cell reference like "AB12"
+fn column_index(reference: &str) -> Option<usize> {
+    let letters: Vec<u8> = reference.bytes().take_while(|b| b.is_ascii_alphabetic()).collect();
+    if letters.is_empty() {
+        return None;
+    }
+    let number = letters
+        .iter()
+        .fold(0usize, |acc, b| acc * 26 + (b.to_ascii_uppercase() - b'A' + 1) as usize);
+    Some(number - 1)
+}
+
+// Rows of a worksheet as TSV; empty rows are left out
+fn sheet_text(xml: &str, strings: &[String]) -> Result<String, String> {
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut row: Vec<String> = Vec::new();
+    let mut cell_type = String::new();
+    let mut column: Option<usize> = None;
+    let mut value = String::new();
+    let mut in_value = false;
+    walk(xml, |node| match node {
+        Node::Open(e) => match local_name(e) {
+            b"row" => row.clear(),
+            b"c" => {
+                cell_type = attr(e, "t").unwrap_or_default();
+                column = attr(e, "r").and_then(|r| column_index(&r));
+                value.clear();
+            }
+            b"v" | b"t" => in_value = true,
+            _ => {}
+        },
+        Node::Text(text) if in_value => value.push_str(text),
+        Node::Close(b"v" | b"t") => in_value = false,
+        Node::Close(b"c") => {
+            let text = match cell_type.as_str() {
+                "s" => value.trim().parse::<usize>().ok().and_then(|i| strings.get(i)).cloned().unwrap_or_default(),
+                "b" => if value.trim() == "1" { "TRUE" } else { "FALSE" }.to_string(),
+                _ => value.clone(),
+            };
+            let index = column.unwrap_or(row.len());
+            if index >= row.len() {
+                row.resize(index + 1, String::new());
+            }
+            row[index] = clean_cell(&text);
+        }
+        Node::Close(b"row") => {
+            while row.last().is_some_and(|c| c.is_empty()) {
+                row.pop();
+            }
+            if !row.is_empty() {
+                rows.push(std::mem::take(&mut row));
+            }
+        }
+        _ => {}
+    })?;
+    Ok(tsv(&rows))
+}
+
+fn extract_xlsx<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<Vec<OfficeSection>, String> {
+    let workbook = read_part(archive, "xl/workbook.xml")?.ok_or("xl/workbook.xml is missing")?;
+    let targets = relationships(archive, "xl/workbook.xml")?;
+    let strings = shared_strings(archive)?;
+
+    let mut sheets = Vec::new();
+    walk(&workbook, |node| {
+        if let Node::Open(e) = node {
+            if local_name(e) == b"sheet" {
+                sheets.push((attr(e, "name").unwrap_or_default(), attr(e, "r:id")));
+            }
+        }
+    })?;
+
+    let mut sections = Vec::new();
+    for (name, id) in sheets {
+        let Some(path) = id.and_then(|id| targets.get(&id).cloned()) else {
+            continue;
+        };
+        // Chart sheets have no cells
+        let Some(xml) = read_part(archive, &path)? else {
+            continue;
+        };
+        sections.push(OfficeSection {
+            title: Some(name),
+            level: 1,
+            text: sheet_text(&xml, &strings)?,
+            notes: None,
+        });
+    }
+    Ok(sections)
+}
+
+// Text of a slide or notes page by shape, with the shape's placeholder type (title, body, ...)
+fn slide_shapes(xml: &str) -> Result<Vec<(Option<String>, String)>, String> {
+    let mut shapes = Vec::new();
+    let mut flow = Flow::default();
+    let mut placeholder = None;
+    walk(xml, |node| {
+        match &node {
+            Node::Open(e) if matches!(local_name(e), b"sp" | b"graphicFrame") => {
+                flow = Flow::default();
+                placeholder = None;
+            }
+            // A placeholder without a type is a body placeholder
+            Node::Open(e) if local_name(e) == b"ph" => placeholder = Some(attr(e, "type").unwrap_or_else(|| "body".to_string())),
+            Node::Close(b"sp" | b"graphicFrame") => {
+                let text = flow.text();
+                if !text.is_empty() {
+                    shapes.push((placeholder.take(), text));
+                }
+                return;
+            }
+            _ => {}
+        }
+        flow.visit(&node, |_| None);
+    })?;
+    Ok(shapes)
+}
+
+fn extract_pptx<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<Vec<OfficeSection>, String> {
+    let presentation = read_part(archive, "ppt/presentation.xml")?.ok_or("ppt/presentation.xml is missing")?;
+    let targets = relationships(archive, "ppt/presentation.xml")?;
+
+    // Slide order comes from the slide id list, not from the file names
+    let mut slide_ids = Vec::new();
+    walk(&presentation, |node| {
+        if let Node::Open(e) = node {
+            if local_name(e) == b"sldId" {
+                if let Some(id) = attr(e, "r:id") {
+                    slide_ids.push(id);
+                }
+            }
+        }
+    })?;
+
+    let mut sections = Vec::new();
+    for (index, id) in slide_ids.iter().enumerate() {
+        let Some(path) = targets.get(id) else {
+            continue;
+        };
+        let Some(xml) = read_part(archive, path)? else {
+            continue;
+        };
+        let mut title = None;
+        let mut body = Vec::new();
+        for (placeholder, text) in slide_shapes(&xml)? {
+            match placeholder.as_deref() {
+                Some("title" | "ctrTitle") if title.is_none() => title = Some(clean_cell(&text)),
+                Some("sldNum" | "dt" | "ftr") => {}
+                _ => body.push(text),
+            }
+        }
+
+        let notes_path = relationships(archive, path)?
+            .into_values()
+            .find(|target| target.contains("notesSlides/"));
+        let notes = match notes_path {
+            Some(notes_path) => read_part(archive, &notes_path)?
+                .map(|xml| slide_shapes(&xml))
+                .transpose()?
+                .map(|shapes| {
+                    shapes
+                        .into_iter()
+                        .filter(|(placeholder, _)| placeholder.as_deref() == Some("body"))
+                        .map(|(_, text)| text)
+                        .collect::<Vec<_>>()
+                        .join("\n\n")
+                })
+                .filter(|notes| !notes.is_empty()),
+            None => None,
+        };
+
+        sections.push(OfficeSection {
+            title: Some(match title {
+                Some(title) => format!("Slide {}: {}", index + 1, title),
+                None => format!("Slide {}", index + 1),
+            }),
+            level: 1,
+            text: body.join("\n\n"),
+            notes,
+        });
+    }
+    Ok(sections)
+}
+
+// Extract a DOCX, XLSX or PPTX document held in memory
+pub fn parse(bytes: &[u8]) -> Result<OfficeDocument, String> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("Not an Office document: {}", e))?;
+    let has = |archive: &ZipArchive<Cursor<&[u8]>>, name: &str| archive.index_for_name(name).is_some();
+    let format = if has(&archive, "word/document.xml") {
+        OfficeFormat::Docx
+    } else if has(&archive, "xl/workbook.xml") {
+        OfficeFormat::Xlsx
+    } else if has(&archive, "ppt/presentation.xml") {
+        OfficeFormat::Pptx
+    } else {
+        return Err("Not a DOCX, XLSX or PPTX document".to_string());
+    };
+    let sections = match format {
+        OfficeFormat::Docx => extract_docx(&mut archive)?,
+        OfficeFormat::Xlsx => extract_xlsx(&mut archive)?,
+        OfficeFormat::Pptx => extract_pptx(&mut archive)?,
+    };
+    Ok(OfficeDocument { format, sections })
+}
+
+pub fn load(path: &Path) -> Result<OfficeDocument, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse(&bytes)
+}
+
+#[tauri::command]
+pub async fn extract_office(path: String) -> Result<OfficeDocument, String> {
+    tokio::task::spawn_blocking(move || load(Path::new(&path)))
+        .await
+        .map_err(|e| format!("Office extraction task failed: {}", e))?
+}
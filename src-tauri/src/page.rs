@@ -0,0 +1,293 @@
+// Structure of a web page for the agent to navigate by: its links with their anchor text, resolved
+// to absolute URLs, and its heading outline. Pages are given as a URL or as HTML.
+use std::collections::HashSet;
+use std::time::Duration;
+
+use kuchikiki::NodeRef;
+use reqwest::Url;
+use tauri::AppHandle;
+
+use crate::http_client::{self, BROWSER_USER_AGENT};
+use crate::{clean_text, html, settings};
+
+// Cap on links handed to the agent
+const MAX_TOOL_LINKS: usize = 200;
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PageLink {
+    pub url: String,
+    // Anchor text, or the alt text of a linked image
+    pub text: String,
+    // The heading of the section the link is in
+    pub section: Option<String>,
+    // Whether the link stays on the page's site
+    pub internal: bool,
+    // Inside the page's navigation, header or footer rather than its content
+    pub navigation: bool,
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PageHeading {
+    // 1 to 6
+    pub level: usize,
+    pub text: String,
+    // Link to the heading when it has an id and the page URL is known
+    pub url: Option<String>,
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PageOutline {
+    pub title: Option<String>,
+    pub headings: Vec<PageHeading>,
+}
+
+fn attribute(node: &NodeRef, name: &str) -> Option<String> {
+    node.as_element()?.attributes.borrow().get(name).map(str::to_string)
+}
+
+fn heading_level(node: &NodeRef) -> Option<usize> {
+    let element = node.as_element()?;
+    match &*element.name.local {
+        "h1" => Some(1),
+        "h2" => Some(2),
+        "h3" => Some(3),
+        "h4" => Some(4),
+        "h5" => Some(5),
+        "h6" => Some(6),
+        _ => None,
+    }
+}
+
+fn is_navigation(node: &NodeRef) -> bool {
+    node.ancestors().any(|a| {
+        let Some(element) = a.as_element() else { return false };
+        let role = element.attributes.borrow().get("role").unwrap_or_default().to_string();
+        matches!(&*element.name.local, "nav" | "header" | "footer" | "aside")
+            || matches!(role.as_str(), "navigation" | "banner" | "contentinfo")
+    })
+}
+
+// A <base href> overrides the page URL for relative links
+fn base_url(document: &NodeRef, page_url: Option<&Url>) -> Option<Url> {
+    let href = document.select_first("base[href]").ok().and_then(|b| attribute(b.as_node(), "href"));
+    match (href, page_url) {
+        (Some(href), Some(page)) => page.join(&href).ok().or_else(|| Some(page.clone())),
+        (Some(href), None) => Url::parse(&href).ok(),
+        (None, page) => page.cloned(),
+    }
+}
+
+fn link_text(link: &NodeRef) -> String {
+    let text = clean_text(&link.text_contents());
+    if !text.is_empty() {
+        return text;
+    }
+    let image_alt = link.select_first("img[alt]").ok().and_then(|img| attribute(img.as_node(), "alt"));
+    [attribute(link, "aria-label"), attribute(link, "title"), image_alt]
+        .into_iter()
+        .flatten()
+        .map(|text| clean_text(&text))
+        .find(|text| !text.is_empty())
+        .unwrap_or_default()
+}
+
+// http(s) links of a page in document order, once per URL; `page_url` resolves relative ones.
+// Links to a part of the same page are left out.
+pub fn links(html: &str, page_url: Option<&Url>) -> Vec<PageLink> {
+    let document = html::parse(html);
+    let base = base_url(&document, page_url);
+    let site = page_url.or(base.as_ref()).and_then(|u| u.host_str()).map(|h| h.trim_start_matches("www.").to_string());
+    let mut seen = HashSet::new();
+    let mut section: Option<String> = None;
+    let mut found = Vec::new();
+    for node in document.descendants() {
+        if heading_level(&node).is_some() {
+            section = Some(clean_text(&node.text_contents())).filter(|t| !t.is_empty());
+            continue;
+        }
+        if node.as_element().is_none_or(|e| &*e.name.local != "a") {
+            continue;
+        }
+        let Some(href) = attribute(&node, "href").map(|h| h.trim().to_string()) else { continue };
+        if href.is_empty() || href.starts_with('#') {
+            continue;
+        }
+        let url = match &base {
+            Some(base) => base.join(&href),
+            None => Url::parse(&href),
+        };
+        let Ok(url) = url else { continue };
+        if !matches!(url.scheme(), "http" | "https") || !seen.insert(url.to_string()) {
+            continue;
+        }
+        let host = url.host_str().map(|h| h.trim_start_matches("www."));
+        found.push(PageLink {
+            internal: site.is_some() && host == site.as_deref(),
+            url: url.to_string(),
+            text: link_text(&node),
+            section: section.clone(),
+            navigation: is_navigation(&node),
+        });
+    }
+    found
+}
+
+// Title and h1-h6 headings of a page, in document order
+pub fn outline(html: &str, page_url: Option<&Url>) -> PageOutline {
+    let document = html::parse(html);
+    let title = document
+        .select_first("title")
+        .ok()
+        .map(|t| clean_text(&t.text_contents()))
+        .filter(|t| !t.is_empty());
+    let headings = document
+        .descendants()
+        .filter_map(|node| {
+            let level = heading_level(&node)?;
+            let text = clean_text(&node.text_contents());
+            if text.is_empty() {
+                return None;
+            }
+            // Docs generators often put the anchor on a link inside the heading
+            let id = attribute(&node, "id")
+                .or_else(|| node.select_first("[id]").ok().and_then(|e| attribute(e.as_node(), "id")))
+                .filter(|id| !id.is_empty());
+            let url = page_url.zip(id).map(|(page, id)| {
+                let mut url = page.clone();
+                url.set_fragment(Some(&id));
+                url.to_string()
+            });
+            Some(PageHeading { level, text, url })
+        })
+        .collect();
+    PageOutline { title, headings }
+}
+
+async fn fetch(client: &reqwest::Client, url: &str, timeout_secs: u64) -> Result<String, String> {
+    let response = client
+        .get(url)
+        .timeout(Duration::from_secs(timeout_secs))
+        .header("User-Agent", BROWSER_USER_AGENT)
+        .header("Accept", "text/html,application/xhtml+xml;q=0.9,*/*;q=0.8")
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Request failed with status {}", response.status()));
+    }
+    response.text().await.map_err(|e| format!("Failed to read response body: {}", e))
+}
+
+// The HTML of `source`, an http(s) URL or HTML itself, with the page URL when there is one
+pub async fn load(
+    client: &reqwest::Client,
+    source: &str,
+    timeout_secs: u64,
+) -> Result<(String, Option<Url>), String> {
+    let source = source.trim();
+    if source.starts_with("http://") || source.starts_with("https://") {
+        let url = Url::parse(source).map_err(|e| format!("Invalid URL: {}", e))?;
+        return Ok((fetch(client, source, timeout_secs).await?, Some(url)));
+    }
+    Ok((source.to_string(), None))
+}
+
+// Links whose text, URL or section contains `filter`, case-insensitively
+fn filter_links(links: Vec<PageLink>, filter: Option<&str>) -> Vec<PageLink> {
+    let Some(filter) = filter.map(str::to_lowercase).filter(|f| !f.trim().is_empty()) else { return links };
+    links
+        .into_iter()
+        .filter(|link| {
+            let section = link.section.as_deref().unwrap_or_default();
+            [link.text.as_str(), link.url.as_str(), section].iter().any(|s| s.to_lowercase().contains(&filter))
+        })
+        .collect()
+}
+
+pub async fn links_from_source(
+    client: &reqwest::Client,
+    source: &str,
+    filter: Option<&str>,
+    timeout_secs: u64,
+) -> Result<Vec<PageLink>, String> {
+    let (html, url) = load(client, source, timeout_secs).await?;
+    let links = tokio::task::spawn_blocking(move || links(&html, url.as_ref()))
+        .await
+        .map_err(|e| format!("Link extraction failed: {}", e))?;
+    Ok(filter_links(links, filter))
+}
+
+pub async fn outline_from_source(
+    client: &reqwest::Client,
+    source: &str,
+    timeout_secs: u64,
+) -> Result<PageOutline, String> {
+    let (html, url) = load(client, source, timeout_secs).await?;
+    tokio::task::spawn_blocking(move || outline(&html, url.as_ref()))
+        .await
+        .map_err(|e| format!("Outline extraction failed: {}", e))
+}
+
+// Content links first, then navigation, as a list with their sections
+pub fn links_to_markdown(links: &[PageLink]) -> String {
+    if links.is_empty() {
+        return "No links found".to_string();
+    }
+    let (content, navigation): (Vec<&PageLink>, Vec<&PageLink>) = links.iter().partition(|l| !l.navigation);
+    let line = |link: &PageLink| {
+        let text = if link.text.is_empty() { "(no text)" } else { link.text.as_str() };
+        match &link.section {
+            Some(section) if !link.navigation => format!("- [{}]({}) ({})", text, link.url, section),
+            _ => format!("- [{}]({})", text, link.url),
+        }
+    };
+    let mut out = String::new();
+    for (title, group) in [("Content links", content), ("Navigation links", navigation)] {
+        if group.is_empty() {
+            continue;
+        }
+        let lines: Vec<String> = group.into_iter().take(MAX_TOOL_LINKS).map(line).collect();
+        out.push_str(&format!("## {}\n\n{}\n\n", title, lines.join("\n")));
+    }
+    let omitted = links.len().saturating_sub(2 * MAX_TOOL_LINKS);
+    if omitted > 0 {
+        out.push_str(&format!("({} more links; use a filter to narrow them down)", omitted));
+    }
+    out.trim_end().to_string()
+}
+
+// Headings indented by level
+pub fn outline_to_markdown(outline: &PageOutline) -> String {
+    let mut out = format!("# {}\n\n", outline.title.as_deref().unwrap_or("Outline"));
+    if outline.headings.is_empty() {
+        out.push_str("No headings found");
+    }
+    for heading in &outline.headings {
+        let indent = "  ".repeat(heading.level - 1);
+        match &heading.url {
+            Some(url) => out.push_str(&format!("{}- [{}]({})\n", indent, heading.text, url)),
+            None => out.push_str(&format!("{}- {}\n", indent, heading.text)),
+        }
+    }
+    out.trim_end().to_string()
+}
+
+// Links of a page (a URL or HTML) with their anchor text, resolved to absolute URLs; `filter`
+// keeps those whose text, URL or section contains it
+#[tauri::command]
+pub async fn extract_links(app: AppHandle, source: String, filter: Option<String>) -> Result<Vec<PageLink>, String> {
+    let settings = settings::load(&app);
+    let client = http_client::for_app(&app, &settings)?;
+    links_from_source(&client, &source, filter.as_deref(), settings.fetch_timeout_secs).await
+}
+
+// Title and heading outline of a page (a URL or HTML)
+#[tauri::command]
+pub async fn extract_outline(app: AppHandle, source: String) -> Result<PageOutline, String> {
+    let settings = settings::load(&app);
+    let client = http_client::for_app(&app, &settings)?;
+    outline_from_source(&client, &source, settings.fetch_timeout_secs).await
+}
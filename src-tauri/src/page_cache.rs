@@ -0,0 +1,157 @@
+// On-disk cache of scraped pages, per profile in <profile data>/page_cache/, one JSON file per URL
+// and kind of extraction. Entries keep the ETag and Last-Modified validators of the page; a repeat
+// scrape sends them back (If-None-Match, If-Modified-Since), and when the site answers 304 Not
+// Modified the stored extraction is returned instead of downloading and rendering the page again.
+// Pages served without validators aren't stored. Off when `scrapeCache` is unset.
+use std::path::{Path, PathBuf};
+
+use reqwest::blocking::RequestBuilder;
+use reqwest::header::{HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tauri::AppHandle;
+
+use crate::blobs::to_hex;
+
+const CACHE_DIR: &str = "page_cache";
+// Older entries are fetched again in full, whatever the site says
+const MAX_AGE_SECS: i64 = 7 * 24 * 60 * 60;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Validators {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_modified: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry<T> {
+    url: String,
+    #[serde(flatten)]
+    validators: Validators,
+    // Unix seconds
+    stored_at: i64,
+    page: T,
+}
+
+pub struct CachedPage<T> {
+    pub page: T,
+    validators: Validators,
+}
+
+#[derive(Clone)]
+pub struct PageCache {
+    dir: PathBuf,
+}
+
+impl Validators {
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name| headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+        Validators {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    // These, with the parts they lack taken from `older`
+    pub fn or(self, older: &Validators) -> Validators {
+        Validators {
+            etag: self.etag.or_else(|| older.etag.clone()),
+            last_modified: self.last_modified.or_else(|| older.last_modified.clone()),
+        }
+    }
+}
+
+impl<T> CachedPage<T> {
+    // Ask for the page only if it changed since it was cached
+    pub fn conditional(&self, mut request: RequestBuilder) -> RequestBuilder {
+        if let Some(etag) = &self.validators.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &self.validators.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+        request
+    }
+
+    pub fn validators(&self) -> &Validators {
+        &self.validators
+    }
+}
+
+impl PageCache {
+    pub fn for_profile(data_dir: &Path) -> Self {
+        PageCache {
+            dir: data_dir.join(CACHE_DIR),
+        }
+    }
+
+    // `variant` tells apart extractions of the same URL, e.g. as text and as Markdown
+    fn path(&self, url: &str, variant: &str) -> PathBuf {
+        let hash = Sha256::digest(format!("{}\n{}", variant, url).as_bytes());
+        self.dir.join(format!("{}.json", to_hex(&hash)))
+    }
+
+    pub fn lookup<T: DeserializeOwned>(&self, url: &str, variant: &str) -> Option<CachedPage<T>> {
+        let text = std::fs::read_to_string(self.path(url, variant)).ok()?;
+        let entry: Entry<T> = serde_json::from_str(&text).ok()?;
+        if entry.url != url || chrono::Utc::now().timestamp() - entry.stored_at > MAX_AGE_SECS {
+            return None;
+        }
+        Some(CachedPage {
+            page: entry.page,
+            validators: entry.validators,
+        })
+    }
+
+    // Keep `page` until the site reports a change; failures only cost the cache
+    pub fn store<T: Serialize>(&self, url: &str, variant: &str, validators: Validators, page: &T) {
+        if validators.is_empty() {
+            return;
+        }
+        let entry = Entry {
+            url: url.to_string(),
+            validators,
+            stored_at: chrono::Utc::now().timestamp(),
+            page,
+        };
+        let saved = std::fs::create_dir_all(&self.dir)
+            .map_err(|e| e.to_string())
+            .and_then(|_| serde_json::to_string(&entry).map_err(|e| e.to_string()))
+            .and_then(|json| std::fs::write(self.path(url, variant), json).map_err(|e| e.to_string()));
+        if let Err(err) = saved {
+            eprintln!("[PageCache] Failed to store {}: {}", url, err);
+        }
+    }
+
+    // Remove every entry; returns how many went
+    pub fn clear(&self) -> Result<usize, String> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else { return Ok(0) };
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                std::fs::remove_file(&path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+// Empty the active profile's page cache, so the next scrapes fetch every page in full. Returns the
+// number of pages removed.
+#[tauri::command]
+pub fn clear_page_cache(app: AppHandle) -> Result<usize, String> {
+    let removed = PageCache::for_profile(&crate::profiles::data_dir(&app)?).clear()?;
+    eprintln!("[PageCache] Cleared {} pages", removed);
+    Ok(removed)
+}
@@ -0,0 +1,98 @@
+// Paywalls and login walls. A page behind one still scrapes, but what comes back is a teaser or a
+// sign-in form rather than the article. Publishers mark paywalled articles for search engines
+// (schema.org isAccessibleForFree, article:content_tier); without that, the wall shows in a text
+// much shorter than the article claims to be, or a short text next to paywall markup or a password
+// field.
+use kuchikiki::NodeRef;
+use serde_json::Value;
+
+use crate::html;
+
+// Class and id fragments of paywall and registration-wall overlays, Piano's and Poool's included
+const WALL_MARKERS: &[&str] = &[
+    "paywall",
+    "regwall",
+    "registration-wall",
+    "loginwall",
+    "login-wall",
+    "subscriber-only",
+    "subscription-wall",
+    "tp-modal",
+    "poool-widget",
+];
+// Set to locked, metered or free by news sites
+const CONTENT_TIER: &str = "meta[property='article:content_tier'][content], meta[name='article:content_tier'][content]";
+// Fewer words than this next to wall markup read as a teaser
+const TEASER_WORDS: usize = 300;
+// A password field with fewer words than this around it is a login wall
+const LOGIN_WORDS: usize = 150;
+
+fn is_false(value: &Value) -> bool {
+    match value {
+        Value::Bool(free) => !free,
+        Value::String(free) => free.trim().eq_ignore_ascii_case("false"),
+        _ => false,
+    }
+}
+
+// Record the largest wordCount of the items in `value` and whether one says it isn't free
+fn scan_json_ld(value: &Value, words: &mut u64, locked: &mut bool) {
+    match value {
+        Value::Object(map) => {
+            if let Some(count) = map.get("wordCount").and_then(|c| c.as_u64().or_else(|| c.as_str()?.parse().ok())) {
+                *words = (*words).max(count);
+            }
+            *locked |= map.get("isAccessibleForFree").is_some_and(is_false);
+            map.values().for_each(|value| scan_json_ld(value, words, locked));
+        }
+        Value::Array(items) => items.iter().for_each(|item| scan_json_ld(item, words, locked)),
+        _ => {}
+    }
+}
+
+fn attribute_matches(document: &NodeRef, selector: &str, attribute: &str, test: impl Fn(&str) -> bool) -> bool {
+    let Ok(nodes) = document.select(selector) else { return false };
+    nodes.into_iter().any(|node| node.attributes.borrow().get(attribute).is_some_and(&test))
+}
+
+// Whether the page is behind a paywall or login wall; `word_count` is that of the scraped text
+pub fn detect(markup: &str, word_count: usize) -> bool {
+    let document = html::parse(markup);
+
+    let mut article_words = 0;
+    let mut locked = false;
+    if let Ok(scripts) = document.select("script[type='application/ld+json']") {
+        for script in scripts {
+            if let Ok(json) = serde_json::from_str::<Value>(script.text_contents().trim()) {
+                scan_json_ld(&json, &mut article_words, &mut locked);
+            }
+        }
+    }
+    // The same in microdata and meta tags; metered articles give some reads away, so only locked
+    // ones count
+    locked = locked
+        || attribute_matches(&document, "[itemprop='isAccessibleForFree'][content]", "content", |c| {
+            c.trim().eq_ignore_ascii_case("false")
+        })
+        || attribute_matches(&document, CONTENT_TIER, "content", |c| c.trim().eq_ignore_ascii_case("locked"));
+    if locked {
+        return true;
+    }
+
+    // Far less text than the article's own word count
+    if article_words > 0 && (word_count as u64) * 2 + 100 < article_words {
+        return true;
+    }
+
+    let wall_markup = ["class", "id"].iter().any(|attribute| {
+        attribute_matches(&document, &format!("[{}]", attribute), attribute, |value| {
+            let value = value.to_lowercase();
+            WALL_MARKERS.iter().any(|marker| value.contains(marker))
+        })
+    });
+    if wall_markup && word_count < TEASER_WORDS {
+        return true;
+    }
+    let password = document.select_first("input[type='password']").is_ok();
+    password && word_count < LOGIN_WORDS
+}
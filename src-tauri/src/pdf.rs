@@ -0,0 +1,411 @@
+// PDF text extraction without a browser: per-page text, the outline (bookmarks) and the document
+// info dictionary. Used for PDF attachments, ingestion and scraped PDF URLs. Pages without a text
+// layer (scans) are OCRed from their embedded images when an OCR config is given.
+use std::path::Path;
+
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+use tauri::AppHandle;
+
+use crate::ocr::{self, OcrConfig};
+
+// Images smaller than this on either side are logos or decorations, not scanned pages
+const MIN_OCR_IMAGE_SIDE: i64 = 200;
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfPage {
+    // Page number, from 1
+    pub number: u32,
+    pub text: String,
+    // Mean OCR word confidence (0-100) when the text was recognized from page images
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ocr_confidence: Option<f32>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutlineEntry {
+    // Nesting depth, from 1 for top-level bookmarks
+    pub level: usize,
+    pub title: String,
+    pub page: usize,
+}
+
+#[derive(serde::Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creator: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub producer: Option<String>,
+    // RFC 3339 when the PDF date parses, otherwise as stored
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfDocument {
+    pub page_count: usize,
+    pub pages: Vec<PdfPage>,
+    pub outline: Vec<OutlineEntry>,
+    pub metadata: PdfMetadata,
+}
+
+impl PdfDocument {
+    // All page text, pages separated by a blank line
+    pub fn text(&self) -> String {
+        self.pages
+            .iter()
+            .map(|p| p.text.trim())
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+// PDF text strings are UTF-16BE with a byte order mark, UTF-8 with a BOM, or PDFDocEncoding
+// (treated as Latin-1, which it matches for printable characters)
+fn decode_text_string(bytes: &[u8]) -> String {
+    if let Some(rest) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        let units: Vec<u16> = rest.chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]])).collect();
+        String::from_utf16_lossy(&units)
+    } else if let Some(rest) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        String::from_utf8_lossy(rest).into_owned()
+    } else {
+        bytes.iter().map(|&b| b as char).collect()
+    }
+}
+
+// D:YYYYMMDDHHmmSSOHH'mm' where everything after the year is optional
+fn parse_pdf_date(raw: &str) -> Option<String> {
+    let s = raw.trim().strip_prefix("D:").unwrap_or(raw.trim());
+    let digits: String = s.chars().take_while(|c| c.is_ascii_digit()).collect();
+    if digits.len() < 4 {
+        return None;
+    }
+    let field = |from: usize, len: usize, default: u32| -> Option<u32> {
+        match digits.get(from..from + len) {
+            Some(part) => part.parse().ok(),
+            None => Some(default),
+        }
+    };
+    let date = chrono::NaiveDate::from_ymd_opt(field(0, 4, 0)? as i32, field(4, 2, 1)?, field(6, 2, 1)?)?;
+    let time = date.and_hms_opt(field(8, 2, 0)?, field(10, 2, 0)?, field(12, 2, 0)?)?;
+
+    let zone = &s[digits.len()..];
+    let offset_secs = match zone.chars().next() {
+        Some(sign @ ('+' | '-')) => {
+            let parts: Vec<i32> = zone[1..]
+                .split('\'')
+                .filter(|p| !p.is_empty())
+                .map(|p| p.parse().ok())
+                .collect::<Option<_>>()?;
+            let secs = parts.first().copied().unwrap_or(0) * 3600 + parts.get(1).copied().unwrap_or(0) * 60;
+            if sign == '-' {
+                -secs
+            } else {
+                secs
+            }
+        }
+        _ => 0,
+    };
+    let offset = chrono::FixedOffset::east_opt(offset_secs)?;
+    time.and_local_timezone(offset).single().map(|t| t.to_rfc3339())
+}
+
+fn read_metadata(doc: &Document) -> PdfMetadata {
+    let Some(info) = doc
+        .trailer
+        .get(b"Info")
+        .ok()
+        .and_then(|obj| doc.dereference(obj).ok())
+        .and_then(|(_, obj)| obj.as_dict().ok())
+    else {
+        return PdfMetadata::default();
+    };
+    let field = |key: &[u8]| -> Option<String> {
+        let (_, obj) = doc.dereference(info.get(key).ok()?).ok()?;
+        match obj {
+            Object::String(bytes, _) => Some(decode_text_string(bytes).trim().to_string()).filter(|s| !s.is_empty()),
+            _ => None,
+        }
+    };
+    let date = |key: &[u8]| field(key).map(|raw| parse_pdf_date(&raw).unwrap_or(raw));
+    PdfMetadata {
+        title: field(b"Title"),
+        author: field(b"Author"),
+        subject: field(b"Subject"),
+        keywords: field(b"Keywords"),
+        creator: field(b"Creator"),
+        producer: field(b"Producer"),
+        created: date(b"CreationDate"),
+        modified: date(b"ModDate"),
+    }
+}
+
+fn name_list(obj: Option<&Object>) -> Vec<Vec<u8>> {
+    match obj {
+        Some(Object::Name(name)) => vec![name.clone()],
+        Some(Object::Array(items)) => items.iter().filter_map(|i| i.as_name().ok().map(<[u8]>::to_vec)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn int(doc: &Document, dict: &Dictionary, key: &[u8]) -> Option<i64> {
+    let (_, obj) = doc.dereference(dict.get(key).ok()?).ok()?;
+    obj.as_i64().ok()
+}
+
+// Colour components of an image's colour space, for the ones that can be written as PNM
+fn components(doc: &Document, dict: &Dictionary) -> Option<usize> {
+    if matches!(dict.get(b"ImageMask"), Ok(Object::Boolean(true))) {
+        return Some(1);
+    }
+    let (_, space) = doc.dereference(dict.get(b"ColorSpace").ok()?).ok()?;
+    match space {
+        Object::Name(name) => match name.as_slice() {
+            b"DeviceGray" | b"CalGray" => Some(1),
+            b"DeviceRGB" | b"CalRGB" => Some(3),
+            b"DeviceCMYK" => Some(4),
+            _ => None,
+        },
+        // [/ICCBased stream] with the component count in the stream's /N
+        Object::Array(items) if items.first().and_then(|i| i.as_name().ok()) == Some(b"ICCBased".as_slice()) => {
+            let (_, profile) = doc.dereference(items.get(1)?).ok()?;
+            int(doc, &profile.as_stream().ok()?.dict, b"N").map(|n| n as usize)
+        }
+        _ => None,
+    }
+}
+
+// Decoded pixels as a binary PNM image (PBM, PGM or PPM); CMYK is converted to RGB
+fn pnm(pixels: &[u8], width: usize, height: usize, components: usize, bits: i64) -> Option<Vec<u8>> {
+    match (components, bits) {
+        (1, 1) => {
+            let row = width.div_ceil(8);
+            let data = pixels.get(..row * height)?;
+            // 0 bits are black in PDF images and stencil masks, white in PBM
+            let mut out = format!("P4\n{} {}\n", width, height).into_bytes();
+            out.extend(data.iter().map(|b| !b));
+            Some(out)
+        }
+        (1, 8) | (3, 8) => {
+            let data = pixels.get(..width * height * components)?;
+            let magic = if components == 1 { "P5" } else { "P6" };
+            let mut out = format!("{}\n{} {}\n255\n", magic, width, height).into_bytes();
+            out.extend_from_slice(data);
+            Some(out)
+        }
+        (4, 8) => {
+            let data = pixels.get(..width * height * 4)?;
+            let mut out = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+            for cmyk in data.chunks_exact(4) {
+                let k = 255 - cmyk[3] as u16;
+                out.extend(cmyk[..3].iter().map(|c| ((255 - *c as u16) * k / 255) as u8));
+            }
+            Some(out)
+        }
+        _ => None,
+    }
+}
+
+// Wrap CCITT fax data in a single-strip TIFF, which Tesseract reads directly
+fn ccitt_tiff(data: &[u8], width: u32, height: u32, params: Option<&Dictionary>) -> Vec<u8> {
+    let param = |key: &[u8]| params.and_then(|p| p.get(key).ok());
+    let k = param(b"K").and_then(|k| k.as_i64().ok()).unwrap_or(0);
+    let black_is_1 = matches!(param(b"BlackIs1"), Some(Object::Boolean(true)));
+    // Group 4 for K < 0, otherwise Group 3 (two-dimensional when K > 0)
+    let compression = if k < 0 { 4 } else { 3 };
+    let mut entries: Vec<(u16, u16, u32)> = vec![
+        (256, 4, width),
+        (257, 4, height),
+        (258, 3, 1),
+        (259, 3, compression),
+        // Photometric: 0 means 0 bits are white, 1 that they're black
+        (262, 3, if black_is_1 { 0 } else { 1 }),
+        (273, 4, 0),
+        (277, 3, 1),
+        (278, 4, height),
+        (279, 4, data.len() as u32),
+    ];
+    if compression == 3 {
+        entries.push((292, 4, if k > 0 { 1 } else { 0 }));
+    }
+    let data_offset = 8 + 2 + entries.len() * 12 + 4;
+    let mut out = Vec::with_capacity(data_offset + data.len());
+    out.extend_from_slice(b"II*\0");
+    out.extend_from_slice(&8u32.to_le_bytes());
+    out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    for (tag, kind, value) in entries {
+        let value = if tag == 273 { data_offset as u32 } else { value };
+        out.extend_from_slice(&tag.to_le_bytes());
+        out.extend_from_slice(&kind.to_le_bytes());
+        out.extend_from_slice(&1u32.to_le_bytes());
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    out.extend_from_slice(&0u32.to_le_bytes());
+    out.extend_from_slice(data);
+    out
+}
+
+// An image XObject in a format Tesseract can read: JPEG and JPEG 2000 pass through, CCITT becomes
+// TIFF and raw pixels become PNM. JBIG2 and palette images are skipped.
+fn encode_image(doc: &Document, stream: &Stream) -> Option<Vec<u8>> {
+    let dict = &stream.dict;
+    let (width, height) = (int(doc, dict, b"Width")?, int(doc, dict, b"Height")?);
+    if width < MIN_OCR_IMAGE_SIDE || height < MIN_OCR_IMAGE_SIDE {
+        return None;
+    }
+    let filters = name_list(dict.get(b"Filter").ok());
+    match filters.last().map(Vec::as_slice) {
+        Some(b"DCTDecode" | b"JPXDecode") if filters.len() == 1 => Some(stream.content.clone()),
+        Some(b"CCITTFaxDecode") if filters.len() == 1 => {
+            let params = dict.get(b"DecodeParms").ok().and_then(|p| match p {
+                Object::Array(items) => items.first().and_then(|i| i.as_dict().ok()),
+                other => other.as_dict().ok(),
+            });
+            Some(ccitt_tiff(&stream.content, width as u32, height as u32, params))
+        }
+        Some(b"DCTDecode" | b"JPXDecode" | b"CCITTFaxDecode" | b"JBIG2Decode") => None,
+        _ => {
+            let mask = matches!(dict.get(b"ImageMask"), Ok(Object::Boolean(true)));
+            let bits = if mask { 1 } else { int(doc, dict, b"BitsPerComponent")? };
+            let pixels = if filters.is_empty() {
+                stream.content.clone()
+            } else {
+                stream.decompressed_content().ok()?
+            };
+            pnm(&pixels, width as usize, height as usize, components(doc, dict)?, bits)
+        }
+    }
+}
+
+// Images drawn on a page, from its own resources or those it inherits from the page tree
+fn page_images(doc: &Document, page: ObjectId) -> Vec<Vec<u8>> {
+    let mut node = doc.get_dictionary(page).ok();
+    let mut resources = None;
+    while let Some(dict) = node {
+        if let Ok(found) = dict.get(b"Resources") {
+            resources = doc.dereference(found).ok().and_then(|(_, r)| r.as_dict().ok());
+            break;
+        }
+        node = dict
+            .get(b"Parent")
+            .ok()
+            .and_then(|p| doc.dereference(p).ok())
+            .and_then(|(_, p)| p.as_dict().ok());
+    }
+    let Some(xobjects) = resources
+        .and_then(|r| r.get(b"XObject").ok())
+        .and_then(|x| doc.dereference(x).ok())
+        .and_then(|(_, x)| x.as_dict().ok())
+    else {
+        return Vec::new();
+    };
+    xobjects
+        .iter()
+        .filter_map(|(_, obj)| doc.dereference(obj).ok()?.1.as_stream().ok())
+        .filter(|stream| stream.dict.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Image".as_slice()))
+        .filter_map(|stream| encode_image(doc, stream))
+        .collect()
+}
+
+// OCR the images of a page without a text layer; the confidence is averaged over its words
+fn ocr_page(doc: &Document, number: u32, page: ObjectId, config: &OcrConfig) -> (String, Option<f32>) {
+    let mut texts = Vec::new();
+    let (mut confidence_sum, mut words) = (0.0, 0);
+    for image in page_images(doc, page) {
+        match ocr::recognize(&image, config) {
+            Ok(result) => {
+                confidence_sum += result.confidence.unwrap_or(0.0) * result.words as f32;
+                words += result.words;
+                if !result.text.is_empty() {
+                    texts.push(result.text);
+                }
+            }
+            Err(err) => eprintln!("[PDF] OCR of page {} failed: {}", number, err),
+        }
+    }
+    (texts.join("\n\n"), (words > 0).then(|| confidence_sum / words as f32))
+}
+
+// Parse a PDF held in memory; pages whose text can't be extracted come back empty unless `ocr`
+// is given, in which case their images are recognized instead
+pub fn parse(bytes: &[u8], ocr: Option<&OcrConfig>) -> Result<PdfDocument, String> {
+    if !bytes.starts_with(b"%PDF") {
+        return Err("Not a PDF file".to_string());
+    }
+    let doc = Document::load_mem(bytes).map_err(|e| format!("Failed to parse PDF: {}", e))?;
+    if doc.is_encrypted() {
+        return Err("PDF is encrypted".to_string());
+    }
+
+    let pages: Vec<PdfPage> = doc
+        .get_pages()
+        .into_iter()
+        .map(|(number, id)| {
+            let text = doc.extract_text(&[number]).unwrap_or_else(|e| {
+                eprintln!("[PDF] No text on page {}: {}", number, e);
+                String::new()
+            });
+            match ocr {
+                Some(config) if text.trim().is_empty() => {
+                    let (text, ocr_confidence) = ocr_page(&doc, number, id, config);
+                    PdfPage { number, text, ocr_confidence }
+                }
+                _ => PdfPage { number, text, ocr_confidence: None },
+            }
+        })
+        .collect();
+    let recognized = pages.iter().filter(|p| p.ocr_confidence.is_some()).count();
+    if recognized > 0 {
+        eprintln!("[PDF] Recognized {} scanned pages with OCR", recognized);
+    }
+
+    // Many PDFs have no outline; a broken one shouldn't fail the extraction
+    let outline = doc
+        .get_toc()
+        .map(|toc| {
+            toc.toc
+                .into_iter()
+                .map(|entry| OutlineEntry {
+                    level: entry.level,
+                    title: entry.title,
+                    page: entry.page,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(PdfDocument {
+        page_count: pages.len(),
+        pages,
+        outline,
+        metadata: read_metadata(&doc),
+    })
+}
+
+pub fn load(path: &Path, ocr: Option<&OcrConfig>) -> Result<PdfDocument, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse(&bytes, ocr)
+}
+
+// Scanned pages are OCRed unless OCR is off in settings; `ocrLanguages` overrides its languages
+#[tauri::command]
+pub async fn extract_pdf(app: AppHandle, path: String, ocr_languages: Option<String>) -> Result<PdfDocument, String> {
+    let ocr = ocr::config(&app, ocr_languages.as_deref())?;
+    tokio::task::spawn_blocking(move || load(Path::new(&path), ocr.as_ref()))
+        .await
+        .map_err(|e| format!("PDF extraction task failed: {}", e))?
+}
@@ -0,0 +1,178 @@
+// Persona profiles: a named system prompt with default model and tools
+use rusqlite::{params, OptionalExtension, Row};
+use tauri::State;
+
+use crate::db::{self, Database};
+use crate::llm::{ChatMessage, ProviderConfig};
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Persona {
+    pub id: String,
+    pub name: String,
+    pub system_prompt: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_model: Option<String>,
+    // Tool names enabled for the agent; None means all tools
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_tools: Option<Vec<String>>,
+    pub active: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersonaInput {
+    name: String,
+    system_prompt: String,
+    default_model: Option<String>,
+    default_tools: Option<Vec<String>>,
+}
+
+const SELECT_PERSONA: &str =
+    "SELECT id, name, system_prompt, default_model, default_tools, is_active, created_at, updated_at FROM personas";
+
+fn row_to_persona(row: &Row) -> rusqlite::Result<Persona> {
+    let tools: Option<String> = row.get(4)?;
+    Ok(Persona {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        system_prompt: row.get(2)?,
+        default_model: row.get(3)?,
+        default_tools: tools.and_then(|t| serde_json::from_str(&t).ok()),
+        active: row.get::<_, i64>(5)? != 0,
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+    })
+}
+
+pub fn get_persona(db: &Database, id: &str) -> Result<Persona, String> {
+    db.with_conn(|conn| {
+        conn.query_row(&format!("{} WHERE id = ?1", SELECT_PERSONA), params![id], row_to_persona)
+            .optional()
+    })?
+    .ok_or_else(|| format!("Persona {} not found", id))
+}
+
+pub fn active_persona(db: &Database) -> Result<Option<Persona>, String> {
+    db.with_conn(|conn| {
+        conn.query_row(&format!("{} WHERE is_active = 1", SELECT_PERSONA), [], row_to_persona)
+            .optional()
+    })
+}
+
+// Resolve the persona for a request: an explicit id wins over the active persona
+pub fn resolve(db: &Database, persona_id: Option<&str>) -> Result<Option<Persona>, String> {
+    match persona_id {
+        Some(id) => get_persona(db, id).map(Some),
+        None => active_persona(db),
+    }
+}
+
+impl Persona {
+    // Put the persona's system prompt first and fill in its default model when none was chosen
+    pub fn apply(&self, provider: &mut ProviderConfig, messages: &mut Vec<ChatMessage>) {
+        if !self.system_prompt.trim().is_empty() {
+            messages.insert(0, ChatMessage::new("system", self.system_prompt.clone()));
+        }
+        if provider.model.trim().is_empty() {
+            if let Some(model) = &self.default_model {
+                provider.model = model.clone();
+            }
+        }
+    }
+}
+
+fn validate_input(input: &PersonaInput) -> Result<(), String> {
+    if input.name.trim().is_empty() {
+        return Err("Persona name cannot be empty".to_string());
+    }
+    Ok(())
+}
+
+fn tools_json(tools: &Option<Vec<String>>) -> Option<String> {
+    tools.as_ref().map(|t| serde_json::to_string(t).unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn list_personas(db: State<'_, Database>) -> Result<Vec<Persona>, String> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(&format!("{} ORDER BY name COLLATE NOCASE", SELECT_PERSONA))?;
+        let rows = stmt.query_map([], row_to_persona)?;
+        rows.collect()
+    })
+}
+
+#[tauri::command]
+pub fn create_persona(db: State<'_, Database>, persona: PersonaInput) -> Result<Persona, String> {
+    validate_input(&persona)?;
+    let id = db::new_id("persona");
+    let now = chrono::Utc::now().to_rfc3339();
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO personas (id, name, system_prompt, default_model, default_tools, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)",
+            params![
+                id,
+                persona.name.trim(),
+                persona.system_prompt,
+                persona.default_model,
+                tools_json(&persona.default_tools),
+                now
+            ],
+        )
+    })?;
+    eprintln!("[Persona] Created persona {}", id);
+    get_persona(&db, &id)
+}
+
+#[tauri::command]
+pub fn update_persona(db: State<'_, Database>, id: String, persona: PersonaInput) -> Result<Persona, String> {
+    validate_input(&persona)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let updated = db.with_conn(|conn| {
+        conn.execute(
+            "UPDATE personas SET name = ?1, system_prompt = ?2, default_model = ?3, default_tools = ?4, updated_at = ?5
+             WHERE id = ?6",
+            params![
+                persona.name.trim(),
+                persona.system_prompt,
+                persona.default_model,
+                tools_json(&persona.default_tools),
+                now,
+                id
+            ],
+        )
+    })?;
+    if updated == 0 {
+        return Err(format!("Persona {} not found", id));
+    }
+    get_persona(&db, &id)
+}
+
+#[tauri::command]
+pub fn delete_persona(db: State<'_, Database>, id: String) -> Result<(), String> {
+    let deleted = db.with_conn(|conn| conn.execute("DELETE FROM personas WHERE id = ?1", params![id]))?;
+    if deleted == 0 {
+        return Err(format!("Persona {} not found", id));
+    }
+    Ok(())
+}
+
+// Make a persona active for all chat commands; pass no id to clear the active persona
+#[tauri::command]
+pub fn apply_persona(db: State<'_, Database>, id: Option<String>) -> Result<Option<Persona>, String> {
+    if let Some(id) = &id {
+        get_persona(&db, id)?;
+    }
+    db.with_conn(|conn| {
+        conn.execute("UPDATE personas SET is_active = 0 WHERE is_active = 1", [])?;
+        if let Some(id) = &id {
+            conn.execute("UPDATE personas SET is_active = 1 WHERE id = ?1", params![id])?;
+        }
+        Ok(())
+    })?;
+    eprintln!("[Persona] Active persona: {:?}", id);
+    active_persona(&db)
+}
@@ -0,0 +1,104 @@
+// Per-host politeness for scraping: at most `scrapeMaxPerHost` pages of one site are scraped at once,
+// and each scrape of a site starts at least `scrapeHostDelayMs` after the previous one. Search
+// results often come from a handful of sites, and hitting one with a whole batch at once gets the
+// app rate limited or banned. The scheduler is shared, so concurrent batches count together.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::Url;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+use crate::settings::Settings;
+
+#[derive(Clone, Copy)]
+pub struct HostLimits {
+    pub min_delay: Duration,
+    pub max_parallel: usize,
+}
+
+impl HostLimits {
+    pub fn from_settings(settings: &Settings) -> Self {
+        HostLimits {
+            min_delay: Duration::from_millis(settings.scrape_host_delay_ms),
+            max_parallel: settings.scrape_max_per_host.max(1),
+        }
+    }
+}
+
+struct Host {
+    slots: Arc<Semaphore>,
+    max_parallel: usize,
+    // When the last scrape of the host started; held across the wait so starts queue up in order
+    last_start: tokio::sync::Mutex<Option<Instant>>,
+}
+
+#[derive(Clone, Default)]
+pub struct HostScheduler {
+    hosts: Arc<Mutex<HashMap<String, Arc<Host>>>>,
+}
+
+// A host slot; the next scrape of the host may take it once this is dropped
+pub struct HostPermit {
+    _slot: Option<OwnedSemaphorePermit>,
+}
+
+// Hosts that differ only by "www." are the same site
+fn host_key(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    let host = url.host_str()?.to_lowercase();
+    Some(host.trim_start_matches("www.").to_string())
+}
+
+impl Host {
+    fn new(max_parallel: usize) -> Self {
+        Host {
+            slots: Arc::new(Semaphore::new(max_parallel)),
+            max_parallel,
+            last_start: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    // Nothing running or waiting, and the delay since the last start has passed
+    fn is_done(self: &Arc<Self>, min_delay: Duration) -> bool {
+        let started_long_ago = match self.last_start.try_lock() {
+            Ok(last_start) => last_start.is_none_or(|at| at.elapsed() >= min_delay),
+            Err(_) => false,
+        };
+        Arc::strong_count(self) == 1 && self.slots.available_permits() == self.max_parallel && started_long_ago
+    }
+}
+
+impl HostScheduler {
+    fn host(&self, key: &str, limits: HostLimits) -> Arc<Host> {
+        let mut hosts = self.hosts.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        hosts.retain(|_, host| !host.is_done(limits.min_delay));
+        let host = hosts.entry(key.to_string()).or_insert_with(|| Arc::new(Host::new(limits.max_parallel)));
+        // A changed limit applies to new waiters; scrapes already running finish under the old one
+        if host.max_parallel != limits.max_parallel {
+            *host = Arc::new(Host::new(limits.max_parallel));
+        }
+        host.clone()
+    }
+
+    // Wait until `url`'s host has a free slot and its delay since the last start has passed.
+    // URLs without a host aren't limited.
+    pub async fn acquire(&self, url: &str, limits: HostLimits) -> HostPermit {
+        let Some(key) = host_key(url) else { return HostPermit { _slot: None } };
+        let host = self.host(&key, limits);
+        // The semaphore is never closed
+        let slot = host.slots.clone().acquire_owned().await.ok();
+        let mut last_start = host.last_start.lock().await;
+        if let Some(at) = *last_start {
+            let ready = at + limits.min_delay;
+            if ready > Instant::now() {
+                let wait = ready - Instant::now();
+                eprintln!("[Politeness] Waiting {}ms before the next request to {}", wait.as_millis(), key);
+                tokio::time::sleep_until(ready).await;
+            }
+        }
+        *last_start = Some(Instant::now());
+        HostPermit { _slot: slot }
+    }
+}
@@ -0,0 +1,252 @@
+// Previews of linked pages for the citation cards of the sources panel: the site's name and
+// favicon, and the page's title, description and OpenGraph image. Only the start of a page is
+// read, up to the end of its <head>. Previews are kept for a day, and favicons once per site.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use kuchikiki::NodeRef;
+use reqwest::Url;
+use tauri::{AppHandle, State};
+
+use crate::http_client::{self, BROWSER_USER_AGENT};
+use crate::{html, metadata, settings};
+
+// Bytes of a page read looking for the end of its <head>
+const MAX_HEAD_BYTES: usize = 512 * 1024;
+const MAX_FAVICON_BYTES: usize = 100 * 1024;
+const MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+// Entries kept of each kind; the oldest go first
+const MAX_ENTRIES: usize = 500;
+// Icons a page declares that are tried before /favicon.ico
+const MAX_ICON_CANDIDATES: usize = 3;
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SitePreview {
+    // The page after redirects
+    pub url: String,
+    // og:site_name, the application name, or the host
+    pub site_name: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    // Plain base64 without a data: prefix
+    pub favicon: Option<String>,
+    pub favicon_mime_type: Option<String>,
+}
+
+#[derive(Clone)]
+struct Favicon {
+    data: String,
+    mime_type: String,
+}
+
+struct Entries<T> {
+    entries: HashMap<String, (Instant, T)>,
+}
+
+impl<T: Clone> Entries<T> {
+    fn get(&self, key: &str) -> Option<T> {
+        let (stored, value) = self.entries.get(key)?;
+        (stored.elapsed() < MAX_AGE).then(|| value.clone())
+    }
+
+    fn insert(&mut self, key: String, value: T) {
+        if self.entries.len() >= MAX_ENTRIES && !self.entries.contains_key(&key) {
+            let oldest = self.entries.iter().min_by_key(|(_, (stored, _))| *stored).map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, (Instant::now(), value));
+    }
+}
+
+impl<T> Default for Entries<T> {
+    fn default() -> Self {
+        Entries { entries: HashMap::new() }
+    }
+}
+
+// Previews by URL, and favicons by origin; sites without one are remembered too
+#[derive(Clone, Default)]
+pub struct PreviewCache {
+    previews: Arc<Mutex<Entries<SitePreview>>>,
+    favicons: Arc<Mutex<Entries<Option<Favicon>>>>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+// The page up to the end of its <head>
+async fn read_head(mut response: reqwest::Response) -> Result<String, String> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| format!("Failed to read response body: {}", e))? {
+        let searched_from = bytes.len().saturating_sub(6);
+        bytes.extend_from_slice(&chunk);
+        let head_ended = bytes[searched_from..].windows(7).any(|w| w.eq_ignore_ascii_case(b"</head>"));
+        if head_ended || bytes.len() >= MAX_HEAD_BYTES {
+            break;
+        }
+    }
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn attribute(node: &NodeRef, name: &str) -> Option<String> {
+    let value = node.as_element()?.attributes.borrow().get(name).map(|v| v.trim().to_string());
+    value.filter(|v| !v.is_empty())
+}
+
+// Icons the page links, plain icons before touch icons, then the conventional /favicon.ico
+fn icon_candidates(document: &NodeRef, page: &Url) -> Vec<Url> {
+    let mut icons: Vec<(bool, Url)> = document
+        .select("link[rel][href]")
+        .into_iter()
+        .flatten()
+        .filter_map(|link| {
+            let rel = attribute(link.as_node(), "rel")?.to_lowercase();
+            let rels: Vec<&str> = rel.split_whitespace().collect();
+            if !rels.iter().any(|r| *r == "icon" || r.starts_with("apple-touch-icon")) {
+                return None;
+            }
+            let url = page.join(&attribute(link.as_node(), "href")?).ok()?;
+            Some((!rels.contains(&"icon"), url))
+        })
+        .collect();
+    icons.sort_by_key(|(touch, _)| *touch);
+    let mut candidates: Vec<Url> = icons.into_iter().map(|(_, url)| url).take(MAX_ICON_CANDIDATES).collect();
+    if let Ok(conventional) = page.join("/favicon.ico") {
+        if !candidates.contains(&conventional) {
+            candidates.push(conventional);
+        }
+    }
+    candidates
+}
+
+fn get(client: &reqwest::Client, url: &Url, timeout: Duration) -> reqwest::RequestBuilder {
+    client.get(url.as_str()).timeout(timeout).header("User-Agent", BROWSER_USER_AGENT)
+}
+
+async fn fetch_favicon(client: &reqwest::Client, url: &Url, timeout: Duration) -> Option<Favicon> {
+    let response = get(client, url, timeout).send().await.ok()?;
+    if !response.status().is_success() || response.content_length().is_some_and(|l| l > MAX_FAVICON_BYTES as u64) {
+        return None;
+    }
+    let declared = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(|v| v.trim().to_lowercase());
+    let bytes = response.bytes().await.ok()?;
+    if bytes.is_empty() || bytes.len() > MAX_FAVICON_BYTES {
+        return None;
+    }
+    // Servers often send icons as generic downloads; those are known by their bytes
+    let mime_type = match declared.filter(|t| t.starts_with("image/")) {
+        Some(declared) => declared,
+        None => image::guess_format(&bytes).ok()?.to_mime_type().to_string(),
+    };
+    Some(Favicon {
+        data: base64::engine::general_purpose::STANDARD.encode(&bytes),
+        mime_type,
+    })
+}
+
+async fn favicon(
+    cache: &PreviewCache,
+    client: &reqwest::Client,
+    timeout: Duration,
+    icons: Vec<Url>,
+    page: &Url,
+) -> Option<Favicon> {
+    let origin = page.origin().ascii_serialization();
+    if let Some(known) = lock(&cache.favicons).get(&origin) {
+        return known;
+    }
+    let mut found = None;
+    for candidate in icons {
+        found = fetch_favicon(client, &candidate, timeout).await;
+        if found.is_some() {
+            break;
+        }
+    }
+    lock(&cache.favicons).insert(origin, found.clone());
+    found
+}
+
+async fn fetch_preview(
+    cache: &PreviewCache,
+    client: &reqwest::Client,
+    url: &Url,
+    timeout: Duration,
+) -> Result<SitePreview, String> {
+    let response = get(client, url, timeout)
+        .header("Accept", "text/html,application/xhtml+xml;q=0.9,*/*;q=0.8")
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Request failed with status {}", response.status()));
+    }
+    let page = response.url().clone();
+    let head = read_head(response).await?;
+    let (mut preview, icons) = from_head(&head, &page);
+    if let Some(favicon) = favicon(cache, client, timeout, icons, &page).await {
+        preview.favicon = Some(favicon.data);
+        preview.favicon_mime_type = Some(favicon.mime_type);
+    }
+    Ok(preview)
+}
+
+// The preview a page's <head> gives, without the favicon, and the icons to try for that
+fn from_head(head: &str, page: &Url) -> (SitePreview, Vec<Url>) {
+    let document = html::parse(head);
+    let found = metadata::extract(head);
+    let og = found.open_graph.unwrap_or_default();
+    let card = found.twitter_card.unwrap_or_default();
+    let title_tag = document
+        .select_first("title")
+        .ok()
+        .map(|t| t.text_contents().split_whitespace().collect::<Vec<_>>().join(" "));
+    let site_name = og
+        .site_name
+        .or_else(|| metadata::meta(&document, "application-name"))
+        .or_else(|| page.host_str().map(|host| host.trim_start_matches("www.").to_string()))
+        .unwrap_or_default();
+    let preview = SitePreview {
+        url: page.to_string(),
+        site_name,
+        title: og.title.or(card.title).or(title_tag.filter(|t| !t.is_empty())),
+        description: og.description.or_else(|| metadata::meta(&document, "description")).or(card.description),
+        image_url: og.image.or(card.image).and_then(|image| page.join(&image).ok()).map(String::from),
+        favicon: None,
+        favicon_mime_type: None,
+    };
+    (preview, icon_candidates(&document, page))
+}
+
+// Favicon, site name, title, description and preview image of a page, for citation cards
+#[tauri::command]
+pub async fn get_site_preview(
+    app: AppHandle,
+    cache: State<'_, PreviewCache>,
+    url: String,
+) -> Result<SitePreview, String> {
+    let parsed = Url::parse(url.trim()).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("Only http and https URLs have previews".to_string());
+    }
+    if let Some(preview) = lock(&cache.previews).get(parsed.as_str()) {
+        return Ok(preview);
+    }
+    let settings = settings::load(&app);
+    let client = http_client::for_app(&app, &settings)?;
+    let timeout = Duration::from_secs(settings.fetch_timeout_secs);
+    let preview = fetch_preview(&cache, &client, &parsed, timeout).await?;
+    lock(&cache.previews).insert(parsed.to_string(), preview.clone());
+    Ok(preview)
+}
@@ -0,0 +1,356 @@
+// Isolated user profiles. Each profile has its own data dir (database, attachments, agent logs),
+// config dir (sync, MCP servers, the providers with API keys) and keychain entries. The default
+// profile uses the app's top-level dirs so installs from before profiles existed keep their data;
+// other profiles live under profiles/<id> inside them.
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use tauri::{AppHandle, Manager, State};
+
+use crate::blobs::BlobStore;
+use crate::db::{self, Database};
+use crate::encryption::{self, KEYCHAIN_SERVICE};
+use crate::llm::{ProviderConfig, ProviderType};
+
+const INDEX_FILE: &str = "profiles.json";
+const PROVIDERS_FILE: &str = "providers.json";
+const PROFILES_DIR: &str = "profiles";
+pub const DEFAULT_PROFILE: &str = "default";
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+}
+
+// Profile list and the active profile, kept in the top-level config dir
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfileIndex {
+    active: String,
+    profiles: Vec<Profile>,
+}
+
+// The providers a profile has API keys for; the keys are in the keychain. Requests without an
+// API key get the profile's one.
+#[derive(serde::Serialize, serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct ProviderSettings {
+    #[serde(default)]
+    providers: Vec<ProviderType>,
+    // Keys kept here by earlier versions; they move to the keychain on load
+    #[serde(default, skip_serializing)]
+    api_keys: HashMap<ProviderType, String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileInfo {
+    #[serde(flatten)]
+    profile: Profile,
+    active: bool,
+}
+
+struct ActiveProfile {
+    profile: Profile,
+    data_dir: PathBuf,
+    config_dir: PathBuf,
+}
+
+pub struct ProfileManager {
+    root_data_dir: PathBuf,
+    root_config_dir: PathBuf,
+    active: RwLock<ActiveProfile>,
+}
+
+impl ProfileIndex {
+    fn new() -> Self {
+        ProfileIndex {
+            active: DEFAULT_PROFILE.to_string(),
+            profiles: vec![Profile {
+                id: DEFAULT_PROFILE.to_string(),
+                name: "Default".to_string(),
+                created_at: chrono::Utc::now().to_rfc3339(),
+            }],
+        }
+    }
+}
+
+fn load_index(root_config_dir: &Path) -> Result<ProfileIndex, String> {
+    let path = root_config_dir.join(INDEX_FILE);
+    if !path.exists() {
+        return Ok(ProfileIndex::new());
+    }
+    let text = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&text).map_err(|e| format!("Invalid profile index: {}", e))
+}
+
+fn profile_dirs(root_data_dir: &Path, root_config_dir: &Path, id: &str) -> (PathBuf, PathBuf) {
+    if id == DEFAULT_PROFILE {
+        return (root_data_dir.to_path_buf(), root_config_dir.to_path_buf());
+    }
+    (
+        root_data_dir.join(PROFILES_DIR).join(id),
+        root_config_dir.join(PROFILES_DIR).join(id),
+    )
+}
+
+impl ProfileManager {
+    // Load the profile index and activate the profile that was active last
+    pub fn load(root_data_dir: PathBuf, root_config_dir: PathBuf) -> Result<Self, String> {
+        let index = load_index(&root_config_dir)?;
+        let profile = index
+            .profiles
+            .iter()
+            .find(|p| p.id == index.active)
+            .or_else(|| index.profiles.first())
+            .cloned()
+            .ok_or("Profile index has no profiles")?;
+        let (data_dir, config_dir) = profile_dirs(&root_data_dir, &root_config_dir, &profile.id);
+        eprintln!("[Profiles] Active profile {} ({})", profile.name, profile.id);
+        Ok(ProfileManager {
+            root_data_dir,
+            root_config_dir,
+            active: RwLock::new(ActiveProfile {
+                profile,
+                data_dir,
+                config_dir,
+            }),
+        })
+    }
+
+    fn load_index(&self) -> Result<ProfileIndex, String> {
+        load_index(&self.root_config_dir)
+    }
+
+    fn save_index(&self, index: &ProfileIndex) -> Result<(), String> {
+        std::fs::create_dir_all(&self.root_config_dir).map_err(|e| format!("Failed to create config dir: {}", e))?;
+        let json = serde_json::to_string_pretty(index).map_err(|e| e.to_string())?;
+        std::fs::write(self.root_config_dir.join(INDEX_FILE), json)
+            .map_err(|e| format!("Failed to save profile index: {}", e))
+    }
+
+    fn dirs(&self, id: &str) -> (PathBuf, PathBuf) {
+        profile_dirs(&self.root_data_dir, &self.root_config_dir, id)
+    }
+
+    fn activate(&self, profile: Profile) {
+        let (data_dir, config_dir) = self.dirs(&profile.id);
+        if let Ok(mut active) = self.active.write() {
+            *active = ActiveProfile {
+                profile,
+                data_dir,
+                config_dir,
+            };
+        }
+    }
+
+    pub fn active_id(&self) -> String {
+        self.active
+            .read()
+            .map(|a| a.profile.id.clone())
+            .unwrap_or_else(|_| DEFAULT_PROFILE.to_string())
+    }
+
+    pub fn data_dir(&self) -> PathBuf {
+        self.active
+            .read()
+            .map(|a| a.data_dir.clone())
+            .unwrap_or_else(|_| self.root_data_dir.clone())
+    }
+
+    pub fn config_dir(&self) -> PathBuf {
+        self.active
+            .read()
+            .map(|a| a.config_dir.clone())
+            .unwrap_or_else(|_| self.root_config_dir.clone())
+    }
+
+    fn provider_keys_entry(&self) -> Result<keyring::Entry, String> {
+        keyring::Entry::new(KEYCHAIN_SERVICE, &format!("provider-keys-{}", self.active_id()))
+            .map_err(|e| format!("Keychain unavailable: {}", e))
+    }
+
+    fn load_providers(&self) -> ProviderSettings {
+        let mut settings: ProviderSettings = std::fs::read_to_string(self.config_dir().join(PROVIDERS_FILE))
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        if !settings.api_keys.is_empty() {
+            let keys = std::mem::take(&mut settings.api_keys);
+            match self.save_provider_keys(&keys) {
+                Ok(()) => {
+                    eprintln!("[Profiles] Moved provider API keys to the keychain");
+                    settings.providers = keys.into_keys().collect();
+                }
+                Err(err) => eprintln!("[Profiles] Failed to move provider API keys to the keychain: {}", err),
+            }
+        }
+        settings
+    }
+
+    // The active profile's API keys by provider
+    fn provider_keys(&self) -> HashMap<ProviderType, String> {
+        if self.load_providers().providers.is_empty() {
+            return HashMap::new();
+        }
+        self.provider_keys_entry()
+            .ok()
+            .and_then(|entry| entry.get_password().ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    // Keys go to the keychain and only the provider list to providers.json
+    fn save_provider_keys(&self, keys: &HashMap<ProviderType, String>) -> Result<(), String> {
+        let entry = self.provider_keys_entry()?;
+        if keys.is_empty() {
+            match entry.delete_credential() {
+                Ok(()) | Err(keyring::Error::NoEntry) => {}
+                Err(err) => return Err(format!("Failed to remove provider API keys: {}", err)),
+            }
+        } else {
+            let json = serde_json::to_string(keys).map_err(|e| e.to_string())?;
+            entry
+                .set_password(&json)
+                .map_err(|e| format!("Failed to store provider API keys: {}", e))?;
+        }
+        let settings = ProviderSettings {
+            providers: keys.keys().copied().collect(),
+            api_keys: HashMap::new(),
+        };
+        let dir = self.config_dir();
+        create_dir(&dir)?;
+        let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+        std::fs::write(dir.join(PROVIDERS_FILE), json).map_err(|e| format!("Failed to save provider settings: {}", e))
+    }
+
+    // Every API key stored in the active profile
+    pub fn api_keys(&self) -> Vec<String> {
+        self.provider_keys().into_values().collect()
+    }
+
+    // Fill in the active profile's API key when the request didn't bring one
+    pub fn apply_credentials(&self, provider: &mut ProviderConfig) {
+        if provider.api_key.as_deref().is_some_and(|k| !k.is_empty()) {
+            return;
+        }
+        if let Some(key) = self.provider_keys().remove(&provider.provider) {
+            provider.api_key = Some(key);
+        }
+    }
+}
+
+// Data dir of the active profile
+pub fn data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.try_state::<ProfileManager>()
+        .map(|profiles| profiles.data_dir())
+        .ok_or_else(|| "Profiles are not initialized".to_string())
+}
+
+// Config dir of the active profile
+pub fn config_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.try_state::<ProfileManager>()
+        .map(|profiles| profiles.config_dir())
+        .ok_or_else(|| "Profiles are not initialized".to_string())
+}
+
+fn create_dir(path: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))
+}
+
+#[tauri::command]
+pub fn list_profiles(profiles: State<'_, ProfileManager>) -> Result<Vec<ProfileInfo>, String> {
+    let active = profiles.active_id();
+    Ok(profiles
+        .load_index()?
+        .profiles
+        .into_iter()
+        .map(|profile| ProfileInfo {
+            active: profile.id == active,
+            profile,
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub fn create_profile(profiles: State<'_, ProfileManager>, name: String) -> Result<Profile, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+    let mut index = profiles.load_index()?;
+    if index.profiles.iter().any(|p| p.name.eq_ignore_ascii_case(name)) {
+        return Err(format!("A profile named '{}' already exists", name));
+    }
+
+    let profile = Profile {
+        id: db::new_id("profile"),
+        name: name.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let (data_dir, config_dir) = profiles.dirs(&profile.id);
+    create_dir(&data_dir)?;
+    create_dir(&config_dir)?;
+    index.profiles.push(profile.clone());
+    profiles.save_index(&index)?;
+    eprintln!("[Profiles] Created profile {} ({})", profile.name, profile.id);
+    Ok(profile)
+}
+
+// Point storage, config and MCP servers at another profile
+#[tauri::command]
+pub async fn switch_profile(
+    app: AppHandle,
+    profiles: State<'_, ProfileManager>,
+    db: State<'_, Database>,
+    blobs: State<'_, BlobStore>,
+    id: String,
+) -> Result<Profile, String> {
+    let mut index = profiles.load_index()?;
+    let profile = index
+        .profiles
+        .iter()
+        .find(|p| p.id == id)
+        .cloned()
+        .ok_or_else(|| format!("Profile {} not found", id))?;
+    if profiles.active_id() == profile.id {
+        return Ok(profile);
+    }
+
+    let (data_dir, _) = profiles.dirs(&profile.id);
+    encryption::switch_storage(&data_dir, &db, &blobs)?;
+    profiles.activate(profile.clone());
+    index.active = profile.id.clone();
+    profiles.save_index(&index)?;
+
+    if let Err(err) = crate::mcp::client::start_configured_servers(&app).await {
+        eprintln!("[MCP] Failed to start servers for profile {}: {}", profile.id, err);
+    }
+    eprintln!("[Profiles] Switched to {} ({})", profile.name, profile.id);
+    Ok(profile)
+}
+
+// Store (or with an empty key, remove) the active profile's API key for a provider
+#[tauri::command]
+pub fn set_provider_api_key(
+    profiles: State<'_, ProfileManager>,
+    provider: ProviderType,
+    api_key: Option<String>,
+) -> Result<(), String> {
+    let mut keys = profiles.provider_keys();
+    match api_key.filter(|k| !k.trim().is_empty()) {
+        Some(key) => keys.insert(provider, key.trim().to_string()),
+        None => keys.remove(&provider),
+    };
+    profiles.save_provider_keys(&keys)
+}
+
+// Providers that have an API key in the active profile (the keys themselves aren't returned)
+#[tauri::command]
+pub fn list_provider_api_keys(profiles: State<'_, ProfileManager>) -> Vec<ProviderType> {
+    profiles.load_providers().providers
+}
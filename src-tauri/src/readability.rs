@@ -0,0 +1,269 @@
+// Main-article extraction in the manner of Mozilla's Readability: once boilerplate and unlikely
+// containers (comments, sidebars, share bars) are dropped, paragraphs score their parent and
+// grandparent by length and commas, scores are discounted by link density, and the best container
+// is kept together with sibling blocks that score close to it. Title, byline and publication date
+// come from the page's meta tags.
+use std::collections::HashMap;
+
+use kuchikiki::{Node, NodeRef};
+use reqwest::Url;
+
+use crate::{clean_text, html};
+
+// Class and id fragments of containers that are never the article
+const UNLIKELY: &[&str] = &[
+    "ad-", "advert", "banner", "breadcrumb", "combx", "comment", "community", "cookie", "disqus", "extra", "footer",
+    "footnote", "header", "legends", "menu", "modal", "newsletter", "outbrain", "pager", "pagination", "popup",
+    "promo", "related", "remark", "replies", "rss", "share", "shoutbox", "sidebar", "skyscraper", "social", "sponsor",
+    "subscribe", "taboola", "tags", "widget",
+];
+// Fragments that outweigh an unlikely one
+const MAYBE: &[&str] = &["and", "article", "body", "column", "content", "main", "shadow"];
+const POSITIVE: &[&str] = &[
+    "article", "blog", "body", "content", "entry", "hentry", "h-entry", "main", "page", "post", "story", "text",
+];
+const NEGATIVE: &[&str] = &[
+    "byline", "comment", "com-", "contact", "footer", "footnote", "masthead", "media", "meta", "outbrain", "promo",
+    "related", "scroll", "share", "shoutbox", "sidebar", "skyscraper", "sponsor", "shopping", "tags", "widget",
+];
+// Blocks whose text scores their ancestors
+const SCORED: &str = "p, pre, td, blockquote, section > div, article > div";
+// Shorter blocks are captions, labels and the like
+const MIN_BLOCK_CHARS: usize = 25;
+// Below this the page has no article to speak of and the whole main content is returned
+const MIN_ARTICLE_CHARS: usize = 250;
+
+pub struct Article {
+    pub title: Option<String>,
+    pub byline: Option<String>,
+    // As written in the page, usually ISO 8601
+    pub published_date: Option<String>,
+    pub text: String,
+}
+
+fn key(node: &NodeRef) -> *const Node {
+    &**node as *const Node
+}
+
+fn class_and_id(node: &NodeRef) -> String {
+    let Some(element) = node.as_element() else { return String::new() };
+    let attributes = element.attributes.borrow();
+    let (class, id) = (attributes.get("class").unwrap_or_default(), attributes.get("id").unwrap_or_default());
+    format!("{} {}", class, id).to_lowercase()
+}
+
+fn matches_any(text: &str, fragments: &[&str]) -> bool {
+    fragments.iter().any(|fragment| text.contains(fragment))
+}
+
+fn tag_name(node: &NodeRef) -> String {
+    node.as_element().map(|e| e.name.local.to_string()).unwrap_or_default()
+}
+
+fn select_all(root: &NodeRef, selector: &str) -> Vec<NodeRef> {
+    root.select(selector)
+        .map(|found| found.map(|e| e.as_node().clone()).collect())
+        .unwrap_or_default()
+}
+
+// Drop containers whose class or id marks them as page furniture
+fn remove_unlikely(document: &NodeRef) {
+    let doomed: Vec<NodeRef> = select_all(document, "body *")
+        .into_iter()
+        .filter(|node| {
+            let kept = ["a", "article", "main", "body", "table", "tbody", "tr", "td"];
+            !kept.contains(&tag_name(node).as_str())
+        })
+        .filter(|node| {
+            let names = class_and_id(node);
+            !names.trim().is_empty() && matches_any(&names, UNLIKELY) && !matches_any(&names, MAYBE)
+        })
+        .collect();
+    for node in doomed {
+        node.detach();
+    }
+}
+
+fn class_weight(node: &NodeRef) -> f64 {
+    let names = class_and_id(node);
+    let mut weight = 0.0;
+    if matches_any(&names, POSITIVE) {
+        weight += 25.0;
+    }
+    if matches_any(&names, NEGATIVE) {
+        weight -= 25.0;
+    }
+    weight
+}
+
+fn initial_score(node: &NodeRef) -> f64 {
+    let tag_bonus = match tag_name(node).as_str() {
+        "article" => 10.0,
+        "div" | "section" | "main" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    };
+    tag_bonus + class_weight(node)
+}
+
+// Share of a node's text that sits in links
+fn link_density(node: &NodeRef) -> f64 {
+    let length = clean_text(&node.text_contents()).len();
+    if length == 0 {
+        return 0.0;
+    }
+    let linked: usize = select_all(node, "a").iter().map(|a| clean_text(&a.text_contents()).len()).sum();
+    linked as f64 / length as f64
+}
+
+// The best-scoring container and the sibling blocks that belong with it
+fn best_content(document: &NodeRef) -> Option<Vec<NodeRef>> {
+    let mut candidates: Vec<(NodeRef, f64)> = Vec::new();
+    let mut positions: HashMap<*const Node, usize> = HashMap::new();
+    for block in select_all(document, SCORED) {
+        let text = clean_text(&block.text_contents());
+        if text.len() < MIN_BLOCK_CHARS {
+            continue;
+        }
+        // One point per block, one per comma, one per 100 characters up to three
+        let score = 1.0 + text.matches([',', '，']).count() as f64 + (text.len() / 100).min(3) as f64;
+        let ancestors = block.ancestors().filter(|a| a.as_element().is_some()).take(2);
+        for (level, ancestor) in ancestors.enumerate() {
+            if matches!(tag_name(&ancestor).as_str(), "html" | "body") {
+                break;
+            }
+            let position = *positions.entry(key(&ancestor)).or_insert_with(|| {
+                candidates.push((ancestor.clone(), initial_score(&ancestor)));
+                candidates.len() - 1
+            });
+            candidates[position].1 += if level == 0 { score } else { score / 2.0 };
+        }
+    }
+    let (top, top_score) = candidates
+        .iter()
+        .map(|(node, score)| (node, score * (1.0 - link_density(node))))
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+
+    let threshold = (top_score * 0.2).max(10.0);
+    let parent = top.parent()?;
+    let mut blocks = Vec::new();
+    for sibling in parent.children().filter(|n| n.as_element().is_some()) {
+        if key(&sibling) == key(top) {
+            blocks.push(sibling);
+            continue;
+        }
+        let score = positions
+            .get(&key(&sibling))
+            .map(|&i| candidates[i].1 * (1.0 - link_density(&sibling)))
+            .unwrap_or(0.0);
+        // Paragraphs split off the main container still belong to the article
+        let text = clean_text(&sibling.text_contents());
+        let loose_paragraph = tag_name(&sibling) == "p"
+            && link_density(&sibling) < 0.25
+            && (text.len() > 80 || (!text.is_empty() && text.ends_with('.')));
+        if score >= threshold || loose_paragraph {
+            blocks.push(sibling);
+        }
+    }
+    Some(blocks)
+}
+
+fn meta(document: &NodeRef, selectors: &[&str]) -> Option<String> {
+    selectors.iter().find_map(|selector| {
+        let element = document.select_first(selector).ok()?;
+        let attributes = element.attributes.borrow();
+        let value = match attributes.get("content").or_else(|| attributes.get("datetime")) {
+            Some(value) => clean_text(value),
+            None => clean_text(&element.text_contents()),
+        };
+        Some(value).filter(|v| !v.is_empty())
+    })
+}
+
+// Author names rather than profile links
+fn byline(document: &NodeRef) -> Option<String> {
+    let author = meta(
+        document,
+        &[
+            "meta[name=author]",
+            "meta[property='article:author']",
+            "meta[name='twitter:creator']",
+            "[itemprop=author] [itemprop=name]",
+            "[itemprop=author]",
+            "[rel=author]",
+            ".byline",
+            ".author",
+        ],
+    )?;
+    let author = author.trim_start_matches("By ").trim_start_matches("by ").trim().to_string();
+    let plausible = !author.starts_with("http") && author.len() <= 100;
+    plausible.then_some(author)
+}
+
+// Title without the " | Site Name" most pages append
+fn title(document: &NodeRef, site_name: Option<&str>) -> Option<String> {
+    if let Some(title) = meta(document, &["meta[property='og:title']", "meta[name='twitter:title']"]) {
+        return Some(title);
+    }
+    let Some(title) = meta(document, &["title"]) else { return meta(document, &["h1"]) };
+    for separator in [" | ", " - ", " — ", " – ", " :: "] {
+        if let Some((head, tail)) = title.rsplit_once(separator) {
+            let is_site =
+                site_name.is_some_and(|site| tail.eq_ignore_ascii_case(site)) || tail.split_whitespace().count() <= 4;
+            if is_site && head.split_whitespace().count() >= 3 {
+                return Some(head.trim().to_string());
+            }
+        }
+    }
+    Some(title)
+}
+
+// Article as text or Markdown; `base` is the page's URL, for resolving links in Markdown
+pub fn extract(html: &str, format: html::Format, base: Option<&Url>) -> Article {
+    let document = html::parse(html);
+    let site_name = meta(&document, &["meta[property='og:site_name']", "meta[name='application-name']"]);
+    let title = title(&document, site_name.as_deref());
+    let byline = byline(&document);
+    let published_date = meta(
+        &document,
+        &[
+            "meta[property='article:published_time']",
+            "meta[itemprop=datePublished]",
+            "meta[name=date]",
+            "meta[name=pubdate]",
+            "meta[name=publishdate]",
+            "meta[name='dc.date']",
+            "meta[name='DC.date.issued']",
+            "[itemprop=datePublished]",
+            "time[datetime]",
+        ],
+    );
+
+    html::remove_boilerplate(&document);
+    remove_unlikely(&document);
+    let text = best_content(&document)
+        .map(|blocks| {
+            blocks
+                .iter()
+                .map(|block| html::render_as(block, format, base))
+                .filter(|text| !text.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        })
+        .filter(|text| text.len() >= MIN_ARTICLE_CHARS)
+        // Listings, forums and the like have no single article; keep all of the main content
+        .unwrap_or_else(|| {
+            let document = html::parse(html);
+            html::remove_boilerplate(&document);
+            html::render_as(&html::main_content(document), format, base)
+        });
+
+    Article {
+        title,
+        byline,
+        published_date,
+        text,
+    }
+}
@@ -0,0 +1,225 @@
+// Cross-encoder reranking with an ONNX model (e.g. bge-reranker), built with the `rerank` feature.
+//
+// A cross-encoder reads the query and a passage together, which ranks far better than comparing
+// embeddings but is too slow for a whole collection, so it reorders the few dozen candidates that
+// retrieval returns. The model directory holds `model.onnx` and the Hugging Face `tokenizer.json`.
+// When `rerankEnabled` is set, hybrid queries and scrapes with a query are reranked automatically.
+#[cfg(feature = "rerank")]
+use std::path::PathBuf;
+#[cfg(feature = "rerank")]
+use std::sync::{Arc, Mutex};
+
+use tauri::AppHandle;
+#[cfg(feature = "rerank")]
+use tauri::Manager;
+
+use crate::settings;
+
+#[cfg(feature = "rerank")]
+mod onnx {
+    use std::borrow::Cow;
+    use std::path::Path;
+
+    use ort::session::builder::GraphOptimizationLevel;
+    use ort::session::{Session, SessionInputValue};
+    use ort::value::Tensor;
+    use tokenizers::{Tokenizer, TruncationParams, TruncationStrategy};
+
+    // Longest query + passage pair the model sees; passages are cut to fit
+    const MAX_TOKENS: usize = 512;
+    const BATCH: usize = 16;
+
+    pub struct Reranker {
+        session: Session,
+        tokenizer: Tokenizer,
+        // BERT-style models take segment ids, XLM-R based ones (bge-reranker) don't
+        token_type_ids: bool,
+    }
+
+    fn model_error(e: impl std::fmt::Display) -> String {
+        format!("Reranker error: {}", e)
+    }
+
+    impl Reranker {
+        pub fn load(dir: &Path) -> Result<Self, String> {
+            let mut tokenizer = Tokenizer::from_file(dir.join("tokenizer.json"))
+                .map_err(|e| format!("Failed to load {}: {}", dir.join("tokenizer.json").display(), e))?;
+            tokenizer
+                .with_truncation(Some(TruncationParams {
+                    max_length: MAX_TOKENS,
+                    strategy: TruncationStrategy::OnlySecond,
+                    ..Default::default()
+                }))
+                .map_err(model_error)?;
+            tokenizer.with_padding(None);
+            let session = Session::builder()
+                .and_then(|b| b.with_optimization_level(GraphOptimizationLevel::Level3))
+                .and_then(|b| b.commit_from_file(dir.join("model.onnx")))
+                .map_err(|e| format!("Failed to load {}: {}", dir.join("model.onnx").display(), e))?;
+            let token_type_ids = session.inputs.iter().any(|input| input.name == "token_type_ids");
+            Ok(Reranker {
+                session,
+                tokenizer,
+                token_type_ids,
+            })
+        }
+
+        // Relevance of each passage to the query, from 0 to 1
+        pub fn score(&self, query: &str, passages: &[String]) -> Result<Vec<f32>, String> {
+            let mut scores = Vec::with_capacity(passages.len());
+            for batch in passages.chunks(BATCH) {
+                let pairs: Vec<(&str, &str)> = batch.iter().map(|p| (query, p.as_str())).collect();
+                let encodings = self.tokenizer.encode_batch(pairs, true).map_err(model_error)?;
+                let width = encodings.iter().map(|e| e.len()).max().unwrap_or(0);
+
+                // Pad every row to the longest one; padding is masked out
+                let mut ids = vec![0i64; batch.len() * width];
+                let mut mask = vec![0i64; batch.len() * width];
+                let mut types = vec![0i64; batch.len() * width];
+                for (row, encoding) in encodings.iter().enumerate() {
+                    let offset = row * width;
+                    for (i, ((id, m), t)) in encoding
+                        .get_ids()
+                        .iter()
+                        .zip(encoding.get_attention_mask())
+                        .zip(encoding.get_type_ids())
+                        .enumerate()
+                    {
+                        ids[offset + i] = *id as i64;
+                        mask[offset + i] = *m as i64;
+                        types[offset + i] = *t as i64;
+                    }
+                }
+
+                let shape = [batch.len(), width];
+                let mut inputs: Vec<(Cow<str>, SessionInputValue)> = vec![
+                    ("input_ids".into(), Tensor::from_array((shape, ids)).map_err(model_error)?.into()),
+                    ("attention_mask".into(), Tensor::from_array((shape, mask)).map_err(model_error)?.into()),
+                ];
+                if self.token_type_ids {
+                    inputs.push(("token_type_ids".into(), Tensor::from_array((shape, types)).map_err(model_error)?.into()));
+                }
+                let outputs = self.session.run(inputs).map_err(model_error)?;
+                let (_, logits) = outputs[0].try_extract_raw_tensor::<f32>().map_err(model_error)?;
+
+                // One logit per pair, or one per class with "relevant" last
+                let per_row = logits.len() / batch.len().max(1);
+                if per_row == 0 {
+                    return Err("Reranker returned no scores".to_string());
+                }
+                scores.extend(logits.chunks(per_row).map(|row| 1.0 / (1.0 + (-row[per_row - 1]).exp())));
+            }
+            Ok(scores)
+        }
+    }
+}
+
+// The loaded model, kept until the configured directory changes
+#[derive(Default)]
+pub struct RerankerState {
+    #[cfg(feature = "rerank")]
+    loaded: Mutex<Option<(PathBuf, Arc<onnx::Reranker>)>>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RerankerStatus {
+    // Whether this build includes the ONNX runtime
+    available: bool,
+    enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RankedPassage {
+    // Position of the passage in the request
+    index: usize,
+    score: f32,
+}
+
+#[cfg(feature = "rerank")]
+fn model_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    settings::load(app)
+        .reranker_model
+        .filter(|dir| !dir.trim().is_empty())
+        .map(PathBuf::from)
+        .ok_or_else(|| "No reranker model configured".to_string())
+}
+
+#[cfg(feature = "rerank")]
+fn score(app: &AppHandle, query: &str, passages: &[String]) -> Result<Vec<f32>, String> {
+    let dir = model_dir(app)?;
+    let state = app.state::<RerankerState>();
+    let reranker = {
+        let mut loaded = state.loaded.lock().map_err(|_| "Reranker state poisoned".to_string())?;
+        match loaded.as_ref() {
+            Some((path, reranker)) if *path == dir => reranker.clone(),
+            _ => {
+                let reranker = Arc::new(onnx::Reranker::load(&dir)?);
+                eprintln!("[Rerank] Loaded model from {}", dir.display());
+                *loaded = Some((dir, reranker.clone()));
+                reranker
+            }
+        }
+    };
+    reranker.score(query, passages)
+}
+
+#[cfg(not(feature = "rerank"))]
+fn score(_app: &AppHandle, _query: &str, _passages: &[String]) -> Result<Vec<f32>, String> {
+    Err("Reranking is not available in this build".to_string())
+}
+
+// Reorder items best first by relevance to `query` when reranking is enabled, and return their
+// scores in the new order. Failures are logged and leave the order as it was, so retrieval keeps
+// working without the model.
+pub fn rerank_items<T>(app: &AppHandle, query: &str, items: &mut Vec<T>, text: impl Fn(&T) -> &str) -> Option<Vec<f32>> {
+    if !cfg!(feature = "rerank") || items.len() < 2 || !settings::load(app).rerank_enabled {
+        return None;
+    }
+    let passages: Vec<String> = items.iter().map(|item| text(item).to_string()).collect();
+    let scores = match score(app, query, &passages) {
+        Ok(scores) if scores.len() == items.len() => scores,
+        Ok(_) => {
+            eprintln!("[Rerank] Model returned the wrong number of scores");
+            return None;
+        }
+        Err(err) => {
+            eprintln!("[Rerank] {}", err);
+            return None;
+        }
+    };
+    let mut scored: Vec<(f32, T)> = scores.into_iter().zip(items.drain(..)).collect();
+    scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+    let (scores, reordered): (Vec<f32>, Vec<T>) = scored.into_iter().unzip();
+    *items = reordered;
+    Some(scores)
+}
+
+// Score passages against a query with the configured model, best first; works whether or not
+// automatic reranking is enabled
+#[tauri::command]
+pub async fn rerank(app: AppHandle, query: String, passages: Vec<String>) -> Result<Vec<RankedPassage>, String> {
+    let scores = tokio::task::spawn_blocking(move || score(&app, &query, &passages))
+        .await
+        .map_err(|e| format!("Rerank task failed: {}", e))??;
+    let mut ranked: Vec<RankedPassage> = scores
+        .into_iter()
+        .enumerate()
+        .map(|(index, score)| RankedPassage { index, score })
+        .collect();
+    ranked.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    Ok(ranked)
+}
+
+#[tauri::command]
+pub fn get_reranker_status(app: AppHandle) -> RerankerStatus {
+    let settings = settings::load(&app);
+    RerankerStatus {
+        available: cfg!(feature = "rerank"),
+        enabled: settings.rerank_enabled,
+        model: settings.reranker_model,
+    }
+}
@@ -0,0 +1,235 @@
+// Brave Search API; the subscription token is kept in the keychain per profile
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use serde_json::Value;
+use tauri::State;
+
+use super::images::ImageResult;
+use super::news::{self, NewsResult};
+use super::{plain_text, SafeSearch, SearchOptions, SearchProvider, SearchResult, TimeRange};
+use crate::encryption::KEYCHAIN_SERVICE;
+use crate::http_client::{ClientDefaults, HttpClients};
+use crate::profiles::ProfileManager;
+use crate::proxy::ProxyConfig;
+
+const WEB_SEARCH_API: &str = "https://api.search.brave.com/res/v1/web/search";
+const NEWS_SEARCH_API: &str = "https://api.search.brave.com/res/v1/news/search";
+const IMAGE_SEARCH_API: &str = "https://api.search.brave.com/res/v1/images/search";
+// Most results the endpoints return per request
+const MAX_COUNT: usize = 20;
+const MAX_NEWS_COUNT: usize = 50;
+const MAX_IMAGE_COUNT: usize = 100;
+const MAX_OFFSET: usize = 9;
+
+pub struct Brave {
+    pub api_key: String,
+    pub timeout_secs: u64,
+    pub proxy: Option<ProxyConfig>,
+    pub clients: HttpClients,
+    pub client_defaults: ClientDefaults,
+}
+
+fn api_key_entry(profiles: &ProfileManager) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, &format!("brave-search-key-{}", profiles.active_id()))
+        .map_err(|e| format!("Keychain unavailable: {}", e))
+}
+
+pub fn api_key(profiles: &ProfileManager) -> Option<String> {
+    api_key_entry(profiles).ok()?.get_password().ok()
+}
+
+impl Brave {
+    // GET one of the search endpoints; `max_count` is that endpoint's page size limit
+    async fn get(
+        &self,
+        endpoint: &str,
+        query: &str,
+        options: &SearchOptions,
+        limit: usize,
+        max_count: usize,
+    ) -> Result<Value, String> {
+        let count = limit.clamp(1, max_count);
+        let mut params = vec![("q", query.to_string()), ("count", count.to_string())];
+        // Brave pages in units of `count`
+        let page = options.page.unwrap_or(1).saturating_sub(1);
+        if page > MAX_OFFSET {
+            return Err(format!("Brave only returns the first {} pages", MAX_OFFSET + 1));
+        }
+        if page > 0 {
+            params.push(("offset", page.to_string()));
+        }
+        if let Some(language) = options.language.as_deref().filter(|l| !l.is_empty()) {
+            params.push(("search_lang", language.to_string()));
+        }
+        if let Some((country, _)) = options.region_parts() {
+            params.push(("country", country.to_string()));
+        }
+        if let Some(safe_search) = options.safe_search {
+            let level = match safe_search {
+                SafeSearch::Off => "off",
+                SafeSearch::Moderate => "moderate",
+                SafeSearch::Strict => "strict",
+            };
+            params.push(("safesearch", level.to_string()));
+        }
+        if let Some(time_range) = options.time_range {
+            let freshness = match time_range {
+                TimeRange::Day => "pd",
+                TimeRange::Week => "pw",
+                TimeRange::Month => "pm",
+                TimeRange::Year => "py",
+            };
+            params.push(("freshness", freshness.to_string()));
+        }
+
+        let client = self.clients.client(&self.client_defaults, self.proxy.as_ref())?;
+        eprintln!("[Search] Brave search for: {}", query);
+        let response = client
+            .get(endpoint)
+            .timeout(Duration::from_secs(self.timeout_secs))
+            .header("Accept", "application/json")
+            .header("X-Subscription-Token", &self.api_key)
+            .query(&params)
+            .send()
+            .await
+            .map_err(|e| format!("Brave request failed: {}", e))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(format!("Brave returned {}", status));
+        }
+        response.json().await.map_err(|e| format!("Invalid response from Brave: {}", e))
+    }
+}
+
+fn text(result: &Value, key: &str) -> String {
+    plain_text(result.get(key).and_then(|v| v.as_str()).unwrap_or_default())
+}
+
+impl SearchProvider for Brave {
+    fn name(&self) -> &'static str {
+        "brave"
+    }
+
+    fn search<'a>(
+        &'a self,
+        query: &'a str,
+        options: &'a SearchOptions,
+        limit: usize,
+    ) -> BoxFuture<'a, Result<Vec<SearchResult>, String>> {
+        Box::pin(async move {
+            let json = self.get(WEB_SEARCH_API, query, options, limit, MAX_COUNT).await?;
+            let results: Vec<SearchResult> = json
+                .pointer("/web/results")
+                .and_then(|r| r.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|result| {
+                    Some(SearchResult {
+                        url: result.get("url")?.as_str()?.to_string(),
+                        title: text(result, "title"),
+                        snippet: text(result, "description"),
+                    })
+                })
+                .take(limit)
+                .collect();
+            eprintln!("[Search] Brave returned {} results", results.len());
+            Ok(results)
+        })
+    }
+
+    fn news<'a>(
+        &'a self,
+        query: &'a str,
+        options: &'a SearchOptions,
+        limit: usize,
+    ) -> BoxFuture<'a, Result<Vec<NewsResult>, String>> {
+        Box::pin(async move {
+            let json = self.get(NEWS_SEARCH_API, query, options, limit, MAX_NEWS_COUNT).await?;
+            let results: Vec<NewsResult> = json
+                .get("results")
+                .and_then(|r| r.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|result| {
+                    let url = result.get("url")?.as_str()?.to_string();
+                    Some(NewsResult {
+                        title: text(result, "title"),
+                        snippet: text(result, "description"),
+                        source: result
+                            .pointer("/meta_url/hostname")
+                            .and_then(|h| h.as_str())
+                            .map(|h| h.trim_start_matches("www.").to_string())
+                            .or_else(|| news::host_of(&url)),
+                        published_date: result
+                            .get("page_age")
+                            .and_then(|d| d.as_str())
+                            .and_then(news::parse_date)
+                            .map(news::format_date),
+                        image: result.pointer("/thumbnail/src").and_then(|i| i.as_str()).map(str::to_string),
+                        url,
+                    })
+                })
+                .take(limit)
+                .collect();
+            eprintln!("[Search] Brave returned {} news results", results.len());
+            Ok(results)
+        })
+    }
+
+    fn images<'a>(
+        &'a self,
+        query: &'a str,
+        options: &'a SearchOptions,
+        limit: usize,
+    ) -> BoxFuture<'a, Result<Vec<ImageResult>, String>> {
+        Box::pin(async move {
+            // Image search has no pages or time ranges, and safe search is only off or strict
+            if options.page.unwrap_or(1) > 1 {
+                return Err("Brave image search has a single page".to_string());
+            }
+            let options = SearchOptions {
+                safe_search: options.safe_search.map(|s| if s == SafeSearch::Off { s } else { SafeSearch::Strict }),
+                time_range: None,
+                ..options.clone()
+            };
+            let json = self.get(IMAGE_SEARCH_API, query, &options, limit, MAX_IMAGE_COUNT).await?;
+            let results: Vec<ImageResult> = json
+                .get("results")
+                .and_then(|r| r.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|result| {
+                    let field = |pointer: &str| result.pointer(pointer).and_then(|v| v.as_str()).map(str::to_string);
+                    let dimension = |pointer: &str| result.pointer(pointer).and_then(|v| v.as_u64()).map(|v| v as u32);
+                    Some(ImageResult {
+                        title: text(result, "title"),
+                        image_url: field("/properties/url")?,
+                        thumbnail_url: field("/thumbnail/src"),
+                        width: dimension("/properties/width"),
+                        height: dimension("/properties/height"),
+                        source_url: field("/url"),
+                    })
+                })
+                .take(limit)
+                .collect();
+            eprintln!("[Search] Brave returned {} images", results.len());
+            Ok(results)
+        })
+    }
+}
+
+// Store the Brave Search API key in the keychain, or remove it when empty
+#[tauri::command]
+pub fn set_brave_api_key(profiles: State<'_, ProfileManager>, key: Option<String>) -> Result<(), String> {
+    let entry = api_key_entry(&profiles)?;
+    match key.filter(|k| !k.trim().is_empty()) {
+        Some(key) => entry
+            .set_password(key.trim())
+            .map_err(|e| format!("Failed to store Brave API key: {}", e)),
+        None => match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(err) => Err(format!("Failed to remove Brave API key: {}", err)),
+        },
+    }
+}
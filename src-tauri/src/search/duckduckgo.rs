@@ -0,0 +1,359 @@
+// DuckDuckGo's HTML endpoint, scraped: needs no key but breaks or rate-limits more often than the APIs
+use std::collections::HashSet;
+use std::time::Duration;
+
+use chrono::DateTime;
+use futures::future::BoxFuture;
+use kuchikiki::NodeRef;
+use reqwest::header::USER_AGENT;
+use reqwest::Url;
+use serde_json::Value;
+
+use super::images::ImageResult;
+use super::news::{self, NewsResult};
+use super::{plain_text, SafeSearch, SearchOptions, SearchProvider, SearchResult, TimeRange};
+use crate::clean_text;
+use crate::html;
+use crate::http_client::{ClientDefaults, HttpClients, Session, BROWSER_USER_AGENT};
+use crate::proxy::ProxyConfig;
+
+pub struct DuckDuckGo {
+    pub timeout_secs: u64,
+    pub proxy: Option<ProxyConfig>,
+    pub clients: HttpClients,
+    pub client_defaults: ClientDefaults,
+}
+
+impl SearchProvider for DuckDuckGo {
+    fn name(&self) -> &'static str {
+        "duckduckgo"
+    }
+
+    fn search<'a>(
+        &'a self,
+        query: &'a str,
+        options: &'a SearchOptions,
+        limit: usize,
+    ) -> BoxFuture<'a, Result<Vec<SearchResult>, String>> {
+        Box::pin(async move {
+            let query = query.to_string();
+            let params = form_params(options, limit);
+            let (timeout, proxy) = (Duration::from_secs(self.timeout_secs), self.proxy.clone());
+            let (clients, defaults) = (self.clients.clone(), self.client_defaults.clone());
+            let html = tokio::task::spawn_blocking(move || {
+                fetch_html(&clients.session(&defaults, proxy.as_ref(), timeout)?, &query, &params)
+            })
+                .await
+                .map_err(|e| format!("Task error: {}", e))??;
+            parse_results(&html, limit)
+        })
+    }
+
+    fn news<'a>(
+        &'a self,
+        query: &'a str,
+        options: &'a SearchOptions,
+        limit: usize,
+    ) -> BoxFuture<'a, Result<Vec<NewsResult>, String>> {
+        Box::pin(news_search(query, options, limit, self))
+    }
+
+    fn images<'a>(
+        &'a self,
+        query: &'a str,
+        options: &'a SearchOptions,
+        limit: usize,
+    ) -> BoxFuture<'a, Result<Vec<ImageResult>, String>> {
+        Box::pin(image_search(query, options, limit, self))
+    }
+}
+
+// news.js only answers with the vqd token of a search page for the same query
+fn extract_vqd(html: &str) -> Option<String> {
+    [("vqd=\"", '"'), ("vqd='", '\''), ("vqd=", '&')].iter().find_map(|(start, end)| {
+        let rest = &html[html.find(start)? + start.len()..];
+        let token = &rest[..rest.find(*end)?];
+        (!token.is_empty() && token.len() < 100).then(|| token.to_string())
+    })
+}
+
+// GET one of the JSON endpoints behind the search page (news.js, i.js) with the query's token
+async fn vertical(
+    endpoint: &str,
+    tab: &str,
+    query: &str,
+    params: Vec<(&str, String)>,
+    engine: &DuckDuckGo,
+) -> Result<Value, String> {
+    let client = engine.clients.client(&engine.client_defaults, engine.proxy.as_ref())?;
+    let timeout = Duration::from_secs(engine.timeout_secs);
+    eprintln!("[Search] DuckDuckGo {} search for: {}", tab, query);
+    let page = client
+        .get("https://duckduckgo.com/")
+        .header(USER_AGENT, BROWSER_USER_AGENT)
+        .timeout(timeout)
+        .query(&[("q", query), ("ia", tab)])
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read response body: {}", e))?;
+    let vqd = extract_vqd(&page).ok_or("DuckDuckGo returned no search token (vqd)")?;
+
+    let response = client
+        .get(endpoint)
+        .header(USER_AGENT, BROWSER_USER_AGENT)
+        .header("Referer", "https://duckduckgo.com/")
+        .timeout(timeout)
+        .query(&[("q", query), ("vqd", &vqd), ("o", "json")])
+        .query(&params)
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("DuckDuckGo {} returned {}", tab, response.status()));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid response from DuckDuckGo {}: {}", tab, e))
+}
+
+async fn news_search(
+    query: &str,
+    options: &SearchOptions,
+    limit: usize,
+    engine: &DuckDuckGo,
+) -> Result<Vec<NewsResult>, String> {
+    // Same fields as the HTML form, under news.js's names
+    let mut params = vec![("noamp", "1".to_string())];
+    for (key, value) in form_params(options, limit) {
+        match key {
+            "kl" => params.push(("l", value)),
+            "kp" => params.push(("p", value)),
+            "df" | "s" => params.push((key, value)),
+            _ => {}
+        }
+    }
+    let json = vertical("https://duckduckgo.com/news.js", "news", query, params, engine).await?;
+
+    let results: Vec<NewsResult> = json
+        .get("results")
+        .and_then(|r| r.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|result| {
+            let url = result.get("url")?.as_str()?.to_string();
+            let text = |key: &str| result.get(key).and_then(|v| v.as_str()).filter(|v| !v.is_empty());
+            Some(NewsResult {
+                title: plain_text(text("title").unwrap_or_default()),
+                snippet: plain_text(text("excerpt").unwrap_or_default()),
+                source: text("source").map(str::to_string).or_else(|| news::host_of(&url)),
+                published_date: result
+                    .get("date")
+                    .and_then(|d| d.as_i64())
+                    .and_then(|secs| DateTime::from_timestamp(secs, 0))
+                    .map(news::format_date),
+                image: text("image").map(str::to_string),
+                url,
+            })
+        })
+        .take(limit)
+        .collect();
+    eprintln!("[Search] DuckDuckGo returned {} news results", results.len());
+    Ok(results)
+}
+
+async fn image_search(
+    query: &str,
+    options: &SearchOptions,
+    limit: usize,
+    engine: &DuckDuckGo,
+) -> Result<Vec<ImageResult>, String> {
+    let region = form_params(options, limit).into_iter().find(|(key, _)| *key == "kl").map(|(_, v)| v);
+    // Images only know safe search on or off
+    let safe = if options.safe_search == Some(SafeSearch::Off) { "-1" } else { "1" };
+    let time = match options.time_range {
+        Some(TimeRange::Day) => "time:Day",
+        Some(TimeRange::Week) => "time:Week",
+        Some(TimeRange::Month) => "time:Month",
+        Some(TimeRange::Year) => "time:Year",
+        None => "",
+    };
+    let mut params = vec![
+        ("l", region.unwrap_or_default()),
+        ("p", safe.to_string()),
+        ("f", format!("{},,,,,", time)),
+    ];
+    let offset = options.offset(limit);
+    if offset > 0 {
+        params.push(("s", offset.to_string()));
+    }
+    let json = vertical("https://duckduckgo.com/i.js", "images", query, params, engine).await?;
+
+    let results: Vec<ImageResult> = json
+        .get("results")
+        .and_then(|r| r.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|result| {
+            let text = |key: &str| result.get(key).and_then(|v| v.as_str()).filter(|v| !v.is_empty());
+            let dimension = |key: &str| result.get(key).and_then(|v| v.as_u64()).map(|v| v as u32);
+            Some(ImageResult {
+                title: plain_text(text("title").unwrap_or_default()),
+                image_url: text("image")?.to_string(),
+                thumbnail_url: text("thumbnail").map(str::to_string),
+                width: dimension("width"),
+                height: dimension("height"),
+                source_url: text("url").map(str::to_string),
+            })
+        })
+        .take(limit)
+        .collect();
+    eprintln!("[Search] DuckDuckGo returned {} images", results.len());
+    Ok(results)
+}
+
+// Form fields for region (kl), safe search (kp), time range (df) and the result offset (s)
+pub fn form_params(options: &SearchOptions, limit: usize) -> Vec<(&'static str, String)> {
+    let region = match options.region_parts() {
+        Some((country, language)) => format!("{}-{}", country, language),
+        None => "wt-wt".to_string(),
+    };
+    let mut params = vec![("b", String::new()), ("kl", region)];
+    if let Some(safe_search) = options.safe_search {
+        let kp = match safe_search {
+            SafeSearch::Strict => "1",
+            SafeSearch::Moderate => "-1",
+            SafeSearch::Off => "-2",
+        };
+        params.push(("kp", kp.to_string()));
+    }
+    if let Some(time_range) = options.time_range {
+        let df = match time_range {
+            TimeRange::Day => "d",
+            TimeRange::Week => "w",
+            TimeRange::Month => "m",
+            TimeRange::Year => "y",
+        };
+        params.push(("df", df.to_string()));
+    }
+    let offset = options.offset(limit);
+    if offset > 0 {
+        params.push(("s", offset.to_string()));
+        params.push(("dc", (offset + 1).to_string()));
+    }
+    params
+}
+
+// The result page on `session`, which gives the timeout and proxy
+pub fn fetch_html(session: &Session, query: &str, params: &[(&str, String)]) -> Result<String, String> {
+    // reqwest automatically handles decompression when using .text()
+    // The key is to NOT manually set Accept-Encoding header
+
+    // DuckDuckGo requires POST request with form data
+    let mut form = vec![("q", query)];
+    form.extend(params.iter().map(|(key, value)| (*key, value.as_str())));
+
+    eprintln!("Searching DuckDuckGo for: {}", query);
+
+    let request = session
+        .post("https://html.duckduckgo.com/html/")
+        .form(&form)
+        .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8")
+        .header("Accept-Language", "en-US,en;q=0.9")
+        // NOTE: Do NOT set Accept-Encoding - let reqwest handle it automatically
+        .header("DNT", "1")
+        .header("Connection", "keep-alive")
+        .header("Upgrade-Insecure-Requests", "1");
+    let response = session.send(request)?.response;
+
+    eprintln!("Response status: {}", response.status());
+
+    if !response.status().is_success() {
+        return Err(format!("Request failed with status {}", response.status()));
+    }
+
+    // Using .text() automatically handles decompression
+    let text = response
+        .text()
+        .map_err(|err| format!("Failed to read response body: {err}"))?;
+
+    eprintln!("Received {} characters of text", text.len());
+
+    Ok(text)
+}
+
+fn has_class(node: &NodeRef, class: &str) -> bool {
+    node.as_element().is_some_and(|e| {
+        e.attributes
+            .borrow()
+            .get("class")
+            .is_some_and(|classes| classes.split_whitespace().any(|c| c == class))
+    })
+}
+
+// Result links point at DuckDuckGo's redirect (//duckduckgo.com/l/?uddg=<target>); unwrap it
+fn decode_result_url(href: &str) -> Option<String> {
+    let absolute = if href.starts_with("//") {
+        format!("https:{}", href)
+    } else if href.starts_with('/') {
+        format!("https://duckduckgo.com{}", href)
+    } else {
+        href.to_string()
+    };
+    let url = Url::parse(&absolute).ok()?;
+    let target = if url.host_str().is_some_and(|h| h.ends_with("duckduckgo.com")) {
+        // Anything else on duckduckgo.com (ads via y.js, internal links) is not a result
+        if url.path() != "/l/" {
+            return None;
+        }
+        let (_, target) = url.query_pairs().find(|(key, _)| key == "uddg")?;
+        Url::parse(&target).ok()?
+    } else {
+        url
+    };
+    matches!(target.scheme(), "http" | "https").then(|| target.to_string())
+}
+
+// Organic results of a html.duckduckgo.com page, ads skipped, at most `limit`
+pub fn parse_results(html: &str, limit: usize) -> Result<Vec<SearchResult>, String> {
+    let document = html::parse(html);
+    let Ok(blocks) = document.select(".result") else { return Ok(Vec::new()) };
+
+    let mut seen = HashSet::new();
+    let mut results = Vec::new();
+    for block in blocks {
+        let node = block.as_node();
+        if has_class(node, "result--ad") {
+            continue;
+        }
+        let Ok(link) = node.select_first(".result__a") else { continue };
+        let href = link.attributes.borrow().get("href").unwrap_or_default().to_string();
+        let Some(url) = decode_result_url(&href) else { continue };
+        if !seen.insert(url.clone()) {
+            continue;
+        }
+        let title = clean_text(&link.text_contents());
+        let snippet = node
+            .select_first(".result__snippet")
+            .map(|s| clean_text(&s.text_contents()))
+            .unwrap_or_default();
+        results.push(SearchResult {
+            title: if title.is_empty() { url.clone() } else { title },
+            url,
+            snippet,
+        });
+        if results.len() >= limit {
+            break;
+        }
+    }
+
+    // DuckDuckGo answers suspected bots with a captcha page instead of results
+    if results.is_empty() && document.select_first(".anomaly-modal__modal, #challenge-form").is_ok() {
+        return Err("DuckDuckGo refused the search with a bot check, try again later".to_string());
+    }
+    eprintln!("[Search] Parsed {} DuckDuckGo results", results.len());
+    Ok(results)
+}
@@ -0,0 +1,47 @@
+// Image search through the provider chain. Results carry the full image and a thumbnail for the
+// chat UI; `prepare_image_url` turns a full image into input for vision models.
+use tauri::AppHandle;
+
+use super::{failover, SearchOptions, SearchResponse};
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageResult {
+    pub title: String,
+    pub image_url: String,
+    pub thumbnail_url: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    // Page the image appears on
+    pub source_url: Option<String>,
+}
+
+// "1920x1080" or "1920 x 1080"
+pub fn parse_resolution(text: &str) -> Option<(u32, u32)> {
+    let (width, height) = text.split_once(['x', 'X', '×'])?;
+    Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+}
+
+// Images for a query; `count` defaults to 20
+#[tauri::command]
+pub async fn search_images(
+    app: AppHandle,
+    query: String,
+    count: Option<usize>,
+    options: Option<SearchOptions>,
+) -> Result<SearchResponse<ImageResult>, String> {
+    let query = query.trim().to_string();
+    if query.is_empty() {
+        return Err("Search query cannot be empty".to_string());
+    }
+    let options = SearchOptions {
+        max_results: Some(count.unwrap_or(20)),
+        ..options.unwrap_or_default()
+    };
+    let owned = options.clone();
+    failover(&app, &options, move |provider, limit| {
+        let (query, options) = (query.clone(), owned.clone());
+        Box::pin(async move { provider.images(&query, &options, limit).await })
+    })
+    .await
+}
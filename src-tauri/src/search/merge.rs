@@ -0,0 +1,131 @@
+// Merging the result lists of several providers into one: URLs are canonicalized so the same page
+// found by two engines is recognized, and the lists are fused by reciprocal rank.
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use reqwest::Url;
+
+use super::images::ImageResult;
+use super::news::NewsResult;
+use super::SearchResult;
+use crate::vector::RRF_K;
+
+// Query parameters that only track where a click came from
+const TRACKING_PARAMS: &[&str] = &[
+    "fbclid", "gclid", "gclsrc", "dclid", "msclkid", "yclid", "igshid", "mc_cid", "mc_eid", "_hsenc", "_hsmi",
+    "mkt_tok", "ref_src", "ref_url", "spm", "vero_id", "oly_anon_id", "oly_enc_id", "wickedid",
+];
+
+fn is_tracking(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    key.starts_with("utm_") || TRACKING_PARAMS.contains(&key.as_str())
+}
+
+pub trait Mergeable {
+    // The URL that identifies the result
+    fn url_mut(&mut self) -> &mut String;
+}
+
+impl Mergeable for SearchResult {
+    fn url_mut(&mut self) -> &mut String {
+        &mut self.url
+    }
+}
+
+impl Mergeable for NewsResult {
+    fn url_mut(&mut self) -> &mut String {
+        &mut self.url
+    }
+}
+
+impl Mergeable for ImageResult {
+    fn url_mut(&mut self) -> &mut String {
+        &mut self.image_url
+    }
+}
+
+// The URL without tracking parameters or fragment
+pub fn clean_url(url: &str) -> String {
+    let Ok(mut parsed) = Url::parse(url) else { return url.to_string() };
+    // Rewriting the query re-encodes it, so leave URLs without tracking parameters alone
+    if parsed.query_pairs().any(|(key, _)| is_tracking(&key)) {
+        let kept: Vec<(String, String)> = parsed
+            .query_pairs()
+            .filter(|(key, _)| !is_tracking(key))
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect();
+        if kept.is_empty() {
+            parsed.set_query(None);
+        } else {
+            parsed.query_pairs_mut().clear().extend_pairs(kept);
+        }
+    }
+    parsed.set_fragment(None);
+    parsed.to_string()
+}
+
+// Key under which variants of one page compare equal: no scheme, www., trailing slash, tracking
+// parameters or fragment, and the remaining parameters sorted
+pub fn canonical_url(url: &str) -> String {
+    let Ok(parsed) = Url::parse(&clean_url(url)) else { return url.trim().to_string() };
+    let host = parsed.host_str().unwrap_or_default();
+    let host = host.strip_prefix("www.").unwrap_or(host);
+    let port = parsed.port().map(|p| format!(":{}", p)).unwrap_or_default();
+    let path = parsed.path().trim_end_matches('/');
+    let mut params: Vec<(String, String)> = parsed.query_pairs().map(|(k, v)| (k.into_owned(), v.into_owned())).collect();
+    params.sort();
+    let query: Vec<String> = params.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+    format!("{}{}{}?{}", host, port, path, query.join("&"))
+}
+
+struct Fused<T> {
+    result: T,
+    score: f32,
+    // Best rank the page had in any list
+    rank: usize,
+    https: bool,
+}
+
+// Fuse ranked lists by reciprocal rank; each page appears once, as returned by the list that
+// ranked it highest, with an https URL when any list had one
+pub fn fuse<T: Mergeable>(lists: Vec<Vec<T>>, limit: usize) -> Vec<T> {
+    let mut fused: Vec<Fused<T>> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for list in lists {
+        for (rank, mut result) in list.into_iter().enumerate() {
+            let url = result.url_mut();
+            *url = clean_url(url);
+            let key = canonical_url(url);
+            let https = url.starts_with("https:");
+            let score = 1.0 / (RRF_K + (rank + 1) as f32);
+            match positions.get(&key) {
+                Some(&position) => {
+                    let entry = &mut fused[position];
+                    entry.score += score;
+                    entry.https |= https;
+                    if rank < entry.rank {
+                        entry.result = result;
+                        entry.rank = rank;
+                    }
+                }
+                None => {
+                    positions.insert(key, fused.len());
+                    fused.push(Fused { result, score, rank, https });
+                }
+            }
+        }
+    }
+    // Stable, so equal scores keep the order of the first list
+    fused.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+    fused
+        .into_iter()
+        .take(limit)
+        .map(|mut entry| {
+            let url = entry.result.url_mut();
+            if let Some(rest) = url.strip_prefix("http:").filter(|_| entry.https) {
+                *url = format!("https:{}", rest);
+            }
+            entry.result
+        })
+        .collect()
+}
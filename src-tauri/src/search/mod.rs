@@ -0,0 +1,363 @@
+// Web search through a chain of providers (Brave, SearxNG, DuckDuckGo scraping): each is tried in
+// the configured order until one returns results, so a broken or rate-limited engine fails over.
+use std::sync::Arc;
+
+use futures::future::{join_all, BoxFuture};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+
+use crate::clean_text;
+use crate::http_client::{ClientDefaults, HttpClients};
+use crate::profiles::ProfileManager;
+use crate::proxy::{self, ProxyConfig};
+use crate::settings::{self, Settings};
+use crate::tools::{string_arg, Tool, ToolParameters, ToolRegistry};
+
+pub mod brave;
+pub mod duckduckgo;
+pub mod images;
+mod merge;
+pub mod news;
+mod searxng;
+
+use brave::Brave;
+use duckduckgo::DuckDuckGo;
+use images::ImageResult;
+use merge::Mergeable;
+use news::NewsResult;
+use searxng::Searxng;
+
+pub const PROVIDERS: &[&str] = &["brave", "searxng", "duckduckgo"];
+pub const DEFAULT_MAX_RESULTS: usize = 10;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct SearchResult {
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
+}
+
+// Snippets mark the query terms with <strong>/<b> and escape entities
+fn plain_text(html_text: &str) -> String {
+    clean_text(&crate::html::parse(html_text).text_contents())
+}
+
+#[derive(serde::Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchOptions {
+    pub max_results: Option<usize>,
+    // Providers to try, in order, instead of the configured chain
+    pub providers: Option<Vec<String>>,
+    // SearxNG categories (general, news, science, ...) and engines as configured on the instance
+    pub categories: Vec<String>,
+    pub engines: Vec<String>,
+    // Result language, e.g. en or de
+    pub language: Option<String>,
+    // Country and language as DuckDuckGo writes them, e.g. us-en or de-de
+    pub region: Option<String>,
+    pub safe_search: Option<SafeSearch>,
+    // Only results published within this range
+    pub time_range: Option<TimeRange>,
+    // 1-based page of `max_results` results
+    pub page: Option<usize>,
+    // Query every provider in the chain and merge their results; defaults to the searchMerge setting
+    pub merge: Option<bool>,
+    // Proxy URL for this search instead of the networkProxy setting
+    pub proxy: Option<String>,
+}
+
+#[derive(serde::Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SafeSearch {
+    Off,
+    Moderate,
+    Strict,
+}
+
+#[derive(serde::Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeRange {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl SearchOptions {
+    // Results to skip for the requested page
+    pub fn offset(&self, limit: usize) -> usize {
+        self.page.unwrap_or(1).saturating_sub(1) * limit
+    }
+
+    // ("us", "en") from a us-en region; wt-wt is DuckDuckGo's "no region"
+    pub fn region_parts(&self) -> Option<(&str, &str)> {
+        self.region
+            .as_deref()?
+            .split_once('-')
+            .filter(|(c, l)| !c.is_empty() && !l.is_empty() && *c != "wt")
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderFailure {
+    pub provider: String,
+    pub error: String,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResponse<T = SearchResult> {
+    // Provider the results came from
+    pub provider: Option<String>,
+    pub results: Vec<T>,
+    // Providers tried before it, and why they were skipped
+    pub failures: Vec<ProviderFailure>,
+    // When merging, every provider that contributed results
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub merged: Vec<String>,
+}
+
+pub trait SearchProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn search<'a>(
+        &'a self,
+        query: &'a str,
+        options: &'a SearchOptions,
+        limit: usize,
+    ) -> BoxFuture<'a, Result<Vec<SearchResult>, String>>;
+
+    fn news<'a>(
+        &'a self,
+        _query: &'a str,
+        _options: &'a SearchOptions,
+        _limit: usize,
+    ) -> BoxFuture<'a, Result<Vec<NewsResult>, String>> {
+        let name = self.name();
+        Box::pin(async move { Err(format!("{} has no news search", name)) })
+    }
+
+    fn images<'a>(
+        &'a self,
+        _query: &'a str,
+        _options: &'a SearchOptions,
+        _limit: usize,
+    ) -> BoxFuture<'a, Result<Vec<ImageResult>, String>> {
+        let name = self.name();
+        Box::pin(async move { Err(format!("{} has no image search", name)) })
+    }
+}
+
+fn provider(
+    app: &AppHandle,
+    settings: &Settings,
+    name: &str,
+    proxy: Option<&ProxyConfig>,
+) -> Result<Box<dyn SearchProvider>, String> {
+    let (timeout_secs, proxy) = (settings.fetch_timeout_secs, proxy.cloned());
+    let clients = app.state::<HttpClients>().inner().clone();
+    let client_defaults = ClientDefaults::from_settings(settings);
+    match name {
+        "brave" => {
+            let api_key = app
+                .try_state::<ProfileManager>()
+                .and_then(|profiles| brave::api_key(&profiles))
+                .ok_or("no API key configured")?;
+            Ok(Box::new(Brave {
+                api_key,
+                timeout_secs,
+                proxy,
+                clients,
+                client_defaults,
+            }))
+        }
+        "searxng" => {
+            let instance = settings.searxng_url.clone().ok_or("no instance URL (searxngUrl) configured")?;
+            Ok(Box::new(Searxng {
+                instance,
+                timeout_secs,
+                proxy,
+                clients,
+                client_defaults,
+            }))
+        }
+        "duckduckgo" => Ok(Box::new(DuckDuckGo { timeout_secs, proxy, clients, client_defaults })),
+        other => Err(format!("unknown search provider {}", other)),
+    }
+}
+
+fn validate(options: &SearchOptions) -> Result<(), String> {
+    if options.page == Some(0) {
+        return Err("page starts at 1".to_string());
+    }
+    if options.region.as_deref().is_some_and(|r| r != "wt-wt") && options.region_parts().is_none() {
+        return Err("region must look like us-en (country-language)".to_string());
+    }
+    Ok(())
+}
+
+// Try the providers in order. An empty result list also moves on, since scraped engines return
+// nothing when they break; it is only returned when no provider found anything. In merge mode all
+// providers are queried at once and their results fused instead.
+async fn failover<T, F>(app: &AppHandle, options: &SearchOptions, mut call: F) -> Result<SearchResponse<T>, String>
+where
+    T: Mergeable,
+    F: FnMut(Box<dyn SearchProvider>, usize) -> BoxFuture<'static, Result<Vec<T>, String>>,
+{
+    validate(options)?;
+    let settings = settings::load(app);
+    let chain = options.providers.clone().unwrap_or_else(|| settings.search_providers.clone());
+    let limit = options.max_results.unwrap_or(DEFAULT_MAX_RESULTS).max(1);
+    let proxy = proxy::for_request(&settings, options.proxy.as_deref())?;
+
+    let mut failures = Vec::new();
+    let mut providers = Vec::new();
+    for name in chain {
+        match provider(app, &settings, &name, proxy.as_ref()) {
+            Ok(provider) => providers.push(provider),
+            Err(error) => {
+                eprintln!("[Search] Skipping {}: {}", name, error);
+                failures.push(ProviderFailure { provider: name, error });
+            }
+        }
+    }
+    if options.merge.unwrap_or(settings.search_merge) && providers.len() > 1 {
+        return merge_all(providers, failures, limit, call).await;
+    }
+
+    let mut answered = None;
+    for provider in providers {
+        let name = provider.name();
+        match call(provider, limit).await {
+            Ok(results) if !results.is_empty() => {
+                return Ok(SearchResponse {
+                    provider: Some(name.to_string()),
+                    results,
+                    failures,
+                    merged: Vec::new(),
+                })
+            }
+            Ok(_) => {
+                eprintln!("[Search] {} found nothing, trying the next provider", name);
+                answered.get_or_insert(name.to_string());
+            }
+            Err(error) => {
+                eprintln!("[Search] {} failed: {}", name, error);
+                failures.push(ProviderFailure { provider: name.to_string(), error });
+            }
+        }
+    }
+    if answered.is_none() {
+        return Err(all_failed(&failures));
+    }
+    Ok(SearchResponse {
+        provider: answered,
+        results: Vec::new(),
+        failures,
+        merged: Vec::new(),
+    })
+}
+
+fn all_failed(failures: &[ProviderFailure]) -> String {
+    let reasons: Vec<String> = failures.iter().map(|f| format!("{}: {}", f.provider, f.error)).collect();
+    format!("All search providers failed ({})", reasons.join("; "))
+}
+
+// Query all providers concurrently and fuse their lists, in chain order for ties
+async fn merge_all<T, F>(
+    providers: Vec<Box<dyn SearchProvider>>,
+    mut failures: Vec<ProviderFailure>,
+    limit: usize,
+    mut call: F,
+) -> Result<SearchResponse<T>, String>
+where
+    T: Mergeable,
+    F: FnMut(Box<dyn SearchProvider>, usize) -> BoxFuture<'static, Result<Vec<T>, String>>,
+{
+    let names: Vec<&'static str> = providers.iter().map(|p| p.name()).collect();
+    let outcomes = join_all(providers.into_iter().map(|provider| call(provider, limit))).await;
+
+    let mut merged = Vec::new();
+    let mut lists = Vec::new();
+    let mut answered = false;
+    for (name, outcome) in names.into_iter().zip(outcomes) {
+        match outcome {
+            Ok(results) => {
+                answered = true;
+                if !results.is_empty() {
+                    merged.push(name.to_string());
+                    lists.push(results);
+                }
+            }
+            Err(error) => {
+                eprintln!("[Search] {} failed: {}", name, error);
+                failures.push(ProviderFailure { provider: name.to_string(), error });
+            }
+        }
+    }
+    if !answered {
+        return Err(all_failed(&failures));
+    }
+    let results = merge::fuse(lists, limit);
+    eprintln!("[Search] Merged {} results from {}", results.len(), merged.join(", "));
+    Ok(SearchResponse {
+        provider: merged.first().cloned(),
+        results,
+        failures,
+        merged,
+    })
+}
+
+pub async fn web_search(app: &AppHandle, query: &str, options: &SearchOptions) -> Result<SearchResponse, String> {
+    let (query, owned) = (query.to_string(), options.clone());
+    failover(app, options, move |provider, limit| {
+        let (query, options) = (query.clone(), owned.clone());
+        Box::pin(async move { provider.search(&query, &options, limit).await })
+    })
+    .await
+}
+
+// Search the web with the configured provider chain
+#[tauri::command]
+pub async fn search(app: AppHandle, query: String, options: Option<SearchOptions>) -> Result<SearchResponse, String> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Err("Search query cannot be empty".to_string());
+    }
+    web_search(&app, query, &options.unwrap_or_default()).await
+}
+
+// Replaces the builtin DuckDuckGo-only web_search tool with the configured chain
+pub fn register_tools(registry: &ToolRegistry, app: &AppHandle) {
+    registry.register(Arc::new(WebSearchTool { app: app.clone() }));
+}
+
+struct WebSearchTool {
+    app: AppHandle,
+}
+
+impl Tool for WebSearchTool {
+    fn name(&self) -> &str {
+        "web_search"
+    }
+
+    fn description(&self) -> &str {
+        "Search the web and return the top results"
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        ToolParameters::new().required("query", "string", "Search query")
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move {
+            let query = string_arg(&args, "query")?;
+            let options = SearchOptions {
+                max_results: Some(5),
+                ..SearchOptions::default()
+            };
+            let response = web_search(&self.app, &query, &options).await?;
+            serde_json::to_string_pretty(&response.results).map_err(|e| e.to_string())
+        })
+    }
+}
@@ -0,0 +1,103 @@
+// News search: articles with their publication date and source, optionally limited to those
+// published since a given date. Runs through the same provider chain as web search.
+use chrono::{DateTime, NaiveDate, NaiveDateTime, SecondsFormat, Utc};
+use tauri::AppHandle;
+
+use super::{failover, SearchOptions, SearchResponse, TimeRange};
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NewsResult {
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
+    // Publisher, e.g. Reuters, or the site's host name when the engine doesn't name it
+    pub source: Option<String>,
+    // RFC 3339, UTC
+    pub published_date: Option<String>,
+    pub image: Option<String>,
+}
+
+pub fn format_date(date: DateTime<Utc>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+// RFC 3339, or the naive forms engines use (2024-06-10T13:00:05, 2024-06-10 13:00:05, 2024-06-10), read as UTC
+pub fn parse_date(text: &str) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(text) {
+        return Some(date.with_timezone(&Utc));
+    }
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .or_else(|| NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0))
+        .map(|naive| naive.and_utc())
+}
+
+// Host name without www., for engines that don't name the publisher
+pub fn host_of(url: &str) -> Option<String> {
+    let host = reqwest::Url::parse(url).ok()?.host_str()?.to_string();
+    Some(host.strip_prefix("www.").map(str::to_string).unwrap_or(host))
+}
+
+// Narrowest engine time range that still covers everything since `since`
+fn time_range_since(since: DateTime<Utc>, now: DateTime<Utc>) -> Option<TimeRange> {
+    let days = (now - since).num_days();
+    match days {
+        ..=0 => Some(TimeRange::Day),
+        1..=6 => Some(TimeRange::Week),
+        7..=30 => Some(TimeRange::Month),
+        31..=364 => Some(TimeRange::Year),
+        _ => None,
+    }
+}
+
+pub async fn news_search(
+    app: &AppHandle,
+    query: &str,
+    since: Option<DateTime<Utc>>,
+    options: &SearchOptions,
+) -> Result<SearchResponse<NewsResult>, String> {
+    let mut options = options.clone();
+    if let Some(since) = since {
+        options.time_range = options.time_range.or_else(|| time_range_since(since, Utc::now()));
+    }
+    let (query, owned) = (query.to_string(), options.clone());
+    let mut response = failover(app, &options, move |provider, limit| {
+        let (query, options) = (query.clone(), owned.clone());
+        Box::pin(async move { provider.news(&query, &options, limit).await })
+    })
+    .await?;
+
+    // Engine time ranges are coarse; undated articles can't be shown to be recent
+    if let Some(since) = since {
+        response.results.retain(|result| {
+            result
+                .published_date
+                .as_deref()
+                .and_then(parse_date)
+                .is_some_and(|date| date >= since)
+        });
+    }
+    Ok(response)
+}
+
+// News articles for a query; `since` (RFC 3339 or YYYY-MM-DD) drops anything published earlier
+#[tauri::command]
+pub async fn search_news(
+    app: AppHandle,
+    query: String,
+    since: Option<String>,
+    options: Option<SearchOptions>,
+) -> Result<SearchResponse<NewsResult>, String> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Err("Search query cannot be empty".to_string());
+    }
+    let since = match since.as_deref().filter(|s| !s.trim().is_empty()) {
+        Some(text) => Some(parse_date(text).ok_or_else(|| format!("Invalid date: {}", text))?),
+        None => None,
+    };
+    news_search(&app, query, since, &options.unwrap_or_default()).await
+}
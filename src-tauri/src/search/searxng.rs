@@ -0,0 +1,219 @@
+// A user-supplied SearxNG instance, queried through its JSON API
+use std::collections::HashSet;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use reqwest::Url;
+use serde_json::Value;
+
+use super::images::{self, ImageResult};
+use super::news::{self, NewsResult};
+use super::{SafeSearch, SearchOptions, SearchProvider, SearchResult, TimeRange};
+use crate::clean_text;
+use crate::http_client::{ClientDefaults, HttpClients};
+use crate::proxy::ProxyConfig;
+
+pub struct Searxng {
+    pub instance: String,
+    pub timeout_secs: u64,
+    pub proxy: Option<ProxyConfig>,
+    pub clients: HttpClients,
+    pub client_defaults: ClientDefaults,
+}
+
+impl SearchProvider for Searxng {
+    fn name(&self) -> &'static str {
+        "searxng"
+    }
+
+    fn search<'a>(
+        &'a self,
+        query: &'a str,
+        options: &'a SearchOptions,
+        limit: usize,
+    ) -> BoxFuture<'a, Result<Vec<SearchResult>, String>> {
+        Box::pin(async move {
+            let results: Vec<SearchResult> = self
+                .results(query, options, &options.categories)
+                .await?
+                .iter()
+                .filter_map(|result| {
+                    let url = result.get("url")?.as_str()?.to_string();
+                    let title = text(result, "title");
+                    Some(SearchResult {
+                        title: if title.is_empty() { url.clone() } else { title },
+                        snippet: text(result, "content"),
+                        url,
+                    })
+                })
+                .take(limit)
+                .collect();
+            eprintln!("[Search] SearxNG returned {} results", results.len());
+            Ok(results)
+        })
+    }
+
+    fn news<'a>(
+        &'a self,
+        query: &'a str,
+        options: &'a SearchOptions,
+        limit: usize,
+    ) -> BoxFuture<'a, Result<Vec<NewsResult>, String>> {
+        Box::pin(async move {
+            let results: Vec<NewsResult> = self
+                .results(query, options, &["news".to_string()])
+                .await?
+                .iter()
+                .filter_map(|result| {
+                    let url = result.get("url")?.as_str()?.to_string();
+                    Some(NewsResult {
+                        title: text(result, "title"),
+                        snippet: text(result, "content"),
+                        source: news::host_of(&url),
+                        published_date: result
+                            .get("publishedDate")
+                            .and_then(|d| d.as_str())
+                            .and_then(news::parse_date)
+                            .map(news::format_date),
+                        image: ["thumbnail", "img_src"]
+                            .iter()
+                            .find_map(|key| result.get(*key).and_then(|i| i.as_str()).filter(|i| !i.is_empty()))
+                            .map(str::to_string),
+                        url,
+                    })
+                })
+                .take(limit)
+                .collect();
+            eprintln!("[Search] SearxNG returned {} news results", results.len());
+            Ok(results)
+        })
+    }
+
+    fn images<'a>(
+        &'a self,
+        query: &'a str,
+        options: &'a SearchOptions,
+        limit: usize,
+    ) -> BoxFuture<'a, Result<Vec<ImageResult>, String>> {
+        Box::pin(async move {
+            let results: Vec<ImageResult> = self
+                .results(query, options, &["images".to_string()])
+                .await?
+                .iter()
+                .filter_map(|result| {
+                    let field = |key: &str| result.get(key).and_then(|v| v.as_str()).filter(|v| !v.is_empty());
+                    let resolution = field("resolution").and_then(images::parse_resolution);
+                    Some(ImageResult {
+                        title: text(result, "title"),
+                        image_url: field("img_src")?.to_string(),
+                        thumbnail_url: field("thumbnail_src").map(str::to_string),
+                        width: resolution.map(|(width, _)| width),
+                        height: resolution.map(|(_, height)| height),
+                        source_url: field("url").map(str::to_string),
+                    })
+                })
+                .take(limit)
+                .collect();
+            eprintln!("[Search] SearxNG returned {} images", results.len());
+            Ok(results)
+        })
+    }
+}
+
+fn text(result: &Value, key: &str) -> String {
+    clean_text(result.get(key).and_then(|v| v.as_str()).unwrap_or_default())
+}
+
+impl Searxng {
+    // Raw results, one per URL. The instance must allow `format=json` in its settings.yml.
+    async fn results(&self, query: &str, options: &SearchOptions, categories: &[String]) -> Result<Vec<Value>, String> {
+        let client = self.clients.client(&self.client_defaults, self.proxy.as_ref())?;
+        search(&client, &self.instance, query, options, categories, self.timeout_secs).await
+    }
+}
+
+async fn search(
+    client: &reqwest::Client,
+    instance: &str,
+    query: &str,
+    options: &SearchOptions,
+    categories: &[String],
+    timeout_secs: u64,
+) -> Result<Vec<Value>, String> {
+    // Instances may live under a path (https://host/searxng); keep it when appending /search
+    let base = format!("{}/", instance.trim().trim_end_matches('/'));
+    let mut url = Url::parse(&base)
+        .and_then(|base| base.join("search"))
+        .map_err(|e| format!("Invalid SearxNG URL: {}", e))?;
+    {
+        let mut pairs = url.query_pairs_mut();
+        pairs.append_pair("q", query).append_pair("format", "json");
+        if !categories.is_empty() {
+            pairs.append_pair("categories", &categories.join(","));
+        }
+        if !options.engines.is_empty() {
+            pairs.append_pair("engines", &options.engines.join(","));
+        }
+        match (options.language.as_deref().filter(|l| !l.is_empty()), options.region_parts()) {
+            (Some(language), _) => {
+                pairs.append_pair("language", language);
+            }
+            (None, Some((country, language))) => {
+                pairs.append_pair("language", &format!("{}-{}", language, country.to_uppercase()));
+            }
+            (None, None) => {}
+        }
+        if let Some(safe_search) = options.safe_search {
+            let level = match safe_search {
+                SafeSearch::Off => "0",
+                SafeSearch::Moderate => "1",
+                SafeSearch::Strict => "2",
+            };
+            pairs.append_pair("safesearch", level);
+        }
+        if let Some(time_range) = options.time_range {
+            let range = match time_range {
+                TimeRange::Day => "day",
+                TimeRange::Week => "week",
+                TimeRange::Month => "month",
+                TimeRange::Year => "year",
+            };
+            pairs.append_pair("time_range", range);
+        }
+        pairs.append_pair("pageno", &options.page.unwrap_or(1).to_string());
+    }
+
+    eprintln!("[Search] SearxNG search for: {}", query);
+    let response = client
+        .get(url)
+        .timeout(Duration::from_secs(timeout_secs))
+        .header("User-Agent", "OpenChat")
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .map_err(|e| format!("SearxNG request failed: {}", e))?;
+    let status = response.status();
+    if status == reqwest::StatusCode::FORBIDDEN {
+        return Err("SearxNG refused the JSON API; enable the json format under search.formats in its settings.yml".to_string());
+    }
+    if !status.is_success() {
+        return Err(format!("SearxNG returned {}", status));
+    }
+    let json: Value = response.json().await.map_err(|e| format!("Invalid response from SearxNG: {}", e))?;
+
+    if let Some(unresponsive) = json.get("unresponsive_engines").and_then(|u| u.as_array()).filter(|u| !u.is_empty()) {
+        eprintln!("[Search] Unresponsive SearxNG engines: {}", Value::Array(unresponsive.clone()));
+    }
+    let mut seen = HashSet::new();
+    let results = match json {
+        Value::Object(mut object) => match object.remove("results") {
+            Some(Value::Array(results)) => results,
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    };
+    Ok(results
+        .into_iter()
+        .filter(|result| seen.insert(result.get("url").and_then(|u| u.as_str()).unwrap_or_default().to_string()))
+        .collect())
+}
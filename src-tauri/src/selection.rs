@@ -0,0 +1,48 @@
+// Elements picked out of a page by CSS selectors the caller gives (e.g. `.price`, `#changelog li`),
+// for scraping specific values rather than the whole article. Every selector maps to the text and
+// HTML of the elements it matches, in document order.
+use std::collections::HashMap;
+
+use crate::html;
+
+// Matches kept per selector
+const MAX_MATCHES: usize = 200;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct SelectedElement {
+    pub text: String,
+    // Outer HTML of the element
+    pub html: String,
+}
+
+// Catch a selector kuchikiki can't parse before anything is fetched
+pub fn validate(selectors: &[String]) -> Result<(), String> {
+    let document = html::parse("");
+    match selectors.iter().find(|s| s.trim().is_empty() || document.select(s.trim()).is_err()) {
+        Some(invalid) => Err(format!("Invalid CSS selector: {}", invalid)),
+        None => Ok(()),
+    }
+}
+
+pub fn extract(markup: &str, selectors: &[String]) -> HashMap<String, Vec<SelectedElement>> {
+    if selectors.is_empty() {
+        return HashMap::new();
+    }
+    let document = html::parse(markup);
+    selectors
+        .iter()
+        .map(|selector| {
+            let elements = match document.select(selector.trim()) {
+                Ok(matches) => matches
+                    .take(MAX_MATCHES)
+                    .map(|element| SelectedElement {
+                        text: html::to_text(element.as_node()),
+                        html: element.as_node().to_string(),
+                    })
+                    .collect(),
+                Err(()) => Vec::new(),
+            };
+            (selector.clone(), elements)
+        })
+        .collect()
+}
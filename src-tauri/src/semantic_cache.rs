@@ -0,0 +1,162 @@
+// Semantic response cache: answers are stored with the embedding of the question they answered,
+// and a later question that means the same (cosine similarity above the configured threshold) is
+// answered from the cache instead of the provider. Only questions asked in the same context hit:
+// same model, sampling options, system prompt and earlier messages. Entries expire after the
+// configured TTL.
+use rusqlite::params;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, State};
+
+use crate::db::Database;
+use crate::llm::{self, ChatMessage, GenerationOptions, ProviderConfig};
+use crate::settings;
+use crate::vector;
+
+#[derive(serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticCacheOptions {
+    // Model the questions are embedded with
+    pub embedding: ProviderConfig,
+    // Overrides the configured similarity threshold
+    pub threshold: Option<f32>,
+}
+
+pub struct CachedAnswer {
+    pub response: String,
+    pub generation_id: Option<String>,
+    pub similarity: f32,
+}
+
+// A question looked up in the cache, kept to store its answer on a miss
+pub struct Lookup {
+    context_key: String,
+    embedding_model: String,
+    embedding: Vec<f32>,
+    query: String,
+    pub hit: Option<CachedAnswer>,
+}
+
+fn context_key(provider: &ProviderConfig, messages: &[ChatMessage], options: &GenerationOptions) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(provider.base_url.trim_end_matches('/').as_bytes());
+    hasher.update([0]);
+    hasher.update(provider.model.as_bytes());
+    // The seed only picks among equally valid answers
+    let options = GenerationOptions {
+        seed: None,
+        ..options.clone()
+    };
+    hasher.update(serde_json::to_string(&options).unwrap_or_default().as_bytes());
+    for message in messages {
+        hasher.update([0]);
+        hasher.update(message.role.as_bytes());
+        hasher.update([0]);
+        hasher.update(message.content.as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+fn cutoff(app: &AppHandle) -> String {
+    let ttl = settings::load(app).semantic_cache_ttl_secs;
+    (chrono::Utc::now() - chrono::Duration::seconds(ttl as i64)).to_rfc3339()
+}
+
+// Look the last user message up in the cache. Messages before it are part of the context key.
+pub async fn lookup(
+    app: &AppHandle,
+    db: &Database,
+    options: &SemanticCacheOptions,
+    provider: &ProviderConfig,
+    messages: &[ChatMessage],
+    generation: &GenerationOptions,
+) -> Result<Option<Lookup>, String> {
+    let Some(position) = messages.iter().rposition(|m| m.role == "user") else { return Ok(None) };
+    let query = messages[position].content.trim().to_string();
+    if query.is_empty() {
+        return Ok(None);
+    }
+    let context_key = context_key(provider, &messages[..position], generation);
+    let embedding = llm::embed(&options.embedding, std::slice::from_ref(&query))
+        .await?
+        .pop()
+        .ok_or("Embedder returned no vector for the question")?;
+    let threshold = options
+        .threshold
+        .unwrap_or_else(|| settings::load(app).semantic_cache_threshold);
+    let cutoff = cutoff(app);
+
+    let candidates: Vec<(i64, Vec<u8>, String, Option<String>)> = db.with_conn(|conn| {
+        conn.execute("DELETE FROM semantic_cache WHERE created_at < ?1", params![cutoff])?;
+        let mut stmt = conn.prepare(
+            "SELECT id, embedding, response, generation_id FROM semantic_cache
+             WHERE context_key = ?1 AND embedding_model = ?2",
+        )?;
+        let rows = stmt.query_map(params![context_key, options.embedding.model], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?;
+        rows.collect()
+    })?;
+    let best = candidates
+        .into_iter()
+        .map(|(id, blob, response, generation_id)| {
+            (vector::cosine_similarity(&embedding, &vector::from_blob(&blob)), id, response, generation_id)
+        })
+        .filter(|(similarity, ..)| *similarity >= threshold)
+        .max_by(|a, b| a.0.total_cmp(&b.0));
+
+    let hit = match best {
+        Some((similarity, id, response, generation_id)) => {
+            db.with_conn(|conn| {
+                conn.execute(
+                    "UPDATE semantic_cache SET hits = hits + 1, last_hit_at = ?1 WHERE id = ?2",
+                    params![chrono::Utc::now().to_rfc3339(), id],
+                )
+            })?;
+            eprintln!("[Cache] Answering from cache entry {} (similarity {:.3})", id, similarity);
+            Some(CachedAnswer {
+                response,
+                generation_id,
+                similarity,
+            })
+        }
+        None => None,
+    };
+    Ok(Some(Lookup {
+        context_key,
+        embedding_model: options.embedding.model.clone(),
+        embedding,
+        query,
+        hit,
+    }))
+}
+
+// Remember the answer to a question that missed the cache
+pub fn store(db: &Database, lookup: &Lookup, response: &str, generation_id: &str) -> Result<(), String> {
+    if response.trim().is_empty() {
+        return Ok(());
+    }
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO semantic_cache (context_key, embedding_model, embedding, query, response, generation_id, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                lookup.context_key,
+                lookup.embedding_model,
+                vector::to_blob(&lookup.embedding),
+                lookup.query,
+                response,
+                generation_id,
+                chrono::Utc::now().to_rfc3339()
+            ],
+        )
+    })?;
+    Ok(())
+}
+
+// Forget every cached answer; returns how many there were
+#[tauri::command]
+pub fn clear_semantic_cache(db: State<'_, Database>) -> Result<usize, String> {
+    let cleared = db.with_conn(|conn| conn.execute("DELETE FROM semantic_cache", []))?;
+    eprintln!("[Cache] Cleared {} cached answers", cleared);
+    Ok(cleared)
+}
@@ -0,0 +1,272 @@
+// Central backend settings (timeouts, retries, concurrency), stored per profile in settings.json.
+// Every change is broadcast to all windows as a `settings-changed` event.
+use std::path::PathBuf;
+
+use serde_json::Value;
+use tauri::{AppHandle, Emitter};
+
+use crate::language::{self, LanguageFilter};
+use crate::ocr;
+use crate::profiles;
+use crate::proxy;
+use crate::search;
+use crate::stealth;
+
+const SETTINGS_FILE: &str = "settings.json";
+const CHANGED_EVENT: &str = "settings-changed";
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    // Per-URL budget for scraping, including browser navigation
+    pub scrape_timeout_ms: u64,
+    pub scrape_max_retries: u32,
+    // URLs scraped at the same time
+    pub scrape_max_concurrent: usize,
+    // Of those, pages of the same site at most, and the gap between starting them
+    pub scrape_max_per_host: usize,
+    pub scrape_host_delay_ms: u64,
+    // Keep cookies between scrapes (per profile), for consent banners and logged-in sites
+    pub scrape_cookies: bool,
+    // Keep scraped pages (per profile) and only fetch them again when the site reports a change
+    pub scrape_cache: bool,
+    // Languages wanted in scraped pages (ISO 639-3, e.g. eng or deu), none for any; pages in others
+    // are skipped or only sorted last
+    pub scrape_languages: Vec<String>,
+    pub scrape_language_filter: LanguageFilter,
+    // Tabs the shared headless browser keeps open at most
+    pub browser_pool_size: usize,
+    // The browser shuts down after this long unused
+    pub browser_idle_secs: u64,
+    // Desktop browser the scraping browser passes for (chrome-windows, chrome-mac, chrome-linux,
+    // edge-windows), or none to keep its own; the three after it replace parts of the profile
+    pub browser_profile: Option<String>,
+    pub browser_user_agent: Option<String>,
+    // Window size, e.g. 1366x768
+    pub browser_viewport: Option<String>,
+    // e.g. en-US or de-DE
+    pub browser_locale: Option<String>,
+    // Hide navigator.webdriver and the other marks of an automated browser
+    pub browser_stealth: bool,
+    // Plain page fetches and search requests
+    pub fetch_timeout_secs: u64,
+    // Requests forwarded for the frontend
+    pub proxy_timeout_secs: u64,
+    // Connections of the shared HTTP clients: how long connecting may take, and how many idle ones
+    // are kept per host and for how long
+    pub http_connect_timeout_secs: u64,
+    pub http_pool_max_idle_per_host: usize,
+    pub http_pool_idle_secs: u64,
+    // Rerank retrieval results with the cross-encoder in `reranker_model` (a directory)
+    pub rerank_enabled: bool,
+    pub reranker_model: Option<String>,
+    // OCR scanned PDF pages and images with Tesseract; languages are codes joined by '+'
+    pub ocr_enabled: bool,
+    pub ocr_languages: String,
+    // Tesseract executable when it isn't on the PATH
+    pub tesseract_path: Option<String>,
+    // ggml model file used for transcription
+    pub whisper_model: Option<String>,
+    // System voice id or name, or a Piper model (.onnx), used when speak() gets no voice
+    pub tts_voice: Option<String>,
+    pub piper_path: Option<String>,
+    // Cached answers are reused for questions at least this similar (cosine), for this long
+    pub semantic_cache_threshold: f32,
+    pub semantic_cache_ttl_secs: u64,
+    // Web search providers tried in order until one returns results (brave, searxng, duckduckgo)
+    pub search_providers: Vec<String>,
+    // Query all of them and merge the results instead of stopping at the first that answers
+    pub search_merge: bool,
+    pub searxng_url: Option<String>,
+    // Proxy for scraping, web search and page fetches: http(s)://, socks5:// or socks5h:// (names
+    // resolved by the proxy, for Tor), optionally with a user whose password set_proxy_password keeps
+    pub network_proxy: Option<String>,
+    // Hosts reached without it; subdomains included
+    pub network_proxy_bypass: Vec<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            // Slow sites like GitHub need the generous budget
+            scrape_timeout_ms: 45_000,
+            scrape_max_retries: 3,
+            scrape_max_concurrent: 5,
+            scrape_max_per_host: 2,
+            scrape_host_delay_ms: 1_000,
+            scrape_cookies: false,
+            scrape_cache: true,
+            scrape_languages: Vec::new(),
+            scrape_language_filter: LanguageFilter::Deprioritize,
+            browser_pool_size: 4,
+            browser_idle_secs: 120,
+            browser_profile: Some("chrome-windows".to_string()),
+            browser_user_agent: None,
+            browser_viewport: None,
+            browser_locale: None,
+            browser_stealth: true,
+            fetch_timeout_secs: 20,
+            proxy_timeout_secs: 30,
+            http_connect_timeout_secs: 30,
+            http_pool_max_idle_per_host: 8,
+            http_pool_idle_secs: 90,
+            rerank_enabled: false,
+            reranker_model: None,
+            ocr_enabled: true,
+            ocr_languages: "eng".to_string(),
+            tesseract_path: None,
+            whisper_model: None,
+            tts_voice: None,
+            piper_path: None,
+            semantic_cache_threshold: 0.95,
+            semantic_cache_ttl_secs: 24 * 60 * 60,
+            search_providers: vec!["duckduckgo".to_string()],
+            search_merge: false,
+            searxng_url: None,
+            network_proxy: None,
+            network_proxy_bypass: vec!["localhost".to_string(), "127.0.0.1".to_string()],
+        }
+    }
+}
+
+impl Settings {
+    fn validate(&self) -> Result<(), String> {
+        let in_range = |name: &str, value: u64, min: u64, max: u64| {
+            if value < min || value > max {
+                Err(format!("{} must be between {} and {}", name, min, max))
+            } else {
+                Ok(())
+            }
+        };
+        in_range("scrapeTimeoutMs", self.scrape_timeout_ms, 1_000, 600_000)?;
+        in_range("scrapeMaxRetries", self.scrape_max_retries as u64, 1, 10)?;
+        in_range("scrapeMaxConcurrent", self.scrape_max_concurrent as u64, 1, 50)?;
+        in_range("scrapeMaxPerHost", self.scrape_max_per_host as u64, 1, 10)?;
+        in_range("scrapeHostDelayMs", self.scrape_host_delay_ms, 0, 60_000)?;
+        in_range("browserPoolSize", self.browser_pool_size as u64, 1, 16)?;
+        in_range("browserIdleSecs", self.browser_idle_secs, 10, 3600)?;
+        in_range("fetchTimeoutSecs", self.fetch_timeout_secs, 1, 600)?;
+        in_range("proxyTimeoutSecs", self.proxy_timeout_secs, 1, 600)?;
+        in_range("httpConnectTimeoutSecs", self.http_connect_timeout_secs, 1, 120)?;
+        in_range("httpPoolMaxIdlePerHost", self.http_pool_max_idle_per_host as u64, 0, 64)?;
+        in_range("httpPoolIdleSecs", self.http_pool_idle_secs, 1, 3600)?;
+        in_range("semanticCacheTtlSecs", self.semantic_cache_ttl_secs, 60, 30 * 24 * 60 * 60)?;
+        if !(0.5..=1.0).contains(&self.semantic_cache_threshold) {
+            return Err("semanticCacheThreshold must be between 0.5 and 1".to_string());
+        }
+        if self.search_providers.is_empty() {
+            return Err("searchProviders needs at least one provider".to_string());
+        }
+        if let Some(unknown) = self.search_providers.iter().find(|p| !search::PROVIDERS.contains(&p.as_str())) {
+            return Err(format!("Unknown search provider {}, expected one of {}", unknown, search::PROVIDERS.join(", ")));
+        }
+        if let Some(url) = &self.searxng_url {
+            match reqwest::Url::parse(url) {
+                Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+                _ => return Err("searxngUrl must be an http(s) URL".to_string()),
+            }
+        }
+        if let Some(profile) = self.browser_profile.as_deref().filter(|p| !stealth::PROFILES.contains(p)) {
+            let expected = stealth::PROFILES.join(", ");
+            return Err(format!("Unknown browser profile {}, expected one of {}", profile, expected));
+        }
+        if self.browser_viewport.as_deref().is_some_and(|v| stealth::parse_viewport(v).is_none()) {
+            return Err("browserViewport must look like 1366x768, each side between 320 and 7680".to_string());
+        }
+        if self.browser_locale.as_deref().is_some_and(|l| !stealth::valid_locale(l)) {
+            return Err("browserLocale must be a language with an optional region, e.g. en or de-DE".to_string());
+        }
+        let user_agent = self.browser_user_agent.as_deref();
+        if user_agent.is_some_and(|ua| reqwest::header::HeaderValue::from_str(ua).is_err()) {
+            return Err("browserUserAgent isn't a valid header value".to_string());
+        }
+        if let Some(proxy) = self.network_proxy.as_deref().filter(|p| !p.trim().is_empty()) {
+            proxy::validate_setting(proxy)?;
+        }
+        if let Some(code) = self.scrape_languages.iter().find(|code| !language::valid_code(code.trim())) {
+            return Err(format!("Unknown language {} in scrapeLanguages, expected ISO 639-3 codes like eng", code));
+        }
+        if !ocr::valid_languages(&self.ocr_languages) {
+            return Err("ocrLanguages must be language codes joined by '+', e.g. eng+deu".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SettingChanged {
+    key: String,
+    value: Value,
+}
+
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    profiles::config_dir(app).map(|dir| dir.join(SETTINGS_FILE))
+}
+
+fn read_settings(app: &AppHandle) -> Result<Settings, String> {
+    let path = settings_path(app)?;
+    if !path.exists() {
+        return Ok(Settings::default());
+    }
+    let text = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&text).map_err(|e| format!("Invalid settings file: {}", e))
+}
+
+// Settings of the active profile; a broken file falls back to the defaults so commands keep working
+pub fn load(app: &AppHandle) -> Settings {
+    read_settings(app).unwrap_or_else(|err| {
+        eprintln!("[Settings] {}, using defaults", err);
+        Settings::default()
+    })
+}
+
+fn save(app: &AppHandle, settings: &Settings) -> Result<(), String> {
+    let path = settings_path(app)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to save settings: {}", e))
+}
+
+fn to_object(settings: &Settings) -> Result<serde_json::Map<String, Value>, String> {
+    match serde_json::to_value(settings).map_err(|e| e.to_string())? {
+        Value::Object(map) => Ok(map),
+        _ => unreachable!("settings serialize to an object"),
+    }
+}
+
+#[tauri::command]
+pub fn get_all_settings(app: AppHandle) -> Result<Settings, String> {
+    read_settings(&app)
+}
+
+#[tauri::command]
+pub fn get_setting(app: AppHandle, key: String) -> Result<Value, String> {
+    to_object(&read_settings(&app)?)?
+        .remove(&key)
+        .ok_or_else(|| format!("Unknown setting: {}", key))
+}
+
+// Change one setting; the value must have the setting's type and lie in its allowed range
+#[tauri::command]
+pub fn set_setting(app: AppHandle, key: String, value: Value) -> Result<Value, String> {
+    let mut object = to_object(&read_settings(&app)?)?;
+    if !object.contains_key(&key) {
+        return Err(format!("Unknown setting: {}", key));
+    }
+    object.insert(key.clone(), value);
+    let settings: Settings =
+        serde_json::from_value(Value::Object(object)).map_err(|e| format!("Invalid value for {}: {}", key, e))?;
+    settings.validate()?;
+    save(&app, &settings)?;
+
+    // Read back the stored form so listeners see exactly what was saved
+    let value = to_object(&settings)?.remove(&key).unwrap_or(Value::Null);
+    if let Err(e) = app.emit(CHANGED_EVENT, SettingChanged { key: key.clone(), value: value.clone() }) {
+        eprintln!("[Settings] Failed to emit change event: {}", e);
+    }
+    eprintln!("[Settings] {} = {}", key, value);
+    Ok(value)
+}
@@ -0,0 +1,301 @@
+// Crawling a site by its sitemap: the sitemap (found through robots.txt or at /sitemap.xml when
+// given a site rather than a sitemap) is read along with the sitemaps a sitemap index lists, the
+// page URLs are filtered by pattern and last-modified date, and the pages go through the usual
+// scrape pipeline. Each page reports its progress with a `sitemap-progress` event. Documentation
+// sites are the main use: the results are ready for ingestion into a knowledge base.
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use futures::stream::{self, StreamExt};
+use reqwest::Url;
+use tauri::{AppHandle, Emitter};
+
+use crate::http_client::BROWSER_USER_AGENT;
+use crate::xml::{self, Node};
+use crate::{html, settings};
+
+const PROGRESS_EVENT: &str = "sitemap-progress";
+const DEFAULT_LIMIT: usize = 100;
+const MAX_LIMIT: usize = 1000;
+// Sitemap indexes can nest; large sites split their sitemap into hundreds of files
+const MAX_SITEMAPS: usize = 50;
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SitemapEntry {
+    pub url: String,
+    // As written in the sitemap, usually YYYY-MM-DD or RFC 3339
+    pub last_modified: Option<String>,
+}
+
+#[derive(serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SitemapFilters {
+    // URL patterns to keep and to drop; `*` matches any run of characters, and a pattern without
+    // one matches anywhere in the URL
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    exclude: Vec<String>,
+    // YYYY-MM-DD or RFC 3339; pages without a last-modified date are dropped when set
+    modified_since: Option<String>,
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SitemapProgress {
+    url: String,
+    // Position of the page in the crawl, from 1
+    page: usize,
+    pages: usize,
+    // scraping, done or failed
+    stage: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SitemapCrawl {
+    // Sitemap files read
+    sitemaps: Vec<String>,
+    // Page URLs listed, and those left after filtering
+    listed: usize,
+    matched: usize,
+    results: Vec<crate::ScrapeResult>,
+}
+
+// Page URLs and nested sitemap URLs of a sitemap or sitemap index
+pub fn parse(sitemap: &str) -> Result<(Vec<SitemapEntry>, Vec<String>), String> {
+    let mut entries = Vec::new();
+    let mut nested = Vec::new();
+    let mut path: Vec<Vec<u8>> = Vec::new();
+    let (mut loc, mut last_modified) = (String::new(), String::new());
+    xml::walk(sitemap, |node| match node {
+        Node::Open(element) => {
+            let name = xml::local_name(element).to_vec();
+            if matches!(name.as_slice(), b"url" | b"sitemap") {
+                loc.clear();
+                last_modified.clear();
+            }
+            path.push(name);
+        }
+        Node::Text(text) => match path.last().map(Vec::as_slice) {
+            Some(b"loc") => loc.push_str(text),
+            Some(b"lastmod") => last_modified.push_str(text),
+            _ => {}
+        },
+        Node::Close(name) => {
+            path.pop();
+            let url = loc.trim().to_string();
+            if url.is_empty() {
+                return;
+            }
+            match name {
+                b"url" => entries.push(SitemapEntry {
+                    url,
+                    last_modified: Some(last_modified.trim().to_string()).filter(|d| !d.is_empty()),
+                }),
+                b"sitemap" => nested.push(url),
+                _ => {}
+            }
+        }
+    })?;
+    Ok((entries, nested))
+}
+
+// `*` wildcards over the whole URL, or a plain substring
+fn matches_pattern(url: &str, pattern: &str) -> bool {
+    if !pattern.contains('*') {
+        return url.contains(pattern);
+    }
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !url.starts_with(first) || !url[first.len()..].ends_with(last) {
+        return false;
+    }
+    let mut rest = &url[first.len()..url.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+// The calendar day of a sitemap date
+fn parse_day(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date.trim().get(..10)?, "%Y-%m-%d").ok()
+}
+
+fn filter_entries(entries: Vec<SitemapEntry>, filters: &SitemapFilters) -> Result<Vec<SitemapEntry>, String> {
+    let since = match filters.modified_since.as_deref().filter(|d| !d.trim().is_empty()) {
+        Some(date) => Some(parse_day(date).ok_or_else(|| format!("Invalid date: {}", date))?),
+        None => None,
+    };
+    let mut seen = HashSet::new();
+    Ok(entries
+        .into_iter()
+        .filter(|entry| filters.include.is_empty() || filters.include.iter().any(|p| matches_pattern(&entry.url, p)))
+        .filter(|entry| !filters.exclude.iter().any(|p| matches_pattern(&entry.url, p)))
+        .filter(|entry| match since {
+            Some(since) => entry.last_modified.as_deref().and_then(parse_day).is_some_and(|day| day >= since),
+            None => true,
+        })
+        .filter(|entry| seen.insert(entry.url.clone()))
+        .collect())
+}
+
+async fn fetch(client: &reqwest::Client, url: &str, timeout: Duration) -> Result<String, String> {
+    let request = client.get(url).timeout(timeout).header("User-Agent", BROWSER_USER_AGENT);
+    let response = request.send().await.map_err(|e| format!("Request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Request for {} failed with status {}", url, response.status()));
+    }
+    response.text().await.map_err(|e| format!("Failed to read {}: {}", url, e))
+}
+
+// Sitemaps a site declares in robots.txt, or its /sitemap.xml when it declares none
+async fn site_sitemaps(client: &reqwest::Client, site: &Url, timeout: Duration) -> Vec<String> {
+    let declared: Vec<String> = match site.join("/robots.txt") {
+        Ok(robots) => fetch(client, robots.as_str(), timeout)
+            .await
+            .map(|robots| {
+                robots
+                    .lines()
+                    .filter_map(|line| line.split_once(':'))
+                    .filter(|(key, _)| key.trim().eq_ignore_ascii_case("sitemap"))
+                    .map(|(_, value)| value.trim().to_string())
+                    .collect()
+            })
+            .unwrap_or_default(),
+        Err(_) => Vec::new(),
+    };
+    if !declared.is_empty() {
+        return declared;
+    }
+    site.join("/sitemap.xml").map(|u| vec![u.to_string()]).unwrap_or_default()
+}
+
+// Page entries of the sitemaps starting at `url`, following sitemap indexes breadth-first, with the
+// sitemap files read
+pub async fn collect(
+    client: &reqwest::Client,
+    url: &str,
+    timeout_secs: u64,
+) -> Result<(Vec<SitemapEntry>, Vec<String>), String> {
+    let start = Url::parse(url.trim()).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(start.scheme(), "http" | "https") {
+        return Err("Only http and https URLs are allowed".to_string());
+    }
+    let timeout = Duration::from_secs(timeout_secs);
+    let is_sitemap = start.path().ends_with(".xml") || start.path().contains("sitemap");
+    let mut queue: VecDeque<String> = if is_sitemap {
+        VecDeque::from([start.to_string()])
+    } else {
+        site_sitemaps(client, &start, timeout).await.into()
+    };
+
+    let mut visited = HashSet::new();
+    let mut read = Vec::new();
+    let mut entries = Vec::new();
+    let mut last_error = None;
+    while let Some(sitemap) = queue.pop_front() {
+        if read.len() >= MAX_SITEMAPS {
+            eprintln!("[Sitemap] Stopping after {} sitemaps", MAX_SITEMAPS);
+            break;
+        }
+        if !visited.insert(sitemap.clone()) {
+            continue;
+        }
+        // reqwest only undoes gzip transfer encoding, not gzipped files
+        if sitemap.ends_with(".gz") {
+            eprintln!("[Sitemap] Skipping compressed sitemap {}", sitemap);
+            continue;
+        }
+        let parsed = match fetch(client, &sitemap, timeout).await {
+            Ok(body) => parse(&body),
+            Err(e) => Err(e),
+        };
+        match parsed {
+            Ok((found, nested)) => {
+                eprintln!("[Sitemap] {}: {} pages, {} sitemaps", sitemap, found.len(), nested.len());
+                entries.extend(found);
+                queue.extend(nested);
+                read.push(sitemap);
+            }
+            Err(e) => {
+                eprintln!("[Sitemap] Failed to read {}: {}", sitemap, e);
+                last_error = Some(e);
+            }
+        }
+    }
+    if read.is_empty() {
+        return Err(last_error.unwrap_or_else(|| format!("No sitemap found for {}", url)));
+    }
+    Ok((entries, read))
+}
+
+fn emit_progress(app: &AppHandle, progress: SitemapProgress) {
+    if let Err(e) = app.emit(PROGRESS_EVENT, progress) {
+        eprintln!("[Sitemap] Failed to emit progress: {}", e);
+    }
+}
+
+// Scrape up to `limit` pages listed in a site's sitemap; `url` is the sitemap or the site itself.
+// `format` is "text" (default) or "markdown", and `limits` cuts long pages, as for scrape_urls.
+#[tauri::command]
+pub async fn crawl_sitemap(
+    app: AppHandle,
+    url: String,
+    limit: Option<usize>,
+    filters: Option<SitemapFilters>,
+    format: Option<html::Format>,
+    limits: Option<crate::truncation::ContentLimits>,
+) -> Result<SitemapCrawl, String> {
+    let settings = settings::load(&app);
+    let mut options = crate::ScrapeOptions::for_app(&app, &settings);
+    options.format = format.unwrap_or_default();
+    options.max_chars = limits.and_then(|limits| limits.max_chars());
+    let client = options.clients.client(&options.client_defaults, options.proxy.as_ref())?;
+    let (entries, sitemaps) = collect(&client, &url, settings.fetch_timeout_secs).await?;
+    let listed = entries.len();
+    let mut entries = filter_entries(entries, &filters.unwrap_or_default())?;
+    let matched = entries.len();
+    entries.truncate(limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT));
+    eprintln!("[Sitemap] Scraping {} of {} listed pages", entries.len(), listed);
+
+    let pages = entries.len();
+    // Up to scrapeMaxConcurrent pages in flight at any time, results in the order of the entries
+    let results: Vec<crate::ScrapeResult> = stream::iter(entries.into_iter().enumerate())
+        .map(|(i, entry)| {
+            let (app, options) = (app.clone(), options.clone());
+            async move {
+                let progress = |stage, error| SitemapProgress {
+                    url: entry.url.clone(),
+                    page: i + 1,
+                    pages,
+                    stage,
+                    error,
+                };
+                emit_progress(&app, progress("scraping", None));
+                let result = crate::scrape_url_async(entry.url.clone(), options).await;
+                let stage = if result.success { "done" } else { "failed" };
+                emit_progress(&app, progress(stage, result.error.clone()));
+                result
+            }
+        })
+        .buffered(settings.scrape_max_concurrent.max(1))
+        .collect()
+        .await;
+    let successful = results.iter().filter(|r| r.success).count();
+    eprintln!("[Sitemap] Crawl complete: {} of {} pages scraped", successful, pages);
+    Ok(SitemapCrawl {
+        sitemaps,
+        listed,
+        matched,
+        results,
+    })
+}
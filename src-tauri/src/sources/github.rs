@@ -0,0 +1,490 @@
+// GitHub REST API: repository, issue and code search, and file contents. The profile's GitHub token
+// (set_github_token, shared with gist sharing) raises the rate limit and is required for code search.
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+
+use super::{str_field, string_field, u64_field, SourceClient};
+use crate::profiles::ProfileManager;
+use crate::tools::{string_arg, Tool, ToolParameters, ToolRegistry};
+
+const API: &str = "https://api.github.com";
+const API_VERSION: &str = "2022-11-28";
+const DEFAULT_LIMIT: usize = 10;
+const MAX_LIMIT: usize = 100;
+// Larger files are refused rather than handed to the model
+const MAX_FILE_BYTES: usize = 2 * 1024 * 1024;
+// Characters of an issue body kept in search results
+const MAX_BODY_CHARS: usize = 1000;
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GithubRepo {
+    pub full_name: String,
+    pub url: String,
+    pub description: Option<String>,
+    pub stars: u64,
+    pub forks: u64,
+    pub language: Option<String>,
+    pub topics: Vec<String>,
+    pub updated: Option<String>,
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GithubIssue {
+    pub repo: String,
+    pub number: u64,
+    pub title: String,
+    pub url: String,
+    // open or closed
+    pub state: String,
+    pub is_pull_request: bool,
+    pub author: String,
+    pub comments: u64,
+    pub created: Option<String>,
+    // Start of the markdown body
+    pub body: String,
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GithubCodeMatch {
+    pub repo: String,
+    pub path: String,
+    pub url: String,
+    // Lines around the matches
+    pub fragments: Vec<String>,
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GithubFile {
+    pub repo: String,
+    pub path: String,
+    // Branch, tag or commit; the default branch when None
+    pub git_ref: Option<String>,
+    pub url: String,
+    pub content: String,
+}
+
+fn app_token(app: &AppHandle) -> Option<String> {
+    app.try_state::<ProfileManager>().and_then(|profiles| crate::share::github_token(&profiles))
+}
+
+// `accept` picks the media type: JSON, raw file contents or search text matches
+async fn send(
+    request: reqwest::RequestBuilder,
+    accept: &str,
+    token: Option<&str>,
+) -> Result<reqwest::Response, String> {
+    let mut request = request.header("Accept", accept).header("X-GitHub-Api-Version", API_VERSION);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send().await.map_err(|e| format!("GitHub request failed: {}", e))?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let exhausted = response
+        .headers()
+        .get("x-ratelimit-remaining")
+        .is_some_and(|remaining| remaining == "0");
+    if exhausted || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        let hint = if token.is_some() { "" } else { "; adding a GitHub token raises the limit" };
+        return Err(format!("GitHub rate limit reached{}", hint));
+    }
+    // GitHub explains most failures in a JSON message
+    let message = response
+        .json::<Value>()
+        .await
+        .ok()
+        .and_then(|json| json.get("message")?.as_str().map(str::to_string))
+        .unwrap_or_default();
+    match status {
+        reqwest::StatusCode::UNAUTHORIZED => Err("GitHub rejected the token".to_string()),
+        reqwest::StatusCode::NOT_FOUND => Err("Not found on GitHub (or private)".to_string()),
+        _ => Err(format!("GitHub returned {}: {}", status, message)),
+    }
+}
+
+async fn search_items(
+    kind: &str,
+    query: &str,
+    limit: Option<usize>,
+    token: Option<&str>,
+    http: &SourceClient,
+) -> Result<Vec<Value>, String> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Err("Search query cannot be empty".to_string());
+    }
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let request = http
+        .get(format!("{}/search/{}", API, kind))
+        .query(&[("q", query), ("per_page", &limit.to_string())]);
+    // Code search only returns the matched lines with the text-match media type
+    let accept = if kind == "code" {
+        "application/vnd.github.text-match+json"
+    } else {
+        "application/vnd.github+json"
+    };
+    let json: Value = send(request, accept, token)
+        .await?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid response from GitHub: {}", e))?;
+    let items = json.get("items").and_then(|i| i.as_array()).cloned().unwrap_or_default();
+    eprintln!("[GitHub] {} {} results for: {}", items.len(), kind, query);
+    Ok(items)
+}
+
+// `query` takes GitHub's qualifiers, e.g. "tauri plugin language:rust stars:>100"
+pub async fn search_repos(
+    query: &str,
+    limit: Option<usize>,
+    token: Option<&str>,
+    http: &SourceClient,
+) -> Result<Vec<GithubRepo>, String> {
+    let items = search_items("repositories", query, limit, token, http).await?;
+    Ok(items
+        .iter()
+        .map(|item| GithubRepo {
+            full_name: string_field(item, "/full_name"),
+            url: string_field(item, "/html_url"),
+            description: str_field(item, "/description").map(str::to_string),
+            stars: u64_field(item, "stargazers_count"),
+            forks: u64_field(item, "forks_count"),
+            language: str_field(item, "/language").map(str::to_string),
+            topics: item
+                .get("topics")
+                .and_then(|t| t.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|t| t.as_str().map(str::to_string))
+                .collect(),
+            updated: str_field(item, "/pushed_at").map(str::to_string),
+        })
+        .collect())
+}
+
+// Issues and pull requests; `query` takes qualifiers such as repo:owner/name or is:open
+pub async fn search_issues(
+    query: &str,
+    limit: Option<usize>,
+    token: Option<&str>,
+    http: &SourceClient,
+) -> Result<Vec<GithubIssue>, String> {
+    let items = search_items("issues", query, limit, token, http).await?;
+    Ok(items
+        .iter()
+        .map(|item| {
+            let full_body = string_field(item, "/body");
+            let mut body: String = full_body.chars().take(MAX_BODY_CHARS).collect();
+            if body.len() < full_body.len() {
+                body.push('…');
+            }
+            GithubIssue {
+                // https://api.github.com/repos/{owner}/{repo}
+                repo: str_field(item, "/repository_url")
+                    .and_then(|url| url.split("/repos/").nth(1).map(str::to_string))
+                    .unwrap_or_default(),
+                number: u64_field(item, "number"),
+                title: string_field(item, "/title"),
+                url: string_field(item, "/html_url"),
+                state: string_field(item, "/state"),
+                is_pull_request: item.get("pull_request").is_some(),
+                author: string_field(item, "/user/login"),
+                comments: u64_field(item, "comments"),
+                created: str_field(item, "/created_at").map(str::to_string),
+                body,
+            }
+        })
+        .collect())
+}
+
+// Code search needs a token; `query` takes qualifiers such as repo:, language: or path:
+pub async fn search_code(
+    query: &str,
+    limit: Option<usize>,
+    token: Option<&str>,
+    http: &SourceClient,
+) -> Result<Vec<GithubCodeMatch>, String> {
+    if token.is_none() {
+        return Err("GitHub code search requires a GitHub token".to_string());
+    }
+    let items = search_items("code", query, limit, token, http).await?;
+    Ok(items
+        .iter()
+        .map(|item| GithubCodeMatch {
+            repo: string_field(item, "/repository/full_name"),
+            path: string_field(item, "/path"),
+            url: string_field(item, "/html_url"),
+            fragments: item
+                .get("text_matches")
+                .and_then(|m| m.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|m| str_field(m, "/fragment").map(str::to_string))
+                .collect(),
+        })
+        .collect())
+}
+
+// (owner/repo, path, ref) from github.com blob/raw URLs and raw.githubusercontent.com URLs. Refs
+// containing slashes can't be told apart from the path and are read as their first segment.
+pub fn parse_file_url(url: &str) -> Option<(String, String, Option<String>)> {
+    let parsed = reqwest::Url::parse(url).ok()?;
+    let segments: Vec<&str> = parsed.path_segments()?.filter(|s| !s.is_empty()).collect();
+    let (owner, repo, git_ref, path) = match parsed.host_str()? {
+        "github.com" | "www.github.com" => match segments.as_slice() {
+            [owner, repo, "blob" | "raw", git_ref, path @ ..] if !path.is_empty() => (owner, repo, git_ref, path),
+            _ => return None,
+        },
+        "raw.githubusercontent.com" => match segments.as_slice() {
+            [owner, repo, git_ref, path @ ..] if !path.is_empty() => (owner, repo, git_ref, path),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some((format!("{}/{}", owner, repo), path.join("/"), Some(git_ref.to_string())))
+}
+
+fn valid_repo(repo: &str) -> bool {
+    let valid = |part: &str| {
+        !part.is_empty() && part != "." && part != ".." && part.chars().all(|c| c.is_ascii_alphanumeric() || "._-".contains(c))
+    };
+    matches!(repo.split_once('/'), Some((owner, name)) if valid(owner) && valid(name))
+}
+
+fn is_listing(content: &str) -> bool {
+    serde_json::from_str::<Vec<Value>>(content)
+        .is_ok_and(|entries| entries.iter().all(|e| e.get("sha").is_some() && e.get("type").is_some()))
+}
+
+// A text file from a repository, as it is at `git_ref` (default branch when None)
+pub async fn get_file(
+    repo: &str,
+    path: &str,
+    git_ref: Option<&str>,
+    token: Option<&str>,
+    http: &SourceClient,
+) -> Result<GithubFile, String> {
+    if !valid_repo(repo) {
+        return Err(format!("Invalid repository, expected owner/name: {}", repo));
+    }
+    let path = path.trim_matches('/');
+    if path.is_empty() || path.split('/').any(|segment| segment == "..") {
+        return Err(format!("Invalid file path: {}", path));
+    }
+    let encoded: Vec<String> = path.split('/').map(|s| urlencoding::encode(s).into_owned()).collect();
+    let mut request = http.get(format!("{}/repos/{}/contents/{}", API, repo, encoded.join("/")));
+    if let Some(git_ref) = git_ref.filter(|r| !r.is_empty()) {
+        request = request.query(&[("ref", git_ref)]);
+    }
+    // The raw media type returns the file itself (up to 100 MB) instead of base64 JSON
+    let mut response = send(request, "application/vnd.github.raw+json", token).await?;
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| format!("Failed to read {}: {}", path, e))? {
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() > MAX_FILE_BYTES {
+            return Err(format!("{} is larger than {} MB", path, MAX_FILE_BYTES / 1024 / 1024));
+        }
+    }
+    let content = String::from_utf8(bytes).map_err(|_| format!("{} is not a text file", path))?;
+    // Directories come back as their JSON listing even with the raw media type
+    if content.starts_with('[') && is_listing(&content) {
+        return Err(format!("{} is a directory, not a file", path));
+    }
+    eprintln!("[GitHub] Fetched {}/{} ({} bytes)", repo, path, content.len());
+    let shown_ref = git_ref.unwrap_or("HEAD");
+    Ok(GithubFile {
+        url: format!("https://github.com/{}/blob/{}/{}", repo, shown_ref, path),
+        repo: repo.to_string(),
+        path: path.to_string(),
+        git_ref: git_ref.map(str::to_string),
+        content,
+    })
+}
+
+pub fn repos_to_markdown(repos: &[GithubRepo]) -> String {
+    let mut out = String::new();
+    for (i, repo) in repos.iter().enumerate() {
+        out.push_str(&format!("{}. **{}** ({} stars", i + 1, repo.full_name, repo.stars));
+        if let Some(language) = &repo.language {
+            out.push_str(&format!(", {}", language));
+        }
+        out.push_str(&format!(")\n   {}\n", repo.url));
+        if let Some(description) = &repo.description {
+            out.push_str(&format!("   {}\n", description));
+        }
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+pub fn issues_to_markdown(issues: &[GithubIssue]) -> String {
+    let mut out = String::new();
+    for (i, issue) in issues.iter().enumerate() {
+        let kind = if issue.is_pull_request { "PR" } else { "Issue" };
+        out.push_str(&format!(
+            "{}. **{}** ({} {}#{}, {}, {} comments)\n   {}\n",
+            i + 1,
+            issue.title,
+            kind,
+            issue.repo,
+            issue.number,
+            issue.state,
+            issue.comments,
+            issue.url
+        ));
+        if !issue.body.is_empty() {
+            out.push_str(&format!("   {}\n", issue.body.split_whitespace().collect::<Vec<_>>().join(" ")));
+        }
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+pub fn code_to_markdown(matches: &[GithubCodeMatch]) -> String {
+    let mut out = String::new();
+    for (i, found) in matches.iter().enumerate() {
+        out.push_str(&format!("{}. {} `{}`\n   {}\n", i + 1, found.repo, found.path, found.url));
+        for fragment in &found.fragments {
+            out.push_str(&format!("```\n{}\n```\n", fragment.trim_end()));
+        }
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+#[tauri::command]
+pub async fn github_search_repos(
+    app: AppHandle,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<GithubRepo>, String> {
+    let http = SourceClient::for_app(&app)?;
+    search_repos(&query, limit, app_token(&app).as_deref(), &http).await
+}
+
+#[tauri::command]
+pub async fn github_search_issues(
+    app: AppHandle,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<GithubIssue>, String> {
+    let http = SourceClient::for_app(&app)?;
+    search_issues(&query, limit, app_token(&app).as_deref(), &http).await
+}
+
+#[tauri::command]
+pub async fn github_search_code(
+    app: AppHandle,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<GithubCodeMatch>, String> {
+    let http = SourceClient::for_app(&app)?;
+    search_code(&query, limit, app_token(&app).as_deref(), &http).await
+}
+
+// A file by repo and path, or by its github.com / raw.githubusercontent.com URL in `repo`
+#[tauri::command]
+pub async fn github_get_file(
+    app: AppHandle,
+    repo: String,
+    path: Option<String>,
+    git_ref: Option<String>,
+) -> Result<GithubFile, String> {
+    let http = SourceClient::for_app(&app)?;
+    let (repo, path, git_ref) = match parse_file_url(&repo) {
+        Some(parsed) => parsed,
+        None => (repo, path.ok_or("A file path is required")?, git_ref),
+    };
+    get_file(&repo, &path, git_ref.as_deref(), app_token(&app).as_deref(), &http).await
+}
+
+pub fn register_tools(registry: &ToolRegistry, app: &AppHandle) {
+    registry.register(Arc::new(GithubSearchTool { app: app.clone() }));
+    registry.register(Arc::new(GithubFileTool { app: app.clone() }));
+}
+
+struct GithubSearchTool {
+    app: AppHandle,
+}
+
+impl Tool for GithubSearchTool {
+    fn name(&self) -> &str {
+        "github_search"
+    }
+
+    fn description(&self) -> &str {
+        "Search GitHub repositories, issues and pull requests, or code. Queries accept GitHub \
+         qualifiers such as repo:owner/name, language:rust or is:open."
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        ToolParameters::new()
+            .required("query", "string", "Search query with optional qualifiers")
+            .optional("kind", "string", "repositories (default), issues or code")
+            .optional("limit", "integer", "Number of results (default 10)")
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move {
+            let query = string_arg(&args, "query")?;
+            let limit = args.get("limit").and_then(|v| v.as_u64()).map(|n| n as usize);
+            let token = app_token(&self.app);
+            let token = token.as_deref();
+            let http = SourceClient::for_app(&self.app)?;
+            let markdown = match args.get("kind").and_then(|v| v.as_str()).unwrap_or("repositories") {
+                "repositories" => repos_to_markdown(&search_repos(&query, limit, token, &http).await?),
+                "issues" => issues_to_markdown(&search_issues(&query, limit, token, &http).await?),
+                "code" => code_to_markdown(&search_code(&query, limit, token, &http).await?),
+                other => return Err(format!("Unknown search kind: {}", other)),
+            };
+            Ok(if markdown.is_empty() { "No results.".to_string() } else { markdown })
+        })
+    }
+}
+
+struct GithubFileTool {
+    app: AppHandle,
+}
+
+impl Tool for GithubFileTool {
+    fn name(&self) -> &str {
+        "github_file"
+    }
+
+    fn description(&self) -> &str {
+        "Read a text file from a GitHub repository, by repository and path or by its GitHub URL"
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        ToolParameters::new()
+            .required("repo", "string", "owner/name, or the file's github.com URL")
+            .optional("path", "string", "File path in the repository")
+            .optional("ref", "string", "Branch, tag or commit (default branch when omitted)")
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move {
+            let repo = string_arg(&args, "repo")?;
+            let (repo, path, git_ref) = match parse_file_url(&repo) {
+                Some(parsed) => parsed,
+                None => (
+                    repo,
+                    string_arg(&args, "path")?,
+                    args.get("ref").and_then(|v| v.as_str()).map(str::to_string),
+                ),
+            };
+            let http = SourceClient::for_app(&self.app)?;
+            let file = get_file(&repo, &path, git_ref.as_deref(), app_token(&self.app).as_deref(), &http).await?;
+            Ok(format!("{}\n\n```\n{}\n```", file.url, super::truncate(&file.content, super::MAX_SOURCE_CHARS)))
+        })
+    }
+}
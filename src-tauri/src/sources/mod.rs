@@ -0,0 +1,95 @@
+// Sources with public APIs (Wikipedia, arXiv, Reddit, Stack Exchange, GitHub, YouTube, Open-Meteo),
+// queried directly instead of scraping their pages.
+// Each returns typed results for the frontend, and the agent gets them as tools.
+use std::time::Duration;
+
+use serde_json::Value;
+use tauri::AppHandle;
+
+use crate::settings;
+
+pub mod github;
+pub mod papers;
+pub mod reddit;
+pub mod stackexchange;
+pub mod weather;
+pub mod wikipedia;
+pub mod youtube;
+
+// Cap on what source tools hand the model, in characters
+pub const MAX_SOURCE_CHARS: usize = 20_000;
+
+// API etiquette (Wikimedia requires it) asks for an identifying user agent
+const USER_AGENT: &str = concat!("OpenChat/", env!("CARGO_PKG_VERSION"), " (https://github.com/OpenChatGit/OpenChat)");
+
+// Requests to the source APIs, on a shared client with the app's connection settings and proxy
+#[derive(Clone)]
+pub struct SourceClient {
+    client: reqwest::Client,
+    timeout: Duration,
+}
+
+impl SourceClient {
+    pub fn new(client: reqwest::Client, timeout_secs: u64) -> Self {
+        SourceClient {
+            client,
+            timeout: Duration::from_secs(timeout_secs),
+        }
+    }
+
+    // The app's client and fetch timeout
+    pub fn for_app(app: &AppHandle) -> Result<Self, String> {
+        let settings = settings::load(app);
+        Ok(SourceClient::new(crate::http_client::for_app(app, &settings)?, settings.fetch_timeout_secs))
+    }
+
+    pub fn get(&self, url: impl reqwest::IntoUrl) -> reqwest::RequestBuilder {
+        self.get_as(url, USER_AGENT)
+    }
+
+    // For sites that only answer browsers
+    pub fn get_as(&self, url: impl reqwest::IntoUrl, user_agent: &str) -> reqwest::RequestBuilder {
+        self.client.get(url).timeout(self.timeout).header(reqwest::header::USER_AGENT, user_agent)
+    }
+}
+
+// GET a JSON document; None when it doesn't exist (404)
+pub async fn get_json(request: reqwest::RequestBuilder, source: &str) -> Result<Option<Value>, String> {
+    let response = request.send().await.map_err(|e| format!("{} request failed: {}", source, e))?;
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    // Public APIs share one quota between all anonymous clients
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Err(format!("{} is rate limiting requests, try again in a minute", source));
+    }
+    if !status.is_success() {
+        return Err(format!("{} returned {}", source, status));
+    }
+    response
+        .json()
+        .await
+        .map(Some)
+        .map_err(|e| format!("Invalid response from {}: {}", source, e))
+}
+
+// The non-empty string at `key`, a field name or a JSON pointer such as /user/login
+pub(crate) fn str_field<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    let field = if key.starts_with('/') { value.pointer(key) } else { value.get(key) };
+    field.and_then(Value::as_str).filter(|s| !s.is_empty())
+}
+
+// The string at `key`, or an empty one
+pub(crate) fn string_field(value: &Value, key: &str) -> String {
+    str_field(value, key).unwrap_or_default().to_string()
+}
+
+pub(crate) fn u64_field(value: &Value, key: &str) -> u64 {
+    value.get(key).and_then(Value::as_u64).unwrap_or(0)
+}
+
+// Text cut to at most `max_chars` as crate::truncation cuts it, or whole when it fits
+pub fn truncate(text: &str, max_chars: usize) -> String {
+    crate::truncation::truncate(text, max_chars).unwrap_or_else(|| text.to_string())
+}
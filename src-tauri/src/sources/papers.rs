@@ -0,0 +1,290 @@
+// Academic papers from the arXiv Atom API and the Semantic Scholar Graph API. Paper PDFs can be
+// downloaded into the profile for ingestion, or read directly through the PDF extractor.
+use std::path::PathBuf;
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tauri::AppHandle;
+
+use super::{get_json, SourceClient};
+use crate::clean_text;
+use crate::pdf;
+use crate::profiles;
+use crate::xml::{self, Node};
+
+const ARXIV_API: &str = "https://export.arxiv.org/api/query";
+const SEMANTIC_SCHOLAR_API: &str = "https://api.semanticscholar.org/graph/v1/paper/search";
+const SEMANTIC_SCHOLAR_FIELDS: &str = "title,authors,abstract,year,citationCount,openAccessPdf,externalIds,url,venue";
+const DEFAULT_MAX_RESULTS: usize = 10;
+const MAX_RESULTS: usize = 100;
+const MAX_PDF_BYTES: usize = 100 * 1024 * 1024;
+// Downloaded papers, in the profile's data dir
+const PAPERS_DIR: &str = "papers";
+
+#[derive(serde::Serialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Paper {
+    // arXiv id (2101.00001v2) or Semantic Scholar paper id
+    pub id: String,
+    pub title: String,
+    pub authors: Vec<String>,
+    #[serde(rename = "abstract")]
+    pub abstract_text: Option<String>,
+    pub year: Option<i32>,
+    // Semantic Scholar only
+    pub citation_count: Option<u64>,
+    pub venue: Option<String>,
+    pub doi: Option<String>,
+    pub pdf_url: Option<String>,
+    pub url: String,
+}
+
+fn limit(max_results: Option<usize>) -> usize {
+    max_results.unwrap_or(DEFAULT_MAX_RESULTS).clamp(1, MAX_RESULTS)
+}
+
+// Plain words are all required; queries already using arXiv fields (ti:, au:, cat:) pass unchanged
+fn arxiv_query(query: &str) -> String {
+    if query.contains(':') {
+        return query.to_string();
+    }
+    query
+        .split_whitespace()
+        .map(|word| format!("all:{}", word))
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+fn parse_arxiv(feed: &str) -> Result<Vec<Paper>, String> {
+    let mut papers = Vec::new();
+    let mut current: Option<Paper> = None;
+    // Element whose text is being collected, and the text so far
+    let mut field: Option<Vec<u8>> = None;
+    let mut text = String::new();
+    xml::walk(feed, |node| match node {
+        Node::Open(e) => match xml::local_name(e) {
+            b"entry" => current = Some(Paper::default()),
+            b"link" => {
+                if let Some(paper) = current.as_mut().filter(|_| xml::attr(e, "title").as_deref() == Some("pdf")) {
+                    paper.pdf_url = xml::attr(e, "href").map(|href| href.replacen("http://", "https://", 1));
+                }
+            }
+            name @ (b"id" | b"title" | b"summary" | b"published" | b"name" | b"doi" | b"journal_ref") => {
+                field = Some(name.to_vec());
+                text.clear();
+            }
+            _ => {}
+        },
+        Node::Text(t) => {
+            if field.is_some() {
+                text.push_str(t);
+            }
+        }
+        Node::Close(name) => {
+            if field.as_deref() == Some(name) {
+                field = None;
+                if let Some(paper) = current.as_mut() {
+                    let value = clean_text(&text);
+                    match name {
+                        b"id" => {
+                            paper.url = value.replacen("http://", "https://", 1);
+                            paper.id = value.rsplit("/abs/").next().unwrap_or_default().to_string();
+                        }
+                        b"title" => paper.title = value,
+                        b"summary" => paper.abstract_text = Some(value),
+                        b"published" => paper.year = value.get(..4).and_then(|y| y.parse().ok()),
+                        b"name" => paper.authors.push(value),
+                        b"doi" => paper.doi = Some(value),
+                        b"journal_ref" => paper.venue = Some(value),
+                        _ => {}
+                    }
+                }
+            }
+            if name == b"entry" {
+                papers.extend(current.take());
+            }
+        }
+    })?;
+    Ok(papers)
+}
+
+pub async fn search_arxiv(query: &str, max_results: Option<usize>, http: &SourceClient) -> Result<Vec<Paper>, String> {
+    let request = http.get(ARXIV_API).query(&[
+        ("search_query", arxiv_query(query)),
+        ("start", "0".to_string()),
+        ("max_results", limit(max_results).to_string()),
+        ("sortBy", "relevance".to_string()),
+    ]);
+    let response = request.send().await.map_err(|e| format!("arXiv request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("arXiv returned {}", response.status()));
+    }
+    let feed = response.text().await.map_err(|e| format!("Failed to read arXiv response: {}", e))?;
+    let papers = parse_arxiv(&feed)?;
+    eprintln!("[Papers] arXiv returned {} papers for: {}", papers.len(), query);
+    Ok(papers)
+}
+
+fn parse_semantic_scholar(paper: &Value) -> Option<Paper> {
+    let text = |pointer: &str| paper.pointer(pointer).and_then(|v| v.as_str()).filter(|v| !v.is_empty()).map(str::to_string);
+    let arxiv_id = text("/externalIds/ArXiv");
+    Some(Paper {
+        id: text("/paperId")?,
+        title: clean_text(&text("/title")?),
+        authors: paper
+            .get("authors")
+            .and_then(|a| a.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|author| author.get("name")?.as_str().map(str::to_string))
+            .collect(),
+        abstract_text: text("/abstract"),
+        year: paper.get("year").and_then(|y| y.as_i64()).map(|y| y as i32),
+        citation_count: paper.get("citationCount").and_then(|c| c.as_u64()),
+        venue: text("/venue"),
+        doi: text("/externalIds/DOI"),
+        // Open-access papers link their PDF; arXiv preprints always have one
+        pdf_url: text("/openAccessPdf/url").or_else(|| arxiv_id.as_ref().map(|id| format!("https://arxiv.org/pdf/{}", id))),
+        url: text("/url").unwrap_or_default(),
+    })
+}
+
+// `year` narrows to a year or range: 2019, 2016-2020, 2010- or -2015
+pub async fn search_semantic_scholar(
+    query: &str,
+    max_results: Option<usize>,
+    year: Option<&str>,
+    http: &SourceClient,
+) -> Result<Vec<Paper>, String> {
+    let mut params = vec![
+        ("query", query.to_string()),
+        ("limit", limit(max_results).to_string()),
+        ("fields", SEMANTIC_SCHOLAR_FIELDS.to_string()),
+    ];
+    if let Some(year) = year.filter(|y| !y.trim().is_empty()) {
+        params.push(("year", year.trim().to_string()));
+    }
+    let request = http.get(SEMANTIC_SCHOLAR_API).query(&params);
+    let json = get_json(request, "Semantic Scholar").await?.unwrap_or(Value::Null);
+    let papers: Vec<Paper> = json
+        .get("data")
+        .and_then(|d| d.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(parse_semantic_scholar)
+        .collect();
+    eprintln!("[Papers] Semantic Scholar returned {} papers for: {}", papers.len(), query);
+    Ok(papers)
+}
+
+// Markdown list for the agent, with the PDF link it can pass to read_paper
+pub fn to_markdown(papers: &[Paper]) -> String {
+    if papers.is_empty() {
+        return "No papers found.".to_string();
+    }
+    let mut out = String::new();
+    for (i, paper) in papers.iter().enumerate() {
+        out.push_str(&format!("{}. **{}**", i + 1, paper.title));
+        if let Some(year) = paper.year {
+            out.push_str(&format!(" ({})", year));
+        }
+        out.push('\n');
+        if !paper.authors.is_empty() {
+            out.push_str(&format!("   Authors: {}\n", paper.authors.join(", ")));
+        }
+        if let Some(venue) = &paper.venue {
+            out.push_str(&format!("   Venue: {}\n", venue));
+        }
+        if let Some(count) = paper.citation_count {
+            out.push_str(&format!("   Citations: {}\n", count));
+        }
+        out.push_str(&format!("   URL: {}\n", paper.url));
+        if let Some(pdf_url) = &paper.pdf_url {
+            out.push_str(&format!("   PDF: {}\n", pdf_url));
+        }
+        if let Some(abstract_text) = &paper.abstract_text {
+            out.push_str(&format!("   Abstract: {}\n", abstract_text));
+        }
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+// Download a paper's PDF, refusing anything that isn't one
+pub async fn fetch_pdf(url: &str, http: &SourceClient) -> Result<Vec<u8>, String> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid PDF URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("Only http(s) PDF URLs can be fetched".to_string());
+    }
+    let mut response = http
+        .get(parsed)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to download {}: {}", url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("Downloading {} failed with status {}", url, response.status()));
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| format!("Failed to download {}: {}", url, e))? {
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() > MAX_PDF_BYTES {
+            return Err(format!("PDF is larger than {} MB", MAX_PDF_BYTES / 1024 / 1024));
+        }
+    }
+    if !bytes.starts_with(b"%PDF") {
+        return Err(format!("{} did not return a PDF", url));
+    }
+    Ok(bytes)
+}
+
+// Full text of a paper's PDF, pages separated by blank lines
+pub async fn read_pdf(url: &str, http: &SourceClient) -> Result<String, String> {
+    let bytes = fetch_pdf(url, http).await?;
+    tokio::task::spawn_blocking(move || pdf::parse(&bytes, None).map(|document| document.text()))
+        .await
+        .map_err(|e| format!("PDF extraction task failed: {}", e))?
+}
+
+// File name for a downloaded paper: the last URL segment (the arXiv id) when it is usable, with a
+// hash of the URL so papers served as e.g. /download don't overwrite each other
+fn paper_file_name(url: &str) -> String {
+    let hash = format!("{:x}", Sha256::digest(url.as_bytes()));
+    let segment = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.path_segments()?.next_back().map(str::to_string))
+        .unwrap_or_default();
+    let stem = segment.trim_end_matches(".pdf");
+    let safe = !stem.is_empty() && stem.len() <= 80 && stem.chars().all(|c| c.is_ascii_alphanumeric() || "._-".contains(c));
+    if safe {
+        format!("{}-{}.pdf", stem, &hash[..8])
+    } else {
+        format!("{}.pdf", &hash[..16])
+    }
+}
+
+#[tauri::command]
+pub async fn search_arxiv_papers(app: AppHandle, query: String, max_results: Option<usize>) -> Result<Vec<Paper>, String> {
+    search_arxiv(&query, max_results, &SourceClient::for_app(&app)?).await
+}
+
+#[tauri::command]
+pub async fn search_semantic_scholar_papers(
+    app: AppHandle,
+    query: String,
+    max_results: Option<usize>,
+    year: Option<String>,
+) -> Result<Vec<Paper>, String> {
+    search_semantic_scholar(&query, max_results, year.as_deref(), &SourceClient::for_app(&app)?).await
+}
+
+// Save a paper's PDF in the profile's papers folder and return its path, ready for ingest_documents
+#[tauri::command]
+pub async fn download_paper(app: AppHandle, pdf_url: String) -> Result<String, String> {
+    let bytes = fetch_pdf(&pdf_url, &SourceClient::for_app(&app)?).await?;
+    let dir: PathBuf = profiles::data_dir(&app)?.join(PAPERS_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(paper_file_name(&pdf_url));
+    std::fs::write(&path, &bytes).map_err(|e| format!("Failed to save {}: {}", path.display(), e))?;
+    eprintln!("[Papers] Saved {} ({} KB)", path.display(), bytes.len() / 1024);
+    Ok(path.to_string_lossy().to_string())
+}
@@ -0,0 +1,300 @@
+// Reddit through the .json form of its pages: post search, and a thread with its best comments.
+use chrono::DateTime;
+use serde_json::Value;
+use tauri::AppHandle;
+
+use super::{get_json, string_field, SourceClient};
+use crate::search::news::format_date;
+use crate::search::TimeRange;
+
+const BASE: &str = "https://www.reddit.com";
+const DEFAULT_LIMIT: usize = 10;
+const DEFAULT_MAX_COMMENTS: usize = 10;
+const DEFAULT_MAX_DEPTH: usize = 3;
+// Reddit returns at most 100 listing items per request
+const MAX_LIMIT: usize = 100;
+const MAX_DEPTH: usize = 8;
+// Replies kept under each comment, best first
+const MAX_REPLIES: usize = 3;
+// Text post bodies in search results
+const PREVIEW_CHARS: usize = 300;
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RedditPost {
+    pub id: String,
+    pub title: String,
+    pub subreddit: String,
+    pub author: String,
+    pub score: i64,
+    pub num_comments: u64,
+    // The thread on reddit
+    pub url: String,
+    // Linked page, for link posts
+    pub link_url: Option<String>,
+    // Markdown body, for text posts
+    pub text: String,
+    // RFC 3339, UTC
+    pub created: Option<String>,
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RedditComment {
+    pub author: String,
+    pub score: i64,
+    // Markdown
+    pub body: String,
+    // 0 for top-level comments
+    pub depth: usize,
+    pub replies: Vec<RedditComment>,
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RedditThread {
+    pub post: RedditPost,
+    pub comments: Vec<RedditComment>,
+}
+
+fn score(data: &Value) -> i64 {
+    data.get("score").and_then(|v| v.as_i64()).unwrap_or(0)
+}
+
+// Listing children of one kind (t1 comments, t3 posts), as their data objects
+fn children<'a>(listing: &'a Value, kind: &'a str) -> impl Iterator<Item = &'a Value> + 'a {
+    listing
+        .pointer("/data/children")
+        .and_then(|c| c.as_array())
+        .into_iter()
+        .flatten()
+        .filter(move |child| child.get("kind").and_then(|k| k.as_str()) == Some(kind))
+        .filter_map(|child| child.get("data"))
+}
+
+fn parse_post(data: &Value) -> RedditPost {
+    let permalink = string_field(data, "permalink");
+    let link_url = Some(string_field(data, "url")).filter(|url| {
+        let is_self = data.get("is_self").and_then(|v| v.as_bool()).unwrap_or(false);
+        !is_self && !url.is_empty() && !url.ends_with(&permalink)
+    });
+    RedditPost {
+        id: string_field(data, "id"),
+        title: string_field(data, "title"),
+        subreddit: string_field(data, "subreddit"),
+        author: string_field(data, "author"),
+        score: score(data),
+        num_comments: data.get("num_comments").and_then(|v| v.as_u64()).unwrap_or(0),
+        url: format!("{}{}", BASE, permalink),
+        link_url,
+        text: string_field(data, "selftext"),
+        created: data
+            .get("created_utc")
+            .and_then(|v| v.as_f64())
+            .and_then(|secs| DateTime::from_timestamp(secs as i64, 0))
+            .map(format_date),
+    }
+}
+
+// Comments of a listing, best first; removed and deleted ones are dropped with their replies
+fn parse_comments(listing: &Value, depth: usize, max_depth: usize, limit: usize) -> Vec<RedditComment> {
+    let mut comments: Vec<RedditComment> = children(listing, "t1")
+        .filter(|data| !matches!(string_field(data, "body").as_str(), "[removed]" | "[deleted]"))
+        .map(|data| RedditComment {
+            author: string_field(data, "author"),
+            score: score(data),
+            body: string_field(data, "body"),
+            depth,
+            replies: match data.get("replies") {
+                Some(replies) if depth + 1 < max_depth => parse_comments(replies, depth + 1, max_depth, MAX_REPLIES),
+                _ => Vec::new(),
+            },
+        })
+        .collect();
+    comments.sort_by_key(|comment| std::cmp::Reverse(comment.score));
+    comments.truncate(limit);
+    comments
+}
+
+// `sort` is relevance, hot, top, new or comments; `subreddit` restricts the search to one
+pub async fn search(
+    query: &str,
+    subreddit: Option<&str>,
+    sort: Option<&str>,
+    time_range: Option<TimeRange>,
+    limit: Option<usize>,
+    http: &SourceClient,
+) -> Result<Vec<RedditPost>, String> {
+    let sort = sort.unwrap_or("relevance");
+    if !matches!(sort, "relevance" | "hot" | "top" | "new" | "comments") {
+        return Err(format!("Invalid Reddit sort: {}", sort));
+    }
+    let subreddit = subreddit.map(|s| s.trim().trim_start_matches("r/")).filter(|s| !s.is_empty());
+    let url = match subreddit {
+        Some(name) if name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') => {
+            format!("{}/r/{}/search.json", BASE, name)
+        }
+        Some(name) => return Err(format!("Invalid subreddit: {}", name)),
+        None => format!("{}/search.json", BASE),
+    };
+    let time = match time_range {
+        Some(TimeRange::Day) => "day",
+        Some(TimeRange::Week) => "week",
+        Some(TimeRange::Month) => "month",
+        Some(TimeRange::Year) => "year",
+        None => "all",
+    };
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT).to_string();
+    let mut params = vec![("q", query), ("sort", sort), ("t", time), ("limit", &limit), ("raw_json", "1")];
+    if subreddit.is_some() {
+        params.push(("restrict_sr", "1"));
+    }
+    let request = http.get(url).query(&params);
+    let json = get_json(request, "Reddit").await?.ok_or_else(|| "Subreddit not found".to_string())?;
+    let posts: Vec<RedditPost> = children(&json, "t3").map(parse_post).collect();
+    eprintln!("[Reddit] {} posts for: {}", posts.len(), query);
+    Ok(posts)
+}
+
+// Post id of a thread: accepts thread URLs from any reddit host, redd.it links and bare ids
+fn thread_id(url_or_id: &str) -> Result<String, String> {
+    let input = url_or_id.trim();
+    let is_id = |id: &str| !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric());
+    if is_id(input) {
+        return Ok(input.to_string());
+    }
+    let not_thread = || format!("Not a Reddit thread: {}", input);
+    let parsed = reqwest::Url::parse(input).map_err(|_| not_thread())?;
+    let host = parsed.host_str().unwrap_or_default();
+    let segments: Vec<&str> = parsed.path_segments().into_iter().flatten().filter(|s| !s.is_empty()).collect();
+    let id = if host == "redd.it" {
+        segments.first()
+    } else if host == "reddit.com" || host.ends_with(".reddit.com") {
+        segments.iter().position(|s| *s == "comments").and_then(|i| segments.get(i + 1))
+    } else {
+        None
+    };
+    id.filter(|id| is_id(id)).map(|id| id.to_string()).ok_or_else(not_thread)
+}
+
+pub async fn thread(
+    url_or_id: &str,
+    max_comments: Option<usize>,
+    max_depth: Option<usize>,
+    http: &SourceClient,
+) -> Result<RedditThread, String> {
+    let id = thread_id(url_or_id)?;
+    let max_comments = max_comments.unwrap_or(DEFAULT_MAX_COMMENTS).clamp(1, MAX_LIMIT);
+    let max_depth = max_depth.unwrap_or(DEFAULT_MAX_DEPTH).clamp(1, MAX_DEPTH);
+    let request = http.get(format!("{}/comments/{}.json", BASE, id)).query(&[
+        ("sort", "top".to_string()),
+        ("limit", max_comments.to_string()),
+        ("depth", max_depth.to_string()),
+        ("raw_json", "1".to_string()),
+    ]);
+    let json = get_json(request, "Reddit")
+        .await?
+        .ok_or_else(|| format!("Reddit thread not found: {}", url_or_id))?;
+    // [post listing, comment listing]
+    let post = json
+        .get(0)
+        .and_then(|listing| children(listing, "t3").next())
+        .map(parse_post)
+        .ok_or_else(|| "Unexpected response from Reddit".to_string())?;
+    let comments = json
+        .get(1)
+        .map(|listing| parse_comments(listing, 0, max_depth, max_comments))
+        .unwrap_or_default();
+    eprintln!("[Reddit] Thread {} with {} top comments", post.id, comments.len());
+    Ok(RedditThread { post, comments })
+}
+
+pub fn posts_to_markdown(posts: &[RedditPost]) -> String {
+    if posts.is_empty() {
+        return "No posts found.".to_string();
+    }
+    let mut out = String::new();
+    for (i, post) in posts.iter().enumerate() {
+        out.push_str(&format!(
+            "{}. **{}** (r/{}, {} points, {} comments)\n   {}\n",
+            i + 1,
+            post.title,
+            post.subreddit,
+            post.score,
+            post.num_comments,
+            post.url
+        ));
+        if let Some(link) = &post.link_url {
+            out.push_str(&format!("   Link: {}\n", link));
+        }
+        if !post.text.is_empty() {
+            let preview: String = post.text.split_whitespace().collect::<Vec<_>>().join(" ");
+            let mut cut: String = preview.chars().take(PREVIEW_CHARS).collect();
+            if cut.len() < preview.len() {
+                cut.push('…');
+            }
+            out.push_str(&format!("   {}\n", cut));
+        }
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+fn push_comment(out: &mut String, comment: &RedditComment) {
+    let indent = "> ".repeat(comment.depth);
+    out.push_str(&format!("{}**u/{}** ({} points)\n", indent, comment.author, comment.score));
+    for line in comment.body.lines() {
+        out.push_str(&format!("{}{}\n", indent, line));
+    }
+    out.push('\n');
+    for reply in &comment.replies {
+        push_comment(out, reply);
+    }
+}
+
+// The post, then comments with replies quoted one level deeper
+pub fn thread_to_markdown(thread: &RedditThread) -> String {
+    let post = &thread.post;
+    let mut out = format!(
+        "# {}\n\nr/{} · u/{} · {} points · {}\n\n",
+        post.title, post.subreddit, post.author, post.score, post.url
+    );
+    if let Some(link) = &post.link_url {
+        out.push_str(&format!("Link: {}\n\n", link));
+    }
+    if !post.text.is_empty() {
+        out.push_str(&post.text);
+        out.push_str("\n\n");
+    }
+    if !thread.comments.is_empty() {
+        out.push_str("## Top comments\n\n");
+    }
+    for comment in &thread.comments {
+        push_comment(&mut out, comment);
+    }
+    out.trim_end().to_string()
+}
+
+#[tauri::command]
+pub async fn reddit_search(
+    app: AppHandle,
+    query: String,
+    subreddit: Option<String>,
+    sort: Option<String>,
+    time_range: Option<TimeRange>,
+    limit: Option<usize>,
+) -> Result<Vec<RedditPost>, String> {
+    let http = SourceClient::for_app(&app)?;
+    search(&query, subreddit.as_deref(), sort.as_deref(), time_range, limit, &http).await
+}
+
+// A thread by URL or post id, with up to `maxComments` top-level comments and replies `maxDepth` deep
+#[tauri::command]
+pub async fn reddit_thread(
+    app: AppHandle,
+    url: String,
+    max_comments: Option<usize>,
+    max_depth: Option<usize>,
+) -> Result<RedditThread, String> {
+    thread(&url, max_comments, max_depth, &SourceClient::for_app(&app)?).await
+}
@@ -0,0 +1,224 @@
+// Stack Exchange API: questions matching a query on one site (Stack Overflow by default) with
+// their accepted and best-voted answers, bodies rendered as markdown with code blocks kept intact.
+use std::collections::HashMap;
+
+use chrono::DateTime;
+use kuchikiki::NodeRef;
+use serde_json::Value;
+use tauri::AppHandle;
+
+use super::{get_json, string_field, u64_field, SourceClient};
+use crate::html;
+use crate::search::news::format_date;
+
+const API: &str = "https://api.stackexchange.com/2.3";
+const DEFAULT_SITE: &str = "stackoverflow";
+const DEFAULT_MAX_QUESTIONS: usize = 5;
+const DEFAULT_MAX_ANSWERS: usize = 2;
+// Page size limit of the API
+const MAX_PAGE_SIZE: usize = 100;
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StackAnswer {
+    pub id: u64,
+    pub score: i64,
+    pub accepted: bool,
+    // Markdown
+    pub body: String,
+    pub url: String,
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StackQuestion {
+    pub id: u64,
+    pub title: String,
+    pub url: String,
+    pub score: i64,
+    pub tags: Vec<String>,
+    pub answer_count: u64,
+    // Markdown
+    pub body: String,
+    // RFC 3339, UTC
+    pub created: Option<String>,
+    // Accepted answer first, then by votes
+    pub answers: Vec<StackAnswer>,
+}
+
+// Markdown from an API body: <pre> blocks become fenced code and inline <code> gets backticks
+fn body_to_markdown(body: &str) -> String {
+    let document = html::parse(body);
+    let select = |selector: &str| -> Vec<NodeRef> {
+        document
+            .select(selector)
+            .map(|found| found.map(|e| e.as_node().clone()).collect())
+            .unwrap_or_default()
+    };
+    for pre in select("pre") {
+        let newline = if pre.text_contents().ends_with('\n') { "" } else { "\n" };
+        pre.prepend(NodeRef::new_text("```\n"));
+        pre.append(NodeRef::new_text(format!("{}```", newline)));
+    }
+    for code in select(":not(pre) > code") {
+        code.prepend(NodeRef::new_text("`"));
+        code.append(NodeRef::new_text("`"));
+    }
+    html::to_text(&document)
+}
+
+// Titles come HTML-escaped
+fn decode(text: &str) -> String {
+    html::parse(text).text_contents()
+}
+
+fn items(json: &Value) -> impl Iterator<Item = &Value> {
+    json.get("items").and_then(|i| i.as_array()).into_iter().flatten()
+}
+
+fn i64_field(item: &Value, key: &str) -> i64 {
+    item.get(key).and_then(|v| v.as_i64()).unwrap_or(0)
+}
+
+fn parse_question(item: &Value) -> StackQuestion {
+    StackQuestion {
+        id: u64_field(item, "question_id"),
+        title: decode(&string_field(item, "title")),
+        url: string_field(item, "link"),
+        score: i64_field(item, "score"),
+        tags: item
+            .get("tags")
+            .and_then(|t| t.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|t| t.as_str().map(str::to_string))
+            .collect(),
+        answer_count: u64_field(item, "answer_count"),
+        body: body_to_markdown(&string_field(item, "body")),
+        created: item
+            .get("creation_date")
+            .and_then(|v| v.as_i64())
+            .and_then(|secs| DateTime::from_timestamp(secs, 0))
+            .map(format_date),
+        answers: Vec::new(),
+    }
+}
+
+fn valid_site(site: &str) -> bool {
+    !site.is_empty() && site.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+}
+
+// Questions matching `query` on `site`, each with up to `max_answers` answers. `tagged` is a
+// semicolon-separated tag list the questions must all have.
+pub async fn search(
+    query: &str,
+    site: Option<&str>,
+    tagged: Option<&str>,
+    max_questions: Option<usize>,
+    max_answers: Option<usize>,
+    http: &SourceClient,
+) -> Result<Vec<StackQuestion>, String> {
+    let site = site.map(str::trim).filter(|s| !s.is_empty()).unwrap_or(DEFAULT_SITE);
+    if !valid_site(site) {
+        return Err(format!("Invalid Stack Exchange site: {}", site));
+    }
+    let max_questions = max_questions.unwrap_or(DEFAULT_MAX_QUESTIONS).clamp(1, MAX_PAGE_SIZE);
+    let max_answers = max_answers.unwrap_or(DEFAULT_MAX_ANSWERS);
+
+    let page_size = max_questions.to_string();
+    let mut params = vec![
+        ("q", query),
+        ("site", site),
+        ("order", "desc"),
+        ("sort", "relevance"),
+        ("filter", "withbody"),
+        ("pagesize", &page_size),
+    ];
+    if let Some(tagged) = tagged.filter(|t| !t.trim().is_empty()) {
+        params.push(("tagged", tagged));
+    }
+    let request = http.get(format!("{}/search/advanced", API)).query(&params);
+    let json = get_json(request, "Stack Exchange").await?.unwrap_or(Value::Null);
+    let mut questions: Vec<StackQuestion> = items(&json).map(parse_question).collect();
+
+    let answered: Vec<String> = questions
+        .iter()
+        .filter(|q| q.answer_count > 0)
+        .map(|q| q.id.to_string())
+        .collect();
+    if max_answers > 0 && !answered.is_empty() {
+        let request = http
+            .get(format!("{}/questions/{}/answers", API, answered.join(";")))
+            .query(&[
+                ("site", site),
+                ("order", "desc"),
+                ("sort", "votes"),
+                ("filter", "withbody"),
+                ("pagesize", &MAX_PAGE_SIZE.to_string()),
+            ]);
+        let json = get_json(request, "Stack Exchange").await?.unwrap_or(Value::Null);
+        let mut by_question: HashMap<u64, Vec<StackAnswer>> = HashMap::new();
+        for item in items(&json) {
+            let id = u64_field(item, "answer_id");
+            by_question.entry(u64_field(item, "question_id")).or_default().push(StackAnswer {
+                id,
+                score: i64_field(item, "score"),
+                accepted: item.get("is_accepted").and_then(|v| v.as_bool()).unwrap_or(false),
+                body: body_to_markdown(&string_field(item, "body")),
+                url: String::new(),
+            });
+        }
+        for question in &mut questions {
+            // Answers don't carry their link; the short form works on the question's host
+            let host = reqwest::Url::parse(&question.url)
+                .ok()
+                .and_then(|u| u.host_str().map(str::to_string))
+                .unwrap_or_default();
+            let mut answers = by_question.remove(&question.id).unwrap_or_default();
+            for answer in &mut answers {
+                answer.url = format!("https://{}/a/{}", host, answer.id);
+            }
+            answers.sort_by_key(|a| (!a.accepted, std::cmp::Reverse(a.score)));
+            answers.truncate(max_answers);
+            question.answers = answers;
+        }
+    }
+    eprintln!("[StackExchange] {} questions on {} for: {}", questions.len(), site, query);
+    Ok(questions)
+}
+
+pub fn to_markdown(questions: &[StackQuestion]) -> String {
+    if questions.is_empty() {
+        return "No questions found.".to_string();
+    }
+    let mut out = String::new();
+    for question in questions {
+        out.push_str(&format!("# {}\n\n{} · score {}", question.title, question.url, question.score));
+        if !question.tags.is_empty() {
+            out.push_str(&format!(" · {}", question.tags.join(", ")));
+        }
+        out.push_str(&format!("\n\n{}\n\n", question.body));
+        for answer in &question.answers {
+            let label = if answer.accepted { "Accepted answer" } else { "Answer" };
+            out.push_str(&format!("## {} (score {})\n\n{}\n\n", label, answer.score, answer.body));
+        }
+        if question.answers.is_empty() {
+            out.push_str("_No answers yet._\n\n");
+        }
+    }
+    out.trim_end().to_string()
+}
+
+// Questions for a query with their best answers; `site` is an API site name such as superuser
+#[tauri::command]
+pub async fn stackexchange_search(
+    app: AppHandle,
+    query: String,
+    site: Option<String>,
+    tagged: Option<String>,
+    max_questions: Option<usize>,
+    max_answers: Option<usize>,
+) -> Result<Vec<StackQuestion>, String> {
+    let http = SourceClient::for_app(&app)?;
+    search(&query, site.as_deref(), tagged.as_deref(), max_questions, max_answers, &http).await
+}
@@ -0,0 +1,277 @@
+// Weather from Open-Meteo (no API key): the location is geocoded by name, or given as
+// "latitude,longitude", and the forecast has current conditions plus one entry per day.
+use serde_json::Value;
+use tauri::AppHandle;
+
+use super::{get_json, SourceClient};
+
+const GEOCODING_API: &str = "https://geocoding-api.open-meteo.com/v1/search";
+const FORECAST_API: &str = "https://api.open-meteo.com/v1/forecast";
+const CURRENT_FIELDS: &str = "temperature_2m,apparent_temperature,relative_humidity_2m,precipitation,weather_code,\
+    wind_speed_10m,wind_direction_10m,is_day";
+const DAILY_FIELDS: &str = "weather_code,temperature_2m_max,temperature_2m_min,precipitation_sum,\
+    precipitation_probability_max,wind_speed_10m_max,sunrise,sunset";
+const DEFAULT_DAYS: usize = 3;
+// Longest forecast Open-Meteo offers
+const MAX_DAYS: usize = 16;
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WeatherLocation {
+    pub name: String,
+    pub region: Option<String>,
+    pub country: Option<String>,
+    pub latitude: f64,
+    pub longitude: f64,
+    // IANA time zone the times are in
+    pub timezone: Option<String>,
+}
+
+// Temperatures in °C, wind in km/h, precipitation in mm
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentWeather {
+    // Local time, e.g. 2024-06-10T13:00
+    pub time: String,
+    pub temperature: Option<f64>,
+    pub feels_like: Option<f64>,
+    pub humidity: Option<f64>,
+    pub precipitation: Option<f64>,
+    pub wind_speed: Option<f64>,
+    // Degrees the wind comes from
+    pub wind_direction: Option<f64>,
+    pub weather_code: Option<u64>,
+    pub description: String,
+    pub is_day: bool,
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyForecast {
+    pub date: String,
+    pub weather_code: Option<u64>,
+    pub description: String,
+    pub temperature_max: Option<f64>,
+    pub temperature_min: Option<f64>,
+    pub precipitation_sum: Option<f64>,
+    // Highest chance of precipitation during the day, in percent
+    pub precipitation_probability: Option<f64>,
+    pub wind_speed_max: Option<f64>,
+    pub sunrise: Option<String>,
+    pub sunset: Option<String>,
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Weather {
+    pub location: WeatherLocation,
+    pub current: Option<CurrentWeather>,
+    pub daily: Vec<DailyForecast>,
+}
+
+// WMO weather interpretation codes, as used by Open-Meteo
+fn describe(code: Option<u64>) -> String {
+    let text = match code {
+        Some(0) => "Clear sky",
+        Some(1) => "Mainly clear",
+        Some(2) => "Partly cloudy",
+        Some(3) => "Overcast",
+        Some(45 | 48) => "Fog",
+        Some(51) => "Light drizzle",
+        Some(53) => "Drizzle",
+        Some(55) => "Dense drizzle",
+        Some(56 | 57) => "Freezing drizzle",
+        Some(61) => "Light rain",
+        Some(63) => "Rain",
+        Some(65) => "Heavy rain",
+        Some(66 | 67) => "Freezing rain",
+        Some(71) => "Light snow",
+        Some(73) => "Snow",
+        Some(75) => "Heavy snow",
+        Some(77) => "Snow grains",
+        Some(80) => "Light showers",
+        Some(81) => "Showers",
+        Some(82) => "Violent showers",
+        Some(85 | 86) => "Snow showers",
+        Some(95) => "Thunderstorm",
+        Some(96 | 99) => "Thunderstorm with hail",
+        _ => "Unknown",
+    };
+    text.to_string()
+}
+
+// "48.85, 2.35" style coordinates
+fn parse_coordinates(text: &str) -> Option<(f64, f64)> {
+    let (latitude, longitude) = text.split_once(',')?;
+    let (latitude, longitude): (f64, f64) = (latitude.trim().parse().ok()?, longitude.trim().parse().ok()?);
+    ((-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude)).then_some((latitude, longitude))
+}
+
+// Best match for a place name; "Paris, Texas" searches Paris and prefers the result in Texas, since
+// the geocoder only matches names
+async fn geocode(http: &SourceClient, location: &str) -> Result<WeatherLocation, String> {
+    let (name, qualifier) = match location.split_once(',') {
+        Some((name, qualifier)) => (name.trim(), qualifier.trim().to_lowercase()),
+        None => (location, String::new()),
+    };
+    let request = http
+        .get(GEOCODING_API)
+        .query(&[("name", name), ("count", "10"), ("language", "en"), ("format", "json")]);
+    let json = get_json(request, "Open-Meteo geocoding").await?.unwrap_or(Value::Null);
+    let results = json.get("results").and_then(|r| r.as_array()).cloned().unwrap_or_default();
+    let text = |place: &Value, key: &str| place.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    let matches_qualifier = |place: &Value| {
+        ["admin1", "country", "country_code"]
+            .iter()
+            .filter_map(|key| text(place, key))
+            .any(|value| value.to_lowercase() == qualifier)
+    };
+    let place = results
+        .iter()
+        .find(|place| !qualifier.is_empty() && matches_qualifier(place))
+        .or_else(|| results.first())
+        .ok_or_else(|| format!("No place named '{}' found", location))?;
+    Ok(WeatherLocation {
+        name: text(place, "name").unwrap_or_else(|| name.to_string()),
+        region: text(place, "admin1"),
+        country: text(place, "country"),
+        latitude: place.get("latitude").and_then(|v| v.as_f64()).unwrap_or_default(),
+        longitude: place.get("longitude").and_then(|v| v.as_f64()).unwrap_or_default(),
+        timezone: text(place, "timezone"),
+    })
+}
+
+fn parse_current(json: &Value) -> Option<CurrentWeather> {
+    let current = json.get("current")?;
+    let number = |key: &str| current.get(key).and_then(|v| v.as_f64());
+    let weather_code = current.get("weather_code").and_then(|v| v.as_u64());
+    Some(CurrentWeather {
+        time: current.get("time").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+        temperature: number("temperature_2m"),
+        feels_like: number("apparent_temperature"),
+        humidity: number("relative_humidity_2m"),
+        precipitation: number("precipitation"),
+        wind_speed: number("wind_speed_10m"),
+        wind_direction: number("wind_direction_10m"),
+        weather_code,
+        description: describe(weather_code),
+        is_day: number("is_day") == Some(1.0),
+    })
+}
+
+// Daily values come as parallel arrays, one per field
+fn parse_daily(json: &Value) -> Vec<DailyForecast> {
+    let Some(daily) = json.get("daily") else { return Vec::new() };
+    let column = |key: &str| daily.get(key).and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    let (dates, codes) = (column("time"), column("weather_code"));
+    let (max, min) = (column("temperature_2m_max"), column("temperature_2m_min"));
+    let (rain, chance, wind) = (
+        column("precipitation_sum"),
+        column("precipitation_probability_max"),
+        column("wind_speed_10m_max"),
+    );
+    let (sunrise, sunset) = (column("sunrise"), column("sunset"));
+    let number = |values: &[Value], i: usize| values.get(i).and_then(|v| v.as_f64());
+    let text = |values: &[Value], i: usize| values.get(i).and_then(|v| v.as_str()).map(str::to_string);
+    (0..dates.len())
+        .map(|i| {
+            let weather_code = codes.get(i).and_then(|v| v.as_u64());
+            DailyForecast {
+                date: text(&dates, i).unwrap_or_default(),
+                weather_code,
+                description: describe(weather_code),
+                temperature_max: number(&max, i),
+                temperature_min: number(&min, i),
+                precipitation_sum: number(&rain, i),
+                precipitation_probability: number(&chance, i),
+                wind_speed_max: number(&wind, i),
+                sunrise: text(&sunrise, i),
+                sunset: text(&sunset, i),
+            }
+        })
+        .collect()
+}
+
+// Current conditions and a `days`-day forecast (default 3, at most 16)
+pub async fn forecast(location: &str, days: Option<usize>, http: &SourceClient) -> Result<Weather, String> {
+    let location = location.trim();
+    if location.is_empty() {
+        return Err("No location given".to_string());
+    }
+    let days = days.unwrap_or(DEFAULT_DAYS).clamp(1, MAX_DAYS);
+    let mut place = match parse_coordinates(location) {
+        Some((latitude, longitude)) => WeatherLocation {
+            name: location.to_string(),
+            region: None,
+            country: None,
+            latitude,
+            longitude,
+            timezone: None,
+        },
+        None => geocode(http, location).await?,
+    };
+
+    let request = http.get(FORECAST_API).query(&[
+        ("latitude", place.latitude.to_string()),
+        ("longitude", place.longitude.to_string()),
+        ("current", CURRENT_FIELDS.to_string()),
+        ("daily", DAILY_FIELDS.to_string()),
+        ("timezone", "auto".to_string()),
+        ("forecast_days", days.to_string()),
+    ]);
+    let json = get_json(request, "Open-Meteo").await?.ok_or("Open-Meteo has no forecast for this location")?;
+    if place.timezone.is_none() {
+        place.timezone = json.get("timezone").and_then(|v| v.as_str()).map(str::to_string);
+    }
+    let weather = Weather {
+        current: parse_current(&json),
+        daily: parse_daily(&json),
+        location: place,
+    };
+    eprintln!("[Weather] {} day forecast for {}", weather.daily.len(), weather.location.name);
+    Ok(weather)
+}
+
+fn value(number: Option<f64>, unit: &str) -> String {
+    number.map_or("?".to_string(), |n| format!("{:.0}{}", n, unit))
+}
+
+pub fn to_markdown(weather: &Weather) -> String {
+    let place = &weather.location;
+    let area: Vec<&str> = [place.region.as_deref(), place.country.as_deref()].into_iter().flatten().collect();
+    let mut out = format!("# Weather for {}", place.name);
+    if !area.is_empty() {
+        out.push_str(&format!(", {}", area.join(", ")));
+    }
+    out.push_str("\n\n");
+    if let Some(now) = &weather.current {
+        out.push_str(&format!(
+            "Now ({}): {}, {} (feels like {}), humidity {}, wind {}\n\n",
+            now.time,
+            now.description,
+            value(now.temperature, "°C"),
+            value(now.feels_like, "°C"),
+            value(now.humidity, "%"),
+            value(now.wind_speed, " km/h")
+        ));
+    }
+    for day in &weather.daily {
+        out.push_str(&format!(
+            "- {}: {}, {} to {}, precipitation {} ({} chance), wind up to {}\n",
+            day.date,
+            day.description,
+            value(day.temperature_min, "°C"),
+            value(day.temperature_max, "°C"),
+            day.precipitation_sum.map_or("?".to_string(), |mm| format!("{:.1} mm", mm)),
+            value(day.precipitation_probability, "%"),
+            value(day.wind_speed_max, " km/h")
+        ));
+    }
+    out.trim_end().to_string()
+}
+
+// Forecast for a place name or "latitude,longitude"
+#[tauri::command]
+pub async fn get_weather(app: AppHandle, location: String, days: Option<usize>) -> Result<Weather, String> {
+    forecast(&location, days, &SourceClient::for_app(&app)?).await
+}
@@ -0,0 +1,202 @@
+// Wikipedia through its REST and action APIs: page summaries, search for queries that aren't an
+// exact title, and full articles as plain text split into their sections.
+use serde_json::Value;
+use tauri::AppHandle;
+
+use super::{get_json, str_field, string_field, SourceClient};
+
+const SEARCH_LIMIT: usize = 5;
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ArticleSection {
+    pub title: String,
+    // 2 for top-level sections (== Title ==), deeper ones count up
+    pub level: usize,
+    pub text: String,
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WikipediaArticle {
+    pub title: String,
+    pub description: Option<String>,
+    pub summary: String,
+    pub url: String,
+    pub lang: String,
+    // The page lists meanings of an ambiguous title instead of describing one
+    pub disambiguation: bool,
+    // Lead text and sections of the full article, when requested
+    pub sections: Vec<ArticleSection>,
+    // Other pages the query matched
+    pub alternatives: Vec<String>,
+}
+
+fn api_base(lang: &str) -> Result<String, String> {
+    let valid = (2..=12).contains(&lang.len()) && lang.chars().all(|c| c.is_ascii_lowercase() || c == '-');
+    if !valid {
+        return Err(format!("Invalid Wikipedia language code: {}", lang));
+    }
+    Ok(format!("https://{}.wikipedia.org", lang))
+}
+
+async fn summary(http: &SourceClient, base: &str, title: &str) -> Result<Option<Value>, String> {
+    let title = urlencoding::encode(&title.trim().replace(' ', "_")).into_owned();
+    let url = format!("{}/api/rest_v1/page/summary/{}?redirect=true", base, title);
+    get_json(http.get(url), "Wikipedia").await
+}
+
+// Titles of the pages best matching a query
+async fn search(http: &SourceClient, base: &str, query: &str) -> Result<Vec<String>, String> {
+    let request = http
+        .get(format!("{}/w/rest.php/v1/search/page", base))
+        .query(&[("q", query), ("limit", &SEARCH_LIMIT.to_string())]);
+    let json = get_json(request, "Wikipedia").await?.unwrap_or(Value::Null);
+    Ok(json
+        .get("pages")
+        .and_then(|p| p.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|page| str_field(page, "/title").map(str::to_string))
+        .collect())
+}
+
+// Plain-text article with "== Heading ==" lines, split at the headings. Sections that are empty in
+// plain text (references, galleries) are left out.
+async fn sections(http: &SourceClient, base: &str, title: &str) -> Result<Vec<ArticleSection>, String> {
+    let request = http.get(format!("{}/w/api.php", base)).query(&[
+        ("action", "query"),
+        ("prop", "extracts"),
+        ("explaintext", "1"),
+        ("exsectionformat", "wiki"),
+        ("redirects", "1"),
+        ("format", "json"),
+        ("formatversion", "2"),
+        ("titles", title),
+    ]);
+    let json = get_json(request, "Wikipedia").await?.unwrap_or(Value::Null);
+    let extract = string_field(&json, "/query/pages/0/extract");
+    Ok(split_sections(&extract))
+}
+
+fn split_sections(extract: &str) -> Vec<ArticleSection> {
+    let mut sections = vec![ArticleSection {
+        title: String::new(),
+        level: 1,
+        text: String::new(),
+    }];
+    for line in extract.lines() {
+        let trimmed = line.trim();
+        let level = trimmed.chars().take_while(|&c| c == '=').count();
+        let is_heading = level >= 2 && trimmed.len() > level * 2 && trimmed.ends_with(&"=".repeat(level));
+        if is_heading {
+            sections.push(ArticleSection {
+                title: trimmed[level..trimmed.len() - level].trim().to_string(),
+                level,
+                text: String::new(),
+            });
+        } else if let Some(section) = sections.last_mut() {
+            section.text.push_str(line);
+            section.text.push('\n');
+        }
+    }
+    sections
+        .into_iter()
+        .map(|section| ArticleSection {
+            text: section.text.trim().to_string(),
+            ..section
+        })
+        .filter(|section| !section.text.is_empty())
+        .collect()
+}
+
+// Look a title up, falling back to search when no page has exactly that title
+pub async fn lookup(
+    title_or_query: &str,
+    lang: &str,
+    full: bool,
+    http: &SourceClient,
+) -> Result<WikipediaArticle, String> {
+    let query = title_or_query.trim();
+    if query.is_empty() {
+        return Err("Nothing to look up".to_string());
+    }
+    let base = api_base(lang)?;
+
+    let mut alternatives = Vec::new();
+    let page = match summary(http, &base, query).await? {
+        Some(page) => page,
+        None => {
+            alternatives = search(http, &base, query).await?;
+            if alternatives.is_empty() {
+                return Err(format!("No Wikipedia ({}) article matches '{}'", lang, query));
+            }
+            let best = alternatives.remove(0);
+            summary(http, &base, &best)
+                .await?
+                .ok_or_else(|| format!("Wikipedia has no summary for '{}'", best))?
+        }
+    };
+
+    let title = str_field(&page, "/title").unwrap_or(query).to_string();
+    let disambiguation = str_field(&page, "/type") == Some("disambiguation");
+    if disambiguation && alternatives.is_empty() {
+        alternatives = search(http, &base, query).await?;
+        alternatives.retain(|t| *t != title);
+    }
+    let sections = if full && !disambiguation {
+        sections(http, &base, &title).await?
+    } else {
+        Vec::new()
+    };
+    eprintln!("[Wikipedia] {} ({}), {} sections", title, lang, sections.len());
+
+    Ok(WikipediaArticle {
+        url: str_field(&page, "/content_urls/desktop/page")
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{}/wiki/{}", base, urlencoding::encode(&title.replace(' ', "_")))),
+        description: str_field(&page, "/description").map(str::to_string),
+        summary: string_field(&page, "/extract"),
+        title,
+        lang: lang.to_string(),
+        disambiguation,
+        sections,
+        alternatives,
+    })
+}
+
+// Markdown for the agent: summary, or the full article with its headings
+pub fn to_markdown(article: &WikipediaArticle) -> String {
+    let mut out = format!("# {}\n\nSource: {}\n\n", article.title, article.url);
+    if let Some(description) = &article.description {
+        out.push_str(&format!("_{}_\n\n", description));
+    }
+    if article.sections.is_empty() {
+        out.push_str(&article.summary);
+        out.push_str("\n\n");
+    }
+    for section in &article.sections {
+        if section.level > 1 {
+            out.push_str(&format!("{} {}\n\n", "#".repeat(section.level), section.title));
+        }
+        out.push_str(&section.text);
+        out.push_str("\n\n");
+    }
+    if !article.alternatives.is_empty() {
+        let label = if article.disambiguation { "Possible meanings" } else { "Other matches" };
+        out.push_str(&format!("{}: {}\n", label, article.alternatives.join("; ")));
+    }
+    out.trim_end().to_string()
+}
+
+// Summary of the article titled (or best matching) `titleOrQuery`; `full` adds all sections
+#[tauri::command]
+pub async fn wikipedia_lookup(
+    app: AppHandle,
+    title_or_query: String,
+    lang: Option<String>,
+    full: Option<bool>,
+) -> Result<WikipediaArticle, String> {
+    let lang = lang.unwrap_or_else(|| "en".to_string());
+    lookup(&title_or_query, &lang, full.unwrap_or(false), &SourceClient::for_app(&app)?).await
+}
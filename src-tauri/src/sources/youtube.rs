@@ -0,0 +1,262 @@
+// YouTube captions without a browser: the watch page embeds the player response, which lists the
+// video's caption tracks; the chosen track is fetched from the timedtext endpoint as JSON.
+use serde_json::Value;
+use tauri::AppHandle;
+
+use super::SourceClient;
+use crate::http_client::BROWSER_USER_AGENT;
+
+// Skips the cookie consent interstitial served in the EU
+const CONSENT_COOKIE: &str = "SOCS=CAI";
+const PLAYER_RESPONSE_MARKER: &str = "ytInitialPlayerResponse = ";
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoMetadata {
+    pub id: String,
+    pub title: String,
+    pub channel: String,
+    pub url: String,
+    pub duration_secs: Option<u64>,
+    pub view_count: Option<u64>,
+    pub description: String,
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptSegment {
+    // Seconds from the start of the video
+    pub start: f64,
+    pub duration: f64,
+    pub text: String,
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct YoutubeTranscript {
+    pub video: VideoMetadata,
+    // Language code of the segments, e.g. en
+    pub language: String,
+    // Speech recognition captions rather than ones uploaded by the creator
+    pub auto_generated: bool,
+    // Whether YouTube translated the captions into the requested language
+    pub translated: bool,
+    pub segments: Vec<TranscriptSegment>,
+    // Languages the video has caption tracks in
+    pub available_languages: Vec<String>,
+}
+
+fn is_video_id(id: &str) -> bool {
+    id.len() == 11 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+// Video id from watch, youtu.be, shorts, embed and live URLs, or a bare id
+pub fn video_id(url: &str) -> Option<String> {
+    let url = url.trim();
+    if is_video_id(url) {
+        return Some(url.to_string());
+    }
+    let parsed = reqwest::Url::parse(url).ok()?;
+    let host = parsed.host_str()?.trim_start_matches("www.").trim_start_matches("m.");
+    let mut segments = parsed.path_segments()?.filter(|s| !s.is_empty());
+    let id = match host {
+        "youtu.be" => segments.next().map(str::to_string),
+        "youtube.com" | "music.youtube.com" | "youtube-nocookie.com" => match segments.next() {
+            Some("watch") => parsed.query_pairs().find(|(k, _)| k == "v").map(|(_, v)| v.into_owned()),
+            Some("shorts" | "embed" | "live" | "v") => segments.next().map(str::to_string),
+            _ => None,
+        },
+        _ => None,
+    };
+    id.filter(|id| is_video_id(id))
+}
+
+// The player response JSON embedded in the watch page
+fn player_response(html: &str) -> Option<Value> {
+    let start = html.find(PLAYER_RESPONSE_MARKER)? + PLAYER_RESPONSE_MARKER.len();
+    // The object is followed by more script, which the stream deserializer leaves unread
+    serde_json::Deserializer::from_str(&html[start..])
+        .into_iter::<Value>()
+        .next()?
+        .ok()
+}
+
+fn metadata(id: &str, player: &Value) -> VideoMetadata {
+    let details = player.get("videoDetails").cloned().unwrap_or(Value::Null);
+    let text = |key: &str| details.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    // Counts come as strings
+    let number = |key: &str| details.get(key).and_then(|v| v.as_str()).and_then(|v| v.parse().ok());
+    VideoMetadata {
+        id: id.to_string(),
+        title: text("title"),
+        channel: text("author"),
+        url: format!("https://www.youtube.com/watch?v={}", id),
+        duration_secs: number("lengthSeconds"),
+        view_count: number("viewCount"),
+        description: text("shortDescription"),
+    }
+}
+
+struct CaptionTrack {
+    base_url: String,
+    language: String,
+    auto_generated: bool,
+}
+
+fn caption_tracks(player: &Value) -> Vec<CaptionTrack> {
+    player
+        .pointer("/captions/playerCaptionsTracklistRenderer/captionTracks")
+        .and_then(|t| t.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|track| {
+            Some(CaptionTrack {
+                base_url: track.get("baseUrl")?.as_str()?.to_string(),
+                language: track.get("languageCode")?.as_str()?.to_string(),
+                auto_generated: track.get("kind").and_then(|k| k.as_str()) == Some("asr"),
+            })
+        })
+        .collect()
+}
+
+// Track in `lang` (creator captions before speech recognition), matching en-US to en; otherwise
+// the first track, to be translated when a language was asked for
+fn choose_track<'a>(tracks: &'a [CaptionTrack], lang: Option<&str>) -> Option<(&'a CaptionTrack, bool)> {
+    let Some(lang) = lang else {
+        let best = tracks.iter().find(|t| !t.auto_generated).or_else(|| tracks.first())?;
+        return Some((best, false));
+    };
+    let base = |code: &str| code.split('-').next().unwrap_or(code).to_ascii_lowercase();
+    let matching: Vec<&CaptionTrack> = tracks
+        .iter()
+        .filter(|t| t.language.eq_ignore_ascii_case(lang))
+        .chain(tracks.iter().filter(|t| base(&t.language) == base(lang)))
+        .collect();
+    match matching.iter().find(|t| !t.auto_generated).or_else(|| matching.first()) {
+        Some(track) => Some((track, false)),
+        None => Some((tracks.iter().find(|t| !t.auto_generated).or_else(|| tracks.first())?, true)),
+    }
+}
+
+// Segments of a json3 timedtext document; line breaks within a caption become spaces
+fn parse_json3(json: &Value) -> Vec<TranscriptSegment> {
+    json.get("events")
+        .and_then(|e| e.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|event| {
+            let text: String = event
+                .get("segs")?
+                .as_array()?
+                .iter()
+                .filter_map(|seg| seg.get("utf8")?.as_str())
+                .collect();
+            let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            if text.is_empty() {
+                return None;
+            }
+            let millis = |key: &str| event.get(key).and_then(|v| v.as_f64()).unwrap_or(0.0) / 1000.0;
+            Some(TranscriptSegment {
+                start: millis("tStartMs"),
+                duration: millis("dDurationMs"),
+                text,
+            })
+        })
+        .collect()
+}
+
+// Transcript of a video; `lang` picks the caption language, translating when the video has none in it
+pub async fn transcript(url: &str, lang: Option<&str>, http: &SourceClient) -> Result<YoutubeTranscript, String> {
+    let id = video_id(url).ok_or_else(|| format!("Not a YouTube video: {}", url))?;
+    let lang = lang.map(str::trim).filter(|l| !l.is_empty());
+
+    // The watch page only embeds the player response for browsers
+    let html = http
+        .get_as("https://www.youtube.com/watch", BROWSER_USER_AGENT)
+        .query(&[("v", id.as_str()), ("hl", "en")])
+        .header(reqwest::header::COOKIE, CONSENT_COOKIE)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to load the YouTube video: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read the YouTube page: {}", e))?;
+    let player = player_response(&html).ok_or("Could not read the video details from YouTube")?;
+    if let Some(reason) = player.pointer("/playabilityStatus/reason").and_then(|r| r.as_str()) {
+        if player.get("videoDetails").is_none() {
+            return Err(format!("YouTube: {}", reason));
+        }
+    }
+    let video = metadata(&id, &player);
+
+    let tracks = caption_tracks(&player);
+    let (track, translated) =
+        choose_track(&tracks, lang).ok_or_else(|| format!("'{}' has no captions", video.title))?;
+    let mut request = http.get_as(&track.base_url, BROWSER_USER_AGENT).query(&[("fmt", "json3")]);
+    if let Some(lang) = lang.filter(|_| translated) {
+        request = request.query(&[("tlang", lang)]);
+    }
+    let body = request
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch captions: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read captions: {}", e))?;
+    // YouTube answers some clients with an empty body instead of an error
+    if body.trim().is_empty() {
+        return Err("YouTube returned no captions for this video".to_string());
+    }
+    let json: Value = serde_json::from_str(&body).map_err(|e| format!("Invalid captions from YouTube: {}", e))?;
+    let segments = parse_json3(&json);
+    eprintln!("[YouTube] {} segments for {} ({})", segments.len(), id, track.language);
+
+    let mut available_languages: Vec<String> = Vec::new();
+    for track in &tracks {
+        if !available_languages.contains(&track.language) {
+            available_languages.push(track.language.clone());
+        }
+    }
+    Ok(YoutubeTranscript {
+        language: if translated { lang.unwrap_or_default().to_string() } else { track.language.clone() },
+        auto_generated: track.auto_generated,
+        translated,
+        segments,
+        available_languages,
+        video,
+    })
+}
+
+fn timestamp(secs: f64) -> String {
+    let secs = secs as u64;
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+// Video details, then one line per caption with its timestamp
+pub fn to_markdown(transcript: &YoutubeTranscript) -> String {
+    let video = &transcript.video;
+    let mut out = format!("# {}\n\nChannel: {}\nURL: {}\n", video.title, video.channel, video.url);
+    if let Some(duration) = video.duration_secs {
+        out.push_str(&format!("Duration: {}\n", timestamp(duration as f64)));
+    }
+    let kind = match (transcript.auto_generated, transcript.translated) {
+        (_, true) => "translated captions",
+        (true, false) => "automatic captions",
+        (false, false) => "captions",
+    };
+    out.push_str(&format!("Transcript: {} ({})\n\n", transcript.language, kind));
+    for segment in &transcript.segments {
+        out.push_str(&format!("[{}] {}\n", timestamp(segment.start), segment.text));
+    }
+    out.trim_end().to_string()
+}
+
+// Timestamped captions and details of a video; `lang` (e.g. en, de) defaults to the video's own captions
+#[tauri::command]
+pub async fn get_youtube_transcript(app: AppHandle, url: String, lang: Option<String>) -> Result<YoutubeTranscript, String> {
+    transcript(&url, lang.as_deref(), &SourceClient::for_app(&app)?).await
+}
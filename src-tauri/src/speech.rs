@@ -0,0 +1,412 @@
+// Text-to-speech for reading responses aloud, built with the `tts` feature. The system voice
+// (WinRT/SAPI on Windows, AVSpeechSynthesizer on macOS, speech-dispatcher on Linux) is used by
+// default; a voice given as a Piper model (`.onnx`) is synthesized by the `piper` executable and
+// streamed to the default output device while it's still being generated.
+//
+// Speech engines and audio output aren't thread-safe, so one worker thread owns them and
+// commands reach it through a channel. Progress is broadcast as `speech-status` events.
+use std::sync::{mpsc, Mutex};
+
+use tauri::{AppHandle, State};
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoiceInfo {
+    id: String,
+    name: String,
+    // BCP 47 tag such as en-US
+    language: String,
+}
+
+#[cfg_attr(not(feature = "tts"), allow(dead_code))]
+struct Utterance {
+    id: String,
+    text: String,
+    // System voice id or name, or the path of a Piper model
+    voice: Option<String>,
+    // Multiple of the normal speaking rate
+    rate: f32,
+}
+
+#[cfg_attr(not(feature = "tts"), allow(dead_code))]
+enum Request {
+    Speak(Utterance),
+    Stop,
+    Pause(mpsc::Sender<Result<(), String>>),
+    Resume(mpsc::Sender<Result<(), String>>),
+    Voices(mpsc::Sender<Result<Vec<VoiceInfo>, String>>),
+}
+
+// Channel to the speech worker, started on first use
+#[derive(Default)]
+pub struct SpeechEngine {
+    requests: Mutex<Option<mpsc::Sender<Request>>>,
+}
+
+#[cfg(feature = "tts")]
+mod worker {
+    use std::io::Read;
+    use std::path::Path;
+    use std::process::{Child, Command, Stdio};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{mpsc, Arc, Mutex};
+
+    use rodio::buffer::SamplesBuffer;
+    use rodio::{OutputStream, OutputStreamHandle, Sink};
+    use tauri::{AppHandle, Emitter};
+
+    use super::{Request, Utterance, VoiceInfo};
+    use crate::settings;
+
+    const STATUS_EVENT: &str = "speech-status";
+
+    #[derive(serde::Serialize, Clone)]
+    #[serde(rename_all = "camelCase")]
+    struct SpeechStatus {
+        utterance_id: String,
+        // speaking, paused, resumed, finished, stopped or failed
+        state: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    }
+
+    // Piper's default when the model config doesn't say
+    const PIPER_SAMPLE_RATE: u32 = 22_050;
+    // Samples handed to the audio device at a time (about a quarter second)
+    const STREAM_CHUNK: usize = 4096;
+
+    fn speech_error(e: impl std::fmt::Display) -> String {
+        format!("Speech error: {}", e)
+    }
+
+    fn emit_status(app: &AppHandle, utterance_id: &str, state: &'static str, error: Option<String>) {
+        let status = SpeechStatus {
+            utterance_id: utterance_id.to_string(),
+            state,
+            error,
+        };
+        if let Err(e) = app.emit(STATUS_EVENT, status) {
+            eprintln!("[Speech] Failed to emit status: {}", e);
+        }
+    }
+
+    struct Playback {
+        id: String,
+        sink: Arc<Sink>,
+        child: Arc<Mutex<Child>>,
+        stopped: Arc<AtomicBool>,
+    }
+
+    struct Worker {
+        app: AppHandle,
+        system: Option<tts::Tts>,
+        output: Option<(OutputStream, OutputStreamHandle)>,
+        piper: Option<Playback>,
+        // Utterance the system voice is reading
+        current: Arc<Mutex<Option<String>>>,
+    }
+
+    fn is_piper_voice(voice: &str) -> bool {
+        voice.to_lowercase().ends_with(".onnx")
+    }
+
+    // Sample rate from the model's config file (voice.onnx.json)
+    fn piper_sample_rate(model: &str) -> u32 {
+        std::fs::read_to_string(format!("{}.json", model))
+            .ok()
+            .and_then(|text| serde_json::from_str::<serde_json::Value>(&text).ok())
+            .and_then(|config| config["audio"]["sample_rate"].as_u64())
+            .map(|rate| rate as u32)
+            .unwrap_or(PIPER_SAMPLE_RATE)
+    }
+
+    impl Worker {
+        fn system(&mut self) -> Result<&mut tts::Tts, String> {
+            if self.system.is_none() {
+                let tts = tts::Tts::default().map_err(speech_error)?;
+                if tts.supported_features().utterance_callbacks {
+                    let (app, current) = (self.app.clone(), self.current.clone());
+                    tts.on_utterance_end(Some(Box::new(move |_| {
+                        if let Some(id) = current.lock().ok().and_then(|mut c| c.take()) {
+                            emit_status(&app, &id, "finished", None);
+                        }
+                    })))
+                    .map_err(speech_error)?;
+                }
+                self.system = Some(tts);
+            }
+            Ok(self.system.as_mut().expect("just created"))
+        }
+
+        fn output(&mut self) -> Result<&OutputStreamHandle, String> {
+            if self.output.is_none() {
+                self.output = Some(OutputStream::try_default().map_err(|e| format!("No audio output: {}", e))?);
+            }
+            Ok(&self.output.as_ref().expect("just created").1)
+        }
+
+        fn stop(&mut self) {
+            if let Some(playback) = self.piper.take() {
+                playback.stopped.store(true, Ordering::SeqCst);
+                playback.sink.stop();
+                if let Ok(mut child) = playback.child.lock() {
+                    let _ = child.kill();
+                }
+                emit_status(&self.app, &playback.id, "stopped", None);
+            }
+            let interrupted = self.current.lock().ok().and_then(|mut c| c.take());
+            if let (Some(id), Some(system)) = (interrupted, self.system.as_mut()) {
+                if let Err(err) = system.stop() {
+                    eprintln!("[Speech] Failed to stop the system voice: {}", err);
+                }
+                emit_status(&self.app, &id, "stopped", None);
+            }
+        }
+
+        fn speak_system(&mut self, utterance: Utterance) -> Result<(), String> {
+            let system = self.system()?;
+            if let Some(wanted) = &utterance.voice {
+                let voices = system.voices().map_err(speech_error)?;
+                let voice = voices
+                    .iter()
+                    .find(|v| v.id() == *wanted || v.name().eq_ignore_ascii_case(wanted))
+                    .ok_or_else(|| format!("Voice '{}' not found", wanted))?;
+                system.set_voice(voice).map_err(speech_error)?;
+            }
+            if system.supported_features().rate {
+                let rate = (system.normal_rate() * utterance.rate).clamp(system.min_rate(), system.max_rate());
+                system.set_rate(rate).map_err(speech_error)?;
+            }
+            system.speak(utterance.text, true).map_err(speech_error)?;
+            if let Ok(mut current) = self.current.lock() {
+                *current = Some(utterance.id);
+            }
+            Ok(())
+        }
+
+        // Raw 16-bit mono PCM from `piper --output-raw`, played chunk by chunk as it arrives
+        fn speak_piper(&mut self, utterance: Utterance, model: String) -> Result<(), String> {
+            if !Path::new(&model).is_file() {
+                return Err(format!("Piper voice {} not found", model));
+            }
+            let binary = settings::load(&self.app)
+                .piper_path
+                .filter(|p| !p.trim().is_empty())
+                .unwrap_or_else(|| "piper".to_string());
+            let sample_rate = piper_sample_rate(&model);
+            let mut child = Command::new(&binary)
+                .args(["--model", &model, "--output-raw", "--length_scale", &format!("{}", 1.0 / utterance.rate)])
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .map_err(|e| format!("Failed to run {} (is Piper installed?): {}", binary, e))?;
+            let mut stdin = child.stdin.take().ok_or("Piper has no input")?;
+            let mut stdout = child.stdout.take().ok_or("Piper has no output")?;
+            // Piper reads one utterance per line
+            let text = utterance.text.split_whitespace().collect::<Vec<_>>().join(" ");
+            std::thread::spawn(move || {
+                use std::io::Write;
+                let _ = writeln!(stdin, "{}", text);
+            });
+
+            let sink = Arc::new(Sink::try_new(self.output()?).map_err(|e| format!("No audio output: {}", e))?);
+            let stopped = Arc::new(AtomicBool::new(false));
+            let child = Arc::new(Mutex::new(child));
+            let (app, id) = (self.app.clone(), utterance.id.clone());
+            let (stream_sink, stream_stopped, stream_child) = (sink.clone(), stopped.clone(), child.clone());
+            std::thread::spawn(move || {
+                let mut buffer = vec![0u8; STREAM_CHUNK * 2];
+                let mut pending: Vec<u8> = Vec::new();
+                loop {
+                    let read = match stdout.read(&mut buffer) {
+                        Ok(0) | Err(_) => break,
+                        Ok(read) => read,
+                    };
+                    pending.extend_from_slice(&buffer[..read]);
+                    let whole = pending.len() / 2 * 2;
+                    let samples: Vec<i16> =
+                        pending[..whole].chunks_exact(2).map(|b| i16::from_le_bytes([b[0], b[1]])).collect();
+                    pending.drain(..whole);
+                    stream_sink.append(SamplesBuffer::new(1, sample_rate, samples));
+                }
+                let failed = stream_child
+                    .lock()
+                    .ok()
+                    .and_then(|mut c| c.wait().ok())
+                    .is_some_and(|status| !status.success());
+                stream_sink.sleep_until_end();
+                if !stream_stopped.load(Ordering::SeqCst) {
+                    if failed {
+                        emit_status(&app, &id, "failed", Some("Piper exited with an error".to_string()));
+                    } else {
+                        emit_status(&app, &id, "finished", None);
+                    }
+                }
+            });
+            self.piper = Some(Playback {
+                id: utterance.id,
+                sink,
+                child,
+                stopped,
+            });
+            Ok(())
+        }
+
+        fn set_paused(&mut self, paused: bool) -> Result<(), String> {
+            let playback = self
+                .piper
+                .as_ref()
+                .filter(|p| !p.sink.empty())
+                .ok_or("Only Piper voices can be paused; the system voice can only be stopped")?;
+            if paused {
+                playback.sink.pause();
+            } else {
+                playback.sink.play();
+            }
+            emit_status(&self.app, &playback.id, if paused { "paused" } else { "resumed" }, None);
+            Ok(())
+        }
+
+        fn voices(&mut self) -> Result<Vec<VoiceInfo>, String> {
+            let voices = self.system()?.voices().map_err(speech_error)?;
+            Ok(voices
+                .into_iter()
+                .map(|v| VoiceInfo {
+                    id: v.id(),
+                    name: v.name(),
+                    language: v.language().to_string(),
+                })
+                .collect())
+        }
+    }
+
+    pub fn run(app: AppHandle, requests: mpsc::Receiver<Request>) {
+        let mut worker = Worker {
+            app,
+            system: None,
+            output: None,
+            piper: None,
+            current: Arc::default(),
+        };
+        for request in requests {
+            match request {
+                Request::Speak(mut utterance) => {
+                    worker.stop();
+                    let id = utterance.id.clone();
+                    let voice = utterance
+                        .voice
+                        .take()
+                        .or_else(|| settings::load(&worker.app).tts_voice)
+                        .filter(|v| !v.trim().is_empty());
+                    let result = match voice {
+                        Some(model) if is_piper_voice(&model) => worker.speak_piper(utterance, model),
+                        voice => worker.speak_system(Utterance { voice, ..utterance }),
+                    };
+                    match result {
+                        Ok(()) => emit_status(&worker.app, &id, "speaking", None),
+                        Err(err) => {
+                            eprintln!("[Speech] {}", err);
+                            emit_status(&worker.app, &id, "failed", Some(err));
+                        }
+                    }
+                }
+                Request::Stop => worker.stop(),
+                Request::Pause(reply) => {
+                    let _ = reply.send(worker.set_paused(true));
+                }
+                Request::Resume(reply) => {
+                    let _ = reply.send(worker.set_paused(false));
+                }
+                Request::Voices(reply) => {
+                    let _ = reply.send(worker.voices());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tts")]
+fn start_worker(app: &AppHandle) -> Result<mpsc::Sender<Request>, String> {
+    let (sender, receiver) = mpsc::channel();
+    let app = app.clone();
+    std::thread::Builder::new()
+        .name("speech".to_string())
+        .spawn(move || worker::run(app, receiver))
+        .map_err(|e| format!("Failed to start speech worker: {}", e))?;
+    Ok(sender)
+}
+
+#[cfg(not(feature = "tts"))]
+fn start_worker(_app: &AppHandle) -> Result<mpsc::Sender<Request>, String> {
+    Err("Text-to-speech is not available in this build".to_string())
+}
+
+fn send(app: &AppHandle, engine: &SpeechEngine, request: Request) -> Result<(), String> {
+    let mut requests = engine.requests.lock().map_err(|_| "Speech state poisoned".to_string())?;
+    if requests.is_none() {
+        *requests = Some(start_worker(app)?);
+    }
+    requests
+        .as_ref()
+        .expect("just started")
+        .send(request)
+        .map_err(|_| "Speech worker has stopped".to_string())
+}
+
+// Send a request that answers, and wait for the answer
+fn ask<T>(app: &AppHandle, engine: &SpeechEngine, request: impl FnOnce(mpsc::Sender<T>) -> Request) -> Result<T, String> {
+    let (reply, answer) = mpsc::channel();
+    send(app, engine, request(reply))?;
+    answer.recv().map_err(|_| "Speech worker has stopped".to_string())
+}
+
+// Read text aloud, interrupting whatever is being read. `voice` is a system voice id or name, or
+// the path of a Piper model (.onnx); `rate` multiplies the normal speed. Returns the utterance id
+// used in `speech-status` events.
+#[tauri::command]
+pub fn speak(
+    app: AppHandle,
+    engine: State<'_, SpeechEngine>,
+    text: String,
+    voice: Option<String>,
+    rate: Option<f32>,
+) -> Result<String, String> {
+    if text.trim().is_empty() {
+        return Err("Nothing to read".to_string());
+    }
+    let rate = rate.unwrap_or(1.0);
+    if !(0.25..=4.0).contains(&rate) {
+        return Err("rate must be between 0.25 and 4".to_string());
+    }
+    let id = crate::db::new_id("speech");
+    let utterance = Utterance {
+        id: id.clone(),
+        text,
+        voice: voice.filter(|v| !v.trim().is_empty()),
+        rate,
+    };
+    send(&app, &engine, Request::Speak(utterance))?;
+    Ok(id)
+}
+
+#[tauri::command]
+pub fn stop_speaking(app: AppHandle, engine: State<'_, SpeechEngine>) -> Result<(), String> {
+    send(&app, &engine, Request::Stop)
+}
+
+#[tauri::command]
+pub fn pause_speaking(app: AppHandle, engine: State<'_, SpeechEngine>) -> Result<(), String> {
+    ask(&app, &engine, Request::Pause)?
+}
+
+#[tauri::command]
+pub fn resume_speaking(app: AppHandle, engine: State<'_, SpeechEngine>) -> Result<(), String> {
+    ask(&app, &engine, Request::Resume)?
+}
+
+// System voices; Piper voices are model files and aren't listed
+#[tauri::command]
+pub fn list_voices(app: AppHandle, engine: State<'_, SpeechEngine>) -> Result<Vec<VoiceInfo>, String> {
+    ask(&app, &engine, Request::Voices)?
+}
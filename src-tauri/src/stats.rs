@@ -0,0 +1,212 @@
+// Usage statistics for the stats dashboard, aggregated from stored messages and generation records
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate};
+use rusqlite::{params, Connection};
+use tauri::State;
+
+use crate::db::Database;
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    #[default]
+    Day,
+    Week,
+}
+
+#[derive(serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageRange {
+    // Only the last N days (including today); all time when omitted
+    days: Option<u32>,
+    #[serde(default)]
+    granularity: Granularity,
+}
+
+#[derive(serde::Serialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageCounts {
+    messages: u64,
+    user_messages: u64,
+    assistant_messages: u64,
+    generations: u64,
+    prompt_tokens: u64,
+    completion_tokens: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    avg_latency_ms: Option<u64>,
+    // Sum and count behind the average, so buckets can be merged
+    #[serde(skip)]
+    latency_total: u64,
+    #[serde(skip)]
+    latency_samples: u64,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageBucket {
+    // First day of the bucket (YYYY-MM-DD)
+    period: String,
+    #[serde(flatten)]
+    counts: UsageCounts,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelUsage {
+    model: String,
+    generations: u64,
+    prompt_tokens: u64,
+    completion_tokens: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    avg_latency_ms: Option<u64>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    from: Option<String>,
+    granularity: Granularity,
+    conversations: u64,
+    totals: UsageCounts,
+    buckets: Vec<UsageBucket>,
+    models: Vec<ModelUsage>,
+}
+
+impl UsageCounts {
+    fn add(&mut self, other: &UsageCounts) {
+        self.messages += other.messages;
+        self.user_messages += other.user_messages;
+        self.assistant_messages += other.assistant_messages;
+        self.generations += other.generations;
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.latency_total += other.latency_total;
+        self.latency_samples += other.latency_samples;
+    }
+
+    fn finish(&mut self) {
+        self.avg_latency_ms = (self.latency_samples > 0).then(|| self.latency_total / self.latency_samples);
+    }
+}
+
+// Bucket key for a day: the day itself, or the Monday of its ISO week
+fn period_of(day: &str, granularity: Granularity) -> String {
+    match (granularity, NaiveDate::parse_from_str(day, "%Y-%m-%d")) {
+        (Granularity::Week, Ok(date)) => {
+            let monday = date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64);
+            monday.format("%Y-%m-%d").to_string()
+        }
+        _ => day.to_string(),
+    }
+}
+
+fn to_u64(value: Option<i64>) -> u64 {
+    value.unwrap_or(0).max(0) as u64
+}
+
+// Per-day counts; timestamps are RFC 3339 in UTC, so the first ten characters are the day
+fn daily_counts(conn: &Connection, from: &str) -> rusqlite::Result<BTreeMap<String, UsageCounts>> {
+    let mut days: BTreeMap<String, UsageCounts> = BTreeMap::new();
+
+    let mut stmt = conn.prepare(
+        "SELECT substr(created_at, 1, 10) AS day, COUNT(*),
+                SUM(role = 'user'), SUM(role = 'assistant')
+         FROM messages WHERE substr(created_at, 1, 10) >= ?1 GROUP BY day",
+    )?;
+    let rows = stmt.query_map(params![from], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, Option<i64>>(2)?, row.get::<_, Option<i64>>(3)?))
+    })?;
+    for row in rows {
+        let (day, messages, user, assistant) = row?;
+        let counts = days.entry(day).or_default();
+        counts.messages = to_u64(Some(messages));
+        counts.user_messages = to_u64(user);
+        counts.assistant_messages = to_u64(assistant);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT substr(created_at, 1, 10) AS day, COUNT(*), SUM(prompt_tokens), SUM(completion_tokens),
+                SUM(latency_ms), COUNT(latency_ms)
+         FROM generations WHERE substr(created_at, 1, 10) >= ?1 GROUP BY day",
+    )?;
+    let rows = stmt.query_map(params![from], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            [row.get::<_, Option<i64>>(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?],
+        ))
+    })?;
+    for row in rows {
+        let (day, [generations, prompt, completion, latency, samples]) = row?;
+        let counts = days.entry(day).or_default();
+        counts.generations = to_u64(generations);
+        counts.prompt_tokens = to_u64(prompt);
+        counts.completion_tokens = to_u64(completion);
+        counts.latency_total = to_u64(latency);
+        counts.latency_samples = to_u64(samples);
+    }
+    Ok(days)
+}
+
+fn model_usage(conn: &Connection, from: &str) -> rusqlite::Result<Vec<ModelUsage>> {
+    let mut stmt = conn.prepare(
+        "SELECT COALESCE(json_extract(provider, '$.model'), 'unknown') AS model, COUNT(*),
+                SUM(prompt_tokens), SUM(completion_tokens), AVG(latency_ms)
+         FROM generations WHERE substr(created_at, 1, 10) >= ?1
+         GROUP BY model ORDER BY COUNT(*) DESC",
+    )?;
+    let rows = stmt.query_map(params![from], |row| {
+        Ok(ModelUsage {
+            model: row.get(0)?,
+            generations: to_u64(row.get(1)?),
+            prompt_tokens: to_u64(row.get(2)?),
+            completion_tokens: to_u64(row.get(3)?),
+            avg_latency_ms: row.get::<_, Option<f64>>(4)?.map(|ms| ms.round() as u64),
+        })
+    })?;
+    rows.collect()
+}
+
+#[tauri::command]
+pub fn get_usage_stats(db: State<'_, Database>, range: Option<UsageRange>) -> Result<UsageReport, String> {
+    let range = range.unwrap_or_default();
+    let from = range.days.map(|days| {
+        let start = chrono::Utc::now().date_naive() - chrono::Duration::days(days.saturating_sub(1) as i64);
+        start.format("%Y-%m-%d").to_string()
+    });
+    // Every stored day sorts after the empty string
+    let since = from.clone().unwrap_or_default();
+
+    let (days, models, conversations) = db.with_conn(|conn| {
+        let conversations: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM conversations WHERE substr(updated_at, 1, 10) >= ?1",
+            params![since],
+            |row| row.get(0),
+        )?;
+        Ok((daily_counts(conn, &since)?, model_usage(conn, &since)?, conversations))
+    })?;
+
+    let mut totals = UsageCounts::default();
+    let mut buckets: BTreeMap<String, UsageCounts> = BTreeMap::new();
+    for (day, counts) in &days {
+        totals.add(counts);
+        buckets.entry(period_of(day, range.granularity)).or_default().add(counts);
+    }
+    totals.finish();
+
+    Ok(UsageReport {
+        from,
+        granularity: range.granularity,
+        conversations: conversations as u64,
+        totals,
+        buckets: buckets
+            .into_iter()
+            .map(|(period, mut counts)| {
+                counts.finish();
+                UsageBucket { period, counts }
+            })
+            .collect(),
+        models,
+    })
+}
@@ -0,0 +1,194 @@
+// Identity and stealth for the scraping browser. Headless Chrome gives itself away to bot checks:
+// "HeadlessChrome" in its user agent, navigator.webdriver set, no plugins, an 800x600 window and a
+// bare language list. A browser profile makes it look like a common desktop browser (user agent,
+// platform, window size and locale), and stealth mode hides the automation markers. Both are set
+// when the browser launches and when each of its tabs is opened.
+use headless_chrome::protocol::cdp::Page;
+use headless_chrome::Tab;
+
+use crate::settings::Settings;
+
+pub const PROFILES: &[&str] = &["chrome-windows", "chrome-mac", "chrome-linux", "edge-windows"];
+// Base for user agent, viewport or locale settings without a profile
+const DEFAULT_PROFILE: &str = "chrome-windows";
+
+#[derive(Clone, PartialEq)]
+pub struct BrowserProfile {
+    pub user_agent: String,
+    // navigator.platform
+    pub platform: String,
+    pub viewport: (u32, u32),
+    // BCP 47, e.g. en-US
+    pub locale: String,
+}
+
+fn builtin(name: &str) -> Option<BrowserProfile> {
+    let (user_agent, platform, viewport) = match name {
+        "chrome-windows" => (
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 \
+             Safari/537.36",
+            "Win32",
+            (1920, 1080),
+        ),
+        "chrome-mac" => (
+            "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) \
+             Chrome/124.0.0.0 Safari/537.36",
+            "MacIntel",
+            (1440, 900),
+        ),
+        "chrome-linux" => (
+            "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36",
+            "Linux x86_64",
+            (1366, 768),
+        ),
+        "edge-windows" => (
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 \
+             Safari/537.36 Edg/124.0.0.0",
+            "Win32",
+            (1536, 864),
+        ),
+        _ => return None,
+    };
+    Some(BrowserProfile {
+        user_agent: user_agent.to_string(),
+        platform: platform.to_string(),
+        viewport,
+        locale: "en-US".to_string(),
+    })
+}
+
+// "1366x768"
+pub fn parse_viewport(viewport: &str) -> Option<(u32, u32)> {
+    let (width, height) = viewport.trim().split_once(['x', 'X'])?;
+    let size = (width.trim().parse().ok()?, height.trim().parse().ok()?);
+    let valid = |side: u32| (320..=7680).contains(&side);
+    (valid(size.0) && valid(size.1)).then_some(size)
+}
+
+// A language with an optional region, e.g. en or de-DE
+pub fn valid_locale(locale: &str) -> bool {
+    let mut parts = locale.split('-');
+    let language = parts.next().unwrap_or_default();
+    let region = parts.next();
+    (2..=3).contains(&language.len())
+        && language.chars().all(|c| c.is_ascii_lowercase())
+        && region.is_none_or(|r| r.len() == 2 && r.chars().all(|c| c.is_ascii_uppercase()))
+        && parts.next().is_none()
+}
+
+// The profile the settings describe: `browserProfile` with the user agent, viewport and locale
+// settings replacing its parts. None leaves the browser as it is.
+pub fn profile_from_settings(settings: &Settings) -> Option<BrowserProfile> {
+    let overridden = [&settings.browser_user_agent, &settings.browser_viewport, &settings.browser_locale]
+        .iter()
+        .any(|part| part.is_some());
+    let name = match settings.browser_profile.as_deref() {
+        Some(name) => name,
+        None if overridden => DEFAULT_PROFILE,
+        None => return None,
+    };
+    let mut profile = builtin(name).or_else(|| builtin(DEFAULT_PROFILE))?;
+    if let Some(user_agent) = settings.browser_user_agent.as_deref().filter(|ua| !ua.trim().is_empty()) {
+        profile.user_agent = user_agent.trim().to_string();
+    }
+    if let Some(viewport) = settings.browser_viewport.as_deref().and_then(parse_viewport) {
+        profile.viewport = viewport;
+    }
+    if let Some(locale) = settings.browser_locale.as_deref().filter(|l| valid_locale(l)) {
+        profile.locale = locale.to_string();
+    }
+    Some(profile)
+}
+
+impl BrowserProfile {
+    // de-DE,de;q=0.9,en;q=0.8
+    fn accept_language(&self) -> String {
+        let language = self.locale.split('-').next().unwrap_or("en");
+        let mut languages = vec![self.locale.clone()];
+        if language != self.locale {
+            languages.push(format!("{};q=0.9", language));
+        }
+        if language != "en" {
+            languages.push("en;q=0.8".to_string());
+        }
+        languages.join(",")
+    }
+
+    // navigator.languages, as a JavaScript array
+    fn languages(&self) -> String {
+        let languages: Vec<String> =
+            self.accept_language().split(',').map(|l| format!("'{}'", l.split(';').next().unwrap_or(l))).collect();
+        format!("[{}]", languages.join(", "))
+    }
+}
+
+// Command-line flags for the browser
+pub fn launch_args(profile: Option<&BrowserProfile>, stealth: bool) -> Vec<String> {
+    let mut args = Vec::new();
+    if let Some(profile) = profile {
+        args.push(format!("--lang={}", profile.locale));
+    }
+    if stealth {
+        // Keeps Blink from setting navigator.webdriver
+        args.push("--disable-blink-features=AutomationControlled".to_string());
+    }
+    args
+}
+
+// Run before any script of the pages a tab loads; wrapped so it declares nothing in their scope
+fn stealth_script(profile: Option<&BrowserProfile>) -> String {
+    let languages = profile
+        .map(|p| format!("Object.defineProperty(navigator, 'languages', {{ get: () => {} }});", p.languages()))
+        .unwrap_or_default();
+    format!(
+        "(() => {{
+            Object.defineProperty(navigator, 'webdriver', {{ get: () => undefined }});
+            window.chrome = window.chrome || {{ runtime: {{}} }};
+            const pdf = {{ filename: 'internal-pdf-viewer', description: 'Portable Document Format' }};
+            Object.defineProperty(navigator, 'plugins', {{ get: () => [
+                {{ name: 'PDF Viewer', ...pdf }},
+                {{ name: 'Chrome PDF Viewer', ...pdf }},
+                {{ name: 'Chromium PDF Viewer', ...pdf }},
+            ] }});
+            const query = navigator.permissions && navigator.permissions.query;
+            if (query) {{
+                navigator.permissions.query = (parameters) => parameters.name === 'notifications'
+                    ? Promise.resolve({{ state: Notification.permission }})
+                    : query.call(navigator.permissions, parameters);
+            }}
+            {}
+        }})();",
+        languages
+    )
+}
+
+// Give a new tab the profile's user agent and, in stealth mode, hide the automation markers
+pub fn prepare_tab(tab: &Tab, profile: Option<&BrowserProfile>, stealth: bool) -> Result<(), String> {
+    let user_agent = match profile {
+        Some(profile) => {
+            Some((profile.user_agent.clone(), Some(profile.accept_language()), Some(profile.platform.as_str())))
+        }
+        // Without a profile only the giveaway in the browser's own user agent goes
+        None if stealth => tab
+            .evaluate("navigator.userAgent", false)
+            .ok()
+            .and_then(|result| result.value)
+            .and_then(|value| value.as_str().map(|ua| ua.replace("HeadlessChrome/", "Chrome/")))
+            .map(|ua| (ua, None, None)),
+        None => None,
+    };
+    if let Some((user_agent, accept_language, platform)) = user_agent {
+        tab.set_user_agent(&user_agent, accept_language.as_deref(), platform)
+            .map_err(|e| format!("Failed to set the user agent: {}", e))?;
+    }
+    if stealth {
+        tab.call_method(Page::AddScriptToEvaluateOnNewDocument {
+            source: stealth_script(profile),
+            world_name: None,
+            include_command_line_api: None,
+            run_immediately: None,
+        })
+        .map_err(|e| format!("Failed to add the stealth script: {}", e))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,761 @@
+// Conversation sync with a WebDAV server or S3-compatible bucket.
+//
+// Remote layout under the configured root:
+//   openchat/index.json               revision, device and tombstone per conversation, list of blob hashes
+//   openchat/conversations/<id>.json  conversation with every message of every branch
+//   openchat/blobs/<sha256>           attachment contents
+//
+// Each conversation carries a revision counter bumped on every local change. The revision last
+// synced is kept in sync_state, so a conversation changed both locally and remotely since then
+// is a conflict: the local version wins and the remote one is kept as a separate copy.
+//
+// With storage encryption enabled, conversations and blobs are uploaded encrypted with a key derived
+// from the storage key, so only devices sharing that key can read them. The WebDAV password and S3
+// secret key are kept in the OS keychain, not in sync.json.
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use rusqlite::params;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+
+use crate::blobs::{is_blob_hash, to_hex, BlobStore};
+use crate::conversations::{self, Conversation, StoredMessage};
+use crate::db::{self, Database};
+use crate::encryption::KEYCHAIN_SERVICE;
+use crate::profiles::{self, ProfileManager};
+
+const CONFIG_FILE: &str = "sync.json";
+const REMOTE_ROOT: &str = "openchat";
+// How often the background task checks whether an interval sync is due
+const BACKGROUND_TICK: Duration = Duration::from_secs(60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+// Derives the key of remote payloads from the storage key
+const SYNC_KEY_CONTEXT: &str = "openchat-sync-v1";
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SyncBackend {
+    // Secrets are only read from the frontend (and old config files); they are saved to the keychain
+    #[serde(rename_all = "camelCase")]
+    Webdav {
+        url: String,
+        username: Option<String>,
+        #[serde(default, skip_serializing)]
+        password: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    S3 {
+        endpoint: String,
+        bucket: String,
+        region: String,
+        access_key_id: String,
+        #[serde(default, skip_serializing)]
+        secret_access_key: Option<String>,
+        prefix: Option<String>,
+    },
+}
+
+impl SyncBackend {
+    fn secret_mut(&mut self) -> &mut Option<String> {
+        match self {
+            SyncBackend::Webdav { password, .. } => password,
+            SyncBackend::S3 { secret_access_key, .. } => secret_access_key,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncConfig {
+    pub enabled: bool,
+    pub backend: Option<SyncBackend>,
+    // Minutes between background syncs; no background sync when unset or 0
+    pub interval_minutes: Option<u64>,
+    // Identifies this installation in the remote index
+    #[serde(default)]
+    pub device_id: String,
+    pub last_sync: Option<String>,
+}
+
+// The config as the settings form sees it: the secret itself is never returned
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncConfigView {
+    #[serde(flatten)]
+    config: SyncConfig,
+    has_secret: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct RemoteEntry {
+    revision: i64,
+    device_id: String,
+    updated_at: String,
+    #[serde(default)]
+    deleted: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct RemoteIndex {
+    conversations: BTreeMap<String, RemoteEntry>,
+    blobs: BTreeSet<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteConversation {
+    conversation: Conversation,
+    messages: Vec<StoredMessage>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncConflict {
+    conversation_id: String,
+    title: String,
+    // Local copy holding the remote version
+    copy_id: String,
+}
+
+#[derive(serde::Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncReport {
+    pushed: usize,
+    pulled: usize,
+    deleted_local: usize,
+    deleted_remote: usize,
+    uploaded_blobs: usize,
+    downloaded_blobs: usize,
+    conflicts: Vec<SyncConflict>,
+    finished_at: String,
+}
+
+// Serializes sync runs between the command and the background task
+#[derive(Default)]
+pub struct SyncEngine {
+    running: tokio::sync::Mutex<()>,
+}
+
+fn config_path(app: &AppHandle) -> Result<PathBuf, String> {
+    profiles::config_dir(app).map(|dir| dir.join(CONFIG_FILE))
+}
+
+fn secret_entry(app: &AppHandle) -> Result<keyring::Entry, String> {
+    let profiles = app.state::<ProfileManager>();
+    keyring::Entry::new(KEYCHAIN_SERVICE, &format!("sync-secret-{}", profiles.active_id()))
+        .map_err(|e| format!("Keychain unavailable: {}", e))
+}
+
+fn load_secret(app: &AppHandle) -> Option<String> {
+    secret_entry(app).ok()?.get_password().ok()
+}
+
+// Store the secret in the keychain, or remove it when empty
+fn save_secret(app: &AppHandle, secret: &str) -> Result<(), String> {
+    let entry = secret_entry(app)?;
+    if !secret.is_empty() {
+        return entry
+            .set_password(secret)
+            .map_err(|e| format!("Failed to store sync secret: {}", e));
+    }
+    match entry.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(err) => Err(format!("Failed to remove sync secret: {}", err)),
+    }
+}
+
+// The saved config; a secret left in sync.json by earlier versions moves to the keychain
+fn load_config(app: &AppHandle) -> Result<SyncConfig, String> {
+    let path = config_path(app)?;
+    if !path.exists() {
+        return Ok(SyncConfig::default());
+    }
+    let text = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut config: SyncConfig = serde_json::from_str(&text).map_err(|e| format!("Invalid sync config: {}", e))?;
+    if let Some(secret) = config.backend.as_mut().and_then(|backend| backend.secret_mut().take()) {
+        save_secret(app, &secret)?;
+        save_config(app, &config)?;
+    }
+    Ok(config)
+}
+
+fn save_config(app: &AppHandle, config: &SyncConfig) -> Result<(), String> {
+    let path = config_path(app)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to save sync config: {}", e))
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+// Percent-encode a key path for S3 (everything but unreserved characters and '/')
+fn s3_encode_path(path: &str) -> String {
+    path.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+struct Remote {
+    client: reqwest::Client,
+    backend: SyncBackend,
+}
+
+impl Remote {
+    // On the app's client, so syncs go through its proxy
+    fn new(app: &AppHandle, backend: SyncBackend) -> Result<Self, String> {
+        let client = crate::http_client::for_app(app, &crate::settings::load(app))?;
+        Ok(Remote { client, backend })
+    }
+
+    fn webdav_url(base: &str, key: &str) -> String {
+        format!("{}/{}", base.trim_end_matches('/'), key)
+    }
+
+    // Build a request signed with AWS Signature Version 4 (path-style addressing)
+    fn s3_request(&self, method: reqwest::Method, key: &str, body: Vec<u8>) -> Result<reqwest::RequestBuilder, String> {
+        let SyncBackend::S3 { endpoint, bucket, region, access_key_id, secret_access_key, prefix } = &self.backend else {
+            unreachable!()
+        };
+        let secret_access_key = secret_access_key.as_deref().ok_or("The S3 secret access key is not set")?;
+        let base = reqwest::Url::parse(endpoint).map_err(|e| format!("Invalid S3 endpoint: {}", e))?;
+        let host = match (base.host_str(), base.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            _ => return Err("Invalid S3 endpoint: missing host".to_string()),
+        };
+        let full_key = match prefix.as_deref().map(|p| p.trim_matches('/')).filter(|p| !p.is_empty()) {
+            Some(prefix) => format!("{}/{}", prefix, key),
+            None => key.to_string(),
+        };
+        let path = s3_encode_path(&format!("/{}/{}", bucket, full_key));
+
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = to_hex(&Sha256::digest(&body));
+
+        let canonical_request = format!(
+            "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\nhost;x-amz-content-sha256;x-amz-date\n{}",
+            method, path, host, payload_hash, amz_date, payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            to_hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let mut key = hmac_sha256(format!("AWS4{}", secret_access_key).as_bytes(), &date);
+        for part in [region.as_str(), "s3", "aws4_request"] {
+            key = hmac_sha256(&key, part);
+        }
+        let signature = to_hex(&hmac_sha256(&key, &string_to_sign));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={}",
+            access_key_id, scope, signature
+        );
+
+        let url = format!("{}://{}{}", base.scheme(), host, path);
+        Ok(self
+            .client
+            .request(method, url)
+            .timeout(REQUEST_TIMEOUT)
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("Authorization", authorization)
+            .body(body))
+    }
+
+    fn request(&self, method: reqwest::Method, key: &str, body: Vec<u8>) -> Result<reqwest::RequestBuilder, String> {
+        match &self.backend {
+            SyncBackend::Webdav { url, username, password } => {
+                let mut request =
+                    self.client.request(method, Self::webdav_url(url, key)).timeout(REQUEST_TIMEOUT).body(body);
+                if let Some(username) = username {
+                    request = request.basic_auth(username, password.as_ref());
+                }
+                Ok(request)
+            }
+            SyncBackend::S3 { .. } => self.s3_request(method, key, body),
+        }
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        let response = self
+            .request(reqwest::Method::GET, key, Vec::new())?
+            .send()
+            .await
+            .map_err(|e| format!("Sync request failed: {}", e))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(format!("GET {} failed: HTTP {}", key, response.status()));
+        }
+        let bytes = response.bytes().await.map_err(|e| format!("Failed to read {}: {}", key, e))?;
+        Ok(Some(bytes.to_vec()))
+    }
+
+    async fn put(&self, key: &str, body: Vec<u8>) -> Result<(), String> {
+        let response = self
+            .request(reqwest::Method::PUT, key, body)?
+            .send()
+            .await
+            .map_err(|e| format!("Sync request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("PUT {} failed: HTTP {}", key, response.status()));
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), String> {
+        let response = self
+            .request(reqwest::Method::DELETE, key, Vec::new())?
+            .send()
+            .await
+            .map_err(|e| format!("Sync request failed: {}", e))?;
+        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+            return Err(format!("DELETE {} failed: HTTP {}", key, response.status()));
+        }
+        Ok(())
+    }
+
+    // WebDAV needs collections created before files can be put into them; S3 has no directories
+    async fn prepare(&self) -> Result<(), String> {
+        let SyncBackend::Webdav { .. } = &self.backend else {
+            return Ok(());
+        };
+        for dir in [REMOTE_ROOT.to_string(), format!("{}/conversations", REMOTE_ROOT), format!("{}/blobs", REMOTE_ROOT)] {
+            let method = reqwest::Method::from_bytes(b"MKCOL").expect("valid method");
+            let response = self
+                .request(method, &dir, Vec::new())?
+                .send()
+                .await
+                .map_err(|e| format!("Sync request failed: {}", e))?;
+            // 405 means the collection already exists
+            let status = response.status();
+            if !status.is_success() && status != reqwest::StatusCode::METHOD_NOT_ALLOWED {
+                return Err(format!("MKCOL {} failed: HTTP {}", dir, status));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn conversation_key(id: &str) -> String {
+    format!("{}/conversations/{}.json", REMOTE_ROOT, id)
+}
+
+fn blob_key(hash: &str) -> String {
+    format!("{}/blobs/{}", REMOTE_ROOT, hash)
+}
+
+fn index_key() -> String {
+    format!("{}/index.json", REMOTE_ROOT)
+}
+
+// The key of remote payloads, when storage encryption is enabled and unlocked
+fn sync_key(blobs: &BlobStore) -> Option<[u8; 32]> {
+    let key = blobs.current_key()?;
+    hmac_sha256(&key, SYNC_KEY_CONTEXT).try_into().ok()
+}
+
+struct LocalState {
+    conversations: HashMap<String, Conversation>,
+    synced: HashMap<String, i64>,
+}
+
+fn local_state(db: &Database) -> Result<LocalState, String> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare("SELECT id FROM conversations")?;
+        let ids: Vec<String> = stmt.query_map([], |row| row.get(0))?.collect::<rusqlite::Result<_>>()?;
+        let mut conversations = HashMap::new();
+        for id in ids {
+            if let Some(conversation) = conversations::get_conversation_row(conn, &id)? {
+                conversations.insert(id, conversation);
+            }
+        }
+        let mut stmt = conn.prepare("SELECT conversation_id, synced_revision FROM sync_state")?;
+        let synced = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(LocalState { conversations, synced })
+    })
+}
+
+fn mark_synced(db: &Database, id: &str, revision: i64) -> Result<(), String> {
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO sync_state (conversation_id, synced_revision, synced_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(conversation_id) DO UPDATE SET synced_revision = ?2, synced_at = ?3",
+            params![id, revision, chrono::Utc::now().to_rfc3339()],
+        )
+    })?;
+    Ok(())
+}
+
+fn forget_synced(db: &Database, id: &str) -> Result<(), String> {
+    db.with_conn(|conn| conn.execute("DELETE FROM sync_state WHERE conversation_id = ?1", params![id]))?;
+    Ok(())
+}
+
+struct SyncRun<'a> {
+    db: &'a Database,
+    blobs: &'a BlobStore,
+    remote: Remote,
+    key: Option<[u8; 32]>,
+    device_id: String,
+    index: RemoteIndex,
+    report: SyncReport,
+}
+
+impl SyncRun<'_> {
+    fn seal(&self, bytes: Vec<u8>) -> Result<Vec<u8>, String> {
+        match &self.key {
+            Some(key) => BlobStore::encrypt(key, &bytes),
+            None => Ok(bytes),
+        }
+    }
+
+    // Remote data uploaded without encryption is read as it is
+    fn open(&self, data: Vec<u8>) -> Result<Vec<u8>, String> {
+        if !BlobStore::is_encrypted_data(&data) {
+            return Ok(data);
+        }
+        let key = self.key.as_ref().ok_or("Remote data is encrypted; enable and unlock storage encryption")?;
+        BlobStore::decrypt_with(key, &data)
+    }
+
+    async fn push(&mut self, conversation: &Conversation) -> Result<(), String> {
+        let messages = self
+            .db
+            .with_conn(|conn| conversations::load_all_messages(conn, &conversation.id))?;
+        for attachment in messages.iter().flat_map(|m| &m.attachments) {
+            let Some(hash) = &attachment.hash else { continue };
+            if self.index.blobs.contains(hash) {
+                continue;
+            }
+            // Re-encrypted with the sync key, still addressed by the hash of the plaintext
+            let bytes = self
+                .blobs
+                .read(Path::new(&attachment.path))
+                .map_err(|e| format!("Failed to read attachment {}: {}", attachment.file_name, e))?;
+            self.remote.put(&blob_key(hash), self.seal(bytes)?).await?;
+            self.index.blobs.insert(hash.clone());
+            self.report.uploaded_blobs += 1;
+        }
+
+        let payload = RemoteConversation {
+            conversation: conversation.clone(),
+            messages,
+        };
+        let json = serde_json::to_vec(&payload).map_err(|e| e.to_string())?;
+        self.remote.put(&conversation_key(&conversation.id), self.seal(json)?).await?;
+        self.index.conversations.insert(
+            conversation.id.clone(),
+            RemoteEntry {
+                revision: conversation.revision,
+                device_id: self.device_id.clone(),
+                updated_at: conversation.updated_at.clone(),
+                deleted: false,
+            },
+        );
+        mark_synced(self.db, &conversation.id, conversation.revision)?;
+        self.report.pushed += 1;
+        Ok(())
+    }
+
+    async fn fetch(&mut self, id: &str) -> Result<RemoteConversation, String> {
+        let bytes = self
+            .remote
+            .get(&conversation_key(id))
+            .await?
+            .ok_or_else(|| format!("Remote conversation {} is missing", id))?;
+        let bytes = self.open(bytes).map_err(|e| format!("Remote conversation {}: {}", id, e))?;
+        let mut remote: RemoteConversation =
+            serde_json::from_slice(&bytes).map_err(|e| format!("Invalid remote conversation {}: {}", id, e))?;
+
+        // Point attachments at local blobs, downloading the ones this device doesn't have
+        for attachment in remote.messages.iter_mut().flat_map(|m| m.attachments.iter_mut()) {
+            let Some(hash) = &attachment.hash else { continue };
+            if !is_blob_hash(hash) {
+                return Err(format!("Remote conversation {} has an invalid attachment hash", id));
+            }
+            let local = self.blobs.blob_path(hash);
+            if !local.exists() {
+                if let Some(bytes) = self.remote.get(&blob_key(hash)).await? {
+                    let bytes = self.open(bytes).map_err(|e| format!("Remote blob {}: {}", hash, e))?;
+                    let stored = self.blobs.put_bytes(&bytes)?;
+                    if &stored.hash != hash {
+                        if !stored.existing {
+                            let _ = std::fs::remove_file(&stored.path);
+                        }
+                        return Err(format!("Remote blob {} doesn't match its hash", hash));
+                    }
+                    self.report.downloaded_blobs += 1;
+                }
+            }
+            attachment.path = local.to_string_lossy().to_string();
+        }
+        Ok(remote)
+    }
+
+    // Replace (or create) the local conversation with the remote version
+    async fn pull(&mut self, id: &str) -> Result<(), String> {
+        let remote = self.fetch(id).await?;
+        self.db.with_conn(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            tx.execute("DELETE FROM conversations WHERE id = ?1", params![id])?;
+            conversations::insert_conversation(&tx, &remote.conversation)?;
+            for message in &remote.messages {
+                conversations::insert_stored_message(&tx, message)?;
+            }
+            tx.commit()
+        })?;
+        mark_synced(self.db, id, remote.conversation.revision)?;
+        self.report.pulled += 1;
+        Ok(())
+    }
+
+    // Keep the remote version of a conflicting conversation as a new local conversation
+    async fn save_conflict_copy(&mut self, id: &str) -> Result<(), String> {
+        let remote = self.fetch(id).await?;
+        let copy_id = db::new_id("conv");
+        let title = remote.conversation.title.clone();
+
+        // Fresh ids so the copy doesn't collide with the local messages
+        let id_map: HashMap<String, String> = remote
+            .messages
+            .iter()
+            .map(|m| (m.id.clone(), db::new_id("msg")))
+            .collect();
+        let conversation = Conversation {
+            id: copy_id.clone(),
+            title: format!("{} (conflict copy)", title),
+            active_leaf_id: remote.conversation.active_leaf_id.as_ref().and_then(|l| id_map.get(l).cloned()),
+            revision: 0,
+            ..remote.conversation
+        };
+        self.db.with_conn(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            conversations::insert_conversation(&tx, &conversation)?;
+            for message in &remote.messages {
+                let mut copy = message.clone();
+                copy.id = id_map[&message.id].clone();
+                copy.conversation_id = copy_id.clone();
+                copy.parent_id = message.parent_id.as_ref().and_then(|p| id_map.get(p).cloned());
+                for attachment in copy.attachments.iter_mut() {
+                    attachment.id = db::new_id("att");
+                    attachment.message_id = copy.id.clone();
+                }
+                conversations::insert_stored_message(&tx, &copy)?;
+            }
+            tx.commit()
+        })?;
+        eprintln!("[Sync] Conflict on {}: remote version saved as {}", id, copy_id);
+        self.report.conflicts.push(SyncConflict {
+            conversation_id: id.to_string(),
+            title,
+            copy_id,
+        });
+        Ok(())
+    }
+
+    async fn run(&mut self) -> Result<(), String> {
+        self.remote.prepare().await?;
+        if let Some(bytes) = self.remote.get(&index_key()).await? {
+            self.index = serde_json::from_slice(&bytes).map_err(|e| format!("Invalid remote index: {}", e))?;
+        }
+        let local = local_state(self.db)?;
+
+        let mut ids: BTreeSet<String> = local.conversations.keys().cloned().collect();
+        ids.extend(self.index.conversations.keys().cloned());
+        ids.extend(local.synced.keys().cloned());
+
+        for id in ids {
+            let local_conversation = local.conversations.get(&id);
+            let base = local.synced.get(&id).copied();
+            let remote = self.index.conversations.get(&id).cloned();
+            let remote_changed = match (&remote, base) {
+                (Some(entry), Some(base)) => entry.revision != base || entry.deleted,
+                (Some(entry), None) => !entry.deleted,
+                (None, _) => false,
+            };
+
+            match (local_conversation, remote) {
+                // Only on this device
+                (Some(conversation), None) => self.push(conversation).await?,
+                (Some(conversation), Some(entry)) => {
+                    let local_changed = base != Some(conversation.revision);
+                    match (local_changed, remote_changed) {
+                        (false, false) => {}
+                        // Deleted on another device and untouched here
+                        (false, true) if entry.deleted => {
+                            self.db
+                                .with_conn(|conn| conn.execute("DELETE FROM conversations WHERE id = ?1", params![id]))?;
+                            forget_synced(self.db, &id)?;
+                            self.report.deleted_local += 1;
+                        }
+                        (false, true) => self.pull(&id).await?,
+                        (true, false) => self.push(conversation).await?,
+                        (true, true) => {
+                            if !entry.deleted {
+                                self.save_conflict_copy(&id).await?;
+                            }
+                            self.push(conversation).await?;
+                        }
+                    }
+                }
+                // Deleted here after the last sync
+                (None, Some(entry)) if base.is_some() => {
+                    if remote_changed && !entry.deleted {
+                        // Edited elsewhere in the meantime: keep the edits
+                        self.pull(&id).await?;
+                    } else if !entry.deleted {
+                        self.remote.delete(&conversation_key(&id)).await?;
+                        self.index.conversations.insert(
+                            id.clone(),
+                            RemoteEntry {
+                                deleted: true,
+                                device_id: self.device_id.clone(),
+                                updated_at: chrono::Utc::now().to_rfc3339(),
+                                ..entry
+                            },
+                        );
+                        forget_synced(self.db, &id)?;
+                        self.report.deleted_remote += 1;
+                    } else {
+                        forget_synced(self.db, &id)?;
+                    }
+                }
+                // New on another device
+                (None, Some(entry)) if !entry.deleted => self.pull(&id).await?,
+                (None, _) => {
+                    forget_synced(self.db, &id)?;
+                }
+            }
+        }
+
+        let json = serde_json::to_vec_pretty(&self.index).map_err(|e| e.to_string())?;
+        self.remote.put(&index_key(), json).await
+    }
+}
+
+async fn sync_once(app: &AppHandle) -> Result<SyncReport, String> {
+    let engine = app.state::<SyncEngine>();
+    let _guard = engine
+        .running
+        .try_lock()
+        .map_err(|_| "A sync is already running".to_string())?;
+
+    let mut config = load_config(app)?;
+    let mut backend = config.backend.clone().ok_or("Sync is not configured")?;
+    *backend.secret_mut() = load_secret(app);
+    if config.device_id.is_empty() {
+        config.device_id = db::new_id("device");
+    }
+
+    let db = app.state::<Database>();
+    let blobs = app.state::<BlobStore>();
+    let mut run = SyncRun {
+        db: &db,
+        blobs: &blobs,
+        remote: Remote::new(app, backend)?,
+        key: sync_key(&blobs),
+        device_id: config.device_id.clone(),
+        index: RemoteIndex::default(),
+        report: SyncReport::default(),
+    };
+    run.run().await?;
+
+    let mut report = run.report;
+    report.finished_at = chrono::Utc::now().to_rfc3339();
+    config.last_sync = Some(report.finished_at.clone());
+    save_config(app, &config)?;
+    eprintln!(
+        "[Sync] Pushed {}, pulled {}, {} conflict(s)",
+        report.pushed,
+        report.pulled,
+        report.conflicts.len()
+    );
+    Ok(report)
+}
+
+// Background loop: runs a sync whenever the configured interval has elapsed
+pub fn start_background(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last_run = std::time::Instant::now();
+        loop {
+            tokio::time::sleep(BACKGROUND_TICK).await;
+            let Ok(config) = load_config(&app) else { continue };
+            let interval = match config.interval_minutes {
+                Some(minutes) if config.enabled && minutes > 0 && config.backend.is_some() => minutes,
+                _ => continue,
+            };
+            if last_run.elapsed() < Duration::from_secs(interval * 60) {
+                continue;
+            }
+            last_run = std::time::Instant::now();
+            if let Err(err) = sync_once(&app).await {
+                eprintln!("[Sync] Background sync failed: {}", err);
+            }
+        }
+    });
+}
+
+fn view(app: &AppHandle, config: SyncConfig) -> SyncConfigView {
+    let has_secret = config.backend.is_some() && load_secret(app).is_some();
+    SyncConfigView { config, has_secret }
+}
+
+// The password or secret key isn't returned, only whether one is stored
+#[tauri::command]
+pub fn get_sync_config(app: AppHandle) -> Result<SyncConfigView, String> {
+    Ok(view(&app, load_config(&app)?))
+}
+
+// A password or secret key given is stored in the keychain, an empty one removes it, and
+// without one the stored one is kept
+#[tauri::command]
+pub fn set_sync_config(app: AppHandle, mut config: SyncConfig) -> Result<SyncConfigView, String> {
+    let existing = load_config(&app)?;
+    match config.backend.as_mut() {
+        Some(backend) => {
+            if let Some(secret) = backend.secret_mut().take() {
+                save_secret(&app, secret.trim())?;
+            }
+        }
+        None => save_secret(&app, "")?,
+    }
+    let config = SyncConfig {
+        // The device id and last sync time are managed by the sync engine
+        device_id: if existing.device_id.is_empty() {
+            db::new_id("device")
+        } else {
+            existing.device_id
+        },
+        last_sync: existing.last_sync,
+        ..config
+    };
+    save_config(&app, &config)?;
+    Ok(view(&app, config))
+}
+
+#[tauri::command]
+pub async fn sync_now(app: AppHandle) -> Result<SyncReport, String> {
+    sync_once(&app).await
+}
@@ -0,0 +1,220 @@
+// Tables in web pages as structured data: each <table> becomes a grid of cells, with colspan and
+// rowspan cells repeated into every position they cover, the header rows detected and merged into
+// one header per column, and a CSV rendering alongside.
+use kuchikiki::NodeRef;
+use tauri::AppHandle;
+
+use crate::{clean_text, html, http_client, page, settings};
+
+// Spans beyond these are markup errors
+const MAX_COLSPAN: usize = 100;
+const MAX_ROWSPAN: usize = 1000;
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PageTable {
+    pub caption: Option<String>,
+    // One per column; "Column N" where the table has no header
+    pub headers: Vec<String>,
+    // Whether the headers came from the table rather than being numbered
+    pub has_header: bool,
+    pub rows: Vec<Vec<String>>,
+    pub csv: String,
+}
+
+fn tag_name(node: &NodeRef) -> Option<String> {
+    node.as_element().map(|e| e.name.local.to_string())
+}
+
+fn span(cell: &NodeRef, name: &str, max: usize) -> usize {
+    cell.as_element()
+        .and_then(|e| e.attributes.borrow().get(name).and_then(|v| v.trim().parse::<usize>().ok()))
+        .unwrap_or(1)
+        .clamp(1, max)
+}
+
+// Rows of this table, not of tables nested in its cells
+fn own_rows(table: &NodeRef) -> Vec<NodeRef> {
+    let Ok(rows) = table.select("tr") else { return Vec::new() };
+    rows.map(|row| row.as_node().clone())
+        .filter(|row| {
+            let owner = row.ancestors().find(|a| tag_name(a).as_deref() == Some("table"));
+            owner.is_some_and(|owner| std::ptr::eq(&*owner, &**table))
+        })
+        .collect()
+}
+
+struct Cell {
+    text: String,
+    header: bool,
+}
+
+// Cells carried down into this row by rowspans from the rows above, from `column` on
+fn take_carried(carried: &mut [Option<(usize, String, bool)>], cells: &mut Vec<Cell>, column: &mut usize) {
+    while let Some(Some((left, text, header))) = carried.get_mut(*column) {
+        cells.push(Cell {
+            text: text.clone(),
+            header: *header,
+        });
+        *left -= 1;
+        if *left == 0 {
+            carried[*column] = None;
+        }
+        *column += 1;
+    }
+}
+
+// Rows as cells, with spanned cells repeated; the second value says which rows sit in <thead>
+fn grid(table: &NodeRef) -> (Vec<Vec<Cell>>, Vec<bool>) {
+    let mut grid: Vec<Vec<Cell>> = Vec::new();
+    let mut in_head = Vec::new();
+    // Cells still to be carried down by rowspan, per column: rows left, text, header
+    let mut carried: Vec<Option<(usize, String, bool)>> = Vec::new();
+    for row in own_rows(table) {
+        let mut cells: Vec<Cell> = Vec::new();
+        let mut column = 0;
+        let row_cells = row.children().filter(|c| matches!(tag_name(c).as_deref(), Some("td" | "th")));
+        for cell in row_cells {
+            take_carried(&mut carried, &mut cells, &mut column);
+            let text = clean_text(&cell.text_contents());
+            let header = tag_name(&cell).as_deref() == Some("th");
+            let (colspan, rowspan) = (span(&cell, "colspan", MAX_COLSPAN), span(&cell, "rowspan", MAX_ROWSPAN));
+            for _ in 0..colspan {
+                if rowspan > 1 {
+                    if carried.len() <= column {
+                        carried.resize(column + 1, None);
+                    }
+                    carried[column] = Some((rowspan - 1, text.clone(), header));
+                }
+                cells.push(Cell {
+                    text: text.clone(),
+                    header,
+                });
+                column += 1;
+            }
+        }
+        take_carried(&mut carried, &mut cells, &mut column);
+        if cells.is_empty() {
+            continue;
+        }
+        in_head.push(row.ancestors().any(|a| tag_name(&a).as_deref() == Some("thead")));
+        grid.push(cells);
+    }
+    (grid, in_head)
+}
+
+// Header rows are those in <thead>, or else the leading rows made up only of <th> cells
+fn header_rows(grid: &[Vec<Cell>], in_head: &[bool]) -> usize {
+    let from_thead = in_head.iter().take_while(|&&head| head).count();
+    if from_thead > 0 {
+        return from_thead;
+    }
+    let all_th = grid.iter().take_while(|row| row.iter().all(|cell| cell.header)).count();
+    // In a table of nothing but <th> cells only the first row is the header
+    if all_th == grid.len() {
+        all_th.min(1)
+    } else {
+        all_th
+    }
+}
+
+fn to_csv(headers: &[String], rows: &[Vec<String>]) -> String {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for record in std::iter::once(headers).chain(rows.iter().map(Vec::as_slice)) {
+        // Writing to memory doesn't fail
+        let _ = writer.write_record(record);
+    }
+    writer
+        .into_inner()
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .unwrap_or_default()
+}
+
+fn parse_table(table: &NodeRef) -> Option<PageTable> {
+    let (grid, in_head) = grid(table);
+    let columns = grid.iter().map(Vec::len).max()?;
+    let header_count = header_rows(&grid, &in_head);
+    let text_at = |row: &Vec<Cell>, column: usize| row.get(column).map(|c| c.text.clone()).unwrap_or_default();
+
+    // Stacked header rows merge per column, e.g. "2023 / Revenue"; spans repeat the same text
+    let headers: Vec<String> = (0..columns)
+        .map(|column| {
+            let mut parts: Vec<String> = Vec::new();
+            for row in &grid[..header_count] {
+                let text = text_at(row, column);
+                if !text.is_empty() && !parts.contains(&text) {
+                    parts.push(text);
+                }
+            }
+            if parts.is_empty() {
+                format!("Column {}", column + 1)
+            } else {
+                parts.join(" / ")
+            }
+        })
+        .collect();
+    let rows: Vec<Vec<String>> = grid[header_count..]
+        .iter()
+        .map(|row| (0..columns).map(|column| text_at(row, column)).collect::<Vec<_>>())
+        .filter(|row| row.iter().any(|cell| !cell.is_empty()))
+        .collect();
+    // Layout tables hold a single column of page sections
+    if rows.is_empty() || columns < 2 {
+        return None;
+    }
+    let caption = table
+        .children()
+        .find(|c| tag_name(c).as_deref() == Some("caption"))
+        .map(|c| clean_text(&c.text_contents()))
+        .filter(|c| !c.is_empty());
+    Some(PageTable {
+        caption,
+        csv: to_csv(&headers, &rows),
+        has_header: header_count > 0,
+        headers,
+        rows,
+    })
+}
+
+// Data tables of an HTML document, in document order
+pub fn extract(html: &str) -> Vec<PageTable> {
+    let document = html::parse(html);
+    let Ok(tables) = document.select("table:not([role=presentation])") else { return Vec::new() };
+    let tables: Vec<NodeRef> = tables.map(|t| t.as_node().clone()).collect();
+    tables.iter().filter_map(parse_table).collect()
+}
+
+// Tables of a page given by URL, or of an HTML string
+pub async fn from_source(client: &reqwest::Client, source: &str, timeout_secs: u64) -> Result<Vec<PageTable>, String> {
+    let (html, _) = page::load(client, source, timeout_secs).await?;
+    let tables = tokio::task::spawn_blocking(move || extract(&html))
+        .await
+        .map_err(|e| format!("Table extraction failed: {}", e))?;
+    eprintln!("[Tables] {} tables found", tables.len());
+    Ok(tables)
+}
+
+// Tables as CSV blocks under their captions
+pub fn to_markdown(tables: &[PageTable]) -> String {
+    if tables.is_empty() {
+        return "No data tables found".to_string();
+    }
+    let blocks: Vec<String> = tables
+        .iter()
+        .enumerate()
+        .map(|(i, table)| {
+            let title = table.caption.clone().unwrap_or_else(|| format!("Table {}", i + 1));
+            format!("## {} ({} rows)\n\n```csv\n{}```", title, table.rows.len(), table.csv)
+        })
+        .collect();
+    blocks.join("\n\n")
+}
+
+// Tables of a page as headers, rows and CSV; `source` is an http(s) URL or HTML
+#[tauri::command]
+pub async fn extract_tables(app: AppHandle, source: String) -> Result<Vec<PageTable>, String> {
+    let settings = settings::load(&app);
+    let client = http_client::for_app(&app, &settings)?;
+    from_source(&client, &source, settings.fetch_timeout_secs).await
+}
@@ -0,0 +1,434 @@
+// Tabular data analysis for the agent: a CSV/TSV or JSON file is loaded into an in-memory SQLite
+// table named `data`, described (columns, types, sample rows) and queried with read-only SQL, so
+// questions about a large file can be answered without sending it to the model.
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use rusqlite::types::Value as SqlValue;
+use rusqlite::Connection;
+use serde_json::{json, Map, Value};
+
+// Files larger than this aren't loaded
+const MAX_FILE_BYTES: u64 = 200 * 1024 * 1024;
+const DEFAULT_SAMPLE_ROWS: usize = 5;
+const MAX_SAMPLE_ROWS: usize = 50;
+const DEFAULT_RESULT_ROWS: usize = 100;
+const MAX_RESULT_ROWS: usize = 1000;
+const QUERY_TIMEOUT: Duration = Duration::from_secs(15);
+// Text values in results are cut to this many characters
+const MAX_CELL_CHARS: usize = 500;
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnInfo {
+    name: String,
+    // integer, real, boolean, text or empty
+    #[serde(rename = "type")]
+    kind: &'static str,
+    nulls: usize,
+    distinct: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mean: Option<f64>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableSchema {
+    path: String,
+    table: &'static str,
+    rows: usize,
+    columns: Vec<ColumnInfo>,
+    sample: Vec<Map<String, Value>>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryResult {
+    columns: Vec<String>,
+    rows: Vec<Vec<Value>>,
+    // More rows matched than were returned
+    truncated: bool,
+}
+
+struct Table {
+    columns: Vec<String>,
+    rows: Vec<Vec<Option<String>>>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Empty,
+    Integer,
+    Real,
+    Boolean,
+    Text,
+}
+
+impl Kind {
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Empty => "empty",
+            Kind::Integer => "integer",
+            Kind::Real => "real",
+            Kind::Boolean => "boolean",
+            Kind::Text => "text",
+        }
+    }
+
+    fn sql_type(self) -> &'static str {
+        match self {
+            Kind::Integer | Kind::Boolean => "INTEGER",
+            Kind::Real => "REAL",
+            Kind::Empty | Kind::Text => "TEXT",
+        }
+    }
+
+    fn of(value: &str) -> Kind {
+        if value.parse::<i64>().is_ok() {
+            Kind::Integer
+        } else if value.parse::<f64>().is_ok_and(f64::is_finite) {
+            Kind::Real
+        } else if matches!(value.to_ascii_lowercase().as_str(), "true" | "false") {
+            Kind::Boolean
+        } else {
+            Kind::Text
+        }
+    }
+
+    // The narrowest type holding both
+    fn widen(self, other: Kind) -> Kind {
+        match (self, other) {
+            (Kind::Empty, kind) | (kind, Kind::Empty) => kind,
+            (a, b) if a == b => a,
+            (Kind::Integer, Kind::Real) | (Kind::Real, Kind::Integer) => Kind::Real,
+            _ => Kind::Text,
+        }
+    }
+
+    fn convert(self, value: &str) -> SqlValue {
+        match self {
+            Kind::Integer => value.parse().map(SqlValue::Integer).unwrap_or(SqlValue::Null),
+            Kind::Real => value.parse().map(SqlValue::Real).unwrap_or(SqlValue::Null),
+            Kind::Boolean => SqlValue::Integer(value.eq_ignore_ascii_case("true") as i64),
+            Kind::Empty | Kind::Text => SqlValue::Text(value.to_string()),
+        }
+    }
+}
+
+// Column names made unique and non-empty, so each can be a SQL column
+fn column_names(headers: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for (index, header) in headers.into_iter().enumerate() {
+        let base = header.trim().to_string();
+        let base = if base.is_empty() { format!("column_{}", index + 1) } else { base };
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while names.iter().any(|n| n.eq_ignore_ascii_case(&name)) {
+            name = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        names.push(name);
+    }
+    names
+}
+
+// Comma, semicolon or tab, whichever splits the header line into the most fields
+fn sniff_delimiter(text: &str) -> u8 {
+    let header = text.lines().next().unwrap_or("");
+    [b',', b';', b'\t']
+        .into_iter()
+        .max_by_key(|d| header.matches(*d as char).count())
+        .unwrap_or(b',')
+}
+
+fn load_csv(text: &str, delimiter: u8) -> Result<Table, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let headers = reader.headers().map_err(|e| format!("Invalid CSV: {}", e))?;
+    let columns = column_names(headers.iter().map(str::to_string));
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| format!("Invalid CSV: {}", e))?;
+        let row = (0..columns.len())
+            .map(|i| record.get(i).map(str::trim).filter(|v| !v.is_empty()).map(str::to_string))
+            .collect();
+        rows.push(row);
+    }
+    Ok(Table { columns, rows })
+}
+
+fn json_cell(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        // Numbers and booleans keep their JSON spelling; arrays and objects stay JSON text
+        other => Some(other.to_string()),
+    }
+}
+
+// Rows from an array of objects (or of arrays, with a header row), the first such array in an
+// object, or JSON Lines
+fn load_json(text: &str, lines: bool) -> Result<Table, String> {
+    let records: Vec<Value> = if lines {
+        text.lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("Invalid JSON Lines: {}", e))?
+    } else {
+        match serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {}", e))? {
+            Value::Array(items) => items,
+            Value::Object(object) => object
+                .into_iter()
+                .find_map(|(_, v)| match v {
+                    Value::Array(items) if items.iter().any(Value::is_object) => Some(items),
+                    _ => None,
+                })
+                .ok_or("JSON object holds no array of records")?,
+            _ => return Err("JSON must be an array of records".to_string()),
+        }
+    };
+
+    if records.iter().all(Value::is_array) && !records.is_empty() {
+        let header = records[0].as_array().expect("checked");
+        let columns = column_names(header.iter().map(|v| json_cell(v).unwrap_or_default()));
+        let rows = records[1..]
+            .iter()
+            .map(|r| {
+                let cells = r.as_array().expect("checked");
+                (0..columns.len()).map(|i| cells.get(i).and_then(json_cell)).collect()
+            })
+            .collect();
+        return Ok(Table { columns, rows });
+    }
+
+    // Columns in order of first appearance
+    let mut keys: Vec<String> = Vec::new();
+    for record in &records {
+        let Some(object) = record.as_object() else {
+            return Err("JSON records must all be objects".to_string());
+        };
+        for key in object.keys() {
+            if !keys.contains(key) {
+                keys.push(key.clone());
+            }
+        }
+    }
+    let rows = records
+        .iter()
+        .map(|r| keys.iter().map(|k| r.get(k).and_then(json_cell)).collect())
+        .collect();
+    Ok(Table {
+        columns: column_names(keys.clone()),
+        rows,
+    })
+}
+
+fn load_table(path: &Path) -> Result<Table, String> {
+    let size = std::fs::metadata(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+        .len();
+    if size > MAX_FILE_BYTES {
+        return Err(format!("{} is too large ({} MB)", path.display(), size / 1024 / 1024));
+    }
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let text = String::from_utf8_lossy(&bytes);
+    let text = text.trim_start_matches('\u{feff}');
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let table = match extension.as_str() {
+        "json" => load_json(text, false)?,
+        "jsonl" | "ndjson" => load_json(text, true)?,
+        "tsv" | "tab" => load_csv(text, b'\t')?,
+        "csv" | "txt" => load_csv(text, sniff_delimiter(text))?,
+        other => return Err(format!("Unsupported data file: .{}; use CSV, TSV, JSON or JSON Lines", other)),
+    };
+    if table.columns.is_empty() {
+        return Err("The file has no columns".to_string());
+    }
+    Ok(table)
+}
+
+// Column types, inferred from every value
+fn column_kinds(table: &Table) -> Vec<Kind> {
+    (0..table.columns.len())
+        .map(|i| {
+            table
+                .rows
+                .iter()
+                .filter_map(|row| row[i].as_deref())
+                .fold(Kind::Empty, |kind, value| kind.widen(Kind::of(value)))
+        })
+        .collect()
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn into_sqlite(table: &Table, kinds: &[Kind]) -> Result<Connection, String> {
+    let conn = Connection::open_in_memory().map_err(|e| format!("Failed to open database: {}", e))?;
+    let definition = table
+        .columns
+        .iter()
+        .zip(kinds)
+        .map(|(name, kind)| format!("{} {}", quote_identifier(name), kind.sql_type()))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let placeholders = vec!["?"; table.columns.len()].join(", ");
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    tx.execute(&format!("CREATE TABLE data ({})", definition), [])
+        .map_err(|e| format!("Failed to create table: {}", e))?;
+    {
+        let mut insert = tx
+            .prepare(&format!("INSERT INTO data VALUES ({})", placeholders))
+            .map_err(|e| e.to_string())?;
+        for row in &table.rows {
+            let values = row
+                .iter()
+                .zip(kinds)
+                .map(|(value, kind)| value.as_deref().map(|v| kind.convert(v)).unwrap_or(SqlValue::Null));
+            insert
+                .execute(rusqlite::params_from_iter(values))
+                .map_err(|e| format!("Failed to load row: {}", e))?;
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(conn)
+}
+
+fn json_value(value: SqlValue) -> Value {
+    match value {
+        SqlValue::Null => Value::Null,
+        SqlValue::Integer(n) => json!(n),
+        SqlValue::Real(n) => json!(n),
+        SqlValue::Text(text) if text.chars().count() > MAX_CELL_CHARS => {
+            Value::String(format!("{}...", text.chars().take(MAX_CELL_CHARS).collect::<String>()))
+        }
+        SqlValue::Text(text) => Value::String(text),
+        SqlValue::Blob(bytes) => Value::String(format!("<{} bytes>", bytes.len())),
+    }
+}
+
+// Run one read-only statement, interrupted when it takes too long
+fn run_query(conn: &Connection, sql: &str, max_rows: usize) -> Result<QueryResult, String> {
+    let mut stmt = conn.prepare(sql).map_err(|e| format!("Invalid query: {}", e))?;
+    if !stmt.readonly() {
+        return Err("Only read-only queries (SELECT) are allowed".to_string());
+    }
+    let columns: Vec<String> = stmt.column_names().into_iter().map(str::to_string).collect();
+
+    let (done, finished) = mpsc::channel::<()>();
+    let interrupt = conn.get_interrupt_handle();
+    std::thread::spawn(move || {
+        if finished.recv_timeout(QUERY_TIMEOUT) == Err(mpsc::RecvTimeoutError::Timeout) {
+            interrupt.interrupt();
+        }
+    });
+    let mut rows = Vec::new();
+    let mut truncated = false;
+    let result = (|| {
+        let mut cursor = stmt.query([])?;
+        while let Some(row) = cursor.next()? {
+            if rows.len() == max_rows {
+                truncated = true;
+                break;
+            }
+            let values = (0..columns.len())
+                .map(|i| row.get::<_, SqlValue>(i).map(json_value))
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            rows.push(values);
+        }
+        Ok::<_, rusqlite::Error>(())
+    })();
+    let _ = done.send(());
+    result.map_err(|e| match e {
+        rusqlite::Error::SqliteFailure(err, _) if err.code == rusqlite::ErrorCode::OperationInterrupted => {
+            format!("Query took longer than {} seconds", QUERY_TIMEOUT.as_secs())
+        }
+        other => format!("Query failed: {}", other),
+    })?;
+    Ok(QueryResult {
+        columns,
+        rows,
+        truncated,
+    })
+}
+
+pub fn describe(path: &Path, sample_rows: Option<usize>) -> Result<TableSchema, String> {
+    let table = load_table(path)?;
+    let kinds = column_kinds(&table);
+    let conn = into_sqlite(&table, &kinds)?;
+    let mut columns = Vec::with_capacity(table.columns.len());
+    for (name, kind) in table.columns.iter().zip(&kinds) {
+        let column = quote_identifier(name);
+        let numeric = matches!(kind, Kind::Integer | Kind::Real);
+        let stats = conn
+            .query_row(
+                &format!(
+                    "SELECT COUNT(*) - COUNT({c}), COUNT(DISTINCT {c}), MIN({c}), MAX({c}), {mean} FROM data",
+                    c = column,
+                    mean = if numeric { format!("AVG({})", column) } else { "NULL".to_string() }
+                ),
+                [],
+                |row| {
+                    Ok(ColumnInfo {
+                        name: name.clone(),
+                        kind: kind.name(),
+                        nulls: row.get::<_, i64>(0)? as usize,
+                        distinct: row.get::<_, i64>(1)? as usize,
+                        min: Some(json_value(row.get(2)?)).filter(|v| !v.is_null()),
+                        max: Some(json_value(row.get(3)?)).filter(|v| !v.is_null()),
+                        mean: row.get(4)?,
+                    })
+                },
+            )
+            .map_err(|e| format!("Failed to describe {}: {}", name, e))?;
+        columns.push(stats);
+    }
+    let sample_rows = sample_rows.unwrap_or(DEFAULT_SAMPLE_ROWS).min(MAX_SAMPLE_ROWS);
+    let sample = run_query(&conn, &format!("SELECT * FROM data LIMIT {}", sample_rows), sample_rows)?
+        .rows
+        .into_iter()
+        .map(|row| table.columns.iter().cloned().zip(row).collect())
+        .collect();
+    Ok(TableSchema {
+        path: path.to_string_lossy().to_string(),
+        table: "data",
+        rows: table.rows.len(),
+        columns,
+        sample,
+    })
+}
+
+pub fn query(path: &Path, sql: &str, max_rows: Option<usize>) -> Result<QueryResult, String> {
+    let table = load_table(path)?;
+    let kinds = column_kinds(&table);
+    let conn = into_sqlite(&table, &kinds)?;
+    run_query(&conn, sql, max_rows.unwrap_or(DEFAULT_RESULT_ROWS).min(MAX_RESULT_ROWS))
+}
+
+// Columns with their types and statistics, and the first rows of a CSV, TSV or JSON file
+#[tauri::command]
+pub async fn describe_data_file(path: String, sample_rows: Option<usize>) -> Result<TableSchema, String> {
+    tokio::task::spawn_blocking(move || describe(Path::new(&path), sample_rows))
+        .await
+        .map_err(|e| format!("Data task failed: {}", e))?
+}
+
+// Run a read-only SQL query against the file, loaded as the table `data`
+#[tauri::command]
+pub async fn query_data_file(path: String, sql: String, max_rows: Option<usize>) -> Result<QueryResult, String> {
+    tokio::task::spawn_blocking(move || query(Path::new(&path), &sql, max_rows))
+        .await
+        .map_err(|e| format!("Data task failed: {}", e))?
+}
@@ -0,0 +1,283 @@
+// Tags and folders for organizing conversations.
+//
+// A conversation sits in at most one folder and can carry any number of tags. Both are local to
+// this device: sync only carries the conversations themselves.
+use std::collections::HashMap;
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use tauri::State;
+
+use crate::db::{self, Database};
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Tag {
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagInfo {
+    #[serde(flatten)]
+    tag: Tag,
+    conversation_count: usize,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Folder {
+    id: String,
+    name: String,
+    created_at: String,
+    conversation_count: usize,
+}
+
+fn row_to_tag(row: &Row) -> rusqlite::Result<Tag> {
+    Ok(Tag {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        color: row.get(2)?,
+        created_at: row.get(3)?,
+    })
+}
+
+// Tags of every conversation that has any, keyed by conversation id
+pub fn tags_by_conversation(conn: &Connection) -> rusqlite::Result<HashMap<String, Vec<Tag>>> {
+    let mut stmt = conn.prepare(
+        "SELECT ct.conversation_id, t.id, t.name, t.color, t.created_at
+         FROM conversation_tags ct JOIN tags t ON t.id = ct.tag_id
+         ORDER BY t.name COLLATE NOCASE",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            Tag {
+                id: row.get(1)?,
+                name: row.get(2)?,
+                color: row.get(3)?,
+                created_at: row.get(4)?,
+            },
+        ))
+    })?;
+    let mut tags: HashMap<String, Vec<Tag>> = HashMap::new();
+    for row in rows {
+        let (conversation_id, tag) = row?;
+        tags.entry(conversation_id).or_default().push(tag);
+    }
+    Ok(tags)
+}
+
+fn clean_name(name: &str, kind: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(format!("{} name cannot be empty", kind));
+    }
+    Ok(name.to_string())
+}
+
+// Unique-name violations become a readable error instead of a raw constraint message
+fn name_error(err: String, kind: &str, name: &str) -> String {
+    if err.contains("UNIQUE") {
+        format!("A {} named '{}' already exists", kind.to_lowercase(), name)
+    } else {
+        err
+    }
+}
+
+fn get_tag(db: &Database, id: &str) -> Result<Tag, String> {
+    db.with_conn(|conn| {
+        conn.query_row("SELECT id, name, color, created_at FROM tags WHERE id = ?1", params![id], row_to_tag)
+            .optional()
+    })?
+    .ok_or_else(|| format!("Tag {} not found", id))
+}
+
+fn conversation_exists(conn: &Connection, id: &str) -> rusqlite::Result<bool> {
+    conn.query_row("SELECT 1 FROM conversations WHERE id = ?1", params![id], |_| Ok(()))
+        .optional()
+        .map(|found| found.is_some())
+}
+
+#[tauri::command]
+pub fn list_tags(db: State<'_, Database>) -> Result<Vec<TagInfo>, String> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT t.id, t.name, t.color, t.created_at,
+                    (SELECT COUNT(*) FROM conversation_tags ct WHERE ct.tag_id = t.id)
+             FROM tags t ORDER BY t.name COLLATE NOCASE",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(TagInfo {
+                tag: row_to_tag(row)?,
+                conversation_count: row.get::<_, i64>(4)? as usize,
+            })
+        })?;
+        rows.collect()
+    })
+}
+
+#[tauri::command]
+pub fn create_tag(db: State<'_, Database>, name: String, color: Option<String>) -> Result<Tag, String> {
+    let name = clean_name(&name, "Tag")?;
+    let id = db::new_id("tag");
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO tags (id, name, color, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![id, name, color, chrono::Utc::now().to_rfc3339()],
+        )
+    })
+    .map_err(|e| name_error(e, "Tag", &name))?;
+    get_tag(&db, &id)
+}
+
+// Rename a tag and/or change its color
+#[tauri::command]
+pub fn update_tag(db: State<'_, Database>, id: String, name: String, color: Option<String>) -> Result<Tag, String> {
+    let name = clean_name(&name, "Tag")?;
+    let updated = db
+        .with_conn(|conn| {
+            conn.execute(
+                "UPDATE tags SET name = ?1, color = ?2 WHERE id = ?3",
+                params![name, color, id],
+            )
+        })
+        .map_err(|e| name_error(e, "Tag", &name))?;
+    if updated == 0 {
+        return Err(format!("Tag {} not found", id));
+    }
+    get_tag(&db, &id)
+}
+
+// Removing a tag also removes it from every conversation
+#[tauri::command]
+pub fn delete_tag(db: State<'_, Database>, id: String) -> Result<(), String> {
+    let deleted = db.with_conn(|conn| conn.execute("DELETE FROM tags WHERE id = ?1", params![id]))?;
+    if deleted == 0 {
+        return Err(format!("Tag {} not found", id));
+    }
+    Ok(())
+}
+
+// Replace the tags of a conversation
+#[tauri::command]
+pub fn set_conversation_tags(
+    db: State<'_, Database>,
+    conversation_id: String,
+    tag_ids: Vec<String>,
+) -> Result<Vec<Tag>, String> {
+    let found = db.with_conn(|conn| {
+        if !conversation_exists(conn, &conversation_id)? {
+            return Ok(false);
+        }
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM conversation_tags WHERE conversation_id = ?1",
+            params![conversation_id],
+        )?;
+        for tag_id in &tag_ids {
+            tx.execute(
+                "INSERT OR IGNORE INTO conversation_tags (conversation_id, tag_id) VALUES (?1, ?2)",
+                params![conversation_id, tag_id],
+            )?;
+        }
+        tx.commit()?;
+        Ok(true)
+    });
+    match found {
+        Ok(true) => {}
+        Ok(false) => return Err(format!("Conversation {} not found", conversation_id)),
+        // Foreign key failure: one of the tag ids doesn't exist
+        Err(err) if err.contains("FOREIGN KEY") => return Err("Unknown tag id".to_string()),
+        Err(err) => return Err(err),
+    }
+    let mut tags = db.with_conn(tags_by_conversation)?;
+    Ok(tags.remove(&conversation_id).unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn list_folders(db: State<'_, Database>) -> Result<Vec<Folder>, String> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT f.id, f.name, f.created_at,
+                    (SELECT COUNT(*) FROM conversations c WHERE c.folder_id = f.id)
+             FROM folders f ORDER BY f.name COLLATE NOCASE",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(Folder {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                created_at: row.get(2)?,
+                conversation_count: row.get::<_, i64>(3)? as usize,
+            })
+        })?;
+        rows.collect()
+    })
+}
+
+#[tauri::command]
+pub fn create_folder(db: State<'_, Database>, name: String) -> Result<Folder, String> {
+    let name = clean_name(&name, "Folder")?;
+    let folder = Folder {
+        id: db::new_id("folder"),
+        name,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        conversation_count: 0,
+    };
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO folders (id, name, created_at) VALUES (?1, ?2, ?3)",
+            params![folder.id, folder.name, folder.created_at],
+        )
+    })
+    .map_err(|e| name_error(e, "Folder", &folder.name))?;
+    Ok(folder)
+}
+
+#[tauri::command]
+pub fn rename_folder(db: State<'_, Database>, id: String, name: String) -> Result<(), String> {
+    let name = clean_name(&name, "Folder")?;
+    let updated = db
+        .with_conn(|conn| conn.execute("UPDATE folders SET name = ?1 WHERE id = ?2", params![name, id]))
+        .map_err(|e| name_error(e, "Folder", &name))?;
+    if updated == 0 {
+        return Err(format!("Folder {} not found", id));
+    }
+    Ok(())
+}
+
+// Conversations in the folder are kept and become unfiled
+#[tauri::command]
+pub fn delete_folder(db: State<'_, Database>, id: String) -> Result<(), String> {
+    let deleted = db.with_conn(|conn| conn.execute("DELETE FROM folders WHERE id = ?1", params![id]))?;
+    if deleted == 0 {
+        return Err(format!("Folder {} not found", id));
+    }
+    Ok(())
+}
+
+// Move a conversation into a folder, or out of any folder when `folder_id` is omitted
+#[tauri::command]
+pub fn move_to_folder(db: State<'_, Database>, conversation_id: String, folder_id: Option<String>) -> Result<(), String> {
+    let updated = db
+        .with_conn(|conn| {
+            conn.execute(
+                "UPDATE conversations SET folder_id = ?1 WHERE id = ?2",
+                params![folder_id, conversation_id],
+            )
+        })
+        .map_err(|e| {
+            if e.contains("FOREIGN KEY") {
+                format!("Folder {} not found", folder_id.as_deref().unwrap_or_default())
+            } else {
+                e
+            }
+        })?;
+    if updated == 0 {
+        return Err(format!("Conversation {} not found", conversation_id));
+    }
+    Ok(())
+}
@@ -0,0 +1,204 @@
+// Reusable prompt templates with {{variable}} placeholders
+//
+// `{{name}}` is a required variable, `{{name|default text}}` an optional one with a default.
+use std::collections::HashMap;
+
+use rusqlite::{params, OptionalExtension, Row};
+use tauri::State;
+
+use crate::db::{self, Database};
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateVariable {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default: Option<String>,
+    required: bool,
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptTemplate {
+    id: String,
+    name: String,
+    description: String,
+    content: String,
+    variables: Vec<TemplateVariable>,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateInput {
+    name: String,
+    #[serde(default)]
+    description: String,
+    content: String,
+}
+
+// A placeholder found in the template text: byte range plus the parsed variable
+struct Placeholder {
+    start: usize,
+    end: usize,
+    variable: TemplateVariable,
+}
+
+fn parse_placeholders(content: &str) -> Result<Vec<Placeholder>, String> {
+    let mut placeholders = Vec::new();
+    let mut offset = 0;
+    while let Some(open) = content[offset..].find("{{") {
+        let start = offset + open;
+        let close = content[start..]
+            .find("}}")
+            .ok_or_else(|| format!("Unclosed placeholder at position {}", start))?;
+        let end = start + close + 2;
+        let inner = &content[start + 2..end - 2];
+        let (name, default) = match inner.split_once('|') {
+            Some((name, default)) => (name.trim(), Some(default.to_string())),
+            None => (inner.trim(), None),
+        };
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.') {
+            return Err(format!("Invalid variable name '{}'", name));
+        }
+        placeholders.push(Placeholder {
+            start,
+            end,
+            variable: TemplateVariable {
+                name: name.to_string(),
+                required: default.is_none(),
+                default,
+            },
+        });
+        offset = end;
+    }
+    Ok(placeholders)
+}
+
+// Unique variables in order of first appearance
+fn template_variables(content: &str) -> Result<Vec<TemplateVariable>, String> {
+    let mut variables: Vec<TemplateVariable> = Vec::new();
+    for placeholder in parse_placeholders(content)? {
+        match variables.iter_mut().find(|v| v.name == placeholder.variable.name) {
+            // A variable is only optional if every occurrence has a default
+            Some(existing) => existing.required |= placeholder.variable.required,
+            None => variables.push(placeholder.variable),
+        }
+    }
+    Ok(variables)
+}
+
+pub fn render(content: &str, vars: &HashMap<String, String>) -> Result<String, String> {
+    let placeholders = parse_placeholders(content)?;
+
+    let mut missing: Vec<&str> = Vec::new();
+    for p in placeholders.iter().filter(|p| p.variable.required) {
+        if !vars.contains_key(&p.variable.name) && !missing.contains(&p.variable.name.as_str()) {
+            missing.push(&p.variable.name);
+        }
+    }
+    if !missing.is_empty() {
+        return Err(format!("Missing required variables: {}", missing.join(", ")));
+    }
+
+    let mut output = String::with_capacity(content.len());
+    let mut last = 0;
+    for p in &placeholders {
+        output.push_str(&content[last..p.start]);
+        let value = vars
+            .get(&p.variable.name)
+            .map(|s| s.as_str())
+            .or(p.variable.default.as_deref())
+            .unwrap_or("");
+        output.push_str(value);
+        last = p.end;
+    }
+    output.push_str(&content[last..]);
+    Ok(output)
+}
+
+fn row_to_template(row: &Row) -> rusqlite::Result<PromptTemplate> {
+    let content: String = row.get(3)?;
+    Ok(PromptTemplate {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        variables: template_variables(&content).unwrap_or_default(),
+        content,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
+
+const SELECT_TEMPLATE: &str = "SELECT id, name, description, content, created_at, updated_at FROM prompt_templates";
+
+fn get_template(db: &Database, id: &str) -> Result<PromptTemplate, String> {
+    db.with_conn(|conn| {
+        conn.query_row(&format!("{} WHERE id = ?1", SELECT_TEMPLATE), params![id], row_to_template)
+            .optional()
+    })?
+    .ok_or_else(|| format!("Template {} not found", id))
+}
+
+fn validate_input(input: &TemplateInput) -> Result<(), String> {
+    if input.name.trim().is_empty() {
+        return Err("Template name cannot be empty".to_string());
+    }
+    template_variables(&input.content).map(|_| ())
+}
+
+#[tauri::command]
+pub fn list_templates(db: State<'_, Database>) -> Result<Vec<PromptTemplate>, String> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(&format!("{} ORDER BY name COLLATE NOCASE", SELECT_TEMPLATE))?;
+        let rows = stmt.query_map([], row_to_template)?;
+        rows.collect()
+    })
+}
+
+#[tauri::command]
+pub fn create_template(db: State<'_, Database>, template: TemplateInput) -> Result<PromptTemplate, String> {
+    validate_input(&template)?;
+    let id = db::new_id("tpl");
+    let now = chrono::Utc::now().to_rfc3339();
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO prompt_templates (id, name, description, content, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+            params![id, template.name.trim(), template.description, template.content, now],
+        )
+    })?;
+    eprintln!("[Templates] Created template {}", id);
+    get_template(&db, &id)
+}
+
+#[tauri::command]
+pub fn update_template(db: State<'_, Database>, id: String, template: TemplateInput) -> Result<PromptTemplate, String> {
+    validate_input(&template)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let updated = db.with_conn(|conn| {
+        conn.execute(
+            "UPDATE prompt_templates SET name = ?1, description = ?2, content = ?3, updated_at = ?4 WHERE id = ?5",
+            params![template.name.trim(), template.description, template.content, now, id],
+        )
+    })?;
+    if updated == 0 {
+        return Err(format!("Template {} not found", id));
+    }
+    get_template(&db, &id)
+}
+
+#[tauri::command]
+pub fn delete_template(db: State<'_, Database>, id: String) -> Result<(), String> {
+    let deleted = db.with_conn(|conn| conn.execute("DELETE FROM prompt_templates WHERE id = ?1", params![id]))?;
+    if deleted == 0 {
+        return Err(format!("Template {} not found", id));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn render_template(db: State<'_, Database>, id: String, vars: HashMap<String, String>) -> Result<String, String> {
+    let template = get_template(&db, &id)?;
+    render(&template.content, &vars)
+}
@@ -0,0 +1,751 @@
+// Built-in tools wrapping the existing search, scrape, terminal and file commands
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use serde_json::Value;
+
+use tauri::{AppHandle, Manager};
+
+use super::{string_arg, Tool, ToolParameters, ToolRegistry};
+use crate::browser::BrowserPool;
+use crate::http_client::{ClientDefaults, HttpClients};
+use crate::politeness::HostScheduler;
+use crate::proxy::ProxyConfig;
+use crate::settings::Settings;
+use crate::sources::{SourceClient, MAX_SOURCE_CHARS};
+
+// What the network tools fetch with. In the app: its current settings and proxy, and its browser
+// pool, host scheduler and HTTP clients. The headless MCP server has no profile to read settings
+// from, so it uses the defaults and its own pool, scheduler and clients.
+#[derive(Clone)]
+enum ToolEnv {
+    App(AppHandle),
+    Standalone {
+        pool: BrowserPool,
+        hosts: HostScheduler,
+        clients: HttpClients,
+    },
+}
+
+impl ToolEnv {
+    fn settings(&self) -> Settings {
+        match self {
+            ToolEnv::App(app) => crate::settings::load(app),
+            ToolEnv::Standalone { .. } => Settings::default(),
+        }
+    }
+
+    fn proxy(&self, settings: &Settings) -> Option<ProxyConfig> {
+        match self {
+            ToolEnv::App(_) => crate::proxy::from_settings(settings),
+            ToolEnv::Standalone { .. } => None,
+        }
+    }
+
+    // The shared async client for `settings`
+    fn client(&self, settings: &Settings) -> Result<reqwest::Client, String> {
+        self.clients().client(&ClientDefaults::from_settings(settings), self.proxy(settings).as_ref())
+    }
+
+    // The source APIs' client, with the fetch timeout of the current settings
+    fn source_client(&self) -> Result<SourceClient, String> {
+        let settings = self.settings();
+        Ok(SourceClient::new(self.client(&settings)?, settings.fetch_timeout_secs))
+    }
+
+    fn clients(&self) -> HttpClients {
+        match self {
+            ToolEnv::App(app) => app.state::<HttpClients>().inner().clone(),
+            ToolEnv::Standalone { clients, .. } => clients.clone(),
+        }
+    }
+
+    fn scrape_options(&self, settings: &Settings) -> crate::ScrapeOptions {
+        match self {
+            ToolEnv::App(app) => crate::ScrapeOptions::for_app(app, settings),
+            ToolEnv::Standalone { pool, hosts, clients } => {
+                crate::ScrapeOptions::new(settings, pool.clone(), hosts.clone(), clients.clone())
+            }
+        }
+    }
+}
+
+pub fn register_all(registry: &ToolRegistry) {
+    let env = ToolEnv::Standalone {
+        pool: BrowserPool::default(),
+        hosts: HostScheduler::default(),
+        clients: HttpClients::default(),
+    };
+    registry.register(Arc::new(WebSearchTool { env: env.clone() }));
+    register_network_tools(registry, &env);
+    registry.register(Arc::new(TerminalTool));
+    registry.register(Arc::new(ReadFileTool));
+    registry.register(Arc::new(WriteFileTool));
+    registry.register(Arc::new(AnalyzeDataTool));
+}
+
+// Replace the standalone network tools with ones using the app's settings, proxy and shared state.
+// web_search is left to crate::search, whose tool searches with the configured provider chain.
+pub fn register_app_tools(registry: &ToolRegistry, app: &AppHandle) {
+    register_network_tools(registry, &ToolEnv::App(app.clone()));
+}
+
+fn register_network_tools(registry: &ToolRegistry, env: &ToolEnv) {
+    registry.register(Arc::new(CalculatorTool { env: env.clone() }));
+    registry.register(Arc::new(ScrapeUrlTool { env: env.clone() }));
+    registry.register(Arc::new(ExtractTablesTool { env: env.clone() }));
+    registry.register(Arc::new(PageLinksTool { env: env.clone() }));
+    registry.register(Arc::new(PageOutlineTool { env: env.clone() }));
+    registry.register(Arc::new(WikipediaTool { env: env.clone() }));
+    registry.register(Arc::new(ArxivSearchTool { env: env.clone() }));
+    registry.register(Arc::new(SemanticScholarSearchTool { env: env.clone() }));
+    registry.register(Arc::new(ReadPaperTool { env: env.clone() }));
+    registry.register(Arc::new(RedditSearchTool { env: env.clone() }));
+    registry.register(Arc::new(RedditThreadTool { env: env.clone() }));
+    registry.register(Arc::new(StackExchangeTool { env: env.clone() }));
+    registry.register(Arc::new(YoutubeTranscriptTool { env: env.clone() }));
+    registry.register(Arc::new(WeatherTool { env: env.clone() }));
+}
+
+struct WebSearchTool {
+    env: ToolEnv,
+}
+
+impl Tool for WebSearchTool {
+    fn name(&self) -> &str {
+        "web_search"
+    }
+
+    fn description(&self) -> &str {
+        "Search the web and return the top results"
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        ToolParameters::new().required("query", "string", "Search query")
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move {
+            let query = string_arg(&args, "query")?;
+            let settings = self.env.settings();
+            let timeout = std::time::Duration::from_secs(settings.fetch_timeout_secs);
+            let defaults = ClientDefaults::from_settings(&settings);
+            let proxy = self.env.proxy(&settings);
+            let params = crate::search::duckduckgo::form_params(&Default::default(), 5);
+            let clients = self.env.clients();
+            let html = tokio::task::spawn_blocking(move || {
+                let session = clients.session(&defaults, proxy.as_ref(), timeout)?;
+                crate::search::duckduckgo::fetch_html(&session, &query, &params)
+            })
+                .await
+                .map_err(|e| format!("Task error: {}", e))??;
+            let results = crate::search::duckduckgo::parse_results(&html, 5)?;
+            if results.is_empty() {
+                return Ok(crate::strip_html_tags(&html));
+            }
+            serde_json::to_string_pretty(&results).map_err(|e| e.to_string())
+        })
+    }
+}
+
+struct ScrapeUrlTool {
+    env: ToolEnv,
+}
+
+impl Tool for ScrapeUrlTool {
+    fn name(&self) -> &str {
+        "scrape_url"
+    }
+
+    fn description(&self) -> &str {
+        "Fetch a web page and return its main content as Markdown"
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        ToolParameters::new().required("url", "string", "http(s) URL to fetch").optional(
+            "selector",
+            "string",
+            "CSS selector, e.g. .price or #changelog li, to return only the text of the matching elements",
+        )
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move {
+            let url = string_arg(&args, "url")?;
+            let settings = self.env.settings();
+            let mut options = self.env.scrape_options(&settings);
+            options.max_retries = 2;
+            options.format = crate::html::Format::Markdown;
+            options.max_chars = Some(MAX_SOURCE_CHARS);
+            let selector = args.get("selector").and_then(|s| s.as_str()).map(str::trim).filter(|s| !s.is_empty());
+            if let Some(selector) = selector {
+                options.selectors = vec![selector.to_string()];
+                crate::selection::validate(&options.selectors)?;
+            }
+            let result = crate::scrape_url_async(url, options).await;
+            if let (Some(content), Some(selector)) = (&result.content, selector) {
+                let matches = content.selected.get(selector).map(Vec::as_slice).unwrap_or_default();
+                if matches.is_empty() {
+                    return Ok(format!("# {}\n\nNo elements match `{}`", content.title, selector));
+                }
+                let items: Vec<String> = matches.iter().map(|m| format!("- {}", m.text.replace('\n', " "))).collect();
+                let list = crate::sources::truncate(&items.join("\n"), MAX_SOURCE_CHARS);
+                return Ok(format!("# {}\n\nMatches for `{}`:\n{}", content.title, selector, list));
+            }
+            match (result.content, result.error) {
+                (Some(content), _) => {
+                    let mut notes = String::new();
+                    if let Some(final_url) = &content.response.final_url {
+                        notes.push_str(&format!("> Redirected to {}; cite that URL.\n\n", final_url));
+                    }
+                    if content.paywalled {
+                        notes.push_str("> This page is behind a paywall or login; the content is likely incomplete.");
+                        notes.push_str("\n\n");
+                    }
+                    Ok(format!("# {}\n\n{}{}", content.title, notes, content.content))
+                }
+                (None, error) => Err(error.unwrap_or_else(|| "Scrape failed".to_string())),
+            }
+        })
+    }
+}
+
+struct ExtractTablesTool {
+    env: ToolEnv,
+}
+
+impl Tool for ExtractTablesTool {
+    fn name(&self) -> &str {
+        "extract_tables"
+    }
+
+    fn description(&self) -> &str {
+        "Extract the data tables of a web page (specs, prices, statistics) as CSV with their headers"
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        ToolParameters::new().required("url", "string", "http(s) URL of the page")
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move {
+            let url = string_arg(&args, "url")?;
+            let settings = self.env.settings();
+            let client = self.env.client(&settings)?;
+            let tables = crate::tables::from_source(&client, &url, settings.fetch_timeout_secs).await?;
+            Ok(crate::sources::truncate(&crate::tables::to_markdown(&tables), MAX_SOURCE_CHARS))
+        })
+    }
+}
+
+struct PageLinksTool {
+    env: ToolEnv,
+}
+
+impl Tool for PageLinksTool {
+    fn name(&self) -> &str {
+        "page_links"
+    }
+
+    fn description(&self) -> &str {
+        "List the links of a web page with their anchor text and section, e.g. to find the API reference \
+         link on a docs page"
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        ToolParameters::new()
+            .required("url", "string", "http(s) URL of the page")
+            .optional("filter", "string", "Only links whose text, URL or section contains this")
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move {
+            let url = string_arg(&args, "url")?;
+            let filter = args.get("filter").and_then(|v| v.as_str());
+            let settings = self.env.settings();
+            let client = self.env.client(&settings)?;
+            let links = crate::page::links_from_source(&client, &url, filter, settings.fetch_timeout_secs).await?;
+            Ok(crate::sources::truncate(&crate::page::links_to_markdown(&links), MAX_SOURCE_CHARS))
+        })
+    }
+}
+
+struct PageOutlineTool {
+    env: ToolEnv,
+}
+
+impl Tool for PageOutlineTool {
+    fn name(&self) -> &str {
+        "page_outline"
+    }
+
+    fn description(&self) -> &str {
+        "Get the title and heading outline of a web page, with links to the headings"
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        ToolParameters::new().required("url", "string", "http(s) URL of the page")
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move {
+            let url = string_arg(&args, "url")?;
+            let settings = self.env.settings();
+            let client = self.env.client(&settings)?;
+            let outline = crate::page::outline_from_source(&client, &url, settings.fetch_timeout_secs).await?;
+            Ok(crate::sources::truncate(&crate::page::outline_to_markdown(&outline), MAX_SOURCE_CHARS))
+        })
+    }
+}
+
+struct TerminalTool;
+
+impl Tool for TerminalTool {
+    fn name(&self) -> &str {
+        "run_terminal_command"
+    }
+
+    fn description(&self) -> &str {
+        "Run a shell command and return stdout, stderr and the exit code"
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        ToolParameters::new()
+            .required("command", "string", "Command line to execute")
+            .optional("working_dir", "string", "Optional working directory")
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move {
+            let command = string_arg(&args, "command")?;
+            let working_dir = args.get("working_dir").and_then(|v| v.as_str()).map(|s| s.to_string());
+            let result = tokio::task::spawn_blocking(move || crate::run_terminal_command(command, working_dir))
+                .await
+                .map_err(|e| format!("Task error: {}", e))??;
+            Ok(format!(
+                "exit code: {}\nstdout:\n{}\nstderr:\n{}",
+                result.exit_code, result.stdout, result.stderr
+            ))
+        })
+    }
+}
+
+struct ReadFileTool;
+
+impl Tool for ReadFileTool {
+    fn name(&self) -> &str {
+        "read_file"
+    }
+
+    fn description(&self) -> &str {
+        "Read a text file from disk"
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        ToolParameters::new().required("path", "string", "File path")
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move { crate::read_file_content(string_arg(&args, "path")?) })
+    }
+}
+
+struct WriteFileTool;
+
+impl Tool for WriteFileTool {
+    fn name(&self) -> &str {
+        "write_file"
+    }
+
+    fn description(&self) -> &str {
+        "Write text content to a file on disk"
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        ToolParameters::new()
+            .required("path", "string", "File path")
+            .required("content", "string", "Content to write")
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move {
+            let path = string_arg(&args, "path")?;
+            crate::write_file_content(path.clone(), string_arg(&args, "content")?)?;
+            Ok(format!("Wrote {}", path))
+        })
+    }
+}
+
+struct AnalyzeDataTool;
+
+impl Tool for AnalyzeDataTool {
+    fn name(&self) -> &str {
+        "analyze_data"
+    }
+
+    fn description(&self) -> &str {
+        "Inspect a CSV, TSV or JSON data file without reading all of it. Without `sql`, returns the \
+         columns with their types and statistics and a few sample rows. With `sql`, runs a read-only \
+         SQLite query against the file loaded as the table `data` and returns the result rows."
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        ToolParameters::new()
+            .required("path", "string", "Path of the CSV, TSV, JSON or JSON Lines file")
+            .optional(
+                "sql",
+                "string",
+                "SQLite SELECT over the table `data`, e.g. SELECT region, SUM(sales) FROM data GROUP BY region",
+            )
+            .optional("max_rows", "integer", "Rows returned at most (default 100)")
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move {
+            let path = string_arg(&args, "path")?;
+            let sql = args.get("sql").and_then(|v| v.as_str()).filter(|s| !s.trim().is_empty()).map(str::to_string);
+            let max_rows = args.get("max_rows").and_then(|v| v.as_u64()).map(|n| n as usize);
+            let output = tokio::task::spawn_blocking(move || {
+                let path = std::path::Path::new(&path);
+                match sql {
+                    Some(sql) => serde_json::to_string(&crate::tabular::query(path, &sql, max_rows)?),
+                    None => serde_json::to_string_pretty(&crate::tabular::describe(path, None)?),
+                }
+                .map_err(|e| e.to_string())
+            })
+            .await
+            .map_err(|e| format!("Task error: {}", e))??;
+            Ok(output)
+        })
+    }
+}
+
+struct WikipediaTool {
+    env: ToolEnv,
+}
+
+impl Tool for WikipediaTool {
+    fn name(&self) -> &str {
+        "wikipedia_lookup"
+    }
+
+    fn description(&self) -> &str {
+        "Look up a Wikipedia article by title or search query. Returns its summary, or with `full` the \
+         whole article with its section headings."
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        ToolParameters::new()
+            .required("title_or_query", "string", "Article title, or a query to search for")
+            .optional("lang", "string", "Wikipedia language code (default en)")
+            .optional("full", "boolean", "Return the full article instead of the summary")
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move {
+            let query = string_arg(&args, "title_or_query")?;
+            let lang = args.get("lang").and_then(|v| v.as_str()).filter(|l| !l.is_empty()).unwrap_or("en");
+            let full = args.get("full").and_then(|v| v.as_bool()).unwrap_or(false);
+            let http = self.env.source_client()?;
+            let article = crate::sources::wikipedia::lookup(&query, lang, full, &http).await?;
+            Ok(crate::sources::truncate(&crate::sources::wikipedia::to_markdown(&article), MAX_SOURCE_CHARS))
+        })
+    }
+}
+
+fn max_results_arg(args: &Value) -> Option<usize> {
+    args.get("max_results").and_then(|v| v.as_u64()).map(|n| n as usize)
+}
+
+struct ArxivSearchTool {
+    env: ToolEnv,
+}
+
+impl Tool for ArxivSearchTool {
+    fn name(&self) -> &str {
+        "arxiv_search"
+    }
+
+    fn description(&self) -> &str {
+        "Search arXiv preprints. Returns title, authors, abstract, year and PDF link for each paper; \
+         pass the PDF link to read_paper for the full text."
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        ToolParameters::new()
+            .required("query", "string", "Search terms, or an arXiv query such as ti:transformer AND cat:cs.CL")
+            .optional("max_results", "integer", "Number of papers (default 10)")
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move {
+            let query = string_arg(&args, "query")?;
+            let http = self.env.source_client()?;
+            let papers = crate::sources::papers::search_arxiv(&query, max_results_arg(&args), &http).await?;
+            Ok(crate::sources::papers::to_markdown(&papers))
+        })
+    }
+}
+
+struct SemanticScholarSearchTool {
+    env: ToolEnv,
+}
+
+impl Tool for SemanticScholarSearchTool {
+    fn name(&self) -> &str {
+        "semantic_scholar_search"
+    }
+
+    fn description(&self) -> &str {
+        "Search published academic papers on Semantic Scholar. Returns title, authors, abstract, year, \
+         citation count and, for open-access papers, a PDF link to pass to read_paper."
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        ToolParameters::new()
+            .required("query", "string", "Search terms")
+            .optional("max_results", "integer", "Number of papers (default 10)")
+            .optional("year", "string", "Publication year or range, e.g. 2020 or 2016-2020")
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move {
+            let query = string_arg(&args, "query")?;
+            let year = args.get("year").and_then(|v| v.as_str());
+            let http = self.env.source_client()?;
+            let papers =
+                crate::sources::papers::search_semantic_scholar(&query, max_results_arg(&args), year, &http).await?;
+            Ok(crate::sources::papers::to_markdown(&papers))
+        })
+    }
+}
+
+struct ReadPaperTool {
+    env: ToolEnv,
+}
+
+impl Tool for ReadPaperTool {
+    fn name(&self) -> &str {
+        "read_paper"
+    }
+
+    fn description(&self) -> &str {
+        "Download a paper's PDF and return its full text"
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        ToolParameters::new().required("pdf_url", "string", "PDF link from arxiv_search or semantic_scholar_search")
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move {
+            let url = string_arg(&args, "pdf_url")?;
+            let http = self.env.source_client()?;
+            let text = crate::sources::papers::read_pdf(&url, &http).await?;
+            Ok(crate::sources::truncate(&text, MAX_SOURCE_CHARS))
+        })
+    }
+}
+
+struct RedditSearchTool {
+    env: ToolEnv,
+}
+
+impl Tool for RedditSearchTool {
+    fn name(&self) -> &str {
+        "reddit_search"
+    }
+
+    fn description(&self) -> &str {
+        "Search Reddit posts, optionally within one subreddit. Good for first-hand experiences and \
+         community recommendations; open a result with reddit_thread to read its comments."
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        ToolParameters::new()
+            .required("query", "string", "Search terms")
+            .optional("subreddit", "string", "Only search this subreddit, e.g. rust")
+            .optional("sort", "string", "relevance (default), hot, top, new or comments")
+            .optional("time_range", "string", "day, week, month or year")
+            .optional("limit", "integer", "Number of posts (default 10)")
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move {
+            let query = string_arg(&args, "query")?;
+            let subreddit = args.get("subreddit").and_then(|v| v.as_str());
+            let sort = args.get("sort").and_then(|v| v.as_str()).filter(|s| !s.is_empty());
+            let time_range = match args.get("time_range").filter(|v| !v.is_null()) {
+                Some(value) => Some(serde_json::from_value(value.clone()).map_err(|_| format!("Invalid time_range: {}", value))?),
+                None => None,
+            };
+            let limit = args.get("limit").and_then(|v| v.as_u64()).map(|n| n as usize);
+            let http = self.env.source_client()?;
+            let posts = crate::sources::reddit::search(&query, subreddit, sort, time_range, limit, &http).await?;
+            Ok(crate::sources::reddit::posts_to_markdown(&posts))
+        })
+    }
+}
+
+struct RedditThreadTool {
+    env: ToolEnv,
+}
+
+impl Tool for RedditThreadTool {
+    fn name(&self) -> &str {
+        "reddit_thread"
+    }
+
+    fn description(&self) -> &str {
+        "Read a Reddit thread: the post and its highest-scored comments with their top replies"
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        ToolParameters::new()
+            .required("url", "string", "Thread URL or post id")
+            .optional("max_comments", "integer", "Top-level comments to include (default 10)")
+            .optional("max_depth", "integer", "Reply levels to include, 1 for top-level only (default 3)")
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move {
+            let url = string_arg(&args, "url")?;
+            let max_comments = args.get("max_comments").and_then(|v| v.as_u64()).map(|n| n as usize);
+            let max_depth = args.get("max_depth").and_then(|v| v.as_u64()).map(|n| n as usize);
+            let http = self.env.source_client()?;
+            let thread = crate::sources::reddit::thread(&url, max_comments, max_depth, &http).await?;
+            Ok(crate::sources::truncate(&crate::sources::reddit::thread_to_markdown(&thread), MAX_SOURCE_CHARS))
+        })
+    }
+}
+
+struct StackExchangeTool {
+    env: ToolEnv,
+}
+
+impl Tool for StackExchangeTool {
+    fn name(&self) -> &str {
+        "stackexchange_search"
+    }
+
+    fn description(&self) -> &str {
+        "Search Stack Overflow (or another Stack Exchange site) for questions and return them with \
+         their accepted and top-voted answers, code blocks included"
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        ToolParameters::new()
+            .required("query", "string", "Search terms, e.g. an error message")
+            .optional("site", "string", "Site name such as superuser, serverfault or math (default stackoverflow)")
+            .optional("tagged", "string", "Tags the questions must have, separated by semicolons, e.g. rust;tokio")
+            .optional("max_questions", "integer", "Number of questions (default 5)")
+            .optional("max_answers", "integer", "Answers per question (default 2)")
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move {
+            let query = string_arg(&args, "query")?;
+            let site = args.get("site").and_then(|v| v.as_str());
+            let tagged = args.get("tagged").and_then(|v| v.as_str());
+            let max_questions = args.get("max_questions").and_then(|v| v.as_u64()).map(|n| n as usize);
+            let max_answers = args.get("max_answers").and_then(|v| v.as_u64()).map(|n| n as usize);
+            let http = self.env.source_client()?;
+            let questions =
+                crate::sources::stackexchange::search(&query, site, tagged, max_questions, max_answers, &http)
+                    .await?;
+            Ok(crate::sources::truncate(&crate::sources::stackexchange::to_markdown(&questions), MAX_SOURCE_CHARS))
+        })
+    }
+}
+
+struct YoutubeTranscriptTool {
+    env: ToolEnv,
+}
+
+impl Tool for YoutubeTranscriptTool {
+    fn name(&self) -> &str {
+        "youtube_transcript"
+    }
+
+    fn description(&self) -> &str {
+        "Get the timestamped transcript and details of a YouTube video, e.g. to summarize it"
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        ToolParameters::new()
+            .required("url", "string", "Video URL or id")
+            .optional("lang", "string", "Language code such as en or de; translated when the video has no captions in it")
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move {
+            let url = string_arg(&args, "url")?;
+            let lang = args.get("lang").and_then(|v| v.as_str());
+            let http = self.env.source_client()?;
+            let transcript = crate::sources::youtube::transcript(&url, lang, &http).await?;
+            Ok(crate::sources::truncate(&crate::sources::youtube::to_markdown(&transcript), MAX_SOURCE_CHARS))
+        })
+    }
+}
+
+struct CalculatorTool {
+    env: ToolEnv,
+}
+
+impl Tool for CalculatorTool {
+    fn name(&self) -> &str {
+        "calculator"
+    }
+
+    fn description(&self) -> &str {
+        "Evaluate arithmetic exactly instead of working it out: expressions with + - * / % ^ !, \
+         parentheses, sqrt, ln, log, sin, min, max, pi and e; unit conversions such as \"5 km to mi\" or \
+         \"72 F to C\"; and currency conversions at today's ECB rates such as \"100 USD to EUR\"."
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        ToolParameters::new().required("expression", "string", "Expression, or '<quantity> <unit> to <unit>'")
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move {
+            let expression = string_arg(&args, "expression")?;
+            let http = self.env.source_client()?;
+            let result = crate::calc::calculate(&expression, &http).await?;
+            Ok(match result.rates_date {
+                Some(date) => format!("{} (ECB rates of {})", result.text, date),
+                None => result.text,
+            })
+        })
+    }
+}
+
+struct WeatherTool {
+    env: ToolEnv,
+}
+
+impl Tool for WeatherTool {
+    fn name(&self) -> &str {
+        "get_weather"
+    }
+
+    fn description(&self) -> &str {
+        "Current weather and daily forecast for a place"
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        ToolParameters::new()
+            .required("location", "string", "Place name, e.g. Berlin or Paris, Texas, or 'latitude,longitude'")
+            .optional("days", "integer", "Days to forecast, 1 to 16 (default 3)")
+    }
+
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>> {
+        Box::pin(async move {
+            let location = string_arg(&args, "location")?;
+            let days = args.get("days").and_then(|v| v.as_u64()).map(|n| n as usize);
+            let http = self.env.source_client()?;
+            let weather = crate::sources::weather::forecast(&location, days, &http).await?;
+            Ok(crate::sources::weather::to_markdown(&weather))
+        })
+    }
+}
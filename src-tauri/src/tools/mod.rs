@@ -0,0 +1,158 @@
+// Tool registry shared by the agent loop and the frontend tool-calling payloads
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+use futures::future::BoxFuture;
+use serde_json::{json, Value};
+
+mod builtin;
+
+pub use builtin::register_app_tools;
+
+// JSON schema for a single tool parameter
+// Keywords we don't model (items, default, ...) are preserved in `extra`
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct ParameterSchema {
+    #[serde(rename = "type", default)]
+    pub kind: Value,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(rename = "enum", default, skip_serializing_if = "Option::is_none")]
+    pub allowed: Option<Vec<Value>>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+// JSON schema for a tool's arguments object
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct ToolParameters {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub properties: BTreeMap<String, ParameterSchema>,
+    #[serde(default)]
+    pub required: Vec<String>,
+}
+
+impl Default for ToolParameters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ToolParameters {
+    pub fn new() -> Self {
+        ToolParameters {
+            kind: "object".to_string(),
+            properties: BTreeMap::new(),
+            required: Vec::new(),
+        }
+    }
+
+    pub fn required(mut self, name: &str, kind: &str, description: &str) -> Self {
+        self.required.push(name.to_string());
+        self.optional(name, kind, description)
+    }
+
+    pub fn optional(mut self, name: &str, kind: &str, description: &str) -> Self {
+        self.properties.insert(
+            name.to_string(),
+            ParameterSchema {
+                kind: json!(kind),
+                description: description.to_string(),
+                allowed: None,
+                extra: serde_json::Map::new(),
+            },
+        );
+        self
+    }
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    pub parameters: ToolParameters,
+}
+
+impl ToolDefinition {
+    // OpenAI function-calling format (also accepted by Ollama)
+    pub fn to_function(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": self.name,
+                "description": self.description,
+                "parameters": self.parameters,
+            },
+        })
+    }
+}
+
+pub trait Tool: Send + Sync {
+    fn name(&self) -> &str;
+    fn description(&self) -> &str;
+    fn parameters(&self) -> ToolParameters;
+    fn execute(&self, args: Value) -> BoxFuture<'_, Result<String, String>>;
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: self.name().to_string(),
+            description: self.description().to_string(),
+            parameters: self.parameters(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ToolRegistry {
+    tools: RwLock<BTreeMap<String, Arc<dyn Tool>>>,
+}
+
+impl ToolRegistry {
+    pub fn with_builtin_tools() -> Self {
+        let registry = ToolRegistry::default();
+        builtin::register_all(&registry);
+        registry
+    }
+
+    pub fn register(&self, tool: Arc<dyn Tool>) {
+        let name = tool.name().to_string();
+        eprintln!("[Tools] Registered tool: {}", name);
+        self.tools.write().unwrap().insert(name, tool);
+    }
+
+    pub fn unregister(&self, name: &str) {
+        if self.tools.write().unwrap().remove(name).is_some() {
+            eprintln!("[Tools] Unregistered tool: {}", name);
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Tool>> {
+        self.tools.read().unwrap().get(name).cloned()
+    }
+
+    pub fn definitions(&self) -> Vec<ToolDefinition> {
+        self.tools.read().unwrap().values().map(|t| t.definition()).collect()
+    }
+
+    pub async fn execute(&self, name: &str, args: Value) -> Result<String, String> {
+        let tool = self.get(name).ok_or_else(|| format!("Unknown tool: {}", name))?;
+        tool.execute(args).await
+    }
+}
+
+// Helper for tools to read a required string argument
+pub fn string_arg(args: &Value, key: &str) -> Result<String, String> {
+    args.get(key)
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .ok_or_else(|| format!("Missing string argument '{}'", key))
+}
+
+// Returns tool schemas in the function-calling format used by provider payloads
+#[tauri::command]
+pub fn list_tools(registry: tauri::State<'_, ToolRegistry>) -> Vec<Value> {
+    registry.definitions().iter().map(|d| d.to_function()).collect()
+}
@@ -0,0 +1,322 @@
+// Local speech-to-text with whisper.cpp (through whisper-rs), built with the `whisper` feature.
+//
+// Audio is decoded with Symphonia (WAV, MP3, FLAC, Ogg Vorbis, AAC/M4A), mixed down to mono and
+// resampled to the 16 kHz whisper expects. Each segment is emitted as a `transcription-segment`
+// event as soon as it's decoded, so long recordings show text while they're still running. Models
+// are ggml files (e.g. ggml-base.en.bin) from the whisper.cpp project.
+#[cfg(feature = "whisper")]
+use std::path::PathBuf;
+#[cfg(feature = "whisper")]
+use std::sync::{Arc, Mutex};
+
+use tauri::AppHandle;
+#[cfg(feature = "whisper")]
+use tauri::{Emitter, Manager};
+
+use crate::settings;
+
+#[cfg(feature = "whisper")]
+const SEGMENT_EVENT: &str = "transcription-segment";
+
+#[cfg(feature = "whisper")]
+mod audio {
+    use std::io::Cursor;
+    use std::path::Path;
+
+    use symphonia::core::audio::SampleBuffer;
+    use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+    use symphonia::core::errors::Error;
+    use symphonia::core::formats::FormatOptions;
+    use symphonia::core::io::{MediaSource, MediaSourceStream};
+    use symphonia::core::meta::MetadataOptions;
+    use symphonia::core::probe::Hint;
+
+    pub const SAMPLE_RATE: u32 = 16_000;
+
+    fn audio_error(e: impl std::fmt::Display) -> String {
+        format!("Failed to decode audio: {}", e)
+    }
+
+    // Linear interpolation; plenty for speech going down to 16 kHz
+    fn resample(samples: &[f32], from: u32) -> Vec<f32> {
+        if from == SAMPLE_RATE || samples.is_empty() {
+            return samples.to_vec();
+        }
+        let step = from as f64 / SAMPLE_RATE as f64;
+        let length = (samples.len() as f64 / step) as usize;
+        (0..length)
+            .map(|i| {
+                let position = i as f64 * step;
+                let index = position as usize;
+                let fraction = (position - index as f64) as f32;
+                let next = samples.get(index + 1).copied().unwrap_or(samples[index]);
+                samples[index] + (next - samples[index]) * fraction
+            })
+            .collect()
+    }
+
+    // Mono 16 kHz samples of the first audio track
+    fn decode(source: Box<dyn MediaSource>, extension: Option<&str>) -> Result<Vec<f32>, String> {
+        let stream = MediaSourceStream::new(source, Default::default());
+        let mut hint = Hint::new();
+        if let Some(extension) = extension {
+            hint.with_extension(extension);
+        }
+        let probed = symphonia::default::get_probe()
+            .format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+            .map_err(|e| format!("Unsupported audio format: {}", e))?;
+        let mut format = probed.format;
+        let track = format
+            .tracks()
+            .iter()
+            .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+            .ok_or("No audio track found")?;
+        let track_id = track.id;
+        let rate = track.codec_params.sample_rate.ok_or("Audio track has no sample rate")?;
+        let mut decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())
+            .map_err(audio_error)?;
+
+        let mut mono = Vec::new();
+        loop {
+            let packet = match format.next_packet() {
+                Ok(packet) => packet,
+                Err(Error::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+                Err(Error::ResetRequired) => break,
+                Err(e) => return Err(audio_error(e)),
+            };
+            if packet.track_id() != track_id {
+                continue;
+            }
+            let decoded = match decoder.decode(&packet) {
+                Ok(decoded) => decoded,
+                // A corrupt packet costs a few milliseconds of audio, not the whole file
+                Err(Error::DecodeError(_)) => continue,
+                Err(e) => return Err(audio_error(e)),
+            };
+            let spec = *decoded.spec();
+            let channels = spec.channels.count().max(1);
+            let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+            buffer.copy_interleaved_ref(decoded);
+            mono.extend(buffer.samples().chunks(channels).map(|frame| frame.iter().sum::<f32>() / channels as f32));
+        }
+        if mono.is_empty() {
+            return Err("Recording contains no audio".to_string());
+        }
+        Ok(resample(&mono, rate))
+    }
+
+    pub fn load_file(path: &Path) -> Result<Vec<f32>, String> {
+        let file = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase());
+        decode(Box::new(file), extension.as_deref())
+    }
+
+    pub fn load_bytes(bytes: Vec<u8>) -> Result<Vec<f32>, String> {
+        decode(Box::new(Cursor::new(bytes)), None)
+    }
+}
+
+#[cfg(feature = "whisper")]
+mod engine {
+    use whisper_rs::{FullParams, SamplingStrategy, SegmentCallbackData, WhisperContext, WhisperContextParameters};
+
+    use super::TranscriptSegment;
+
+    fn model_error(e: impl std::fmt::Display) -> String {
+        format!("Transcription error: {}", e)
+    }
+
+    pub fn load(path: &std::path::Path) -> Result<WhisperContext, String> {
+        let path_str = path.to_str().ok_or("Model path is not valid UTF-8")?;
+        WhisperContext::new_with_params(path_str, WhisperContextParameters::default())
+            .map_err(|e| format!("Failed to load {}: {}", path.display(), e))
+    }
+
+    // whisper timestamps are in centiseconds
+    fn segment(index: i32, start: i64, end: i64, text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            index: index.max(0) as usize,
+            start_ms: start.max(0) as u64 * 10,
+            end_ms: end.max(0) as u64 * 10,
+            text: text.trim().to_string(),
+        }
+    }
+
+    // Segments of the whole recording and the spoken language; `on_segment` sees each segment
+    // as soon as whisper produces it
+    pub fn transcribe(
+        context: &WhisperContext,
+        samples: &[f32],
+        language: Option<&str>,
+        mut on_segment: impl FnMut(TranscriptSegment) + 'static,
+    ) -> Result<(Vec<TranscriptSegment>, Option<String>), String> {
+        let mut state = context.create_state().map_err(model_error)?;
+        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+        // "auto" lets whisper detect the language from the first 30 seconds
+        params.set_language(Some(language.unwrap_or("auto")));
+        params.set_n_threads(std::thread::available_parallelism().map(|n| n.get().min(8) as i32).unwrap_or(4));
+        params.set_print_special(false);
+        params.set_print_progress(false);
+        params.set_print_realtime(false);
+        params.set_print_timestamps(false);
+        params.set_segment_callback_safe_lossy(move |data: SegmentCallbackData| {
+            on_segment(segment(data.segment, data.start_timestamp, data.end_timestamp, &data.text))
+        });
+        state.full(params, samples).map_err(model_error)?;
+
+        let count = state.full_n_segments().map_err(model_error)?;
+        let mut segments = Vec::with_capacity(count.max(0) as usize);
+        for i in 0..count {
+            let text = state.full_get_segment_text_lossy(i).map_err(model_error)?;
+            let start = state.full_get_segment_t0(i).map_err(model_error)?;
+            let end = state.full_get_segment_t1(i).map_err(model_error)?;
+            segments.push(segment(i, start, end, &text));
+        }
+        let language = state
+            .full_lang_id_from_state()
+            .ok()
+            .and_then(whisper_rs::get_lang_str)
+            .map(str::to_string);
+        Ok((segments, language))
+    }
+}
+
+// The loaded model, kept until a different one is requested
+#[derive(Default)]
+pub struct TranscriberState {
+    #[cfg(feature = "whisper")]
+    loaded: Mutex<Option<(PathBuf, Arc<whisper_rs::WhisperContext>)>>,
+}
+
+// A recording given by path, or its bytes (e.g. from the webview's MediaRecorder)
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(not(feature = "whisper"), allow(dead_code))]
+pub enum AudioInput {
+    Path(String),
+    Bytes(Vec<u8>),
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptSegment {
+    index: usize,
+    start_ms: u64,
+    end_ms: u64,
+    text: String,
+}
+
+#[cfg(feature = "whisper")]
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SegmentEvent {
+    job_id: String,
+    #[serde(flatten)]
+    segment: TranscriptSegment,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transcript {
+    // Matches the `jobId` of the segment events
+    job_id: String,
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+    duration_ms: u64,
+    segments: Vec<TranscriptSegment>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriberStatus {
+    // Whether this build includes whisper.cpp
+    available: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
+}
+
+#[cfg(feature = "whisper")]
+fn context(app: &AppHandle, model: Option<String>) -> Result<Arc<whisper_rs::WhisperContext>, String> {
+    let path = model
+        .or_else(|| settings::load(app).whisper_model)
+        .filter(|m| !m.trim().is_empty())
+        .map(PathBuf::from)
+        .ok_or("No whisper model configured")?;
+    let state = app.state::<TranscriberState>();
+    let mut loaded = state.loaded.lock().map_err(|_| "Transcriber state poisoned".to_string())?;
+    match loaded.as_ref() {
+        Some((loaded_path, context)) if *loaded_path == path => Ok(context.clone()),
+        _ => {
+            let context = Arc::new(engine::load(&path)?);
+            eprintln!("[Transcribe] Loaded model from {}", path.display());
+            *loaded = Some((path, context.clone()));
+            Ok(context)
+        }
+    }
+}
+
+#[cfg(feature = "whisper")]
+fn transcribe(app: &AppHandle, audio: AudioInput, model: Option<String>, language: Option<String>) -> Result<Transcript, String> {
+    let samples = match audio {
+        AudioInput::Path(path) => audio::load_file(std::path::Path::new(&path))?,
+        AudioInput::Bytes(bytes) => audio::load_bytes(bytes)?,
+    };
+    let context = context(app, model)?;
+    let job_id = crate::db::new_id("transcript");
+    let language = language.map(|l| l.trim().to_lowercase()).filter(|l| !l.is_empty());
+
+    let (emitter, event_job) = (app.clone(), job_id.clone());
+    let started = std::time::Instant::now();
+    let (segments, detected) = engine::transcribe(&context, &samples, language.as_deref(), move |segment| {
+        let event = SegmentEvent {
+            job_id: event_job.clone(),
+            segment,
+        };
+        if let Err(e) = emitter.emit(SEGMENT_EVENT, event) {
+            eprintln!("[Transcribe] Failed to emit segment: {}", e);
+        }
+    })?;
+    let duration_ms = samples.len() as u64 * 1000 / audio::SAMPLE_RATE as u64;
+    eprintln!(
+        "[Transcribe] {} segments from {}s of audio in {}s",
+        segments.len(),
+        duration_ms / 1000,
+        started.elapsed().as_secs()
+    );
+    Ok(Transcript {
+        job_id,
+        text: segments.iter().map(|s| s.text.as_str()).filter(|t| !t.is_empty()).collect::<Vec<_>>().join(" "),
+        language: detected.or(language),
+        duration_ms,
+        segments,
+    })
+}
+
+#[cfg(not(feature = "whisper"))]
+fn transcribe(_app: &AppHandle, _audio: AudioInput, _model: Option<String>, _language: Option<String>) -> Result<Transcript, String> {
+    Err("Transcription is not available in this build".to_string())
+}
+
+// Transcribe a recording with the ggml model at `model` (the configured one when omitted).
+// `language` is an ISO code such as "en"; whisper detects it when omitted.
+#[tauri::command]
+pub async fn transcribe_audio(
+    app: AppHandle,
+    audio: AudioInput,
+    model: Option<String>,
+    language: Option<String>,
+) -> Result<Transcript, String> {
+    tokio::task::spawn_blocking(move || transcribe(&app, audio, model, language))
+        .await
+        .map_err(|e| format!("Transcription task failed: {}", e))?
+}
+
+#[tauri::command]
+pub fn get_transcriber_status(app: AppHandle) -> TranscriberStatus {
+    TranscriberStatus {
+        available: cfg!(feature = "whisper"),
+        model: settings::load(&app).whisper_model,
+    }
+}
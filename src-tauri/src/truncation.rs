@@ -0,0 +1,83 @@
+// Length limits for scraped content, to keep pages within the model's context budget. A page over
+// its limit keeps its beginning, cut at a paragraph or sentence, followed by the headings of the
+// sections that were cut, so the model still sees what the rest of the page covers.
+
+// As context::estimate_tokens counts
+const CHARS_PER_TOKEN: usize = 4;
+// Share of the limit the headings of cut sections may take: a quarter
+const OUTLINE_SHARE: usize = 4;
+
+// Limits given with a scrape; the tighter one applies
+#[derive(serde::Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentLimits {
+    pub max_chars: Option<usize>,
+    pub max_tokens: Option<usize>,
+}
+
+impl ContentLimits {
+    pub fn max_chars(&self) -> Option<usize> {
+        let from_tokens = self.max_tokens.map(|tokens| tokens.saturating_mul(CHARS_PER_TOKEN));
+        match (self.max_chars, from_tokens) {
+            (Some(chars), Some(tokens)) => Some(chars.min(tokens)),
+            (chars, tokens) => chars.or(tokens),
+        }
+    }
+}
+
+// A Markdown heading line (# to ######)
+fn is_heading(line: &str) -> bool {
+    let line = line.trim_start();
+    let level = line.chars().take_while(|&c| c == '#').count();
+    (1..=6).contains(&level) && line[level..].starts_with(' ')
+}
+
+// Byte index at most `max_chars` characters into `text`, moved back to the end of a paragraph,
+// line, sentence or word when there is one in the last quarter
+fn cut_point(text: &str, max_chars: usize) -> usize {
+    let limit = text.char_indices().nth(max_chars).map(|(i, _)| i).unwrap_or(text.len());
+    let head = &text[..limit];
+    let floor = limit * 3 / 4;
+    for boundary in ["\n\n", "\n", ". "] {
+        if let Some(i) = head.rfind(boundary).filter(|&i| i >= floor) {
+            // Sentences keep their full stop
+            return i + boundary.trim_end().len();
+        }
+    }
+    head.rfind(char::is_whitespace).filter(|&i| i >= floor).unwrap_or(limit)
+}
+
+// `text` cut to at most `max_chars` characters, or None when it fits
+pub fn truncate(text: &str, max_chars: usize) -> Option<String> {
+    let total = text.chars().count();
+    if total <= max_chars {
+        return None;
+    }
+    let has_headings = text[cut_point(text, max_chars)..].lines().any(is_heading);
+    if !has_headings {
+        let note = format!("\n\n[Truncated from {} characters]", total);
+        let head = &text[..cut_point(text, max_chars.saturating_sub(note.chars().count()))];
+        return Some(format!("{}{}", head.trim_end(), note));
+    }
+
+    // Room for the outline is kept first, then filled with the headings after the cut
+    let outline_budget = max_chars / OUTLINE_SHARE;
+    let mut head = text[..cut_point(text, max_chars - outline_budget)].trim_end();
+    // A heading left without its section goes to the outline
+    loop {
+        let last_line = head.rfind('\n').map_or(0, |i| i + 1);
+        if !is_heading(&head[last_line..]) {
+            break;
+        }
+        head = head[..last_line].trim_end();
+    }
+    let mut note = format!("\n\n[Truncated from {} characters; the sections cut were:]", total);
+    for heading in text[head.len()..].lines().filter(|line| is_heading(line)) {
+        let line = format!("\n{}", heading.trim());
+        if note.chars().count() + line.chars().count() > outline_budget {
+            break;
+        }
+        note.push_str(&line);
+    }
+    Some(format!("{}{}", head.trim_end(), note))
+}
@@ -0,0 +1,636 @@
+// Vector helpers shared by the embedding-backed stores, and the vector store: named collections of
+// embeddings with optional text and JSON metadata, kept in the main (possibly encrypted) database.
+// Queries are an exact scan of the collection, filtered on metadata first.
+use std::cmp::Ordering;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, State};
+
+use crate::db::Database;
+use crate::llm::{self, ProviderConfig};
+use crate::profiles::ProfileManager;
+use crate::rerank;
+use crate::settings;
+
+const DEFAULT_TOP_K: usize = 10;
+// Rank offset of reciprocal-rank fusion; 60 is the value from the original paper
+pub const RRF_K: f32 = 60.0;
+const HYBRID_MIN_CANDIDATES: usize = 50;
+// Fused results handed to the reranker per result returned
+const RERANK_POOL: usize = 3;
+
+pub fn to_blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+pub fn from_blob(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let mut dot = 0.0;
+    let mut norm_a = 0.0;
+    let mut norm_b = 0.0;
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Metric {
+    #[default]
+    Cosine,
+    Dot,
+    Euclidean,
+}
+
+impl Metric {
+    fn as_str(self) -> &'static str {
+        match self {
+            Metric::Cosine => "cosine",
+            Metric::Dot => "dot",
+            Metric::Euclidean => "euclidean",
+        }
+    }
+
+    pub fn parse(value: &str) -> Metric {
+        match value {
+            "dot" => Metric::Dot,
+            "euclidean" => Metric::Euclidean,
+            _ => Metric::Cosine,
+        }
+    }
+
+    // Higher is more similar for every metric; euclidean distance is negated
+    fn score(self, a: &[f32], b: &[f32]) -> f32 {
+        match self {
+            Metric::Cosine => cosine_similarity(a, b),
+            Metric::Dot => a.iter().zip(b).map(|(x, y)| x * y).sum(),
+            Metric::Euclidean => -a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum::<f32>().sqrt(),
+        }
+    }
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VectorCollection {
+    pub name: String,
+    pub dimensions: usize,
+    pub metric: Metric,
+    // Model the embeddings came from, so queries can be embedded the same way
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedding_model: Option<String>,
+    pub created_at: String,
+    pub count: usize,
+}
+
+#[derive(serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VectorItem {
+    pub id: String,
+    pub embedding: Vec<f32>,
+    pub content: Option<String>,
+    pub metadata: Option<Value>,
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VectorMatch {
+    pub id: String,
+    pub score: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Value>,
+}
+
+#[derive(serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct VectorQuery {
+    pub top_k: Option<usize>,
+    // Matches scoring below this are dropped
+    pub min_score: Option<f32>,
+    // Metadata conditions, all of which must hold: {"field": value} for equality, or
+    // {"field": {"$op": value}} with $eq, $ne, $in, $nin, $gt, $gte, $lt, $lte, $exists
+    pub filter: Option<Map<String, Value>>,
+}
+
+fn get_collection(conn: &Connection, name: &str) -> rusqlite::Result<Option<VectorCollection>> {
+    conn.query_row(
+        "SELECT c.name, c.dimensions, c.metric, c.embedding_model, c.created_at,
+                (SELECT COUNT(*) FROM vector_items i WHERE i.collection = c.name)
+         FROM vector_collections c WHERE c.name = ?1",
+        params![name],
+        |row| {
+            Ok(VectorCollection {
+                name: row.get(0)?,
+                dimensions: row.get::<_, i64>(1)? as usize,
+                metric: Metric::parse(&row.get::<_, String>(2)?),
+                embedding_model: row.get(3)?,
+                created_at: row.get(4)?,
+                count: row.get::<_, i64>(5)? as usize,
+            })
+        },
+    )
+    .optional()
+}
+
+pub fn collection(db: &Database, name: &str) -> Result<VectorCollection, String> {
+    db.with_conn(|conn| get_collection(conn, name))?
+        .ok_or_else(|| format!("Vector collection '{}' not found", name))
+}
+
+fn check_dimensions(collection: &VectorCollection, vector: &[f32], what: &str) -> Result<(), String> {
+    if vector.len() != collection.dimensions {
+        return Err(format!(
+            "{} has {} dimensions, collection '{}' expects {}",
+            what,
+            vector.len(),
+            collection.name,
+            collection.dimensions
+        ));
+    }
+    Ok(())
+}
+
+// Create the collection, or return it unchanged when one with the same shape already exists
+pub fn create_collection(
+    db: &Database,
+    name: &str,
+    dimensions: usize,
+    metric: Metric,
+    embedding_model: Option<&str>,
+) -> Result<VectorCollection, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Collection name cannot be empty".to_string());
+    }
+    if dimensions == 0 {
+        return Err("Dimensions must be greater than zero".to_string());
+    }
+    if let Some(existing) = db.with_conn(|conn| get_collection(conn, name))? {
+        if existing.dimensions != dimensions || existing.metric != metric {
+            return Err(format!(
+                "Vector collection '{}' already exists with {} dimensions ({})",
+                name,
+                existing.dimensions,
+                existing.metric.as_str()
+            ));
+        }
+        return Ok(existing);
+    }
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO vector_collections (name, dimensions, metric, embedding_model, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![name, dimensions as i64, metric.as_str(), embedding_model, chrono::Utc::now().to_rfc3339()],
+        )
+    })?;
+    eprintln!("[Vector] Created collection {} ({} dimensions, {})", name, dimensions, metric.as_str());
+    collection(db, name)
+}
+
+// Insert or replace items by id; returns how many were written
+pub fn upsert(db: &Database, name: &str, items: &[VectorItem]) -> Result<usize, String> {
+    let collection = collection(db, name)?;
+    for item in items {
+        check_dimensions(&collection, &item.embedding, &format!("Item '{}'", item.id))?;
+    }
+    let now = chrono::Utc::now().to_rfc3339();
+    db.with_conn(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO vector_items (collection, id, embedding, content, metadata, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            for item in items {
+                stmt.execute(params![
+                    collection.name,
+                    item.id,
+                    to_blob(&item.embedding),
+                    item.content,
+                    item.metadata.as_ref().map(|m| m.to_string()),
+                    now
+                ])?;
+            }
+        }
+        tx.commit()
+    })?;
+    Ok(items.len())
+}
+
+pub fn delete_items(db: &Database, name: &str, ids: &[String]) -> Result<usize, String> {
+    let collection = collection(db, name)?;
+    db.with_conn(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        let mut deleted = 0;
+        for id in ids {
+            deleted += tx.execute(
+                "DELETE FROM vector_items WHERE collection = ?1 AND id = ?2",
+                params![collection.name, id],
+            )?;
+        }
+        tx.commit()?;
+        Ok(deleted)
+    })
+}
+
+// Remove every item whose metadata matches the filter; returns how many were removed
+pub fn delete_matching(db: &Database, name: &str, filter: &Map<String, Value>) -> Result<usize, String> {
+    let collection = collection(db, name)?;
+    validate_filter(filter)?;
+    db.with_conn(|conn| {
+        let ids: Vec<String> = {
+            let mut stmt = conn.prepare("SELECT id, metadata FROM vector_items WHERE collection = ?1")?;
+            let rows = stmt
+                .query_map(params![collection.name], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            rows.into_iter()
+                .filter(|(_, metadata)| {
+                    let metadata = metadata.as_deref().and_then(|m| serde_json::from_str(m).ok());
+                    matches_filter(metadata.as_ref().unwrap_or(&Value::Null), filter)
+                })
+                .map(|(id, _)| id)
+                .collect()
+        };
+        let tx = conn.unchecked_transaction()?;
+        for id in &ids {
+            tx.execute("DELETE FROM vector_items WHERE collection = ?1 AND id = ?2", params![collection.name, id])?;
+        }
+        tx.commit()?;
+        Ok(ids.len())
+    })
+}
+
+const FILTER_OPERATORS: &[&str] = &["$eq", "$ne", "$in", "$nin", "$gt", "$gte", "$lt", "$lte", "$exists"];
+
+// Condition objects are the ones whose keys are all operators; anything else is compared for equality
+fn operators(condition: &Value) -> Option<&Map<String, Value>> {
+    condition
+        .as_object()
+        .filter(|c| !c.is_empty() && c.keys().all(|k| k.starts_with('$')))
+}
+
+fn validate_filter(filter: &Map<String, Value>) -> Result<(), String> {
+    for (op, expected) in filter.values().filter_map(operators).flatten() {
+        if !FILTER_OPERATORS.contains(&op.as_str()) {
+            return Err(format!("Unknown filter operator: {}", op));
+        }
+        if matches!(op.as_str(), "$in" | "$nin") && !expected.is_array() {
+            return Err(format!("{} expects a list", op));
+        }
+    }
+    Ok(())
+}
+
+fn compare(actual: Option<&Value>, op: &str, expected: &Value) -> bool {
+    // Numbers compare numerically, strings lexically (which also orders RFC 3339 dates)
+    let ordered = |accept: fn(Ordering) -> bool| {
+        let ordering = match (actual, expected) {
+            (Some(Value::Number(a)), Value::Number(b)) => a.as_f64().zip(b.as_f64()).and_then(|(a, b)| a.partial_cmp(&b)),
+            (Some(Value::String(a)), Value::String(b)) => Some(a.cmp(b)),
+            _ => None,
+        };
+        ordering.is_some_and(accept)
+    };
+    let listed = || actual.is_some_and(|a| expected.as_array().is_some_and(|list| list.contains(a)));
+    match op {
+        "$eq" => actual == Some(expected),
+        "$ne" => actual != Some(expected),
+        "$in" => listed(),
+        "$nin" => !listed(),
+        "$gt" => ordered(Ordering::is_gt),
+        "$gte" => ordered(Ordering::is_ge),
+        "$lt" => ordered(Ordering::is_lt),
+        "$lte" => ordered(Ordering::is_le),
+        "$exists" => actual.is_some() == expected.as_bool().unwrap_or(true),
+        _ => false,
+    }
+}
+
+// Metadata fields may be nested with dots ("source.path")
+fn field<'a>(metadata: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(metadata, |value, key| value.get(key))
+}
+
+// Filters must have passed validate_filter
+pub fn matches_filter(metadata: &Value, filter: &Map<String, Value>) -> bool {
+    filter.iter().all(|(path, condition)| {
+        let actual = field(metadata, path);
+        match operators(condition) {
+            Some(operators) => operators.iter().all(|(op, expected)| compare(actual, op, expected)),
+            None => actual == Some(condition),
+        }
+    })
+}
+
+// Top-k items most similar to `vector`, best first
+pub fn query(db: &Database, name: &str, vector: &[f32], options: &VectorQuery) -> Result<Vec<VectorMatch>, String> {
+    let collection = collection(db, name)?;
+    check_dimensions(&collection, vector, "Query vector")?;
+    if let Some(filter) = &options.filter {
+        validate_filter(filter)?;
+    }
+
+    // Candidates carry their embedding until scored
+    let candidates: Vec<(VectorMatch, Vec<u8>)> = db.with_conn(|conn| {
+        let mut stmt = conn.prepare("SELECT id, embedding, content, metadata FROM vector_items WHERE collection = ?1")?;
+        let rows = stmt.query_map(params![collection.name], |row| {
+            let metadata: Option<String> = row.get(3)?;
+            Ok((
+                VectorMatch {
+                    id: row.get(0)?,
+                    score: 0.0,
+                    content: row.get(2)?,
+                    metadata: metadata.and_then(|m| serde_json::from_str(&m).ok()),
+                },
+                row.get(1)?,
+            ))
+        })?;
+        rows.collect()
+    })?;
+
+    let mut matches = Vec::new();
+    for (mut candidate, blob) in candidates {
+        if let Some(filter) = &options.filter {
+            if !matches_filter(candidate.metadata.as_ref().unwrap_or(&Value::Null), filter) {
+                continue;
+            }
+        }
+        candidate.score = collection.metric.score(vector, &from_blob(&blob));
+        if options.min_score.is_some_and(|min| candidate.score < min) {
+            continue;
+        }
+        matches.push(candidate);
+    }
+    matches.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+    matches.truncate(options.top_k.unwrap_or(DEFAULT_TOP_K));
+    Ok(matches)
+}
+
+// FTS5 query matching any of the words in `text`, each quoted so punctuation and operators in
+// the text are taken literally
+fn keyword_expression(text: &str) -> Option<String> {
+    let terms: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|t| !t.is_empty())
+        .map(|t| format!("\"{}\"", t))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" OR "))
+}
+
+// Top-k items for the words in `text` by BM25, best first; scores are positive, higher is better
+pub fn keyword_query(db: &Database, name: &str, text: &str, options: &VectorQuery) -> Result<Vec<VectorMatch>, String> {
+    let collection = collection(db, name)?;
+    if let Some(filter) = &options.filter {
+        validate_filter(filter)?;
+    }
+    let Some(expression) = keyword_expression(text) else {
+        return Ok(Vec::new());
+    };
+    let top_k = options.top_k.unwrap_or(DEFAULT_TOP_K);
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT i.id, -bm25(vector_fts), i.content, i.metadata
+             FROM vector_fts
+             JOIN vector_fts_keys k ON k.key = vector_fts.rowid
+             JOIN vector_items i ON i.collection = k.collection AND i.id = k.id
+             WHERE vector_fts MATCH ?1 AND k.collection = ?2
+             ORDER BY bm25(vector_fts)",
+        )?;
+        let mut rows = stmt.query(params![expression, collection.name])?;
+        let mut matches = Vec::new();
+        // Rows come best first, so reading stops once enough pass the filter
+        while let Some(row) = rows.next()? {
+            let metadata: Option<Value> = row.get::<_, Option<String>>(3)?.and_then(|m| serde_json::from_str(&m).ok());
+            if let Some(filter) = &options.filter {
+                if !matches_filter(metadata.as_ref().unwrap_or(&Value::Null), filter) {
+                    continue;
+                }
+            }
+            let score = row.get::<_, f64>(1)? as f32;
+            if options.min_score.is_some_and(|min| score < min) {
+                break;
+            }
+            matches.push(VectorMatch {
+                id: row.get(0)?,
+                score,
+                content: row.get(2)?,
+                metadata,
+            });
+            if matches.len() >= top_k {
+                break;
+            }
+        }
+        Ok(matches)
+    })
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HybridMatch {
+    // `score` is the fused reciprocal-rank score
+    #[serde(flatten)]
+    pub item: VectorMatch,
+    // Positions in the vector and keyword rankings, from 1, when the item was in them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vector_rank: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keyword_rank: Option<usize>,
+    // Cross-encoder relevance (0-1) when the results were reranked; the order then follows it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rerank_score: Option<f32>,
+}
+
+// Merge the vector and keyword rankings with reciprocal-rank fusion: each list adds
+// 1 / (RRF_K + rank) for every item it contains. `min_score` applies to the vector ranking only.
+pub fn hybrid_search(
+    db: &Database,
+    name: &str,
+    text: &str,
+    vector: &[f32],
+    options: &VectorQuery,
+) -> Result<Vec<HybridMatch>, String> {
+    let top_k = options.top_k.unwrap_or(DEFAULT_TOP_K);
+    // Each ranking contributes more candidates than are returned so fusion has room to reorder
+    let candidates = VectorQuery {
+        top_k: Some((top_k * 4).max(HYBRID_MIN_CANDIDATES)),
+        min_score: options.min_score,
+        filter: options.filter.clone(),
+    };
+    let by_vector = query(db, name, vector, &candidates)?;
+    let by_keyword = keyword_query(
+        db,
+        name,
+        text,
+        &VectorQuery {
+            min_score: None,
+            ..candidates
+        },
+    )?;
+
+    let mut fused: Vec<HybridMatch> = Vec::new();
+    for (ranking, is_vector) in [(by_vector, true), (by_keyword, false)] {
+        for (index, item) in ranking.into_iter().enumerate() {
+            let rank = index + 1;
+            let contribution = 1.0 / (RRF_K + rank as f32);
+            let entry = match fused.iter_mut().position(|m| m.item.id == item.id) {
+                Some(position) => &mut fused[position],
+                None => {
+                    fused.push(HybridMatch {
+                        item: VectorMatch { score: 0.0, ..item },
+                        vector_rank: None,
+                        keyword_rank: None,
+                        rerank_score: None,
+                    });
+                    fused.last_mut().expect("just pushed")
+                }
+            };
+            entry.item.score += contribution;
+            if is_vector {
+                entry.vector_rank = Some(rank);
+            } else {
+                entry.keyword_rank = Some(rank);
+            }
+        }
+    }
+    fused.sort_by(|a, b| b.item.score.partial_cmp(&a.item.score).unwrap_or(Ordering::Equal));
+    fused.truncate(top_k);
+    Ok(fused)
+}
+
+#[tauri::command]
+pub fn create_vector_collection(
+    db: State<'_, Database>,
+    name: String,
+    dimensions: usize,
+    metric: Option<Metric>,
+    embedding_model: Option<String>,
+) -> Result<VectorCollection, String> {
+    create_collection(&db, &name, dimensions, metric.unwrap_or_default(), embedding_model.as_deref())
+}
+
+#[tauri::command]
+pub fn list_vector_collections(db: State<'_, Database>) -> Result<Vec<VectorCollection>, String> {
+    let names: Vec<String> = db.with_conn(|conn| {
+        let mut stmt = conn.prepare("SELECT name FROM vector_collections ORDER BY name")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect()
+    })?;
+    names.iter().map(|name| collection(&db, name)).collect()
+}
+
+// Removes the collection and every item in it
+#[tauri::command]
+pub fn delete_vector_collection(db: State<'_, Database>, name: String) -> Result<(), String> {
+    let deleted = db.with_conn(|conn| conn.execute("DELETE FROM vector_collections WHERE name = ?1", params![name]))?;
+    if deleted == 0 {
+        return Err(format!("Vector collection '{}' not found", name));
+    }
+    eprintln!("[Vector] Deleted collection {}", name);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn upsert_vectors(db: State<'_, Database>, collection: String, items: Vec<VectorItem>) -> Result<usize, String> {
+    upsert(&db, &collection, &items)
+}
+
+#[tauri::command]
+pub fn delete_vectors(db: State<'_, Database>, collection: String, ids: Vec<String>) -> Result<usize, String> {
+    delete_items(&db, &collection, &ids)
+}
+
+#[tauri::command]
+pub fn query_vectors(
+    db: State<'_, Database>,
+    collection: String,
+    vector: Vec<f32>,
+    options: Option<VectorQuery>,
+) -> Result<Vec<VectorMatch>, String> {
+    query(&db, &collection, &vector, &options.unwrap_or_default())
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HybridOptions {
+    // Must be the model the collection was built with
+    pub embedding: ProviderConfig,
+    pub filter: Option<Map<String, Value>>,
+}
+
+// Hybrid search for a text query, embedding it with `embedding` (credentials already applied).
+// With reranking enabled, a larger candidate pool is reordered by the cross-encoder before the
+// top k are returned.
+pub async fn retrieve(
+    app: &AppHandle,
+    db: &Database,
+    collection: &str,
+    query: &str,
+    top_k: usize,
+    embedding: &ProviderConfig,
+    filter: Option<Map<String, Value>>,
+) -> Result<Vec<HybridMatch>, String> {
+    let target = self::collection(db, collection)?;
+    if let Some(model) = target.embedding_model.as_deref().filter(|m| *m != embedding.model) {
+        return Err(format!("Collection '{}' was embedded with {}, not {}", collection, model, embedding.model));
+    }
+    let vector = llm::embed(embedding, &[query.to_string()])
+        .await?
+        .pop()
+        .ok_or("Embedder returned no vector for the query")?;
+    let reranking = settings::load(app).rerank_enabled;
+    let options = VectorQuery {
+        top_k: Some(if reranking { top_k * RERANK_POOL } else { top_k }),
+        min_score: None,
+        filter,
+    };
+    let mut matches = hybrid_search(db, collection, query, &vector, &options)?;
+    if reranking {
+        let (app, query) = (app.clone(), query.to_string());
+        matches = tokio::task::spawn_blocking(move || {
+            if let Some(scores) = rerank::rerank_items(&app, &query, &mut matches, |m| m.item.content.as_deref().unwrap_or("")) {
+                for (m, score) in matches.iter_mut().zip(scores) {
+                    m.rerank_score = Some(score);
+                }
+            }
+            matches
+        })
+        .await
+        .map_err(|e| format!("Rerank task failed: {}", e))?;
+    }
+    matches.truncate(top_k);
+    Ok(matches)
+}
+
+// Search a collection by meaning and by exact words at once
+#[tauri::command]
+pub async fn hybrid_query(
+    app: AppHandle,
+    db: State<'_, Database>,
+    profiles: State<'_, ProfileManager>,
+    collection: String,
+    query: String,
+    k: Option<usize>,
+    options: HybridOptions,
+) -> Result<Vec<HybridMatch>, String> {
+    let HybridOptions { mut embedding, filter } = options;
+    profiles.apply_credentials(&mut embedding);
+    retrieve(&app, &db, &collection, &query, k.unwrap_or(DEFAULT_TOP_K), &embedding, filter).await
+}
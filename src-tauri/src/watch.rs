@@ -0,0 +1,449 @@
+// Watched folders: directories kept in sync with a vector collection.
+//
+// A background task rescans every watched folder periodically. Files are compared by modification
+// time and size with what was indexed last: new and changed files are (re)ingested, and the chunks
+// of deleted files are removed. Progress is broadcast as `watch-status` events.
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::db::{self, Database};
+use crate::ingest::{self, IngestOptions};
+use crate::ocr;
+use crate::profiles::ProfileManager;
+use crate::vector;
+
+const STATUS_EVENT: &str = "watch-status";
+const SCAN_TICK: Duration = Duration::from_secs(30);
+// Directories never descended into, besides hidden ones
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "__pycache__", "venv"];
+
+#[derive(Default)]
+pub struct WatchEngine {
+    scanning: tokio::sync::Mutex<()>,
+}
+
+impl WatchEngine {
+    // Wait for a running scan and hold off new ones, while a collection is rewritten
+    pub async fn hold(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.scanning.lock().await
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchedSource {
+    id: String,
+    path: String,
+    collection: String,
+    recursive: bool,
+    files: usize,
+    chunks: usize,
+    // Files whose last ingestion failed
+    failed: usize,
+    created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_scan: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_error: Option<String>,
+}
+
+#[derive(serde::Serialize, Default, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanReport {
+    added: usize,
+    updated: usize,
+    removed: usize,
+    failed: usize,
+    // Chunks skipped as duplicates, and stored but flagged as near duplicates
+    duplicates: usize,
+    near_duplicates: usize,
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct WatchStatus {
+    source_id: String,
+    // scanning, indexing, removed, idle or failed
+    state: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    report: Option<ScanReport>,
+}
+
+struct Source {
+    id: String,
+    path: String,
+    collection: String,
+    options: String,
+    recursive: bool,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct FileStamp {
+    pub modified_ms: i64,
+    pub size: i64,
+}
+
+impl FileStamp {
+    pub fn of(meta: &std::fs::Metadata) -> Self {
+        FileStamp {
+            modified_ms: meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as i64)
+                .unwrap_or(0),
+            size: meta.len() as i64,
+        }
+    }
+}
+
+fn emit_status(app: &AppHandle, source_id: &str, state: &'static str, path: Option<&str>, error: Option<String>, report: Option<ScanReport>) {
+    let status = WatchStatus {
+        source_id: source_id.to_string(),
+        state,
+        path: path.map(str::to_string),
+        error,
+        report,
+    };
+    if let Err(e) = app.emit(STATUS_EVENT, status) {
+        eprintln!("[Watch] Failed to emit status: {}", e);
+    }
+}
+
+fn is_skipped_dir(path: &Path) -> bool {
+    path.file_name()
+        .map(|n| n.to_string_lossy())
+        .is_some_and(|name| name.starts_with('.') || SKIPPED_DIRS.contains(&name.as_ref()))
+}
+
+// Supported files under `dir` with their modification time and size
+pub fn scan_folder(dir: &Path, recursive: bool, files: &mut HashMap<String, FileStamp>) -> Result<(), String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let Ok(meta) = entry.metadata() else { continue };
+        if meta.is_dir() {
+            if recursive && !is_skipped_dir(&path) {
+                // An unreadable subfolder shouldn't hide the rest of the tree
+                if let Err(err) = scan_folder(&path, recursive, files) {
+                    eprintln!("[Watch] {}", err);
+                }
+            }
+            continue;
+        }
+        let hidden = path.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.'));
+        if !meta.is_file() || hidden || !ingest::is_supported(&path) {
+            continue;
+        }
+        files.insert(path.to_string_lossy().to_string(), FileStamp::of(&meta));
+    }
+    Ok(())
+}
+
+const SELECT_SOURCE: &str = "SELECT id, path, collection, options, recursive FROM watched_sources";
+
+fn source_from_row(row: &rusqlite::Row) -> rusqlite::Result<Source> {
+    Ok(Source {
+        id: row.get(0)?,
+        path: row.get(1)?,
+        collection: row.get(2)?,
+        options: row.get(3)?,
+        recursive: row.get::<_, i64>(4)? != 0,
+    })
+}
+
+fn load_sources(db: &Database) -> Result<Vec<Source>, String> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(&format!("{} ORDER BY created_at", SELECT_SOURCE))?;
+        let rows = stmt.query_map([], source_from_row)?;
+        rows.collect()
+    })
+}
+
+fn load_source(db: &Database, id: &str) -> Result<Source, String> {
+    db.with_conn(|conn| {
+        conn.query_row(&format!("{} WHERE id = ?1", SELECT_SOURCE), params![id], source_from_row)
+            .optional()
+    })?
+    .ok_or_else(|| format!("Watched source {} not found", id))
+}
+
+fn get_watched_source(conn: &Connection, id: &str) -> rusqlite::Result<WatchedSource> {
+    conn.query_row(
+        "SELECT s.id, s.path, s.collection, s.recursive, s.created_at, s.last_scan, s.last_error,
+                COUNT(f.path), COALESCE(SUM(f.chunks), 0), COUNT(f.error)
+         FROM watched_sources s LEFT JOIN watched_files f ON f.source_id = s.id
+         WHERE s.id = ?1
+         GROUP BY s.id",
+        params![id],
+        |row| {
+            Ok(WatchedSource {
+                id: row.get(0)?,
+                path: row.get(1)?,
+                collection: row.get(2)?,
+                recursive: row.get::<_, i64>(3)? != 0,
+                created_at: row.get(4)?,
+                last_scan: row.get(5)?,
+                last_error: row.get(6)?,
+                files: row.get::<_, i64>(7)? as usize,
+                chunks: row.get::<_, i64>(8)? as usize,
+                failed: row.get::<_, i64>(9)? as usize,
+            })
+        },
+    )
+}
+
+// Remove the chunks a file contributed to the collection
+fn remove_file_chunks(db: &Database, collection: &str, path: &str) -> Result<(), String> {
+    // Nothing was stored yet when every ingestion so far failed
+    if vector::collection(db, collection).is_err() {
+        return Ok(());
+    }
+    // Or a later ingest_documents would take the file as indexed still
+    db.with_conn(|conn| {
+        conn.execute("DELETE FROM ingested_files WHERE collection = ?1 AND path = ?2", params![collection, path])
+    })?;
+    let mut filter = Map::new();
+    filter.insert("source".to_string(), Value::String(path.to_string()));
+    vector::delete_matching(db, collection, &filter).map(|_| ())
+}
+
+async fn scan_source(app: &AppHandle, source: &Source) -> Result<ScanReport, String> {
+    let db = app.state::<Database>();
+    let mut options: IngestOptions =
+        serde_json::from_str(&source.options).map_err(|e| format!("Invalid ingestion options: {}", e))?;
+    app.state::<ProfileManager>().apply_credentials(&mut options.embedding);
+    let ocr = ocr::config(app, options.ocr_languages.as_deref())?;
+
+    let root = PathBuf::from(&source.path);
+    if !root.is_dir() {
+        return Err(format!("Folder {} is not available", source.path));
+    }
+    let recursive = source.recursive;
+    let current = tokio::task::spawn_blocking(move || {
+        let mut files = HashMap::new();
+        scan_folder(&root, recursive, &mut files).map(|_| files)
+    })
+    .await
+    .map_err(|e| format!("Folder scan failed: {}", e))??;
+
+    let known: HashMap<String, FileStamp> = db.with_conn(|conn| {
+        let mut stmt = conn.prepare("SELECT path, modified_ms, size FROM watched_files WHERE source_id = ?1")?;
+        let rows = stmt.query_map(params![source.id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                FileStamp {
+                    modified_ms: row.get(1)?,
+                    size: row.get(2)?,
+                },
+            ))
+        })?;
+        rows.collect()
+    })?;
+
+    let mut report = ScanReport::default();
+    for path in known.keys().filter(|path| !current.contains_key(*path)) {
+        remove_file_chunks(&db, &source.collection, path)?;
+        db.with_conn(|conn| {
+            conn.execute(
+                "DELETE FROM watched_files WHERE source_id = ?1 AND path = ?2",
+                params![source.id, path],
+            )
+        })?;
+        emit_status(app, &source.id, "removed", Some(path), None, None);
+        report.removed += 1;
+    }
+
+    let mut changed: Vec<(&String, &FileStamp)> = current
+        .iter()
+        .filter(|(path, stamp)| known.get(*path) != Some(*stamp))
+        .collect();
+    changed.sort_by(|a, b| a.0.cmp(b.0));
+    for (path, stamp) in changed {
+        emit_status(app, &source.id, "indexing", Some(path), None, None);
+        let result = ingest::ingest_file(&db, &source.collection, Path::new(path), &options, ocr.as_ref(), |_| {}).await;
+        let (chunks, error) = match result {
+            Ok(stats) => {
+                report.duplicates += stats.duplicates;
+                report.near_duplicates += stats.near_duplicates;
+                (stats.stored, None)
+            }
+            Err(err) => {
+                eprintln!("[Watch] Failed to index {}: {}", path, err);
+                emit_status(app, &source.id, "failed", Some(path), Some(err.clone()), None);
+                report.failed += 1;
+                (0, Some(err))
+            }
+        };
+        if known.contains_key(path) {
+            report.updated += 1;
+        } else {
+            report.added += 1;
+        }
+        db.with_conn(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO watched_files (source_id, path, modified_ms, size, chunks, indexed_at, error)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    source.id,
+                    path,
+                    stamp.modified_ms,
+                    stamp.size,
+                    chunks as i64,
+                    chrono::Utc::now().to_rfc3339(),
+                    error
+                ],
+            )
+        })?;
+    }
+    Ok(report)
+}
+
+// Scan one source and record the outcome on it
+async fn run_scan(app: &AppHandle, source: &Source) -> Result<ScanReport, String> {
+    emit_status(app, &source.id, "scanning", None, None, None);
+    let result = scan_source(app, source).await;
+    let db = app.state::<Database>();
+    let error = result.as_ref().err().cloned();
+    db.with_conn(|conn| {
+        conn.execute(
+            "UPDATE watched_sources SET last_scan = ?1, last_error = ?2 WHERE id = ?3",
+            params![chrono::Utc::now().to_rfc3339(), error, source.id],
+        )
+    })?;
+    match &result {
+        Ok(report) => {
+            if report.added + report.updated + report.removed > 0 {
+                eprintln!(
+                    "[Watch] {}: {} added, {} updated, {} removed, {} failed",
+                    source.path, report.added, report.updated, report.removed, report.failed
+                );
+            }
+            emit_status(app, &source.id, "idle", None, None, Some(*report));
+        }
+        Err(err) => emit_status(app, &source.id, "failed", None, Some(err.clone()), None),
+    }
+    result
+}
+
+// Background loop: rescans every watched folder; a tick is skipped while a scan is running
+pub fn start_background(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(SCAN_TICK).await;
+            let engine = app.state::<WatchEngine>();
+            let Ok(_guard) = engine.scanning.try_lock() else { continue };
+            let Ok(sources) = load_sources(&app.state::<Database>()) else { continue };
+            for source in sources {
+                if let Err(err) = run_scan(&app, &source).await {
+                    eprintln!("[Watch] Scan of {} failed: {}", source.path, err);
+                }
+            }
+        }
+    });
+}
+
+// Start watching a folder; its files are indexed right away in the background
+#[tauri::command]
+pub fn add_watched_source(
+    app: AppHandle,
+    db: State<'_, Database>,
+    path: String,
+    collection: String,
+    mut options: IngestOptions,
+    recursive: Option<bool>,
+) -> Result<WatchedSource, String> {
+    if !Path::new(&path).is_dir() {
+        return Err(format!("{} is not a folder", path));
+    }
+    ingest::chunk_settings(&options)?;
+    // Keys come from the profile at scan time
+    options.embedding.api_key = None;
+    let options_json = serde_json::to_string(&options).map_err(|e| e.to_string())?;
+
+    let id = db::new_id("watch");
+    let source = db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO watched_sources (id, path, collection, options, recursive, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                id,
+                path,
+                collection,
+                options_json,
+                recursive.unwrap_or(true) as i64,
+                chrono::Utc::now().to_rfc3339()
+            ],
+        )?;
+        get_watched_source(conn, &id)
+    });
+    let source = source.map_err(|err| {
+        if err.contains("UNIQUE") {
+            format!("{} is already watched for {}", path, collection)
+        } else {
+            err
+        }
+    })?;
+    eprintln!("[Watch] Watching {} for collection {}", path, collection);
+
+    tauri::async_runtime::spawn(async move {
+        let engine = app.state::<WatchEngine>();
+        let _guard = engine.scanning.lock().await;
+        match load_source(&app.state::<Database>(), &id) {
+            Ok(source) => {
+                if let Err(err) = run_scan(&app, &source).await {
+                    eprintln!("[Watch] Initial scan of {} failed: {}", source.path, err);
+                }
+            }
+            Err(err) => eprintln!("[Watch] {}", err),
+        }
+    });
+    Ok(source)
+}
+
+#[tauri::command]
+pub fn list_watched_sources(db: State<'_, Database>) -> Result<Vec<WatchedSource>, String> {
+    let ids: Vec<String> = db.with_conn(|conn| {
+        let mut stmt = conn.prepare("SELECT id FROM watched_sources ORDER BY created_at")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect()
+    })?;
+    db.with_conn(|conn| ids.iter().map(|id| get_watched_source(conn, id)).collect())
+}
+
+// Stop watching; with `removeIndex` the chunks of the folder's files are deleted as well
+#[tauri::command]
+pub fn remove_watched_source(db: State<'_, Database>, id: String, remove_index: Option<bool>) -> Result<(), String> {
+    let source = load_source(&db, &id)?;
+    if remove_index.unwrap_or(false) {
+        let paths: Vec<String> = db.with_conn(|conn| {
+            let mut stmt = conn.prepare("SELECT path FROM watched_files WHERE source_id = ?1")?;
+            let rows = stmt.query_map(params![id], |row| row.get(0))?;
+            rows.collect()
+        })?;
+        for path in &paths {
+            remove_file_chunks(&db, &source.collection, path)?;
+        }
+    }
+    db.with_conn(|conn| conn.execute("DELETE FROM watched_sources WHERE id = ?1", params![id]))?;
+    eprintln!("[Watch] Stopped watching {}", source.path);
+    Ok(())
+}
+
+// Scan a watched folder now instead of waiting for the next background pass
+#[tauri::command]
+pub async fn rescan_watched_source(app: AppHandle, id: String) -> Result<ScanReport, String> {
+    let engine = app.state::<WatchEngine>();
+    let _guard = engine.scanning.lock().await;
+    let source = load_source(&app.state::<Database>(), &id)?;
+    run_scan(&app, &source).await
+}
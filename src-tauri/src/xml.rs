@@ -0,0 +1,116 @@
+// Helpers for XML documents and the XML parts of ZIP packages (Office documents, EPUB)
+use std::collections::HashMap;
+use std::io::{Read, Seek};
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use zip::ZipArchive;
+
+pub enum Node<'a, 'b> {
+    Open(&'b BytesStart<'a>),
+    Close(&'b [u8]),
+    Text(&'b str),
+}
+
+// Feed the elements and text of an XML part to `visit`; empty elements open and close
+pub fn walk(xml: &str, mut visit: impl FnMut(Node)) -> Result<(), String> {
+    let mut reader = Reader::from_str(xml);
+    loop {
+        let event = reader.read_event().map_err(|e| format!("Invalid XML: {}", e))?;
+        match &event {
+            Event::Start(e) => visit(Node::Open(e)),
+            Event::Empty(e) => {
+                visit(Node::Open(e));
+                visit(Node::Close(e.local_name().as_ref()));
+            }
+            Event::End(e) => visit(Node::Close(e.local_name().as_ref())),
+            Event::Text(t) => visit(Node::Text(&t.xml10_content().map_err(|e| format!("Invalid XML text: {}", e))?)),
+            Event::CData(t) => visit(Node::Text(&t.decode().map_err(|e| format!("Invalid XML text: {}", e))?)),
+            Event::GeneralRef(r) => {
+                let resolved = if r.is_char_ref() {
+                    r.resolve_char_ref().ok().flatten().map(String::from)
+                } else {
+                    r.decode()
+                        .ok()
+                        .and_then(|name| quick_xml::escape::resolve_predefined_entity(&name))
+                        .map(str::to_string)
+                };
+                if let Some(text) = resolved {
+                    visit(Node::Text(&text));
+                }
+            }
+            Event::Eof => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+pub fn local_name<'a>(element: &'a BytesStart) -> &'a [u8] {
+    element.local_name().into_inner()
+}
+
+// Attribute by its qualified name as written (`w:val`, `r:id`)
+pub fn attr(element: &BytesStart, name: &str) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|a| a.key.as_ref() == name.as_bytes())
+        .and_then(|a| a.unescape_value().ok())
+        .map(|v| v.into_owned())
+}
+
+pub fn read_part<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str) -> Result<Option<String>, String> {
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(format!("Failed to open {}: {}", name, e)),
+    };
+    let mut text = String::new();
+    entry
+        .read_to_string(&mut text)
+        .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+    Ok(Some(text))
+}
+
+// Relationship targets of an Office part by id (from its _rels file), as archive paths
+pub fn relationships<R: Read + Seek>(archive: &mut ZipArchive<R>, part: &str) -> Result<HashMap<String, String>, String> {
+    let (dir, file) = part.rsplit_once('/').unwrap_or(("", part));
+    let rels_path = if dir.is_empty() {
+        format!("_rels/{}.rels", file)
+    } else {
+        format!("{}/_rels/{}.rels", dir, file)
+    };
+    let Some(xml) = read_part(archive, &rels_path)? else {
+        return Ok(HashMap::new());
+    };
+    let mut targets = HashMap::new();
+    walk(&xml, |node| {
+        if let Node::Open(e) = node {
+            let external = attr(e, "TargetMode").is_some_and(|m| m == "External");
+            if local_name(e) == b"Relationship" && !external {
+                if let (Some(id), Some(target)) = (attr(e, "Id"), attr(e, "Target")) {
+                    targets.insert(id, resolve_target(dir, &target));
+                }
+            }
+        }
+    })?;
+    Ok(targets)
+}
+
+// Targets are relative to the part's folder unless they start at the package root
+pub fn resolve_target(dir: &str, target: &str) -> String {
+    if let Some(absolute) = target.strip_prefix('/') {
+        return absolute.to_string();
+    }
+    let mut segments: Vec<&str> = dir.split('/').filter(|s| !s.is_empty()).collect();
+    for segment in target.split('/') {
+        match segment {
+            ".." => {
+                segments.pop();
+            }
+            "." | "" => {}
+            _ => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
@@ -10,6 +10,34 @@ const isTauri = () => {
   return typeof window !== 'undefined' && '__TAURI__' in window
 }
 
+/**
+ * Response of the Rust backend's `proxy_http_request`, whatever its status
+ */
+export interface ProxyResponse {
+  status: number
+  statusText: string
+  // Lowercase names; repeated headers are joined with ", "
+  headers: Record<string, string>
+  // Text bodies; empty for binary ones
+  body: string
+  // Binary bodies up to 4 MB, as plain base64
+  bodyBase64?: string
+  // Larger binary bodies, saved to a temporary file
+  filePath?: string
+  size: number
+  contentType: string | null
+  elapsedMs: number
+}
+
+// A field of the `form` argument of proxy_http_request: a text value, or a file the backend reads from disk
+export interface ProxyFormPart {
+  name: string
+  value?: string
+  path?: string
+  fileName?: string
+  contentType?: string
+}
+
 // Lazy-load Tauri modules
 let tauriHttp: any = null
 let tauriCore: any = null
@@ -84,19 +112,22 @@ export async function tauriFetch(
         const method = options.method || 'GET'
         const body = options.body ? String(options.body) : undefined
 
-        const responseText = await core.invoke('proxy_http_request', {
+        const response: ProxyResponse = await core.invoke('proxy_http_request', {
           url,
           method,
-          body
+          body,
+          headers: Object.fromEntries(new Headers(options.headers).entries())
         })
 
-        // Create a Response-like object
-        return new Response(responseText, {
-          status: 200,
-          statusText: 'OK',
-          headers: new Headers({
-            'Content-Type': 'application/json'
-          })
+        // Create a Response-like object; these statuses can't have a body
+        const nullBody = [204, 205, 304].includes(response.status)
+        const body = response.bodyBase64
+          ? Uint8Array.from(atob(response.bodyBase64), (c) => c.charCodeAt(0))
+          : response.body
+        return new Response(nullBody ? null : body, {
+          status: response.status,
+          statusText: response.statusText,
+          headers: new Headers(response.headers)
         })
       }
     } catch (e) {
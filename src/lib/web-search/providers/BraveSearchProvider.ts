@@ -14,6 +14,7 @@ import type {
 } from './types';
 import { ProviderError, ProviderErrorType } from './types';
 import { invoke } from '@tauri-apps/api/core';
+import type { ProxyResponse } from '../../tauriFetch';
 
 interface BraveSearchResponse {
   web?: {
@@ -78,8 +79,8 @@ export class BraveSearchProvider extends BaseProvider {
       // For now, we'll try with the API key in the URL
       const url = `${this.baseUrl}?${params}&api_key=${apiKey}`;
       
-      const responseText = await this.withTimeout(
-        invoke<string>('proxy_http_request', {
+      const response = await this.withTimeout(
+        invoke<ProxyResponse>('proxy_http_request', {
           url,
           method: 'GET',
           body: null
@@ -88,7 +89,7 @@ export class BraveSearchProvider extends BaseProvider {
         'Brave Search request'
       );
 
-      const data: BraveSearchResponse = JSON.parse(responseText);
+      const data: BraveSearchResponse = JSON.parse(response.body);
 
       if (data.error) {
         throw new ProviderError(
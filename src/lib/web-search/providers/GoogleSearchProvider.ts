@@ -13,6 +13,7 @@ import type {
 } from './types';
 import { ProviderError, ProviderErrorType } from './types';
 import { invoke } from '@tauri-apps/api/core';
+import type { ProxyResponse } from '../../tauriFetch';
 
 interface GoogleSearchResponse {
   items?: Array<{
@@ -81,8 +82,8 @@ export class GoogleSearchProvider extends BaseProvider {
       // Use Tauri's proxy_http_request command to bypass CORS
       const url = `${this.baseUrl}?${params}`;
       
-      const responseText = await this.withTimeout(
-        invoke<string>('proxy_http_request', {
+      const response = await this.withTimeout(
+        invoke<ProxyResponse>('proxy_http_request', {
           url,
           method: 'GET',
           body: null
@@ -91,7 +92,7 @@ export class GoogleSearchProvider extends BaseProvider {
         'Google Search request'
       );
 
-      const data: GoogleSearchResponse = JSON.parse(responseText);
+      const data: GoogleSearchResponse = JSON.parse(response.body);
 
       if (data.error) {
         throw new ProviderError(
@@ -14,6 +14,7 @@ import type {
 } from './types';
 import { ProviderError, ProviderErrorType } from './types';
 import { invoke } from '@tauri-apps/api/core';
+import type { ProxyResponse } from '../../tauriFetch';
 
 interface SerperAPIResponse {
   organic?: Array<{
@@ -67,8 +68,8 @@ export class SerperAPIProvider extends BaseProvider {
       // Since proxy doesn't support custom headers, we'll add the key to the URL
       const url = `${this.baseUrl}?api_key=${apiKey}`;
       
-      const responseText = await this.withTimeout(
-        invoke<string>('proxy_http_request', {
+      const response = await this.withTimeout(
+        invoke<ProxyResponse>('proxy_http_request', {
           url,
           method: 'POST',
           body: JSON.stringify(requestBody)
@@ -77,7 +78,7 @@ export class SerperAPIProvider extends BaseProvider {
         'Serper API request'
       );
 
-      const data: SerperAPIResponse = JSON.parse(responseText);
+      const data: SerperAPIResponse = JSON.parse(response.body);
 
       if (data.error) {
         // Check for authentication errors
@@ -212,13 +213,13 @@ export class SerperAPIProvider extends BaseProvider {
       // Serper API provides usage info in the account endpoint
       const url = `https://google.serper.dev/account?api_key=${apiKey}`;
       
-      const responseText = await invoke<string>('proxy_http_request', {
+      const response = await invoke<ProxyResponse>('proxy_http_request', {
         url,
         method: 'GET',
         body: null
       });
 
-      const data = JSON.parse(responseText);
+      const data = JSON.parse(response.body);
       
       console.log('[Serper API] Account data:', data);
       
@@ -15,6 +15,7 @@ import type {
 } from './types';
 import { ProviderError, ProviderErrorType } from './types';
 import { invoke } from '@tauri-apps/api/core';
+import type { ProxyResponse } from '../../tauriFetch';
 
 interface SerperAPIResponse {
   organic?: Array<{
@@ -153,8 +154,8 @@ export class SharedSerperProvider extends BaseProvider {
       // Use shared API key
       const url = `${this.baseUrl}?api_key=${this.sharedApiKey}`;
       
-      const responseText = await this.withTimeout(
-        invoke<string>('proxy_http_request', {
+      const response = await this.withTimeout(
+        invoke<ProxyResponse>('proxy_http_request', {
           url,
           method: 'POST',
           body: JSON.stringify(requestBody)
@@ -163,7 +164,7 @@ export class SharedSerperProvider extends BaseProvider {
         'Shared Serper API request'
       );
 
-      const data: SerperAPIResponse = JSON.parse(responseText);
+      const data: SerperAPIResponse = JSON.parse(response.body);
 
       if (data.error) {
         // Check for authentication errors
@@ -278,13 +279,13 @@ export class SharedSerperProvider extends BaseProvider {
       if (this.sharedApiKey) {
         try {
           const url = `https://google.serper.dev/account?api_key=${this.sharedApiKey}`;
-          const responseText = await invoke<string>('proxy_http_request', {
+          const response = await invoke<ProxyResponse>('proxy_http_request', {
             url,
             method: 'GET',
             body: null
           });
           
-          const data = JSON.parse(responseText);
+          const data = JSON.parse(response.body);
           
           // New API format: { balance: number, rateLimit: number }
           if (data.balance !== undefined) {